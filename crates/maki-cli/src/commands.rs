@@ -11,19 +11,25 @@
 
 // Command modules organized hierarchically
 pub mod build;
+pub mod codegen;
+pub mod compat;
 pub mod config;
+pub mod docs;
+pub mod generate_example;
 pub mod gofsh;
 pub mod init;
+pub mod locate;
 
 use maki_core::config::UnifiedConfig;
 use maki_core::{
-    AstFormatter, AutofixEngine, CachedFshParser, ConfigLoader, DefaultAutofixEngine,
-    DefaultExecutor, DefaultFileDiscovery, DefaultSemanticAnalyzer, ExecutionContext, Executor,
-    FileDiscovery, FixConfig, FormatterConfiguration, Result, Rule, RuleCategory, RuleEngine,
-    RuleMetadata,
+    AnalyzedFile, AstFormatter, AutofixEngine, Baseline, CachedFshParser, ConfigLoader,
+    DefaultAutofixEngine, DefaultExecutor, DefaultFileDiscovery, DefaultSemanticAnalyzer,
+    ExecutionContext, Executor, FileDiscovery, FixConfig, FormatterConfiguration, Result, Rule,
+    RuleCategory, RuleEngine, RuleMetadata,
 };
 use maki_rules::gritql::GritQLRuleLoader;
 use maki_rules::{BuiltinRules, DefaultRuleEngine};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
@@ -46,6 +52,8 @@ pub async fn lint_command(
     exclude: Vec<String>,
     error_on_warnings: bool,
     progress: bool,
+    write_baseline: Option<PathBuf>,
+    baseline: Option<PathBuf>,
     config_path: Option<PathBuf>,
 ) -> Result<()> {
     debug!("Running lint command on paths: {:?}", paths);
@@ -102,6 +110,8 @@ pub async fn lint_command(
         config.files.get_or_insert_with(Default::default).exclude = Some(exclude.clone());
     }
 
+    maki_rules::options::validate_rule_options(&config)?;
+
     debug!("Loaded configuration");
 
     let start_time = Instant::now();
@@ -118,7 +128,7 @@ pub async fn lint_command(
     };
 
     // Determine which files to lint
-    let fsh_files = if paths.is_empty() {
+    let mut fsh_files = if paths.is_empty() {
         // No paths specified - discover files based on config patterns
         let file_discovery = DefaultFileDiscovery::new(std::env::current_dir()?);
         file_discovery.discover_files(&config)?
@@ -175,6 +185,24 @@ pub async fn lint_command(
         files
     };
 
+    // Apply per-path overrides: skip files with the linter disabled and give
+    // the others their own rule configuration
+    let root = ConfigLoader::override_root(&config_root(config_source.as_deref()));
+    let mut file_rule_configs = HashMap::new();
+    fsh_files.retain(
+        |file| match ConfigLoader::resolve_for_file(&config, file, &root) {
+            Some(file_config) if file_config.linter_config().enabled == Some(false) => false,
+            Some(file_config) => {
+                file_rule_configs.insert(
+                    file.clone(),
+                    maki_rules::options::rule_configs(&file_config),
+                );
+                true
+            }
+            None => true,
+        },
+    );
+
     if fsh_files.is_empty() {
         println!("No FSH files found in specified paths.");
         return Ok(());
@@ -187,6 +215,19 @@ pub async fn lint_command(
 
     // Create rule engine
     let mut rule_engine = DefaultRuleEngine::new();
+    rule_engine.set_rule_configs(maki_rules::options::rule_configs(&config));
+    for (file, rule_configs) in file_rule_configs {
+        rule_engine.set_file_rule_configs(file, rule_configs);
+    }
+    if let Some(version) = config
+        .build
+        .as_ref()
+        .and_then(|build| build.fhir_version.first())
+    {
+        rule_engine.set_fhir_version(maki_rules::fhir_registry::FhirVersion::from_version(
+            version,
+        ));
+    }
 
     // Set lazy session for rules that need parent resolution
     if let Some(lazy) = lazy_session {
@@ -286,14 +327,21 @@ pub async fn lint_command(
 
     debug!("Total rules loaded: {}", compiled_rules.len());
 
-    // Step: Collect all ValueSet names from all FSH files for cross-file reference checking
-    // This pre-pass enables the binding-without-valueset rule to find ValueSets defined in other files
-    {
+    // Step: Analyze all FSH files up front for cross-file checks. This pre-pass collects
+    // the ValueSet and StructureDefinition names used by the binding-without-valueset and
+    // profile-assignment-present rules, and runs the project-wide rules (duplicates,
+    // circular parents, unused definitions) whose diagnostics are reported with each file.
+    // The per-file pass below reuses the semantic models built here
+    let analyzed_files: Vec<AnalyzedFile> = {
         use maki_core::Parser;
+        use maki_core::SemanticAnalyzer;
         use maki_core::cst::ast::{AstNode, Document};
         use std::collections::HashSet;
 
         let mut global_valuesets: HashSet<String> = HashSet::new();
+        let mut global_definitions: HashSet<String> = HashSet::new();
+        let mut models = Vec::with_capacity(fsh_files.len());
+        let mut parse_errors = Vec::with_capacity(fsh_files.len());
 
         debug!(
             "Pre-parsing {} files to collect global ValueSet registry",
@@ -301,15 +349,34 @@ pub async fn lint_command(
         );
 
         if let Ok(mut parser) = CachedFshParser::new() {
+            let analyzer = DefaultSemanticAnalyzer::new();
+            // A file reached through a symlink is one definition, not a duplicate
+            let mut seen = HashSet::new();
             for file_path in &fsh_files {
+                if !seen.insert(
+                    file_path
+                        .canonicalize()
+                        .unwrap_or_else(|_| file_path.clone()),
+                ) {
+                    continue;
+                }
                 if let Ok(source) = std::fs::read_to_string(file_path)
                     && let Ok(parse_result) = parser.parse(&source)
-                    && let Some(document) = Document::cast(parse_result.cst)
                 {
-                    for vs in document.value_sets() {
-                        if let Some(name) = vs.name() {
-                            global_valuesets.insert(name);
+                    if let Some(document) = Document::cast(parse_result.cst.clone()) {
+                        for vs in document.value_sets() {
+                            if let Some(name) = vs.name() {
+                                global_valuesets.insert(name);
+                            }
                         }
+                        global_definitions
+                            .extend(maki_rules::builtin::profile::definition_names(&document));
+                    }
+                    if let Ok(model) =
+                        analyzer.analyze(&parse_result.cst, &source, file_path.clone())
+                    {
+                        models.push(model);
+                        parse_errors.push(parse_result.errors);
                     }
                 }
             }
@@ -320,7 +387,28 @@ pub async fn lint_command(
             global_valuesets.len()
         );
         rule_engine.set_global_valuesets(global_valuesets);
-    }
+        rule_engine.set_global_definitions(global_definitions);
+
+        rule_engine.set_project_terminology(
+            maki_rules::builtin::terminology::ProjectTerminology::from_models(&models),
+        );
+
+        let project_diagnostics = rule_engine.execute_project_rules(&models, Some(&config));
+        debug!(
+            "Project rules reported {} diagnostics",
+            project_diagnostics.len()
+        );
+        rule_engine.set_project_diagnostics(project_diagnostics);
+
+        models
+            .into_iter()
+            .zip(parse_errors)
+            .map(|(model, parse_errors)| AnalyzedFile {
+                model,
+                parse_errors,
+            })
+            .collect()
+    };
 
     let semantic_analyzer = Box::new(DefaultSemanticAnalyzer::new());
     let context = ExecutionContext::new(config.clone(), compiled_rules);
@@ -331,7 +419,27 @@ pub async fn lint_command(
         progress_reporter.update(0, "Starting linting...");
     }
 
-    let results = executor.execute_parallel(fsh_files)?;
+    // Files the pre-pass could not analyze (or reached twice through a symlink)
+    // go through the full pipeline
+    let analyzed_paths: std::collections::HashSet<PathBuf> = analyzed_files
+        .iter()
+        .map(|file| file.model.source_file.clone())
+        .collect();
+    let remaining: Vec<PathBuf> = fsh_files
+        .iter()
+        .filter(|file| !analyzed_paths.contains(*file))
+        .cloned()
+        .collect();
+
+    let mut results = executor.execute_analyzed(analyzed_files);
+    results.extend(executor.execute_parallel(remaining)?);
+    // Report files in the order they were found
+    let order: HashMap<&PathBuf, usize> = fsh_files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| (file, index))
+        .collect();
+    results.sort_by_key(|result| order.get(&result.file_path).copied());
 
     if progress {
         progress_reporter.finish("Linting");
@@ -470,19 +578,50 @@ pub async fn lint_command(
 
     // Recalculate summary based on remaining diagnostics
     if write && !dry_run && !fixed_diagnostic_ids.is_empty() {
-        summary.errors = 0;
-        summary.warnings = 0;
-        summary.info = 0;
-        summary.hints = 0;
-        for d in &remaining_diagnostics {
-            match d.severity {
-                maki_core::Severity::Error => summary.errors += 1,
-                maki_core::Severity::Warning => summary.warnings += 1,
-                maki_core::Severity::Info => summary.info += 1,
-                maki_core::Severity::Hint => summary.hints += 1,
+        recount_summary(&mut summary, &remaining_diagnostics);
+    }
+
+    if let Some(path) = write_baseline {
+        let root = baseline_root(&path);
+        let baseline = Baseline::from_diagnostics(&remaining_diagnostics, &root);
+        baseline.save(&path)?;
+        println!(
+            "Wrote baseline with {} diagnostic(s) to {}",
+            baseline.entries.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    // Only report diagnostics that are not in the baseline
+    let remaining_diagnostics = if let Some(path) = baseline {
+        let comparison =
+            Baseline::load(&path)?.compare(remaining_diagnostics, &baseline_root(&path));
+        recount_summary(&mut summary, &comparison.new);
+        println!(
+            "{} diagnostic(s) matched the baseline {}",
+            comparison.baselined,
+            path.display()
+        );
+        if !comparison.fixed.is_empty() {
+            println!(
+                "{} baselined diagnostic(s) no longer occur (update with --write-baseline):",
+                comparison.fixed.len()
+            );
+            for entry in &comparison.fixed {
+                match &entry.entity {
+                    Some(entity) => {
+                        println!("  {} {} ({})", entry.file, entry.rule_id, entity)
+                    }
+                    None => println!("  {} {}", entry.file, entry.rule_id),
+                }
             }
         }
-    }
+        println!();
+        comparison.new
+    } else {
+        remaining_diagnostics
+    };
 
     // Format and print remaining results
     let formatter = OutputFormatter::new(format, use_colors);
@@ -505,6 +644,40 @@ pub async fn lint_command(
     Ok(())
 }
 
+/// Reset the severity counts of `summary` to those of `diagnostics`
+fn recount_summary(summary: &mut LintSummary, diagnostics: &[maki_core::Diagnostic]) {
+    summary.errors = 0;
+    summary.warnings = 0;
+    summary.info = 0;
+    summary.hints = 0;
+    for d in diagnostics {
+        match d.severity {
+            maki_core::Severity::Error => summary.errors += 1,
+            maki_core::Severity::Warning => summary.warnings += 1,
+            maki_core::Severity::Info => summary.info += 1,
+            maki_core::Severity::Hint => summary.hints += 1,
+        }
+    }
+}
+
+/// Directory per-path override patterns are relative to: the directory of
+/// the config file, or the current directory without one
+fn config_root(config_source: Option<&Path>) -> PathBuf {
+    config_source
+        .and_then(Path::parent)
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Directory baseline file paths are relative to
+fn baseline_root(baseline: &Path) -> PathBuf {
+    match baseline.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
 /// Format command implementation
 ///
 /// Dedicated formatter that only formats code without running lint rules.
@@ -529,8 +702,8 @@ pub async fn format_command(
     debug!("Running format command on paths: {:?}", paths);
 
     // Load configuration
-    let mut config = if let Some(path) = config_path {
-        ConfigLoader::load_from_file(&path)?
+    let (mut config, config_source) = if let Some(path) = config_path {
+        (ConfigLoader::load_from_file(&path)?, Some(path))
     } else {
         let start_path = if !paths.is_empty() && paths[0].is_file() {
             // Get parent directory of the file, or current directory if no parent
@@ -545,9 +718,10 @@ pub async fn format_command(
         };
 
         if let Some(discovered_path) = ConfigLoader::auto_discover(start_path)? {
-            ConfigLoader::load_from_file(&discovered_path)?
+            let cfg = ConfigLoader::load_from_file(&discovered_path)?;
+            (cfg, Some(discovered_path))
         } else {
-            UnifiedConfig::default()
+            (UnifiedConfig::default(), None)
         }
     };
 
@@ -628,7 +802,7 @@ pub async fn format_command(
     let start_time = Instant::now();
 
     // Determine which files to format
-    let fsh_files = if paths.is_empty() {
+    let mut fsh_files = if paths.is_empty() {
         let file_discovery = DefaultFileDiscovery::new(std::env::current_dir()?);
         file_discovery.discover_files(&config)?
     } else {
@@ -659,6 +833,36 @@ pub async fn format_command(
         files
     };
 
+    // Apply per-path overrides: skip files with the formatter disabled and give
+    // the others their own settings. Line width and indent size from the
+    // command line still win.
+    let root = ConfigLoader::override_root(&config_root(config_source.as_deref()));
+    let mut file_formatter_configs = HashMap::new();
+    fsh_files.retain(
+        |file| match ConfigLoader::resolve_for_file(&config, file, &root) {
+            Some(file_config) => {
+                let mut file_formatter = file_config.formatter_config();
+                if file_formatter.enabled == Some(false) {
+                    return false;
+                }
+                if line_width.is_some() {
+                    file_formatter.line_width = line_width;
+                }
+                if indent_size.is_some() {
+                    file_formatter.indent_size = indent_size;
+                }
+                file_formatter_configs.insert(file.clone(), file_formatter);
+                true
+            }
+            None => true,
+        },
+    );
+    let formatter_config_for = |file: &Path| {
+        file_formatter_configs
+            .get(file)
+            .unwrap_or(&formatter_config)
+    };
+
     if fsh_files.is_empty() {
         if !quiet {
             println!("No FSH files found in specified paths.");
@@ -705,7 +909,10 @@ pub async fn format_command(
                 let thread_parser = CachedFshParser::new().ok()?;
                 let mut thread_formatter = AstFormatter::new(thread_parser);
                 thread_formatter
-                    .format_file_with_diagnostic(file_path.as_path(), &formatter_config)
+                    .format_file_with_diagnostic(
+                        file_path.as_path(),
+                        formatter_config_for(file_path),
+                    )
                     .ok()
             };
 
@@ -781,9 +988,10 @@ pub async fn format_command(
             let mut thread_formatter = AstFormatter::new(thread_parser);
 
             // Get the formatted content for this file
-            if let Ok(Some(formatted_content)) =
-                thread_formatter.get_formatted_content(&diagnostic.location.file, &formatter_config)
-            {
+            if let Ok(Some(formatted_content)) = thread_formatter.get_formatted_content(
+                &diagnostic.location.file,
+                formatter_config_for(&diagnostic.location.file),
+            ) {
                 // Write the formatted content
                 if std::fs::write(&diagnostic.location.file, &formatted_content).is_ok() {
                     let mut applied = fixes_applied.lock().unwrap();
@@ -1112,7 +1320,11 @@ pub async fn config_validate_command(path: Option<PathBuf>) -> Result<()> {
         ConfigLoader::load_from_file(&p)
     } else {
         ConfigLoader::load(None, None)
-    } {
+    }
+    .and_then(|config| {
+        maki_rules::options::validate_rule_options(&config)?;
+        Ok(config)
+    }) {
         Ok(config) => {
             println!("✅ Configuration is valid");
             let linter_enabled = config
@@ -3,14 +3,21 @@
 //! SUSHI-compatible build command for compiling FSH to FHIR resources.
 
 use colored::Colorize;
+use maki_core::canonical::FhirRelease;
 use maki_core::config::{ConfigLoader, SushiConfiguration, UnifiedConfig};
-use maki_core::export::{BuildOptions, BuildOrchestrator, BuildStats};
+use maki_core::export::{
+    BuildOptions, BuildOrchestrator, BuildReport, BuildStats, configured_releases,
+    release_output_dir,
+};
 use maki_core::{MakiError, Result};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tracing::{error, info, warn};
 
+/// Entities listed in the `--profile-build` table
+const PROFILE_TABLE_ROWS: usize = 25;
+
 /// Build FSH files to FHIR resources (SUSHI-compatible)
 ///
 /// This command orchestrates the complete FSH → FHIR build pipeline:
@@ -36,6 +43,13 @@ pub async fn build_command(
     format: bool,
     no_cache: bool,
     skip_deps: bool,
+    bundle: bool,
+    ndjson: bool,
+    bundle_conformance: bool,
+    report: Option<PathBuf>,
+    source_map: bool,
+    narrative: bool,
+    profile_build: Option<PathBuf>,
     config_overrides: HashMap<String, String>,
 ) -> Result<()> {
     // TODO: Implement skip_deps functionality
@@ -100,6 +114,12 @@ pub async fn build_command(
         strict_mode: strict,
         format_on_build: format,
         use_cache: !no_cache, // Invert no_cache flag
+        example_bundle: bundle,
+        example_ndjson: ndjson,
+        bundle_conformance,
+        source_maps: source_map,
+        generate_narratives: narrative,
+        profile_build: profile_build.is_some(),
     };
 
     // Print build info
@@ -140,28 +160,89 @@ pub async fn build_command(
         }
     }
 
-    // Step 2: Create orchestrator and run build
-    let orchestrator = BuildOrchestrator::new(config.clone(), options);
-    let result = orchestrator
-        .build()
-        .await
-        .map_err(|e| MakiError::ConfigError {
-            message: format!("Build failed: {}", e),
-        })?;
+    // Step 2: Create orchestrator and run build, once per FHIR release when
+    // several are configured
+    let releases = configured_releases(&build_config.fhir_version);
+    let mut errors = 0;
+    for release in &releases {
+        let mut options = options.clone();
+        let mut report = report.clone();
+        let mut profile_trace = profile_build.clone();
+        if releases.len() > 1 {
+            options.output_dir = release_output_dir(&output_dir, *release);
+            options.fhir_version = Some(release.to_version_string().to_string());
+            report = report.map(|path| release_file_path(&path, *release));
+            profile_trace = profile_trace.map(|path| release_file_path(&path, *release));
+            println!(
+                "{} {} → {}",
+                "Building FHIR".bold(),
+                release.label().bold(),
+                options.output_dir.display()
+            );
+        }
 
-    let elapsed = start_time.elapsed();
+        let orchestrator = BuildOrchestrator::new(config.clone(), options);
+        let result = match orchestrator.build_with_timings().await {
+            Ok(result) => result,
+            Err((error, timings)) => {
+                if let Some(report_path) = &report {
+                    BuildReport::from_error(&error, timings)
+                        .write(report_path)
+                        .map_err(|e| MakiError::io_error(report_path, e))?;
+                    info!("📄 Build report written to {}", report_path.display());
+                }
+                return Err(MakiError::ConfigError {
+                    message: format!("Build failed: {}", error),
+                });
+            }
+        };
+
+        let elapsed = start_time.elapsed();
 
-    // Print results
-    print_build_results(&result.stats, elapsed);
+        // Print results
+        print_build_results(&result.stats, elapsed);
+
+        // Write machine-readable report (before exiting on errors, so failed builds are reported too)
+        if let Some(report_path) = report {
+            BuildReport::from_result(&result)
+                .write(&report_path)
+                .map_err(|e| MakiError::io_error(&report_path, e))?;
+            info!("📄 Build report written to {}", report_path.display());
+        }
+
+        if let (Some(profile), Some(trace_path)) = (&result.profile, profile_trace) {
+            println!("{}", "Build profile".bold());
+            print!("{}", profile.table(PROFILE_TABLE_ROWS));
+            profile
+                .write_chrome_trace(&trace_path)
+                .map_err(|e| MakiError::io_error(&trace_path, e))?;
+            println!("Chrome trace written to {}", trace_path.display());
+        }
+
+        errors += result.stats.errors;
+    }
 
     // Exit with error code if there were errors
-    if result.stats.has_errors() {
-        std::process::exit(result.stats.errors.min(255) as i32);
+    if errors > 0 {
+        std::process::exit(errors.min(255) as i32);
     }
 
     Ok(())
 }
 
+/// Per-release report or trace path: `report.json` → `report-r5.json`
+fn release_file_path(path: &Path, release: FhirRelease) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "report".to_string());
+    let release = release.label().to_ascii_lowercase();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{}-{}.{}", stem, release, ext.to_string_lossy())),
+        None => path.with_file_name(format!("{}-{}", stem, release)),
+    }
+}
+
 /// Load configuration using ConfigLoader
 fn load_configuration(
     project_path: &Path,
@@ -243,6 +324,9 @@ fn print_build_header(config: &UnifiedConfig, options: &BuildOptions) {
     if options.clean_output {
         println!("  {} Enabled", "Clean Output:".bold());
     }
+    if options.example_bundle || options.example_ndjson {
+        println!("  {} Enabled", "Example Bundle:".bold());
+    }
 
     println!();
     println!("{}", "Starting build...".bright_blue());
@@ -519,6 +603,8 @@ async fn run_linter_before_build(input_dir: &Path, strict: bool) -> Result<LintR
         exclude,
         error_on_warnings,
         progress,
+        None,
+        None,
         config,
     )
     .await
@@ -0,0 +1,76 @@
+//! Codegen command implementation
+//!
+//! Emits TypeScript, Rust or JSON Schema type definitions for the
+//! StructureDefinitions in the build output.
+
+use super::compat::{fhir_release, load_dir, open_session};
+use crate::CodegenLang;
+use colored::Colorize;
+use maki_core::Result;
+use maki_core::canonical::create_default_maki_config;
+use maki_core::export::compat::fill_snapshots;
+use maki_core::export::{CodeGenerator, CodegenLanguage};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Generate type definitions for the project at `project_path`
+pub async fn codegen_command(
+    project_path: Option<PathBuf>,
+    lang: CodegenLang,
+    resources_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+) -> Result<()> {
+    let project_path = project_path.unwrap_or_else(|| PathBuf::from("."));
+    let resources_dir =
+        resources_dir.unwrap_or_else(|| project_path.join("fsh-generated").join("resources"));
+    let mut resources = load_dir(&resources_dir)?;
+    info!(
+        "Loaded {} resources from {}",
+        resources.len(),
+        resources_dir.display()
+    );
+
+    // Inherited elements come from snapshots; without FHIR definitions the
+    // types only cover what the differentials constrain
+    let missing = resources
+        .iter()
+        .filter(|r| {
+            r.get("resourceType").and_then(|t| t.as_str()) == Some("StructureDefinition")
+                && r.get("snapshot").is_none()
+        })
+        .count();
+    if missing > 0 {
+        let release = fhir_release(&resources);
+        match open_session(create_default_maki_config(false), release).await {
+            Ok(session) => {
+                let generated = fill_snapshots(session, &mut resources).await;
+                info!("Generated {} of {} missing snapshots", generated, missing);
+            }
+            Err(e) => warn!(
+                "{}; {} definitions without snapshot only include their differential",
+                e, missing
+            ),
+        }
+    }
+
+    let (language, dir) = match lang {
+        CodegenLang::Typescript => (CodegenLanguage::TypeScript, "typescript"),
+        CodegenLang::Rust => (CodegenLanguage::Rust, "rust"),
+        CodegenLang::JsonSchema => (CodegenLanguage::JsonSchema, "json-schema"),
+    };
+    let output_dir =
+        output_dir.unwrap_or_else(|| project_path.join("fsh-generated").join("codegen").join(dir));
+
+    let generator = CodeGenerator::new(&resources);
+    let written = generator
+        .write(&output_dir, language)
+        .map_err(|e| maki_core::MakiError::io_error(&output_dir, e))?;
+
+    println!(
+        "{} Wrote {} file(s) to {}",
+        "✓".green(),
+        written,
+        output_dir.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Compat command implementation
+//!
+//! Compares two versions of an Implementation Guide and reports which
+//! StructureDefinitions, ValueSets and CodeSystems changed in a breaking way.
+
+use crate::CompatFormat;
+use colored::Colorize;
+use maki_core::canonical::{
+    CanonicalFacade, CanonicalManagerConfig, CanonicalOptions, DefinitionSession, FhirRelease,
+    PackageCoordinate, create_default_maki_config,
+};
+use maki_core::export::compat::{self, CompatReport, Compatibility};
+use maki_core::{MakiError, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Compare `old` (a directory or `name@version` package) with the `new` directory
+pub async fn compat_command(
+    old: String,
+    new: PathBuf,
+    format: CompatFormat,
+    fail_on_breaking: bool,
+) -> Result<()> {
+    let mut new_resources = load_dir(&new)?;
+    let release = fhir_release(&new_resources);
+
+    // Definitions are only needed for packages and missing snapshots; a
+    // directory comparison still works (on differentials) without them
+    let fcm_config = create_default_maki_config(false);
+    let packages_dir = fcm_config.storage.packages_dir.clone();
+    let session = match open_session(fcm_config, release).await {
+        Ok(session) => Some(session),
+        Err(e) => {
+            warn!("{}", e);
+            None
+        }
+    };
+
+    let mut old_resources = if Path::new(&old).exists() {
+        load_dir(Path::new(&old))?
+    } else {
+        let package = parse_package(&old)?;
+        let session = session.as_ref().ok_or_else(|| MakiError::ConfigError {
+            message: format!("Cannot load package {} without FHIR definitions", old),
+        })?;
+        load_package(session, &packages_dir, package).await?
+    };
+
+    if let Some(session) = session {
+        let generated = compat::fill_snapshots(session.clone(), &mut old_resources).await
+            + compat::fill_snapshots(session, &mut new_resources).await;
+        info!("Generated {} snapshots for comparison", generated);
+    }
+
+    let report = compat::compare(&old_resources, &new_resources);
+    match format {
+        CompatFormat::Human => print_human(&report, &old, &new),
+        CompatFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(|e| MakiError::ConfigError {
+                message: format!("Failed to serialize report: {}", e),
+            })?
+        ),
+        CompatFormat::Markdown => print!("{}", report.to_markdown()),
+    }
+
+    if fail_on_breaking && report.is_breaking() {
+        return Err(MakiError::ConfigError {
+            message: format!("{} breaking change(s) found", report.breaking),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) async fn open_session(
+    fcm_config: CanonicalManagerConfig,
+    release: FhirRelease,
+) -> Result<Arc<DefinitionSession>> {
+    create_session(fcm_config, release, true).await
+}
+
+/// Open a session over the already installed packages, without installing
+/// the FHIR core package
+pub(crate) async fn open_local_session(
+    fcm_config: CanonicalManagerConfig,
+    release: FhirRelease,
+) -> Result<Arc<DefinitionSession>> {
+    create_session(fcm_config, release, false).await
+}
+
+async fn create_session(
+    fcm_config: CanonicalManagerConfig,
+    release: FhirRelease,
+    auto_install_core: bool,
+) -> Result<Arc<DefinitionSession>> {
+    let facade = CanonicalFacade::new(CanonicalOptions {
+        config: Some(fcm_config),
+        auto_install_core,
+        quick_init: true,
+        ..Default::default()
+    })
+    .await
+    .map_err(canonical_error)?;
+    let session = facade.session([release]).await.map_err(canonical_error)?;
+    Ok(Arc::new(session))
+}
+
+pub(crate) fn load_dir(dir: &Path) -> Result<Vec<Value>> {
+    if !dir.is_dir() {
+        return Err(MakiError::ConfigError {
+            message: format!("{} is not a directory", dir.display()),
+        });
+    }
+    compat::load_resources(dir).map_err(|e| MakiError::io_error(dir, e))
+}
+
+/// Install a published package and load its resources from the package cache
+async fn load_package(
+    session: &DefinitionSession,
+    packages_dir: &Path,
+    package: PackageCoordinate,
+) -> Result<Vec<Value>> {
+    info!("Loading package {}@{}", package.name, package.version);
+    let dir = packages_dir.join(format!("{}-{}", package.name, package.version));
+    session
+        .ensure_packages([package])
+        .await
+        .map_err(canonical_error)?;
+    load_dir(&dir)
+}
+
+/// Parse `name@version` or `name#version`
+fn parse_package(spec: &str) -> Result<PackageCoordinate> {
+    match spec.split_once(['@', '#']) {
+        Some((name, version)) if !name.is_empty() && !version.is_empty() => {
+            Ok(PackageCoordinate::new(name, version))
+        }
+        _ => Err(MakiError::ConfigError {
+            message: format!(
+                "'{}' is neither a directory nor a package (expected name@version)",
+                spec
+            ),
+        }),
+    }
+}
+
+/// FHIR release declared by the new version's StructureDefinitions (R4 if none)
+pub(crate) fn fhir_release(resources: &[Value]) -> FhirRelease {
+    let version = resources
+        .iter()
+        .find_map(|r| r.get("fhirVersion").and_then(Value::as_str))
+        .unwrap_or("4.0.1");
+    if version.starts_with("4.3") || version.starts_with("4.1") {
+        FhirRelease::R4B
+    } else if version.starts_with("5.") {
+        FhirRelease::R5
+    } else if version.starts_with("6.") {
+        FhirRelease::R6
+    } else {
+        FhirRelease::R4
+    }
+}
+
+fn canonical_error(e: impl std::fmt::Display) -> MakiError {
+    MakiError::ConfigError {
+        message: format!("Failed to load FHIR definitions: {}", e),
+    }
+}
+
+fn print_human(report: &CompatReport, old: &str, new: &Path) {
+    println!("{} {} → {}\n", "Compatibility".bold(), old, new.display());
+    if report.changes.is_empty() {
+        println!("{}", "No changes found".green());
+        return;
+    }
+
+    let mut current = None;
+    for change in &report.changes {
+        let resource = (&change.resource_type, &change.name);
+        if current != Some(resource) {
+            println!("{} {}", change.resource_type.cyan(), change.name.bold());
+            current = Some(resource);
+        }
+        let label = match change.compatibility {
+            Compatibility::Breaking => "breaking".red().bold(),
+            Compatibility::NonBreaking => "non-breaking".green(),
+        };
+        let element = change
+            .element
+            .as_deref()
+            .map(|e| format!("{} ", e.dimmed()))
+            .unwrap_or_default();
+        println!(
+            "  {:<14} {}{}: {}",
+            label, element, change.kind, change.message
+        );
+    }
+
+    let summary = format!(
+        "{} breaking, {} non-breaking",
+        report.breaking, report.non_breaking
+    );
+    println!();
+    if report.is_breaking() {
+        println!("{}", summary.red().bold());
+    } else {
+        println!("{}", summary.green());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_package() {
+        let package = parse_package("hl7.fhir.us.core@6.1.0").unwrap();
+        assert_eq!(package.name, "hl7.fhir.us.core");
+        assert_eq!(package.version, "6.1.0");
+        assert_eq!(parse_package("my.ig#1.0.0").unwrap().version, "1.0.0");
+        assert!(parse_package("./missing-dir").is_err());
+    }
+
+    #[test]
+    fn test_fhir_release() {
+        assert_eq!(fhir_release(&[]), FhirRelease::R4);
+        assert_eq!(
+            fhir_release(&[
+                json!({ "resourceType": "ValueSet" }),
+                json!({ "fhirVersion": "5.0.0" })
+            ]),
+            FhirRelease::R5
+        );
+    }
+}
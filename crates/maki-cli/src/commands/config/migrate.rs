@@ -174,6 +174,8 @@ fn build_unified_config(sushi_path: &Path) -> Result<UnifiedConfig> {
         linter: Some(maki_core::config::LinterConfiguration::default()),
         formatter: Some(maki_core::config::FormatterConfiguration::default()),
         files: Some(maki_core::config::FilesConfiguration::default()),
+        releases: None,
+        overrides: None,
     };
 
     info!("Built unified config successfully");
@@ -0,0 +1,70 @@
+//! Docs command implementation
+//!
+//! Renders a static preview site (HTML or Markdown) from the build output,
+//! laid out by the `groups` and `pages` of sushi-config.yaml.
+
+use crate::DocsOutputFormat;
+use colored::Colorize;
+use maki_core::export::{DocsFormat, DocsSiteGenerator};
+use maki_core::{ConfigLoader, MakiError, Result};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Generate the documentation site for the project at `project_path`
+pub async fn docs_command(
+    project_path: Option<PathBuf>,
+    resources_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+    format: DocsOutputFormat,
+) -> Result<()> {
+    let project_path = project_path.unwrap_or_else(|| PathBuf::from("."));
+    let config = ConfigLoader::load(None, Some(&project_path))?
+        .build
+        .ok_or_else(|| MakiError::ConfigError {
+            message: "Build configuration is required".to_string(),
+        })?;
+
+    let resources_dir =
+        resources_dir.unwrap_or_else(|| project_path.join("fsh-generated").join("resources"));
+    if !resources_dir.is_dir() {
+        return Err(MakiError::ConfigError {
+            message: format!(
+                "{} not found; run `maki build` first",
+                resources_dir.display()
+            ),
+        });
+    }
+    let resources = DocsSiteGenerator::load_resources(&resources_dir)
+        .map_err(|e| MakiError::io_error(&resources_dir, e))?;
+    info!(
+        "Loaded {} resources from {}",
+        resources.len(),
+        resources_dir.display()
+    );
+
+    let mut generator = DocsSiteGenerator::new(config, resources);
+    let page_content = project_path.join("input").join("pagecontent");
+    if page_content.is_dir() {
+        let loaded = generator
+            .load_page_content(&page_content)
+            .map_err(|e| MakiError::io_error(&page_content, e))?;
+        info!("Loaded {} pages from {}", loaded, page_content.display());
+    }
+
+    let output_dir = output_dir.unwrap_or_else(|| project_path.join("fsh-generated").join("site"));
+    let format = match format {
+        DocsOutputFormat::Html => DocsFormat::Html,
+        DocsOutputFormat::Markdown => DocsFormat::Markdown,
+    };
+    let written = generator
+        .write(&output_dir, format)
+        .map_err(|e| MakiError::io_error(&output_dir, e))?;
+
+    println!(
+        "{} Wrote {} pages to {}",
+        "✓".green(),
+        written,
+        output_dir.display()
+    );
+    Ok(())
+}
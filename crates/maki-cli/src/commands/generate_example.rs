@@ -0,0 +1,81 @@
+//! Generate-example command implementation
+//!
+//! Writes a minimal FSH `Instance:` for a profile in the build output.
+
+use super::compat::{fhir_release, load_dir, open_local_session, open_session};
+use colored::Colorize;
+use maki_core::canonical::create_default_maki_config;
+use maki_core::export::ExampleGenerator;
+use maki_core::export::compat::fill_snapshots;
+use maki_core::semantic::PathResolver;
+use maki_core::{MakiError, Result};
+use serde_json::Value;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Generate an example instance of `profile` (name, id or url)
+pub async fn generate_example_command(
+    profile: String,
+    project_path: Option<PathBuf>,
+    resources_dir: Option<PathBuf>,
+    must_support: bool,
+    name: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let project_path = project_path.unwrap_or_else(|| PathBuf::from("."));
+    let resources_dir =
+        resources_dir.unwrap_or_else(|| project_path.join("fsh-generated").join("resources"));
+    let mut resources = load_dir(&resources_dir)?;
+    info!(
+        "Loaded {} resources from {}",
+        resources.len(),
+        resources_dir.display()
+    );
+
+    let index = resources
+        .iter()
+        .position(|r| is_profile(r, &profile))
+        .ok_or_else(|| MakiError::ConfigError {
+            message: format!(
+                "No StructureDefinition '{}' in {}",
+                profile,
+                resources_dir.display()
+            ),
+        })?;
+
+    // Without FHIR definitions the snapshot can't be filled and datatypes
+    // are populated from built-in defaults
+    let release = fhir_release(&resources);
+    let session = match open_session(create_default_maki_config(false), release).await {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("{}; generating from the local definitions only", e);
+            open_local_session(create_default_maki_config(false), release).await?
+        }
+    };
+    if resources[index].get("snapshot").is_none() {
+        fill_snapshots(session.clone(), std::slice::from_mut(&mut resources[index])).await;
+    }
+
+    let resolver = PathResolver::new(session);
+    let fsh = ExampleGenerator::new(&resolver, &resources)
+        .with_must_support(must_support)
+        .generate(&resources[index], name.as_deref())
+        .await;
+
+    match output {
+        Some(output) => {
+            std::fs::write(&output, &fsh).map_err(|e| MakiError::io_error(&output, e))?;
+            println!("{} Wrote {}", "✓".green(), output.display());
+        }
+        None => print!("{}", fsh),
+    }
+    Ok(())
+}
+
+fn is_profile(resource: &Value, profile: &str) -> bool {
+    resource.get("resourceType").and_then(Value::as_str) == Some("StructureDefinition")
+        && ["name", "id", "url"]
+            .iter()
+            .any(|key| resource.get(*key).and_then(Value::as_str) == Some(profile))
+}
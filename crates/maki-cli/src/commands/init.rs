@@ -238,6 +238,7 @@ fn generate_maki_config(meta: &ProjectMetadata) -> Result<()> {
             fsh_only: None,
             apply_extension_metadata_to_root: None,
             instance_options: None,
+            output_format: None,
             meta: None,
             implicit_rules: None,
             language: None,
@@ -271,6 +272,8 @@ fn generate_maki_config(meta: &ProjectMetadata) -> Result<()> {
             ]),
             ..Default::default()
         }),
+        releases: None,
+        overrides: None,
     };
 
     let yaml = serde_yaml::to_string(&config).map_err(|e| {
@@ -0,0 +1,116 @@
+//! Locate command implementation
+//!
+//! Maps a location in a generated JSON resource back to the FSH rule that
+//! produced it, using the `*.fshmap.json` source maps written by
+//! `maki build --source-map`.
+
+use colored::Colorize;
+use maki_core::export::source_map::element_id_pointer;
+use maki_core::export::{DATA_DIR, FSHMAP_DIR, FshSourceMap};
+use maki_core::{MakiError, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Print the FSH rule(s) that produced `location` in `json_file`
+///
+/// `location` is either a JSON pointer (`/differential/element/3`) or an
+/// element id as reported by validators (`Observation.code.coding:loinc`).
+pub async fn locate_command(json_file: PathBuf, location: String) -> Result<()> {
+    let content =
+        std::fs::read_to_string(&json_file).map_err(|e| MakiError::io_error(&json_file, e))?;
+    let resource: Value = serde_json::from_str(&content).map_err(|e| MakiError::ConfigError {
+        message: format!("{} is not valid JSON: {}", json_file.display(), e),
+    })?;
+
+    let map_path = find_source_map(&json_file).ok_or_else(|| MakiError::ConfigError {
+        message: format!(
+            "No source map found for {} (run `maki build --source-map` first)",
+            json_file.display()
+        ),
+    })?;
+    let source_map =
+        FshSourceMap::load(&map_path).map_err(|e| MakiError::io_error(&map_path, e))?;
+
+    let pointer = if location.is_empty() || location.starts_with('/') {
+        location.clone()
+    } else {
+        element_id_pointer(&resource, &location).ok_or_else(|| MakiError::ConfigError {
+            message: format!(
+                "Element '{}' not found in the differential of {}",
+                location,
+                json_file.display()
+            ),
+        })?
+    };
+
+    let Some((matched, rules)) = source_map.locate(&pointer) else {
+        return Err(MakiError::ConfigError {
+            message: format!("No FSH rule recorded for '{}'", location),
+        });
+    };
+
+    println!(
+        "{} {} ({})",
+        source_map.fsh_type.cyan(),
+        source_map.fsh_name.bold(),
+        if matched.is_empty() { "/" } else { matched }
+    );
+    if matched != pointer {
+        println!(
+            "  {}",
+            format!(
+                "No rule sets '{}' directly; showing nearest mapped parent",
+                pointer
+            )
+            .dimmed()
+        );
+    }
+    for rule in rules {
+        println!(
+            "  {}:{}  {}",
+            rule.file,
+            rule.start_line,
+            rule.rule.lines().next().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+/// Find the source map sidecar for a generated resource
+///
+/// Looks next to the resource first, then in `fsh-generated/data/fshmap/`.
+fn find_source_map(json_file: &Path) -> Option<PathBuf> {
+    let filename = json_file.file_name()?.to_str()?;
+    let sidecar = FshSourceMap::sidecar_filename(filename);
+    let dir = json_file.parent().unwrap_or(Path::new(""));
+
+    let candidates = [
+        dir.join(&sidecar),
+        dir.join("..")
+            .join(DATA_DIR)
+            .join(FSHMAP_DIR)
+            .join(&sidecar),
+    ];
+    candidates.into_iter().find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_source_map_in_data_dir() {
+        let temp = TempDir::new().unwrap();
+        let resources = temp.path().join("resources");
+        let fshmap = temp.path().join(DATA_DIR).join(FSHMAP_DIR);
+        std::fs::create_dir_all(&resources).unwrap();
+        std::fs::create_dir_all(&fshmap).unwrap();
+        std::fs::write(fshmap.join("Patient-a.fshmap.json"), "{}").unwrap();
+
+        let found = find_source_map(&resources.join("Patient-a.json")).unwrap();
+        assert!(found.ends_with("Patient-a.fshmap.json"));
+        assert!(find_source_map(&resources.join("Patient-b.json")).is_none());
+    }
+}
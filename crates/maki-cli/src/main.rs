@@ -114,6 +114,50 @@ enum Commands {
         #[arg(long, help = "Skip installing FHIR package dependencies")]
         skip_deps: bool,
 
+        /// Write example instances as a transaction Bundle
+        #[arg(long, help = "Write example instances as a transaction Bundle")]
+        bundle: bool,
+
+        /// Write example instances as NDJSON per resource type
+        #[arg(long, help = "Write example instances as NDJSON for bulk import")]
+        ndjson: bool,
+
+        /// Include conformance resources in the example Bundle / NDJSON
+        #[arg(
+            long,
+            help = "Include conformance resources in --bundle/--ndjson output"
+        )]
+        bundle_conformance: bool,
+
+        /// Write a machine-readable build report (JSON)
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write a JSON build report (timings, outputs, diagnostics)"
+        )]
+        report: Option<PathBuf>,
+
+        /// Write FSH source maps (data/fshmap/*.fshmap.json)
+        #[arg(long, help = "Write FSH source maps for `maki locate`")]
+        source_map: bool,
+
+        /// Generate narratives for instances, ValueSets and CodeSystems
+        #[arg(
+            long,
+            help = "Generate text narratives (templates in input/narrative-templates/)"
+        )]
+        narrative: bool,
+
+        /// Profile the build: print per-entity timings and write a Chrome trace
+        #[arg(
+            long,
+            value_name = "TRACE",
+            num_args = 0..=1,
+            default_missing_value = "build-profile.json",
+            help = "Print per-entity timings and lookup stats; write a Chrome trace (default: build-profile.json)"
+        )]
+        profile_build: Option<PathBuf>,
+
         /// Override configuration values (e.g., --config version:2.0.0)
         #[arg(
             short = 'c',
@@ -174,6 +218,126 @@ enum Commands {
         progress: bool,
     },
 
+    /// Find the FSH rule that produced part of a generated JSON resource
+    Locate {
+        /// Generated JSON resource (e.g., fsh-generated/resources/StructureDefinition-foo.json)
+        #[arg(help = "Generated JSON resource file")]
+        json_file: PathBuf,
+
+        /// JSON pointer or element id to look up
+        #[arg(
+            help = "JSON pointer (e.g., /differential/element/3) or element id (e.g., Observation.code.coding:loinc)"
+        )]
+        pointer: String,
+    },
+
+    /// Report breaking changes between two versions of an IG
+    Compat {
+        /// Old version: a directory of resources or a package (name@version)
+        #[arg(help = "Old version (directory or package, e.g. hl7.fhir.us.core@6.1.0)")]
+        old: String,
+
+        /// New version: a directory of resources (e.g., fsh-generated/resources)
+        #[arg(help = "New version directory")]
+        new: PathBuf,
+
+        /// Output format
+        #[arg(
+            short,
+            long,
+            default_value = "human",
+            help = "Output format for the report"
+        )]
+        format: CompatFormat,
+
+        /// Exit with an error when breaking changes are found
+        #[arg(long, help = "Exit with non-zero status if any change is breaking")]
+        fail_on_breaking: bool,
+    },
+
+    /// Generate TypeScript, Rust or JSON Schema types from the exported profiles
+    Codegen {
+        /// Path to the FSH project directory (defaults to current directory)
+        #[arg(help = "Path to FSH project directory (defaults to current directory)")]
+        path: Option<PathBuf>,
+
+        /// Target language
+        #[arg(short, long, help = "Language to generate types for")]
+        lang: CodegenLang,
+
+        /// Directory of generated resources
+        #[arg(
+            long,
+            help = "Generated resources directory (default: fsh-generated/resources)"
+        )]
+        resources: Option<PathBuf>,
+
+        /// Output directory
+        #[arg(
+            short,
+            long,
+            help = "Output directory (default: fsh-generated/codegen/<lang>)"
+        )]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate a static documentation site from the build output
+    Docs {
+        /// Path to the FSH project directory (defaults to current directory)
+        #[arg(help = "Path to FSH project directory (defaults to current directory)")]
+        path: Option<PathBuf>,
+
+        /// Directory of generated resources
+        #[arg(
+            long,
+            help = "Generated resources directory (default: fsh-generated/resources)"
+        )]
+        resources: Option<PathBuf>,
+
+        /// Output directory for the site
+        #[arg(short, long, help = "Output directory (default: fsh-generated/site)")]
+        output: Option<PathBuf>,
+
+        /// Output format
+        #[arg(
+            short,
+            long,
+            default_value = "html",
+            help = "Output format for the site pages"
+        )]
+        format: DocsOutputFormat,
+    },
+
+    /// Generate a minimal example instance of a profile as FSH
+    GenerateExample {
+        /// Profile name, id or canonical URL
+        #[arg(help = "Profile name, id or canonical URL")]
+        profile: String,
+
+        /// Path to the FSH project directory (defaults to current directory)
+        #[arg(help = "Path to FSH project directory (defaults to current directory)")]
+        path: Option<PathBuf>,
+
+        /// Directory of generated resources
+        #[arg(
+            long,
+            help = "Generated resources directory (default: fsh-generated/resources)"
+        )]
+        resources: Option<PathBuf>,
+
+        /// Also populate must-support elements
+        #[arg(long, help = "Also populate must-support elements")]
+        must_support: bool,
+
+        /// Instance name
+        #[arg(long, help = "Instance name (default: <Profile>Example)")]
+        name: Option<String>,
+
+        /// Output file
+        #[arg(short, long, help = "Write the FSH to a file instead of stdout")]
+        output: Option<PathBuf>,
+    },
+
     /// Lint FSH files for syntax errors, semantic issues, and best practice violations
     #[command(alias = "check")]
     Lint {
@@ -239,6 +403,23 @@ enum Commands {
         /// Show progress for long-running operations
         #[arg(long, help = "Show progress bar for large projects")]
         progress: bool,
+
+        /// Record current diagnostics as a baseline
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write current diagnostics to a baseline file",
+            conflicts_with = "baseline"
+        )]
+        write_baseline: Option<PathBuf>,
+
+        /// Only report diagnostics missing from a baseline
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Only report diagnostics that are not in the baseline file"
+        )]
+        baseline: Option<PathBuf>,
     },
 
     /// Format FSH files according to style guidelines
@@ -426,6 +607,34 @@ enum OutputFormat {
     Github,
 }
 
+#[derive(ValueEnum, Clone, Debug)]
+enum CompatFormat {
+    /// Human-readable output with colors
+    Human,
+    /// JSON format for programmatic consumption
+    Json,
+    /// Markdown tables (e.g., for release notes)
+    Markdown,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum CodegenLang {
+    /// TypeScript interfaces and slice accessor functions
+    Typescript,
+    /// Rust structs with serde derives
+    Rust,
+    /// One JSON Schema (2020-12) per profile or logical model
+    JsonSchema,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum DocsOutputFormat {
+    /// Static HTML pages with a stylesheet
+    Html,
+    /// Markdown pages (e.g., for a wiki or static site generator)
+    Markdown,
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 enum Severity {
     /// Only show errors
@@ -541,6 +750,13 @@ async fn run_command(cli: Cli) -> Result<()> {
             format,
             no_cache,
             skip_deps,
+            bundle,
+            ndjson,
+            bundle_conformance,
+            report,
+            source_map,
+            narrative,
+            profile_build,
             config,
         }) => {
             let config_overrides: std::collections::HashMap<String, String> =
@@ -557,6 +773,13 @@ async fn run_command(cli: Cli) -> Result<()> {
                 format,
                 no_cache,
                 skip_deps,
+                bundle,
+                ndjson,
+                bundle_conformance,
+                report,
+                source_map,
+                narrative,
+                profile_build,
                 config_overrides,
             )
             .await
@@ -587,6 +810,50 @@ async fn run_command(cli: Cli) -> Result<()> {
             .await
         }
 
+        Some(Commands::Locate { json_file, pointer }) => {
+            commands::locate::locate_command(json_file, pointer).await
+        }
+
+        Some(Commands::Compat {
+            old,
+            new,
+            format,
+            fail_on_breaking,
+        }) => commands::compat::compat_command(old, new, format, fail_on_breaking).await,
+
+        Some(Commands::Codegen {
+            path,
+            lang,
+            resources,
+            output,
+        }) => commands::codegen::codegen_command(path, lang, resources, output).await,
+
+        Some(Commands::Docs {
+            path,
+            resources,
+            output,
+            format,
+        }) => commands::docs::docs_command(path, resources, output, format).await,
+
+        Some(Commands::GenerateExample {
+            profile,
+            path,
+            resources,
+            must_support,
+            name,
+            output,
+        }) => {
+            commands::generate_example::generate_example_command(
+                profile,
+                path,
+                resources,
+                must_support,
+                name,
+                output,
+            )
+            .await
+        }
+
         Some(Commands::Lint {
             paths,
             format,
@@ -599,6 +866,8 @@ async fn run_command(cli: Cli) -> Result<()> {
             exclude,
             error_on_warnings,
             progress,
+            write_baseline,
+            baseline,
         }) => {
             let paths = if paths.is_empty() {
                 vec![PathBuf::from(".")]
@@ -617,6 +886,8 @@ async fn run_command(cli: Cli) -> Result<()> {
                 exclude,
                 error_on_warnings,
                 progress,
+                write_baseline,
+                baseline,
                 cli.config,
             )
             .await
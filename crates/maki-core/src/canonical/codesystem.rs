@@ -218,24 +218,26 @@ impl CodeSystem {
         let name = json["name"].as_str().unwrap_or("Unknown").to_string();
 
         let mut code_system = Self::new(url, name);
+        code_system.add_concepts_from_json(&json["concept"]);
 
-        // Parse concepts from concept array
-        if let Some(concepts_array) = json["concept"].as_array() {
-            for concept_json in concepts_array {
-                if let Some(code) = concept_json["code"].as_str() {
-                    let display = concept_json["display"].as_str().map(String::from);
-                    let definition = concept_json["definition"].as_str().map(String::from);
-
-                    code_system.add_concept(Concept {
-                        code: code.to_string(),
-                        display,
-                        definition,
-                    });
-                }
+        Ok(code_system)
+    }
+
+    /// Add concepts from a FHIR `concept` array, including nested child concepts
+    fn add_concepts_from_json(&mut self, concepts: &serde_json::Value) {
+        for concept_json in concepts.as_array().into_iter().flatten() {
+            if let Some(code) = concept_json["code"].as_str() {
+                let display = concept_json["display"].as_str().map(String::from);
+                let definition = concept_json["definition"].as_str().map(String::from);
+
+                self.add_concept(Concept {
+                    code: code.to_string(),
+                    display,
+                    definition,
+                });
             }
+            self.add_concepts_from_json(&concept_json["concept"]);
         }
-
-        Ok(code_system)
     }
 }
 
@@ -682,6 +684,27 @@ mod tests {
         assert_eq!(concept.definition.as_deref(), Some("The entity is active"));
     }
 
+    #[test]
+    fn test_code_system_from_fhir_json_nested_concepts() {
+        let json = serde_json::json!({
+            "resourceType": "CodeSystem",
+            "url": "http://example.org/codes",
+            "concept": [
+                {
+                    "code": "parent",
+                    "concept": [{ "code": "child", "display": "Child" }]
+                }
+            ]
+        });
+
+        let cs = CodeSystem::from_fhir_json(&json).unwrap();
+        assert_eq!(cs.concepts.len(), 2);
+        assert_eq!(
+            cs.get_concept("child").unwrap().display.as_deref(),
+            Some("Child")
+        );
+    }
+
     #[test]
     fn test_code_system_from_fhir_json_missing_url() {
         let json = serde_json::json!({
@@ -0,0 +1,495 @@
+//! Cross-version extension support
+//!
+//! FHIR defines an implied extension for every element of every release, so an
+//! IG on one release can carry data from another:
+//!
+//! ```text
+//! http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient.contact.relationship
+//!                     ^^^                                ^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!                     source release                     element id in that release
+//! ```
+//!
+//! These StructureDefinitions are not shipped in the core packages; like SUSHI,
+//! MAKI synthesizes them from the source release's core package. Simple elements
+//! become `value[x]` extensions with the element's types and binding; complex
+//! elements become complex extensions with one sub-extension per child element
+//! (recursively, following `contentReference`s).
+//!
+//! # Example
+//!
+//! ```rust
+//! use maki_core::canonical::cross_version::{CrossVersionExtensionUrl, CrossVersionRelease};
+//!
+//! let url = "http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient.gender";
+//! let parsed = CrossVersionExtensionUrl::parse(url).unwrap();
+//! assert_eq!(parsed.release, CrossVersionRelease::R5);
+//! assert_eq!(parsed.element_id, "Patient.gender");
+//! assert_eq!(parsed.resource_type(), "Patient");
+//! ```
+
+use crate::canonical::PackageCoordinate;
+use serde_json::{Value, json};
+
+const FHIR_BASE: &str = "http://hl7.org/fhir/";
+const EXTENSION_URL_MARKER: &str = "/StructureDefinition/extension-";
+
+/// Elements of the source definition that are never turned into sub-extensions
+const SKIPPED_CHILDREN: &[&str] = &["id", "extension", "modifierExtension"];
+
+/// FHIR release a cross-version extension is taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrossVersionRelease {
+    Stu3,
+    R4,
+    R4B,
+    R5,
+}
+
+impl CrossVersionRelease {
+    /// Parse the `{major.minor}` URL segment (`3.0`, `4.0`, `4.3`, `5.0`)
+    pub fn from_url_segment(segment: &str) -> Option<Self> {
+        match segment {
+            "3.0" => Some(Self::Stu3),
+            "4.0" => Some(Self::R4),
+            "4.3" => Some(Self::R4B),
+            "5.0" => Some(Self::R5),
+            _ => None,
+        }
+    }
+
+    /// Human readable label
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Stu3 => "STU3",
+            Self::R4 => "R4",
+            Self::R4B => "R4B",
+            Self::R5 => "R5",
+        }
+    }
+
+    /// Full FHIR version of the release's core package
+    pub fn fhir_version(self) -> &'static str {
+        match self {
+            Self::Stu3 => "3.0.2",
+            Self::R4 => "4.0.1",
+            Self::R4B => "4.3.0",
+            Self::R5 => "5.0.0",
+        }
+    }
+
+    /// Core package holding the release's definitions
+    pub fn core_package(self) -> PackageCoordinate {
+        let name = match self {
+            Self::Stu3 => "hl7.fhir.r3.core",
+            Self::R4 => "hl7.fhir.r4.core",
+            Self::R4B => "hl7.fhir.r4b.core",
+            Self::R5 => "hl7.fhir.r5.core",
+        };
+        PackageCoordinate::new(name, self.fhir_version())
+    }
+}
+
+/// A parsed cross-version extension URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossVersionExtensionUrl {
+    /// The full extension URL
+    pub url: String,
+    /// Release the element comes from
+    pub release: CrossVersionRelease,
+    /// Element id in the source release (`Patient.contact.relationship`)
+    pub element_id: String,
+}
+
+impl CrossVersionExtensionUrl {
+    /// Parse `http://hl7.org/fhir/{3.0|4.0|4.3|5.0}/StructureDefinition/extension-<Path>`
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix(FHIR_BASE)?;
+        let (segment, element_id) = rest.split_once(EXTENSION_URL_MARKER)?;
+        let release = CrossVersionRelease::from_url_segment(segment)?;
+
+        let resource_type = element_id.split('.').next()?;
+        if !resource_type.starts_with(|c: char| c.is_ascii_uppercase())
+            || element_id.split('.').any(str::is_empty)
+        {
+            return None;
+        }
+
+        Some(Self {
+            url: url.to_string(),
+            release,
+            element_id: element_id.to_string(),
+        })
+    }
+
+    /// Resource or datatype the element belongs to
+    pub fn resource_type(&self) -> &str {
+        self.element_id
+            .split_once('.')
+            .map_or(self.element_id.as_str(), |(root, _)| root)
+    }
+
+    /// Canonical URL of the source release's StructureDefinition for the type
+    pub fn structure_definition_url(&self) -> String {
+        format!("{}StructureDefinition/{}", FHIR_BASE, self.resource_type())
+    }
+}
+
+/// Whether `url` follows the cross-version extension URL pattern
+pub fn is_cross_version_extension_url(url: &str) -> bool {
+    CrossVersionExtensionUrl::parse(url).is_some()
+}
+
+/// Synthesize the Extension StructureDefinition for a cross-version extension
+///
+/// `source_snapshot` is the snapshot `element` array of the source release's
+/// StructureDefinition for [`CrossVersionExtensionUrl::resource_type`];
+/// `target_fhir_version` is the FHIR version of the IG using the extension.
+/// Returns `None` when the element does not exist in the source release.
+pub fn synthesize_extension(
+    extension: &CrossVersionExtensionUrl,
+    source_snapshot: &[Value],
+    target_fhir_version: &str,
+) -> Option<Value> {
+    let source = SourceElements {
+        elements: source_snapshot,
+    };
+    let element = source.find(&extension.element_id)?;
+
+    let mut elements = Vec::new();
+    let mut root = json!({
+        "id": "Extension",
+        "path": "Extension",
+        "short": element.get("short").cloned().unwrap_or_else(|| json!(extension.element_id)),
+        "definition": element
+            .get("definition")
+            .cloned()
+            .unwrap_or_else(|| json!(format!("Cross-version extension for {}", extension.element_id))),
+        "min": 0,
+        "max": element.get("max").cloned().unwrap_or_else(|| json!("*")),
+    });
+    if let Some(comment) = element.get("comment") {
+        root["comment"] = comment.clone();
+    }
+    elements.push(root);
+    extension_body(
+        &source,
+        element,
+        "Extension",
+        "Extension",
+        &extension.url,
+        &mut elements,
+    );
+
+    let label = extension.release.label();
+    let name = format!(
+        "Extension_{}_{}",
+        label,
+        extension
+            .element_id
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+            .trim_end_matches('_')
+    );
+
+    Some(json!({
+        "resourceType": "StructureDefinition",
+        "id": format!("extension-{}", extension.element_id),
+        "url": extension.url,
+        "version": extension.release.fhir_version(),
+        "name": name,
+        "title": format!("{}: {} (new)", label, extension.element_id),
+        "status": "active",
+        "description": format!(
+            "{}: {} (new:{})",
+            label,
+            element.get("short").and_then(Value::as_str).unwrap_or(&extension.element_id),
+            element_types(element).join(",")
+        ),
+        "fhirVersion": target_fhir_version,
+        "kind": "complex-type",
+        "abstract": false,
+        "context": [{ "type": "element", "expression": "Element" }],
+        "type": "Extension",
+        "baseDefinition": "http://hl7.org/fhir/StructureDefinition/Extension",
+        "derivation": "constraint",
+        "snapshot": { "element": elements.clone() },
+        "differential": { "element": elements },
+    }))
+}
+
+/// Element lookup in the source release's snapshot
+struct SourceElements<'a> {
+    elements: &'a [Value],
+}
+
+impl<'a> SourceElements<'a> {
+    fn find(&self, id: &str) -> Option<&'a Value> {
+        self.elements.iter().find(|e| element_key(e) == Some(id))
+    }
+
+    /// Direct children of `element`, following its `contentReference`
+    fn children(&self, element: &'a Value) -> Vec<(&'a str, &'a Value)> {
+        let target = element
+            .get("contentReference")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.rsplit_once('#').map(|(_, id)| id))
+            .and_then(|id| self.find(id))
+            .unwrap_or(element);
+        let Some(prefix) = element_key(target).map(|id| format!("{}.", id)) else {
+            return Vec::new();
+        };
+
+        self.elements
+            .iter()
+            .filter_map(|e| {
+                let name = element_key(e)?.strip_prefix(&prefix)?;
+                (!name.contains(['.', ':']) && !SKIPPED_CHILDREN.contains(&name))
+                    .then_some((name, e))
+            })
+            .collect()
+    }
+}
+
+/// Append the `extension`, `url` and `value[x]` elements of an extension (or
+/// sub-extension slice) rooted at `id`/`path`
+fn extension_body(
+    source: &SourceElements<'_>,
+    element: &Value,
+    id: &str,
+    path: &str,
+    url: &str,
+    out: &mut Vec<Value>,
+) {
+    let children = source.children(element);
+    let complex = !children.is_empty();
+
+    let mut extension = json!({
+        "id": format!("{}.extension", id),
+        "path": format!("{}.extension", path),
+        "min": 0,
+        "max": if complex { "*" } else { "0" },
+        "type": [{ "code": "Extension" }],
+    });
+    if complex {
+        extension["slicing"] = json!({
+            "discriminator": [{ "type": "value", "path": "url" }],
+            "rules": "open",
+        });
+    }
+    out.push(extension);
+
+    for (name, child) in &children {
+        let slice_id = format!("{}.extension:{}", id, name);
+        let slice_path = format!("{}.extension", path);
+        let mut slice = json!({
+            "id": slice_id,
+            "path": slice_path,
+            "sliceName": name,
+            "min": child.get("min").cloned().unwrap_or(json!(0)),
+            "max": child.get("max").cloned().unwrap_or(json!("*")),
+            "type": [{ "code": "Extension" }],
+        });
+        for field in ["short", "definition", "comment"] {
+            if let Some(value) = child.get(field) {
+                slice[field] = value.clone();
+            }
+        }
+        out.push(slice);
+        extension_body(source, child, &slice_id, &slice_path, name, out);
+    }
+
+    out.push(json!({
+        "id": format!("{}.url", id),
+        "path": format!("{}.url", path),
+        "min": 1,
+        "max": "1",
+        "type": [{ "code": "uri" }],
+        "fixedUri": url,
+    }));
+
+    let mut value = json!({
+        "id": format!("{}.value[x]", id),
+        "path": format!("{}.value[x]", path),
+        "min": if complex { 0 } else { 1 },
+        "max": if complex { "0" } else { "1" },
+    });
+    if !complex {
+        let types: Vec<Value> = element
+            .get("type")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|t| {
+                !matches!(
+                    t.get("code").and_then(Value::as_str),
+                    Some("Element" | "BackboneElement")
+                )
+            })
+            .cloned()
+            .collect();
+        if !types.is_empty() {
+            value["type"] = Value::Array(types);
+        }
+        if let Some(binding) = element.get("binding") {
+            value["binding"] = binding.clone();
+        }
+    }
+    out.push(value);
+}
+
+fn element_key(element: &Value) -> Option<&str> {
+    element
+        .get("id")
+        .or_else(|| element.get("path"))
+        .and_then(Value::as_str)
+}
+
+fn element_types(element: &Value) -> Vec<&str> {
+    element
+        .get("type")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.get("code").and_then(Value::as_str))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patient_r5() -> Vec<Value> {
+        serde_json::from_value(json!([
+            { "id": "Patient", "path": "Patient", "min": 0, "max": "*" },
+            { "id": "Patient.gender", "path": "Patient.gender", "short": "male | female | other | unknown",
+              "min": 0, "max": "1", "type": [{ "code": "code" }],
+              "binding": { "strength": "required", "valueSet": "http://hl7.org/fhir/ValueSet/administrative-gender|5.0.0" } },
+            { "id": "Patient.contact", "path": "Patient.contact", "min": 0, "max": "*",
+              "type": [{ "code": "BackboneElement" }] },
+            { "id": "Patient.contact.id", "path": "Patient.contact.id", "min": 0, "max": "1" },
+            { "id": "Patient.contact.relationship", "path": "Patient.contact.relationship",
+              "min": 0, "max": "*", "type": [{ "code": "CodeableConcept" }] },
+            { "id": "Patient.contact.name", "path": "Patient.contact.name",
+              "min": 0, "max": "1", "type": [{ "code": "HumanName" }] },
+            { "id": "Patient.link", "path": "Patient.link", "min": 0, "max": "*",
+              "type": [{ "code": "BackboneElement" }] },
+            { "id": "Patient.link.other", "path": "Patient.link.other", "min": 1, "max": "1",
+              "type": [{ "code": "Reference", "targetProfile": ["http://hl7.org/fhir/StructureDefinition/Patient"] }] },
+            { "id": "Patient.nested", "path": "Patient.nested", "min": 0, "max": "*",
+              "contentReference": "#Patient.link" }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_cross_version_urls() {
+        let parsed = CrossVersionExtensionUrl::parse(
+            "http://hl7.org/fhir/3.0/StructureDefinition/extension-MedicationRequest.substitution.allowed",
+        )
+        .unwrap();
+        assert_eq!(parsed.release, CrossVersionRelease::Stu3);
+        assert_eq!(parsed.resource_type(), "MedicationRequest");
+        assert_eq!(
+            parsed.structure_definition_url(),
+            "http://hl7.org/fhir/StructureDefinition/MedicationRequest"
+        );
+        assert_eq!(parsed.release.core_package().name, "hl7.fhir.r3.core");
+
+        for url in [
+            "http://hl7.org/fhir/4.0/StructureDefinition/extension-Patient.gender",
+            "http://hl7.org/fhir/4.3/StructureDefinition/extension-Patient.gender",
+        ] {
+            assert!(is_cross_version_extension_url(url));
+        }
+        for url in [
+            "http://hl7.org/fhir/StructureDefinition/patient-birthPlace",
+            "http://hl7.org/fhir/6.0/StructureDefinition/extension-Patient.gender",
+            "http://hl7.org/fhir/5.0/StructureDefinition/extension-patient.gender",
+            "http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient..gender",
+        ] {
+            assert!(!is_cross_version_extension_url(url), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_synthesize_simple_extension() {
+        let url = "http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient.gender";
+        let parsed = CrossVersionExtensionUrl::parse(url).unwrap();
+        let sd = synthesize_extension(&parsed, &patient_r5(), "4.0.1").unwrap();
+
+        assert_eq!(sd["url"], url);
+        assert_eq!(sd["id"], "extension-Patient.gender");
+        assert_eq!(sd["name"], "Extension_R5_Patient_gender");
+        assert_eq!(sd["fhirVersion"], "4.0.1");
+        assert_eq!(sd["type"], "Extension");
+
+        let elements = sd["snapshot"]["element"].as_array().unwrap();
+        let ids: Vec<&str> = elements.iter().map(|e| e["id"].as_str().unwrap()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "Extension",
+                "Extension.extension",
+                "Extension.url",
+                "Extension.value[x]"
+            ]
+        );
+        assert_eq!(elements[0]["max"], "1");
+        assert_eq!(elements[1]["max"], "0");
+        assert_eq!(elements[2]["fixedUri"], url);
+        assert_eq!(elements[3]["type"][0]["code"], "code");
+        assert_eq!(elements[3]["binding"]["strength"], "required");
+    }
+
+    #[test]
+    fn test_synthesize_complex_extension() {
+        let url = "http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient.contact";
+        let parsed = CrossVersionExtensionUrl::parse(url).unwrap();
+        let sd = synthesize_extension(&parsed, &patient_r5(), "4.0.1").unwrap();
+
+        let elements = sd["snapshot"]["element"].as_array().unwrap();
+        let ids: Vec<&str> = elements.iter().map(|e| e["id"].as_str().unwrap()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "Extension",
+                "Extension.extension",
+                "Extension.extension:relationship",
+                "Extension.extension:relationship.extension",
+                "Extension.extension:relationship.url",
+                "Extension.extension:relationship.value[x]",
+                "Extension.extension:name",
+                "Extension.extension:name.extension",
+                "Extension.extension:name.url",
+                "Extension.extension:name.value[x]",
+                "Extension.url",
+                "Extension.value[x]",
+            ]
+        );
+        assert_eq!(elements[1]["slicing"]["rules"], "open");
+        assert_eq!(elements[2]["sliceName"], "relationship");
+        assert_eq!(elements[4]["fixedUri"], "relationship");
+        assert_eq!(elements[5]["type"][0]["code"], "CodeableConcept");
+        assert_eq!(elements[11]["max"], "0");
+    }
+
+    #[test]
+    fn test_synthesize_follows_content_reference() {
+        let url = "http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient.nested";
+        let parsed = CrossVersionExtensionUrl::parse(url).unwrap();
+        let sd = synthesize_extension(&parsed, &patient_r5(), "4.0.1").unwrap();
+
+        let elements = sd["snapshot"]["element"].as_array().unwrap();
+        assert_eq!(elements[2]["id"], "Extension.extension:other");
+        assert_eq!(elements[2]["min"], 1);
+        assert_eq!(
+            elements[5]["type"][0]["targetProfile"][0],
+            "http://hl7.org/fhir/StructureDefinition/Patient"
+        );
+
+        let missing = CrossVersionExtensionUrl::parse(
+            "http://hl7.org/fhir/5.0/StructureDefinition/extension-Patient.unknown",
+        )
+        .unwrap();
+        assert!(synthesize_extension(&missing, &patient_r5(), "4.0.1").is_none());
+    }
+}
@@ -291,11 +291,14 @@ impl ExtensionContext {
             }
             ContextType::Element => {
                 // Match element path (can be resource or element)
-                if self.expression.contains('.') {
-                    // Full path like "Patient.name"
-                    target.starts_with(&self.expression) || self.expression.starts_with(target)
+                if let Some(choice) = self.expression.strip_suffix("[x]") {
+                    // Choice element like "Observation.value[x]" matches its typed variants
+                    self.expression == target
+                        || target
+                            .strip_prefix(choice)
+                            .is_some_and(|variant| !variant.is_empty() && !variant.contains('.'))
                 } else {
-                    // Just resource name
+                    // Full path like "Patient.name", or just a type name
                     self.expression == target
                 }
             }
@@ -683,8 +686,18 @@ mod tests {
     #[test]
     fn test_extension_context_matches_element() {
         let ctx = ExtensionContext::new(ContextType::Element, "Patient.name".to_string());
-        assert!(ctx.matches("Patient"));
+        assert!(!ctx.matches("Patient"));
         assert!(ctx.matches("Patient.name"));
+        assert!(!ctx.matches("Patient.name.given"));
+        assert!(!ctx.matches("Observation"));
+    }
+
+    #[test]
+    fn test_extension_context_matches_choice_element() {
+        let ctx = ExtensionContext::new(ContextType::Element, "Observation.value[x]".to_string());
+        assert!(ctx.matches("Observation.value[x]"));
+        assert!(ctx.matches("Observation.valueQuantity"));
+        assert!(!ctx.matches("Observation.valueQuantity.unit"));
         assert!(!ctx.matches("Observation"));
     }
 
@@ -38,6 +38,7 @@
 //! ```
 
 use crate::canonical::{DefinitionResource, DefinitionSession};
+use octofhir_canonical_manager::domain::ResourceIndex;
 use std::sync::Arc;
 use tracing::{debug, trace};
 
@@ -201,6 +202,65 @@ impl FhirMetadata {
     }
 }
 
+impl FhirMetadata {
+    /// Create metadata from a package index entry, without loading the resource
+    fn from_index(index: &ResourceIndex) -> Self {
+        Self {
+            resource_type: index.resource_type.clone(),
+            id: index.id.clone(),
+            url: Some(index.canonical_url.clone()),
+            name: index.name.clone(),
+            version: index.version.clone(),
+            kind: index.sd_kind.clone(),
+            derivation: index.sd_derivation.clone(),
+            base_definition: index.sd_base_definition.clone(),
+            package_id: format!("{}@{}", index.package_name, index.package_version),
+        }
+    }
+}
+
+impl DefinitionSession {
+    /// Metadata of every installed `resource_type` resource matching `types`
+    ///
+    /// Reads the package index only, so it is cheap enough to compute
+    /// "did you mean" suggestions after a failed [`Fishable::fish`].
+    pub async fn fishable_metadata(
+        &self,
+        resource_type: &str,
+        types: &[FhirType],
+    ) -> crate::canonical::CanonicalResult<Vec<FhirMetadata>> {
+        let mut package_names: Vec<String> = self
+            .facade
+            .manager
+            .list_packages()
+            .await?
+            .into_iter()
+            .map(|package| match package.rsplit_once('@') {
+                Some((name, _)) => name.to_string(),
+                None => package,
+            })
+            .collect();
+        package_names.sort();
+        package_names.dedup();
+
+        let mut metadata = Vec::new();
+        for package_name in package_names {
+            let entries = self
+                .facade
+                .manager
+                .find_by_type_and_package(resource_type, &package_name)
+                .await?;
+            metadata.extend(
+                entries
+                    .iter()
+                    .map(FhirMetadata::from_index)
+                    .filter(|entry| entry.matches_types(types)),
+            );
+        }
+        Ok(metadata)
+    }
+}
+
 /// Fishable trait - unified interface for FHIR resource lookups
 ///
 /// This trait provides a consistent interface for searching FHIR resources
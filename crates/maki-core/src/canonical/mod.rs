@@ -6,6 +6,7 @@
 //! async-friendly API with caching, version awareness, and ergonomic errors.
 
 pub mod codesystem;
+pub mod cross_version;
 pub mod extension;
 pub mod fishable;
 pub mod valueset;
@@ -14,6 +15,7 @@ pub mod version;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use dashmap::{DashMap, DashSet};
 use octofhir_canonical_manager::{CanonicalManager, FcmError, config::FcmConfig};
@@ -283,6 +285,7 @@ impl CanonicalFacade {
             installed: DashSet::new(),
             cardinality_cache: DashMap::new(),
             base_resource_types_cache: DashSet::new(),
+            lookup_counters: Default::default(),
         };
 
         if self.options.auto_install_core {
@@ -323,6 +326,70 @@ impl Clone for CanonicalFacade {
     }
 }
 
+/// Kind of [`DefinitionSession`] lookup counted in [`LookupStats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LookupKind {
+    /// [`DefinitionSession::resolve`] by canonical URL
+    Canonical,
+    /// [`DefinitionSession::resource_by_type_and_name`]
+    TypeAndName,
+    /// [`DefinitionSession::resource_by_type_and_id`]
+    TypeAndId,
+    /// [`DefinitionSession::is_array_element`]
+    Cardinality,
+}
+
+impl LookupKind {
+    pub const ALL: [LookupKind; 4] = [
+        Self::Canonical,
+        Self::TypeAndName,
+        Self::TypeAndId,
+        Self::Cardinality,
+    ];
+
+    /// Human-readable lookup name
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Canonical => "canonical URL",
+            Self::TypeAndName => "type + name",
+            Self::TypeAndId => "type + id",
+            Self::Cardinality => "cardinality",
+        }
+    }
+}
+
+/// Lookup counts of one [`LookupKind`] over the lifetime of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LookupStats {
+    pub kind: LookupKind,
+    pub lookups: u64,
+    /// Lookups answered from the session or global cache
+    pub cache_hits: u64,
+    /// Lookups that found nothing or failed
+    pub misses: u64,
+}
+
+impl LookupStats {
+    /// Fraction of lookups answered from cache (0.0 when there were none)
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / self.lookups as f64
+        }
+    }
+}
+
+/// Atomic counters behind [`LookupStats`]
+#[derive(Debug, Default)]
+struct LookupCounter {
+    lookups: AtomicU64,
+    cache_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
 /// Session-scoped view of canonical resources for a specific set of releases.
 pub struct DefinitionSession {
     facade: Arc<CanonicalFacade>,
@@ -335,6 +402,8 @@ pub struct DefinitionSession {
     /// Cache for base FHIR resource type names (e.g., "Patient", "Observation")
     /// Populated on first access from canonical manager
     base_resource_types_cache: DashSet<String>,
+    /// Lookup counters, indexed by `LookupKind as usize`
+    lookup_counters: [LookupCounter; 4],
 }
 
 impl DefinitionSession {
@@ -461,12 +530,130 @@ impl DefinitionSession {
     /// This method attempts FHIR version-aware resolution using the primary FHIR version
     /// configured for this session. If version-specific resolution fails, it falls back
     /// to version-agnostic resolution for backward compatibility.
+    ///
+    /// Cross-version extension URLs (`http://hl7.org/fhir/5.0/StructureDefinition/extension-*`)
+    /// that no installed package defines are synthesized from the source release's core
+    /// package (see [`cross_version`]).
     pub async fn resolve(&self, canonical_url: &str) -> CanonicalResult<Arc<DefinitionResource>> {
+        self.count_lookup(LookupKind::Canonical);
+        let result = self.resolve_uncounted(canonical_url).await;
+        if result.is_err() {
+            self.count_miss(LookupKind::Canonical);
+        }
+        result
+    }
+
+    async fn resolve_uncounted(
+        &self,
+        canonical_url: &str,
+    ) -> CanonicalResult<Arc<DefinitionResource>> {
+        match self.resolve_from_packages(canonical_url).await {
+            Err(err) if cross_version::is_cross_version_extension_url(canonical_url) => {
+                match self.resolve_cross_version_extension(canonical_url).await {
+                    Ok(Some(resource)) => Ok(resource),
+                    Ok(None) => Err(err),
+                    Err(synthesis_err) => {
+                        warn!(
+                            "Failed to synthesize cross-version extension {}: {}",
+                            canonical_url, synthesis_err
+                        );
+                        Err(err)
+                    }
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Synthesize the StructureDefinition for a cross-version extension URL.
+    ///
+    /// Installs the source release's core package if needed, looks up the element in
+    /// that release and builds the implied Extension definition. Returns `None` if the
+    /// URL is not a cross-version extension URL or the element does not exist.
+    pub async fn resolve_cross_version_extension(
+        &self,
+        extension_url: &str,
+    ) -> CanonicalResult<Option<Arc<DefinitionResource>>> {
+        let Some(extension) = cross_version::CrossVersionExtensionUrl::parse(extension_url) else {
+            return Ok(None);
+        };
+        if let Some(existing) = self.local_cache.get(extension_url) {
+            return Ok(Some(existing.clone()));
+        }
+
+        let package = extension.release.core_package();
+        self.ensure_packages([package.clone()]).await?;
+
+        let source_url = extension.structure_definition_url();
+        let source = self
+            .facade
+            .manager
+            .resolve_with_fhir_version(&source_url, extension.release.fhir_version())
+            .await
+            .map_err(|source| CanonicalLoaderError::Resolution {
+                url: source_url.clone(),
+                source,
+            })?;
+        if source.package_info.name != package.name {
+            warn!(
+                "{} resolved from {} instead of {}; cannot synthesize {}",
+                source_url, source.package_info.name, package.name, extension_url
+            );
+            return Ok(None);
+        }
+
+        let elements = source
+            .resource
+            .content
+            .get("snapshot")
+            .and_then(|snapshot| snapshot.get("element"))
+            .and_then(|elements| elements.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let target_version = self
+            .releases
+            .first()
+            .map_or(FhirRelease::R4.to_version_string(), |release| {
+                release.to_version_string()
+            });
+        let Some(content) =
+            cross_version::synthesize_extension(&extension, elements, target_version)
+        else {
+            debug!(
+                "{} not found in {}; no cross-version extension for {}",
+                extension.element_id, package.name, extension_url
+            );
+            return Ok(None);
+        };
+
+        debug!(
+            "Synthesized cross-version extension {} from {}@{}",
+            extension_url, package.name, package.version
+        );
+        let resource = Arc::new(DefinitionResource {
+            canonical_url: extension_url.to_string(),
+            resource_type: "StructureDefinition".to_string(),
+            package_id: format!("{}@{}", package.name, package.version),
+            version: Some(extension.release.fhir_version().to_string()),
+            content: Arc::new(content),
+        });
+        self.local_cache
+            .insert(extension_url.to_string(), resource.clone());
+        Ok(Some(resource))
+    }
+
+    /// Resolve a canonical URL from the installed packages (and caches).
+    async fn resolve_from_packages(
+        &self,
+        canonical_url: &str,
+    ) -> CanonicalResult<Arc<DefinitionResource>> {
         // Check local and global caches first
         if let Some(existing) = self.local_cache.get(canonical_url) {
+            self.count_cache_hit(LookupKind::Canonical);
             return Ok(existing.clone());
         }
         if let Some(existing) = self.facade.global_cache.get(canonical_url) {
+            self.count_cache_hit(LookupKind::Canonical);
             let arc = existing.clone();
             self.local_cache
                 .insert(canonical_url.to_string(), arc.clone());
@@ -548,13 +735,16 @@ impl DefinitionSession {
         resource_type: &str,
         name: &str,
     ) -> CanonicalResult<Option<Arc<DefinitionResource>>> {
+        self.count_lookup(LookupKind::TypeAndName);
         let results = self
             .facade
             .manager
             .find_by_type_and_name(resource_type, name)
-            .await?;
+            .await
+            .inspect_err(|_| self.count_miss(LookupKind::TypeAndName))?;
 
         if results.is_empty() {
+            self.count_miss(LookupKind::TypeAndName);
             return Ok(None);
         }
 
@@ -600,11 +790,13 @@ impl DefinitionSession {
         id: &str,
     ) -> CanonicalResult<Option<Arc<DefinitionResource>>> {
         // Use fast direct SQL lookup instead of slow text search
+        self.count_lookup(LookupKind::TypeAndId);
         let results = self
             .facade
             .manager
             .find_by_type_and_id(resource_type, id)
-            .await?;
+            .await
+            .inspect_err(|_| self.count_miss(LookupKind::TypeAndId))?;
 
         // Get the primary FHIR version for filtering
         let fhir_version_filter = self.releases.first().map(|r| r.to_version_string());
@@ -652,6 +844,7 @@ impl DefinitionSession {
             return Ok(Some(resolved));
         }
 
+        self.count_miss(LookupKind::TypeAndId);
         Ok(None)
     }
 
@@ -660,6 +853,40 @@ impl DefinitionSession {
         &self.releases
     }
 
+    /// Lookup counts and cache hits since the session was created
+    pub fn lookup_stats(&self) -> Vec<LookupStats> {
+        LookupKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let counter = &self.lookup_counters[kind as usize];
+                LookupStats {
+                    kind,
+                    lookups: counter.lookups.load(Ordering::Relaxed),
+                    cache_hits: counter.cache_hits.load(Ordering::Relaxed),
+                    misses: counter.misses.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+
+    fn count_lookup(&self, kind: LookupKind) {
+        self.lookup_counters[kind as usize]
+            .lookups
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn count_cache_hit(&self, kind: LookupKind) {
+        self.lookup_counters[kind as usize]
+            .cache_hits
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn count_miss(&self, kind: LookupKind) {
+        self.lookup_counters[kind as usize]
+            .misses
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Returns the set of base FHIR resource type names (e.g., "Patient", "Observation").
     ///
     /// This method queries the canonical manager on first call and caches the result.
@@ -880,6 +1107,7 @@ impl DefinitionSession {
                 installed: dashmap::DashSet::new(),
                 cardinality_cache: dashmap::DashMap::new(),
                 base_resource_types_cache: dashmap::DashSet::new(),
+                lookup_counters: Default::default(),
             }
         }
 
@@ -953,7 +1181,9 @@ impl DefinitionSession {
     /// ```
     pub async fn is_array_element(&self, element_path: &str) -> bool {
         // Check cache first
+        self.count_lookup(LookupKind::Cardinality);
         if let Some(cached) = self.cardinality_cache.get(element_path) {
+            self.count_cache_hit(LookupKind::Cardinality);
             return *cached;
         }
 
@@ -975,6 +1205,7 @@ impl DefinitionSession {
                     "StructureDefinition not found for {}, defaulting to non-array",
                     resource_type
                 );
+                self.count_miss(LookupKind::Cardinality);
                 self.cardinality_cache
                     .insert(element_path.to_string(), false);
                 return false;
@@ -984,6 +1215,7 @@ impl DefinitionSession {
                     "Failed to resolve StructureDefinition for {}: {}, defaulting to non-array",
                     resource_type, e
                 );
+                self.count_miss(LookupKind::Cardinality);
                 self.cardinality_cache
                     .insert(element_path.to_string(), false);
                 return false;
@@ -118,7 +118,7 @@ impl FhirVersionExt for FhirRelease {
         // Handle version numbers
         if s.starts_with("4.0") {
             Ok(FhirRelease::R4)
-        } else if s.starts_with("4.3") {
+        } else if s.starts_with("4.3") || s.starts_with("4.1") {
             Ok(FhirRelease::R4B)
         } else if s.starts_with("5.0") {
             Ok(FhirRelease::R5)
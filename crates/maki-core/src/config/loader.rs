@@ -1,7 +1,11 @@
 //! Configuration file discovery and loading
 
+use super::maki_config::{
+    FormatterConfiguration, LinterConfiguration, RuleSetting, RulesConfiguration,
+};
 use super::unified_config::UnifiedConfig;
 use crate::error::MakiError;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// Result type for configuration operations
@@ -63,9 +67,72 @@ impl ConfigLoader {
     ///
     /// Supports YAML (.yaml, .yml) and JSON (.json) formats
     pub fn load_from_file(path: &Path) -> Result<UnifiedConfig> {
-        UnifiedConfig::load(path).map_err(|e| MakiError::ConfigError {
+        let config = UnifiedConfig::load(path).map_err(|e| MakiError::ConfigError {
             message: format!("Failed to load config from '{}': {}", path.display(), e),
-        })
+        })?;
+        Self::validate_overrides(&config).map_err(|e| MakiError::ConfigError {
+            message: format!("Invalid config '{}': {}", path.display(), e),
+        })?;
+        Ok(config)
+    }
+
+    /// Check the `include` patterns of all overrides
+    fn validate_overrides(config: &UnifiedConfig) -> std::result::Result<(), String> {
+        for (index, entry) in config.overrides.iter().flatten().enumerate() {
+            if entry.include.is_empty() {
+                return Err(format!("overrides[{index}] has no include patterns"));
+            }
+            for pattern in &entry.include {
+                glob::Pattern::new(pattern).map_err(|e| {
+                    format!("overrides[{index}] has invalid include pattern '{pattern}': {e}")
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Absolute directory of a config file, to pass to [`resolve_for_file`](Self::resolve_for_file)
+    ///
+    /// Resolve it once per configuration, not once per file.
+    pub fn override_root(config_dir: &Path) -> PathBuf {
+        absolute_path(config_dir)
+    }
+
+    /// Configuration for `file` with the matching overrides merged in order
+    ///
+    /// `root` is the directory of the config file as given by
+    /// [`override_root`](Self::override_root); override patterns match the
+    /// path of `file` relative to it. Returns `None` when no override applies,
+    /// in which case `config` is used as is.
+    pub fn resolve_for_file(
+        config: &UnifiedConfig,
+        file: &Path,
+        root: &Path,
+    ) -> Option<UnifiedConfig> {
+        let overrides = config.overrides.as_ref()?;
+        let relative = relative_path(file, root);
+        let mut matching = overrides
+            .iter()
+            .filter(|entry| entry.matches(&relative))
+            .peekable();
+        matching.peek()?;
+
+        let mut resolved = config.clone();
+        for entry in matching {
+            if let Some(linter) = &entry.linter {
+                match &mut resolved.linter {
+                    Some(base) => merge_linter(base, linter),
+                    None => resolved.linter = Some(linter.clone()),
+                }
+            }
+            if let Some(formatter) = &entry.formatter {
+                match &mut resolved.formatter {
+                    Some(base) => merge_formatter(base, formatter),
+                    None => resolved.formatter = Some(formatter.clone()),
+                }
+            }
+        }
+        Some(resolved)
     }
 
     /// Load config from path or auto-discover
@@ -103,9 +170,96 @@ impl ConfigLoader {
     }
 }
 
+/// `file` relative to the absolute `root`, or `file` itself when it lies outside `root`
+fn relative_path(file: &Path, root: &Path) -> PathBuf {
+    let file = absolute_path(file);
+    match file.strip_prefix(root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => file,
+    }
+}
+
+/// Canonical form of `path`, or `path` joined to the current directory when
+/// it can't be canonicalized
+fn absolute_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+/// Overwrite the fields of `base` that are set in `other`
+fn overlay<T: Clone>(base: &mut Option<T>, other: &Option<T>) {
+    if other.is_some() {
+        base.clone_from(other);
+    }
+}
+
+fn merge_linter(base: &mut LinterConfiguration, other: &LinterConfiguration) {
+    overlay(&mut base.enabled, &other.enabled);
+    overlay(&mut base.rule_directories, &other.rule_directories);
+    overlay(
+        &mut base.ignore_public_definitions,
+        &other.ignore_public_definitions,
+    );
+    if let Some(rules) = &other.rules {
+        match &mut base.rules {
+            Some(base_rules) => merge_rules(base_rules, rules),
+            None => base.rules = Some(rules.clone()),
+        }
+    }
+}
+
+/// Merge rule settings; a rule configured in `other` replaces its setting in `base`
+fn merge_rules(base: &mut RulesConfiguration, other: &RulesConfiguration) {
+    fn merge_category(
+        base: &mut Option<HashMap<String, RuleSetting>>,
+        other: &Option<HashMap<String, RuleSetting>>,
+    ) {
+        if let Some(other) = other {
+            base.get_or_insert_with(HashMap::new).extend(
+                other
+                    .iter()
+                    .map(|(name, setting)| (name.clone(), setting.clone())),
+            );
+        }
+    }
+
+    overlay(&mut base.recommended, &other.recommended);
+    overlay(&mut base.all, &other.all);
+    merge_category(&mut base.blocking, &other.blocking);
+    merge_category(&mut base.correctness, &other.correctness);
+    merge_category(&mut base.suspicious, &other.suspicious);
+    merge_category(&mut base.style, &other.style);
+    merge_category(&mut base.documentation, &other.documentation);
+}
+
+fn merge_formatter(base: &mut FormatterConfiguration, other: &FormatterConfiguration) {
+    overlay(&mut base.enabled, &other.enabled);
+    overlay(&mut base.indent_style, &other.indent_style);
+    overlay(&mut base.indent_size, &other.indent_size);
+    overlay(&mut base.line_width, &other.line_width);
+    overlay(&mut base.align_carets, &other.align_carets);
+    overlay(
+        &mut base.blank_line_before_rules,
+        &other.blank_line_before_rules,
+    );
+    overlay(&mut base.preserve_blank_lines, &other.preserve_blank_lines);
+    overlay(&mut base.max_blank_lines, &other.max_blank_lines);
+    overlay(&mut base.group_rules, &other.group_rules);
+    overlay(&mut base.sort_rules, &other.sort_rules);
+    overlay(
+        &mut base.blank_lines_between_groups,
+        &other.blank_lines_between_groups,
+    );
+    overlay(&mut base.normalize_spacing, &other.normalize_spacing);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::RuleSeverity;
     use std::fs;
     use tempfile::TempDir;
 
@@ -309,4 +463,65 @@ enabled = true
         assert_eq!(formatter.indent_size, Some(2));
         assert_eq!(formatter.line_width, Some(100));
     }
+
+    #[test]
+    fn test_resolve_for_file_merges_overrides_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_temp_config(
+            temp_dir.path(),
+            ".makirc.json",
+            r#"{
+                "linter": {
+                    "rules": {
+                        "documentation": { "missing-metadata": "warn" },
+                        "style": { "naming-convention": "warn" }
+                    }
+                },
+                "formatter": { "indentSize": 2, "lineWidth": 100 },
+                "overrides": [
+                    {
+                        "include": ["input/fsh/examples/**"],
+                        "linter": {
+                            "rules": { "documentation": { "missing-metadata": "off" } }
+                        },
+                        "formatter": { "lineWidth": 120 }
+                    },
+                    {
+                        "include": ["**/Legacy*.fsh"],
+                        "formatter": { "lineWidth": 80 }
+                    }
+                ]
+            }"#,
+        );
+        let config = ConfigLoader::load_from_file(&config_path).unwrap();
+        let root = &ConfigLoader::override_root(temp_dir.path());
+
+        let profile = root.join("input/fsh/profiles/Patient.fsh");
+        assert!(ConfigLoader::resolve_for_file(&config, &profile, root).is_none());
+
+        let example = root.join("input/fsh/examples/Legacy.fsh");
+        let resolved = ConfigLoader::resolve_for_file(&config, &example, root).unwrap();
+        let rules = resolved.linter.unwrap().rules.unwrap();
+        let documentation = rules.documentation.unwrap();
+        assert_eq!(documentation["missing-metadata"], RuleSeverity::Off.into());
+        assert_eq!(
+            rules.style.unwrap()["naming-convention"],
+            RuleSeverity::Warn.into()
+        );
+        let formatter = resolved.formatter.unwrap();
+        assert_eq!(formatter.line_width, Some(80));
+        assert_eq!(formatter.indent_size, Some(2));
+    }
+
+    #[test]
+    fn test_invalid_override_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_temp_config(
+            temp_dir.path(),
+            ".makirc.json",
+            r#"{ "overrides": [{ "include": ["input/[fsh"] }] }"#,
+        );
+        let error = ConfigLoader::load_from_file(&config_path).unwrap_err();
+        assert!(error.to_string().contains("overrides[0]"));
+    }
 }
@@ -3,9 +3,11 @@
 //! This module contains the sub-configuration types used by UnifiedConfig.
 //! The main configuration structure is UnifiedConfig in unified_config.rs.
 
+use glob::Pattern;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Linter configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -22,6 +24,13 @@ pub struct LinterConfiguration {
     /// Directories containing custom GritQL rules
     #[schemars(description = "Paths to directories containing .grit rule files")]
     pub rule_directories: Option<Vec<String>>,
+
+    /// Skip definitions marked `// maki-public` in the unused-definition rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Don't report definitions preceded by a `// maki-public` comment as unused (default: true)"
+    )]
+    pub ignore_public_definitions: Option<bool>,
 }
 
 /// Rules configuration
@@ -38,23 +47,89 @@ pub struct RulesConfiguration {
 
     /// Blocking rules (critical requirements)
     #[schemars(description = "Blocking rules configuration")]
-    pub blocking: Option<HashMap<String, RuleSeverity>>,
+    pub blocking: Option<HashMap<String, RuleSetting>>,
 
     /// Correctness rules (errors in FSH logic)
     #[schemars(description = "Correctness rules configuration")]
-    pub correctness: Option<HashMap<String, RuleSeverity>>,
+    pub correctness: Option<HashMap<String, RuleSetting>>,
 
     /// Suspicious rules (patterns that often indicate bugs)
     #[schemars(description = "Suspicious rules configuration")]
-    pub suspicious: Option<HashMap<String, RuleSeverity>>,
+    pub suspicious: Option<HashMap<String, RuleSetting>>,
 
     /// Style rules (formatting and conventions)
     #[schemars(description = "Style rules configuration")]
-    pub style: Option<HashMap<String, RuleSeverity>>,
+    pub style: Option<HashMap<String, RuleSetting>>,
 
     /// Documentation rules
     #[schemars(description = "Documentation rules configuration")]
-    pub documentation: Option<HashMap<String, RuleSeverity>>,
+    pub documentation: Option<HashMap<String, RuleSetting>>,
+}
+
+impl RulesConfiguration {
+    /// Configured rules with their full rule ID (`category/name`)
+    ///
+    /// Keys that already contain a `/` are taken as full rule IDs.
+    pub fn rules(&self) -> impl Iterator<Item = (String, &RuleSetting)> {
+        [
+            ("blocking", &self.blocking),
+            ("correctness", &self.correctness),
+            ("suspicious", &self.suspicious),
+            ("style", &self.style),
+            ("documentation", &self.documentation),
+        ]
+        .into_iter()
+        .filter_map(|(category, rules)| Some((category, rules.as_ref()?)))
+        .flat_map(|(category, rules)| {
+            rules.iter().map(move |(name, setting)| {
+                let id = if name.contains('/') {
+                    name.clone()
+                } else {
+                    format!("{category}/{name}")
+                };
+                (id, setting)
+            })
+        })
+    }
+}
+
+/// Configuration of a single rule: a severity, or a severity with options
+///
+/// ```jsonc
+/// "naming-convention": "warn"
+/// "naming-convention": { "severity": "warn", "options": { "names": { "profile": "^[A-Z]\\w*Profile$" } } }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(untagged)]
+pub enum RuleSetting {
+    /// Severity only
+    Severity(RuleSeverity),
+    /// Severity and rule-specific options
+    Config(RuleConfig),
+}
+
+impl RuleSetting {
+    /// Configured severity, if any
+    pub fn severity(&self) -> Option<RuleSeverity> {
+        match self {
+            RuleSetting::Severity(severity) => Some(*severity),
+            RuleSetting::Config(config) => config.severity,
+        }
+    }
+
+    /// Configured options, if any
+    pub fn options(&self) -> Option<&serde_json::Value> {
+        match self {
+            RuleSetting::Severity(_) => None,
+            RuleSetting::Config(config) => config.options.as_ref(),
+        }
+    }
+}
+
+impl From<RuleSeverity> for RuleSetting {
+    fn from(severity: RuleSeverity) -> Self {
+        RuleSetting::Severity(severity)
+    }
 }
 
 /// Rule severity levels
@@ -151,11 +226,59 @@ pub struct FilesConfiguration {
     pub ignore_files: Option<Vec<String>>,
 }
 
+/// Linter and formatter settings for the files matching `include`
+///
+/// Overrides are applied in order on top of the top-level `linter` and
+/// `formatter` sections, so later overrides win.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideConfiguration {
+    /// Glob patterns of the files this override applies to
+    #[schemars(
+        description = "Glob patterns, relative to the config file, of the files this override applies to"
+    )]
+    pub include: Vec<String>,
+
+    /// Linter settings for the matching files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Linter settings merged into `linter` for the matching files")]
+    pub linter: Option<LinterConfiguration>,
+
+    /// Formatter settings for the matching files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Formatter settings merged into `formatter` for the matching files")]
+    pub formatter: Option<FormatterConfiguration>,
+}
+
+impl OverrideConfiguration {
+    /// Whether `path`, relative to the config file, matches an `include` pattern
+    pub fn matches(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        self.include.iter().any(|pattern| {
+            Pattern::new(pattern)
+                .map(|p| p.matches(&path))
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// Per-FHIR-release build configuration
+///
+/// Keyed by release label (`R4`, `R4B`, `R5`, `R6`) in the `releases` section.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseConfiguration {
+    /// RuleSet variants: `insert Name` expands `rulesets[Name]` when building this release
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schemars(description = "RuleSet replacements (RuleSet name -> variant) for this release")]
+    pub rulesets: HashMap<String, String>,
+}
+
 /// Rule-specific configuration with options
 ///
 /// This type is used for individual rule configuration, allowing
 /// fine-grained control over rule behavior and options.
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RuleConfig {
     /// Rule severity override
@@ -173,6 +296,7 @@ impl Default for LinterConfiguration {
             enabled: Some(true),
             rules: Some(RulesConfiguration::default()),
             rule_directories: None,
+            ignore_public_definitions: None,
         }
     }
 }
@@ -240,4 +364,32 @@ mod tests {
         let json = serde_json::to_string(&severity).unwrap();
         assert_eq!(json, r#""off""#);
     }
+
+    #[test]
+    fn test_rule_settings() {
+        let rules: RulesConfiguration = serde_json::from_str(
+            r#"{
+                "style": {
+                    "naming-convention": {
+                        "severity": "error",
+                        "options": { "names": { "profile": "^[A-Z]" } }
+                    }
+                },
+                "documentation": { "missing-metadata": "off" }
+            }"#,
+        )
+        .unwrap();
+
+        let mut settings: Vec<_> = rules.rules().collect();
+        settings.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(settings[0].0, "documentation/missing-metadata");
+        assert_eq!(settings[0].1.severity(), Some(RuleSeverity::Off));
+        assert!(settings[0].1.options().is_none());
+        assert_eq!(settings[1].0, "style/naming-convention");
+        assert_eq!(settings[1].1.severity(), Some(RuleSeverity::Error));
+        assert_eq!(
+            settings[1].1.options().unwrap()["names"]["profile"],
+            "^[A-Z]"
+        );
+    }
 }
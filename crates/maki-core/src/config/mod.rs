@@ -49,7 +49,10 @@
 //!         "duplicate-definition": "error"
 //!       },
 //!       "style": {
-//!         "naming-convention": "warn"
+//!         "naming-convention": {
+//!           "severity": "warn",
+//!           "options": { "ids": { "profile": "^[a-z][a-z0-9-]*$" } }
+//!         }
 //!       }
 //!     },
 //!     "ruleDirectories": ["./custom-rules"]
@@ -76,14 +79,15 @@ mod unified_config;
 // Re-export main types
 pub use loader::ConfigLoader;
 pub use maki_config::{
-    FilesConfiguration, FormatterConfiguration, IndentStyle, LinterConfiguration, RuleConfig,
-    RuleSeverity, RulesConfiguration,
+    FilesConfiguration, FormatterConfiguration, IndentStyle, LinterConfiguration,
+    OverrideConfiguration, ReleaseConfiguration, RuleConfig, RuleSetting, RuleSeverity,
+    RulesConfiguration,
 };
 pub use sushi_config::{
     CodeableConcept, Coding, ConfigError, ContactDetail, ContactPoint, DefinitionExtension,
     DependencyVersion, GlobalProfile, IdSetting, InstanceOptions, MenuItem, MetaProfileSetting,
-    PageDefinition, Parameter, PublisherInfo, ResourceEntry, ResourceGroup, SushiConfiguration,
-    Template, UsageContext, parse_dependency,
+    OutputFormat, PageDefinition, Parameter, PublisherInfo, ResourceEntry, ResourceGroup,
+    SushiConfiguration, Template, UsageContext, parse_dependency,
 };
 pub use unified_config::{BuildConfiguration, UnifiedConfig};
 
@@ -173,6 +173,10 @@ pub struct SushiConfiguration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instance_options: Option<InstanceOptions>,
 
+    /// Serialization formats for exported resources (defaults to `[json]`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<Vec<OutputFormat>>,
+
     // === Other IG.definition Properties ===
     #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<serde_json::Value>,
@@ -546,6 +550,16 @@ pub enum IdSetting {
     StandaloneOnly,
 }
 
+/// Serialization format for exported resources
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// FHIR JSON (`*.json`)
+    Json,
+    /// FHIR XML (`*.xml`)
+    Xml,
+}
+
 /// Definition extension container
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DefinitionExtension {
@@ -614,6 +628,14 @@ impl SushiConfiguration {
     pub fn package_id(&self) -> Option<&str> {
         self.package_id.as_deref().or(self.id.as_deref())
     }
+
+    /// Get the configured output formats (JSON only when not specified)
+    pub fn output_formats(&self) -> Vec<OutputFormat> {
+        match &self.output_format {
+            Some(formats) if !formats.is_empty() => formats.clone(),
+            _ => vec![OutputFormat::Json],
+        }
+    }
 }
 
 /// Validate FHIR version string
@@ -739,6 +761,30 @@ fhirVersion:
         assert_eq!(config.fhir_version, vec!["4.0.1", "4.3.0"]);
     }
 
+    #[test]
+    fn test_output_format() {
+        let yaml = r#"
+canonical: http://example.org/fhir/example-ig
+fhirVersion: 4.0.1
+outputFormat:
+  - json
+  - xml
+"#;
+
+        let config = SushiConfiguration::from_yaml(yaml).unwrap();
+        assert_eq!(
+            config.output_formats(),
+            vec![OutputFormat::Json, OutputFormat::Xml]
+        );
+
+        let yaml = r#"
+canonical: http://example.org/fhir/example-ig
+fhirVersion: 4.0.1
+"#;
+        let config = SushiConfiguration::from_yaml(yaml).unwrap();
+        assert_eq!(config.output_formats(), vec![OutputFormat::Json]);
+    }
+
     #[test]
     fn test_validation_missing_canonical() {
         let config = SushiConfiguration {
@@ -774,6 +820,7 @@ fhirVersion:
             fsh_only: None,
             apply_extension_metadata_to_root: None,
             instance_options: None,
+            output_format: None,
             meta: None,
             implicit_rules: None,
             language: None,
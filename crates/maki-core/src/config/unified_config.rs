@@ -52,6 +52,15 @@
 //!     - "input/fsh/**/*.fsh"
 //!   exclude:
 //!     - "**/*.draft.fsh"
+//!
+//! # === Per-Path Overrides ===
+//! overrides:
+//!   - include:
+//!       - "input/fsh/examples/**"
+//!     linter:
+//!       rules:
+//!         documentation:
+//!           missing-metadata: off
 //! ```
 
 use schemars::JsonSchema;
@@ -62,7 +71,7 @@ use std::path::Path;
 
 use super::{
     DependencyVersion, FilesConfiguration, FormatterConfiguration, LinterConfiguration,
-    SushiConfiguration,
+    OverrideConfiguration, ReleaseConfiguration, SushiConfiguration,
 };
 
 /// Build configuration type (alias for SushiConfiguration for clarity)
@@ -128,6 +137,38 @@ pub struct UnifiedConfig {
     /// Specifies which FSH files to include/exclude from processing.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub files: Option<FilesConfiguration>,
+
+    /// Per-release settings for multi-FHIR-version builds
+    ///
+    /// When `build.fhirVersion` lists several releases, each one is built into
+    /// its own output directory (`fsh-generated/r4`, `fsh-generated/r5`).
+    ///
+    /// Example:
+    /// ```yaml
+    /// releases:
+    ///   R5:
+    ///     rulesets:
+    ///       ContactRules: ContactRulesR5
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub releases: Option<HashMap<String, ReleaseConfiguration>>,
+
+    /// Per-path linter and formatter settings
+    ///
+    /// Each override applies to the files matching its `include` patterns and
+    /// is merged in order on top of `linter` and `formatter`.
+    ///
+    /// Example:
+    /// ```yaml
+    /// overrides:
+    ///   - include: ["input/fsh/examples/**"]
+    ///     linter:
+    ///       rules:
+    ///         documentation:
+    ///           missing-metadata: off
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<Vec<OverrideConfiguration>>,
 }
 
 impl UnifiedConfig {
@@ -221,6 +262,8 @@ impl Default for UnifiedConfig {
             linter: Some(LinterConfiguration::default()),
             formatter: Some(FormatterConfiguration::default()),
             files: Some(FilesConfiguration::default()),
+            releases: None,
+            overrides: None,
         }
     }
 }
@@ -252,6 +295,15 @@ impl UnifiedConfig {
     pub fn top_level_dependencies(&self) -> Option<&HashMap<String, DependencyVersion>> {
         self.dependencies.as_ref()
     }
+
+    /// Get the settings for a FHIR release label (`R4`, `r5`, ...)
+    pub fn release(&self, label: &str) -> Option<&ReleaseConfiguration> {
+        self.releases
+            .as_ref()?
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(label))
+            .map(|(_, release)| release)
+    }
 }
 
 #[cfg(test)]
@@ -274,6 +326,8 @@ mod tests {
             linter: Some(LinterConfiguration::default()),
             formatter: Some(FormatterConfiguration::default()),
             files: Some(FilesConfiguration::default()),
+            releases: None,
+            overrides: None,
         };
 
         // Test YAML serialization
@@ -2313,66 +2313,71 @@ impl ContainsRule {
     /// Example: "ExtType named sliceName 0..1" → [("ExtType", "sliceName")]
     /// Example: "ExtType 0..1" → [("ExtType", "ExtType")]
     pub fn items_with_types(&self) -> Vec<(String, String)> {
-        let text = self.syntax.text().to_string();
-
-        // Remove "contains" keyword and clean up whitespace/newlines
-        let after_contains = text
-            .trim_start()
-            .strip_prefix("contains")
-            .unwrap_or(&text)
-            .trim();
-
-        // Clean up the text - replace newlines with spaces and normalize whitespace
-        let cleaned_text = after_contains.replace(['\n', '\r'], " ");
-
-        // Split by "and" keyword and extract item names
-        cleaned_text
-            .split("and")
-            .filter_map(|item| {
-                let item = item.trim();
+        // Split on `and` keyword tokens rather than the raw text so that names
+        // containing "and" (e.g. `BrandName`) stay intact.
+        let mut groups: Vec<Vec<FshSyntaxToken>> = vec![Vec::new()];
+        for token in self
+            .syntax
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+        {
+            match token.kind() {
+                FshSyntaxKind::ContainsKw => {}
+                FshSyntaxKind::AndKw => groups.push(Vec::new()),
+                kind if kind.is_trivia() => {}
+                _ => groups
+                    .last_mut()
+                    .expect("groups is never empty")
+                    .push(token),
+            }
+        }
 
+        groups
+            .iter()
+            .filter_map(|tokens| {
                 // Handle "ExtType named sliceName" format
-                if let Some(named_pos) = item.find(" named ") {
-                    let ext_type = item[..named_pos].trim();
-                    let after_named = item[named_pos + 7..].trim(); // 7 = " named ".len()
-
-                    // Extract slice name (first word after "named", before cardinality/flags)
-                    let slice_name = after_named
-                        .split_whitespace()
-                        .next()
-                        .filter(|w| {
-                            !w.contains("..") && *w != "MS" && *w != "SU" && !w.starts_with("//")
-                        })
-                        .unwrap_or(ext_type);
-
-                    if !ext_type.is_empty() {
-                        Some((ext_type.to_string(), slice_name.to_string()))
-                    } else {
-                        None
+                if let Some(named_pos) = tokens
+                    .iter()
+                    .position(|t| t.kind() == FshSyntaxKind::NamedKw)
+                {
+                    let ext_type: String = tokens[..named_pos].iter().map(|t| t.text()).collect();
+                    if ext_type.is_empty() {
+                        return None;
                     }
+
+                    // Slice name is the first token after "named", before cardinality/flags
+                    let slice_name = tokens
+                        .get(named_pos + 1)
+                        .filter(|t| !Self::is_cardinality_or_flag(t))
+                        .map(|t| t.text().to_string())
+                        .unwrap_or_else(|| ext_type.clone());
+
+                    Some((ext_type, slice_name))
                 } else {
                     // No "named" - extension type is also the slice name
-                    let words: Vec<&str> = item.split_whitespace().collect();
-                    if let Some(first_word) = words.first() {
-                        let first_word = first_word.trim();
-                        // Skip cardinality patterns and flags
-                        if !first_word.is_empty()
-                            && !first_word.contains("..")
-                            && first_word != "MS"
-                            && first_word != "SU"
-                            && !first_word.starts_with("//")
-                        {
-                            Some((first_word.to_string(), first_word.to_string()))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
+                    tokens
+                        .first()
+                        .filter(|t| !Self::is_cardinality_or_flag(t))
+                        .map(|t| (t.text().to_string(), t.text().to_string()))
                 }
             })
             .collect()
     }
+
+    fn is_cardinality_or_flag(token: &FshSyntaxToken) -> bool {
+        matches!(
+            token.kind(),
+            FshSyntaxKind::Integer
+                | FshSyntaxKind::Range
+                | FshSyntaxKind::Asterisk
+                | FshSyntaxKind::MsFlag
+                | FshSyntaxKind::SuFlag
+                | FshSyntaxKind::TuFlag
+                | FshSyntaxKind::NFlag
+                | FshSyntaxKind::DFlag
+                | FshSyntaxKind::ModifierFlag
+        ) || token.text().contains("..")
+    }
 }
 
 /// Only rule: * path only Type1 or Type2
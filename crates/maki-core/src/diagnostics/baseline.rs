@@ -0,0 +1,305 @@
+//! Lint baselines
+//!
+//! A baseline records the diagnostics a project already has, so CI can fail
+//! only on new ones (`maki lint --write-baseline` / `--baseline`).
+//!
+//! Diagnostics are matched by fingerprint rather than by line number. A
+//! fingerprint combines the rule ID, the file (relative to the baseline), the
+//! enclosing entity name and the whitespace-normalized source line, so edits
+//! elsewhere in a file don't invalidate the baseline.
+
+use super::Diagnostic;
+use crate::{MakiError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Current baseline file format version
+pub const BASELINE_VERSION: u32 = 1;
+
+const ENTITY_KEYWORDS: &[&str] = &[
+    "Profile",
+    "Extension",
+    "Logical",
+    "Resource",
+    "Instance",
+    "ValueSet",
+    "CodeSystem",
+    "Invariant",
+    "RuleSet",
+    "Mapping",
+];
+
+/// A baselined diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub rule_id: String,
+    /// File relative to the baseline, with `/` separators
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
+    /// Normalized source line the diagnostic starts on
+    pub snippet: String,
+    pub message: String,
+    /// Line at the time the baseline was written (informational only)
+    pub line: usize,
+}
+
+/// Diagnostics recorded by `maki lint --write-baseline`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Baseline {
+    pub version: u32,
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// Result of checking diagnostics against a [`Baseline`]
+#[derive(Debug, Clone, Default)]
+pub struct BaselineComparison {
+    /// Diagnostics not in the baseline
+    pub new: Vec<Diagnostic>,
+    /// Number of diagnostics matched by the baseline
+    pub baselined: usize,
+    /// Baseline entries that no longer occur
+    pub fixed: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Record `diagnostics`, with file paths relative to `root`
+    ///
+    /// Every diagnostic gets its own entry, even when several share a
+    /// position, so [`compare`](Self::compare) matches them all.
+    pub fn from_diagnostics(diagnostics: &[Diagnostic], root: &Path) -> Self {
+        let mut fingerprinter = Fingerprinter::new(root);
+        let mut entries: Vec<BaselineEntry> = diagnostics
+            .iter()
+            .map(|diagnostic| fingerprinter.entry(diagnostic))
+            .collect();
+        sort_entries(&mut entries);
+
+        Self {
+            version: BASELINE_VERSION,
+            entries,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| MakiError::io_error(path, e))?;
+        let baseline: Self = serde_json::from_str(&content).map_err(|e| {
+            MakiError::config_error(format!("Invalid baseline {}: {}", path.display(), e))
+        })?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(MakiError::config_error(format!(
+                "Unsupported baseline version {} in {} (expected {})",
+                baseline.version,
+                path.display(),
+                BASELINE_VERSION
+            )));
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| MakiError::config_error(format!("Failed to serialize baseline: {}", e)))?;
+        std::fs::write(path, json + "\n").map_err(|e| MakiError::io_error(path, e))
+    }
+
+    /// Split `diagnostics` into new and baselined ones
+    ///
+    /// Each entry matches at most one diagnostic, so a second occurrence of
+    /// a baselined issue is reported as new.
+    pub fn compare(&self, diagnostics: Vec<Diagnostic>, root: &Path) -> BaselineComparison {
+        let mut remaining: HashMap<&str, Vec<&BaselineEntry>> = HashMap::new();
+        for entry in &self.entries {
+            remaining
+                .entry(entry.fingerprint.as_str())
+                .or_default()
+                .push(entry);
+        }
+
+        let mut fingerprinter = Fingerprinter::new(root);
+        let mut comparison = BaselineComparison::default();
+        for diagnostic in diagnostics {
+            let fingerprint = fingerprinter.entry(&diagnostic).fingerprint;
+            let matched = remaining
+                .get_mut(fingerprint.as_str())
+                .and_then(|entries| entries.pop())
+                .is_some();
+            if matched {
+                comparison.baselined += 1;
+            } else {
+                comparison.new.push(diagnostic);
+            }
+        }
+
+        comparison.fixed = remaining.into_values().flatten().cloned().collect();
+        sort_entries(&mut comparison.fixed);
+        comparison
+    }
+}
+
+fn sort_entries(entries: &mut [BaselineEntry]) {
+    entries.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.rule_id.cmp(&b.rule_id))
+    });
+}
+
+/// Computes baseline entries, caching file contents
+struct Fingerprinter {
+    root: PathBuf,
+    sources: HashMap<PathBuf, String>,
+}
+
+impl Fingerprinter {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            sources: HashMap::new(),
+        }
+    }
+
+    fn entry(&mut self, diagnostic: &Diagnostic) -> BaselineEntry {
+        let path = &diagnostic.location.file;
+        let file = relative_path(path, &self.root);
+        let source = self
+            .sources
+            .entry(path.clone())
+            .or_insert_with(|| std::fs::read_to_string(path).unwrap_or_default());
+        baseline_entry(diagnostic, file, source)
+    }
+}
+
+fn relative_path(path: &Path, root: &Path) -> String {
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let relative = absolute.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Fingerprint `diagnostic`, found in `file` with content `source`
+fn baseline_entry(diagnostic: &Diagnostic, file: String, source: &str) -> BaselineEntry {
+    let lines: Vec<&str> = source.lines().collect();
+    let index = diagnostic.location.line.saturating_sub(1).min(lines.len());
+    let snippet = lines
+        .get(index)
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default();
+    let entity = lines[..lines.len().min(index + 1)]
+        .iter()
+        .rev()
+        .find_map(|line| entity_name(line));
+
+    let key = format!(
+        "{}\n{}\n{}\n{}",
+        diagnostic.rule_id,
+        file,
+        entity.as_deref().unwrap_or(""),
+        snippet
+    );
+    BaselineEntry {
+        fingerprint: Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes())
+            .simple()
+            .to_string(),
+        rule_id: diagnostic.rule_id.clone(),
+        file,
+        entity,
+        snippet,
+        message: diagnostic.message.clone(),
+        line: diagnostic.location.line,
+    }
+}
+
+/// Name declared by an entity header line (`Profile: MyPatient`)
+fn entity_name(line: &str) -> Option<String> {
+    let (keyword, name) = line.split_once(':')?;
+    if !ENTITY_KEYWORDS.contains(&keyword.trim_end()) {
+        return None;
+    }
+    name.split_whitespace().next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+    use crate::diagnostics::Location;
+
+    const SOURCE: &str = "Profile: MyPatient\nParent: Patient\n* name   1..1\n";
+
+    fn diagnostic(rule_id: &str, line: usize) -> Diagnostic {
+        Diagnostic::new(
+            rule_id,
+            Severity::Warning,
+            "message",
+            Location::new(PathBuf::from("input/fsh/a.fsh"), line, 1, 0, 0),
+        )
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_line_shifts() {
+        let entry = baseline_entry(&diagnostic("style/x", 3), "input/fsh/a.fsh".into(), SOURCE);
+        assert_eq!(entry.entity.as_deref(), Some("MyPatient"));
+        assert_eq!(entry.snippet, "* name 1..1");
+
+        let shifted = format!("// header\n\n{}", SOURCE.replace("1..1", " 1..1"));
+        let moved = baseline_entry(
+            &diagnostic("style/x", 5),
+            "input/fsh/a.fsh".into(),
+            &shifted,
+        );
+        assert_eq!(entry.fingerprint, moved.fingerprint);
+
+        let other_rule =
+            baseline_entry(&diagnostic("style/y", 3), "input/fsh/a.fsh".into(), SOURCE);
+        assert_ne!(entry.fingerprint, other_rule.fingerprint);
+    }
+
+    #[test]
+    fn test_compare_reports_new_and_fixed() {
+        let root = Path::new(".");
+        let baseline =
+            Baseline::from_diagnostics(&[diagnostic("style/x", 3), diagnostic("style/y", 1)], root);
+        assert_eq!(baseline.entries.len(), 2);
+
+        let comparison = baseline.compare(
+            vec![
+                diagnostic("style/x", 3),
+                diagnostic("style/x", 3),
+                diagnostic("style/z", 2),
+            ],
+            root,
+        );
+        assert_eq!(comparison.baselined, 1);
+        assert_eq!(comparison.new.len(), 2);
+        assert_eq!(comparison.fixed.len(), 1);
+        assert_eq!(comparison.fixed[0].rule_id, "style/y");
+    }
+
+    #[test]
+    fn test_same_position_diagnostics_round_trip() {
+        let root = Path::new(".");
+        let mut title = diagnostic("style/x", 1);
+        title.message = "Missing Title".to_string();
+        let mut description = diagnostic("style/x", 1);
+        description.message = "Missing Description".to_string();
+        let diagnostics = vec![title, description];
+
+        let baseline = Baseline::from_diagnostics(&diagnostics, root);
+        assert_eq!(baseline.entries.len(), 2);
+
+        let comparison = baseline.compare(diagnostics, root);
+        assert!(comparison.new.is_empty());
+        assert_eq!(comparison.baselined, 2);
+        assert!(comparison.fixed.is_empty());
+    }
+}
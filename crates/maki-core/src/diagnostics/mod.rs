@@ -5,12 +5,15 @@
 //! - Rich diff rendering for suggestions
 //! - Console utilities with color support
 //! - Multiple output formats (text, JSON)
+//! - Lint baselines for reporting only new diagnostics
 
+pub mod baseline;
 pub mod diff;
 pub mod renderer;
 pub mod types;
 
 // Re-export new renderers
+pub use baseline::{Baseline, BaselineComparison, BaselineEntry};
 pub use diff::DiffRenderer;
 pub use renderer::{DiagnosticRenderer, OutputFormat};
 
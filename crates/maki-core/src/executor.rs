@@ -36,7 +36,10 @@ fn init_global_thread_pool(threads: usize) {
     });
 }
 
-use crate::{CompiledRule, Diagnostic, MakiError, Result, RuleEngine, SemanticAnalyzer};
+use crate::{
+    CompiledRule, Diagnostic, MakiError, ParseError, Result, RuleEngine, SemanticAnalyzer,
+    SemanticModel,
+};
 
 /// Progress reporting callback type
 pub type ProgressCallback = Arc<dyn Fn(ProgressInfo) + Send + Sync>;
@@ -332,6 +335,14 @@ pub struct FileExecutionResult {
     pub error: Option<MakiError>,
 }
 
+/// A file that was already parsed and analyzed, e.g. by a project-wide pre-pass
+pub struct AnalyzedFile {
+    /// Semantic model of the file
+    pub model: SemanticModel,
+    /// Errors from parsing the file
+    pub parse_errors: Vec<ParseError>,
+}
+
 /// Trait for executing linting operations
 pub trait Executor {
     /// Execute linting on multiple files in parallel
@@ -436,25 +447,7 @@ impl DefaultExecutor {
                 .analyze(&cst, &parse_source, file_path.to_path_buf());
 
         match semantic_result {
-            Ok(semantic_model) => {
-                // Execute rules against the semantic model (async)
-                // Try to use existing Tokio runtime, or create a new one if needed
-                let rule_diagnostics = match tokio::runtime::Handle::try_current() {
-                    Ok(handle) => {
-                        // We're in a Tokio runtime - use block_in_place for efficiency
-                        tokio::task::block_in_place(|| {
-                            handle.block_on(self.rule_engine.execute_rules(&semantic_model))
-                        })
-                    }
-                    Err(_) => {
-                        // No runtime available - create a temporary one
-                        let rt =
-                            tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
-                        rt.block_on(self.rule_engine.execute_rules(&semantic_model))
-                    }
-                };
-                diagnostics.extend(rule_diagnostics);
-            }
+            Ok(semantic_model) => diagnostics.extend(self.execute_rules(&semantic_model)),
             Err(e) => {
                 error!(
                     "Semantic analysis failed for {}: {}",
@@ -465,6 +458,33 @@ impl DefaultExecutor {
             }
         }
 
+        Self::file_result(file_path, diagnostics, start_time)
+    }
+
+    /// Execute rules against the semantic model
+    fn execute_rules(&self, semantic_model: &SemanticModel) -> Vec<Diagnostic> {
+        // Try to use existing Tokio runtime, or create a new one if needed
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                // We're in a Tokio runtime - use block_in_place for efficiency
+                tokio::task::block_in_place(|| {
+                    handle.block_on(self.rule_engine.execute_rules(semantic_model))
+                })
+            }
+            Err(_) => {
+                // No runtime available - create a temporary one
+                let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
+                rt.block_on(self.rule_engine.execute_rules(semantic_model))
+            }
+        }
+    }
+
+    /// Sort `diagnostics` and wrap them into the result for `file_path`
+    fn file_result(
+        file_path: &Path,
+        mut diagnostics: Vec<Diagnostic>,
+        start_time: Instant,
+    ) -> FileExecutionResult {
         // Sort diagnostics for deterministic output
         diagnostics.sort_by(|a, b| {
             a.location
@@ -489,6 +509,46 @@ impl DefaultExecutor {
         }
     }
 
+    /// Lint files that were already analyzed, reusing their semantic models
+    ///
+    /// Semantic models hold syntax trees that can't be sent across threads,
+    /// so the files are linted one after another on the calling thread.
+    pub fn execute_analyzed(&self, files: Vec<AnalyzedFile>) -> Vec<FileExecutionResult> {
+        let total_files = files.len();
+        let start_time = Instant::now();
+
+        let results: Vec<FileExecutionResult> = files
+            .into_iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let file_start = Instant::now();
+                let file_path = file.model.source_file.clone();
+                let mut diagnostics: Vec<Diagnostic> = file
+                    .parse_errors
+                    .into_iter()
+                    .map(|error| Diagnostic::from_parse_error(error, &file_path))
+                    .collect();
+                diagnostics.extend(self.execute_rules(&file.model));
+
+                self.report_progress(ProgressInfo {
+                    total_files,
+                    completed_files: index + 1,
+                    current_file: Some(file_path.clone()),
+                    elapsed: start_time.elapsed(),
+                    estimated_remaining: None,
+                });
+                Self::file_result(&file_path, diagnostics, file_start)
+            })
+            .collect();
+
+        info!(
+            "Completed execution of {} analyzed files in {:?}",
+            total_files,
+            start_time.elapsed()
+        );
+        results
+    }
+
     /// Report progress if callback is configured
     fn report_progress(&self, info: ProgressInfo) {
         if let Some(ref callback) = self.context.progress_callback {
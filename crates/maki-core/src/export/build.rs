@@ -3,6 +3,7 @@
 //! Coordinates all exporters to generate a complete FHIR IG package.
 //! Implements SUSHI-compatible build pipeline with progress reporting.
 
+use crate::canonical::FhirRelease;
 use crate::cst::FshSyntaxNode;
 use crate::cst::TextRange;
 use crate::cst::ast::{CodeSystem, Extension, Instance, Profile, ValueSet};
@@ -11,6 +12,7 @@ use crate::export::*;
 use crate::semantic::ruleset::RuleSetExpander;
 use crate::semantic::{DefaultSemanticAnalyzer, DeferredRule};
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -25,17 +27,63 @@ struct SourceTrackedResource<T> {
     source_file: PathBuf,
     start_line: usize,
     end_line: usize,
+    /// Byte range of the entity within its source file
+    span: std::ops::Range<usize>,
 }
 
 impl<T> SourceTrackedResource<T> {
-    fn new(resource: T, source_file: PathBuf, start_line: usize, end_line: usize) -> Self {
+    fn new(
+        resource: T,
+        source_file: PathBuf,
+        start_line: usize,
+        end_line: usize,
+        span: std::ops::Range<usize>,
+    ) -> Self {
         Self {
             resource,
             source_file,
             start_line,
             end_line,
+            span,
         }
     }
+
+    /// Source span used for build diagnostics
+    fn source_span(&self) -> SourceSpan {
+        SourceSpan {
+            start_line: self.start_line,
+            end_line: self.end_line,
+            start_offset: self.span.start,
+            end_offset: self.span.end,
+        }
+    }
+}
+
+/// Warning for a rule the exporter skipped, located at the rule itself
+fn rule_failure_diagnostic(
+    failure: &RuleFailure,
+    entity: &impl crate::cst::ast::AstNode,
+    entity_name: &str,
+    file: &str,
+    entity_span: &SourceSpan,
+) -> BuildDiagnostic {
+    // Rule ranges are file offsets; the entity text starts at `entity_span`
+    let text = entity.syntax().text().to_string();
+    let start = usize::from(failure.range.start()).saturating_sub(entity_span.start_offset);
+    let end = usize::from(failure.range.end()).saturating_sub(entity_span.start_offset);
+    let local = SourceSpan::from_offsets(&text, start, end);
+    let span = SourceSpan {
+        start_line: entity_span.start_line + local.start_line - 1,
+        end_line: entity_span.start_line + local.end_line - 1,
+        start_offset: entity_span.start_offset + local.start_offset,
+        end_offset: entity_span.start_offset + local.end_offset,
+    };
+    BuildDiagnostic::warning(format!(
+        "Rule '{}' was not applied: {}",
+        failure.rule, failure.message
+    ))
+    .with_entity(entity_name)
+    .with_location(file, span)
 }
 
 /// Parsed FSH resources ready for export
@@ -100,7 +148,8 @@ pub struct BuildOptions {
     /// Show progress during build
     pub show_progress: bool,
 
-    /// FHIR version override
+    /// Build only this FHIR version (one release of a multi-release build)
+    /// Diagnostics are tagged with the release
     pub fhir_version: Option<String>,
 
     /// Configuration overrides (e.g., version, status)
@@ -125,6 +174,35 @@ pub struct BuildOptions {
     /// Default: true (enabled by default for better performance)
     /// Caches parsed files and only re-exports changed resources
     pub use_cache: bool,
+
+    /// Write `Usage: #example` instances as a transaction Bundle
+    /// Default: false (opt-in feature)
+    /// Written to fsh-generated/bundles/ for loading into test servers
+    pub example_bundle: bool,
+
+    /// Write `Usage: #example` instances as NDJSON (one file per resource type)
+    /// Default: false (opt-in feature)
+    pub example_ndjson: bool,
+
+    /// Include conformance resources (profiles, extensions, value sets,
+    /// code systems, definition instances) in the example Bundle / NDJSON
+    /// Default: false
+    pub bundle_conformance: bool,
+
+    /// Write FSH source maps (`*.fshmap.json`) next to the FSH index
+    /// Default: false (opt-in feature)
+    /// Maps JSON pointers in each output file back to the FSH rule that set them
+    pub source_maps: bool,
+
+    /// Generate `text` narratives for instances, ValueSets and CodeSystems
+    /// Default: false (opt-in feature)
+    /// Templates can be overridden in `input/narrative-templates/`
+    pub generate_narratives: bool,
+
+    /// Record per-entity export timings, canonical lookup counts and
+    /// deferred rule retries (returned in `BuildResult::profile`)
+    /// Default: false (opt-in feature)
+    pub profile_build: bool,
 }
 
 impl Default for BuildOptions {
@@ -142,11 +220,18 @@ impl Default for BuildOptions {
             strict_mode: false,     // Default OFF - warnings don't fail build
             format_on_build: false, // Default OFF - opt-in feature
             use_cache: true,        // Default ON - improves performance
+            example_bundle: false,  // Default OFF - opt-in feature
+            example_ndjson: false,  // Default OFF - opt-in feature
+            bundle_conformance: false,
+            source_maps: false,         // Default OFF - opt-in feature
+            generate_narratives: false, // Default OFF - opt-in feature
+            profile_build: false,       // Default OFF - opt-in feature
         }
     }
 }
 /// Build statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BuildStats {
     /// Number of profiles exported
     pub profiles: usize,
@@ -168,6 +253,9 @@ pub struct BuildStats {
     pub errors: usize,
     /// Number of warnings encountered
     pub warnings: usize,
+    /// Warnings and errors with their source locations
+    #[serde(skip)]
+    pub diagnostics: Vec<BuildDiagnostic>,
 }
 
 impl BuildStats {
@@ -191,6 +279,16 @@ impl BuildStats {
     pub fn has_warnings(&self) -> bool {
         self.warnings > 0
     }
+
+    /// Record a diagnostic and bump the matching error/warning counter
+    pub fn record(&mut self, diagnostic: BuildDiagnostic) {
+        if diagnostic.is_error() {
+            self.errors += 1;
+        } else {
+            self.warnings += 1;
+        }
+        self.diagnostics.push(diagnostic);
+    }
 }
 
 /// Build result
@@ -207,6 +305,33 @@ pub struct BuildResult {
 
     /// FSH index entries for generated resources
     pub fsh_index: Vec<FshIndexEntry>,
+
+    /// Time spent in each build phase
+    pub timings: Vec<PhaseTiming>,
+
+    /// Per-entity timings and lookup counts (with `profile_build`)
+    pub profile: Option<BuildProfile>,
+}
+
+/// Index the resources in `package` by output file name
+///
+/// Resources are written as `{resourceType}-{id}.json`, the `output_file` of
+/// their FSH index entry. Values hold the package key alongside the resource.
+fn resources_by_output_file(
+    package: &crate::semantic::Package,
+) -> HashMap<String, (String, Arc<JsonValue>)> {
+    package
+        .all_resources()
+        .iter()
+        .filter_map(|(key, json)| {
+            let resource_type = json.get("resourceType")?.as_str()?;
+            let id = json.get("id")?.as_str()?;
+            Some((
+                format!("{}-{}.json", resource_type, id),
+                (key.clone(), Arc::clone(json)),
+            ))
+        })
+        .collect()
 }
 
 /// Build orchestrator
@@ -226,12 +351,21 @@ pub struct BuildOrchestrator {
     options: BuildOptions,
     config: crate::config::UnifiedConfig,
     deferred_rules: Vec<DeferredRule>,
+    profiler: Option<Arc<BuildProfiler>>,
 }
 
 impl BuildOrchestrator {
     /// Create a new build orchestrator
-    pub fn new(config: crate::config::UnifiedConfig, options: BuildOptions) -> Self {
+    ///
+    /// `options.fhir_version` restricts the build to that one release.
+    pub fn new(mut config: crate::config::UnifiedConfig, options: BuildOptions) -> Self {
+        if let (Some(version), Some(build)) = (&options.fhir_version, config.build.as_mut()) {
+            build.fhir_version = vec![version.clone()];
+        }
         Self {
+            profiler: options
+                .profile_build
+                .then(|| Arc::new(BuildProfiler::new())),
             config,
             options,
             deferred_rules: Vec::new(),
@@ -248,13 +382,33 @@ impl BuildOrchestrator {
 
     /// Run the complete build pipeline with two-phase export
     pub async fn build(&self) -> std::result::Result<BuildResult, BuildError> {
+        self.build_with_timings().await.map_err(|(error, _)| error)
+    }
+
+    /// Execute the build, keeping the phase timings of a failed build
+    ///
+    /// Like [`build`](Self::build), but an error comes with the timings of the
+    /// phases that finished before it, so failed builds can be reported too.
+    pub async fn build_with_timings(
+        &self,
+    ) -> std::result::Result<BuildResult, (BuildError, Vec<PhaseTiming>)> {
+        let mut timings = Vec::new();
+        self.run_build(&mut timings)
+            .await
+            .map_err(|error| (error, timings))
+    }
+
+    async fn run_build(
+        &self,
+        timings: &mut Vec<PhaseTiming>,
+    ) -> std::result::Result<BuildResult, BuildError> {
         info!("🚀 Starting MAKI build...");
+        let mut phase_start = std::time::Instant::now();
         info!("Step 1: Initializing canonical package manager...");
 
         // Create canonical session for FHIR package resolution
         use crate::canonical::{
-            CanonicalFacade, CanonicalLoaderError, CanonicalOptions, FhirRelease,
-            create_default_maki_config,
+            CanonicalFacade, CanonicalLoaderError, CanonicalOptions, create_default_maki_config,
         };
 
         // Create optimized FcmConfig using shared helper (uses ~/.maki storage)
@@ -277,34 +431,9 @@ impl BuildOrchestrator {
 
         // Parse FHIR version from config
         info!("Step 2: Parsing FHIR versions from config...");
-        #[allow(clippy::unnecessary_filter_map)]
-        let fhir_releases: Vec<FhirRelease> = self
-            .build_config()
-            .fhir_version
-            .iter()
-            .filter_map(|v| {
-                // Parse version string like "4.0.1" to FhirRelease
-                if v.starts_with("4.0") {
-                    Some(FhirRelease::R4)
-                } else if v.starts_with("4.3") || v.starts_with("4.1") {
-                    Some(FhirRelease::R4B)
-                } else if v.starts_with("5.0") {
-                    Some(FhirRelease::R5)
-                } else if v.starts_with("6.0") {
-                    Some(FhirRelease::R6)
-                } else {
-                    warn!("Unknown FHIR version: {}, defaulting to R4", v);
-                    Some(FhirRelease::R4)
-                }
-            })
-            .collect();
-
-        let fhir_releases = if fhir_releases.is_empty() {
-            warn!("No FHIR version specified in config, defaulting to R4");
-            vec![FhirRelease::R4]
-        } else {
-            fhir_releases
-        };
+        let fhir_releases = configured_releases(&self.build_config().fhir_version);
+        // Release pragmas and RuleSet variants follow the first configured release
+        let release = fhir_releases[0];
 
         info!(
             "✓ Using FHIR version(s): {}",
@@ -419,6 +548,11 @@ impl BuildOrchestrator {
         } else {
             info!("Step 4: No dependencies found in config");
         }
+        timings.push(PhaseTiming::new(
+            BuildPhase::PackageInit,
+            phase_start.elapsed(),
+        ));
+        phase_start = std::time::Instant::now();
 
         // Create FishingContext with Tank and Package
         // This implements SUSHI's three-tier fishing pattern:
@@ -496,7 +630,7 @@ impl BuildOrchestrator {
 
         // Step 2: Parse FSH files
         info!("📝 Parsing FSH files...");
-        let parsed_files = self.parse_fsh_files(&fsh_files)?;
+        let parsed_files = self.parse_fsh_files(&fsh_files, release, &mut stats)?;
         debug!("  Parsed {} FSH files", parsed_files.len());
 
         // Update cache with parsed files
@@ -513,7 +647,7 @@ impl BuildOrchestrator {
 
         // Step 3a: Extract aliases from parsed files (needed for parent resolution)
         info!("🔗 Extracting FSH aliases...");
-        let alias_table = self.extract_aliases(&parsed_files)?;
+        let alias_table = self.extract_aliases(&parsed_files, &mut stats)?;
 
         // Recreate fishing context with alias table for profile resolution
         let fishing_ctx = Arc::new(
@@ -624,6 +758,13 @@ impl BuildOrchestrator {
         let tank_count = tank.read().await.all_resources().len();
         info!("  ✓ Added {} resources to Tank", tank_count);
 
+        // Predefined resources are registered before export so FSH can
+        // derive from, instantiate and bind to them
+        let predefined = self
+            .register_predefined_resources(session.clone(), &tank, &package, &mut stats)
+            .await;
+        info!("  ✓ Registered {} predefined resources", predefined);
+
         if self.options.show_progress {
             info!("🔄 Phase 1: Expanding RuleSets...");
         }
@@ -635,6 +776,20 @@ impl BuildOrchestrator {
             warn!("Failed to collect RuleSets: {}", e);
         }
 
+        // Per-release RuleSet variants from the `releases` config section
+        if let Some(release_config) = self.config.release(release.label()) {
+            for (name, variant) in &release_config.rulesets {
+                if !ruleset_processor.select_variant(name, variant) {
+                    stats.record(BuildDiagnostic::warning(format!(
+                        "RuleSet variant '{}' for '{}' ({}) not found",
+                        variant,
+                        name,
+                        release.label()
+                    )));
+                }
+            }
+        }
+
         // Phase 1b: Expand all InsertRule statements
         let _expanded_rules = match ruleset_processor.expand_all_inserts(&parsed_files) {
             Ok(rules) => rules,
@@ -651,6 +806,8 @@ impl BuildOrchestrator {
         );
 
         let ruleset_expander: Arc<RuleSetExpander> = Arc::new(ruleset_processor.into_expander());
+        timings.push(PhaseTiming::new(BuildPhase::Parse, phase_start.elapsed()));
+        phase_start = std::time::Instant::now();
 
         if self.options.show_progress {
             info!("📦 Phase 2: Exporting resources...");
@@ -667,6 +824,11 @@ impl BuildOrchestrator {
             alias_table, // Already a plain AliasTable
         )
         .await?;
+        timings.push(PhaseTiming::new(
+            BuildPhase::ExportProfiles,
+            phase_start.elapsed(),
+        ));
+        phase_start = std::time::Instant::now();
 
         // Step 5: Export instances
         self.export_instances(
@@ -680,6 +842,11 @@ impl BuildOrchestrator {
             &mut fsh_index,
         )
         .await?;
+        timings.push(PhaseTiming::new(
+            BuildPhase::ExportInstances,
+            phase_start.elapsed(),
+        ));
+        phase_start = std::time::Instant::now();
 
         // Step 6: Export value sets and code systems
         self.export_vocabularies(
@@ -691,14 +858,24 @@ impl BuildOrchestrator {
             &mut fsh_index,
         )
         .await?;
+        timings.push(PhaseTiming::new(
+            BuildPhase::ExportVocabularies,
+            phase_start.elapsed(),
+        ));
+        phase_start = std::time::Instant::now();
 
         if !self.deferred_rules.is_empty() {
             if self.options.show_progress {
                 info!("🔗 Phase 3: Resolving circular dependencies...");
                 info!("  Processing {} deferred rules", self.deferred_rules.len());
             }
-            self.apply_deferred_rules()?;
+            self.apply_deferred_rules(&mut stats)?;
         }
+        timings.push(PhaseTiming::new(
+            BuildPhase::DeferredRules,
+            phase_start.elapsed(),
+        ));
+        phase_start = std::time::Instant::now();
 
         // Step 7 & 8: Generate ImplementationGuide and package.json (skip if FSHOnly mode)
         let fsh_only = self.build_config().fsh_only.unwrap_or(false);
@@ -728,15 +905,68 @@ impl BuildOrchestrator {
         } else if self.options.show_progress {
             info!("📝 FSHOnly mode: Skipping ImplementationGuide and package.json");
         }
+        timings.push(PhaseTiming::new(
+            BuildPhase::IgGeneration,
+            phase_start.elapsed(),
+        ));
+        phase_start = std::time::Instant::now();
+
+        // Step 9a: Generate narratives (if enabled)
+        if self.options.generate_narratives {
+            let narrated = self
+                .write_narratives(&session, &package, &file_structure, &fsh_index)
+                .await?;
+            if self.options.show_progress {
+                info!("  ✓ {} narratives", narrated);
+            }
+        }
+
+        // Step 9b: Write example transaction Bundle / NDJSON (if enabled)
+        if self.options.example_bundle || self.options.example_ndjson {
+            let bundled = self.write_example_bundles(
+                &resources,
+                &*package.read().await,
+                &file_structure,
+                &fsh_index,
+            )?;
+            if self.options.show_progress {
+                info!("  ✓ Example bundle ({} resources)", bundled);
+            }
+        }
+
+        // Step 9c: Write FSH source maps (if enabled)
+        if self.options.source_maps {
+            let mapped = self.write_source_maps(&resources, &file_structure, &fsh_index)?;
+            if self.options.show_progress {
+                info!("  ✓ {} source maps", mapped);
+            }
+        }
 
-        // Step 9: Load predefined resources
-        self.load_predefined_resources(&file_structure, &stats)?;
+        // Step 9d: Write FHIR XML (if requested via outputFormat)
+        let output_formats = self.build_config().output_formats();
+        if output_formats.contains(&crate::config::OutputFormat::Xml) {
+            let xml_count = self
+                .write_xml_resources(
+                    &session,
+                    &file_structure,
+                    &mut fsh_index,
+                    output_formats.contains(&crate::config::OutputFormat::Json),
+                )
+                .await?;
+            if self.options.show_progress {
+                info!("  ✓ {} XML resources", xml_count);
+            }
+        }
 
         // Step 10: Write FSH index
         self.write_fsh_index(&file_structure, &fsh_index)?;
         if self.options.show_progress {
             info!("  ✓ FSH index");
         }
+        timings.push(PhaseTiming::new(
+            BuildPhase::WriteOutputs,
+            phase_start.elapsed(),
+        ));
 
         info!("✅ Build completed successfully!");
         info!("");
@@ -785,6 +1015,13 @@ impl BuildOrchestrator {
             );
         }
 
+        // Diagnostics of a single-release build are per release
+        if self.options.fhir_version.is_some() {
+            for diagnostic in &mut stats.diagnostics {
+                diagnostic.release = Some(release.label().to_string());
+            }
+        }
+
         // Save cache after successful build
         if let Some(mut cache) = cache {
             cache.mark_build_complete();
@@ -797,11 +1034,18 @@ impl BuildOrchestrator {
             }
         }
 
+        let profile = self
+            .profiler
+            .as_ref()
+            .map(|profiler| profiler.finish(timings, session.lookup_stats()));
+
         Ok(BuildResult {
             stats,
             output_dir: self.options.output_dir.clone(),
             config: self.config.clone(),
             fsh_index,
+            timings: std::mem::take(timings),
+            profile,
         })
     }
 
@@ -841,9 +1085,13 @@ impl BuildOrchestrator {
     }
 
     /// Parse all FSH files
+    ///
+    /// `// maki-if` blocks that don't apply to `release` are blanked first.
     fn parse_fsh_files(
         &self,
         files: &[PathBuf],
+        release: FhirRelease,
+        stats: &mut BuildStats,
     ) -> std::result::Result<Vec<(PathBuf, FshSyntaxNode)>, BuildError> {
         let mut parsed = Vec::new();
 
@@ -853,6 +1101,14 @@ impl BuildOrchestrator {
                     BuildError::ParseError(format!("Failed to read file {:?}: {}", file, e))
                 })?;
 
+            let (content, pragma_issues) = apply_release_pragmas(&content, release);
+            for issue in pragma_issues {
+                stats.record(BuildDiagnostic::warning(issue.message).with_location(
+                    self.relative_path_from_input(file),
+                    SourceSpan::from_offsets(&content, issue.offset, issue.offset),
+                ));
+            }
+
             let (root, lexer_errors, parse_errors) = crate::cst::parse_fsh(&content);
             let total_errors = lexer_errors.len() + parse_errors.len();
 
@@ -867,6 +1123,21 @@ impl BuildOrchestrator {
                 for (i, err) in parse_errors.iter().take(3).enumerate() {
                     debug!("  [PARSE ERROR {}] {:?}", i + 1, err);
                 }
+
+                // Parsing recovers from these, so they are reported as warnings
+                let relative_path = self.relative_path_from_input(file);
+                for err in &lexer_errors {
+                    stats.record(BuildDiagnostic::warning(&err.message).with_location(
+                        &relative_path,
+                        SourceSpan::from_offsets(&content, err.span.start, err.span.end),
+                    ));
+                }
+                for err in &parse_errors {
+                    stats.record(BuildDiagnostic::warning(&err.message).with_location(
+                        &relative_path,
+                        SourceSpan::from_line_col(&content, err.line as usize, err.col as usize),
+                    ));
+                }
             }
 
             parsed.push((file.clone(), root));
@@ -879,6 +1150,7 @@ impl BuildOrchestrator {
     fn extract_aliases(
         &self,
         parsed_files: &[(PathBuf, FshSyntaxNode)],
+        stats: &mut BuildStats,
     ) -> std::result::Result<crate::semantic::AliasTable, BuildError> {
         use crate::cst::ast::{Alias as AstAlias, AstNode};
         use crate::semantic::{Alias, AliasTable};
@@ -900,6 +1172,18 @@ impl BuildOrchestrator {
                     // Add to global table (ignoring duplicates for now, SUSHI allows them)
                     if let Err(e) = alias_table.add_alias(alias) {
                         warn!("Duplicate alias '{}' in {:?}: {}", name, file_path, e);
+                        stats.record(
+                            BuildDiagnostic::warning(format!("Duplicate alias '{}': {}", name, e))
+                                .with_entity(&name)
+                                .with_location(
+                                    self.relative_path_from_input(file_path),
+                                    SourceSpan::from_offsets(
+                                        &root.text().to_string(),
+                                        range.start().into(),
+                                        range.end().into(),
+                                    ),
+                                ),
+                        );
                         // SUSHI allows duplicate aliases, last one wins
                         // We could implement override behavior here if needed
                     } else {
@@ -942,6 +1226,7 @@ impl BuildOrchestrator {
                     file_path.clone(),
                     start_line,
                     end_line,
+                    range.start().into()..range.end().into(),
                 ));
             }
 
@@ -954,6 +1239,7 @@ impl BuildOrchestrator {
                     file_path.clone(),
                     start_line,
                     end_line,
+                    range.start().into()..range.end().into(),
                 ));
             }
 
@@ -966,6 +1252,7 @@ impl BuildOrchestrator {
                     file_path.clone(),
                     start_line,
                     end_line,
+                    range.start().into()..range.end().into(),
                 ));
             }
 
@@ -978,6 +1265,7 @@ impl BuildOrchestrator {
                     file_path.clone(),
                     start_line,
                     end_line,
+                    range.start().into()..range.end().into(),
                 ));
             }
 
@@ -990,6 +1278,7 @@ impl BuildOrchestrator {
                     file_path.clone(),
                     start_line,
                     end_line,
+                    range.start().into()..range.end().into(),
                 ));
             }
         }
@@ -1035,6 +1324,7 @@ impl BuildOrchestrator {
         profiles: &[SourceTrackedResource<Profile>],
         alias_table: &crate::semantic::AliasTable,
     ) -> crate::semantic::DependencyGraph {
+        use crate::cst::ast::AstNode;
         use crate::semantic::{DependencyGraph, DependencyType};
 
         let mut graph = DependencyGraph::new();
@@ -1079,11 +1369,12 @@ impl BuildOrchestrator {
 
                     if is_local_profile {
                         // Add edge: this profile depends on parent profile
+                        let range = parent_rule.syntax().text_range();
                         graph.add_edge(
                             &profile_name,
                             &resolved_parent,
                             DependencyType::Parent,
-                            0..0, // TODO: get actual source location
+                            range.start().into()..range.end().into(),
                         );
                     }
                 } // Close if let Some(parent_name) = parent_rule.value()
@@ -1256,7 +1547,7 @@ impl BuildOrchestrator {
             let failed_profiles_shared = StdArc::new(Mutex::new(Vec::new()));
             let fsh_index_shared = StdArc::new(Mutex::new(Vec::new()));
             let profile_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
-            let error_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+            let diagnostics_shared = StdArc::new(Mutex::new(Vec::new()));
 
             // Wrap exporter in Arc for sharing across tasks
             let profile_exporter = StdArc::new(profile_exporter);
@@ -1288,7 +1579,10 @@ impl BuildOrchestrator {
                         let failed_profiles_shared = failed_profiles_shared.clone();
                         let fsh_index_shared = fsh_index_shared.clone();
                         let profile_count = profile_count.clone();
-                        let error_count = error_count.clone();
+                        let diagnostics_shared = diagnostics_shared.clone();
+                        let profiler = self.profiler.clone();
+                        let fsh_file = self.relative_path_from_input(&tracked.source_file);
+                        let source_span = tracked.source_span();
                         let profile_pb = profile_pb_arc.clone();
                         let package = package.clone();
                         let source_file = tracked.source_file.clone();
@@ -1301,8 +1595,32 @@ impl BuildOrchestrator {
                                 profile.name().unwrap_or_else(|| "Unknown".to_string());
                             debug!("Exporting profile: {}", profile_name);
 
-                            match profile_exporter.export(&profile).await {
-                                Ok(structure_def) => {
+                            let started = std::time::Instant::now();
+                            let exported =
+                                profile_exporter.export_with_rule_failures(&profile).await;
+                            if let Some(profiler) = &profiler {
+                                profiler.record_entity(
+                                    BuildPhase::ExportProfiles,
+                                    "Profile",
+                                    &profile_name,
+                                    started,
+                                );
+                            }
+
+                            match exported {
+                                Ok((structure_def, rule_failures)) => {
+                                    diagnostics_shared.lock().await.extend(
+                                        rule_failures.iter().map(|failure| {
+                                            rule_failure_diagnostic(
+                                                failure,
+                                                &profile,
+                                                &profile_name,
+                                                &fsh_file,
+                                                &source_span,
+                                            )
+                                        }),
+                                    );
+
                                     // Use Id field for filename if present, otherwise fall back to name
                                     let profile_id = profile
                                         .id()
@@ -1323,9 +1641,12 @@ impl BuildOrchestrator {
                                         failed_profiles_shared
                                             .lock()
                                             .await
-                                            .push((profile_name.clone(), error_msg));
-                                        error_count
-                                            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                            .push((profile_name.clone(), error_msg.clone()));
+                                        diagnostics_shared.lock().await.push(
+                                            BuildDiagnostic::error(error_msg)
+                                                .with_entity(&profile_name)
+                                                .with_location(&fsh_file, source_span.clone()),
+                                        );
                                     } else {
                                         profile_count
                                             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -1372,8 +1693,12 @@ impl BuildOrchestrator {
                                     failed_profiles_shared
                                         .lock()
                                         .await
-                                        .push((profile_name.clone(), error_msg));
-                                    error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                        .push((profile_name.clone(), error_msg.clone()));
+                                    diagnostics_shared.lock().await.push(
+                                        BuildDiagnostic::error(error_msg)
+                                            .with_entity(&profile_name)
+                                            .with_location(&fsh_file, source_span.clone()),
+                                    );
                                 }
                             }
 
@@ -1420,7 +1745,9 @@ impl BuildOrchestrator {
             fsh_index.extend(profile_index_entries);
 
             stats.profiles += profile_count.load(std::sync::atomic::Ordering::SeqCst);
-            stats.errors += error_count.load(std::sync::atomic::Ordering::SeqCst);
+            for diagnostic in diagnostics_shared.lock().await.drain(..) {
+                stats.record(diagnostic);
+            }
 
             // Report failed profiles
             if !failed_profiles.is_empty() {
@@ -1449,7 +1776,7 @@ impl BuildOrchestrator {
             // Thread-safe shared state for collecting results
             let fsh_index_shared = StdArc::new(Mutex::new(Vec::new()));
             let extension_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
-            let error_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+            let diagnostics_shared = StdArc::new(Mutex::new(Vec::new()));
 
             // Wrap exporter in Arc for sharing across tasks
             let extension_exporter = StdArc::new(extension_exporter);
@@ -1471,7 +1798,10 @@ impl BuildOrchestrator {
                     let file_structure = file_structure.clone();
                     let fsh_index_shared = fsh_index_shared.clone();
                     let extension_count = extension_count.clone();
-                    let error_count = error_count.clone();
+                    let diagnostics_shared = diagnostics_shared.clone();
+                    let profiler = self.profiler.clone();
+                    let fsh_file = self.relative_path_from_input(&tracked.source_file);
+                    let source_span = tracked.source_span();
                     let extension_pb = extension_pb_arc.clone();
                     let package = package.clone();
                     let source_file = tracked.source_file.clone();
@@ -1491,8 +1821,34 @@ impl BuildOrchestrator {
                             extension_name, extension_id
                         );
 
-                        match extension_exporter.export(&extension).await {
-                            Ok(structure_def) => {
+                        let started = std::time::Instant::now();
+                        let exported = extension_exporter
+                            .export_with_rule_failures(&extension)
+                            .await;
+                        if let Some(profiler) = &profiler {
+                            profiler.record_entity(
+                                BuildPhase::ExportProfiles,
+                                "Extension",
+                                &extension_name,
+                                started,
+                            );
+                        }
+
+                        match exported {
+                            Ok((structure_def, rule_failures)) => {
+                                diagnostics_shared
+                                    .lock()
+                                    .await
+                                    .extend(rule_failures.iter().map(|failure| {
+                                        rule_failure_diagnostic(
+                                            failure,
+                                            &extension,
+                                            &extension_name,
+                                            &fsh_file,
+                                            &source_span,
+                                        )
+                                    }));
+
                                 // Write to file using Id field
                                 let filename = format!("StructureDefinition-{}.json", extension_id);
                                 if let Err(e) =
@@ -1503,7 +1859,11 @@ impl BuildOrchestrator {
                                         extension_name, e
                                     );
                                     warn!("{}", error_msg);
-                                    error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                    diagnostics_shared.lock().await.push(
+                                        BuildDiagnostic::error(error_msg)
+                                            .with_entity(&extension_name)
+                                            .with_location(&fsh_file, source_span.clone()),
+                                    );
                                 } else {
                                     extension_count
                                         .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -1540,7 +1900,11 @@ impl BuildOrchestrator {
                             }
                             Err(e) => {
                                 warn!("Failed to export extension {}: {}", extension_name, e);
-                                error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                diagnostics_shared.lock().await.push(
+                                    BuildDiagnostic::error(e.to_string())
+                                        .with_entity(&extension_name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                );
                             }
                         }
 
@@ -1575,7 +1939,9 @@ impl BuildOrchestrator {
             fsh_index.extend(extension_index_entries);
 
             stats.extensions += extension_count.load(std::sync::atomic::Ordering::SeqCst);
-            stats.errors += error_count.load(std::sync::atomic::Ordering::SeqCst);
+            for diagnostic in diagnostics_shared.lock().await.drain(..) {
+                stats.record(diagnostic);
+            }
         }
 
         Ok(())
@@ -1639,7 +2005,7 @@ impl BuildOrchestrator {
         // Note: We use shared Arc<Mutex<InstanceExporter>> for thread-safe registration
         {
             let exported_instances_shared = StdArc::new(Mutex::new(Vec::new()));
-            let error_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+            let diagnostics_shared = StdArc::new(Mutex::new(Vec::new()));
 
             // Wrap exporter in Arc<Mutex<>> because it needs mutable access for registration
             let instance_exporter = StdArc::new(Mutex::new(instance_exporter));
@@ -1663,7 +2029,10 @@ impl BuildOrchestrator {
                     let instance = tracked.resource.clone();
                     let instance_exporter = instance_exporter.clone();
                     let exported_instances_shared = exported_instances_shared.clone();
-                    let error_count = error_count.clone();
+                    let diagnostics_shared = diagnostics_shared.clone();
+                    let profiler = self.profiler.clone();
+                    let fsh_file = self.relative_path_from_input(&tracked.source_file);
+                    let source_span = tracked.source_span();
                     let source_file = tracked.source_file.clone();
                     let start_line = tracked.start_line;
                     let end_line = tracked.end_line;
@@ -1683,7 +2052,17 @@ impl BuildOrchestrator {
                         // Lock the exporter for this export operation
                         let export_result = {
                             let mut exporter = instance_exporter.lock().await;
-                            exporter.export(&instance).await
+                            let started = std::time::Instant::now();
+                            let result = exporter.export(&instance).await;
+                            if let Some(profiler) = &profiler {
+                                profiler.record_entity(
+                                    BuildPhase::ExportInstances,
+                                    "Instance",
+                                    &instance_name,
+                                    started,
+                                );
+                            }
+                            result
                         };
 
                         match export_result {
@@ -1727,7 +2106,11 @@ impl BuildOrchestrator {
                                     instance_name, e
                                 );
                                 eprintln!("Instance export failed: {} -> {}", instance_name, e);
-                                error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                diagnostics_shared.lock().await.push(
+                                    BuildDiagnostic::error(e.to_string())
+                                        .with_entity(&instance_name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                );
                             }
                         }
                     }
@@ -1750,7 +2133,10 @@ impl BuildOrchestrator {
                     let instance = tracked.resource.clone();
                     let instance_exporter = instance_exporter.clone();
                     let exported_instances_shared = exported_instances_shared.clone();
-                    let error_count = error_count.clone();
+                    let diagnostics_shared = diagnostics_shared.clone();
+                    let profiler = self.profiler.clone();
+                    let fsh_file = self.relative_path_from_input(&tracked.source_file);
+                    let source_span = tracked.source_span();
                     let source_file = tracked.source_file.clone();
                     let start_line = tracked.start_line;
                     let end_line = tracked.end_line;
@@ -1770,7 +2156,17 @@ impl BuildOrchestrator {
                         // Lock the exporter for this export operation
                         let export_result = {
                             let mut exporter = instance_exporter.lock().await;
-                            exporter.export(&instance).await
+                            let started = std::time::Instant::now();
+                            let result = exporter.export(&instance).await;
+                            if let Some(profiler) = &profiler {
+                                profiler.record_entity(
+                                    BuildPhase::ExportInstances,
+                                    "Instance",
+                                    &instance_name,
+                                    started,
+                                );
+                            }
+                            result
                         };
 
                         match export_result {
@@ -1814,7 +2210,11 @@ impl BuildOrchestrator {
                                     "Bundle instance export failed: {} -> {}",
                                     instance_name, e
                                 );
-                                error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                diagnostics_shared.lock().await.push(
+                                    BuildDiagnostic::error(e.to_string())
+                                        .with_entity(&instance_name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                );
                             }
                         }
                     }
@@ -1833,7 +2233,9 @@ impl BuildOrchestrator {
                 Err(arc) => arc.lock().await.clone(),
             };
 
-            stats.errors += error_count.load(std::sync::atomic::Ordering::SeqCst);
+            for diagnostic in diagnostics_shared.lock().await.drain(..) {
+                stats.record(diagnostic);
+            }
         }
 
         // PASS 2: Write all exported instances to files
@@ -1954,7 +2356,7 @@ impl BuildOrchestrator {
             // Thread-safe shared state
             let fsh_index_shared = StdArc::new(Mutex::new(Vec::new()));
             let valueset_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
-            let error_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+            let diagnostics_shared = StdArc::new(Mutex::new(Vec::new()));
 
             // Wrap exporter in Arc
             let valueset_exporter = StdArc::new(valueset_exporter);
@@ -1972,7 +2374,10 @@ impl BuildOrchestrator {
                     let file_structure = file_structure.clone();
                     let fsh_index_shared = fsh_index_shared.clone();
                     let valueset_count = valueset_count.clone();
-                    let error_count = error_count.clone();
+                    let diagnostics_shared = diagnostics_shared.clone();
+                    let profiler = self.profiler.clone();
+                    let fsh_file = self.relative_path_from_input(&tracked.source_file);
+                    let source_span = tracked.source_span();
                     let valueset_pb = valueset_pb_arc.clone();
                     let package = package.clone();
                     let source_file = tracked.source_file.clone();
@@ -1984,7 +2389,18 @@ impl BuildOrchestrator {
                         let name = valueset.name().unwrap_or_else(|| "Unknown".to_string());
                         debug!("Exporting ValueSet: {}", name);
 
-                        match valueset_exporter.export(&valueset).await {
+                        let started = std::time::Instant::now();
+                        let exported = valueset_exporter.export(&valueset).await;
+                        if let Some(profiler) = &profiler {
+                            profiler.record_entity(
+                                BuildPhase::ExportVocabularies,
+                                "ValueSet",
+                                &name,
+                                started,
+                            );
+                        }
+
+                        match exported {
                             Ok(resource_json) => {
                                 let vs_id = valueset
                                     .id()
@@ -1996,7 +2412,14 @@ impl BuildOrchestrator {
                                     file_structure.write_resource(&filename, &resource_json)
                                 {
                                     warn!("Failed to write ValueSet {}: {}", name, e);
-                                    error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                    diagnostics_shared.lock().await.push(
+                                        BuildDiagnostic::error(format!(
+                                            "Failed to write ValueSet {}: {}",
+                                            name, e
+                                        ))
+                                        .with_entity(&name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                    );
                                 } else {
                                     valueset_count
                                         .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -2032,7 +2455,11 @@ impl BuildOrchestrator {
                             }
                             Err(e) => {
                                 warn!("Failed to export ValueSet {}: {}", name, e);
-                                error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                diagnostics_shared.lock().await.push(
+                                    BuildDiagnostic::error(e.to_string())
+                                        .with_entity(&name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                );
                             }
                         }
 
@@ -2064,7 +2491,9 @@ impl BuildOrchestrator {
             fsh_index.extend(valueset_index_entries);
 
             stats.value_sets += valueset_count.load(std::sync::atomic::Ordering::SeqCst);
-            stats.errors += error_count.load(std::sync::atomic::Ordering::SeqCst);
+            for diagnostic in diagnostics_shared.lock().await.drain(..) {
+                stats.record(diagnostic);
+            }
         }
 
         // Export codesystems in parallel using async concurrency
@@ -2085,7 +2514,7 @@ impl BuildOrchestrator {
             // Thread-safe shared state
             let fsh_index_shared = StdArc::new(Mutex::new(Vec::new()));
             let codesystem_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
-            let error_count = StdArc::new(std::sync::atomic::AtomicUsize::new(0));
+            let diagnostics_shared = StdArc::new(Mutex::new(Vec::new()));
 
             // Wrap exporter in Arc
             let codesystem_exporter = StdArc::new(codesystem_exporter);
@@ -2103,7 +2532,10 @@ impl BuildOrchestrator {
                     let file_structure = file_structure.clone();
                     let fsh_index_shared = fsh_index_shared.clone();
                     let codesystem_count = codesystem_count.clone();
-                    let error_count = error_count.clone();
+                    let diagnostics_shared = diagnostics_shared.clone();
+                    let profiler = self.profiler.clone();
+                    let fsh_file = self.relative_path_from_input(&tracked.source_file);
+                    let source_span = tracked.source_span();
                     let codesystem_pb = codesystem_pb_arc.clone();
                     let package = package.clone();
                     let source_file = tracked.source_file.clone();
@@ -2115,7 +2547,18 @@ impl BuildOrchestrator {
                         let name = codesystem.name().unwrap_or_else(|| "Unknown".to_string());
                         debug!("Exporting CodeSystem: {}", name);
 
-                        match codesystem_exporter.export(&codesystem).await {
+                        let started = std::time::Instant::now();
+                        let exported = codesystem_exporter.export(&codesystem).await;
+                        if let Some(profiler) = &profiler {
+                            profiler.record_entity(
+                                BuildPhase::ExportVocabularies,
+                                "CodeSystem",
+                                &name,
+                                started,
+                            );
+                        }
+
+                        match exported {
                             Ok(resource_json) => {
                                 let cs_id = codesystem
                                     .id()
@@ -2127,7 +2570,14 @@ impl BuildOrchestrator {
                                     file_structure.write_resource(&filename, &resource_json)
                                 {
                                     warn!("Failed to write CodeSystem {}: {}", name, e);
-                                    error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                    diagnostics_shared.lock().await.push(
+                                        BuildDiagnostic::error(format!(
+                                            "Failed to write CodeSystem {}: {}",
+                                            name, e
+                                        ))
+                                        .with_entity(&name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                    );
                                 } else {
                                     codesystem_count
                                         .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -2163,7 +2613,11 @@ impl BuildOrchestrator {
                             }
                             Err(e) => {
                                 warn!("Failed to export CodeSystem {}: {}", name, e);
-                                error_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                diagnostics_shared.lock().await.push(
+                                    BuildDiagnostic::error(e.to_string())
+                                        .with_entity(&name)
+                                        .with_location(&fsh_file, source_span.clone()),
+                                );
                             }
                         }
 
@@ -2195,14 +2649,16 @@ impl BuildOrchestrator {
             fsh_index.extend(codesystem_index_entries);
 
             stats.code_systems += codesystem_count.load(std::sync::atomic::Ordering::SeqCst);
-            stats.errors += error_count.load(std::sync::atomic::Ordering::SeqCst);
+            for diagnostic in diagnostics_shared.lock().await.drain(..) {
+                stats.record(diagnostic);
+            }
         }
 
         Ok(())
     }
 
     /// Apply deferred rules (Phase 3: circular dependency resolution)
-    fn apply_deferred_rules(&self) -> std::result::Result<(), BuildError> {
+    fn apply_deferred_rules(&self, stats: &mut BuildStats) -> std::result::Result<(), BuildError> {
         if self.deferred_rules.is_empty() {
             return Ok(());
         }
@@ -2221,7 +2677,11 @@ impl BuildOrchestrator {
             );
 
             // Try to apply the rule
-            match self.retry_deferred_rule(deferred_rule) {
+            let result = self.retry_deferred_rule(deferred_rule);
+            if let Some(profiler) = &self.profiler {
+                profiler.record_deferred_retry(&deferred_rule.entity_id, result.is_ok());
+            }
+            match result {
                 Ok(()) => {
                     applied_count += 1;
                     trace!(
@@ -2255,6 +2715,20 @@ impl BuildOrchestrator {
                     "  Failed rule: entity={}, reason={:?}, error={}",
                     rule.entity_id, rule.reason, reason
                 );
+                let location = &rule.location;
+                stats.record(
+                    BuildDiagnostic::warning(format!("Unresolved deferred rule: {}", reason))
+                        .with_entity(&rule.entity_id)
+                        .with_location(
+                            self.relative_path_from_input(&location.file),
+                            SourceSpan {
+                                start_line: location.line,
+                                end_line: location.end_line.unwrap_or(location.line),
+                                start_offset: location.offset,
+                                end_offset: location.offset + location.length,
+                            },
+                        ),
+                );
             }
 
             // Return warnings but don't fail the build
@@ -2395,17 +2869,21 @@ impl BuildOrchestrator {
         Ok(())
     }
 
-    /// Load predefined resources
-    fn load_predefined_resources(
+    /// Load predefined resources and register them in the Package
+    ///
+    /// Invalid files, missing ids/urls, file names that don't match the id and
+    /// duplicate URLs are reported. Resources FSH also defines are reported
+    /// and left out, since the FSH definition wins. StructureDefinitions
+    /// without a snapshot get one so profiles can derive from them. Returns
+    /// the number of registered resources.
+    async fn register_predefined_resources(
         &self,
-        _file_structure: &FileStructureGenerator,
-        _stats: &BuildStats,
-    ) -> std::result::Result<(), BuildError> {
-        // Build map of generated resources for conflict detection
-        let generated_resources = HashMap::new();
-
-        // TODO: Populate from actual exports
-        // For now, just check predefined resources
+        session: Arc<crate::canonical::DefinitionSession>,
+        tank: &Arc<tokio::sync::RwLock<crate::semantic::FshTank>>,
+        package: &Arc<tokio::sync::RwLock<crate::semantic::Package>>,
+        stats: &mut BuildStats,
+    ) -> usize {
+        use crate::export::predefined_resources::{path_resource_parameters, split_conflicts};
 
         let input_parent = self
             .options
@@ -2413,19 +2891,396 @@ impl BuildOrchestrator {
             .parent()
             .unwrap_or(&self.options.input_dir);
         let project_dir = input_parent.parent().unwrap_or(input_parent);
-        let loader = PredefinedResourcesLoader::new(input_parent, project_dir, vec![]);
+        let custom_paths = path_resource_parameters(self.build_config().parameters.as_ref());
+        let loader = PredefinedResourcesLoader::new(input_parent, project_dir, custom_paths);
+
+        let (resources, issues) = loader.load_checked();
+        for issue in &issues {
+            warn!("{}", issue.description());
+            let diagnostic = if issue.is_error() {
+                BuildDiagnostic::error(issue.description())
+            } else {
+                BuildDiagnostic::warning(issue.description())
+            };
+            stats.record(diagnostic.with_file(issue.file_path().display().to_string()));
+        }
+
+        // Conflict keys of the FSH definitions, as the generated resources will have them
+        let generated_resources: HashMap<String, GeneratedResourceInfo> = {
+            let tank = tank.read().await;
+            tank.all_resources()
+                .into_iter()
+                .filter_map(|resource| {
+                    let url = tank.canonical_url(resource)?;
+                    let resource_type = resource.resource_type.as_str();
+                    let info = GeneratedResourceInfo {
+                        resource_type: resource_type.to_string(),
+                        url: Some(url),
+                        id: Some(resource.id.clone()),
+                        filename: format!("{}-{}.json", resource_type, resource.id),
+                    };
+                    Some((info.conflict_key(), info))
+                })
+                .collect()
+        };
+        let (resources, conflicts) = split_conflicts(resources, &generated_resources);
+        for conflict in &conflicts {
+            warn!("{}", conflict.description());
+            stats.record(
+                BuildDiagnostic::warning(conflict.description())
+                    .with_file(conflict.resource.file_path.display().to_string()),
+            );
+        }
+
+        let mut jsons: Vec<JsonValue> = resources.into_iter().map(|r| r.json).collect();
+        let snapshots = crate::export::compat::fill_snapshots(session, &mut jsons).await;
+        if snapshots > 0 {
+            debug!("Generated {} snapshots for predefined profiles", snapshots);
+        }
+
+        let mut package = package.write().await;
+        let count = jsons.len();
+        for json in jsons {
+            let key = match json.get("url").and_then(|v| v.as_str()) {
+                Some(url) => url.to_string(),
+                None => format!(
+                    "{}/{}/{}",
+                    self.build_config().canonical,
+                    json.get("resourceType")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default(),
+                    json.get("id").and_then(|v| v.as_str()).unwrap_or_default()
+                ),
+            };
+            package.add_resource(key, json);
+        }
+        count
+    }
+
+    /// Add generated narratives to exported instances, ValueSets and CodeSystems
+    ///
+    /// Exported resources are taken from the package, given a `text` element
+    /// unless they already have one, and rewritten in both the output and the
+    /// package. Displays for codes are looked up in the exported CodeSystems.
+    /// Returns the number of resources changed.
+    async fn write_narratives(
+        &self,
+        session: &crate::canonical::DefinitionSession,
+        package: &Arc<tokio::sync::RwLock<crate::semantic::Package>>,
+        file_structure: &FileStructureGenerator,
+        fsh_index: &[FshIndexEntry],
+    ) -> std::result::Result<usize, BuildError> {
+        use crate::canonical::codesystem::{CodeSystem, CodeSystemValidator};
+        use crate::export::narrative::{NARRATIVE_TEMPLATE_DIR, NarrativeGenerator};
+        use crate::export::xml_serializer::{ElementOrderIndex, collect_resource_types};
+        use std::collections::HashSet;
+
+        let mut package = package.write().await;
+        let exported = resources_by_output_file(&package);
+        let mut resources = Vec::new();
+        let mut resource_types = HashSet::new();
+        let mut code_systems = CodeSystemValidator::new();
+        for entry in fsh_index {
+            // Instances are indexed by their InstanceOf type
+            if matches!(
+                entry.fsh_type.as_str(),
+                "Profile" | "Extension" | "Logical" | "Resource"
+            ) {
+                continue;
+            }
+
+            let Some((key, json)) = exported.get(&entry.output_file) else {
+                debug!("No exported resource for {}", entry.output_file);
+                continue;
+            };
+            let json = JsonValue::clone(json);
+            if json.get("resourceType").and_then(JsonValue::as_str) == Some("CodeSystem")
+                && let Ok(cs) = CodeSystem::from_fhir_json(&json)
+            {
+                code_systems.load_code_system(cs);
+            }
+            collect_resource_types(&json, &mut resource_types);
+            resources.push((entry.output_file.as_str(), key.clone(), json));
+        }
 
-        let (predefined, conflicts) = loader.load_with_conflict_check(&generated_resources)?;
+        let order_index = ElementOrderIndex::load(session, resource_types).await;
+        let mut generator = NarrativeGenerator::new(&order_index, &code_systems);
 
-        if !conflicts.is_empty() {
-            warn!("Found {} resource conflicts:", conflicts.len());
-            for conflict in &conflicts {
-                warn!("  Conflict: {}", conflict.description());
+        let input_parent = self
+            .options
+            .input_dir
+            .parent()
+            .unwrap_or(&self.options.input_dir);
+        let template_dir = input_parent.join(NARRATIVE_TEMPLATE_DIR);
+        if template_dir.is_dir() {
+            let loaded =
+                crate::export::run_blocking_io(|| generator.load_templates(&template_dir))?;
+            debug!(
+                "Loaded {} narrative templates from {:?}",
+                loaded, template_dir
+            );
+        }
+
+        let mut written = 0;
+        for (output_file, key, mut json) in resources {
+            if generator.apply(&mut json) {
+                file_structure.write_resource(output_file, &json)?;
+                package.add_resource(key, json);
+                written += 1;
             }
         }
 
-        info!("Loaded {} predefined resources", predefined.len());
-        Ok(())
+        debug!("Generated {} narratives", written);
+        Ok(written)
+    }
+
+    /// Write a `*.fshmap.json` source map for every exported FSH entity
+    ///
+    /// Rule paths are resolved against the written JSON, so pointers match the
+    /// output exactly. Returns the number of source maps written.
+    fn write_source_maps(
+        &self,
+        resources: &ParsedResources,
+        file_structure: &FileStructureGenerator,
+        fsh_index: &[FshIndexEntry],
+    ) -> std::result::Result<usize, BuildError> {
+        use crate::cst::ast::AstNode;
+        use crate::export::source_map::{SourceMapBuilder, entity_rules};
+
+        // (type, name) -> entity node
+        let mut entities: HashMap<(&str, String), FshSyntaxNode> = HashMap::new();
+        let mut add = |fsh_type, name: Option<String>, node: &FshSyntaxNode| {
+            if let Some(name) = name {
+                entities.insert((fsh_type, name), node.clone());
+            }
+        };
+        for tracked in &resources.profiles {
+            add(
+                "Profile",
+                tracked.resource.name(),
+                tracked.resource.syntax(),
+            );
+        }
+        for tracked in &resources.extensions {
+            add(
+                "Extension",
+                tracked.resource.name(),
+                tracked.resource.syntax(),
+            );
+        }
+        for tracked in &resources.valuesets {
+            add(
+                "ValueSet",
+                tracked.resource.name(),
+                tracked.resource.syntax(),
+            );
+        }
+        for tracked in &resources.codesystems {
+            add(
+                "CodeSystem",
+                tracked.resource.name(),
+                tracked.resource.syntax(),
+            );
+        }
+        for tracked in &resources.instances {
+            add(
+                "Instance",
+                tracked.resource.name(),
+                tracked.resource.syntax(),
+            );
+        }
+
+        let mut written = 0;
+        for entry in fsh_index {
+            let Some(entity) = entities.get(&(entry.fsh_type.as_str(), entry.fsh_name.clone()))
+            else {
+                continue;
+            };
+
+            let path = file_structure.resources_dir().join(&entry.output_file);
+            let content = crate::export::run_blocking_io(|| std::fs::read_to_string(&path))?;
+            let json = match serde_json::from_str::<JsonValue>(&content) {
+                Ok(json) => json,
+                Err(e) => {
+                    warn!("Skipping source map for {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            // Offsets are relative to the whole file, so map against the document text
+            let source = entity
+                .ancestors()
+                .last()
+                .map(|root| root.text().to_string())
+                .unwrap_or_default();
+            let mut builder = SourceMapBuilder::new(
+                &entry.output_file,
+                &entry.fsh_name,
+                &entry.fsh_type,
+                &entry.fsh_file,
+                &source,
+                &json,
+            );
+            builder.add_entity(entity);
+            builder.add_rules(entity_rules(entity));
+
+            file_structure.write_source_map(
+                &FshSourceMap::sidecar_filename(&entry.output_file),
+                &builder.finish(),
+            )?;
+            written += 1;
+        }
+
+        debug!("Wrote {} FSH source maps", written);
+        Ok(written)
+    }
+
+    /// Write example instances as a transaction Bundle and/or NDJSON
+    ///
+    /// Includes every `Usage: #example` instance (the default usage) and, when
+    /// `bundle_conformance` is set, all conformance resources and
+    /// `Usage: #definition` instances. Resources are taken from the package,
+    /// so they match the exported JSON including generated narratives.
+    fn write_example_bundles(
+        &self,
+        resources: &ParsedResources,
+        package: &crate::semantic::Package,
+        file_structure: &FileStructureGenerator,
+        fsh_index: &[FshIndexEntry],
+    ) -> std::result::Result<usize, BuildError> {
+        let instance_usage: HashMap<String, String> = resources
+            .instances
+            .iter()
+            .filter_map(|tracked| {
+                let name = tracked.resource.name()?;
+                let usage = tracked
+                    .resource
+                    .usage()
+                    .and_then(|u| u.value())
+                    .unwrap_or_else(|| "example".to_string());
+                Some((name, usage))
+            })
+            .collect();
+
+        let exported = resources_by_output_file(package);
+        let mut generator = ExampleBundleGenerator::new(self.build_config().canonical.clone());
+        for entry in fsh_index {
+            let include = match instance_usage.get(&entry.fsh_name) {
+                Some(usage) if usage == "example" => true,
+                Some(usage) if usage == "definition" => self.options.bundle_conformance,
+                Some(_) => false,
+                None => self.options.bundle_conformance,
+            };
+            if !include {
+                continue;
+            }
+
+            match exported.get(&entry.output_file) {
+                Some((_, json)) => generator.add_resource(JsonValue::clone(json)),
+                None => warn!(
+                    "Skipping {} in example bundle: resource not exported",
+                    entry.output_file
+                ),
+            }
+        }
+
+        if generator.is_empty() {
+            debug!("No example resources to bundle");
+            return Ok(0);
+        }
+
+        if self.options.example_bundle {
+            let bundle_id = format!(
+                "{}-examples",
+                self.build_config()
+                    .id
+                    .as_deref()
+                    .or_else(|| self.build_config().package_id())
+                    .unwrap_or("ig")
+            );
+            let bundle = generator.transaction_bundle(&bundle_id);
+            file_structure.write_bundle("examples-transaction.json", &bundle)?;
+        }
+
+        if self.options.example_ndjson {
+            for (resource_type, content) in generator.ndjson_by_type() {
+                file_structure.write_ndjson(&format!("{}.ndjson", resource_type), &content)?;
+            }
+        }
+
+        debug!("Bundled {} example resources", generator.len());
+        Ok(generator.len())
+    }
+
+    /// Serialize every exported resource to FHIR XML
+    ///
+    /// Element order is taken from the base StructureDefinitions in the session.
+    /// When JSON output is not requested, the JSON files are removed and the
+    /// FSH index is updated to point at the XML files.
+    async fn write_xml_resources(
+        &self,
+        session: &crate::canonical::DefinitionSession,
+        file_structure: &FileStructureGenerator,
+        fsh_index: &mut [FshIndexEntry],
+        keep_json: bool,
+    ) -> std::result::Result<usize, BuildError> {
+        use crate::export::xml_serializer::{
+            ElementOrderIndex, FhirXmlSerializer, collect_resource_types,
+        };
+        use std::collections::HashSet;
+
+        let resources_dir = file_structure.resources_dir();
+        let json_files: Vec<PathBuf> = crate::export::run_blocking_io(|| {
+            std::fs::read_dir(&resources_dir).map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+                    .collect()
+            })
+        })?;
+
+        let mut resources = Vec::with_capacity(json_files.len());
+        let mut resource_types = HashSet::new();
+        for path in json_files {
+            let content = crate::export::run_blocking_io(|| std::fs::read_to_string(&path))?;
+            match serde_json::from_str::<JsonValue>(&content) {
+                Ok(json) => {
+                    collect_resource_types(&json, &mut resource_types);
+                    resources.push((path, json));
+                }
+                Err(e) => warn!("Skipping XML output for {:?}: {}", path, e),
+            }
+        }
+
+        let order_index = ElementOrderIndex::load(session, resource_types).await;
+        let serializer = FhirXmlSerializer::new(&order_index);
+
+        let mut written = 0;
+        for (path, json) in &resources {
+            let xml = serializer.serialize(json).map_err(|e| {
+                BuildError::ExportError(format!("Failed to serialize {:?} to XML: {}", path, e))
+            })?;
+
+            let json_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let xml_name = format!("{}.xml", json_name.trim_end_matches(".json"));
+            file_structure.write_resource_xml(&xml_name, &xml)?;
+            written += 1;
+
+            if !keep_json {
+                crate::export::run_blocking_io(|| std::fs::remove_file(path))?;
+                for entry in fsh_index.iter_mut().filter(|e| e.output_file == json_name) {
+                    entry.output_file = xml_name.clone();
+                }
+            }
+        }
+
+        debug!("Generated {} XML resources", written);
+        Ok(written)
     }
 
     /// Write FSH index
@@ -2591,11 +3446,46 @@ mod tests {
 
         let orchestrator = BuildOrchestrator::new(config, options);
         let files = orchestrator.discover_fsh_files().unwrap();
-        let parsed = orchestrator.parse_fsh_files(&files).unwrap();
+        let parsed = orchestrator
+            .parse_fsh_files(&files, FhirRelease::R4, &mut BuildStats::default())
+            .unwrap();
 
         assert_eq!(parsed.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_parse_fsh_files_for_release() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("test.fsh"),
+            "Profile: TestProfile\nParent: Encounter\n// maki-if R5\n* class 1..*\n// maki-else\n* class 1..1\n// maki-endif\n// maki-endif\n",
+        )
+        .unwrap();
+
+        let mut config = create_test_config();
+        config.build.as_mut().unwrap().fhir_version = vec!["4.0.1".into(), "5.0.0".into()];
+        let options = BuildOptions {
+            input_dir: temp_dir.path().to_path_buf(),
+            fhir_version: Some("5.0.0".to_string()),
+            ..Default::default()
+        };
+
+        let orchestrator = BuildOrchestrator::new(config, options);
+        assert_eq!(orchestrator.build_config().fhir_version, vec!["5.0.0"]);
+
+        let files = orchestrator.discover_fsh_files().unwrap();
+        let mut stats = BuildStats::default();
+        let parsed = orchestrator
+            .parse_fsh_files(&files, FhirRelease::R5, &mut stats)
+            .unwrap();
+
+        let text = parsed[0].1.text().to_string();
+        assert!(text.contains("* class 1..*"));
+        assert!(!text.contains("1..1"));
+        assert_eq!(stats.diagnostics.len(), 1);
+        assert_eq!(stats.diagnostics[0].span.as_ref().unwrap().start_line, 8);
+    }
+
     #[tokio::test]
     async fn test_generate_implementation_guide() {
         let temp_dir = TempDir::new().unwrap();
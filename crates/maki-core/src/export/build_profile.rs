@@ -0,0 +1,357 @@
+//! Build profiling (`maki build --profile-build`)
+//!
+//! Records where a build spends its time:
+//!
+//! - Export time per entity (profiles, extensions, instances, value sets, code systems)
+//! - Canonical lookups per [`DefinitionSession`](crate::canonical::DefinitionSession)
+//!   lookup kind, with cache hit rates
+//! - Deferred rule retries
+//!
+//! The result is printed as a table sorted by cost and written as a Chrome
+//! trace (`chrome://tracing`, Perfetto) with one lane per concurrently
+//! running export.
+
+use crate::canonical::LookupStats;
+use crate::export::build_report::{BuildPhase, PhaseTiming};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Export time of a single FSH entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityTiming {
+    pub phase: BuildPhase,
+    /// FSH entity kind (`Profile`, `Instance`, ...)
+    pub kind: String,
+    pub name: String,
+    /// Start, relative to the start of the build
+    pub start_ms: f64,
+    pub duration_ms: f64,
+}
+
+/// Outcome of retrying deferred rules for one entity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeferredRetries {
+    pub entity: String,
+    pub retries: usize,
+    pub failed: usize,
+}
+
+/// Collects timings while a build runs
+///
+/// Shared between concurrent export tasks.
+#[derive(Debug)]
+pub struct BuildProfiler {
+    origin: Instant,
+    entities: Mutex<Vec<EntityTiming>>,
+    deferred: Mutex<BTreeMap<String, DeferredRetries>>,
+}
+
+impl BuildProfiler {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            entities: Mutex::new(Vec::new()),
+            deferred: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record an entity export that began at `started` and just finished
+    pub fn record_entity(&self, phase: BuildPhase, kind: &str, name: &str, started: Instant) {
+        let timing = EntityTiming {
+            phase,
+            kind: kind.to_string(),
+            name: name.to_string(),
+            start_ms: millis(started.saturating_duration_since(self.origin)),
+            duration_ms: millis(started.elapsed()),
+        };
+        self.entities
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(timing);
+    }
+
+    /// Record one deferred rule retry for `entity`
+    pub fn record_deferred_retry(&self, entity: &str, applied: bool) {
+        let mut deferred = self.deferred.lock().unwrap_or_else(|e| e.into_inner());
+        let retries = deferred
+            .entry(entity.to_string())
+            .or_insert_with(|| DeferredRetries {
+                entity: entity.to_string(),
+                ..Default::default()
+            });
+        retries.retries += 1;
+        if !applied {
+            retries.failed += 1;
+        }
+    }
+
+    /// Combine the recorded timings with the phase timings and lookup counts
+    pub fn finish(&self, phases: &[PhaseTiming], lookups: Vec<LookupStats>) -> BuildProfile {
+        let mut entities = self
+            .entities
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        entities.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+        let deferred = self
+            .deferred
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        BuildProfile {
+            phases: phases.to_vec(),
+            entities,
+            lookups,
+            deferred,
+        }
+    }
+}
+
+impl Default for BuildProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Profile of a finished build
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildProfile {
+    pub phases: Vec<PhaseTiming>,
+    /// Entity export timings, most expensive first
+    pub entities: Vec<EntityTiming>,
+    pub lookups: Vec<LookupStats>,
+    pub deferred: Vec<DeferredRetries>,
+}
+
+impl BuildProfile {
+    /// Plain-text report with the `limit` most expensive entities
+    pub fn table(&self, limit: usize) -> String {
+        let mut out = String::new();
+        let name_width = self
+            .entities
+            .iter()
+            .take(limit)
+            .map(|e| e.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Entity".len());
+
+        let _ = writeln!(out, "{:<20} {:>10}", "Phase", "Time (ms)");
+        for phase in &self.phases {
+            let _ = writeln!(
+                out,
+                "{:<20} {:>10.1}",
+                phase.phase.label(),
+                phase.duration_ms
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "\n{:<name_width$}  {:<10} {:<20} {:>10}",
+            "Entity", "Kind", "Phase", "Time (ms)"
+        );
+        for entity in self.entities.iter().take(limit) {
+            let _ = writeln!(
+                out,
+                "{:<name_width$}  {:<10} {:<20} {:>10.1}",
+                entity.name,
+                entity.kind,
+                entity.phase.label(),
+                entity.duration_ms
+            );
+        }
+        if self.entities.len() > limit {
+            let _ = writeln!(out, "... {} more", self.entities.len() - limit);
+        }
+
+        let _ = writeln!(
+            out,
+            "\n{:<14} {:>8} {:>10} {:>8} {:>9}",
+            "Lookup", "Count", "Cache hits", "Misses", "Hit rate"
+        );
+        for lookup in &self.lookups {
+            let _ = writeln!(
+                out,
+                "{:<14} {:>8} {:>10} {:>8} {:>8.1}%",
+                lookup.kind.label(),
+                lookup.lookups,
+                lookup.cache_hits,
+                lookup.misses,
+                lookup.hit_rate() * 100.0
+            );
+        }
+
+        if !self.deferred.is_empty() {
+            let retries: usize = self.deferred.iter().map(|d| d.retries).sum();
+            let failed: usize = self.deferred.iter().map(|d| d.failed).sum();
+            let _ = writeln!(
+                out,
+                "\nDeferred rules: {} retries across {} entities, {} unresolved",
+                retries,
+                self.deferred.len(),
+                failed
+            );
+        }
+        out
+    }
+
+    /// Chrome trace event JSON
+    ///
+    /// Phases are on lane 0; overlapping entity exports are spread over
+    /// lanes 1.. so concurrent work stays readable.
+    pub fn chrome_trace(&self) -> JsonValue {
+        let mut events = Vec::new();
+
+        let mut phase_start = 0.0;
+        for phase in &self.phases {
+            events.push(json!({
+                "name": phase.phase.label(),
+                "cat": "phase",
+                "ph": "X",
+                "ts": micros(phase_start),
+                "dur": micros(phase.duration_ms),
+                "pid": 1,
+                "tid": 0,
+            }));
+            phase_start += phase.duration_ms;
+        }
+
+        let mut entities: Vec<&EntityTiming> = self.entities.iter().collect();
+        entities.sort_by(|a, b| a.start_ms.total_cmp(&b.start_ms));
+        let mut lane_ends: Vec<f64> = Vec::new();
+        for entity in entities {
+            let end = entity.start_ms + entity.duration_ms;
+            let lane = match lane_ends
+                .iter()
+                .position(|&lane_end| lane_end <= entity.start_ms)
+            {
+                Some(lane) => {
+                    lane_ends[lane] = end;
+                    lane
+                }
+                None => {
+                    lane_ends.push(end);
+                    lane_ends.len() - 1
+                }
+            };
+            events.push(json!({
+                "name": entity.name,
+                "cat": entity.kind,
+                "ph": "X",
+                "ts": micros(entity.start_ms),
+                "dur": micros(entity.duration_ms),
+                "pid": 1,
+                "tid": lane + 1,
+                "args": { "phase": entity.phase.label() },
+            }));
+        }
+
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+
+    /// Write [`Self::chrome_trace`] to `path`
+    pub fn write_chrome_trace(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string(&self.chrome_trace()).map_err(std::io::Error::other)?;
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, json)
+    }
+}
+
+fn millis(duration: std::time::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn micros(ms: f64) -> f64 {
+    (ms * 1000.0).round()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonical::LookupKind;
+
+    fn entity(name: &str, start_ms: f64, duration_ms: f64) -> EntityTiming {
+        EntityTiming {
+            phase: BuildPhase::ExportProfiles,
+            kind: "Profile".to_string(),
+            name: name.to_string(),
+            start_ms,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn test_profiler_records_and_sorts() {
+        let profiler = BuildProfiler::new();
+        profiler.record_entity(
+            BuildPhase::ExportInstances,
+            "Instance",
+            "Ex",
+            Instant::now(),
+        );
+        profiler.record_deferred_retry("A", true);
+        profiler.record_deferred_retry("A", false);
+
+        let lookups = vec![LookupStats {
+            kind: LookupKind::Canonical,
+            lookups: 4,
+            cache_hits: 3,
+            misses: 1,
+        }];
+        let profile = profiler.finish(&[], lookups);
+        assert_eq!(profile.entities.len(), 1);
+        assert_eq!(profile.deferred[0].retries, 2);
+        assert_eq!(profile.deferred[0].failed, 1);
+
+        let table = profile.table(10);
+        assert!(table.contains("Ex"));
+        assert!(table.contains("75.0%"));
+        assert!(table.contains("2 retries across 1 entities, 1 unresolved"));
+    }
+
+    #[test]
+    fn test_chrome_trace_lanes() {
+        let profile = BuildProfile {
+            phases: vec![PhaseTiming {
+                phase: BuildPhase::ExportProfiles,
+                duration_ms: 30.0,
+            }],
+            entities: vec![
+                entity("A", 0.0, 10.0),
+                entity("B", 5.0, 10.0),
+                entity("C", 12.0, 1.0),
+            ],
+            lookups: Vec::new(),
+            deferred: Vec::new(),
+        };
+
+        let trace = profile.chrome_trace();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 4);
+        let lane = |name: &str| {
+            events.iter().find(|e| e["name"] == name).unwrap()["tid"]
+                .as_u64()
+                .unwrap()
+        };
+        // B overlaps A; C starts after A ended and reuses its lane
+        assert_eq!(lane("A"), 1);
+        assert_eq!(lane("B"), 2);
+        assert_eq!(lane("C"), 1);
+        assert_eq!(events[0]["dur"], 30000.0);
+    }
+}
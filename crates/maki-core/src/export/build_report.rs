@@ -0,0 +1,375 @@
+//! Machine-readable build report
+//!
+//! Captures what happened during a build in a form dashboards and PR bots can
+//! consume (`maki build --report report.json`):
+//!
+//! - Per-phase timings (package init, parse, exports, deferred rules, IG generation)
+//! - Provenance for every output file (source FSH file and line range)
+//! - Every build warning and error with its source location
+//!
+//! A build that stops with an error is reported with that error and the
+//! timings of the phases that finished.
+
+use crate::export::FshIndexEntry;
+use crate::export::build::{BuildError, BuildResult, BuildStats};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// Build pipeline phase
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildPhase {
+    /// Canonical package manager, session and dependency installation
+    PackageInit,
+    /// FSH discovery, parsing, alias/resource extraction and Tank population
+    Parse,
+    /// Profile and extension export
+    ExportProfiles,
+    /// Instance export
+    ExportInstances,
+    /// ValueSet and CodeSystem export
+    ExportVocabularies,
+    /// Deferred rule resolution
+    DeferredRules,
+    /// ImplementationGuide, menu.xml and package.json generation
+    IgGeneration,
+    /// Predefined resources, bundles, XML and FSH index output
+    WriteOutputs,
+}
+
+impl BuildPhase {
+    /// Human-readable phase name
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PackageInit => "Package init",
+            Self::Parse => "Parse",
+            Self::ExportProfiles => "Export profiles",
+            Self::ExportInstances => "Export instances",
+            Self::ExportVocabularies => "Export vocabularies",
+            Self::DeferredRules => "Deferred rules",
+            Self::IgGeneration => "IG generation",
+            Self::WriteOutputs => "Write outputs",
+        }
+    }
+}
+
+/// Time spent in a build phase
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: BuildPhase,
+    pub duration_ms: f64,
+}
+
+impl PhaseTiming {
+    pub fn new(phase: BuildPhase, duration: Duration) -> Self {
+        Self {
+            phase,
+            duration_ms: duration.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Severity of a build diagnostic
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildDiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// Source location of a build diagnostic
+///
+/// Lines are 1-based; offsets are byte offsets into the FSH file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceSpan {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_offset: usize,
+    pub end_offset: usize,
+}
+
+impl SourceSpan {
+    /// Compute a span (with line numbers) from a byte range in `source`
+    pub fn from_offsets(source: &str, start_offset: usize, end_offset: usize) -> Self {
+        let clamp = |offset: usize| {
+            let mut offset = offset.min(source.len());
+            while !source.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        };
+        let (start, end) = (clamp(start_offset), clamp(end_offset));
+        Self {
+            start_line: source[..start].matches('\n').count() + 1,
+            end_line: source[..end].matches('\n').count() + 1,
+            start_offset: start,
+            end_offset: end,
+        }
+    }
+
+    /// Compute a zero-width span from a 1-based line and column (as reported by the parser)
+    pub fn from_line_col(source: &str, line: usize, col: usize) -> Self {
+        let line_start = if line <= 1 {
+            0
+        } else {
+            source
+                .match_indices('\n')
+                .nth(line - 2)
+                .map(|(idx, _)| idx + 1)
+                .unwrap_or(source.len())
+        };
+        let offset = line_start + col.saturating_sub(1);
+        Self::from_offsets(source, offset, offset)
+    }
+}
+
+/// A warning or error raised during the build
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDiagnostic {
+    pub severity: BuildDiagnosticSeverity,
+    pub message: String,
+    /// FSH entity name (Profile, Instance, ...) the diagnostic belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity: Option<String>,
+    /// FSH file path relative to the input directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
+    /// FHIR release (`R4`, `R5`, ...) of a multi-release build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+}
+
+impl BuildDiagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(BuildDiagnosticSeverity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(BuildDiagnosticSeverity::Warning, message)
+    }
+
+    fn new(severity: BuildDiagnosticSeverity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            entity: None,
+            file: None,
+            span: None,
+            release: None,
+        }
+    }
+
+    /// Attach the FSH entity name
+    pub fn with_entity(mut self, entity: impl Into<String>) -> Self {
+        self.entity = Some(entity.into());
+        self
+    }
+
+    /// Attach the FSH file and span
+    pub fn with_location(mut self, file: impl Into<String>, span: SourceSpan) -> Self {
+        self.file = Some(file.into());
+        self.span = Some(span);
+        self
+    }
+
+    /// Attach only the FSH file (when no finer location is known)
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Attach the FHIR release the diagnostic was raised for
+    pub fn with_release(mut self, release: impl Into<String>) -> Self {
+        self.release = Some(release.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == BuildDiagnosticSeverity::Error
+    }
+}
+
+/// Complete machine-readable build report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildReport {
+    /// maki version that produced the report
+    pub maki_version: String,
+    /// Whether the build finished without errors
+    pub success: bool,
+    /// Sum of all phase timings
+    pub total_duration_ms: f64,
+    pub phases: Vec<PhaseTiming>,
+    pub stats: BuildStats,
+    /// Provenance for every generated output file
+    pub outputs: Vec<FshIndexEntry>,
+    pub diagnostics: Vec<BuildDiagnostic>,
+}
+
+impl BuildReport {
+    /// Create a report from a finished build
+    pub fn from_result(result: &BuildResult) -> Self {
+        Self {
+            maki_version: env!("CARGO_PKG_VERSION").to_string(),
+            success: !result.stats.has_errors(),
+            total_duration_ms: result.timings.iter().map(|t| t.duration_ms).sum(),
+            phases: result.timings.clone(),
+            stats: result.stats.clone(),
+            outputs: result.fsh_index.clone(),
+            diagnostics: result.stats.diagnostics.clone(),
+        }
+    }
+
+    /// Create a report for a build that stopped with `error`
+    ///
+    /// `timings` are the phases that finished before the error.
+    pub fn from_error(error: &BuildError, timings: Vec<PhaseTiming>) -> Self {
+        let diagnostic = BuildDiagnostic::error(error.to_string());
+        let mut stats = BuildStats::default();
+        stats.record(diagnostic.clone());
+        Self {
+            maki_version: env!("CARGO_PKG_VERSION").to_string(),
+            success: false,
+            total_duration_ms: timings.iter().map(|t| t.duration_ms).sum(),
+            phases: timings,
+            stats,
+            outputs: Vec::new(),
+            diagnostics: vec![diagnostic],
+        }
+    }
+
+    /// Write the report as pretty-printed JSON
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let mut json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        json.push('\n');
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_span_from_offsets() {
+        let source = "Profile: A\nParent: Patient\n* name 1..1\n";
+        let start = source.find("Parent").unwrap();
+        let span = SourceSpan::from_offsets(source, start, source.len() - 1);
+        assert_eq!(span.start_line, 2);
+        assert_eq!(span.end_line, 3);
+        assert_eq!(span.start_offset, start);
+
+        // Out-of-range offsets are clamped
+        let span = SourceSpan::from_offsets(source, 0, 10_000);
+        assert_eq!(span.end_offset, source.len());
+    }
+
+    #[test]
+    fn test_source_span_from_line_col() {
+        let source = "Profile: A\nParent: Patient\n* name 1..1\n";
+        let span = SourceSpan::from_line_col(source, 3, 3);
+        assert_eq!(span.start_line, 3);
+        assert_eq!(span.start_offset, source.find("name").unwrap());
+        assert_eq!(span.start_offset, span.end_offset);
+    }
+
+    #[test]
+    fn test_diagnostic_serialization() {
+        let diagnostic = BuildDiagnostic::error("Parent not found")
+            .with_entity("MyProfile")
+            .with_location(
+                "profiles.fsh",
+                SourceSpan {
+                    start_line: 3,
+                    end_line: 7,
+                    start_offset: 20,
+                    end_offset: 90,
+                },
+            );
+        let json = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["entity"], "MyProfile");
+        assert_eq!(json["file"], "profiles.fsh");
+        assert_eq!(json["span"]["startLine"], 3);
+        assert!(diagnostic.is_error());
+
+        let warning = serde_json::to_value(BuildDiagnostic::warning("w")).unwrap();
+        assert!(warning.get("span").is_none());
+    }
+
+    #[test]
+    fn test_report_from_result() {
+        let mut stats = BuildStats::default();
+        stats.record(BuildDiagnostic::warning("Duplicate alias"));
+        stats.profiles = 2;
+
+        let result = BuildResult {
+            stats,
+            output_dir: "fsh-generated".into(),
+            config: crate::config::UnifiedConfig::default(),
+            fsh_index: vec![FshIndexEntry {
+                output_file: "StructureDefinition-a.json".to_string(),
+                fsh_name: "A".to_string(),
+                fsh_type: "Profile".to_string(),
+                fsh_file: "a.fsh".to_string(),
+                start_line: 1,
+                end_line: 4,
+            }],
+            timings: vec![
+                PhaseTiming::new(BuildPhase::Parse, Duration::from_millis(5)),
+                PhaseTiming::new(BuildPhase::ExportProfiles, Duration::from_millis(10)),
+            ],
+            profile: None,
+        };
+
+        let report = BuildReport::from_result(&result);
+        assert!(report.success);
+        assert_eq!(report.diagnostics.len(), 1);
+        assert!((report.total_duration_ms - 15.0).abs() < 1e-6);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["phases"][0]["phase"], "parse");
+        assert_eq!(json["outputs"][0]["fshFile"], "a.fsh");
+        assert_eq!(json["stats"]["warnings"], 1);
+        assert_eq!(json["stats"]["profiles"], 2);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("reports").join("report.json");
+        report.write(&path).unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_report_from_error() {
+        let timings = vec![PhaseTiming::new(
+            BuildPhase::PackageInit,
+            Duration::from_millis(8),
+        )];
+        let report = BuildReport::from_error(&BuildError::NoFshFiles, timings);
+        assert!(!report.success);
+        assert_eq!(report.phases.len(), 1);
+        assert!((report.total_duration_ms - 8.0).abs() < 1e-6);
+        assert_eq!(report.stats.errors, 1);
+        assert_eq!(
+            report.diagnostics,
+            vec![BuildDiagnostic::error("No FSH files found")]
+        );
+        assert!(report.outputs.is_empty());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["phases"][0]["phase"], "package-init");
+        assert_eq!(json["diagnostics"][0]["message"], "No FSH files found");
+    }
+}
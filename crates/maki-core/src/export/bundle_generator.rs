@@ -0,0 +1,421 @@
+//! Example Bundle generator
+//!
+//! Packages exported example instances (and optionally conformance resources)
+//! for loading into test servers such as HAPI.
+//!
+//! ## Outputs
+//!
+//! - A single `transaction` Bundle. Every entry gets a `urn:uuid` fullUrl and
+//!   literal references between entries are rewritten to those fullUrls.
+//! - NDJSON files (one per resource type) for `$import` / bulk loading. These
+//!   keep the original `Type/id` references.
+//!
+//! Entries are ordered so that a resource always comes after the resources it
+//! references (literal references and canonical URLs). Reference cycles fall
+//! back to the original export order.
+
+use serde_json::{Map, Value as JsonValue, json};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// Example Bundle and NDJSON generator
+#[derive(Debug, Clone)]
+pub struct ExampleBundleGenerator {
+    /// IG canonical base (used for absolute references and fullUrl generation)
+    canonical: String,
+    /// Resources in export order
+    resources: Vec<JsonValue>,
+}
+
+impl ExampleBundleGenerator {
+    /// Create a generator for an IG canonical base
+    pub fn new(canonical: impl Into<String>) -> Self {
+        Self {
+            canonical: canonical.into().trim_end_matches('/').to_string(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Add an exported resource
+    ///
+    /// Resources without `resourceType` are ignored.
+    pub fn add_resource(&mut self, resource: JsonValue) {
+        if resource
+            .get("resourceType")
+            .and_then(|v| v.as_str())
+            .is_some()
+        {
+            self.resources.push(resource);
+        }
+    }
+
+    /// Number of resources added
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Whether no resources have been added
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Deterministic `urn:uuid` fullUrl for a resource
+    ///
+    /// Uses a name-based (v5) UUID so repeated builds produce identical bundles.
+    pub fn full_url(&self, resource_type: &str, id: &str) -> String {
+        let name = format!("{}/{}/{}", self.canonical, resource_type, id);
+        format!(
+            "urn:uuid:{}",
+            Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes())
+        )
+    }
+
+    /// Resources ordered so that dependencies come first
+    ///
+    /// A topological sort that keeps export order among the resources whose
+    /// dependencies are all emitted. When only reference cycles remain, the
+    /// earliest remaining resource goes next.
+    pub fn ordered_resources(&self) -> Vec<&JsonValue> {
+        let count = self.resources.len();
+        let index = self.reference_index();
+
+        // pending[i] = dependencies of i not emitted yet; dependents[j] = resources referencing j
+        let mut pending = vec![0; count];
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (i, resource) in self.resources.iter().enumerate() {
+            let mut dependencies = HashSet::new();
+            collect_dependencies(resource, &mut |target| {
+                if let Some(j) = self.resolve(&index, target)
+                    && j != i
+                {
+                    dependencies.insert(j);
+                }
+            });
+            pending[i] = dependencies.len();
+            for j in dependencies {
+                dependents[j].push(i);
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..count)
+            .filter(|&i| pending[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut emitted = vec![false; count];
+        let mut next_in_export_order = 0;
+        let mut ordered = Vec::with_capacity(count);
+        while ordered.len() < count {
+            let i = match ready.pop() {
+                Some(Reverse(i)) if emitted[i] => continue,
+                Some(Reverse(i)) => i,
+                None => {
+                    // Cycle: fall back to export order
+                    while emitted[next_in_export_order] {
+                        next_in_export_order += 1;
+                    }
+                    next_in_export_order
+                }
+            };
+            emitted[i] = true;
+            ordered.push(&self.resources[i]);
+            for &dependent in &dependents[i] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 && !emitted[dependent] {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+        ordered
+    }
+
+    /// Build a `transaction` Bundle containing every resource
+    ///
+    /// Each entry is a `PUT Type/id` (or `POST Type` when the resource has no
+    /// id), with references rewritten to `urn:uuid` fullUrls.
+    pub fn transaction_bundle(&self, bundle_id: &str) -> JsonValue {
+        let full_urls: HashMap<String, String> = self
+            .resources
+            .iter()
+            .filter_map(|r| {
+                let key = local_key(r)?;
+                let (resource_type, id) = key.split_once('/')?;
+                Some((key.clone(), self.full_url(resource_type, id)))
+            })
+            .collect();
+
+        let entries: Vec<JsonValue> = self
+            .ordered_resources()
+            .into_iter()
+            .enumerate()
+            .map(|(position, resource)| {
+                let resource_type = resource
+                    .get("resourceType")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Resource");
+                let mut rewritten = resource.clone();
+                self.rewrite_references(&mut rewritten, &full_urls);
+
+                match resource.get("id").and_then(|v| v.as_str()) {
+                    Some(id) => json!({
+                        "fullUrl": self.full_url(resource_type, id),
+                        "resource": rewritten,
+                        "request": { "method": "PUT", "url": format!("{}/{}", resource_type, id) }
+                    }),
+                    None => json!({
+                        "fullUrl": self.full_url(resource_type, &format!("entry-{}", position)),
+                        "resource": rewritten,
+                        "request": { "method": "POST", "url": resource_type }
+                    }),
+                }
+            })
+            .collect();
+
+        json!({
+            "resourceType": "Bundle",
+            "id": bundle_id,
+            "type": "transaction",
+            "entry": entries
+        })
+    }
+
+    /// NDJSON content per resource type, keyed by resource type
+    pub fn ndjson_by_type(&self) -> BTreeMap<String, String> {
+        let mut files: BTreeMap<String, String> = BTreeMap::new();
+        for resource in self.ordered_resources() {
+            let resource_type = resource
+                .get("resourceType")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Resource");
+            let content = files.entry(resource_type.to_string()).or_default();
+            content.push_str(&resource.to_string());
+            content.push('\n');
+        }
+        files
+    }
+
+    /// Rewrite `reference` values that point at bundled resources
+    fn rewrite_references(&self, value: &mut JsonValue, full_urls: &HashMap<String, String>) {
+        match value {
+            JsonValue::Object(map) => {
+                if let Some(JsonValue::String(reference)) = map.get("reference")
+                    && let Some(full_url) = full_urls.get(self.strip_canonical(reference))
+                {
+                    map.insert("reference".to_string(), JsonValue::String(full_url.clone()));
+                }
+                // Local `#id` references to contained resources never match a
+                // bundled `Type/id`, so they are left as is
+                for (key, child) in map.iter_mut() {
+                    if key != "reference" {
+                        self.rewrite_references(child, full_urls);
+                    }
+                }
+            }
+            JsonValue::Array(items) => {
+                for item in items {
+                    self.rewrite_references(item, full_urls);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Positions of the resources by `Type/id` key and by canonical URL
+    fn reference_index(&self) -> HashMap<String, usize> {
+        let mut index = HashMap::new();
+        for (i, resource) in self.resources.iter().enumerate() {
+            if let Some(url) = resource.get("url").and_then(|u| u.as_str()) {
+                index.insert(url.to_string(), i);
+            }
+            if let Some(key) = local_key(resource) {
+                index.insert(key, i);
+            }
+        }
+        index
+    }
+
+    /// Position of the resource a reference or canonical points at
+    ///
+    /// Literal references were already resolved to `Type/id` when the
+    /// instances were exported, so they are looked up as written.
+    fn resolve(&self, index: &HashMap<String, usize>, target: &str) -> Option<usize> {
+        let target = self.strip_canonical(target);
+        index
+            .get(target)
+            .or_else(|| index.get(target.split('|').next().unwrap_or(target)))
+            .copied()
+    }
+
+    /// Strip the IG canonical base from an absolute reference
+    fn strip_canonical<'r>(&self, reference: &'r str) -> &'r str {
+        reference
+            .strip_prefix(self.canonical.as_str())
+            .and_then(|r| r.strip_prefix('/'))
+            .filter(|r| r.contains('/'))
+            .unwrap_or(reference)
+    }
+}
+
+/// `Type/id` key of a resource
+fn local_key(resource: &JsonValue) -> Option<String> {
+    let resource_type = resource.get("resourceType")?.as_str()?;
+    let id = resource.get("id")?.as_str()?;
+    Some(format!("{}/{}", resource_type, id))
+}
+
+/// Visit every literal reference and canonical-looking string in a resource
+fn collect_dependencies(value: &JsonValue, visit: &mut dyn FnMut(&str)) {
+    match value {
+        JsonValue::Object(map) => collect_object_dependencies(map, visit),
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_dependencies(item, visit);
+            }
+        }
+        JsonValue::String(s) if s.starts_with("http://") || s.starts_with("https://") => visit(s),
+        _ => {}
+    }
+}
+
+fn collect_object_dependencies(map: &Map<String, JsonValue>, visit: &mut dyn FnMut(&str)) {
+    for (key, child) in map {
+        match (key.as_str(), child) {
+            // A resource's own url is not a dependency
+            ("url", JsonValue::String(_)) if map.contains_key("resourceType") => {}
+            ("reference", JsonValue::String(reference)) => visit(reference),
+            _ => collect_dependencies(child, visit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANONICAL: &str = "http://example.org/fhir";
+
+    fn generator() -> ExampleBundleGenerator {
+        let mut generator = ExampleBundleGenerator::new(CANONICAL);
+        generator.add_resource(json!({
+            "resourceType": "Observation",
+            "id": "obs1",
+            "subject": { "reference": "Patient/pat1" },
+            "performer": [{ "reference": "http://example.org/fhir/Practitioner/prac1" }]
+        }));
+        generator.add_resource(json!({
+            "resourceType": "Patient",
+            "id": "pat1",
+            "generalPractitioner": [{ "reference": "Practitioner/prac1" }]
+        }));
+        generator.add_resource(json!({ "resourceType": "Practitioner", "id": "prac1" }));
+        generator
+    }
+
+    fn entry_ids(bundle: &JsonValue) -> Vec<String> {
+        bundle["entry"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["resource"]["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_orders_entries_by_dependency() {
+        let bundle = generator().transaction_bundle("examples");
+        assert_eq!(bundle["type"], "transaction");
+        assert_eq!(entry_ids(&bundle), vec!["prac1", "pat1", "obs1"]);
+    }
+
+    #[test]
+    fn test_rewrites_references_to_full_urls() {
+        let generator = generator();
+        let bundle = generator.transaction_bundle("examples");
+        let patient_url = generator.full_url("Patient", "pat1");
+        let practitioner_url = generator.full_url("Practitioner", "prac1");
+
+        let observation = &bundle["entry"][2];
+        assert_eq!(observation["resource"]["subject"]["reference"], patient_url);
+        assert_eq!(
+            observation["resource"]["performer"][0]["reference"],
+            practitioner_url
+        );
+        assert_eq!(observation["request"]["method"], "PUT");
+        assert_eq!(observation["request"]["url"], "Observation/obs1");
+        assert!(patient_url.starts_with("urn:uuid:"));
+        assert_eq!(bundle["entry"][1]["fullUrl"], patient_url);
+    }
+
+    #[test]
+    fn test_keeps_contained_references() {
+        let mut generator = ExampleBundleGenerator::new(CANONICAL);
+        generator.add_resource(json!({
+            "resourceType": "Observation",
+            "id": "obs1",
+            "contained": [{ "resourceType": "Patient", "id": "pat1" }],
+            "subject": { "reference": "#pat1" }
+        }));
+        generator.add_resource(json!({ "resourceType": "Patient", "id": "pat1" }));
+
+        let bundle = generator.transaction_bundle("examples");
+        let observation = &bundle["entry"][0]["resource"];
+        assert_eq!(observation["resourceType"], "Observation");
+        assert_eq!(observation["subject"]["reference"], "#pat1");
+    }
+
+    #[test]
+    fn test_full_urls_are_deterministic() {
+        let a = ExampleBundleGenerator::new(CANONICAL).full_url("Patient", "pat1");
+        let b = ExampleBundleGenerator::new(CANONICAL).full_url("Patient", "pat1");
+        let c = ExampleBundleGenerator::new(CANONICAL).full_url("Patient", "pat2");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_canonical_dependencies_and_cycles() {
+        let mut generator = ExampleBundleGenerator::new(CANONICAL);
+        generator.add_resource(json!({
+            "resourceType": "Patient",
+            "id": "a",
+            "meta": { "profile": ["http://example.org/fhir/StructureDefinition/my-patient"] },
+            "link": [{ "other": { "reference": "Patient/b" } }]
+        }));
+        generator.add_resource(json!({
+            "resourceType": "Patient",
+            "id": "b",
+            "link": [{ "other": { "reference": "Patient/a" } }]
+        }));
+        generator.add_resource(json!({
+            "resourceType": "StructureDefinition",
+            "id": "my-patient",
+            "url": "http://example.org/fhir/StructureDefinition/my-patient"
+        }));
+
+        let ids: Vec<&str> = generator
+            .ordered_resources()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["my-patient", "a", "b"]);
+    }
+
+    #[test]
+    fn test_ndjson_by_type_keeps_references() {
+        let files = generator().ndjson_by_type();
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            vec!["Observation", "Patient", "Practitioner"]
+        );
+        let observations = &files["Observation"];
+        assert_eq!(observations.lines().count(), 1);
+        assert!(observations.contains("\"reference\":\"Patient/pat1\""));
+    }
+
+    #[test]
+    fn test_ignores_non_resources() {
+        let mut generator = ExampleBundleGenerator::new(CANONICAL);
+        generator.add_resource(json!({ "id": "x" }));
+        assert!(generator.is_empty());
+    }
+}
@@ -0,0 +1,1271 @@
+//! Code generation from StructureDefinitions
+//!
+//! Turns the exported profiles, extensions and logical models into type
+//! definitions for application code (`maki codegen`): TypeScript interfaces,
+//! Rust structs (serde) or JSON Schemas.
+//!
+//! Types follow the profile rather than the base resource:
+//!
+//! - Cardinality: `min >= 1` makes a field required, the base `max` decides
+//!   between a single value and an array (as in FHIR JSON); prohibited
+//!   (`max = 0`) elements are dropped.
+//! - Choice types (`value[x]`) become one field per allowed type, so a
+//!   narrowed choice yields a single `valueQuantity`-style field.
+//! - Primitive fixed and pattern values become literal types.
+//! - Required bindings on `code` elements to ValueSets that can be expanded
+//!   from local resources become code enums.
+//! - Named slices get their own type and a typed accessor that selects the
+//!   matching items by the slice's discriminator values.
+//!
+//! Backbone elements and constrained datatypes become nested types; datatypes
+//! that are not constrained are emitted as opaque aliases. Snapshots are used
+//! when present so inherited elements are included; for a differential-only
+//! definition (such as a logical model) the differential is used as is.
+
+use crate::export::element_tree::{
+    ElementTree, element_id, fixed_value, is_array, last_segment, str_field, type_entries,
+};
+use crate::export::local_terminology::LocalTerminology;
+use serde_json::{Map, Value as JsonValue, json};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use tracing::debug;
+
+/// Target language of `maki codegen`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodegenLanguage {
+    TypeScript,
+    Rust,
+    JsonSchema,
+}
+
+/// A generated source file, relative to the output directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// Type of a generated field
+#[derive(Debug, Clone, PartialEq)]
+enum FieldType {
+    /// FHIR primitive (`boolean`, `integer`, `string`, ...)
+    Primitive(String),
+    /// Fixed or pattern primitive value
+    Literal(JsonValue),
+    /// Code enum from a required binding
+    Enum(String),
+    /// Generated type (profile, nested element or slice)
+    Named(String),
+    /// FHIR datatype that is not expanded
+    Datatype(String),
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    json_name: String,
+    doc: Option<String>,
+    required: bool,
+    array: bool,
+    /// Profile max when lower than the base max (arrays only)
+    max_items: Option<u64>,
+    min_items: u64,
+    /// Recursive reference (`contentReference`) that needs indirection in Rust
+    boxed: bool,
+    ty: FieldType,
+}
+
+#[derive(Debug, Clone)]
+struct TypeDef {
+    name: String,
+    doc: Option<String>,
+    url: Option<String>,
+    root: bool,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone)]
+struct EnumDef {
+    name: String,
+    doc: Option<String>,
+    codes: Vec<String>,
+}
+
+/// Typed accessor for a named slice
+#[derive(Debug, Clone)]
+struct SliceAccessor {
+    owner: String,
+    field: String,
+    slice: String,
+    item_type: FieldType,
+    array: bool,
+    required: bool,
+    min: u64,
+    max: Option<u64>,
+    /// Values the discriminators of the slice select on
+    pattern: JsonValue,
+}
+
+/// Generates type definitions from StructureDefinitions
+#[derive(Debug, Default)]
+pub struct CodeGenerator {
+    types: Vec<TypeDef>,
+    enums: Vec<EnumDef>,
+    accessors: Vec<SliceAccessor>,
+    datatypes: BTreeSet<String>,
+    definitions: usize,
+}
+
+impl CodeGenerator {
+    /// Build the type model from exported resources
+    ///
+    /// Every StructureDefinition becomes a type; ValueSets and CodeSystems are
+    /// used to expand required bindings.
+    pub fn new(resources: &[JsonValue]) -> Self {
+        let mut builder = Builder {
+            terminology: LocalTerminology::new(resources),
+            local_types: HashMap::new(),
+            enum_names: HashMap::new(),
+            used_names: HashSet::new(),
+            nested: HashMap::new(),
+            out: CodeGenerator::default(),
+        };
+
+        let definitions: Vec<&JsonValue> = resources
+            .iter()
+            .filter(|r| str_field(r, "resourceType") == Some("StructureDefinition"))
+            .collect();
+        // Names first, so profiles can refer to each other in any order
+        let roots: Vec<(String, &JsonValue)> = definitions
+            .iter()
+            .map(|&sd| {
+                let base = str_field(sd, "name")
+                    .or_else(|| str_field(sd, "id"))
+                    .unwrap_or("Type");
+                let name = builder.unique_name(&pascal(base));
+                if let Some(url) = str_field(sd, "url") {
+                    builder.local_types.insert(url, name.clone());
+                }
+                (name, sd)
+            })
+            .collect();
+        for (name, sd) in roots {
+            builder.structure_definition(sd, name);
+        }
+
+        builder.out.definitions = definitions.len();
+        builder.out
+    }
+
+    /// Generate the source files for a language
+    pub fn generate(&self, language: CodegenLanguage) -> Vec<GeneratedFile> {
+        match language {
+            CodegenLanguage::TypeScript => vec![GeneratedFile {
+                path: "types.ts".to_string(),
+                content: self.typescript(),
+            }],
+            CodegenLanguage::Rust => vec![GeneratedFile {
+                path: "types.rs".to_string(),
+                content: self.rust(),
+            }],
+            CodegenLanguage::JsonSchema => self
+                .types
+                .iter()
+                .filter(|t| t.root)
+                .map(|t| GeneratedFile {
+                    path: format!("{}.schema.json", t.name),
+                    content: serde_json::to_string_pretty(&self.json_schema(t)).unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the generated files to `out_dir`
+    ///
+    /// Returns the number of files written.
+    pub fn write(
+        &self,
+        out_dir: &std::path::Path,
+        language: CodegenLanguage,
+    ) -> std::io::Result<usize> {
+        std::fs::create_dir_all(out_dir)?;
+        let files = self.generate(language);
+        for file in &files {
+            std::fs::write(out_dir.join(&file.path), &file.content)?;
+        }
+        Ok(files.len())
+    }
+
+    fn header(&self, comment: &str) -> String {
+        format!(
+            "{} Generated by MAKI {} from {} StructureDefinitions. Do not edit.\n",
+            comment,
+            crate::VERSION,
+            self.definitions
+        )
+    }
+
+    // === TypeScript ===
+
+    fn typescript(&self) -> String {
+        let mut out = self.header("//");
+
+        for datatype in self.opaque_datatypes() {
+            let _ = write!(
+                out,
+                "\n/** FHIR {} (not expanded) */\nexport type {} = Record<string, unknown>;\n",
+                datatype, datatype
+            );
+        }
+        for def in &self.enums {
+            out.push('\n');
+            ts_doc(&mut out, "", def.doc.as_deref());
+            let codes: Vec<String> = def.codes.iter().map(|c| json_string(c)).collect();
+            let _ = writeln!(out, "export type {} = {};", def.name, codes.join(" | "));
+        }
+        for def in &self.types {
+            out.push('\n');
+            let doc = match (&def.doc, &def.url) {
+                (Some(doc), Some(url)) => Some(format!("{}\n\n{}", doc, url)),
+                (doc, url) => doc.clone().or_else(|| url.clone()),
+            };
+            ts_doc(&mut out, "", doc.as_deref());
+            let _ = writeln!(out, "export interface {} {{", def.name);
+            for field in &def.fields {
+                ts_doc(&mut out, "  ", field.doc.as_deref());
+                let name = if is_identifier(&field.json_name) {
+                    field.json_name.clone()
+                } else {
+                    json_string(&field.json_name)
+                };
+                let _ = writeln!(
+                    out,
+                    "  {}{}: {}{};",
+                    name,
+                    if field.required { "" } else { "?" },
+                    ts_type(&field.ty),
+                    if field.array { "[]" } else { "" }
+                );
+            }
+            out.push_str("}\n");
+        }
+
+        if !self.accessors.is_empty() {
+            out.push_str(TS_MATCHES);
+        }
+        for accessor in &self.accessors {
+            let item = ts_type(&accessor.item_type);
+            let function = format!(
+                "{}{}{}",
+                lower_first(&accessor.owner),
+                pascal(&accessor.field),
+                pascal(&accessor.slice)
+            );
+            let pattern = serde_json::to_string(&accessor.pattern).unwrap_or_default();
+            let _ = write!(
+                out,
+                "\n/** Slice `{}` of `{}.{}` */\nexport function {}(owner: {}): ",
+                accessor.slice, accessor.owner, accessor.field, function, accessor.owner
+            );
+            let field = format!("owner.{}", accessor.field);
+            if accessor.array {
+                let items = if accessor.required {
+                    field
+                } else {
+                    format!("({} ?? [])", field)
+                };
+                let _ = writeln!(
+                    out,
+                    "{}[] {{\n  return {}.filter((item) => matchesPattern(item, {})) as unknown as {}[];\n}}",
+                    item, items, pattern, item
+                );
+            } else {
+                let _ = writeln!(
+                    out,
+                    "{} | undefined {{\n  const item = {};\n  return item !== undefined && matchesPattern(item, {}) ? (item as unknown as {}) : undefined;\n}}",
+                    item, field, pattern, item
+                );
+            }
+        }
+        out
+    }
+
+    // === Rust ===
+
+    fn rust(&self) -> String {
+        let mut out = self.header("//!");
+        out.push_str("\nuse serde::{Deserialize, Serialize};\n");
+
+        for datatype in self.opaque_datatypes() {
+            let _ = write!(
+                out,
+                "\n/// FHIR {} (not expanded)\npub type {} = serde_json::Value;\n",
+                datatype, datatype
+            );
+        }
+
+        // Literal strings become single-variant enums named after their field
+        let mut enums: Vec<EnumDef> = self.enums.clone();
+        let mut literal_enums: HashMap<(String, String), String> = HashMap::new();
+        let mut names: HashSet<String> = self
+            .types
+            .iter()
+            .map(|t| t.name.clone())
+            .chain(enums.iter().map(|e| e.name.clone()))
+            .chain(self.datatypes.iter().cloned())
+            .collect();
+        for def in &self.types {
+            for field in &def.fields {
+                if let FieldType::Literal(JsonValue::String(value)) = &field.ty {
+                    let mut name = format!("{}{}", def.name, pascal(&field.json_name));
+                    while !names.insert(name.clone()) {
+                        name.push('_');
+                    }
+                    enums.push(EnumDef {
+                        name: name.clone(),
+                        doc: Some(format!("Fixed value of `{}.{}`", def.name, field.json_name)),
+                        codes: vec![value.clone()],
+                    });
+                    literal_enums.insert((def.name.clone(), field.json_name.clone()), name);
+                }
+            }
+        }
+
+        for def in &enums {
+            out.push('\n');
+            rust_doc(&mut out, "", def.doc.as_deref());
+            out.push_str(
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n",
+            );
+            let _ = writeln!(out, "pub enum {} {{", def.name);
+            let mut variants = HashSet::new();
+            for code in &def.codes {
+                let mut variant = pascal(code);
+                if variant.is_empty() || variant.starts_with(|c: char| c.is_ascii_digit()) {
+                    variant.insert(0, 'V');
+                }
+                let base = variant.clone();
+                let mut n = 2;
+                while !variants.insert(variant.clone()) {
+                    variant = format!("{}{}", base, n);
+                    n += 1;
+                }
+                let _ = writeln!(
+                    out,
+                    "    #[serde(rename = {})]\n    {},",
+                    json_string(code),
+                    variant
+                );
+            }
+            out.push_str("}\n");
+        }
+
+        for def in &self.types {
+            out.push('\n');
+            let doc = match (&def.doc, &def.url) {
+                (Some(doc), Some(url)) => Some(format!("{}\n\n<{}>", doc, url)),
+                (doc, url) => doc
+                    .clone()
+                    .or_else(|| url.as_ref().map(|u| format!("<{}>", u))),
+            };
+            rust_doc(&mut out, "", doc.as_deref());
+            out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+            let _ = writeln!(out, "pub struct {} {{", def.name);
+            for field in &def.fields {
+                rust_doc(&mut out, "    ", field.doc.as_deref());
+                let ident = rust_ident(&snake(&field.json_name));
+                let mut attributes = Vec::new();
+                if ident.trim_start_matches("r#") != field.json_name {
+                    attributes.push(format!("rename = {}", json_string(&field.json_name)));
+                }
+                let mut ty = match &field.ty {
+                    FieldType::Literal(JsonValue::String(_)) => {
+                        literal_enums[&(def.name.clone(), field.json_name.clone())].clone()
+                    }
+                    other => rust_type(other),
+                };
+                if field.boxed && !field.array {
+                    ty = format!("Box<{}>", ty);
+                }
+                if field.array {
+                    ty = format!("Vec<{}>", ty);
+                    if !field.required {
+                        attributes
+                            .push("default, skip_serializing_if = \"Vec::is_empty\"".to_string());
+                    }
+                } else if !field.required {
+                    ty = format!("Option<{}>", ty);
+                    attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+                }
+                if !attributes.is_empty() {
+                    let _ = writeln!(out, "    #[serde({})]", attributes.join(", "));
+                }
+                let _ = writeln!(out, "    pub {}: {},", ident, ty);
+            }
+            out.push_str("}\n");
+        }
+
+        let mut owners: Vec<&str> = Vec::new();
+        for accessor in &self.accessors {
+            if !owners.contains(&accessor.owner.as_str()) {
+                owners.push(&accessor.owner);
+            }
+        }
+        for owner in owners {
+            let _ = write!(out, "\nimpl {} {{", owner);
+            for accessor in self.accessors.iter().filter(|a| a.owner == owner) {
+                let item = rust_type(&accessor.item_type);
+                let field = rust_ident(&snake(&accessor.field));
+                let items = if accessor.array || !accessor.required {
+                    format!("self.{}.iter()", field)
+                } else {
+                    format!("std::iter::once(&self.{})", field)
+                };
+                let _ = write!(
+                    out,
+                    "\n    /// Slice `{}` of `{}`\n    pub fn {}_{}(&self) -> Vec<{}> {{\n        \
+                     let pattern: serde_json::Value =\n            \
+                     serde_json::from_str({:?}).expect(\"valid slice pattern\");\n        \
+                     {}\n            .filter_map(|item| slice_item(item, &pattern))\n            .collect()\n    }}\n",
+                    accessor.slice,
+                    accessor.field,
+                    snake(&accessor.field),
+                    snake(&accessor.slice),
+                    item,
+                    serde_json::to_string(&accessor.pattern).unwrap_or_default(),
+                    items
+                );
+            }
+            out.push_str("}\n");
+        }
+        if !self.accessors.is_empty() {
+            out.push_str(RUST_MATCHES);
+        }
+        out
+    }
+
+    // === JSON Schema ===
+
+    fn json_schema(&self, root: &TypeDef) -> JsonValue {
+        let types: HashMap<&str, &TypeDef> =
+            self.types.iter().map(|t| (t.name.as_str(), t)).collect();
+        let enums: HashMap<&str, &EnumDef> =
+            self.enums.iter().map(|e| (e.name.as_str(), e)).collect();
+
+        let mut defs = Map::new();
+        let mut queue = VecDeque::from([FieldType::Named(root.name.clone())]);
+        let mut seen = HashSet::new();
+        while let Some(ty) = queue.pop_front() {
+            match ty {
+                FieldType::Named(name) if seen.insert(name.clone()) => {
+                    let Some(def) = types.get(name.as_str()) else {
+                        continue;
+                    };
+                    let mut properties = Map::new();
+                    let mut required = Vec::new();
+                    for field in &def.fields {
+                        queue.push_back(field.ty.clone());
+                        properties
+                            .insert(field.json_name.clone(), self.schema_property(def, field));
+                        if field.required {
+                            required.push(JsonValue::String(field.json_name.clone()));
+                        }
+                    }
+                    let mut schema = Map::new();
+                    schema.insert("type".to_string(), json!("object"));
+                    if let Some(doc) = &def.doc {
+                        schema.insert("description".to_string(), json!(doc));
+                    }
+                    schema.insert("properties".to_string(), JsonValue::Object(properties));
+                    if !required.is_empty() {
+                        schema.insert("required".to_string(), JsonValue::Array(required));
+                    }
+                    defs.insert(name, JsonValue::Object(schema));
+                }
+                FieldType::Enum(name) if seen.insert(name.clone()) => {
+                    if let Some(def) = enums.get(name.as_str()) {
+                        defs.insert(name, json!({ "type": "string", "enum": def.codes }));
+                    }
+                }
+                FieldType::Datatype(name) if seen.insert(name.clone()) => {
+                    defs.insert(
+                        name.clone(),
+                        json!({ "type": "object", "description": format!("FHIR {} (not expanded)", name) }),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let mut schema = Map::new();
+        schema.insert(
+            "$schema".to_string(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        if let Some(url) = &root.url {
+            schema.insert("$id".to_string(), json!(url));
+        }
+        schema.insert("title".to_string(), json!(root.name));
+        schema.insert("$ref".to_string(), json!(format!("#/$defs/{}", root.name)));
+        schema.insert("$defs".to_string(), JsonValue::Object(defs));
+        JsonValue::Object(schema)
+    }
+
+    fn schema_property(&self, owner: &TypeDef, field: &Field) -> JsonValue {
+        let mut item = match &field.ty {
+            FieldType::Primitive(code) => match code.as_str() {
+                "boolean" => json!({ "type": "boolean" }),
+                "integer" => json!({ "type": "integer" }),
+                "positiveInt" => json!({ "type": "integer", "minimum": 1 }),
+                "unsignedInt" => json!({ "type": "integer", "minimum": 0 }),
+                "decimal" => json!({ "type": "number" }),
+                _ => json!({ "type": "string" }),
+            },
+            FieldType::Literal(value) => json!({ "const": value }),
+            FieldType::Enum(name) | FieldType::Named(name) | FieldType::Datatype(name) => {
+                json!({ "$ref": format!("#/$defs/{}", name) })
+            }
+        };
+        if let Some(doc) = &field.doc
+            && !field.array
+        {
+            item["description"] = json!(doc);
+        }
+        if !field.array {
+            return item;
+        }
+
+        let mut schema = json!({ "type": "array", "items": item });
+        if let Some(doc) = &field.doc {
+            schema["description"] = json!(doc);
+        }
+        if field.min_items > 0 {
+            schema["minItems"] = json!(field.min_items);
+        }
+        if let Some(max) = field.max_items {
+            schema["maxItems"] = json!(max);
+        }
+        let slices: Vec<JsonValue> = self
+            .accessors
+            .iter()
+            .filter(|a| a.owner == owner.name && a.field == field.json_name)
+            .filter(|a| a.min > 0 || a.max.is_some())
+            .map(|a| {
+                let mut contains = json!({ "contains": pattern_schema(&a.pattern) });
+                contains["minContains"] = json!(a.min);
+                if let Some(max) = a.max {
+                    contains["maxContains"] = json!(max);
+                }
+                contains
+            })
+            .collect();
+        if !slices.is_empty() {
+            schema["allOf"] = JsonValue::Array(slices);
+        }
+        schema
+    }
+
+    /// Datatypes emitted as opaque aliases (not shadowed by a generated type)
+    fn opaque_datatypes(&self) -> Vec<&String> {
+        let names: HashSet<&str> = self
+            .types
+            .iter()
+            .map(|t| t.name.as_str())
+            .chain(self.enums.iter().map(|e| e.name.as_str()))
+            .collect();
+        self.datatypes
+            .iter()
+            .filter(|d| !names.contains(d.as_str()))
+            .collect()
+    }
+}
+
+/// Walks StructureDefinitions into the type model
+struct Builder<'r> {
+    terminology: LocalTerminology<'r>,
+    /// SD url → generated root type
+    local_types: HashMap<&'r str, String>,
+    /// ValueSet url → code enum (None when not expandable locally)
+    enum_names: HashMap<String, Option<String>>,
+    used_names: HashSet<String>,
+    /// Element id → nested type of the current definition (for contentReference)
+    nested: HashMap<String, String>,
+    out: CodeGenerator,
+}
+
+impl<'r> Builder<'r> {
+    fn unique_name(&mut self, base: &str) -> String {
+        let base = if base.is_empty() { "Type" } else { base };
+        let mut name = base.to_string();
+        let mut n = 2;
+        while !self.used_names.insert(name.clone()) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        name
+    }
+
+    fn structure_definition(&mut self, sd: &'r JsonValue, name: String) {
+        let tree = ElementTree::new(sd);
+        let Some(root) = tree.root.clone() else {
+            debug!("Skipping {}: no elements", name);
+            return;
+        };
+        self.nested.clear();
+
+        let doc = str_field(sd, "title")
+            .or_else(|| str_field(sd, "description"))
+            .map(|d| first_line(d).to_string());
+        let mut fields = Vec::new();
+        // Resources carry their type in the JSON
+        if str_field(sd, "kind") == Some("resource")
+            && let Some(resource_type) = str_field(sd, "type")
+        {
+            fields.push(Field {
+                json_name: "resourceType".to_string(),
+                doc: None,
+                required: true,
+                array: false,
+                max_items: None,
+                min_items: 1,
+                boxed: false,
+                ty: FieldType::Literal(json!(resource_type)),
+            });
+        }
+        self.build_type(&tree, &root, name, doc, str_field(sd, "url"), fields, true);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_type(
+        &mut self,
+        tree: &ElementTree<'r>,
+        parent_id: &str,
+        name: String,
+        doc: Option<String>,
+        url: Option<&str>,
+        mut fields: Vec<Field>,
+        root: bool,
+    ) {
+        self.nested.insert(parent_id.to_string(), name.clone());
+        for child in tree.children(parent_id) {
+            if str_field(child, "max") == Some("0") {
+                continue;
+            }
+            let id = element_id(child);
+            let segment = last_segment(id);
+            let doc = str_field(child, "short").map(String::from);
+            let min = child.get("min").and_then(JsonValue::as_u64).unwrap_or(0);
+            let array = is_array(child);
+            let max_items = str_field(child, "max")
+                .and_then(|m| m.parse::<u64>().ok())
+                .filter(|_| array);
+
+            if let Some(base) = segment.strip_suffix("[x]") {
+                let types = type_entries(child);
+                for ty in &types {
+                    let code = str_field(ty, "code").unwrap_or_default();
+                    let json_name = format!("{}{}", base, upper_first(code));
+                    // Constraints on a narrowed choice live under its type slice
+                    let slice_id = format!("{}:{}", id, json_name);
+                    let source = tree.by_id.get(slice_id.as_str()).copied().unwrap_or(child);
+                    let field_type = self.resolve(
+                        tree,
+                        source,
+                        Some(ty),
+                        &format!("{}{}", name, pascal(&json_name)),
+                    );
+                    fields.push(Field {
+                        json_name,
+                        doc: doc.clone(),
+                        required: min >= 1 && types.len() == 1,
+                        array,
+                        max_items,
+                        min_items: min,
+                        boxed: false,
+                        ty: field_type,
+                    });
+                }
+                continue;
+            }
+
+            let nested_name = format!("{}{}", name, pascal(segment));
+            let field_type = self.resolve(
+                tree,
+                child,
+                type_entries(child).first().copied(),
+                &nested_name,
+            );
+            let boxed = child.get("contentReference").is_some();
+            fields.push(Field {
+                json_name: segment.to_string(),
+                doc: with_binding_doc(doc, child, &field_type),
+                required: min >= 1,
+                array,
+                max_items,
+                min_items: min,
+                boxed,
+                ty: field_type,
+            });
+
+            for slice in tree.slices(id) {
+                if str_field(slice, "max") == Some("0") {
+                    continue;
+                }
+                let slice_name = last_segment(element_id(slice))
+                    .split_once(':')
+                    .map(|(_, s)| s)
+                    .unwrap_or_default();
+                let item_type = self.resolve(
+                    tree,
+                    slice,
+                    type_entries(slice).first().copied(),
+                    &format!("{}{}", nested_name, pascal(slice_name)),
+                );
+                let Some(pattern) = tree.slice_pattern(child, slice) else {
+                    debug!("No discriminator values for slice {}", element_id(slice));
+                    continue;
+                };
+                self.out.accessors.push(SliceAccessor {
+                    owner: name.clone(),
+                    field: segment.to_string(),
+                    slice: slice_name.to_string(),
+                    item_type,
+                    array,
+                    required: min >= 1,
+                    min: slice.get("min").and_then(JsonValue::as_u64).unwrap_or(0),
+                    max: str_field(slice, "max").and_then(|m| m.parse().ok()),
+                    pattern,
+                });
+            }
+        }
+
+        self.out.types.push(TypeDef {
+            name,
+            doc,
+            url: url.map(String::from),
+            root,
+            fields,
+        });
+    }
+
+    fn resolve(
+        &mut self,
+        tree: &ElementTree<'r>,
+        element: &'r JsonValue,
+        ty: Option<&JsonValue>,
+        nested_name: &str,
+    ) -> FieldType {
+        let id = element_id(element);
+        if tree.has_children(id) {
+            let name = self.unique_name(nested_name);
+            let doc = str_field(element, "short").map(String::from);
+            self.build_type(tree, id, name.clone(), doc, None, Vec::new(), false);
+            return FieldType::Named(name);
+        }
+        if let Some(reference) = str_field(element, "contentReference") {
+            let target = reference.rsplit('#').next().unwrap_or(reference);
+            if let Some(name) = self.nested.get(target) {
+                return FieldType::Named(name.clone());
+            }
+        }
+        if let Some(value) = fixed_value(element)
+            && !value.is_object()
+            && !value.is_array()
+        {
+            return FieldType::Literal(value.clone());
+        }
+
+        let code = ty
+            .and_then(|t| str_field(t, "code"))
+            .unwrap_or("BackboneElement");
+        if code == "code"
+            && let Some(binding) = element.get("binding")
+            && str_field(binding, "strength") == Some("required")
+            && let Some(value_set) = str_field(binding, "valueSet")
+            && let Some(name) = self.code_enum(value_set)
+        {
+            return FieldType::Enum(name);
+        }
+        if let Some(system) = code.strip_prefix("http://hl7.org/fhirpath/System.") {
+            return FieldType::Primitive(
+                match system {
+                    "Boolean" => "boolean",
+                    "Integer" => "integer",
+                    "Decimal" => "decimal",
+                    _ => "string",
+                }
+                .to_string(),
+            );
+        }
+        if code.starts_with(|c: char| c.is_ascii_lowercase()) {
+            return FieldType::Primitive(code.to_string());
+        }
+        if let Some(local) = ty
+            .and_then(|t| t.get("profile"))
+            .and_then(JsonValue::as_array)
+            .and_then(|profiles| profiles.first())
+            .and_then(JsonValue::as_str)
+            .and_then(|profile| self.local_types.get(profile))
+        {
+            return FieldType::Named(local.clone());
+        }
+        self.out.datatypes.insert(code.to_string());
+        FieldType::Datatype(code.to_string())
+    }
+
+    /// Code enum for a ValueSet that can be expanded from local resources
+    fn code_enum(&mut self, value_set: &str) -> Option<String> {
+        let url = value_set.split('|').next().unwrap_or(value_set);
+        if let Some(name) = self.enum_names.get(url) {
+            return name.clone();
+        }
+        let vs = self.terminology.find("ValueSet", url);
+        let codes = self
+            .terminology
+            .expand(url)
+            .map(|concepts| {
+                let mut codes: Vec<String> = Vec::new();
+                for concept in concepts {
+                    if !codes.contains(&concept.code) {
+                        codes.push(concept.code);
+                    }
+                }
+                codes
+            })
+            .filter(|c| !c.is_empty());
+        let name = match (vs, codes) {
+            (Some(vs), Some(codes)) => {
+                let base = str_field(vs, "name")
+                    .or_else(|| str_field(vs, "id"))
+                    .unwrap_or("Codes");
+                let name = self.unique_name(&pascal(base));
+                self.out.enums.push(EnumDef {
+                    name: name.clone(),
+                    doc: Some(
+                        str_field(vs, "title")
+                            .map(|t| format!("{}\n\n{}", t, url))
+                            .unwrap_or_else(|| url.to_string()),
+                    ),
+                    codes,
+                });
+                Some(name)
+            }
+            _ => None,
+        };
+        self.enum_names.insert(url.to_string(), name.clone());
+        name
+    }
+}
+
+// === Helpers ===
+
+fn with_binding_doc(doc: Option<String>, element: &JsonValue, ty: &FieldType) -> Option<String> {
+    let binding = element.get("binding");
+    match (binding.and_then(|b| str_field(b, "valueSet")), ty) {
+        (Some(value_set), FieldType::Datatype(_)) => {
+            let strength = binding
+                .and_then(|b| str_field(b, "strength"))
+                .unwrap_or("example");
+            let line = format!("Binding ({}): {}", strength, value_set);
+            Some(match doc {
+                Some(doc) => format!("{}\n\n{}", doc, line),
+                None => line,
+            })
+        }
+        _ => doc,
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim()
+}
+
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lower_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `my-patient` → `MyPatient`, `valueQuantity` → `ValueQuantity`
+fn pascal(s: &str) -> String {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .map(upper_first)
+        .collect()
+}
+
+/// `valueQuantity` → `value_quantity`
+fn snake(s: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "super" | "crate" | "Self" => format!("{}_", name),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ if name.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", name),
+        _ => name.to_string(),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn json_string(s: &str) -> String {
+    JsonValue::String(s.to_string()).to_string()
+}
+
+fn ts_type(ty: &FieldType) -> String {
+    match ty {
+        FieldType::Primitive(code) => match code.as_str() {
+            "boolean" => "boolean",
+            "integer" | "positiveInt" | "unsignedInt" | "decimal" => "number",
+            _ => "string",
+        }
+        .to_string(),
+        FieldType::Literal(value) => value.to_string(),
+        FieldType::Enum(name) | FieldType::Named(name) | FieldType::Datatype(name) => name.clone(),
+    }
+}
+
+fn rust_type(ty: &FieldType) -> String {
+    match ty {
+        FieldType::Primitive(code) => match code.as_str() {
+            "boolean" => "bool",
+            "integer" => "i32",
+            "positiveInt" | "unsignedInt" => "u32",
+            "decimal" => "f64",
+            _ => "String",
+        }
+        .to_string(),
+        FieldType::Literal(JsonValue::Bool(_)) => "bool".to_string(),
+        FieldType::Literal(JsonValue::Number(n)) if n.is_i64() => "i64".to_string(),
+        FieldType::Literal(JsonValue::Number(_)) => "f64".to_string(),
+        FieldType::Literal(_) => "String".to_string(),
+        FieldType::Enum(name) | FieldType::Named(name) | FieldType::Datatype(name) => name.clone(),
+    }
+}
+
+fn ts_doc(out: &mut String, indent: &str, doc: Option<&str>) {
+    let Some(doc) = doc else { return };
+    let doc = doc.replace("*/", "*\\/");
+    if !doc.contains('\n') {
+        let _ = writeln!(out, "{}/** {} */", indent, doc);
+        return;
+    }
+    let _ = writeln!(out, "{}/**", indent);
+    for line in doc.lines() {
+        let _ = writeln!(
+            out,
+            "{} *{}{}",
+            indent,
+            if line.is_empty() { "" } else { " " },
+            line
+        );
+    }
+    let _ = writeln!(out, "{} */", indent);
+}
+
+fn rust_doc(out: &mut String, indent: &str, doc: Option<&str>) {
+    let Some(doc) = doc else { return };
+    for line in doc.lines() {
+        let _ = writeln!(
+            out,
+            "{}///{}{}",
+            indent,
+            if line.is_empty() { "" } else { " " },
+            line
+        );
+    }
+}
+
+/// JSON Schema matching values that contain `pattern`
+fn pattern_schema(pattern: &JsonValue) -> JsonValue {
+    match pattern {
+        JsonValue::Object(map) => {
+            let properties: Map<String, JsonValue> = map
+                .iter()
+                .map(|(key, value)| (key.clone(), pattern_schema(value)))
+                .collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": map.keys().collect::<Vec<_>>()
+            })
+        }
+        JsonValue::Array(items) => json!({
+            "type": "array",
+            "allOf": items.iter().map(|item| json!({ "contains": pattern_schema(item) })).collect::<Vec<_>>()
+        }),
+        // Nested arrays in FHIR JSON: the value may be one of several items
+        primitive => json!({
+            "anyOf": [
+                { "const": primitive },
+                { "type": "array", "contains": { "const": primitive } }
+            ]
+        }),
+    }
+}
+
+const TS_MATCHES: &str = r#"
+/** Whether `value` contains everything in `pattern` (FHIR pattern semantics) */
+function matchesPattern(value: unknown, pattern: unknown): boolean {
+  if (Array.isArray(value) && !Array.isArray(pattern)) {
+    return value.some((item) => matchesPattern(item, pattern));
+  }
+  if (Array.isArray(pattern)) {
+    const items = Array.isArray(value) ? value : [value];
+    return pattern.every((p) => items.some((item) => matchesPattern(item, p)));
+  }
+  if (pattern !== null && typeof pattern === "object") {
+    if (value === null || typeof value !== "object") return false;
+    const record = value as Record<string, unknown>;
+    return Object.entries(pattern).every(([key, p]) => matchesPattern(record[key], p));
+  }
+  return value === pattern;
+}
+"#;
+
+const RUST_MATCHES: &str = r#"
+/// Whether `value` contains everything in `pattern` (FHIR pattern semantics)
+fn matches_pattern(value: &serde_json::Value, pattern: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (value, pattern) {
+        (Value::Array(items), Value::Array(patterns)) => patterns
+            .iter()
+            .all(|p| items.iter().any(|item| matches_pattern(item, p))),
+        (Value::Array(items), _) => items.iter().any(|item| matches_pattern(item, pattern)),
+        (_, Value::Array(patterns)) => patterns.iter().all(|p| matches_pattern(value, p)),
+        (Value::Object(map), Value::Object(patterns)) => patterns
+            .iter()
+            .all(|(key, p)| map.get(key).is_some_and(|v| matches_pattern(v, p))),
+        _ => value == pattern,
+    }
+}
+
+/// Convert an item to a slice type if it matches the slice's pattern
+fn slice_item<T: Serialize, S: serde::de::DeserializeOwned>(
+    item: &T,
+    pattern: &serde_json::Value,
+) -> Option<S> {
+    let value = serde_json::to_value(item).ok()?;
+    if matches_pattern(&value, pattern) {
+        serde_json::from_value(value).ok()
+    } else {
+        None
+    }
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resources() -> Vec<JsonValue> {
+        vec![
+            json!({
+                "resourceType": "StructureDefinition",
+                "id": "bp",
+                "url": "http://example.org/StructureDefinition/bp",
+                "name": "BloodPressure",
+                "title": "Blood Pressure",
+                "kind": "resource",
+                "type": "Observation",
+                "derivation": "constraint",
+                "snapshot": { "element": [
+                    { "id": "Observation", "path": "Observation", "min": 0, "max": "*" },
+                    { "id": "Observation.id", "path": "Observation.id", "min": 0, "max": "1",
+                      "type": [{ "code": "http://hl7.org/fhirpath/System.String" }] },
+                    { "id": "Observation.status", "path": "Observation.status", "min": 1, "max": "1",
+                      "type": [{ "code": "code" }], "fixedCode": "final" },
+                    { "id": "Observation.category", "path": "Observation.category", "min": 0, "max": "*",
+                      "base": { "max": "*" }, "type": [{ "code": "CodeableConcept" }] },
+                    { "id": "Observation.interpretation", "path": "Observation.interpretation", "min": 0, "max": "0",
+                      "type": [{ "code": "CodeableConcept" }] },
+                    { "id": "Observation.value[x]", "path": "Observation.value[x]", "min": 1, "max": "1",
+                      "type": [{ "code": "Quantity" }] },
+                    { "id": "Observation.method", "path": "Observation.method", "min": 0, "max": "1",
+                      "type": [{ "code": "code" }],
+                      "binding": { "strength": "required", "valueSet": "http://example.org/ValueSet/methods" } },
+                    { "id": "Observation.component", "path": "Observation.component", "min": 2, "max": "*",
+                      "base": { "max": "*" }, "type": [{ "code": "BackboneElement" }],
+                      "slicing": { "discriminator": [{ "type": "pattern", "path": "code" }], "rules": "open" } },
+                    { "id": "Observation.component.code", "path": "Observation.component.code", "min": 1, "max": "1",
+                      "type": [{ "code": "CodeableConcept" }] },
+                    { "id": "Observation.component:systolic", "path": "Observation.component", "sliceName": "systolic",
+                      "min": 1, "max": "1", "base": { "max": "*" }, "type": [{ "code": "BackboneElement" }] },
+                    { "id": "Observation.component:systolic.code", "path": "Observation.component.code", "min": 1, "max": "1",
+                      "type": [{ "code": "CodeableConcept" }],
+                      "patternCodeableConcept": { "coding": [{ "system": "http://loinc.org", "code": "8480-6" }] } }
+                ]}
+            }),
+            json!({
+                "resourceType": "StructureDefinition",
+                "id": "shoe",
+                "url": "http://example.org/StructureDefinition/shoe",
+                "name": "Shoe",
+                "kind": "logical",
+                "type": "http://example.org/StructureDefinition/shoe",
+                "derivation": "specialization",
+                "differential": { "element": [
+                    { "id": "Shoe", "path": "Shoe" },
+                    { "id": "Shoe.size", "path": "Shoe.size", "min": 1, "max": "1", "type": [{ "code": "decimal" }] },
+                    { "id": "Shoe.laces", "path": "Shoe.laces", "min": 0, "max": "*", "type": [{ "code": "BackboneElement" }] },
+                    { "id": "Shoe.laces.color", "path": "Shoe.laces.color", "min": 0, "max": "1", "type": [{ "code": "string" }] }
+                ]}
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/ValueSet/methods",
+                "name": "Methods",
+                "compose": { "include": [{ "system": "http://example.org/CodeSystem/methods" }] }
+            }),
+            json!({
+                "resourceType": "CodeSystem",
+                "url": "http://example.org/CodeSystem/methods",
+                "concept": [{ "code": "manual", "concept": [{ "code": "auscultation" }] }, { "code": "automatic" }]
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_typescript() {
+        let ts = &CodeGenerator::new(&resources()).generate(CodegenLanguage::TypeScript)[0];
+        assert_eq!(ts.path, "types.ts");
+        let ts = &ts.content;
+
+        assert!(ts.contains("export type CodeableConcept = Record<string, unknown>;"));
+        assert!(
+            ts.contains("export type Methods = \"manual\" | \"auscultation\" | \"automatic\";")
+        );
+        assert!(ts.contains("export interface BloodPressure {\n  resourceType: \"Observation\";\n  id?: string;\n  status: \"final\";\n  category?: CodeableConcept[];\n"));
+        assert!(!ts.contains("interpretation"));
+        assert!(ts.contains("  valueQuantity: Quantity;\n"));
+        assert!(ts.contains("  method?: Methods;\n"));
+        assert!(ts.contains("  component: BloodPressureComponent[];\n"));
+        assert!(ts.contains(
+            "export interface BloodPressureComponentSystolic {\n  code: CodeableConcept;\n}"
+        ));
+        assert!(ts.contains(
+            "export function bloodPressureComponentSystolic(owner: BloodPressure): BloodPressureComponentSystolic[] {\n  \
+             return owner.component.filter((item) => matchesPattern(item, {\"code\":{\"coding\":[{\"system\":\"http://loinc.org\",\"code\":\"8480-6\"}]}}))"
+        ));
+
+        // Logical models are plain types
+        assert!(ts.contains("export interface Shoe {\n  size: number;\n  laces?: ShoeLaces[];\n}"));
+        assert!(ts.contains("export interface ShoeLaces {\n  color?: string;\n}"));
+    }
+
+    #[test]
+    fn test_rust() {
+        let rust = CodeGenerator::new(&resources()).generate(CodegenLanguage::Rust);
+        let rust = &rust[0].content;
+
+        assert!(rust.contains("pub type Quantity = serde_json::Value;"));
+        assert!(
+            rust.contains("pub enum Methods {\n    #[serde(rename = \"manual\")]\n    Manual,")
+        );
+        assert!(rust.contains(
+            "pub enum BloodPressureStatus {\n    #[serde(rename = \"final\")]\n    Final,\n}"
+        ));
+        assert!(rust.contains("    #[serde(rename = \"resourceType\")]\n    pub resource_type: BloodPressureResourceType,"));
+        assert!(rust.contains(
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub id: Option<String>,"
+        ));
+        assert!(rust.contains(
+            "    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]\n    pub category: Vec<CodeableConcept>,"
+        ));
+        assert!(rust.contains(
+            "    #[serde(rename = \"valueQuantity\")]\n    pub value_quantity: Quantity,"
+        ));
+        assert!(rust.contains("    pub component: Vec<BloodPressureComponent>,"));
+        assert!(rust.contains(
+            "    pub fn component_systolic(&self) -> Vec<BloodPressureComponentSystolic> {"
+        ));
+        assert!(rust.contains("    pub size: f64,"));
+        assert!(rust.contains("fn matches_pattern("));
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let files = CodeGenerator::new(&resources()).generate(CodegenLanguage::JsonSchema);
+        assert_eq!(
+            files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(),
+            vec!["BloodPressure.schema.json", "Shoe.schema.json"]
+        );
+        let schema: JsonValue = serde_json::from_str(&files[0].content).unwrap();
+        assert_eq!(schema["$ref"], "#/$defs/BloodPressure");
+        assert_eq!(schema["$id"], "http://example.org/StructureDefinition/bp");
+
+        let bp = &schema["$defs"]["BloodPressure"];
+        assert_eq!(
+            bp["required"],
+            json!(["resourceType", "status", "valueQuantity", "component"])
+        );
+        assert_eq!(bp["properties"]["status"]["const"], "final");
+        assert_eq!(bp["properties"]["method"]["$ref"], "#/$defs/Methods");
+        assert_eq!(
+            schema["$defs"]["Methods"]["enum"],
+            json!(["manual", "auscultation", "automatic"])
+        );
+
+        let component = &bp["properties"]["component"];
+        assert_eq!(component["minItems"], 2);
+        assert_eq!(component["allOf"][0]["minContains"], 1);
+        assert_eq!(component["allOf"][0]["maxContains"], 1);
+        assert_eq!(
+            component["allOf"][0]["contains"]["required"],
+            json!(["code"])
+        );
+        assert!(schema["$defs"].get("BloodPressureComponent").is_some());
+    }
+
+    #[test]
+    fn test_local_expansion() {
+        let mut resources = resources();
+        // A filter cannot be expanded locally: no enum, plain string
+        resources[2]["compose"]["include"][0]["filter"] =
+            json!([{ "property": "concept", "op": "is-a", "value": "manual" }]);
+        let ts = &CodeGenerator::new(&resources).generate(CodegenLanguage::TypeScript)[0].content;
+        assert!(ts.contains("  method?: string;\n"));
+        assert!(!ts.contains("export type Methods"));
+
+        assert_eq!(pascal("my-patient"), "MyPatient");
+        assert_eq!(snake("valueQuantity"), "value_quantity");
+        assert_eq!(rust_ident("type"), "r#type");
+    }
+}
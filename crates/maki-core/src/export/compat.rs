@@ -0,0 +1,864 @@
+//! IG compatibility report
+//!
+//! Compares the StructureDefinitions, ValueSets and CodeSystems of two versions
+//! of an Implementation Guide (`maki compat <old> <new>`) and classifies every
+//! change as breaking or non-breaking for implementers of the old version.
+//!
+//! Resources are matched by canonical URL; resources whose URL changed but whose
+//! `resourceType` and `id` (or `name`) did not are reported as renamed.
+//! StructureDefinitions are compared element by element (by element `id`) on
+//! their snapshots. Missing snapshots are generated with [`SnapshotGenerator`]
+//! (see [`fill_snapshots`]); when that is not possible the differentials are
+//! compared instead, which only detects changes to elements constrained in both
+//! versions.
+//!
+//! | Change | Classification |
+//! |--------|----------------|
+//! | Resource or element removed, canonical renamed | breaking |
+//! | Cardinality tightened (min raised, max lowered) | breaking |
+//! | Type, profile or target profile removed | breaking |
+//! | Binding strength raised, required ValueSet changed | breaking |
+//! | Code removed from a ValueSet or CodeSystem | breaking |
+//! | New must-support or required element | breaking |
+//! | Anything loosened or added | non-breaking |
+
+use crate::canonical::DefinitionSession;
+use crate::export::{SnapshotGenerator, StructureDefinition};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Resource types compared by the compatibility report
+const COMPARED_TYPES: &[&str] = &["StructureDefinition", "ValueSet", "CodeSystem"];
+
+/// Binding strengths from weakest to strongest
+const BINDING_STRENGTHS: &[&str] = &["example", "preferred", "extensible", "required"];
+
+/// Whether a change breaks implementers of the old version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compatibility {
+    Breaking,
+    NonBreaking,
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Breaking => write!(f, "breaking"),
+            Compatibility::NonBreaking => write!(f, "non-breaking"),
+        }
+    }
+}
+
+/// Kind of change between two versions of a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    ResourceAdded,
+    ResourceRemoved,
+    CanonicalRenamed,
+    ElementAdded,
+    ElementRemoved,
+    CardinalityTightened,
+    CardinalityLoosened,
+    TypeAdded,
+    TypeRemoved,
+    BindingStrengthened,
+    BindingWeakened,
+    BindingValueSetChanged,
+    MustSupportAdded,
+    MustSupportRemoved,
+    CodeAdded,
+    CodeRemoved,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ChangeKind::ResourceAdded => "resource added",
+            ChangeKind::ResourceRemoved => "resource removed",
+            ChangeKind::CanonicalRenamed => "canonical renamed",
+            ChangeKind::ElementAdded => "element added",
+            ChangeKind::ElementRemoved => "element removed",
+            ChangeKind::CardinalityTightened => "cardinality tightened",
+            ChangeKind::CardinalityLoosened => "cardinality loosened",
+            ChangeKind::TypeAdded => "type added",
+            ChangeKind::TypeRemoved => "type removed",
+            ChangeKind::BindingStrengthened => "binding strengthened",
+            ChangeKind::BindingWeakened => "binding weakened",
+            ChangeKind::BindingValueSetChanged => "binding value set changed",
+            ChangeKind::MustSupportAdded => "must-support added",
+            ChangeKind::MustSupportRemoved => "must-support removed",
+            ChangeKind::CodeAdded => "code added",
+            ChangeKind::CodeRemoved => "code removed",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A single classified change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatChange {
+    pub compatibility: Compatibility,
+    pub kind: ChangeKind,
+    pub resource_type: String,
+    /// Canonical URL in the new version (old URL for removed resources)
+    pub url: String,
+    /// Resource name (falls back to id)
+    pub name: String,
+    /// Element id for element-level changes, code for code changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<String>,
+    pub message: String,
+}
+
+/// Result of comparing two IG versions
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatReport {
+    pub breaking: usize,
+    pub non_breaking: usize,
+    pub changes: Vec<CompatChange>,
+}
+
+impl CompatReport {
+    /// Whether any change is breaking
+    pub fn is_breaking(&self) -> bool {
+        self.breaking > 0
+    }
+
+    /// Render the report as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Compatibility report\n\n");
+        out.push_str(&format!(
+            "**{}** breaking, **{}** non-breaking changes\n",
+            self.breaking, self.non_breaking
+        ));
+
+        for (compatibility, title) in [
+            (Compatibility::Breaking, "Breaking changes"),
+            (Compatibility::NonBreaking, "Non-breaking changes"),
+        ] {
+            let changes: Vec<&CompatChange> = self
+                .changes
+                .iter()
+                .filter(|c| c.compatibility == compatibility)
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n## {}\n\n", title));
+            out.push_str("| Resource | Element | Change | Details |\n");
+            out.push_str("|----------|---------|--------|---------|\n");
+            for change in changes {
+                out.push_str(&format!(
+                    "| {} `{}` | {} | {} | {} |\n",
+                    change.resource_type,
+                    change.name,
+                    change
+                        .element
+                        .as_deref()
+                        .map(|e| format!("`{}`", e.replace('|', "\\|")))
+                        .unwrap_or_default(),
+                    change.kind,
+                    change.message.replace('|', "\\|")
+                ));
+            }
+        }
+        out
+    }
+
+    fn push(&mut self, change: CompatChange) {
+        match change.compatibility {
+            Compatibility::Breaking => self.breaking += 1,
+            Compatibility::NonBreaking => self.non_breaking += 1,
+        }
+        self.changes.push(change);
+    }
+}
+
+/// Load every StructureDefinition, ValueSet and CodeSystem below `dir`
+///
+/// Works on build output (`fsh-generated/resources`), IG output and unpacked
+/// packages alike. Files that are not JSON resources are skipped.
+pub fn load_resources(dir: &Path) -> std::io::Result<Vec<JsonValue>> {
+    let mut resources = Vec::new();
+    for entry in walkdir::WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().and_then(|e| e.to_str()) != Some("json")
+        {
+            continue;
+        }
+        let content = std::fs::read_to_string(path)?;
+        let Ok(json) = serde_json::from_str::<JsonValue>(&content) else {
+            debug!("Skipping non-JSON file {:?}", path);
+            continue;
+        };
+        if json
+            .get("resourceType")
+            .and_then(JsonValue::as_str)
+            .is_some_and(|rt| COMPARED_TYPES.contains(&rt))
+        {
+            resources.push(json);
+        }
+    }
+    Ok(resources)
+}
+
+/// Generate snapshots for StructureDefinitions that only carry a differential
+///
+/// Returns the number of snapshots generated. Definitions whose parent cannot
+/// be resolved keep their differential and are compared on it.
+pub async fn fill_snapshots(session: Arc<DefinitionSession>, resources: &mut [JsonValue]) -> usize {
+    let generator = SnapshotGenerator::new(session);
+    let mut generated = 0;
+    for resource in resources.iter_mut().filter(|r| {
+        r.get("resourceType").and_then(JsonValue::as_str) == Some("StructureDefinition")
+            && r.get("snapshot").is_none()
+    }) {
+        let sd: StructureDefinition = match serde_json::from_value(resource.clone()) {
+            Ok(sd) => sd,
+            Err(e) => {
+                warn!("Cannot read StructureDefinition: {}", e);
+                continue;
+            }
+        };
+        match generator.generate_snapshot(&sd).await {
+            Ok(elements) => {
+                resource["snapshot"] = serde_json::json!({ "element": elements });
+                generated += 1;
+            }
+            Err(e) => warn!("No snapshot for {} ({}); using the differential", sd.url, e),
+        }
+    }
+    generated
+}
+
+/// Compare two versions of an IG
+pub fn compare(old: &[JsonValue], new: &[JsonValue]) -> CompatReport {
+    let mut report = CompatReport::default();
+
+    let new_by_url: HashMap<&str, &JsonValue> =
+        new.iter().filter_map(|r| Some((url(r)?, r))).collect();
+    let old_urls: HashSet<&str> = old.iter().filter_map(url).collect();
+    let mut unmatched_new: Vec<&JsonValue> = new
+        .iter()
+        .filter(|r| url(r).is_none_or(|u| !old_urls.contains(u)))
+        .collect();
+
+    for old_resource in old {
+        let matched = url(old_resource).and_then(|u| new_by_url.get(u).copied());
+        let matched = matched.or_else(|| {
+            // Same resource under a new canonical
+            let position = unmatched_new
+                .iter()
+                .position(|candidate| same_identity(old_resource, candidate))?;
+            let renamed = unmatched_new.remove(position);
+            report.push(change(
+                Compatibility::Breaking,
+                ChangeKind::CanonicalRenamed,
+                renamed,
+                None,
+                format!(
+                    "canonical changed from {} to {}",
+                    url(old_resource).unwrap_or_default(),
+                    url(renamed).unwrap_or_default()
+                ),
+            ));
+            Some(renamed)
+        });
+
+        match matched {
+            Some(new_resource) => compare_resource(old_resource, new_resource, &mut report),
+            None => report.push(change(
+                Compatibility::Breaking,
+                ChangeKind::ResourceRemoved,
+                old_resource,
+                None,
+                "no longer published".to_string(),
+            )),
+        }
+    }
+
+    for added in unmatched_new {
+        report.push(change(
+            Compatibility::NonBreaking,
+            ChangeKind::ResourceAdded,
+            added,
+            None,
+            "new resource".to_string(),
+        ));
+    }
+
+    report
+}
+
+fn compare_resource(old: &JsonValue, new: &JsonValue, report: &mut CompatReport) {
+    match new.get("resourceType").and_then(JsonValue::as_str) {
+        Some("StructureDefinition") => compare_structure_definitions(old, new, report),
+        Some("ValueSet") => compare_codes(value_set_codes(old), value_set_codes(new), new, report),
+        Some("CodeSystem") => {
+            compare_codes(code_system_codes(old), code_system_codes(new), new, report)
+        }
+        _ => {}
+    }
+}
+
+fn compare_structure_definitions(old: &JsonValue, new: &JsonValue, report: &mut CompatReport) {
+    let old_snapshot = elements(old, "snapshot");
+    let new_snapshot = elements(new, "snapshot");
+    let (old_elements, new_elements, full) = match (old_snapshot, new_snapshot) {
+        (Some(o), Some(n)) => (o, n, true),
+        _ => (
+            elements(old, "differential").unwrap_or_default(),
+            elements(new, "differential").unwrap_or_default(),
+            false,
+        ),
+    };
+
+    let old_by_id: HashMap<&str, &JsonValue> = old_elements
+        .iter()
+        .filter_map(|e| Some((element_id(e)?, *e)))
+        .collect();
+    let new_ids: HashSet<&str> = new_elements.iter().filter_map(|e| element_id(e)).collect();
+    let unconstrained = JsonValue::Object(Default::default());
+
+    for new_element in &new_elements {
+        let Some(id) = element_id(new_element) else {
+            continue;
+        };
+        match old_by_id.get(id) {
+            Some(old_element) => compare_elements(old_element, new_element, id, new, report),
+            None if full => {
+                let min = new_element
+                    .get("min")
+                    .and_then(JsonValue::as_u64)
+                    .unwrap_or(0);
+                let (compatibility, message) = if min > 0 {
+                    (
+                        Compatibility::Breaking,
+                        format!("new required element (min {})", min),
+                    )
+                } else {
+                    (
+                        Compatibility::NonBreaking,
+                        "new optional element".to_string(),
+                    )
+                };
+                report.push(change(
+                    compatibility,
+                    ChangeKind::ElementAdded,
+                    new,
+                    Some(id),
+                    message,
+                ));
+                if must_support(new_element) {
+                    report.push(change(
+                        Compatibility::Breaking,
+                        ChangeKind::MustSupportAdded,
+                        new,
+                        Some(id),
+                        "new element is must-support".to_string(),
+                    ));
+                }
+            }
+            // Newly constrained in the differential: only flags are comparable
+            None => compare_elements(&unconstrained, new_element, id, new, report),
+        }
+    }
+
+    if full {
+        for old_element in &old_elements {
+            if let Some(id) = element_id(old_element)
+                && !new_ids.contains(id)
+            {
+                report.push(change(
+                    Compatibility::Breaking,
+                    ChangeKind::ElementRemoved,
+                    new,
+                    Some(id),
+                    "element no longer present".to_string(),
+                ));
+            }
+        }
+    }
+}
+
+fn compare_elements(
+    old: &JsonValue,
+    new: &JsonValue,
+    id: &str,
+    resource: &JsonValue,
+    report: &mut CompatReport,
+) {
+    let mut push = |compatibility, kind, message: String| {
+        report.push(change(compatibility, kind, resource, Some(id), message));
+    };
+
+    // Cardinality
+    let old_min = old.get("min").and_then(JsonValue::as_u64);
+    let new_min = new.get("min").and_then(JsonValue::as_u64);
+    let old_max = old.get("max").and_then(JsonValue::as_str).map(parse_max);
+    let new_max = new.get("max").and_then(JsonValue::as_str).map(parse_max);
+    let cardinality = |min: Option<u64>, max: Option<&str>| {
+        format!(
+            "{}..{}",
+            min.map(|m| m.to_string())
+                .unwrap_or_else(|| "?".to_string()),
+            max.unwrap_or("?")
+        )
+    };
+    let min_change = old_min.zip(new_min).map(|(o, n)| n.cmp(&o));
+    let max_change = old_max.zip(new_max).map(|(o, n)| n.cmp(&o));
+    let tightened = min_change.is_some_and(|c| c.is_gt()) || max_change.is_some_and(|c| c.is_lt());
+    let loosened = min_change.is_some_and(|c| c.is_lt()) || max_change.is_some_and(|c| c.is_gt());
+    if tightened || loosened {
+        let message = format!(
+            "{} → {}",
+            cardinality(old_min, old.get("max").and_then(JsonValue::as_str)),
+            cardinality(new_min, new.get("max").and_then(JsonValue::as_str))
+        );
+        if tightened {
+            push(
+                Compatibility::Breaking,
+                ChangeKind::CardinalityTightened,
+                message,
+            );
+        } else {
+            push(
+                Compatibility::NonBreaking,
+                ChangeKind::CardinalityLoosened,
+                message,
+            );
+        }
+    }
+
+    // Types (codes, profiles and target profiles)
+    if let (Some(old_types), Some(new_types)) = (type_set(old), type_set(new)) {
+        for removed in old_types.difference(&new_types) {
+            push(
+                Compatibility::Breaking,
+                ChangeKind::TypeRemoved,
+                format!("{} no longer allowed", removed),
+            );
+        }
+        for added in new_types.difference(&old_types) {
+            push(
+                Compatibility::NonBreaking,
+                ChangeKind::TypeAdded,
+                format!("{} now allowed", added),
+            );
+        }
+    }
+
+    // Binding
+    if let (Some(old_binding), Some(new_binding)) = (old.get("binding"), new.get("binding")) {
+        let strength = |b: &JsonValue| {
+            b.get("strength")
+                .and_then(JsonValue::as_str)
+                .and_then(|s| BINDING_STRENGTHS.iter().position(|known| *known == s))
+        };
+        if let (Some(o), Some(n)) = (strength(old_binding), strength(new_binding))
+            && o != n
+        {
+            let message = format!("{} → {}", BINDING_STRENGTHS[o], BINDING_STRENGTHS[n]);
+            if n > o {
+                push(
+                    Compatibility::Breaking,
+                    ChangeKind::BindingStrengthened,
+                    message,
+                );
+            } else {
+                push(
+                    Compatibility::NonBreaking,
+                    ChangeKind::BindingWeakened,
+                    message,
+                );
+            }
+        }
+
+        let value_set = |b: &JsonValue| {
+            b.get("valueSet")
+                .and_then(JsonValue::as_str)
+                .map(|vs| vs.split('|').next().unwrap_or(vs).to_string())
+        };
+        if let (Some(o), Some(n)) = (value_set(old_binding), value_set(new_binding))
+            && o != n
+        {
+            let enforced = strength(new_binding).is_some_and(|s| s >= 2);
+            push(
+                if enforced {
+                    Compatibility::Breaking
+                } else {
+                    Compatibility::NonBreaking
+                },
+                ChangeKind::BindingValueSetChanged,
+                format!("{} → {}", o, n),
+            );
+        }
+    }
+
+    // Must-support
+    match (must_support(old), must_support(new)) {
+        (false, true) => push(
+            Compatibility::Breaking,
+            ChangeKind::MustSupportAdded,
+            "element is now must-support".to_string(),
+        ),
+        (true, false) => push(
+            Compatibility::NonBreaking,
+            ChangeKind::MustSupportRemoved,
+            "element is no longer must-support".to_string(),
+        ),
+        _ => {}
+    }
+}
+
+fn compare_codes(
+    old: BTreeSet<String>,
+    new: BTreeSet<String>,
+    resource: &JsonValue,
+    report: &mut CompatReport,
+) {
+    for removed in old.difference(&new) {
+        report.push(change(
+            Compatibility::Breaking,
+            ChangeKind::CodeRemoved,
+            resource,
+            Some(removed),
+            "code no longer included".to_string(),
+        ));
+    }
+    for added in new.difference(&old) {
+        report.push(change(
+            Compatibility::NonBreaking,
+            ChangeKind::CodeAdded,
+            resource,
+            Some(added),
+            "new code".to_string(),
+        ));
+    }
+}
+
+/// Enumerated `system|code` members of a ValueSet, plus `system|*` and
+/// `valueset|<url>` markers for whole-system and nested-ValueSet includes
+fn value_set_codes(vs: &JsonValue) -> BTreeSet<String> {
+    let mut codes = BTreeSet::new();
+    for include in vs
+        .pointer("/compose/include")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let system = include.get("system").and_then(JsonValue::as_str);
+        match (system, include.get("concept").and_then(JsonValue::as_array)) {
+            (Some(system), Some(concepts)) => codes.extend(
+                concepts
+                    .iter()
+                    .filter_map(|c| c.get("code")?.as_str())
+                    .map(|code| format!("{}|{}", system, code)),
+            ),
+            (Some(system), None) if include.get("filter").is_none() => {
+                codes.insert(format!("{}|*", system));
+            }
+            _ => {}
+        }
+        codes.extend(
+            include
+                .get("valueSet")
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(JsonValue::as_str)
+                .map(|url| format!("valueset|{}", url)),
+        );
+    }
+
+    fn contains(items: &JsonValue, codes: &mut BTreeSet<String>) {
+        for item in items.as_array().into_iter().flatten() {
+            if let (Some(system), Some(code)) = (
+                item.get("system").and_then(JsonValue::as_str),
+                item.get("code").and_then(JsonValue::as_str),
+            ) {
+                codes.insert(format!("{}|{}", system, code));
+            }
+            if let Some(nested) = item.get("contains") {
+                contains(nested, codes);
+            }
+        }
+    }
+    if let Some(expansion) = vs.pointer("/expansion/contains") {
+        contains(expansion, &mut codes);
+    }
+    codes
+}
+
+/// Every code defined by a CodeSystem, including nested concepts
+fn code_system_codes(cs: &JsonValue) -> BTreeSet<String> {
+    fn collect(concepts: Option<&JsonValue>, codes: &mut BTreeSet<String>) {
+        for concept in concepts.and_then(JsonValue::as_array).into_iter().flatten() {
+            if let Some(code) = concept.get("code").and_then(JsonValue::as_str) {
+                codes.insert(code.to_string());
+            }
+            collect(concept.get("concept"), codes);
+        }
+    }
+    let mut codes = BTreeSet::new();
+    collect(cs.get("concept"), &mut codes);
+    codes
+}
+
+/// `code`, `code:profile` and `code->target` entries of an element's types
+fn type_set(element: &JsonValue) -> Option<BTreeSet<String>> {
+    let types = element.get("type")?.as_array()?;
+    let mut set = BTreeSet::new();
+    for ty in types {
+        let Some(code) = ty.get("code").and_then(JsonValue::as_str) else {
+            continue;
+        };
+        set.insert(code.to_string());
+        for (key, separator) in [("profile", ":"), ("targetProfile", "->")] {
+            for url in ty
+                .get(key)
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(JsonValue::as_str)
+            {
+                set.insert(format!("{}{}{}", code, separator, url));
+            }
+        }
+    }
+    Some(set)
+}
+
+fn elements<'v>(sd: &'v JsonValue, view: &str) -> Option<Vec<&'v JsonValue>> {
+    Some(sd.get(view)?.get("element")?.as_array()?.iter().collect())
+}
+
+fn element_id(element: &JsonValue) -> Option<&str> {
+    element
+        .get("id")
+        .or_else(|| element.get("path"))
+        .and_then(JsonValue::as_str)
+}
+
+fn must_support(element: &JsonValue) -> bool {
+    element.get("mustSupport").and_then(JsonValue::as_bool) == Some(true)
+}
+
+/// `*` sorts above every number
+fn parse_max(max: &str) -> u64 {
+    max.parse().unwrap_or(u64::MAX)
+}
+
+fn url(resource: &JsonValue) -> Option<&str> {
+    resource.get("url").and_then(JsonValue::as_str)
+}
+
+fn same_identity(old: &JsonValue, new: &JsonValue) -> bool {
+    let field =
+        |r: &JsonValue, name: &str| r.get(name).and_then(JsonValue::as_str).map(str::to_string);
+    field(old, "resourceType") == field(new, "resourceType")
+        && (field(old, "id").is_some() && field(old, "id") == field(new, "id")
+            || field(old, "name").is_some() && field(old, "name") == field(new, "name"))
+}
+
+fn change(
+    compatibility: Compatibility,
+    kind: ChangeKind,
+    resource: &JsonValue,
+    element: Option<&str>,
+    message: String,
+) -> CompatChange {
+    let field = |name: &str| resource.get(name).and_then(JsonValue::as_str);
+    CompatChange {
+        compatibility,
+        kind,
+        resource_type: field("resourceType").unwrap_or_default().to_string(),
+        url: field("url").unwrap_or_default().to_string(),
+        name: field("name")
+            .or_else(|| field("id"))
+            .unwrap_or_default()
+            .to_string(),
+        element: element.map(str::to_string),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn profile(url: &str, elements: JsonValue) -> JsonValue {
+        json!({
+            "resourceType": "StructureDefinition",
+            "id": "my-patient",
+            "name": "MyPatient",
+            "url": url,
+            "snapshot": { "element": elements }
+        })
+    }
+
+    fn kinds(report: &CompatReport) -> Vec<(ChangeKind, Compatibility)> {
+        report
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.compatibility))
+            .collect()
+    }
+
+    #[test]
+    fn test_element_changes_are_classified() {
+        let old = profile(
+            "http://example.org/SD/my-patient",
+            json!([
+                { "id": "Patient", "path": "Patient", "min": 0, "max": "*" },
+                { "id": "Patient.name", "path": "Patient.name", "min": 0, "max": "*" },
+                { "id": "Patient.gender", "path": "Patient.gender", "min": 1, "max": "1",
+                  "binding": { "strength": "preferred", "valueSet": "http://example.org/vs|1.0" } },
+                { "id": "Patient.link", "path": "Patient.link", "min": 0, "max": "*" },
+                { "id": "Patient.generalPractitioner", "path": "Patient.generalPractitioner",
+                  "type": [{ "code": "Reference", "targetProfile": [
+                      "http://hl7.org/fhir/StructureDefinition/Organization",
+                      "http://hl7.org/fhir/StructureDefinition/Practitioner"
+                  ]}] }
+            ]),
+        );
+        let new = profile(
+            "http://example.org/SD/my-patient",
+            json!([
+                { "id": "Patient", "path": "Patient", "min": 0, "max": "*" },
+                { "id": "Patient.name", "path": "Patient.name", "min": 1, "max": "*", "mustSupport": true },
+                { "id": "Patient.gender", "path": "Patient.gender", "min": 0, "max": "1",
+                  "binding": { "strength": "required", "valueSet": "http://example.org/vs|2.0" } },
+                { "id": "Patient.generalPractitioner", "path": "Patient.generalPractitioner",
+                  "type": [{ "code": "Reference", "targetProfile": [
+                      "http://hl7.org/fhir/StructureDefinition/Practitioner"
+                  ]}] }
+            ]),
+        );
+
+        let report = compare(&[old], &[new]);
+        let kinds = kinds(&report);
+        assert!(kinds.contains(&(ChangeKind::CardinalityTightened, Compatibility::Breaking)));
+        assert!(kinds.contains(&(ChangeKind::MustSupportAdded, Compatibility::Breaking)));
+        assert!(kinds.contains(&(ChangeKind::CardinalityLoosened, Compatibility::NonBreaking)));
+        assert!(kinds.contains(&(ChangeKind::BindingStrengthened, Compatibility::Breaking)));
+        assert!(kinds.contains(&(ChangeKind::TypeRemoved, Compatibility::Breaking)));
+        assert!(kinds.contains(&(ChangeKind::ElementRemoved, Compatibility::Breaking)));
+        // Versioned canonicals of the same ValueSet are not a change
+        assert!(
+            !kinds
+                .iter()
+                .any(|(k, _)| *k == ChangeKind::BindingValueSetChanged)
+        );
+        assert_eq!(report.breaking, 5);
+        assert_eq!(report.non_breaking, 1);
+
+        let removed = report
+            .changes
+            .iter()
+            .find(|c| c.kind == ChangeKind::TypeRemoved)
+            .unwrap();
+        assert_eq!(
+            removed.element.as_deref(),
+            Some("Patient.generalPractitioner")
+        );
+        assert!(
+            removed
+                .message
+                .contains("Reference->http://hl7.org/fhir/StructureDefinition/Organization")
+        );
+    }
+
+    #[test]
+    fn test_renamed_removed_and_added_resources() {
+        let old = vec![
+            profile("http://example.org/old/my-patient", json!([])),
+            json!({ "resourceType": "ValueSet", "id": "gone", "url": "http://example.org/vs/gone" }),
+        ];
+        let new = vec![
+            profile("http://example.org/new/my-patient", json!([])),
+            json!({ "resourceType": "CodeSystem", "id": "fresh", "url": "http://example.org/cs/fresh" }),
+        ];
+
+        let report = compare(&old, &new);
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (ChangeKind::CanonicalRenamed, Compatibility::Breaking),
+                (ChangeKind::ResourceRemoved, Compatibility::Breaking),
+                (ChangeKind::ResourceAdded, Compatibility::NonBreaking),
+            ]
+        );
+        assert_eq!(report.changes[0].url, "http://example.org/new/my-patient");
+    }
+
+    #[test]
+    fn test_code_changes() {
+        let old_cs = json!({ "resourceType": "CodeSystem", "url": "http://example.org/cs",
+            "concept": [{ "code": "a", "concept": [{ "code": "a1" }] }, { "code": "b" }] });
+        let new_cs = json!({ "resourceType": "CodeSystem", "url": "http://example.org/cs",
+            "concept": [{ "code": "a" }, { "code": "b" }, { "code": "c" }] });
+        let old_vs = json!({ "resourceType": "ValueSet", "url": "http://example.org/vs",
+            "compose": { "include": [{ "system": "http://example.org/cs", "concept": [{ "code": "a" }, { "code": "b" }] }] } });
+        let new_vs = json!({ "resourceType": "ValueSet", "url": "http://example.org/vs",
+            "compose": { "include": [{ "system": "http://example.org/cs", "concept": [{ "code": "a" }] }] } });
+
+        let report = compare(&[old_cs, old_vs], &[new_cs, new_vs]);
+        let summary: Vec<(ChangeKind, Option<&str>)> = report
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.element.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::CodeRemoved, Some("a1")),
+                (ChangeKind::CodeAdded, Some("c")),
+                (ChangeKind::CodeRemoved, Some("http://example.org/cs|b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_differential_fallback_and_markdown() {
+        let diff = |elements: JsonValue| {
+            json!({ "resourceType": "StructureDefinition", "name": "Obs",
+                "url": "http://example.org/SD/obs", "differential": { "element": elements } })
+        };
+        let old = diff(json!([{ "id": "Observation.status", "min": 1 }]));
+        let new = diff(json!([
+            { "id": "Observation.code", "mustSupport": true },
+            { "id": "Observation.status", "min": 1 }
+        ]));
+
+        let report = compare(&[old], &[new]);
+        assert_eq!(
+            kinds(&report),
+            vec![(ChangeKind::MustSupportAdded, Compatibility::Breaking)]
+        );
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("**1** breaking, **0** non-breaking"));
+        assert!(markdown.contains("## Breaking changes"));
+        assert!(
+            markdown.contains(
+                "| StructureDefinition `Obs` | `Observation.code` | must-support added |"
+            )
+        );
+        assert!(!markdown.contains("## Non-breaking changes"));
+    }
+}
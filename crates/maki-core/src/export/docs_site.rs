@@ -0,0 +1,1411 @@
+//! Static documentation site
+//!
+//! Renders a lightweight preview site (`maki docs`) from the build output
+//! without the IG Publisher. The structure follows the ImplementationGuide
+//! that [`ImplementationGuideGenerator`] produces for the same configuration:
+//! the index lists the configured `pages` tree and the artifacts of every
+//! `groups` grouping, with ungrouped artifacts listed by kind, just like the
+//! IG Publisher's artifacts page.
+//!
+//! Every StructureDefinition gets a page with its differential and snapshot
+//! element tables, bindings, invariants and examples; ValueSets and CodeSystems
+//! list their concepts; instances show their JSON. Markdown pages from
+//! `input/pagecontent/` are included. Pages are rendered to HTML or Markdown.
+
+use crate::config::SushiConfiguration;
+use crate::export::ig_generator::{ImplementationGuide, ImplementationGuideGenerator, Page};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::debug;
+
+/// Stylesheet written next to HTML pages
+const STYLESHEET: &str = "maki-docs.css";
+
+const CSS: &str = "body{font-family:system-ui,sans-serif;margin:0;color:#222}\
+header{background:#1f4e79;padding:.6em 1.5em}header a{color:#fff;font-weight:bold;text-decoration:none}\
+main{padding:1em 1.5em;max-width:80em}\
+table{border-collapse:collapse;margin:.5em 0 1.5em}\
+th,td{border:1px solid #ccc;padding:.25em .5em;text-align:left;vertical-align:top}\
+th{background:#eef3f8}code{font-size:90%}\
+pre{background:#f6f8fa;padding:.8em;overflow:auto}\
+footer{color:#777;font-size:85%;padding:1em 1.5em}";
+
+/// Artifact kinds listed for resources outside any configured group
+const ARTIFACT_KINDS: &[&str] = &[
+    "Profiles",
+    "Extensions",
+    "Logical Models",
+    "Resources",
+    "Value Sets",
+    "Code Systems",
+    "Examples",
+    "Other",
+];
+
+/// Output format of the documentation site
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsFormat {
+    Html,
+    Markdown,
+}
+
+impl DocsFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DocsFormat::Html => "html",
+            DocsFormat::Markdown => "md",
+        }
+    }
+}
+
+/// A rendered page of the site
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocsPage {
+    pub filename: String,
+    pub content: String,
+}
+
+/// Generates the documentation site from exported resources
+pub struct DocsSiteGenerator {
+    ig: ImplementationGuide,
+    resources: Vec<JsonValue>,
+    /// Page content from `input/pagecontent`, keyed by page name (no extension)
+    page_content: HashMap<String, String>,
+    /// Canonical URL → page name of local definitions
+    local_pages: HashMap<String, String>,
+}
+
+impl DocsSiteGenerator {
+    /// Create a generator for the resources of a build
+    ///
+    /// The ImplementationGuide model (groupings, pages and resource entries)
+    /// is built with [`ImplementationGuideGenerator`] from `config`.
+    pub fn new(config: SushiConfiguration, resources: Vec<JsonValue>) -> Self {
+        let mut ig_generator = ImplementationGuideGenerator::new(config);
+        let mut ig = ig_generator.generate();
+        let resources: Vec<JsonValue> = resources
+            .into_iter()
+            .filter(|r| {
+                r.get("resourceType").and_then(JsonValue::as_str) != Some("ImplementationGuide")
+            })
+            .collect();
+        for resource in &resources {
+            if let Some(entry) = ig_generator.resource_entry(resource) {
+                ig_generator.add_resource(&mut ig, entry);
+            }
+        }
+
+        let local_pages = resources
+            .iter()
+            .filter_map(|r| Some((str_field(r, "url")?.to_string(), page_name(r)?)))
+            .collect();
+
+        Self {
+            ig,
+            resources,
+            page_content: HashMap::new(),
+            local_pages,
+        }
+    }
+
+    /// Read every `*.json` resource in a directory (e.g. `fsh-generated/resources`)
+    pub fn load_resources(dir: &Path) -> std::io::Result<Vec<JsonValue>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+
+        let mut resources = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = std::fs::read_to_string(&path)?;
+            match serde_json::from_str::<JsonValue>(&content) {
+                Ok(json) if json.get("resourceType").is_some() => resources.push(json),
+                _ => debug!("Skipping {:?}: not a FHIR resource", path),
+            }
+        }
+        Ok(resources)
+    }
+
+    /// Load Markdown page content (`input/pagecontent/*.md`)
+    ///
+    /// Returns the number of pages loaded.
+    pub fn load_page_content(&mut self, dir: &Path) -> std::io::Result<usize> {
+        let mut loaded = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                self.page_content
+                    .insert(name.to_string(), std::fs::read_to_string(&path)?);
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Render every page of the site
+    pub fn generate(&self, format: DocsFormat) -> Vec<DocsPage> {
+        let mut docs = vec![self.index_doc()];
+        docs.extend(self.content_docs());
+        docs.extend(self.resources.iter().filter_map(|r| self.resource_doc(r)));
+
+        let site_title = self.site_title();
+        docs.into_iter()
+            .map(|doc| DocsPage {
+                filename: format!("{}.{}", doc.name, format.extension()),
+                content: match format {
+                    DocsFormat::Html => render_html(&doc, &site_title),
+                    DocsFormat::Markdown => render_markdown(&doc),
+                },
+            })
+            .collect()
+    }
+
+    /// Render the site into `out_dir`
+    ///
+    /// Returns the number of pages written.
+    pub fn write(&self, out_dir: &Path, format: DocsFormat) -> std::io::Result<usize> {
+        std::fs::create_dir_all(out_dir)?;
+        let pages = self.generate(format);
+        for page in &pages {
+            std::fs::write(out_dir.join(&page.filename), &page.content)?;
+        }
+        if format == DocsFormat::Html {
+            std::fs::write(out_dir.join(STYLESHEET), CSS)?;
+        }
+        Ok(pages.len())
+    }
+
+    fn site_title(&self) -> String {
+        self.ig
+            .title
+            .clone()
+            .or_else(|| self.ig.name.clone())
+            .unwrap_or_else(|| "Implementation Guide".to_string())
+    }
+
+    // === Index ===
+
+    fn index_doc(&self) -> Doc {
+        let mut doc = Doc::new("index", self.site_title());
+
+        if let Some(content) = self.page_content.get("index") {
+            doc.push(Block::Markdown(content.clone()));
+        } else if let Some(description) = &self.ig.description {
+            doc.push(Block::Paragraph(vec![Inline::text(description)]));
+        }
+        doc.push(Block::Table {
+            header: vec!["Canonical", "Version", "Status", "FHIR Version"],
+            rows: vec![vec![
+                vec![Inline::code(&self.ig.url)],
+                vec![Inline::text(self.ig.version.as_deref().unwrap_or("-"))],
+                vec![Inline::text(&self.ig.status)],
+                vec![Inline::text(&self.ig.fhir_version.join(", "))],
+            ]],
+        });
+
+        let pages: Vec<ListItem> = self
+            .ig
+            .definition
+            .page
+            .page
+            .iter()
+            .flatten()
+            .map(|page| self.page_item(page))
+            .collect();
+        if !pages.is_empty() {
+            doc.push(Block::Heading(2, "Pages".to_string()));
+            doc.push(Block::List(pages));
+        }
+
+        doc.push(Block::Heading(2, "Artifacts".to_string()));
+        let entries = &self.ig.definition.resource;
+        for grouping in self.ig.definition.grouping.iter().flatten() {
+            let members: Vec<&JsonValue> = entries
+                .iter()
+                .filter(|e| e.grouping_id.as_deref() == Some(grouping.id.as_str()))
+                .filter_map(|e| self.resource_by_reference(&e.reference.reference))
+                .collect();
+            doc.push(Block::Heading(3, grouping.name.clone()));
+            if let Some(description) = &grouping.description {
+                doc.push(Block::Paragraph(vec![Inline::text(description)]));
+            }
+            doc.push(self.artifact_table(&members));
+        }
+
+        let grouped: HashSet<&str> = entries
+            .iter()
+            .filter(|e| e.grouping_id.is_some())
+            .map(|e| e.reference.reference.as_str())
+            .collect();
+        for kind in ARTIFACT_KINDS {
+            let members: Vec<&JsonValue> = self
+                .resources
+                .iter()
+                .filter(|r| artifact_kind(r) == *kind)
+                .filter(|r| {
+                    reference(r).is_none_or(|reference| !grouped.contains(reference.as_str()))
+                })
+                .collect();
+            if !members.is_empty() {
+                doc.push(Block::Heading(3, kind.to_string()));
+                doc.push(self.artifact_table(&members));
+            }
+        }
+        doc
+    }
+
+    fn page_item(&self, page: &Page) -> ListItem {
+        let name = page
+            .name_url
+            .as_deref()
+            .map(|url| url.trim_end_matches(".html").to_string());
+        let content = match name {
+            Some(name) => vec![Inline::page(&page.title, &name)],
+            None => vec![Inline::text(&page.title)],
+        };
+        ListItem {
+            content,
+            children: page
+                .page
+                .iter()
+                .flatten()
+                .map(|child| self.page_item(child))
+                .collect(),
+        }
+    }
+
+    fn artifact_table(&self, resources: &[&JsonValue]) -> Block {
+        let entries: HashMap<&str, &crate::export::ig_generator::ResourceEntry> = self
+            .ig
+            .definition
+            .resource
+            .iter()
+            .map(|e| (e.reference.reference.as_str(), e))
+            .collect();
+        Block::Table {
+            header: vec!["Name", "Type", "Description"],
+            rows: resources
+                .iter()
+                .filter_map(|r| {
+                    let page = page_name(r)?;
+                    let entry =
+                        reference(r).and_then(|reference| entries.get(reference.as_str()).copied());
+                    let title = entry
+                        .and_then(|e| e.name.clone())
+                        .unwrap_or_else(|| display_name(r));
+                    let description = entry
+                        .and_then(|e| e.description.clone())
+                        .unwrap_or_default();
+                    Some(vec![
+                        vec![Inline::page(&title, &page)],
+                        vec![Inline::text(
+                            str_field(r, "resourceType").unwrap_or_default(),
+                        )],
+                        vec![Inline::text(first_line(&description))],
+                    ])
+                })
+                .collect(),
+        }
+    }
+
+    fn resource_by_reference(&self, reference_str: &str) -> Option<&JsonValue> {
+        self.resources
+            .iter()
+            .find(|r| reference(r).as_deref() == Some(reference_str))
+    }
+
+    // === Content pages ===
+
+    fn content_docs(&self) -> Vec<Doc> {
+        fn collect<'p>(pages: &'p [Page], out: &mut Vec<&'p Page>) {
+            for page in pages {
+                out.push(page);
+                collect(page.page.as_deref().unwrap_or_default(), out);
+            }
+        }
+        let mut pages = Vec::new();
+        collect(
+            self.ig.definition.page.page.as_deref().unwrap_or_default(),
+            &mut pages,
+        );
+
+        pages
+            .into_iter()
+            .filter_map(|page| {
+                let name = page.name_url.as_deref()?.trim_end_matches(".html");
+                if name == "index" {
+                    return None;
+                }
+                let mut doc = Doc::new(name, page.title.clone());
+                match self.page_content.get(name) {
+                    Some(content) => doc.push(Block::Markdown(content.clone())),
+                    None => doc.push(Block::Paragraph(vec![Inline::text(
+                        "This page has no Markdown content in input/pagecontent.",
+                    )])),
+                }
+                Some(doc)
+            })
+            .collect()
+    }
+
+    // === Resource pages ===
+
+    fn resource_doc(&self, resource: &JsonValue) -> Option<Doc> {
+        let name = page_name(resource)?;
+        let mut doc = Doc::new(&name, display_name(resource));
+        if let Some(description) = str_field(resource, "description") {
+            doc.push(Block::Markdown(description.to_string()));
+        }
+
+        match str_field(resource, "resourceType")? {
+            "StructureDefinition" => self.structure_definition(resource, &mut doc),
+            "ValueSet" => self.value_set(resource, &mut doc),
+            "CodeSystem" => self.code_system(resource, &mut doc),
+            _ => self.instance(resource, &mut doc),
+        }
+        Some(doc)
+    }
+
+    fn metadata(&self, resource: &JsonValue, extra: Vec<(&'static str, Line)>) -> Block {
+        let mut rows = Vec::new();
+        for (label, field) in [("URL", "url"), ("Version", "version"), ("Status", "status")] {
+            if let Some(value) = str_field(resource, field) {
+                rows.push(vec![vec![Inline::strong(label)], vec![Inline::code(value)]]);
+            }
+        }
+        for (label, line) in extra {
+            rows.push(vec![vec![Inline::strong(label)], line]);
+        }
+        Block::Table {
+            header: vec!["", ""],
+            rows,
+        }
+    }
+
+    fn structure_definition(&self, sd: &JsonValue, doc: &mut Doc) {
+        let mut extra = Vec::new();
+        if let Some(sd_type) = str_field(sd, "type") {
+            extra.push(("Type", vec![Inline::text(sd_type)]));
+        }
+        if let Some(parent) = str_field(sd, "baseDefinition") {
+            extra.push(("Parent", self.canonical_link(parent)));
+        }
+        doc.push(self.metadata(sd, extra));
+
+        let differential = elements(sd, "differential");
+        let snapshot = elements(sd, "snapshot");
+        if !differential.is_empty() {
+            doc.push(Block::Heading(2, "Differential".to_string()));
+            doc.push(self.element_table(&differential));
+        }
+        if !snapshot.is_empty() {
+            doc.push(Block::Heading(2, "Snapshot".to_string()));
+            doc.push(self.element_table(&snapshot));
+        }
+
+        // Bindings and invariants come from the fullest view available
+        let view = if snapshot.is_empty() {
+            &differential
+        } else {
+            &snapshot
+        };
+        let bindings: Vec<Vec<Line>> = view
+            .iter()
+            .filter_map(|element| {
+                let binding = element.get("binding")?;
+                Some(vec![
+                    vec![Inline::code(element_label(element))],
+                    vec![Inline::text(
+                        str_field(binding, "strength").unwrap_or_default(),
+                    )],
+                    str_field(binding, "valueSet")
+                        .map(|vs| self.canonical_link(vs))
+                        .unwrap_or_default(),
+                ])
+            })
+            .collect();
+        if !bindings.is_empty() {
+            doc.push(Block::Heading(2, "Bindings".to_string()));
+            doc.push(Block::Table {
+                header: vec!["Path", "Strength", "Value Set"],
+                rows: bindings,
+            });
+        }
+
+        let url = str_field(sd, "url").unwrap_or_default();
+        let mut seen = HashSet::new();
+        let invariants: Vec<Vec<Line>> = differential
+            .iter()
+            .chain(snapshot.iter())
+            .flat_map(|element| {
+                element
+                    .get("constraint")
+                    .and_then(JsonValue::as_array)
+                    .into_iter()
+                    .flatten()
+                    .map(move |constraint| (*element, constraint))
+            })
+            .filter(|(_, constraint)| {
+                // Snapshot constraints inherited from the base are not this profile's
+                str_field(constraint, "source").is_none_or(|source| source == url)
+            })
+            .filter(|(_, constraint)| seen.insert(str_field(constraint, "key").unwrap_or_default()))
+            .map(|(element, constraint)| {
+                let field = |name: &str| str_field(constraint, name).unwrap_or_default();
+                vec![
+                    vec![Inline::code(field("key"))],
+                    vec![Inline::code(element_label(element))],
+                    vec![Inline::text(field("severity"))],
+                    vec![Inline::text(field("human"))],
+                    vec![Inline::code(field("expression"))],
+                ]
+            })
+            .collect();
+        if !invariants.is_empty() {
+            doc.push(Block::Heading(2, "Invariants".to_string()));
+            doc.push(Block::Table {
+                header: vec!["Key", "Path", "Severity", "Description", "Expression"],
+                rows: invariants,
+            });
+        }
+
+        let examples: Vec<ListItem> = self
+            .ig
+            .definition
+            .resource
+            .iter()
+            .filter(|entry| entry.example_canonical.as_deref() == Some(url))
+            .filter_map(|entry| self.resource_by_reference(&entry.reference.reference))
+            .filter_map(|example| {
+                Some(ListItem::new(vec![Inline::page(
+                    &display_name(example),
+                    &page_name(example)?,
+                )]))
+            })
+            .collect();
+        if !examples.is_empty() {
+            doc.push(Block::Heading(2, "Examples".to_string()));
+            doc.push(Block::List(examples));
+        }
+    }
+
+    fn element_table(&self, elements: &[&JsonValue]) -> Block {
+        Block::Table {
+            header: vec![
+                "Element",
+                "Flags",
+                "Card.",
+                "Type",
+                "Description & Constraints",
+            ],
+            rows: elements
+                .iter()
+                .map(|element| {
+                    let mut flags = Vec::new();
+                    if element.get("mustSupport").and_then(JsonValue::as_bool) == Some(true) {
+                        flags.push("S");
+                    }
+                    if element.get("isModifier").and_then(JsonValue::as_bool) == Some(true) {
+                        flags.push("?!");
+                    }
+                    if element.get("isSummary").and_then(JsonValue::as_bool) == Some(true) {
+                        flags.push("Σ");
+                    }
+                    let cardinality = match (element.get("min"), str_field(element, "max")) {
+                        (None, None) => String::new(),
+                        (min, max) => format!(
+                            "{}..{}",
+                            min.and_then(JsonValue::as_u64)
+                                .map(|m| m.to_string())
+                                .unwrap_or_default(),
+                            max.unwrap_or_default()
+                        ),
+                    };
+                    vec![
+                        vec![Inline::code(element_label(element))],
+                        vec![Inline::text(&flags.join(" "))],
+                        vec![Inline::text(&cardinality)],
+                        self.type_line(element),
+                        self.element_description(element),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn type_line(&self, element: &JsonValue) -> Line {
+        let mut line = Vec::new();
+        for (i, ty) in element
+            .get("type")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            if i > 0 {
+                line.push(Inline::text(" | "));
+            }
+            let code = str_field(ty, "code").unwrap_or_default();
+            let targets: Vec<&str> = ["profile", "targetProfile"]
+                .iter()
+                .flat_map(|key| {
+                    ty.get(*key)
+                        .and_then(JsonValue::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(JsonValue::as_str)
+                })
+                .collect();
+            line.push(Inline::text(code));
+            if !targets.is_empty() {
+                line.push(Inline::text("("));
+                for (j, target) in targets.iter().enumerate() {
+                    if j > 0 {
+                        line.push(Inline::text(" | "));
+                    }
+                    line.extend(self.canonical_link(target));
+                }
+                line.push(Inline::text(")"));
+            }
+        }
+        line
+    }
+
+    fn element_description(&self, element: &JsonValue) -> Line {
+        let mut line = Vec::new();
+        if let Some(short) = str_field(element, "short") {
+            line.push(Inline::text(short));
+        }
+        if let Some(slicing) = element.get("slicing") {
+            let discriminators: Vec<String> = slicing
+                .get("discriminator")
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+                .map(|d| {
+                    format!(
+                        "{}:{}",
+                        str_field(d, "type").unwrap_or_default(),
+                        str_field(d, "path").unwrap_or_default()
+                    )
+                })
+                .collect();
+            line.push(Inline::text(&format!(
+                " Slice: {} ({})",
+                discriminators.join(", "),
+                str_field(slicing, "rules").unwrap_or("open")
+            )));
+        }
+        if let Some(map) = element.as_object() {
+            for (key, value) in map {
+                let label = if key.starts_with("fixed") {
+                    "Fixed"
+                } else if key.starts_with("pattern") {
+                    "Pattern"
+                } else {
+                    continue;
+                };
+                line.push(Inline::text(&format!(" {}: ", label)));
+                line.push(Inline::code(&compact_value(value)));
+            }
+        }
+        if let Some(binding) = element.get("binding")
+            && let Some(vs) = str_field(binding, "valueSet")
+        {
+            line.push(Inline::text(&format!(
+                " Binding ({}): ",
+                str_field(binding, "strength").unwrap_or_default()
+            )));
+            line.extend(self.canonical_link(vs));
+        }
+        line
+    }
+
+    fn value_set(&self, vs: &JsonValue, doc: &mut Doc) {
+        doc.push(self.metadata(vs, Vec::new()));
+
+        for (key, title) in [("include", "Included"), ("exclude", "Excluded")] {
+            let components: Vec<&JsonValue> = vs
+                .pointer(&format!("/compose/{}", key))
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+                .collect();
+            if components.is_empty() {
+                continue;
+            }
+            doc.push(Block::Heading(2, format!("{} Concepts", title)));
+            for component in components {
+                let system = str_field(component, "system");
+                let mut intro = Vec::new();
+                if let Some(system) = system {
+                    intro.push(Inline::text("From "));
+                    intro.extend(self.canonical_link(system));
+                }
+                for vs_url in component
+                    .get("valueSet")
+                    .and_then(JsonValue::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(JsonValue::as_str)
+                {
+                    intro.push(Inline::text(if intro.is_empty() { "From " } else { ", " }));
+                    intro.extend(self.canonical_link(vs_url));
+                }
+                for filter in component
+                    .get("filter")
+                    .and_then(JsonValue::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    intro.push(Inline::text(" where "));
+                    intro.push(Inline::code(&format!(
+                        "{} {} {}",
+                        str_field(filter, "property").unwrap_or_default(),
+                        str_field(filter, "op").unwrap_or_default(),
+                        str_field(filter, "value").unwrap_or_default()
+                    )));
+                }
+                if !intro.is_empty() {
+                    doc.push(Block::Paragraph(intro));
+                }
+
+                let concepts: Vec<Vec<Line>> = component
+                    .get("concept")
+                    .and_then(JsonValue::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|concept| {
+                        let code = str_field(concept, "code")?;
+                        let display = str_field(concept, "display")
+                            .map(String::from)
+                            .or_else(|| self.local_display(system?, code))
+                            .unwrap_or_default();
+                        Some(vec![vec![Inline::code(code)], vec![Inline::text(&display)]])
+                    })
+                    .collect();
+                if !concepts.is_empty() {
+                    doc.push(Block::Table {
+                        header: vec!["Code", "Display"],
+                        rows: concepts,
+                    });
+                }
+            }
+        }
+
+        let mut expansion = Vec::new();
+        collect_contains(vs.pointer("/expansion/contains"), &mut expansion);
+        if !expansion.is_empty() {
+            doc.push(Block::Heading(2, "Expansion".to_string()));
+            doc.push(Block::Table {
+                header: vec!["System", "Code", "Display"],
+                rows: expansion,
+            });
+        }
+    }
+
+    fn code_system(&self, cs: &JsonValue, doc: &mut Doc) {
+        let mut extra = Vec::new();
+        if let Some(content) = str_field(cs, "content") {
+            extra.push(("Content", vec![Inline::text(content)]));
+        }
+        doc.push(self.metadata(cs, extra));
+
+        let mut rows = Vec::new();
+        collect_concepts(cs.get("concept"), 0, &mut rows);
+        if !rows.is_empty() {
+            doc.push(Block::Heading(2, "Concepts".to_string()));
+            doc.push(Block::Table {
+                header: vec!["Code", "Display", "Definition"],
+                rows,
+            });
+        }
+    }
+
+    fn instance(&self, resource: &JsonValue, doc: &mut Doc) {
+        let mut extra = vec![(
+            "Type",
+            vec![Inline::text(
+                str_field(resource, "resourceType").unwrap_or_default(),
+            )],
+        )];
+        for profile in resource
+            .pointer("/meta/profile")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(JsonValue::as_str)
+        {
+            extra.push(("Profile", self.canonical_link(profile)));
+        }
+        doc.push(self.metadata(resource, extra));
+        doc.push(Block::Code(
+            serde_json::to_string_pretty(resource).unwrap_or_default(),
+        ));
+    }
+
+    /// Link to the local page of a canonical, or the canonical itself
+    fn canonical_link(&self, canonical: &str) -> Line {
+        let unversioned = canonical.split('|').next().unwrap_or(canonical);
+        match self.local_pages.get(unversioned) {
+            Some(page) => {
+                let label = self
+                    .resources
+                    .iter()
+                    .find(|r| str_field(r, "url") == Some(unversioned))
+                    .map(display_name)
+                    .unwrap_or_else(|| canonical.to_string());
+                vec![Inline::page(&label, page)]
+            }
+            None => {
+                let label = unversioned.rsplit('/').next().unwrap_or(canonical);
+                if canonical.starts_with("http") {
+                    vec![Inline::Link {
+                        text: label.to_string(),
+                        href: canonical.to_string(),
+                    }]
+                } else {
+                    vec![Inline::code(canonical)]
+                }
+            }
+        }
+    }
+
+    /// Display of a code in a local CodeSystem
+    fn local_display(&self, system: &str, code: &str) -> Option<String> {
+        fn find(concepts: Option<&JsonValue>, code: &str) -> Option<String> {
+            concepts?.as_array()?.iter().find_map(|concept| {
+                if str_field(concept, "code") == Some(code) {
+                    str_field(concept, "display").map(String::from)
+                } else {
+                    find(concept.get("concept"), code)
+                }
+            })
+        }
+        let cs = self.resources.iter().find(|r| {
+            str_field(r, "resourceType") == Some("CodeSystem")
+                && str_field(r, "url") == Some(system)
+        })?;
+        find(cs.get("concept"), code)
+    }
+}
+
+// === Document model ===
+
+/// Inline content of a paragraph, list item or table cell
+#[derive(Debug, Clone, PartialEq)]
+enum Inline {
+    Text(String),
+    Code(String),
+    Strong(String),
+    /// Link to another page of the site (name without extension)
+    Page {
+        text: String,
+        page: String,
+    },
+    Link {
+        text: String,
+        href: String,
+    },
+}
+
+impl Inline {
+    fn text(text: &str) -> Self {
+        Inline::Text(text.to_string())
+    }
+
+    fn code(text: &str) -> Self {
+        Inline::Code(text.to_string())
+    }
+
+    fn strong(text: &str) -> Self {
+        Inline::Strong(text.to_string())
+    }
+
+    fn page(text: &str, page: &str) -> Self {
+        Inline::Page {
+            text: text.to_string(),
+            page: page.to_string(),
+        }
+    }
+}
+
+type Line = Vec<Inline>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct ListItem {
+    content: Line,
+    children: Vec<ListItem>,
+}
+
+impl ListItem {
+    fn new(content: Line) -> Self {
+        Self {
+            content,
+            children: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading(u8, String),
+    Paragraph(Line),
+    List(Vec<ListItem>),
+    Table {
+        header: Vec<&'static str>,
+        rows: Vec<Vec<Line>>,
+    },
+    Code(String),
+    /// Markdown source (page content, descriptions)
+    Markdown(String),
+}
+
+struct Doc {
+    name: String,
+    title: String,
+    blocks: Vec<Block>,
+}
+
+impl Doc {
+    fn new(name: &str, title: String) -> Self {
+        Self {
+            name: name.to_string(),
+            title,
+            blocks: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, block: Block) {
+        self.blocks.push(block);
+    }
+}
+
+// === HTML ===
+
+fn render_html(doc: &Doc, site_title: &str) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape(&doc.title));
+    for block in &doc.blocks {
+        match block {
+            Block::Heading(level, text) => {
+                body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(text)));
+            }
+            Block::Paragraph(line) => body.push_str(&format!("<p>{}</p>\n", html_line(line))),
+            Block::List(items) => html_list(items, &mut body),
+            Block::Table { header, rows } => {
+                body.push_str("<table>\n");
+                if header.iter().any(|h| !h.is_empty()) {
+                    body.push_str("<tr>");
+                    for cell in header {
+                        body.push_str(&format!("<th>{}</th>", escape(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                for row in rows {
+                    body.push_str("<tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", html_line(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("</table>\n");
+            }
+            Block::Code(code) => body.push_str(&format!("<pre>{}</pre>\n", escape(code))),
+            Block::Markdown(markdown) => body.push_str(&markdown_to_html(markdown)),
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title} - {site}</title>\n<link rel=\"stylesheet\" href=\"{css}\">\n</head>\n<body>\n\
+         <header><a href=\"index.html\">{site}</a></header>\n<main>\n{body}</main>\n\
+         <footer>Preview generated by MAKI {version}</footer>\n</body>\n</html>\n",
+        title = escape(&doc.title),
+        site = escape(site_title),
+        css = STYLESHEET,
+        body = body,
+        version = crate::VERSION,
+    )
+}
+
+fn html_line(line: &[Inline]) -> String {
+    line.iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => escape(text),
+            Inline::Code(text) => format!("<code>{}</code>", escape(text)),
+            Inline::Strong(text) => format!("<b>{}</b>", escape(text)),
+            Inline::Page { text, page } => {
+                format!("<a href=\"{}.html\">{}</a>", escape(page), escape(text))
+            }
+            Inline::Link { text, href } => {
+                format!("<a href=\"{}\">{}</a>", escape(href), escape(text))
+            }
+        })
+        .collect()
+}
+
+fn html_list(items: &[ListItem], out: &mut String) {
+    out.push_str("<ul>\n");
+    for item in items {
+        out.push_str(&format!("<li>{}", html_line(&item.content)));
+        if !item.children.is_empty() {
+            html_list(&item.children, out);
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ul>\n");
+}
+
+/// Minimal Markdown → HTML for page content: headings, lists, fenced code and
+/// paragraphs with inline code, bold and links
+fn markdown_to_html(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_list = false;
+    let mut in_code = false;
+
+    let flush = |paragraph: &mut Vec<&str>, in_list: &mut bool, out: &mut String| {
+        if !paragraph.is_empty() {
+            out.push_str(&format!(
+                "<p>{}</p>\n",
+                markdown_inline(&paragraph.join(" "))
+            ));
+            paragraph.clear();
+        }
+        if *in_list {
+            out.push_str("</ul>\n");
+            *in_list = false;
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if in_code {
+                out.push_str("</pre>\n");
+            } else {
+                flush(&mut paragraph, &mut in_list, &mut out);
+                out.push_str("<pre>");
+            }
+            in_code = !in_code;
+        } else if in_code {
+            out.push_str(&escape(line));
+            out.push('\n');
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut in_list, &mut out);
+        } else if let Some(level) = heading_level(trimmed) {
+            flush(&mut paragraph, &mut in_list, &mut out);
+            out.push_str(&format!(
+                "<h{0}>{1}</h{0}>\n",
+                level + 1,
+                markdown_inline(trimmed[level..].trim())
+            ));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            if !paragraph.is_empty() {
+                flush(&mut paragraph, &mut in_list, &mut out);
+            }
+            if !in_list {
+                out.push_str("<ul>\n");
+                in_list = true;
+            }
+            out.push_str(&format!("<li>{}</li>\n", markdown_inline(item)));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    if in_code {
+        out.push_str("</pre>\n");
+    }
+    flush(&mut paragraph, &mut in_list, &mut out);
+    out
+}
+
+/// Number of leading `#` of an ATX heading (page headings are demoted one level)
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (1..=5)
+        .contains(&level)
+        .then_some(level)
+        .filter(|&l| line[l..].starts_with(' '))
+}
+
+fn markdown_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`'
+            && let Some(end) = rest[1..].find('`')
+        {
+            out.push_str(&format!("<code>{}</code>", escape(&rest[1..=end])));
+            rest = &rest[end + 2..];
+        } else if rest.starts_with("**")
+            && let Some(end) = rest[2..].find("**")
+        {
+            out.push_str(&format!("<b>{}</b>", markdown_inline(&rest[2..end + 2])));
+            rest = &rest[end + 4..];
+        } else if c == '['
+            && let Some(close) = rest.find("](")
+            && let Some(end) = rest[close..].find(')')
+        {
+            let href = &rest[close + 2..close + end];
+            out.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                escape(href),
+                markdown_inline(&rest[1..close])
+            ));
+            rest = &rest[close + end + 1..];
+        } else {
+            out.push_str(&escape(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+// === Markdown ===
+
+fn render_markdown(doc: &Doc) -> String {
+    let mut out = format!("# {}\n", doc.title);
+    for block in &doc.blocks {
+        out.push('\n');
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&format!("{} {}\n", "#".repeat(*level as usize), text));
+            }
+            Block::Paragraph(line) => out.push_str(&format!("{}\n", markdown_line(line))),
+            Block::List(items) => markdown_list(items, 0, &mut out),
+            Block::Table { header, rows } => {
+                out.push_str(&format!("| {} |\n", header.join(" | ")));
+                out.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+                for row in rows {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|cell| markdown_line(cell).replace('|', "\\|"))
+                        .collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+            Block::Code(code) => out.push_str(&format!("```json\n{}\n```\n", code)),
+            Block::Markdown(markdown) => {
+                out.push_str(markdown.trim_end());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn markdown_line(line: &[Inline]) -> String {
+    line.iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => text.replace('\n', " "),
+            Inline::Code(text) => format!("`{}`", text),
+            Inline::Strong(text) => format!("**{}**", text),
+            Inline::Page { text, page } => format!("[{}]({}.md)", text, page),
+            Inline::Link { text, href } => format!("[{}]({})", text, href),
+        })
+        .collect()
+}
+
+fn markdown_list(items: &[ListItem], depth: usize, out: &mut String) {
+    for item in items {
+        out.push_str(&format!(
+            "{}- {}\n",
+            "  ".repeat(depth),
+            markdown_line(&item.content)
+        ));
+        markdown_list(&item.children, depth + 1, out);
+    }
+}
+
+// === Helpers ===
+
+fn str_field<'v>(value: &'v JsonValue, name: &str) -> Option<&'v str> {
+    value.get(name).and_then(JsonValue::as_str)
+}
+
+/// `ResourceType/id` reference of a resource
+fn reference(resource: &JsonValue) -> Option<String> {
+    Some(format!(
+        "{}/{}",
+        str_field(resource, "resourceType")?,
+        str_field(resource, "id")?
+    ))
+}
+
+/// Page name of a resource, following the IG Publisher's `ResourceType-id` convention
+fn page_name(resource: &JsonValue) -> Option<String> {
+    Some(format!(
+        "{}-{}",
+        str_field(resource, "resourceType")?,
+        str_field(resource, "id")?
+    ))
+}
+
+fn display_name(resource: &JsonValue) -> String {
+    str_field(resource, "title")
+        .or_else(|| str_field(resource, "name"))
+        .or_else(|| str_field(resource, "id"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+/// Artifact kind of a resource, as used for ungrouped artifacts
+fn artifact_kind(resource: &JsonValue) -> &'static str {
+    match str_field(resource, "resourceType") {
+        Some("StructureDefinition") => {
+            match (
+                str_field(resource, "kind"),
+                str_field(resource, "derivation"),
+            ) {
+                (Some("logical"), _) => "Logical Models",
+                (_, Some("constraint")) if str_field(resource, "type") == Some("Extension") => {
+                    "Extensions"
+                }
+                (_, Some("constraint")) => "Profiles",
+                _ => "Resources",
+            }
+        }
+        Some("ValueSet") => "Value Sets",
+        Some("CodeSystem") => "Code Systems",
+        Some(
+            "ConceptMap"
+            | "CapabilityStatement"
+            | "SearchParameter"
+            | "OperationDefinition"
+            | "NamingSystem",
+        ) => "Other",
+        Some(_) => "Examples",
+        None => "Other",
+    }
+}
+
+fn elements<'v>(sd: &'v JsonValue, view: &str) -> Vec<&'v JsonValue> {
+    sd.get(view)
+        .and_then(|v| v.get("element"))
+        .and_then(JsonValue::as_array)
+        .map(|elements| elements.iter().collect())
+        .unwrap_or_default()
+}
+
+fn element_label(element: &JsonValue) -> &str {
+    str_field(element, "id")
+        .or_else(|| str_field(element, "path"))
+        .unwrap_or_default()
+}
+
+/// Short single-line rendering of a fixed/pattern value
+fn compact_value(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn collect_concepts(concepts: Option<&JsonValue>, depth: usize, rows: &mut Vec<Vec<Line>>) {
+    for concept in concepts.and_then(JsonValue::as_array).into_iter().flatten() {
+        let field = |name: &str| str_field(concept, name).unwrap_or_default();
+        let mut code = Vec::new();
+        if depth > 0 {
+            code.push(Inline::text(&format!("{} ", "›".repeat(depth))));
+        }
+        code.push(Inline::code(field("code")));
+        rows.push(vec![
+            code,
+            vec![Inline::text(field("display"))],
+            vec![Inline::text(field("definition"))],
+        ]);
+        collect_concepts(concept.get("concept"), depth + 1, rows);
+    }
+}
+
+fn collect_contains(contains: Option<&JsonValue>, rows: &mut Vec<Vec<Line>>) {
+    for item in contains.and_then(JsonValue::as_array).into_iter().flatten() {
+        if let Some(code) = str_field(item, "code") {
+            rows.push(vec![
+                vec![Inline::text(str_field(item, "system").unwrap_or_default())],
+                vec![Inline::code(code)],
+                vec![Inline::text(str_field(item, "display").unwrap_or_default())],
+            ]);
+        }
+        collect_contains(item.get("contains"), rows);
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> SushiConfiguration {
+        let yaml = r#"
+id: example.ig
+canonical: http://example.org
+name: ExampleIG
+title: Example IG
+status: draft
+version: 1.0.0
+fhirVersion: 4.0.1
+groups:
+  - id: patients
+    name: Patient Profiles
+    description: Profiles for patients
+    resources:
+      - StructureDefinition/my-patient
+pages:
+  index.md:
+    title: Home
+  background.md:
+    title: Background
+"#;
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn resources() -> Vec<JsonValue> {
+        vec![
+            json!({
+                "resourceType": "StructureDefinition",
+                "id": "my-patient",
+                "url": "http://example.org/StructureDefinition/my-patient",
+                "name": "MyPatient",
+                "title": "My Patient",
+                "kind": "resource",
+                "type": "Patient",
+                "derivation": "constraint",
+                "baseDefinition": "http://hl7.org/fhir/StructureDefinition/Patient",
+                "differential": { "element": [
+                    { "id": "Patient.gender", "path": "Patient.gender", "min": 1, "mustSupport": true,
+                      "binding": { "strength": "required", "valueSet": "http://example.org/ValueSet/genders" },
+                      "constraint": [{ "key": "pat-1", "severity": "error", "human": "Must be < 3",
+                                       "expression": "true", "source": "http://example.org/StructureDefinition/my-patient" }] },
+                    { "id": "Patient.generalPractitioner", "path": "Patient.generalPractitioner",
+                      "type": [{ "code": "Reference", "targetProfile": ["http://hl7.org/fhir/StructureDefinition/Practitioner"] }] }
+                ]}
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "id": "genders",
+                "url": "http://example.org/ValueSet/genders",
+                "name": "Genders",
+                "compose": { "include": [{ "system": "http://example.org/CodeSystem/genders", "concept": [{ "code": "f" }] }] }
+            }),
+            json!({
+                "resourceType": "CodeSystem",
+                "id": "genders",
+                "url": "http://example.org/CodeSystem/genders",
+                "name": "GenderCodes",
+                "concept": [{ "code": "f", "display": "Female", "concept": [{ "code": "f2", "display": "Sub" }] }]
+            }),
+            json!({
+                "resourceType": "Patient",
+                "id": "jane",
+                "meta": { "profile": ["http://example.org/StructureDefinition/my-patient"] }
+            }),
+        ]
+    }
+
+    fn page<'p>(pages: &'p [DocsPage], filename: &str) -> &'p str {
+        &pages
+            .iter()
+            .find(|p| p.filename == filename)
+            .unwrap_or_else(|| panic!("missing page {}", filename))
+            .content
+    }
+
+    #[test]
+    fn test_index_follows_groups_and_pages() {
+        let mut generator = DocsSiteGenerator::new(config(), resources());
+        generator
+            .page_content
+            .insert("index".to_string(), "Welcome to **Example**.".to_string());
+        let pages = generator.generate(DocsFormat::Markdown);
+
+        let index = page(&pages, "index.md");
+        assert!(index.starts_with("# Example IG\n"));
+        assert!(index.contains("Welcome to **Example**."));
+        assert!(index.contains("- [Home](index.md)\n- [Background](background.md)"));
+        assert!(index.contains("### Patient Profiles\n\nProfiles for patients"));
+        assert!(
+            index.contains(
+                "| [My Patient](StructureDefinition-my-patient.md) | StructureDefinition |"
+            )
+        );
+        // Grouped resources are not repeated under their kind
+        assert!(!index.contains("### Profiles"));
+        assert!(index.contains("### Value Sets"));
+        assert!(index.contains("### Examples\n"));
+
+        assert!(page(&pages, "background.md").contains("no Markdown content"));
+    }
+
+    #[test]
+    fn test_profile_page() {
+        let generator = DocsSiteGenerator::new(config(), resources());
+        let pages = generator.generate(DocsFormat::Markdown);
+        let profile = page(&pages, "StructureDefinition-my-patient.md");
+
+        assert!(profile.contains("## Differential"));
+        assert!(profile.contains("| `Patient.gender` | S | 1.. |"));
+        assert!(profile.contains(
+            "Reference([Practitioner](http://hl7.org/fhir/StructureDefinition/Practitioner))"
+        ));
+        assert!(profile.contains("## Bindings\n\n| Path | Strength | Value Set |"));
+        assert!(
+            profile.contains("| `Patient.gender` | required | [Genders](ValueSet-genders.md) |")
+        );
+        assert!(profile.contains("| `pat-1` | `Patient.gender` | error | Must be < 3 | `true` |"));
+        assert!(profile.contains("## Examples\n\n- [jane](Patient-jane.md)"));
+    }
+
+    #[test]
+    fn test_terminology_pages_and_html() {
+        let generator = DocsSiteGenerator::new(config(), resources());
+        let pages = generator.generate(DocsFormat::Html);
+
+        let vs = page(&pages, "ValueSet-genders.html");
+        assert!(vs.contains("<td><code>f</code></td><td>Female</td>"));
+        assert!(vs.contains("<a href=\"CodeSystem-genders.html\">GenderCodes</a>"));
+        assert!(vs.contains("<link rel=\"stylesheet\" href=\"maki-docs.css\">"));
+
+        let cs = page(&pages, "CodeSystem-genders.html");
+        assert!(cs.contains("<td>› <code>f2</code></td><td>Sub</td>"));
+
+        let profile = page(&pages, "StructureDefinition-my-patient.html");
+        assert!(profile.contains("Must be &lt; 3"));
+
+        let instance = page(&pages, "Patient-jane.html");
+        assert!(instance.contains("<pre>{\n  &quot;resourceType&quot;: &quot;Patient&quot;"));
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        let html = markdown_to_html(
+            "# Intro\n\nSome `code` and **bold** [link](a.html).\n\n- one\n- two\n\n```\n<x>\n```\n",
+        );
+        assert_eq!(
+            html,
+            "<h2>Intro</h2>\n<p>Some <code>code</code> and <b>bold</b> <a href=\"a.html\">link</a>.</p>\n\
+             <ul>\n<li>one</li>\n<li>two</li>\n</ul>\n<pre>&lt;x&gt;\n</pre>\n"
+        );
+    }
+}
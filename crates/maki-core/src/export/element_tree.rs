@@ -0,0 +1,192 @@
+//! StructureDefinition element tree
+//!
+//! Indexes the elements of a StructureDefinition (snapshot, or differential
+//! when there is no snapshot) by id so generators can walk them as a tree:
+//! children of an element, its named slices, and the values a slice's
+//! discriminators select on.
+
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+
+/// Elements of a StructureDefinition indexed by id
+pub(crate) struct ElementTree<'r> {
+    pub root: Option<String>,
+    pub by_id: HashMap<&'r str, &'r JsonValue>,
+    /// Parent id → direct children (including slices), in definition order
+    children: HashMap<&'r str, Vec<&'r JsonValue>>,
+}
+
+impl<'r> ElementTree<'r> {
+    pub fn new(sd: &'r JsonValue) -> Self {
+        let elements: Vec<&JsonValue> = ["snapshot", "differential"]
+            .iter()
+            .find_map(|view| {
+                sd.get(*view)
+                    .and_then(|v| v.get("element"))
+                    .and_then(JsonValue::as_array)
+                    .filter(|e| !e.is_empty())
+            })
+            .map(|e| e.iter().collect())
+            .unwrap_or_default();
+
+        let root = elements
+            .iter()
+            .map(|e| element_id(e))
+            .find(|id| !id.is_empty() && !id.contains('.'))
+            .map(String::from)
+            .or_else(|| str_field(sd, "type").map(String::from));
+
+        let mut by_id = HashMap::new();
+        let mut children: HashMap<&str, Vec<&JsonValue>> = HashMap::new();
+        for element in elements {
+            let id = element_id(element);
+            by_id.insert(id, element);
+            if let Some((parent, _)) = id.rsplit_once('.') {
+                children.entry(parent).or_default().push(element);
+            }
+        }
+        Self {
+            root,
+            by_id,
+            children,
+        }
+    }
+
+    pub fn has_children(&self, id: &str) -> bool {
+        self.children.contains_key(id)
+    }
+
+    /// Direct child elements, without slices
+    pub fn children(&self, id: &str) -> Vec<&'r JsonValue> {
+        self.children
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter(|e| !last_segment(element_id(e)).contains(':'))
+            .copied()
+            .collect()
+    }
+
+    /// Named slices of an element (not reslices)
+    pub fn slices(&self, id: &str) -> Vec<&'r JsonValue> {
+        let Some((parent, name)) = id.rsplit_once('.') else {
+            return Vec::new();
+        };
+        let prefix = format!("{}:", name);
+        self.children
+            .get(parent)
+            .into_iter()
+            .flatten()
+            .filter(|e| {
+                last_segment(element_id(e))
+                    .strip_prefix(&prefix)
+                    .is_some_and(|slice| !slice.is_empty() && !slice.contains('/'))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Values a slice must have at its discriminator paths
+    pub fn slice_pattern(&self, base: &JsonValue, slice: &JsonValue) -> Option<JsonValue> {
+        let slice_id = element_id(slice);
+        let mut pattern = JsonValue::Object(Map::new());
+        for discriminator in base
+            .pointer("/slicing/discriminator")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if !matches!(str_field(discriminator, "type"), Some("value" | "pattern")) {
+                continue;
+            }
+            let path = str_field(discriminator, "path").unwrap_or("$this");
+            if path == "$this" {
+                match fixed_value(slice) {
+                    Some(JsonValue::Object(map)) => {
+                        if let JsonValue::Object(pattern) = &mut pattern {
+                            pattern.extend(map.clone());
+                        }
+                    }
+                    Some(other) => return Some(other.clone()),
+                    None => {}
+                }
+                continue;
+            }
+            if path.contains('(') {
+                continue;
+            }
+
+            let segments: Vec<&str> = path.split('.').collect();
+            let value = (1..=segments.len()).rev().find_map(|depth| {
+                let id = format!("{}.{}", slice_id, segments[..depth].join("."));
+                let mut value = fixed_value(self.by_id.get(id.as_str())?)?;
+                for segment in &segments[depth..] {
+                    value = value.get(*segment)?;
+                    if let Some(first) = value.as_array().and_then(|a| a.first()) {
+                        value = first;
+                    }
+                }
+                Some(value.clone())
+            });
+            // Extension slices are discriminated by the profile's url
+            let value = value.or_else(|| {
+                (path == "url")
+                    .then(|| type_entries(slice).first()?.pointer("/profile/0").cloned())
+                    .flatten()
+            });
+            if let Some(value) = value {
+                let mut target = &mut pattern;
+                for segment in &segments {
+                    target = target
+                        .as_object_mut()?
+                        .entry(segment.to_string())
+                        .or_insert_with(|| JsonValue::Object(Map::new()));
+                }
+                *target = value;
+            }
+        }
+        (pattern.as_object().is_some_and(|m| !m.is_empty())).then_some(pattern)
+    }
+}
+
+pub(crate) fn str_field<'v>(value: &'v JsonValue, name: &str) -> Option<&'v str> {
+    value.get(name).and_then(JsonValue::as_str)
+}
+
+/// Element id, falling back to the path for elements without one
+pub(crate) fn element_id(element: &JsonValue) -> &str {
+    str_field(element, "id")
+        .or_else(|| str_field(element, "path"))
+        .unwrap_or_default()
+}
+
+pub(crate) fn last_segment(id: &str) -> &str {
+    id.rsplit('.').next().unwrap_or(id)
+}
+
+pub(crate) fn type_entries(element: &JsonValue) -> Vec<&JsonValue> {
+    element
+        .get("type")
+        .and_then(JsonValue::as_array)
+        .map(|types| types.iter().collect())
+        .unwrap_or_default()
+}
+
+/// Arrays in FHIR JSON follow the base definition's max, not the profile's
+pub(crate) fn is_array(element: &JsonValue) -> bool {
+    let max = element
+        .pointer("/base/max")
+        .and_then(JsonValue::as_str)
+        .or_else(|| str_field(element, "max"))
+        .unwrap_or("1");
+    max != "1" && max != "0"
+}
+
+/// `fixed[x]` or `pattern[x]` value of an element
+pub(crate) fn fixed_value(element: &JsonValue) -> Option<&JsonValue> {
+    element
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.starts_with("fixed") || key.starts_with("pattern"))
+        .map(|(_, value)| value)
+}
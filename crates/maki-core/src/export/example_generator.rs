@@ -0,0 +1,705 @@
+//! Synthetic example instances
+//!
+//! Generates a minimal FSH `Instance:` for a profile by walking its snapshot:
+//! every element with `min > 0` (and must-support elements on request), fixed
+//! and pattern values, required slices, and codes for bound elements taken
+//! from locally expandable ValueSets. Complex datatypes the profile does not
+//! constrain are filled through the [`PathResolver`], which unfolds the
+//! datatype's definition to type the leaf that gets populated.
+
+use crate::export::element_tree::{ElementTree, element_id, last_segment, str_field, type_entries};
+use crate::export::local_terminology::{LocalConcept, LocalTerminology};
+use crate::semantic::path_resolver::{PathResolver, ResolutionContext, StructureDefinition};
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Maximum nesting of locally defined extensions
+const MAX_EXTENSION_DEPTH: usize = 4;
+
+/// Leaves populated for complex datatypes without constrained children, with
+/// the leaf type used when the datatype definition is not available
+const DATATYPE_LEAVES: &[(&str, &[(&str, &str)])] = &[
+    ("CodeableConcept", &[("text", "string")]),
+    ("Coding", &[("system", "uri"), ("code", "code")]),
+    ("Quantity", &[("value", "decimal")]),
+    ("SimpleQuantity", &[("value", "decimal")]),
+    ("MoneyQuantity", &[("value", "decimal")]),
+    ("Age", &[("value", "decimal")]),
+    ("Count", &[("value", "decimal")]),
+    ("Distance", &[("value", "decimal")]),
+    ("Duration", &[("value", "decimal")]),
+    ("Money", &[("value", "decimal"), ("currency", "code")]),
+    ("Reference", &[("display", "string")]),
+    ("Identifier", &[("system", "uri"), ("value", "string")]),
+    ("HumanName", &[("family", "string")]),
+    ("Address", &[("text", "string")]),
+    ("ContactPoint", &[("system", "code"), ("value", "string")]),
+    ("Period", &[("start", "dateTime")]),
+    ("Range", &[("low.value", "decimal")]),
+    ("Ratio", &[("numerator.value", "decimal")]),
+    ("Attachment", &[("title", "string")]),
+    ("Annotation", &[("text", "markdown")]),
+    ("Narrative", &[("status", "code"), ("div", "xhtml")]),
+    ("Timing", &[("event", "dateTime")]),
+    ("Dosage", &[("text", "string")]),
+    ("Meta", &[("lastUpdated", "instant")]),
+];
+
+/// Values for datatype leaves whose type default would be invalid
+const LEAF_VALUES: &[(&str, &str, &str)] = &[
+    ("Coding", "system", "\"http://example.org\""),
+    ("Identifier", "system", "\"http://example.org/identifiers\""),
+    ("Identifier", "value", "\"example\""),
+    ("ContactPoint", "system", "#phone"),
+    ("ContactPoint", "value", "\"555-0100\""),
+    ("Money", "currency", "#USD"),
+    ("Narrative", "status", "#generated"),
+];
+
+/// A rule of the generated instance
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    /// `* path = value`
+    Assign(String, String),
+    /// A complex datatype still to be populated: path and type code
+    Complex(String, String),
+    /// A `// TODO` comment for what can't be generated
+    Todo(String, String),
+}
+
+/// Generates minimal FSH example instances for profiles
+pub struct ExampleGenerator<'r> {
+    resolver: &'r PathResolver,
+    terminology: LocalTerminology<'r>,
+    must_support: bool,
+}
+
+impl<'r> ExampleGenerator<'r> {
+    /// Create a generator over the project's resources (for local
+    /// extensions, ValueSets and CodeSystems)
+    pub fn new(resolver: &'r PathResolver, resources: &'r [JsonValue]) -> Self {
+        Self {
+            resolver,
+            terminology: LocalTerminology::new(resources),
+            must_support: false,
+        }
+    }
+
+    /// Also populate must-support elements
+    pub fn with_must_support(mut self, must_support: bool) -> Self {
+        self.must_support = must_support;
+        self
+    }
+
+    /// Generate a FSH instance of `profile`
+    ///
+    /// The instance is named `instance_name`, or `<ProfileName>Example`.
+    pub async fn generate(&self, profile: &JsonValue, instance_name: Option<&str>) -> String {
+        let profile_name = str_field(profile, "name")
+            .or_else(|| str_field(profile, "id"))
+            .unwrap_or("Profile");
+        let mut walk = Walk {
+            generator: self,
+            rules: Vec::new(),
+            assigned: HashSet::new(),
+            depth: 0,
+        };
+        let tree = ElementTree::new(profile);
+        if let Some(root) = &tree.root {
+            walk.children(&tree, root, "");
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Instance: {}",
+            instance_name
+                .map(String::from)
+                .unwrap_or_else(|| format!("{}Example", profile_name))
+        );
+        let _ = writeln!(out, "InstanceOf: {}", profile_name);
+        let _ = writeln!(out, "Usage: #example");
+        let _ = writeln!(
+            out,
+            "Description: {}",
+            quote(&format!(
+                "Example of {}",
+                str_field(profile, "title").unwrap_or(profile_name)
+            ))
+        );
+        for rule in walk.rules {
+            let rules = match rule {
+                Rule::Complex(path, code) => self.populate(&path, &code).await,
+                rule => vec![rule],
+            };
+            for rule in rules {
+                match rule {
+                    Rule::Assign(path, value) => {
+                        let _ = writeln!(out, "* {} = {}", path, value);
+                    }
+                    Rule::Todo(path, note) => {
+                        let _ = writeln!(out, "// TODO: {}: {}", path, note);
+                    }
+                    Rule::Complex(..) => {}
+                }
+            }
+        }
+        out
+    }
+
+    /// Populate a complex datatype through its leaves
+    async fn populate(&self, path: &str, code: &str) -> Vec<Rule> {
+        let Some((_, leaves)) = DATATYPE_LEAVES.iter().find(|(t, _)| *t == code) else {
+            return vec![Rule::Todo(path.to_string(), format!("populate {}", code))];
+        };
+        let context = datatype_context(code);
+        let mut rules = Vec::new();
+        for (leaf, fallback) in leaves.iter() {
+            let leaf_type = match self
+                .resolver
+                .resolve_path(&format!("{}.{}", code, leaf), &context)
+                .await
+            {
+                Ok(resolved) => resolved
+                    .element_definition
+                    .types()
+                    .first()
+                    .map(|t| t.code.clone())
+                    .unwrap_or_else(|| fallback.to_string()),
+                Err(e) => {
+                    debug!("Using {} for {}.{}: {}", fallback, code, leaf, e);
+                    fallback.to_string()
+                }
+            };
+            let value = LEAF_VALUES
+                .iter()
+                .find(|(t, l, _)| *t == code && l == leaf)
+                .map(|(_, _, value)| value.to_string())
+                .or_else(|| primitive_value(&leaf_type));
+            match value {
+                Some(value) => rules.push(Rule::Assign(join(path, leaf), value)),
+                None => rules.push(Rule::Todo(
+                    join(path, leaf),
+                    format!("populate {}", leaf_type),
+                )),
+            }
+        }
+        rules
+    }
+}
+
+/// State of one snapshot walk
+struct Walk<'g, 'r> {
+    generator: &'g ExampleGenerator<'r>,
+    rules: Vec<Rule>,
+    /// Paths set by fixed or pattern values; nothing below them is generated
+    assigned: HashSet<String>,
+    depth: usize,
+}
+
+impl<'r> Walk<'_, 'r> {
+    fn wanted(&self, element: &JsonValue) -> bool {
+        if str_field(element, "max") == Some("0") {
+            return false;
+        }
+        element.get("min").and_then(JsonValue::as_u64).unwrap_or(0) > 0
+            || (self.generator.must_support
+                && element.get("mustSupport").and_then(JsonValue::as_bool) == Some(true))
+    }
+
+    fn covered(&self, path: &str) -> bool {
+        self.assigned.iter().any(|a| {
+            path == a
+                || path
+                    .strip_prefix(a.as_str())
+                    .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+        })
+    }
+
+    fn push(&mut self, rule: Rule) {
+        match &rule {
+            Rule::Assign(path, _) | Rule::Complex(path, _) if self.covered(path) => return,
+            _ => {}
+        }
+        self.rules.push(rule);
+    }
+
+    /// Generate the wanted children of `parent_id` below FSH path `prefix`
+    fn children(&mut self, tree: &ElementTree<'r>, parent_id: &str, prefix: &str) {
+        let extension_url = tree
+            .root
+            .as_deref()
+            .filter(|root| *root == "Extension")
+            .map(|root| format!("{}.url", root));
+        for child in tree.children(parent_id) {
+            let id = element_id(child);
+            // Slicing by url sets the url of extension slices
+            if extension_url.as_deref() == Some(id) {
+                continue;
+            }
+            let slices: Vec<&JsonValue> = tree
+                .slices(id)
+                .into_iter()
+                .filter(|slice| self.wanted(slice))
+                .collect();
+            if slices.is_empty() && !self.wanted(child) {
+                continue;
+            }
+            let name = last_segment(id);
+
+            if name.ends_with("[x]") {
+                // A type slice (`value[x]:valueQuantity`) picks the type
+                let element = slices.first().copied().unwrap_or(child);
+                let Some(code) = first_type(element).or_else(|| first_type(child)) else {
+                    continue;
+                };
+                let path = join(prefix, &fsh_name(name, &code));
+                self.element(tree, element, &path, &code);
+                continue;
+            }
+
+            let path = join(prefix, name);
+            let code = first_type(child).unwrap_or_else(|| "BackboneElement".to_string());
+            if slices.is_empty() {
+                self.element(tree, child, &path, &code);
+                continue;
+            }
+            for slice in slices {
+                let Some(slice_name) = str_field(slice, "sliceName") else {
+                    continue;
+                };
+                let slice_path = format!("{}[{}]", path, slice_name);
+                let slice_code = first_type(slice).unwrap_or_else(|| code.clone());
+                if slice_code != "Extension"
+                    && fixed(slice).is_none()
+                    && let Some(pattern) = tree.slice_pattern(child, slice)
+                {
+                    self.assign_value(&slice_path, &pattern, None);
+                }
+                self.element(tree, slice, &slice_path, &slice_code);
+            }
+        }
+    }
+
+    /// Generate an element at FSH path `path` with type `code`
+    fn element(&mut self, tree: &ElementTree<'r>, element: &JsonValue, path: &str, code: &str) {
+        if let Some((fixed_type, value)) = fixed(element) {
+            self.assign_value(path, value, Some(&fixed_type));
+            return;
+        }
+        if code == "Extension" && path.ends_with(']') {
+            self.extension(element, path);
+            return;
+        }
+
+        let id = element_id(element);
+        let preset = self.covered(path);
+        let before = self.rules.len();
+        if tree.has_children(id) {
+            self.children(tree, id, path);
+        }
+        if preset || self.rules.len() > before {
+            return;
+        }
+
+        if self.bound_value(element, path, code) {
+            return;
+        }
+        if let Some(value) = primitive_value(code) {
+            self.push(Rule::Assign(path.to_string(), value));
+            return;
+        }
+        if matches!(code, "BackboneElement" | "Element") {
+            // Nothing inside is required; populate the first content child
+            let first = tree.children(id).into_iter().find(|child| {
+                !matches!(
+                    last_segment(element_id(child)),
+                    "id" | "extension" | "modifierExtension"
+                ) && str_field(child, "max") != Some("0")
+            });
+            match first.and_then(|child| Some((child, first_type(child)?))) {
+                Some((child, child_code)) => {
+                    let name = fsh_name(last_segment(element_id(child)), &child_code);
+                    self.element(tree, child, &join(path, &name), &child_code);
+                }
+                None => self.push(Rule::Todo(path.to_string(), "add content".to_string())),
+            }
+            return;
+        }
+        self.push(Rule::Complex(path.to_string(), code.to_string()));
+    }
+
+    /// Populate an extension slice from its local definition
+    fn extension(&mut self, element: &JsonValue, path: &str) {
+        let url = type_entries(element)
+            .first()
+            .and_then(|t| t.pointer("/profile/0"))
+            .and_then(JsonValue::as_str);
+        let definition =
+            url.and_then(|url| self.generator.terminology.find("StructureDefinition", url));
+        match definition {
+            Some(definition) if self.depth < MAX_EXTENSION_DEPTH => {
+                let tree = ElementTree::new(definition);
+                if let Some(root) = tree.root.clone() {
+                    self.depth += 1;
+                    self.children(&tree, &root, path);
+                    self.depth -= 1;
+                }
+            }
+            _ => self.push(Rule::Todo(
+                path.to_string(),
+                format!("set the value of {}", url.unwrap_or("the extension")),
+            )),
+        }
+    }
+
+    /// Assign a code from the element's ValueSet; false when none applies
+    fn bound_value(&mut self, element: &JsonValue, path: &str, code: &str) -> bool {
+        let Some(value_set) = element
+            .pointer("/binding/valueSet")
+            .and_then(JsonValue::as_str)
+        else {
+            return false;
+        };
+        let concept = self
+            .generator
+            .terminology
+            .expand(value_set)
+            .and_then(|concepts| concepts.into_iter().next());
+        match concept.and_then(|concept| concept_value(&concept, code)) {
+            Some(value) => {
+                self.push(Rule::Assign(path.to_string(), value));
+                true
+            }
+            None => {
+                if element
+                    .pointer("/binding/strength")
+                    .and_then(JsonValue::as_str)
+                    == Some("required")
+                {
+                    self.push(Rule::Todo(
+                        path.to_string(),
+                        format!("use a code from {}", value_set),
+                    ));
+                }
+                false
+            }
+        }
+    }
+
+    /// Assign a fixed or pattern JSON value, one rule per leaf
+    fn assign_value(&mut self, path: &str, value: &JsonValue, value_type: Option<&str>) {
+        match value {
+            JsonValue::Object(map) => {
+                let coding_like = map.get("code").is_some_and(JsonValue::is_string)
+                    && map.values().all(|v| !v.is_object() && !v.is_array());
+                let mut consumed: Vec<&str> = Vec::new();
+                if coding_like {
+                    let display = str_field(value, "display")
+                        .map(|d| ("display", d))
+                        .or_else(|| str_field(value, "unit").map(|u| ("unit", u)));
+                    let concept = LocalConcept {
+                        system: str_field(value, "system").map(String::from),
+                        code: str_field(value, "code").unwrap_or_default().to_string(),
+                        display: display.map(|(_, d)| d.to_string()),
+                    };
+                    self.assigned.insert(path.to_string());
+                    self.rules
+                        .push(Rule::Assign(path.to_string(), concept_literal(&concept)));
+                    consumed.extend(["system", "code"]);
+                    consumed.extend(display.map(|(key, _)| key));
+                }
+                for (key, child) in map {
+                    if !consumed.contains(&key.as_str()) {
+                        self.assign_value(&join(path, key), child, None);
+                    }
+                }
+            }
+            JsonValue::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    let item_path = match index {
+                        0 => path.to_string(),
+                        _ => format!("{}[{}]", path, index),
+                    };
+                    self.assign_value(&item_path, item, value_type);
+                }
+            }
+            JsonValue::Null => {}
+            scalar => {
+                let key = last_segment(path).split('[').next().unwrap_or_default();
+                let literal = scalar_literal(scalar, value_type, key);
+                self.assigned.insert(path.to_string());
+                self.rules.push(Rule::Assign(path.to_string(), literal));
+            }
+        }
+    }
+}
+
+/// `fixed[x]`/`pattern[x]` value with its type (`patternCodeableConcept` → `CodeableConcept`)
+fn fixed(element: &JsonValue) -> Option<(String, &JsonValue)> {
+    element.as_object()?.iter().find_map(|(key, value)| {
+        let value_type = key
+            .strip_prefix("fixed")
+            .or_else(|| key.strip_prefix("pattern"))?;
+        let primitive = lower_first(value_type);
+        let value_type = match primitive_value(&primitive) {
+            Some(_) => primitive,
+            None => value_type.to_string(),
+        };
+        Some((value_type, value))
+    })
+}
+
+/// First type code, with FHIRPath system types mapped to FHIR primitives
+fn first_type(element: &JsonValue) -> Option<String> {
+    let code = str_field(type_entries(element).first()?, "code")?;
+    Some(match code.strip_prefix("http://hl7.org/fhirpath/System.") {
+        Some(system) => lower_first(system),
+        None => code.to_string(),
+    })
+}
+
+/// FSH name of an element: choices are named by type (`value[x]` → `valueQuantity`)
+fn fsh_name(name: &str, code: &str) -> String {
+    match name.strip_suffix("[x]") {
+        Some(base) => {
+            let mut chars = code.chars();
+            let upper: String = chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+                .collect();
+            format!("{}{}", base, upper)
+        }
+        None => name.to_string(),
+    }
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.to_ascii_lowercase())
+        .into_iter()
+        .chain(chars)
+        .collect()
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+/// A resolution context holding only the root element of a datatype, so the
+/// resolver unfolds its children from the datatype's definition
+fn datatype_context(code: &str) -> ResolutionContext {
+    ResolutionContext {
+        base_definition: StructureDefinition {
+            content: Arc::new(json!({
+                "resourceType": "StructureDefinition",
+                "url": format!("urn:maki:example:{}", code),
+                "type": code,
+                "snapshot": {
+                    "element": [{ "id": code, "path": code, "type": [{ "code": code }] }]
+                }
+            })),
+        },
+        profile_name: code.to_string(),
+    }
+}
+
+/// Default value for a primitive type
+fn primitive_value(code: &str) -> Option<String> {
+    let value = match code {
+        "boolean" => "true",
+        "integer" | "integer64" | "positiveInt" | "unsignedInt" => "1",
+        "decimal" => "1.0",
+        "string" | "markdown" => "\"Example\"",
+        "code" => "#example",
+        "id" => "\"example\"",
+        "uri" | "url" | "canonical" => "\"http://example.org\"",
+        "oid" => "\"urn:oid:1.2.3.4\"",
+        "uuid" => "\"urn:uuid:3e2a8f6c-5b1d-4c7e-9f0a-1b2c3d4e5f60\"",
+        "date" => "2024-01-01",
+        "dateTime" | "instant" => "2024-01-01T00:00:00Z",
+        "time" => "12:00:00",
+        "base64Binary" => "\"ZXhhbXBsZQ==\"",
+        "xhtml" => "\"<div xmlns=\\\"http://www.w3.org/1999/xhtml\\\">Example</div>\"",
+        _ => return None,
+    };
+    Some(value.to_string())
+}
+
+/// FSH value of a concept for an element of type `code`
+fn concept_value(concept: &LocalConcept, code: &str) -> Option<String> {
+    match code {
+        "code" => Some(fsh_code(&concept.code)),
+        "Coding" | "CodeableConcept" | "Quantity" => Some(concept_literal(concept)),
+        "string" | "uri" => Some(quote(&concept.code)),
+        _ => None,
+    }
+}
+
+/// `system#code "display"`
+fn concept_literal(concept: &LocalConcept) -> String {
+    let mut literal = format!(
+        "{}{}",
+        concept.system.as_deref().unwrap_or_default(),
+        fsh_code(&concept.code)
+    );
+    if let Some(display) = &concept.display {
+        literal.push(' ');
+        literal.push_str(&quote(display));
+    }
+    literal
+}
+
+fn fsh_code(code: &str) -> String {
+    if code.chars().any(char::is_whitespace) {
+        format!("#{}", quote(code))
+    } else {
+        format!("#{}", code)
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// FSH literal of a scalar JSON value; `key` types values without a known type
+fn scalar_literal(value: &JsonValue, value_type: Option<&str>, key: &str) -> String {
+    let JsonValue::String(s) = value else {
+        return value.to_string();
+    };
+    let unquoted = match value_type {
+        Some("code") => return fsh_code(s),
+        Some("date" | "dateTime" | "instant" | "time") => true,
+        Some(_) => false,
+        None if key == "code" => return fsh_code(s),
+        None => looks_like_date(s),
+    };
+    if unquoted { s.clone() } else { quote(s) }
+}
+
+fn looks_like_date(value: &str) -> bool {
+    value.len() >= 4
+        && value[..4].chars().all(|c| c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | ':' | 'T' | '.' | '+' | 'Z'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonical::{CanonicalFacade, CanonicalOptions, FhirRelease};
+    use octofhir_canonical_manager::config::FcmConfig;
+    use tempfile::TempDir;
+
+    async fn resolver(dir: &TempDir) -> PathResolver {
+        let facade = CanonicalFacade::new(CanonicalOptions {
+            config: Some(FcmConfig::test_config(dir.path())),
+            auto_install_core: false,
+            quick_init: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let session = facade.session([FhirRelease::R4]).await.unwrap();
+        PathResolver::new(Arc::new(session))
+    }
+
+    fn element(id: &str, min: u64, max: &str, code: &str) -> JsonValue {
+        json!({
+            "id": id,
+            "path": id.split(':').next().unwrap(),
+            "min": min,
+            "max": max,
+            "type": [{ "code": code }]
+        })
+    }
+
+    fn resources() -> Vec<JsonValue> {
+        let mut sliced = element("Observation.category", 1, "*", "CodeableConcept");
+        sliced["slicing"] = json!({
+            "discriminator": [{ "type": "pattern", "path": "$this" }],
+            "rules": "open"
+        });
+        let mut slice = element("Observation.category:lab", 1, "1", "CodeableConcept");
+        slice["sliceName"] = json!("lab");
+        slice["patternCodeableConcept"] = json!({
+            "coding": [{ "system": "http://example.org/cat", "code": "laboratory" }]
+        });
+        let mut status = element("Observation.status", 1, "1", "code");
+        status["binding"] =
+            json!({ "strength": "required", "valueSet": "http://example.org/vs/status" });
+        let mut note = element("Observation.note", 0, "*", "Annotation");
+        note["mustSupport"] = json!(true);
+
+        vec![
+            json!({
+                "resourceType": "StructureDefinition",
+                "id": "lab-observation",
+                "name": "LabObservation",
+                "type": "Observation",
+                "snapshot": { "element": [
+                    element("Observation", 0, "*", "Observation"),
+                    element("Observation.id", 0, "1", "id"),
+                    status,
+                    sliced,
+                    slice,
+                    element("Observation.code", 1, "1", "CodeableConcept"),
+                    element("Observation.value[x]", 1, "1", "Quantity"),
+                    element("Observation.component", 1, "*", "BackboneElement"),
+                    element("Observation.component.code", 1, "1", "CodeableConcept"),
+                    element("Observation.interpretation", 0, "0", "CodeableConcept"),
+                    note,
+                ]}
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/vs/status",
+                "compose": { "include": [{
+                    "system": "http://hl7.org/fhir/observation-status",
+                    "concept": [{ "code": "final" }]
+                }]}
+            }),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_generate_minimal_instance() {
+        let dir = TempDir::new().unwrap();
+        let resolver = resolver(&dir).await;
+        let resources = resources();
+        let generator = ExampleGenerator::new(&resolver, &resources);
+
+        let fsh = generator.generate(&resources[0], None).await;
+        assert!(fsh.starts_with("Instance: LabObservationExample\nInstanceOf: LabObservation\n"));
+        assert!(fsh.contains("* status = #final\n"));
+        assert!(
+            fsh.contains("* category[lab].coding = http://example.org/cat#laboratory\n"),
+            "{}",
+            fsh
+        );
+        assert!(fsh.contains("* code.text = \"Example\"\n"));
+        assert!(fsh.contains("* valueQuantity.value = 1.0\n"));
+        assert!(fsh.contains("* component.code.text = \"Example\"\n"));
+        assert!(!fsh.contains("interpretation"));
+        assert!(!fsh.contains("note"));
+
+        let with_ms = ExampleGenerator::new(&resolver, &resources)
+            .with_must_support(true)
+            .generate(&resources[0], Some("Full"))
+            .await;
+        assert!(with_ms.starts_with("Instance: Full\n"));
+        assert!(with_ms.contains("* note.text = \"Example\"\n"));
+    }
+}
@@ -49,6 +49,7 @@
 
 use super::ExportError;
 use super::fhir_types::*;
+use super::profile_exporter::RuleFailure;
 use crate::canonical::DefinitionSession;
 use crate::cst::ast::{
     AstNode, CardRule, ContainsRule, Extension, FixedValueRule, FlagRule, OnlyRule, Rule,
@@ -133,6 +134,16 @@ impl ExtensionExporter {
     /// - Required fields missing
     /// - Rule application fails
     pub async fn export(&self, extension: &Extension) -> Result<StructureDefinition, ExportError> {
+        self.export_with_rule_failures(extension)
+            .await
+            .map(|(structure_def, _)| structure_def)
+    }
+
+    /// Export an Extension, also returning the rules that could not be applied
+    pub async fn export_with_rule_failures(
+        &self,
+        extension: &Extension,
+    ) -> Result<(StructureDefinition, Vec<RuleFailure>), ExportError> {
         let extension_name = extension
             .name()
             .ok_or_else(|| ExportError::MissingRequiredField("extension name".to_string()))?;
@@ -153,10 +164,12 @@ impl ExtensionExporter {
             .await?;
 
         // 5. Apply all rules to snapshot with enhanced processing
+        let mut rule_failures = Vec::new();
         for rule in extension.rules() {
             if let Err(e) = self.apply_rule(&mut structure_def, &rule).await {
                 warn!("Failed to apply rule: {}", e);
                 // Continue with other rules instead of failing completely
+                rule_failures.push(RuleFailure::new(rule.syntax(), &e));
             }
         }
 
@@ -177,7 +190,7 @@ impl ExtensionExporter {
         self.validate_extension_structure(&structure_def)?;
 
         debug!("Successfully exported extension: {}", extension_name);
-        Ok(structure_def)
+        Ok((structure_def, rule_failures))
     }
 
     /// Get base Extension StructureDefinition
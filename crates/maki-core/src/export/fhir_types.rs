@@ -255,6 +255,10 @@ pub struct ElementDefinition {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub slicing: Option<ElementDefinitionSlicing>,
 
+    /// Reference to an element whose definition (and children) this element reuses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_reference: Option<String>,
+
     /// Fixed value
     #[serde(skip_serializing_if = "Option::is_none", flatten)]
     pub fixed: Option<HashMap<String, serde_json::Value>>,
@@ -287,6 +291,7 @@ impl ElementDefinition {
             binding: None,
             constraint: None,
             slicing: None,
+            content_reference: None,
             fixed: None,
             pattern: None,
             mapping: None,
@@ -7,7 +7,7 @@
 //!
 //! ```text
 //! fsh-generated/
-//! ├── resources/           # All exported FHIR resources (JSON)
+//! ├── resources/           # All exported FHIR resources (JSON and/or XML)
 //! │   ├── StructureDefinition-*.json
 //! │   ├── ValueSet-*.json
 //! │   ├── CodeSystem-*.json
@@ -16,7 +16,11 @@
 //! ├── includes/            # Generated include files
 //! │   └── menu.xml
 //! ├── data/               # Machine-readable indexes
-//! │   └── fsh-index.json
+//! │   ├── fsh-index.json
+//! │   └── fshmap/*.fshmap.json  # Optional FSH source maps
+//! ├── bundles/            # Optional example Bundle / NDJSON output
+//! │   ├── examples-transaction.json
+//! │   └── ndjson/*.ndjson
 //! └── fsh-index.txt       # Human-readable index
 //! ```
 //!
@@ -40,6 +44,12 @@ pub const INCLUDES_DIR: &str = "includes";
 /// Data subdirectory (for machine-readable indexes)
 pub const DATA_DIR: &str = "data";
 
+/// Bundles subdirectory (for example transaction Bundles and NDJSON)
+pub const BUNDLES_DIR: &str = "bundles";
+
+/// Source map subdirectory of `data/` (for `*.fshmap.json` sidecars)
+pub const FSHMAP_DIR: &str = "fshmap";
+
 /// File structure generator for IG output
 ///
 /// Creates and manages the fsh-generated/ directory structure
@@ -87,6 +97,16 @@ impl FileStructureGenerator {
         self.output_dir.join(DATA_DIR)
     }
 
+    /// Get the bundles directory path
+    pub fn bundles_dir(&self) -> PathBuf {
+        self.output_dir.join(BUNDLES_DIR)
+    }
+
+    /// Get the source map directory path
+    pub fn fshmap_dir(&self) -> PathBuf {
+        self.data_dir().join(FSHMAP_DIR)
+    }
+
     /// Initialize the directory structure
     ///
     /// Creates all necessary directories. If `clean_output` is true,
@@ -132,6 +152,59 @@ impl FileStructureGenerator {
         self.write_json(&path, content)
     }
 
+    /// Write a FHIR XML resource to the resources directory
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - Resource filename (e.g., "StructureDefinition-patient.xml")
+    /// * `xml` - Serialized FHIR XML document
+    pub fn write_resource_xml(&self, filename: &str, xml: &str) -> Result<(), FileStructureError> {
+        let path = self.resources_dir().join(filename);
+        let result = run_blocking_io(|| fs::write(&path, xml));
+        if let Err(e) = result {
+            return Err(FileStructureError::WriteFile(path, e));
+        }
+        Ok(())
+    }
+
+    /// Write a Bundle to the bundles directory
+    ///
+    /// Bundles are kept out of `resources/` so the IG Publisher doesn't pick them up.
+    pub fn write_bundle<T: Serialize>(
+        &self,
+        filename: &str,
+        bundle: &T,
+    ) -> Result<(), FileStructureError> {
+        self.create_directory(&self.bundles_dir())?;
+        let path = self.bundles_dir().join(filename);
+        self.write_json(&path, bundle)
+    }
+
+    /// Write an NDJSON file to the `bundles/ndjson` directory
+    pub fn write_ndjson(&self, filename: &str, content: &str) -> Result<(), FileStructureError> {
+        let dir = self.bundles_dir().join("ndjson");
+        self.create_directory(&dir)?;
+        let path = dir.join(filename);
+        let result = run_blocking_io(|| fs::write(&path, content));
+        if let Err(e) = result {
+            return Err(FileStructureError::WriteFile(path, e));
+        }
+        Ok(())
+    }
+
+    /// Write a FSH source map sidecar to the `data/fshmap` directory
+    ///
+    /// Sidecars live outside `resources/` so the IG Publisher doesn't try to load them.
+    pub fn write_source_map<T: Serialize>(
+        &self,
+        filename: &str,
+        source_map: &T,
+    ) -> Result<(), FileStructureError> {
+        self.create_directory(&self.fshmap_dir())?;
+        let path = self.fshmap_dir().join(filename);
+        self.write_json(&path, source_map)
+    }
+
     /// Write the FSH index file (human-readable)
     ///
     /// # Arguments
@@ -394,6 +467,78 @@ mod tests {
         assert!(content.contains("\"name\": \"Test Resource\""));
     }
 
+    #[test]
+    fn test_write_resource_xml() {
+        let temp = TempDir::new().unwrap();
+        let fsh_gen_path = temp.path().join("fsh-generated");
+        let generator = FileStructureGenerator::new(&fsh_gen_path, false);
+        generator.initialize().unwrap();
+
+        let xml = "<Patient xmlns=\"http://hl7.org/fhir\"/>\n";
+        generator
+            .write_resource_xml("Patient-example.xml", xml)
+            .unwrap();
+
+        let path = generator.resources_dir().join("Patient-example.xml");
+        assert_eq!(fs::read_to_string(path).unwrap(), xml);
+    }
+
+    #[test]
+    fn test_write_bundle_and_ndjson() {
+        let temp = TempDir::new().unwrap();
+        let fsh_gen_path = temp.path().join("fsh-generated");
+        let generator = FileStructureGenerator::new(&fsh_gen_path, false);
+        generator.initialize().unwrap();
+
+        let bundle = serde_json::json!({ "resourceType": "Bundle", "type": "transaction" });
+        generator
+            .write_bundle("examples-transaction.json", &bundle)
+            .unwrap();
+        generator
+            .write_ndjson("Patient.ndjson", "{\"resourceType\":\"Patient\"}\n")
+            .unwrap();
+
+        assert!(
+            generator
+                .bundles_dir()
+                .join("examples-transaction.json")
+                .exists()
+        );
+        assert!(
+            generator
+                .bundles_dir()
+                .join("ndjson")
+                .join("Patient.ndjson")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn test_write_source_map() {
+        let temp = TempDir::new().unwrap();
+        let fsh_gen_path = temp.path().join("fsh-generated");
+        let generator = FileStructureGenerator::new(&fsh_gen_path, false);
+        generator.initialize().unwrap();
+
+        let source_map = serde_json::json!({ "resource": "Patient-example.json" });
+        generator
+            .write_source_map("Patient-example.fshmap.json", &source_map)
+            .unwrap();
+
+        assert!(
+            generator
+                .fshmap_dir()
+                .join("Patient-example.fshmap.json")
+                .exists()
+        );
+        assert!(
+            !generator
+                .resources_dir()
+                .join("Patient-example.fshmap.json")
+                .exists()
+        );
+    }
+
     #[test]
     fn test_write_fsh_index_txt() {
         let temp = TempDir::new().unwrap();
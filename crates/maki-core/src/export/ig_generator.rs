@@ -261,6 +261,72 @@ impl ImplementationGuideGenerator {
         }
     }
 
+    /// Build the `definition.resource` entry for an exported resource
+    ///
+    /// Name, description and grouping come from the matching `resources` entry
+    /// or `groups` membership in the configuration, falling back to the
+    /// resource's own title/name and description. Conformance resources are
+    /// marked `exampleBoolean: false`; instances declaring a profile get
+    /// `exampleCanonical`. Returns `None` for resources configured with `omit`.
+    pub fn resource_entry(&self, resource: &JsonValue) -> Option<ResourceEntry> {
+        let field = |name: &str| resource.get(name).and_then(|v| v.as_str());
+        let reference = format!("{}/{}", field("resourceType")?, field("id")?);
+
+        let configured = self.config.resources.iter().flatten().find(|entry| {
+            let configured_reference = entry
+                .reference
+                .get("reference")
+                .unwrap_or(&entry.reference)
+                .as_str();
+            configured_reference == Some(reference.as_str())
+        });
+        if configured.is_some_and(|entry| entry.omit == Some(true)) {
+            return None;
+        }
+
+        let grouping_id = configured
+            .and_then(|entry| entry.grouping_id.clone())
+            .or_else(|| {
+                self.config
+                    .groups
+                    .iter()
+                    .flatten()
+                    .find(|group| group.resources.iter().flatten().any(|r| *r == reference))
+                    .map(|group| group.id.clone())
+            });
+
+        let (example_boolean, example_canonical) =
+            if is_conformance_resource(field("resourceType")?) {
+                (Some(false), None)
+            } else {
+                match resource.pointer("/meta/profile/0").and_then(|p| p.as_str()) {
+                    Some(profile) => (None, Some(profile.to_string())),
+                    None => (Some(true), None),
+                }
+            };
+
+        Some(ResourceEntry {
+            reference: Reference {
+                reference,
+                display: None,
+            },
+            name: configured
+                .and_then(|entry| entry.name.clone())
+                .or_else(|| field("title").or_else(|| field("name")).map(String::from)),
+            description: configured
+                .and_then(|entry| entry.description.clone())
+                .or_else(|| field("description").map(String::from)),
+            example_boolean: configured
+                .and_then(|entry| entry.example_boolean)
+                .or(example_boolean),
+            example_canonical: configured
+                .and_then(|entry| entry.example_canonical.clone())
+                .or(example_canonical),
+            grouping_id,
+            extension: None,
+        })
+    }
+
     /// Add a resource to the IG definition
     pub fn add_resource(&mut self, ig: &mut ImplementationGuide, resource: ResourceEntry) {
         ig.definition.resource.push(resource);
@@ -460,6 +526,22 @@ pub struct Page {
     pub page: Option<Vec<Page>>,
 }
 
+/// Whether a resource type is a conformance resource rather than an example
+fn is_conformance_resource(resource_type: &str) -> bool {
+    matches!(
+        resource_type,
+        "StructureDefinition"
+            | "ValueSet"
+            | "CodeSystem"
+            | "ConceptMap"
+            | "CapabilityStatement"
+            | "SearchParameter"
+            | "OperationDefinition"
+            | "NamingSystem"
+            | "ImplementationGuide"
+    )
+}
+
 /// Convert a page filename to HTML URL
 ///
 /// Examples:
@@ -673,6 +755,63 @@ mod tests {
         assert_eq!(grouping[0].name, "Profiles");
     }
 
+    #[test]
+    fn test_resource_entry() {
+        let mut config = minimal_config();
+        config.groups = Some(vec![crate::config::ResourceGroup {
+            id: "patients".to_string(),
+            name: "Patients".to_string(),
+            description: None,
+            resources: Some(vec!["StructureDefinition/my-patient".to_string()]),
+        }]);
+        config.resources = Some(vec![crate::config::ResourceEntry {
+            reference: serde_json::json!({ "reference": "Patient/hidden" }),
+            name: None,
+            description: None,
+            example_boolean: None,
+            example_canonical: None,
+            grouping_id: None,
+            omit: Some(true),
+            extension: None,
+        }]);
+        let generator = ImplementationGuideGenerator::new(config);
+
+        let profile = generator
+            .resource_entry(&serde_json::json!({
+                "resourceType": "StructureDefinition",
+                "id": "my-patient",
+                "name": "MyPatient",
+                "title": "My Patient"
+            }))
+            .unwrap();
+        assert_eq!(
+            profile.reference.reference,
+            "StructureDefinition/my-patient"
+        );
+        assert_eq!(profile.name.as_deref(), Some("My Patient"));
+        assert_eq!(profile.grouping_id.as_deref(), Some("patients"));
+        assert_eq!(profile.example_boolean, Some(false));
+
+        let example = generator
+            .resource_entry(&serde_json::json!({
+                "resourceType": "Patient",
+                "id": "jane",
+                "meta": { "profile": ["http://example.org/StructureDefinition/my-patient"] }
+            }))
+            .unwrap();
+        assert_eq!(
+            example.example_canonical.as_deref(),
+            Some("http://example.org/StructureDefinition/my-patient")
+        );
+        assert_eq!(example.grouping_id, None);
+
+        assert!(
+            generator
+                .resource_entry(&serde_json::json!({ "resourceType": "Patient", "id": "hidden" }))
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_filename_to_html() {
         assert_eq!(filename_to_html("index.md"), "index.html");
@@ -0,0 +1,235 @@
+//! Local ValueSet expansion
+//!
+//! Expands ValueSets from the project's own ValueSets and CodeSystems, without
+//! a terminology server. A ValueSet is expandable when it carries an
+//! `expansion`, or when every include lists its concepts, names a local
+//! CodeSystem, or includes another expandable ValueSet. Filters and external
+//! systems make a ValueSet non-expandable.
+
+use serde_json::Value as JsonValue;
+
+/// Maximum depth of nested `include.valueSet` references
+const MAX_DEPTH: usize = 8;
+
+/// A concept of a locally expanded ValueSet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalConcept {
+    pub system: Option<String>,
+    pub code: String,
+    pub display: Option<String>,
+}
+
+/// Expands ValueSets from local resources
+pub struct LocalTerminology<'r> {
+    resources: &'r [JsonValue],
+}
+
+impl<'r> LocalTerminology<'r> {
+    pub fn new(resources: &'r [JsonValue]) -> Self {
+        Self { resources }
+    }
+
+    /// Find a local resource by type and canonical (a `|version` suffix is ignored)
+    pub fn find(&self, resource_type: &str, canonical: &str) -> Option<&'r JsonValue> {
+        let url = canonical.split('|').next().unwrap_or(canonical);
+        self.resources.iter().find(|r| {
+            r.get("resourceType").and_then(JsonValue::as_str) == Some(resource_type)
+                && r.get("url").and_then(JsonValue::as_str) == Some(url)
+        })
+    }
+
+    /// Expand a ValueSet by canonical
+    ///
+    /// Returns `None` when the ValueSet is not local or cannot be expanded
+    /// from local resources alone.
+    pub fn expand(&self, value_set: &str) -> Option<Vec<LocalConcept>> {
+        self.expand_value_set(self.find("ValueSet", value_set)?, 0)
+    }
+
+    fn expand_value_set(&self, vs: &JsonValue, depth: usize) -> Option<Vec<LocalConcept>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        if let Some(contains) = vs.pointer("/expansion/contains") {
+            let mut concepts = Vec::new();
+            collect(Some(contains), None, "contains", &mut concepts);
+            return Some(concepts);
+        }
+
+        let mut concepts: Vec<LocalConcept> = Vec::new();
+        for include in vs.pointer("/compose/include")?.as_array()? {
+            if include.get("filter").is_some() {
+                return None;
+            }
+            let system = str_field(include, "system");
+            let mut included: Option<Vec<LocalConcept>> = None;
+            if let Some(listed) = include.get("concept") {
+                let mut found = Vec::new();
+                collect(Some(listed), system, "", &mut found);
+                // Listed concepts often leave the display to the CodeSystem
+                if let Some(cs) = system.and_then(|s| self.find("CodeSystem", s)) {
+                    for concept in found.iter_mut().filter(|c| c.display.is_none()) {
+                        concept.display = code_system_display(cs.get("concept"), &concept.code);
+                    }
+                }
+                included = Some(found);
+            } else if let Some(system) = system {
+                let cs = self.find("CodeSystem", system)?;
+                let mut all = Vec::new();
+                collect(cs.get("concept"), Some(system), "concept", &mut all);
+                included = Some(all);
+            }
+            for nested in include
+                .get("valueSet")
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let nested =
+                    self.expand_value_set(self.find("ValueSet", nested.as_str()?)?, depth + 1)?;
+                included = Some(match included {
+                    // Codes must be in both the system part and every ValueSet
+                    Some(codes) => codes.into_iter().filter(|c| nested.contains(c)).collect(),
+                    None => nested,
+                });
+            }
+            for concept in included? {
+                if !concepts.contains(&concept) {
+                    concepts.push(concept);
+                }
+            }
+        }
+
+        for exclude in vs
+            .pointer("/compose/exclude")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if exclude.get("filter").is_some() || exclude.get("valueSet").is_some() {
+                return None;
+            }
+            let system = str_field(exclude, "system");
+            match exclude.get("concept") {
+                Some(listed) => {
+                    let mut excluded = Vec::new();
+                    collect(Some(listed), system, "", &mut excluded);
+                    concepts.retain(|c| {
+                        !excluded
+                            .iter()
+                            .any(|e| e.code == c.code && (system.is_none() || e.system == c.system))
+                    });
+                }
+                None => concepts.retain(|c| c.system.as_deref() != system),
+            }
+        }
+        Some(concepts)
+    }
+}
+
+fn str_field<'v>(value: &'v JsonValue, name: &str) -> Option<&'v str> {
+    value.get(name).and_then(JsonValue::as_str)
+}
+
+/// Collect concepts, descending into `nested` (`concept` or `contains`) when set
+fn collect(
+    concepts: Option<&JsonValue>,
+    system: Option<&str>,
+    nested: &str,
+    out: &mut Vec<LocalConcept>,
+) {
+    for concept in concepts.and_then(JsonValue::as_array).into_iter().flatten() {
+        if let Some(code) = str_field(concept, "code") {
+            let concept_system = str_field(concept, "system").or(system);
+            if !out
+                .iter()
+                .any(|c| c.code == code && c.system.as_deref() == concept_system)
+            {
+                out.push(LocalConcept {
+                    system: concept_system.map(String::from),
+                    code: code.to_string(),
+                    display: str_field(concept, "display").map(String::from),
+                });
+            }
+        }
+        if !nested.is_empty() {
+            collect(concept.get(nested), system, nested, out);
+        }
+    }
+}
+
+fn code_system_display(concepts: Option<&JsonValue>, code: &str) -> Option<String> {
+    concepts?.as_array()?.iter().find_map(|concept| {
+        if str_field(concept, "code") == Some(code) {
+            str_field(concept, "display").map(String::from)
+        } else {
+            code_system_display(concept.get("concept"), code)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resources() -> Vec<JsonValue> {
+        vec![
+            json!({
+                "resourceType": "CodeSystem",
+                "url": "http://example.org/cs",
+                "concept": [
+                    { "code": "a", "display": "A", "concept": [{ "code": "a1", "display": "A1" }] },
+                    { "code": "b", "display": "B" }
+                ]
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/vs/all",
+                "compose": {
+                    "include": [{ "system": "http://example.org/cs" }],
+                    "exclude": [{ "system": "http://example.org/cs", "concept": [{ "code": "b" }] }]
+                }
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/vs/listed",
+                "compose": { "include": [{ "system": "http://example.org/cs", "concept": [{ "code": "a1" }] }] }
+            }),
+            json!({
+                "resourceType": "ValueSet",
+                "url": "http://example.org/vs/external",
+                "compose": { "include": [{ "system": "http://loinc.org" }] }
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_expand_local_value_sets() {
+        let resources = resources();
+        let terminology = LocalTerminology::new(&resources);
+
+        let all = terminology
+            .expand("http://example.org/vs/all|1.0.0")
+            .unwrap();
+        assert_eq!(
+            all.iter().map(|c| c.code.as_str()).collect::<Vec<_>>(),
+            vec!["a", "a1"]
+        );
+        assert_eq!(all[0].system.as_deref(), Some("http://example.org/cs"));
+
+        let listed = terminology.expand("http://example.org/vs/listed").unwrap();
+        assert_eq!(listed[0].display.as_deref(), Some("A1"));
+
+        assert!(
+            terminology
+                .expand("http://example.org/vs/external")
+                .is_none()
+        );
+        assert!(
+            terminology
+                .expand("http://example.org/vs/missing")
+                .is_none()
+        );
+    }
+}
@@ -12,7 +12,17 @@
 //!
 //! - `fhir_types` - FHIR type definitions (StructureDefinition, ElementDefinition, etc.)
 //! - `profile_exporter` - Exports FSH Profiles to FHIR StructureDefinitions
+//! - `xml_serializer` - Serializes exported resources to FHIR XML
 //! - `build` - Build orchestrator for complete IG generation
+//! - `build_report` - Machine-readable build report (timings, provenance, diagnostics)
+//! - `build_profile` - Per-entity export timings and lookup counts (`--profile-build`)
+//! - `bundle_generator` - Example transaction Bundle / NDJSON output
+//! - `source_map` - FSH → generated JSON source maps (`*.fshmap.json`)
+//! - `narrative` - Generated `text` narratives for instances and terminology
+//! - `compat` - Breaking-change report between two IG versions
+//! - `codegen` - TypeScript / Rust / JSON Schema types from profiles (`maki codegen`)
+//! - `docs_site` - Static HTML/Markdown preview site (`maki docs`)
+//! - `release` - Per-release builds and `// maki-if` release pragmas
 //!
 //! ## Status
 //!
@@ -27,35 +37,57 @@
 
 pub mod build;
 pub mod build_cache;
+pub mod build_profile;
+pub mod build_report;
+pub mod bundle_generator;
+pub mod codegen;
 pub mod codesystem_exporter;
+pub mod compat;
 pub mod differential_generator;
+pub mod docs_site;
+pub(crate) mod element_tree;
+pub mod example_generator;
 pub mod extension_exporter;
 pub mod fhir_types;
 pub mod file_structure;
 pub mod ig_generator;
 pub mod instance_exporter;
 pub mod invariant_processor;
+pub mod local_terminology;
 pub mod logical_exporter;
 pub mod mapping_exporter;
 pub mod menu_generator;
+pub mod narrative;
 pub mod package_json;
 pub mod predefined_resources;
 pub mod profile_exporter;
+pub mod release;
 pub mod ruleset_integration;
 pub mod snapshot;
+pub mod source_map;
 pub mod valueset_exporter;
+pub mod xml_serializer;
 
 pub use build::{BuildError, BuildOptions, BuildOrchestrator, BuildResult, BuildStats};
 pub use build_cache::{BuildCache, CacheStats, IncrementalBuildInfo};
+pub use build_profile::{BuildProfile, BuildProfiler, DeferredRetries, EntityTiming};
+pub use build_report::{
+    BuildDiagnostic, BuildDiagnosticSeverity, BuildPhase, BuildReport, PhaseTiming, SourceSpan,
+};
+pub use bundle_generator::ExampleBundleGenerator;
+pub use codegen::{CodeGenerator, CodegenLanguage, GeneratedFile};
 pub use codesystem_exporter::CodeSystemExporter;
+pub use compat::{ChangeKind, CompatChange, CompatReport, Compatibility};
 pub use differential_generator::{
     DifferentialError, DifferentialGenerator, RuleContext, RuleProcessor,
 };
+pub use docs_site::{DocsFormat, DocsPage, DocsSiteGenerator};
+pub use example_generator::ExampleGenerator;
 pub use extension_exporter::ExtensionExporter;
 pub use fhir_types::*;
 pub use file_structure::{
-    DATA_DIR, FSH_GENERATED_DIR, FileStructureError, FileStructureGenerator, FshIndexEntry,
-    INCLUDES_DIR, RESOURCES_DIR, format_fsh_index_table,
+    BUNDLES_DIR, DATA_DIR, FSH_GENERATED_DIR, FSHMAP_DIR, FileStructureError,
+    FileStructureGenerator, FshIndexEntry, INCLUDES_DIR, RESOURCES_DIR, format_fsh_index_table,
 };
 pub use ig_generator::{
     Definition, DependsOn, Grouping, ImplementationGuide, ImplementationGuideGenerator, Page,
@@ -63,17 +95,23 @@ pub use ig_generator::{
 };
 pub use instance_exporter::InstanceExporter;
 pub use invariant_processor::InvariantProcessor;
+pub use local_terminology::{LocalConcept, LocalTerminology};
 pub use logical_exporter::LogicalExporter;
 pub use mapping_exporter::MappingExporter;
 pub use menu_generator::MenuGenerator;
+pub use narrative::{NARRATIVE_TEMPLATE_DIR, NarrativeGenerator};
 pub use package_json::{Maintainer, PackageJson, Repository};
 pub use predefined_resources::{
     ConflictInfo, GeneratedResourceInfo, PREDEFINED_PACKAGE_NAME, PREDEFINED_PACKAGE_VERSION,
-    PredefinedResource, PredefinedResourceError, PredefinedResourcesLoader,
+    PredefinedResource, PredefinedResourceError, PredefinedResourceIssue,
+    PredefinedResourcesLoader,
 };
-pub use profile_exporter::{ExportError, ProfileExporter};
+pub use profile_exporter::{ExportError, ProfileExporter, RuleFailure};
+pub use release::{PragmaIssue, apply_release_pragmas, configured_releases, release_output_dir};
 pub use snapshot::{SnapshotError, SnapshotGenerator};
+pub use source_map::{FSHMAP_SUFFIX, FshRuleLocation, FshSourceMap, SourceMapBuilder};
 pub use valueset_exporter::ValueSetExporter;
+pub use xml_serializer::{ElementOrderIndex, FhirXmlSerializer, XmlSerializeError};
 
 /// Execute a blocking filesystem operation without starving Tokio's scheduler.
 ///
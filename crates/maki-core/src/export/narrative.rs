@@ -0,0 +1,677 @@
+//! Narrative generation
+//!
+//! Generates a `text` narrative (`status: generated` plus an xhtml `div`) for
+//! exported instances, ValueSets and CodeSystems (`maki build --narrative`).
+//!
+//! Narratives are rendered from templates, one per resource type. A template
+//! holds the xhtml *content* of the div; the generator adds the surrounding
+//! `<div xmlns="http://www.w3.org/1999/xhtml">`. Placeholders:
+//!
+//! - `{{path}}` – a value from the resource (`{{status}}`, `{{code.coding}}`);
+//!   `{{title|name}}` uses the first alternative that has a value
+//! - `{{elements}}` – a table of every top-level element, in StructureDefinition
+//!   element order
+//! - `{{compose}}` – the ValueSet compose definition
+//! - `{{concepts}}` – the CodeSystem concept table
+//!
+//! Built-in templates can be overridden with `input/narrative-templates/<Type>.html`
+//! (`default.html` replaces the generic template used for all other types).
+//! Resources that already have `text` are left alone.
+
+use crate::canonical::codesystem::CodeSystemValidator;
+use crate::export::xml_serializer::ElementOrderIndex;
+use serde_json::{Map, Value as JsonValue, json};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::debug;
+
+/// Directory (under `input/`) holding narrative template overrides
+pub const NARRATIVE_TEMPLATE_DIR: &str = "narrative-templates";
+
+/// Template key for the generic template
+const DEFAULT_TEMPLATE: &str = "default";
+
+const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+const GENERIC_TEMPLATE: &str =
+    "<p><b>Generated Narrative: {{resourceType}} {{id}}</b></p>{{elements}}";
+const VALUESET_TEMPLATE: &str = "<p><b>{{title|name|id}}</b></p>{{compose}}";
+const CODESYSTEM_TEMPLATE: &str = "<p><b>{{title|name|id}}</b></p><p>This code system defines the following codes:</p>{{concepts}}";
+
+/// Resource types that are not DomainResources and therefore have no `text`
+const NO_NARRATIVE_TYPES: &[&str] = &["Bundle", "Binary", "Parameters"];
+
+/// Elements left out of the generic element table
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "resourceType",
+    "id",
+    "meta",
+    "implicitRules",
+    "language",
+    "text",
+    "contained",
+    "extension",
+    "modifierExtension",
+];
+
+/// Renders `text` narratives from per-resource-type templates
+pub struct NarrativeGenerator<'a> {
+    order: &'a ElementOrderIndex,
+    code_systems: &'a CodeSystemValidator,
+    templates: HashMap<String, String>,
+}
+
+impl<'a> NarrativeGenerator<'a> {
+    /// Create a generator with the built-in templates
+    ///
+    /// `order` provides element order for the generic template; `code_systems`
+    /// supplies displays for codes that don't carry one.
+    pub fn new(order: &'a ElementOrderIndex, code_systems: &'a CodeSystemValidator) -> Self {
+        let templates = HashMap::from([
+            (DEFAULT_TEMPLATE.to_string(), GENERIC_TEMPLATE.to_string()),
+            ("ValueSet".to_string(), VALUESET_TEMPLATE.to_string()),
+            ("CodeSystem".to_string(), CODESYSTEM_TEMPLATE.to_string()),
+        ]);
+        Self {
+            order,
+            code_systems,
+            templates,
+        }
+    }
+
+    /// Override the template for a resource type (`default` for the generic one)
+    pub fn set_template(&mut self, resource_type: impl Into<String>, template: impl Into<String>) {
+        self.templates.insert(resource_type.into(), template.into());
+    }
+
+    /// Load `<Type>.html` template overrides from a directory
+    ///
+    /// Returns the number of templates loaded.
+    pub fn load_templates(&mut self, dir: &Path) -> std::io::Result<usize> {
+        let mut loaded = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("html") {
+                continue;
+            }
+            let Some(resource_type) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let template = std::fs::read_to_string(&path)?;
+            debug!(
+                "Loaded narrative template for {} from {:?}",
+                resource_type, path
+            );
+            self.set_template(resource_type, template.trim());
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Add a generated narrative to `resource` unless it already has `text`
+    ///
+    /// Returns `true` if the resource was changed.
+    pub fn apply(&self, resource: &mut JsonValue) -> bool {
+        let Some(map) = resource.as_object() else {
+            return false;
+        };
+        let resource_type = map
+            .get("resourceType")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        if map.contains_key("text") || NO_NARRATIVE_TYPES.contains(&resource_type) {
+            return false;
+        }
+
+        let text = json!({ "status": "generated", "div": self.render(resource) });
+        if let Some(map) = resource.as_object_mut() {
+            insert_text(map, text);
+        }
+        true
+    }
+
+    /// Render the narrative div for a resource
+    pub fn render(&self, resource: &JsonValue) -> String {
+        let resource_type = resource
+            .get("resourceType")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        let template = self
+            .templates
+            .get(resource_type)
+            .or_else(|| self.templates.get(DEFAULT_TEMPLATE))
+            .map_or(GENERIC_TEMPLATE, String::as_str);
+
+        format!(
+            "<div xmlns=\"{}\">{}</div>",
+            XHTML_NAMESPACE,
+            self.fill(template, resource)
+        )
+    }
+
+    /// Replace the `{{...}}` placeholders of a template
+    fn fill(&self, template: &str, resource: &JsonValue) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find("}}") else {
+                rest = &rest[start..];
+                break;
+            };
+            let placeholder = rest[start + 2..start + end].trim();
+            out.push_str(&self.placeholder(placeholder, resource));
+            rest = &rest[start + end + 2..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn placeholder(&self, placeholder: &str, resource: &JsonValue) -> String {
+        match placeholder {
+            "elements" => self.element_table(resource),
+            "compose" => self.compose(resource),
+            "concepts" => concept_table(resource.get("concept")),
+            _ => placeholder
+                .split('|')
+                .map(|path| {
+                    lookup(resource, path.trim())
+                        .iter()
+                        .map(|value| self.summarize(value))
+                        .filter(|s| !s.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .find(|s| !s.is_empty())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Table of the top-level elements in StructureDefinition order
+    fn element_table(&self, resource: &JsonValue) -> String {
+        let Some(map) = resource.as_object() else {
+            return String::new();
+        };
+        let resource_type = map
+            .get("resourceType")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        let names: Vec<&str> = map
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !name.starts_with('_') && !SKIPPED_ELEMENTS.contains(name))
+            .collect();
+
+        let rows: String = self
+            .order
+            .order_names(resource_type, &names)
+            .into_iter()
+            .filter_map(|name| {
+                let value = self.summarize(&map[name]);
+                (!value.is_empty()).then(|| {
+                    format!(
+                        "<tr><td><b>{}</b></td><td>{}</td></tr>",
+                        escape(&label(name)),
+                        value
+                    )
+                })
+            })
+            .collect();
+        if rows.is_empty() {
+            String::new()
+        } else {
+            format!("<table class=\"grid\">{}</table>", rows)
+        }
+    }
+
+    /// ValueSet compose as a list of include/exclude statements
+    fn compose(&self, resource: &JsonValue) -> String {
+        let Some(compose) = resource.get("compose") else {
+            return String::new();
+        };
+        let mut items = String::new();
+        for (key, verb) in [("include", "Include"), ("exclude", "Exclude")] {
+            for component in compose
+                .get(key)
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+            {
+                items.push_str(&format!(
+                    "<li>{}</li>",
+                    self.compose_component(verb, component)
+                ));
+            }
+        }
+        if items.is_empty() {
+            String::new()
+        } else {
+            format!("<ul>{}</ul>", items)
+        }
+    }
+
+    fn compose_component(&self, verb: &str, component: &JsonValue) -> String {
+        let system = component.get("system").and_then(JsonValue::as_str);
+        let system_label = system
+            .map(|s| format!("<code>{}</code>", escape(s)))
+            .unwrap_or_default();
+
+        if let Some(concepts) = component.get("concept").and_then(JsonValue::as_array) {
+            let codes: String = concepts
+                .iter()
+                .filter_map(|concept| {
+                    let code = concept.get("code")?.as_str()?;
+                    let display = concept
+                        .get("display")
+                        .and_then(JsonValue::as_str)
+                        .map(str::to_string)
+                        .or_else(|| system.and_then(|s| self.display_for(s, code)));
+                    Some(match display {
+                        Some(display) => format!(
+                            "<li><code>{}</code>: {}</li>",
+                            escape(code),
+                            escape(&display)
+                        ),
+                        None => format!("<li><code>{}</code></li>", escape(code)),
+                    })
+                })
+                .collect();
+            return format!(
+                "{} these codes from {}:<ul>{}</ul>",
+                verb, system_label, codes
+            );
+        }
+
+        let mut parts = Vec::new();
+        if system.is_some() {
+            parts.push(format!("all codes from {}", system_label));
+        }
+        let filters: Vec<String> = component
+            .get("filter")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .map(|filter| {
+                let field = |name: &str| filter.get(name).and_then(JsonValue::as_str).unwrap_or("");
+                format!(
+                    "{} {} {}",
+                    escape(field("property")),
+                    escape(field("op")),
+                    escape(field("value"))
+                )
+            })
+            .collect();
+        if !filters.is_empty() {
+            parts.push(format!("where {}", filters.join(" and ")));
+        }
+        let value_sets: Vec<String> = component
+            .get("valueSet")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(JsonValue::as_str)
+            .map(|vs| format!("<code>{}</code>", escape(vs)))
+            .collect();
+        if !value_sets.is_empty() {
+            parts.push(format!("codes from {}", value_sets.join(", ")));
+        }
+        format!("{} {}", verb, parts.join(" "))
+    }
+
+    /// One-line xhtml summary of an element value
+    fn summarize(&self, value: &JsonValue) -> String {
+        match value {
+            JsonValue::Null => String::new(),
+            JsonValue::String(s) => escape(s),
+            JsonValue::Bool(_) | JsonValue::Number(_) => value.to_string(),
+            JsonValue::Array(items) => items
+                .iter()
+                .map(|item| self.summarize(item))
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(", "),
+            JsonValue::Object(map) => self.summarize_object(map),
+        }
+    }
+
+    fn summarize_object(&self, map: &Map<String, JsonValue>) -> String {
+        let text = |name: &str| map.get(name).and_then(JsonValue::as_str);
+
+        // Resource (contained, Bundle entry)
+        if let Some(resource_type) = text("resourceType") {
+            return escape(&format!(
+                "{}/{}",
+                resource_type,
+                text("id").unwrap_or_default()
+            ));
+        }
+        // CodeableConcept
+        if map.contains_key("coding") || (map.len() == 1 && map.contains_key("text")) {
+            return match text("text") {
+                Some(t) => escape(t),
+                None => self.summarize(&map["coding"]),
+            };
+        }
+        // Coding
+        if let Some(code) = text("code")
+            && (map.contains_key("system") || map.contains_key("display"))
+            && !map.contains_key("value")
+        {
+            let display = text("display")
+                .map(str::to_string)
+                .or_else(|| text("system").and_then(|s| self.display_for(s, code)));
+            return match (display, text("system")) {
+                (Some(display), _) => {
+                    format!("{} (<code>{}</code>)", escape(&display), escape(code))
+                }
+                (None, Some(system)) => format!("<code>{}#{}</code>", escape(system), escape(code)),
+                (None, None) => format!("<code>{}</code>", escape(code)),
+            };
+        }
+        // Reference
+        if map.contains_key("reference")
+            || map.contains_key("identifier") && map.contains_key("display")
+        {
+            return escape(text("display").or(text("reference")).unwrap_or_default());
+        }
+        // Quantity
+        if let Some(number) = map.get("value").filter(|v| v.is_number()) {
+            let unit = text("unit").or(text("code")).unwrap_or_default();
+            return escape(format!("{} {}", number, unit).trim());
+        }
+        // HumanName / Address
+        if let Some(t) = text("text") {
+            return escape(t);
+        }
+        if map.contains_key("family") || map.contains_key("given") {
+            let mut parts: Vec<&str> = lookup_strs(map, "given");
+            parts.extend(text("family"));
+            return escape(&parts.join(" "));
+        }
+        if map.contains_key("line") || map.contains_key("city") {
+            let mut parts: Vec<&str> = lookup_strs(map, "line");
+            for field in ["city", "state", "postalCode", "country"] {
+                parts.extend(text(field));
+            }
+            return escape(&parts.join(", "));
+        }
+        // Identifier / ContactPoint
+        if let Some(value) = text("value") {
+            return match text("system") {
+                Some(system) => format!("{} ({})", escape(value), escape(system)),
+                None => escape(value),
+            };
+        }
+        // Period
+        if map.contains_key("start") || map.contains_key("end") {
+            return escape(&format!(
+                "{} – {}",
+                text("start").unwrap_or("?"),
+                text("end").unwrap_or("?")
+            ));
+        }
+
+        map.iter()
+            .filter(|(key, _)| !key.starts_with('_') && !matches!(key.as_str(), "id" | "extension"))
+            .map(|(key, value)| format!("{}: {}", escape(&label(key)), self.summarize(value)))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn display_for(&self, system: &str, code: &str) -> Option<String> {
+        self.code_systems
+            .get_code_system(system)?
+            .get_concept(code)?
+            .display
+            .clone()
+    }
+}
+
+/// CodeSystem concepts as a code/display/definition table (nested concepts indented)
+fn concept_table(concepts: Option<&JsonValue>) -> String {
+    fn rows(concepts: &JsonValue, depth: usize, out: &mut String) {
+        for concept in concepts.as_array().into_iter().flatten() {
+            let field = |name: &str| {
+                escape(
+                    concept
+                        .get(name)
+                        .and_then(JsonValue::as_str)
+                        .unwrap_or_default(),
+                )
+            };
+            out.push_str(&format!(
+                "<tr><td>{}<code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                "&#160;&#160;".repeat(depth),
+                field("code"),
+                field("display"),
+                field("definition")
+            ));
+            if let Some(children) = concept.get("concept") {
+                rows(children, depth + 1, out);
+            }
+        }
+    }
+
+    let Some(concepts) = concepts else {
+        return String::new();
+    };
+    let mut out = String::from(
+        "<table class=\"codes\"><tr><td><b>Code</b></td><td><b>Display</b></td><td><b>Definition</b></td></tr>",
+    );
+    rows(concepts, 0, &mut out);
+    out.push_str("</table>");
+    out
+}
+
+/// Insert `text` after the resource header elements (FHIR JSON element order)
+fn insert_text(map: &mut Map<String, JsonValue>, text: JsonValue) {
+    let entries: Vec<(String, JsonValue)> = std::mem::take(map).into_iter().collect();
+    let position = entries
+        .iter()
+        .rposition(|(key, _)| {
+            matches!(
+                key.as_str(),
+                "resourceType"
+                    | "id"
+                    | "meta"
+                    | "implicitRules"
+                    | "_implicitRules"
+                    | "language"
+                    | "_language"
+            )
+        })
+        .map_or(0, |i| i + 1);
+
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i == position {
+            map.insert("text".to_string(), text.clone());
+        }
+        map.insert(key, value);
+    }
+    if !map.contains_key("text") {
+        map.insert("text".to_string(), text);
+    }
+}
+
+/// Values at a dotted path (arrays are flattened)
+fn lookup<'v>(value: &'v JsonValue, path: &str) -> Vec<&'v JsonValue> {
+    let mut current = vec![value];
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        current = current
+            .into_iter()
+            .filter_map(|v| v.get(segment))
+            .flat_map(|v| match v {
+                JsonValue::Array(items) => items.iter().collect(),
+                other => vec![other],
+            })
+            .collect();
+    }
+    current
+}
+
+fn lookup_strs<'v>(map: &'v Map<String, JsonValue>, name: &str) -> Vec<&'v str> {
+    match map.get(name) {
+        Some(JsonValue::Array(items)) => items.iter().filter_map(JsonValue::as_str).collect(),
+        Some(JsonValue::String(s)) => vec![s.as_str()],
+        _ => Vec::new(),
+    }
+}
+
+/// Turn an element name into a label (`birthDate` → `Birth Date`)
+fn label(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if i == 0 {
+            out.extend(c.to_uppercase());
+        } else {
+            if c.is_uppercase() {
+                out.push(' ');
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonical::codesystem::{CodeSystem, Concept};
+
+    fn patient_order() -> ElementOrderIndex {
+        let mut index = ElementOrderIndex::new();
+        index.add_structure_definition(&json!({
+            "type": "Patient",
+            "snapshot": { "element": [
+                { "path": "Patient" },
+                { "path": "Patient.id" },
+                { "path": "Patient.text" },
+                { "path": "Patient.active" },
+                { "path": "Patient.name" },
+                { "path": "Patient.gender" },
+                { "path": "Patient.maritalStatus" }
+            ]}
+        }));
+        index
+    }
+
+    fn code_systems() -> CodeSystemValidator {
+        let mut validator = CodeSystemValidator::new();
+        let mut cs = CodeSystem::new("http://example.org/cs".to_string(), "CS".to_string());
+        cs.add_concept(Concept::new("M".to_string()).with_display("Married".to_string()));
+        validator.load_code_system(cs);
+        validator
+    }
+
+    #[test]
+    fn test_generic_narrative_follows_element_order() {
+        let order = patient_order();
+        let codes = code_systems();
+        let generator = NarrativeGenerator::new(&order, &codes);
+
+        let mut patient = json!({
+            "resourceType": "Patient",
+            "id": "example",
+            "maritalStatus": { "coding": [{ "system": "http://example.org/cs", "code": "M" }] },
+            "name": [{ "given": ["Jane"], "family": "Doe" }],
+            "active": true
+        });
+        assert!(generator.apply(&mut patient));
+
+        let keys: Vec<&String> = patient.as_object().unwrap().keys().collect();
+        assert_eq!(keys[2], "text");
+        assert_eq!(patient["text"]["status"], "generated");
+
+        let div = patient["text"]["div"].as_str().unwrap();
+        assert!(div.starts_with("<div xmlns=\"http://www.w3.org/1999/xhtml\">"));
+        assert!(div.contains("Generated Narrative: Patient example"));
+        let active = div.find("Active").unwrap();
+        let name = div.find("Jane Doe").unwrap();
+        let marital = div.find("Married (<code>M</code>)").unwrap();
+        assert!(active < name && name < marital);
+    }
+
+    #[test]
+    fn test_existing_text_is_left_alone() {
+        let order = ElementOrderIndex::new();
+        let codes = CodeSystemValidator::new();
+        let generator = NarrativeGenerator::new(&order, &codes);
+
+        let mut patient = json!({
+            "resourceType": "Patient",
+            "text": { "status": "additional", "div": "<div>mine</div>" }
+        });
+        assert!(!generator.apply(&mut patient));
+        assert_eq!(patient["text"]["div"], "<div>mine</div>");
+
+        let mut bundle = json!({ "resourceType": "Bundle", "type": "collection" });
+        assert!(!generator.apply(&mut bundle));
+    }
+
+    #[test]
+    fn test_valueset_and_codesystem_templates() {
+        let order = ElementOrderIndex::new();
+        let codes = code_systems();
+        let generator = NarrativeGenerator::new(&order, &codes);
+
+        let vs = json!({
+            "resourceType": "ValueSet",
+            "name": "MaritalVS",
+            "compose": {
+                "include": [
+                    { "system": "http://example.org/cs", "concept": [{ "code": "M" }] },
+                    { "system": "http://loinc.org", "filter": [{ "property": "class", "op": "=", "value": "LAB" }] }
+                ],
+                "exclude": [{ "valueSet": ["http://example.org/vs"] }]
+            }
+        });
+        let div = generator.render(&vs);
+        assert!(div.contains("<b>MaritalVS</b>"));
+        assert!(div.contains("<li><code>M</code>: Married</li>"));
+        assert!(div.contains("all codes from <code>http://loinc.org</code> where class = LAB"));
+        assert!(div.contains("Exclude codes from <code>http://example.org/vs</code>"));
+
+        let cs = json!({
+            "resourceType": "CodeSystem",
+            "title": "Codes & More",
+            "concept": [{ "code": "a", "display": "A", "concept": [{ "code": "b" }] }]
+        });
+        let div = generator.render(&cs);
+        assert!(div.contains("<b>Codes &amp; More</b>"));
+        assert!(div.contains("<td><code>a</code></td><td>A</td>"));
+        assert!(div.contains("&#160;&#160;<code>b</code>"));
+    }
+
+    #[test]
+    fn test_template_overrides() {
+        let order = ElementOrderIndex::new();
+        let codes = CodeSystemValidator::new();
+        let mut generator = NarrativeGenerator::new(&order, &codes);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Observation.html"),
+            "<p>{{code.coding}} = {{valueQuantity|valueString}}</p>\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        assert_eq!(generator.load_templates(dir.path()).unwrap(), 1);
+
+        let obs = json!({
+            "resourceType": "Observation",
+            "code": { "coding": [{ "system": "http://loinc.org", "code": "8867-4", "display": "Heart rate" }] },
+            "valueQuantity": { "value": 72, "unit": "beats/min" }
+        });
+        assert_eq!(
+            generator.render(&obs),
+            "<div xmlns=\"http://www.w3.org/1999/xhtml\"><p>Heart rate (<code>8867-4</code>) = 72 beats/min</p></div>"
+        );
+    }
+}
@@ -251,6 +251,7 @@ impl Default for SushiConfiguration {
             fsh_only: None,
             apply_extension_metadata_to_root: None,
             instance_options: None,
+            output_format: None,
             meta: None,
             implicit_rules: None,
             language: None,
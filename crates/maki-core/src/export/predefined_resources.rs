@@ -25,7 +25,15 @@
 //!
 //! If a predefined resource has the same URL and resource type as a
 //! FSH-generated resource, the FSH resource takes precedence and the
-//! predefined resource is ignored with a warning.
+//! predefined resource is ignored with a warning. Two predefined resources
+//! with the same URL are reported as well; the first one found is kept.
+//!
+//! ## Validation
+//!
+//! Every predefined file must be a JSON resource with a `resourceType` and an
+//! `id`; canonical resources also need a `url`. The IG Publisher expects the
+//! file to be named after the resource (`<id>.json` or
+//! `<resourceType>-<id>.json`), so other names are reported.
 //!
 //! **SUSHI Reference**: `src/ig/predefinedResources.ts`
 
@@ -56,6 +64,29 @@ const STANDARD_RESOURCE_DIRS: &[&str] = &[
     "examples",
 ];
 
+/// Resource types that are identified by a canonical URL
+const CANONICAL_RESOURCE_TYPES: &[&str] = &[
+    "ActivityDefinition",
+    "CapabilityStatement",
+    "CodeSystem",
+    "CompartmentDefinition",
+    "ConceptMap",
+    "GraphDefinition",
+    "ImplementationGuide",
+    "Library",
+    "Measure",
+    "MessageDefinition",
+    "NamingSystem",
+    "OperationDefinition",
+    "PlanDefinition",
+    "Questionnaire",
+    "SearchParameter",
+    "StructureDefinition",
+    "StructureMap",
+    "TerminologyCapabilities",
+    "ValueSet",
+];
+
 /// A loaded predefined FHIR resource
 #[derive(Debug, Clone)]
 pub struct PredefinedResource {
@@ -116,6 +147,41 @@ impl PredefinedResource {
         })
     }
 
+    /// Whether the resource type is identified by a canonical URL
+    pub fn is_canonical(&self) -> bool {
+        CANONICAL_RESOURCE_TYPES.contains(&self.resource_type.as_str())
+    }
+
+    /// Check the resource for a missing `id`/`url` and a file name that
+    /// doesn't match its id
+    pub fn check(&self) -> Vec<PredefinedResourceIssue> {
+        let mut issues = Vec::new();
+        match &self.id {
+            Some(id) => {
+                let stem = self.filename.trim_end_matches(".json");
+                let typed = format!("{}-{}", self.resource_type, id);
+                if stem != id && stem != typed {
+                    issues.push(PredefinedResourceIssue::FilenameMismatch {
+                        file_path: self.file_path.clone(),
+                        id: id.clone(),
+                        expected: format!("{}.json", typed),
+                    });
+                }
+            }
+            None => issues.push(PredefinedResourceIssue::MissingField {
+                file_path: self.file_path.clone(),
+                field: "id",
+            }),
+        }
+        if self.is_canonical() && self.url.is_none() {
+            issues.push(PredefinedResourceIssue::MissingField {
+                file_path: self.file_path.clone(),
+                field: "url",
+            });
+        }
+        issues
+    }
+
     /// Get a unique key for conflict detection
     pub fn conflict_key(&self) -> String {
         if let Some(ref url) = self.url {
@@ -212,16 +278,10 @@ impl PredefinedResourcesLoader {
         }
     }
 
-    /// Load all predefined resources from discovered paths
-    ///
-    /// Searches all resource directories for JSON files and loads them
-    /// as FHIR resources. Non-JSON files and invalid resources are skipped
-    /// with warnings.
-    pub fn load_all(&self) -> Result<Vec<PredefinedResource>, PredefinedResourceError> {
-        let mut resources = Vec::new();
-        let resource_paths = self.get_resource_paths();
-
-        for dir_path in resource_paths {
+    /// JSON files in all resource directories
+    fn json_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for dir_path in self.get_resource_paths() {
             // Collect JSON paths using blocking IO helper to avoid starving Tokio
             let json_files: Vec<PathBuf> = run_blocking_io(|| {
                 WalkDir::new(&dir_path)
@@ -240,20 +300,30 @@ impl PredefinedResourcesLoader {
                     })
                     .collect()
             });
+            files.extend(json_files);
+        }
+        files
+    }
 
-            for path in json_files {
-                match PredefinedResource::from_file(&path) {
-                    Ok(resource) => {
-                        resources.push(resource);
-                    }
-                    Err(e) => {
-                        // Log warning but continue processing
-                        warn!(
-                            "Failed to load predefined resource from {}: {}",
-                            path.display(),
-                            e
-                        );
-                    }
+    /// Load all predefined resources from discovered paths
+    ///
+    /// Searches all resource directories for JSON files and loads them
+    /// as FHIR resources. Non-JSON files and invalid resources are skipped
+    /// with warnings.
+    pub fn load_all(&self) -> Result<Vec<PredefinedResource>, PredefinedResourceError> {
+        let mut resources = Vec::new();
+        for path in self.json_files() {
+            match PredefinedResource::from_file(&path) {
+                Ok(resource) => {
+                    resources.push(resource);
+                }
+                Err(e) => {
+                    // Log warning but continue processing
+                    warn!(
+                        "Failed to load predefined resource from {}: {}",
+                        path.display(),
+                        e
+                    );
                 }
             }
         }
@@ -261,6 +331,51 @@ impl PredefinedResourcesLoader {
         Ok(resources)
     }
 
+    /// Load and validate all predefined resources
+    ///
+    /// Unlike [`load_all`](Self::load_all), problems are returned as issues:
+    /// files that are not valid resources, missing `id`/`url`, file names
+    /// that don't match the id, and URLs defined by more than one file. Of
+    /// resources sharing a URL only the first is returned.
+    pub fn load_checked(&self) -> (Vec<PredefinedResource>, Vec<PredefinedResourceIssue>) {
+        let mut resources: Vec<PredefinedResource> = Vec::new();
+        let mut issues = Vec::new();
+        let mut by_key: HashMap<String, usize> = HashMap::new();
+
+        for path in self.json_files() {
+            let resource = match PredefinedResource::from_file(&path) {
+                Ok(resource) => resource,
+                Err(error) => {
+                    issues.push(PredefinedResourceIssue::Invalid {
+                        file_path: path,
+                        message: error.to_string(),
+                    });
+                    continue;
+                }
+            };
+            issues.extend(resource.check());
+
+            if resource.url.is_some() {
+                let key = resource.conflict_key();
+                if let Some(&first) = by_key.get(&key) {
+                    let kept = &resources[first];
+                    issues.push(PredefinedResourceIssue::DuplicateUrl {
+                        url: resource.url.clone().unwrap_or_default(),
+                        file_path: resource.file_path.clone(),
+                        id: resource.id.clone(),
+                        kept_path: kept.file_path.clone(),
+                        kept_id: kept.id.clone(),
+                    });
+                    continue;
+                }
+                by_key.insert(key, resources.len());
+            }
+            resources.push(resource);
+        }
+
+        (resources, issues)
+    }
+
     /// Load resources and check for conflicts with generated resources
     ///
     /// # Arguments
@@ -274,26 +389,48 @@ impl PredefinedResourcesLoader {
         &self,
         generated_resources: &HashMap<String, GeneratedResourceInfo>,
     ) -> Result<(Vec<PredefinedResource>, Vec<ConflictInfo>), PredefinedResourceError> {
-        let all_resources = self.load_all()?;
-        let mut non_conflicting = Vec::new();
-        let mut conflicts = Vec::new();
-
-        for resource in all_resources {
-            let key = resource.conflict_key();
-
-            if let Some(generated_info) = generated_resources.get(&key) {
-                // Conflict detected
-                conflicts.push(ConflictInfo {
-                    resource,
-                    generated_resource: generated_info.clone(),
-                });
-            } else {
-                // No conflict
-                non_conflicting.push(resource);
-            }
+        Ok(split_conflicts(self.load_all()?, generated_resources))
+    }
+}
+
+/// Split predefined resources into those FSH doesn't define and conflicts
+///
+/// FSH-generated resources take precedence, so conflicting predefined
+/// resources are left out of the first list.
+pub fn split_conflicts(
+    resources: Vec<PredefinedResource>,
+    generated_resources: &HashMap<String, GeneratedResourceInfo>,
+) -> (Vec<PredefinedResource>, Vec<ConflictInfo>) {
+    let mut non_conflicting = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for resource in resources {
+        let key = resource.conflict_key();
+
+        if let Some(generated_info) = generated_resources.get(&key) {
+            // Conflict detected
+            conflicts.push(ConflictInfo {
+                resource,
+                generated_resource: generated_info.clone(),
+            });
+        } else {
+            // No conflict
+            non_conflicting.push(resource);
         }
+    }
+
+    (non_conflicting, conflicts)
+}
 
-        Ok((non_conflicting, conflicts))
+/// Custom resource directories from the `path-resource` IG parameter
+pub fn path_resource_parameters(parameters: Option<&HashMap<String, JsonValue>>) -> Vec<String> {
+    match parameters.and_then(|p| p.get("path-resource")) {
+        Some(JsonValue::String(path)) => vec![path.clone()],
+        Some(JsonValue::Array(paths)) => paths
+            .iter()
+            .filter_map(|p| p.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
     }
 }
 
@@ -339,7 +476,7 @@ pub struct ConflictInfo {
 impl ConflictInfo {
     /// Get a human-readable conflict description
     pub fn description(&self) -> String {
-        format!(
+        let mut description = format!(
             "Predefined resource {} ({}) conflicts with FSH-generated resource {}",
             self.resource.filename,
             self.resource
@@ -349,7 +486,103 @@ impl ConflictInfo {
                 .map(|s| s.as_str())
                 .unwrap_or("unknown"),
             self.generated_resource.filename
-        )
+        );
+        if let (Some(id), Some(generated_id)) = (&self.resource.id, &self.generated_resource.id)
+            && id != generated_id
+        {
+            description.push_str(&format!(" (ids differ: '{}' and '{}')", id, generated_id));
+        }
+        description.push_str("; the FSH definition is used");
+        description
+    }
+}
+
+/// A problem with a predefined resource file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredefinedResourceIssue {
+    /// The file is not a FHIR resource (unreadable, invalid JSON, or no `resourceType`)
+    Invalid { file_path: PathBuf, message: String },
+    /// A required field is missing
+    MissingField {
+        file_path: PathBuf,
+        field: &'static str,
+    },
+    /// The file name doesn't match the resource id
+    FilenameMismatch {
+        file_path: PathBuf,
+        id: String,
+        expected: String,
+    },
+    /// Another predefined file already defines the URL
+    DuplicateUrl {
+        url: String,
+        file_path: PathBuf,
+        id: Option<String>,
+        kept_path: PathBuf,
+        kept_id: Option<String>,
+    },
+}
+
+impl PredefinedResourceIssue {
+    /// File the issue was found in
+    pub fn file_path(&self) -> &Path {
+        match self {
+            Self::Invalid { file_path, .. }
+            | Self::MissingField { file_path, .. }
+            | Self::FilenameMismatch { file_path, .. }
+            | Self::DuplicateUrl { file_path, .. } => file_path,
+        }
+    }
+
+    /// Whether the file can't be used at all
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Invalid { .. })
+    }
+
+    /// Get a human-readable issue description
+    pub fn description(&self) -> String {
+        match self {
+            Self::Invalid { message, .. } => {
+                format!("Invalid predefined resource: {}", message)
+            }
+            Self::MissingField { file_path, field } => format!(
+                "Predefined resource {} has no '{}'",
+                file_path.display(),
+                field
+            ),
+            Self::FilenameMismatch {
+                file_path,
+                id,
+                expected,
+            } => format!(
+                "Predefined resource {} has id '{}'; name the file {}",
+                file_path.display(),
+                id,
+                expected
+            ),
+            Self::DuplicateUrl {
+                url,
+                file_path,
+                id,
+                kept_path,
+                kept_id,
+            } => {
+                let ids = match (id, kept_id) {
+                    (Some(id), Some(kept_id)) if id != kept_id => {
+                        format!(" with different ids ('{}' and '{}')", id, kept_id)
+                    }
+                    _ => String::new(),
+                };
+                format!(
+                    "Predefined resources {} and {} both define {}{}; using {}",
+                    file_path.display(),
+                    kept_path.display(),
+                    url,
+                    ids,
+                    kept_path.display()
+                )
+            }
+        }
     }
 }
 
@@ -560,4 +793,72 @@ mod tests {
         assert_eq!(non_conflicting.len(), 1);
         assert_eq!(conflicts.len(), 0);
     }
+
+    #[test]
+    fn test_load_checked_reports_issues() {
+        let temp = TempDir::new().unwrap();
+        let input_dir = temp.path().join("input");
+        let profiles_dir = input_dir.join("profiles");
+        let resources_dir = input_dir.join("resources");
+        fs::create_dir_all(&profiles_dir).unwrap();
+        fs::create_dir_all(&resources_dir).unwrap();
+
+        let url = "http://example.org/fhir/StructureDefinition/Patient";
+        create_test_resource(&profiles_dir, "Patient.json", "StructureDefinition", url);
+        // Same URL with another id
+        fs::write(
+            resources_dir.join("StructureDefinition-patient-copy.json"),
+            serde_json::json!({
+                "resourceType": "StructureDefinition",
+                "id": "patient-copy",
+                "url": url
+            })
+            .to_string(),
+        )
+        .unwrap();
+        // No url, and a file name that doesn't match the id
+        fs::write(
+            resources_dir.join("my-valueset.json"),
+            serde_json::json!({ "resourceType": "ValueSet", "id": "colors" }).to_string(),
+        )
+        .unwrap();
+        fs::write(resources_dir.join("broken.json"), "{ not json").unwrap();
+
+        let loader = PredefinedResourcesLoader::new(&input_dir, temp.path(), vec![]);
+        let (resources, issues) = loader.load_checked();
+
+        assert_eq!(resources.len(), 2);
+        assert!(resources.iter().any(|r| r.id.as_deref() == Some("Patient")));
+        assert_eq!(issues.len(), 4, "{:?}", issues);
+        assert!(issues.iter().any(|i| matches!(
+            i,
+            PredefinedResourceIssue::DuplicateUrl { id: Some(id), kept_id: Some(kept), .. }
+                if id == "patient-copy" && kept == "Patient"
+        )));
+        assert!(issues.iter().any(|i| matches!(
+            i,
+            PredefinedResourceIssue::MissingField { field: "url", .. }
+        )));
+        let mismatch = issues
+            .iter()
+            .find(|i| matches!(i, PredefinedResourceIssue::FilenameMismatch { .. }))
+            .unwrap();
+        assert!(mismatch.description().contains("ValueSet-colors.json"));
+        assert_eq!(issues.iter().filter(|i| i.is_error()).count(), 1);
+    }
+
+    #[test]
+    fn test_path_resource_parameters() {
+        let mut parameters = HashMap::new();
+        assert!(path_resource_parameters(Some(&parameters)).is_empty());
+
+        parameters.insert(
+            "path-resource".to_string(),
+            serde_json::json!(["input/extra", "input/more/*"]),
+        );
+        assert_eq!(
+            path_resource_parameters(Some(&parameters)),
+            vec!["input/extra", "input/more/*"]
+        );
+    }
 }
@@ -46,11 +46,13 @@
 
 use super::differential_generator::DifferentialGenerator;
 use super::fhir_types::*;
+use crate::canonical::cross_version::is_cross_version_extension_url;
 use crate::canonical::{CanonicalLoaderError, DefinitionSession};
 use crate::cst::ast::{
     CardRule, CaretValueRule, ContainsRule, FixedValueRule, FlagRule, FlagValue, ObeysRule,
     OnlyRule, Profile, Rule, ValueSetRule,
 };
+use crate::cst::{FshSyntaxNode, TextRange};
 use crate::semantic::path_resolver::PathResolver;
 use serde_json::Value as JsonValue;
 use std::sync::Arc;
@@ -253,6 +255,29 @@ fn get_element_type(element: &ElementDefinition, full_path: &str) -> Option<Stri
 // This follows SUSHI's approach: no hardcoded type lists.
 // See: SUSHI ElementDefinition.ts:369-382 isPrimitive()
 
+/// A rule that could not be applied during export
+///
+/// The rule is skipped and the export continues; the build reports these as
+/// warnings (e.g. an element that doesn't exist in the targeted FHIR release).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleFailure {
+    /// Rule source text (`* contact 1..*`)
+    pub rule: String,
+    /// Byte range of the rule in its FSH file
+    pub range: TextRange,
+    pub message: String,
+}
+
+impl RuleFailure {
+    pub(crate) fn new(rule: &FshSyntaxNode, error: &ExportError) -> Self {
+        Self {
+            rule: rule.text().to_string().trim().to_string(),
+            range: rule.text_range(),
+            message: error.to_string(),
+        }
+    }
+}
+
 /// Profile export errors
 #[derive(Debug, Error)]
 pub enum ExportError {
@@ -435,6 +460,16 @@ impl ProfileExporter {
     /// - Rule application fails
     /// - Required fields missing
     pub async fn export(&self, profile: &Profile) -> Result<StructureDefinition, ExportError> {
+        self.export_with_rule_failures(profile)
+            .await
+            .map(|(structure_def, _)| structure_def)
+    }
+
+    /// Export a Profile, also returning the rules that could not be applied
+    pub async fn export_with_rule_failures(
+        &self,
+        profile: &Profile,
+    ) -> Result<(StructureDefinition, Vec<RuleFailure>), ExportError> {
         let profile_name = profile
             .name()
             .ok_or_else(|| ExportError::MissingRequiredField("profile name".to_string()))?;
@@ -475,11 +510,13 @@ impl ProfileExporter {
         // 5. Apply all rules to snapshot (modifying in-place)
         let all_rules: Vec<_> = profile.rules().collect();
         debug!("Profile has {} rules", all_rules.len());
+        let mut rule_failures = Vec::new();
         for (i, rule) in all_rules.iter().enumerate() {
             debug!("  Rule {}: {:?}", i, std::mem::discriminant(rule));
             if let Err(e) = self.apply_rule(&mut structure_def, rule).await {
                 warn!("Failed to apply rule: {}", e);
                 // Continue with other rules instead of failing completely
+                rule_failures.push(RuleFailure::new(rule.syntax(), &e));
             }
         }
 
@@ -517,7 +554,7 @@ impl ProfileExporter {
         self.validate_structure_definition(&structure_def)?;
 
         debug!("Successfully exported profile: {}", profile_name);
-        Ok(structure_def)
+        Ok((structure_def, rule_failures))
     }
 
     /// Validate exported StructureDefinition
@@ -585,24 +622,25 @@ impl ProfileExporter {
     ) -> Result<StructureDefinition, ExportError> {
         // Helper to decide if a resolved StructureDefinition is an unexpected Extension parent
         let is_unexpected_extension = |sd: &StructureDefinition| -> bool {
-            sd.type_field == "Extension" && parent != "Extension"
+            sd.type_field == "Extension"
+                && parent != "Extension"
+                && !is_cross_version_extension_url(&sd.url)
         };
 
         debug!("Resolving parent: {}", parent);
 
-        // 0a. Check if parent is in locally exported Package (by name)
-        // This allows child profiles to find parent profiles exported earlier in the same build
+        // 0a. Check if parent is in locally exported Package (by name, id or url)
+        // This allows child profiles to find parent profiles exported earlier in the same
+        // build, and profiles derived from predefined StructureDefinitions
         {
             let package = self.package.read().await;
             for (_canonical_url, resource_json) in package.all_resources().iter() {
                 if let Ok(sd) =
                     serde_json::from_value::<StructureDefinition>((**resource_json).clone())
+                    && (sd.name == parent || sd.url == parent || sd.id.as_deref() == Some(parent))
                 {
-                    // Check if name matches parent
-                    if sd.name == parent {
-                        debug!("Found parent '{}' in local Package", parent);
-                        return Ok(sd);
-                    }
+                    debug!("Found parent '{}' in local Package", parent);
+                    return Ok(sd);
                 }
             }
         }
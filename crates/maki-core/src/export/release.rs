@@ -0,0 +1,245 @@
+//! Multi-FHIR-release builds
+//!
+//! A project whose `fhirVersion` lists several versions (e.g. `4.0.1` and
+//! `5.0.0`) is built once per release, each into its own output directory
+//! (`fsh-generated/r4`, `fsh-generated/r5`).
+//!
+//! ## Release pragmas
+//!
+//! FSH that only applies to some releases is wrapped in comment pragmas:
+//!
+//! ```fsh
+//! Profile: MyEncounter
+//! Parent: Encounter
+//! // maki-if R4 R4B
+//! * class 1..1
+//! // maki-else
+//! * class 1..*
+//! // maki-endif
+//! ```
+//!
+//! `maki-if` takes release labels (`R4`, `R4B`, `R5`, `R6`) or FHIR version
+//! numbers. Pragmas nest. Lines in inactive branches are blanked before
+//! parsing; line numbers and byte offsets stay the same.
+//!
+//! RuleSet variants are selected in config instead (see `releases` in
+//! [`UnifiedConfig`](crate::config::UnifiedConfig)).
+
+use crate::canonical::FhirRelease;
+use crate::canonical::version::FhirVersionExt;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const PRAGMA_IF: &str = "maki-if";
+const PRAGMA_ELSE: &str = "maki-else";
+const PRAGMA_ENDIF: &str = "maki-endif";
+
+/// Releases targeted by `fhirVersion`, in order and without duplicates
+///
+/// Unknown versions fall back to R4, as does an empty list.
+pub fn configured_releases(fhir_versions: &[String]) -> Vec<FhirRelease> {
+    let mut releases = Vec::new();
+    for version in fhir_versions {
+        let release = FhirRelease::from_version_string(version).unwrap_or_else(|_| {
+            warn!("Unknown FHIR version: {}, defaulting to R4", version);
+            FhirRelease::R4
+        });
+        if !releases.contains(&release) {
+            releases.push(release);
+        }
+    }
+    if releases.is_empty() {
+        warn!("No FHIR version specified in config, defaulting to R4");
+        releases.push(FhirRelease::R4);
+    }
+    releases
+}
+
+/// Output directory of one release in a multi-release build (`<base>/r5`)
+pub fn release_output_dir(base: &Path, release: FhirRelease) -> PathBuf {
+    base.join(release.label().to_ascii_lowercase())
+}
+
+/// A malformed release pragma
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PragmaIssue {
+    /// 1-based line of the pragma
+    pub line: usize,
+    /// Byte offset of the pragma line
+    pub offset: usize,
+    pub message: String,
+}
+
+/// One open `maki-if` block
+struct Branch {
+    line: usize,
+    /// Whether the `maki-if` matched the release
+    matched: bool,
+    in_else: bool,
+    /// Whether every enclosing block is active
+    parent_active: bool,
+}
+
+impl Branch {
+    fn active(&self) -> bool {
+        self.parent_active && (self.matched != self.in_else)
+    }
+}
+
+/// Blank the lines of `source` that don't apply to `release`
+///
+/// Returns the source unchanged (apart from blanked lines) together with any
+/// malformed pragmas. Unknown releases in `maki-if` never match.
+pub fn apply_release_pragmas(source: &str, release: FhirRelease) -> (String, Vec<PragmaIssue>) {
+    if !source.contains("maki-") {
+        return (source.to_string(), Vec::new());
+    }
+
+    let mut output = String::with_capacity(source.len());
+    let mut issues = Vec::new();
+    let mut stack: Vec<Branch> = Vec::new();
+    let mut offset = 0;
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let line_number = index + 1;
+        let active = stack.last().is_none_or(Branch::active);
+        let mut issue = |message: String| {
+            issues.push(PragmaIssue {
+                line: line_number,
+                offset,
+                message,
+            })
+        };
+
+        match pragma(line) {
+            Some((PRAGMA_IF, args)) => {
+                let mut matched = false;
+                for arg in &args {
+                    match FhirRelease::from_version_string(arg) {
+                        Ok(candidate) => matched |= candidate == release,
+                        Err(_) => issue(format!("Unknown FHIR release '{}' in {}", arg, PRAGMA_IF)),
+                    }
+                }
+                if args.is_empty() {
+                    issue(format!("{} needs at least one FHIR release", PRAGMA_IF));
+                }
+                stack.push(Branch {
+                    line: line_number,
+                    matched,
+                    in_else: false,
+                    parent_active: active,
+                });
+            }
+            Some((PRAGMA_ELSE, _)) => match stack.last_mut() {
+                Some(branch) if branch.in_else => issue(format!(
+                    "Duplicate {} for the same {}",
+                    PRAGMA_ELSE, PRAGMA_IF
+                )),
+                Some(branch) => branch.in_else = true,
+                None => issue(format!("{} without {}", PRAGMA_ELSE, PRAGMA_IF)),
+            },
+            Some((PRAGMA_ENDIF, _)) if stack.pop().is_none() => {
+                issue(format!("{} without {}", PRAGMA_ENDIF, PRAGMA_IF));
+            }
+            _ => {}
+        }
+
+        if active || pragma(line).is_some() {
+            output.push_str(line);
+        } else {
+            // Keep byte offsets stable for spans and source maps
+            for c in line.chars() {
+                match c {
+                    '\n' | '\r' => output.push(c),
+                    _ => output.extend(std::iter::repeat_n(' ', c.len_utf8())),
+                }
+            }
+        }
+        offset += line.len();
+    }
+
+    for branch in stack {
+        issues.push(PragmaIssue {
+            line: branch.line,
+            offset: source.len(),
+            message: format!("{} is never closed with {}", PRAGMA_IF, PRAGMA_ENDIF),
+        });
+    }
+
+    (output, issues)
+}
+
+/// Split a `// maki-if R4 R5` comment line into directive and arguments
+fn pragma(line: &str) -> Option<(&str, Vec<&str>)> {
+    let rest = line.trim().strip_prefix("//")?.trim_start();
+    let mut words = rest.split_whitespace();
+    let directive = words.next()?;
+    [PRAGMA_IF, PRAGMA_ELSE, PRAGMA_ENDIF]
+        .into_iter()
+        .find(|known| *known == directive)
+        .map(|known| (known, words.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "Profile: MyEncounter\n\
+        Parent: Encounter\n\
+        // maki-if R4 R4B\n\
+        * class 1..1\n\
+        // maki-else\n\
+        * class 1..* // ü\n\
+        // maki-endif\n\
+        * status MS\n";
+
+    #[test]
+    fn test_configured_releases() {
+        assert_eq!(
+            configured_releases(&["4.0.1".into(), "5.0.0".into(), "4.0.0".into()]),
+            vec![FhirRelease::R4, FhirRelease::R5]
+        );
+        assert_eq!(configured_releases(&[]), vec![FhirRelease::R4]);
+        assert_eq!(
+            release_output_dir(Path::new("fsh-generated"), FhirRelease::R4B),
+            PathBuf::from("fsh-generated/r4b")
+        );
+    }
+
+    #[test]
+    fn test_apply_release_pragmas() {
+        let (r4, issues) = apply_release_pragmas(SOURCE, FhirRelease::R4);
+        assert!(issues.is_empty());
+        assert!(r4.contains("* class 1..1\n"));
+        assert!(!r4.contains("1..*"));
+        assert!(r4.contains("* status MS"));
+        assert_eq!(r4.len(), SOURCE.len());
+        assert_eq!(r4.lines().count(), SOURCE.lines().count());
+
+        let (r5, _) = apply_release_pragmas(SOURCE, FhirRelease::R5);
+        assert!(!r5.contains("1..1"));
+        assert!(r5.contains("* class 1..* // ü"));
+        assert_eq!(r5.len(), SOURCE.len());
+    }
+
+    #[test]
+    fn test_nested_and_malformed_pragmas() {
+        let source = "// maki-if R5\n// maki-if 4.0.1\nA\n// maki-endif\nB\n// maki-endif\n";
+        let (r5, issues) = apply_release_pragmas(source, FhirRelease::R5);
+        assert!(issues.is_empty());
+        assert!(!r5.contains('A'));
+        assert!(r5.contains('B'));
+
+        let (_, issues) = apply_release_pragmas(
+            "// maki-if R9\n// maki-endif\n// maki-else\n",
+            FhirRelease::R4,
+        );
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("R9"));
+        assert_eq!(issues[1].line, 3);
+
+        let (_, issues) = apply_release_pragmas("// maki-if R4\nA\n", FhirRelease::R4);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 1);
+    }
+}
@@ -250,6 +250,14 @@ impl RuleSetProcessor {
         None
     }
 
+    /// Expand the RuleSet `variant` wherever `name` is inserted
+    ///
+    /// Must be called before [`Self::expand_all_inserts`]. Returns `false`
+    /// when `variant` was not collected.
+    pub fn select_variant(&mut self, name: &str, variant: &str) -> bool {
+        self.expander.set_variant(name, variant)
+    }
+
     /// Get statistics
     pub fn stats(&self) -> (usize, usize) {
         (self.rulesets_collected, self.inserts_expanded)
@@ -21,11 +21,14 @@
 //! ## Algorithm 4: Snapshot Generation
 //!
 //! Creates a complete element tree by merging differential with parent:
-//! 1. Get parent snapshot as starting point
-//! 2. Apply differential element by element
-//! 3. Merge properties (child overrides parent)
-//! 4. Unfold choice types and references
-//! 5. Sort elements in tree order
+//! 1. Get parent snapshot as starting point (generating it first if the parent
+//!    only has a differential)
+//! 2. Apply differential element by element, matched by element `id`
+//! 3. Create slices and reslices by copying the sliced element's subtree
+//! 4. Unfold complex and profiled types, choice types and `contentReference`s
+//!    when the differential constrains their children
+//! 5. Merge properties (child overrides parent; constraints and mappings are
+//!    concatenated) and propagate them into existing slices
 //! 6. Validate the result
 //!
 //! # FHIR Specification
@@ -62,8 +65,8 @@
 
 use super::fhir_types::*;
 use crate::canonical::DefinitionSession;
-use std::cmp::Ordering;
-use std::collections::HashSet;
+use futures::future::BoxFuture;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use thiserror::Error;
 use tracing::{debug, trace, warn};
@@ -75,9 +78,12 @@ use tracing::{debug, trace, warn};
 /// Errors that can occur during snapshot generation
 #[derive(Debug, Error)]
 pub enum SnapshotError {
-    /// Parent StructureDefinition not found
-    #[error("Parent snapshot not found for: {0}")]
-    ParentSnapshotNotFound(String),
+    /// Parent StructureDefinition not found (or has neither snapshot nor differential)
+    ///
+    /// `chain` lists the definitions walked to get here, starting with the one
+    /// whose snapshot was requested and ending with `url`.
+    #[error("Parent snapshot not found for: {url} (chain: {})", .chain.join(" -> "))]
+    ParentSnapshotNotFound { url: String, chain: Vec<String> },
 
     /// Failed to merge element properties
     #[error("Invalid element merge: {element} - {reason}")]
@@ -253,12 +259,19 @@ impl SnapshotGenerator {
     /// Implements **Algorithm 4** from MAKI_PLAN.md.
     ///
     /// Creates a complete element tree by:
-    /// 1. Getting the parent's snapshot
+    /// 1. Resolving the parent's snapshot (generating it first when the parent
+    ///    only carries a differential, recursively up the chain)
     /// 2. Cloning it as a starting point
-    /// 3. Applying the differential element by element
-    /// 4. Sorting elements in tree order
+    /// 3. Applying the differential element by element, matched by element `id`:
+    ///    new slices and reslices copy the sliced element's subtree, complex and
+    ///    profiled types are unfolded from their own snapshots, `contentReference`
+    ///    elements are expanded from the referenced element
+    /// 4. Propagating constraints on sliced elements' descendants into every slice
     /// 5. Validating the result
     ///
+    /// Base element order is preserved; new elements are placed after the
+    /// subtree they belong to.
+    ///
     /// # Arguments
     ///
     /// * `sd` - StructureDefinition with differential populated
@@ -269,9 +282,10 @@ impl SnapshotGenerator {
     ///
     /// # Errors
     ///
-    /// - `ParentSnapshotNotFound`: Parent definition not found or has no snapshot
-    /// - `InvalidMerge`: Failed to merge element properties
-    /// - `OrderingError`: Failed to sort elements correctly
+    /// - `ParentSnapshotNotFound`: A definition in the parent chain could not be
+    ///   resolved (the error carries the full chain)
+    /// - `CircularDependency`: The parent chain loops back on itself
+    /// - `InvalidMerge` / `CardinalityConflict`: Failed to merge element properties
     /// - `ValidationFailed`: Generated snapshot is invalid
     ///
     /// # Example
@@ -295,73 +309,139 @@ impl SnapshotGenerator {
         &self,
         sd: &StructureDefinition,
     ) -> Result<Vec<ElementDefinition>, SnapshotError> {
-        debug!("Generating snapshot for {}", sd.url);
+        let mut types = TypeSnapshots::new();
+        self.snapshot_for(sd, Vec::new(), &mut types).await
+    }
 
-        // Step 1: Get parent snapshot
-        let parent_url = sd.base_definition.as_ref().ok_or_else(|| {
-            SnapshotError::ParentSnapshotNotFound("No base definition specified".to_string())
-        })?;
+    /// Generate a snapshot for `sd`, where `chain` holds the URLs of the
+    /// definitions that led here (child first)
+    fn snapshot_for<'a>(
+        &'a self,
+        sd: &'a StructureDefinition,
+        mut chain: Vec<String>,
+        types: &'a mut TypeSnapshots,
+    ) -> BoxFuture<'a, Result<Vec<ElementDefinition>, SnapshotError>> {
+        Box::pin(async move {
+            debug!("Generating snapshot for {}", sd.url);
+            chain.push(sd.url.clone());
+
+            // Step 1: Get parent snapshot
+            let parent_url = sd.base_definition.as_ref().ok_or_else(|| {
+                SnapshotError::ParentSnapshotNotFound {
+                    url: format!("{} (no baseDefinition)", sd.url),
+                    chain: chain.clone(),
+                }
+            })?;
+            let parent_snapshot = self.parent_snapshot(parent_url, &chain, types).await?;
+            debug!(
+                "Resolved parent snapshot with {} elements",
+                parent_snapshot.len()
+            );
 
-        trace!("Resolving parent: {}", parent_url);
+            // Steps 2-4: Apply differential, fetching type snapshots on demand
+            let differential = sd
+                .differential
+                .as_ref()
+                .map(|d| d.element.as_slice())
+                .unwrap_or_default();
+            let snapshot = loop {
+                let builder = SnapshotBuilder::new(parent_snapshot.clone(), types);
+                match builder.build(differential)? {
+                    BuildOutcome::Complete(snapshot) => break snapshot,
+                    BuildOutcome::NeedsType(url) => {
+                        trace!("Fetching type snapshot: {}", url);
+                        let elements = self.type_snapshot(&url, &chain, types).await;
+                        types.insert(url, elements);
+                    }
+                }
+            };
+
+            // Step 5: Validate snapshot
+            self.validate_snapshot(&snapshot)?;
+
+            debug!(
+                "Successfully generated snapshot with {} elements",
+                snapshot.len()
+            );
+            Ok(snapshot)
+        })
+    }
+
+    /// Resolve the snapshot of a parent definition, generating it from the
+    /// parent's own differential when it has none
+    async fn parent_snapshot(
+        &self,
+        url: &str,
+        chain: &[String],
+        types: &mut TypeSnapshots,
+    ) -> Result<Vec<ElementDefinition>, SnapshotError> {
+        let mut full_chain = chain.to_vec();
+        full_chain.push(url.to_string());
+        if chain.iter().any(|seen| seen == url) {
+            return Err(SnapshotError::CircularDependency(full_chain.join(" -> ")));
+        }
 
-        let parent_sd = self
+        trace!("Resolving parent: {}", url);
+        let parent = self
             .session
-            .resolve_structure_definition(parent_url)
+            .resolve_structure_definition(url)
             .await
             .map_err(|e| {
-                SnapshotError::CanonicalError(format!(
-                    "Failed to resolve parent {}: {}",
-                    parent_url, e
-                ))
+                SnapshotError::CanonicalError(format!("Failed to resolve parent {}: {}", url, e))
             })?
-            .ok_or_else(|| SnapshotError::ParentSnapshotNotFound(parent_url.clone()))?;
-
-        let parent_snapshot = parent_sd.snapshot.as_ref().ok_or_else(|| {
-            SnapshotError::ParentSnapshotNotFound(format!("Parent {} has no snapshot", parent_url))
-        })?;
+            .ok_or_else(|| SnapshotError::ParentSnapshotNotFound {
+                url: url.to_string(),
+                chain: full_chain.clone(),
+            })?;
 
-        // Step 2: Clone parent snapshot as starting point
-        let mut snapshot = parent_snapshot.element.clone();
-        debug!("Cloned parent snapshot with {} elements", snapshot.len());
+        if let Some(snapshot) = parent.snapshot {
+            return Ok(snapshot.element);
+        }
+        if parent.differential.is_none() {
+            return Err(SnapshotError::ParentSnapshotNotFound {
+                url: url.to_string(),
+                chain: full_chain,
+            });
+        }
 
-        // Step 3: Apply differential
-        if let Some(differential) = &sd.differential {
-            trace!(
-                "Applying differential with {} elements",
-                differential.element.len()
-            );
+        debug!("Parent {} has no snapshot, generating it", url);
+        self.snapshot_for(&parent, chain.to_vec(), types).await
+    }
 
-            for diff_elem in &differential.element {
-                trace!("Applying differential element: {}", diff_elem.path);
+    /// Resolve the snapshot of a (possibly profiled) type used for unfolding
+    ///
+    /// Returns `None` when the type cannot be resolved; the builder then falls
+    /// back to the base type or leaves the element folded.
+    async fn type_snapshot(
+        &self,
+        url: &str,
+        chain: &[String],
+        types: &mut TypeSnapshots,
+    ) -> Option<Vec<ElementDefinition>> {
+        if chain.iter().any(|seen| seen == url) {
+            return None;
+        }
 
-                // Find corresponding element in snapshot
-                if let Some(snap_elem) = snapshot.iter_mut().find(|e| e.path == diff_elem.path) {
-                    // Merge with existing element
-                    *snap_elem = self.merge_element(snap_elem, diff_elem)?;
-                    debug!("Merged element: {}", diff_elem.path);
-                } else {
-                    // Insert new element
-                    self.insert_element(&mut snapshot, diff_elem.clone())?;
-                    debug!("Inserted new element: {}", diff_elem.path);
-                }
+        let sd = match self.session.resolve_structure_definition(url).await {
+            Ok(Some(sd)) => sd,
+            Ok(None) => return None,
+            Err(e) => {
+                debug!("Failed to resolve type {}: {}", url, e);
+                return None;
             }
-        } else {
-            debug!("No differential to apply");
+        };
+        if let Some(snapshot) = sd.snapshot {
+            return Some(snapshot.element);
         }
 
-        // Step 4: Sort elements in tree order
-        self.sort_elements(&mut snapshot)?;
-
-        // Step 5: Validate snapshot
-        self.validate_snapshot(&snapshot)?;
-
-        debug!(
-            "Successfully generated snapshot with {} elements",
-            snapshot.len()
-        );
-        Ok(snapshot)
+        match self.snapshot_for(&sd, chain.to_vec(), types).await {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                warn!("Failed to generate snapshot for type {}: {}", url, e);
+                None
+            }
+        }
     }
-
     /// Merge child element properties into parent element
     ///
     /// Implements property merging rules according to FHIR specification:
@@ -385,7 +465,6 @@ impl SnapshotGenerator {
     /// - `CardinalityConflict`: If child cardinality is outside parent range
     /// - `TypeConstraintError`: If child type is not compatible with parent
     fn merge_element(
-        &self,
         parent: &ElementDefinition,
         child: &ElementDefinition,
     ) -> Result<ElementDefinition, SnapshotError> {
@@ -419,7 +498,7 @@ impl SnapshotGenerator {
         if child.max.is_some() {
             // Validate max cardinality
             if let (Some(parent_max), Some(child_max)) = (&parent.max, &child.max)
-                && !self.is_cardinality_compatible(parent_max, child_max)
+                && !Self::is_cardinality_compatible(parent_max, child_max)
             {
                 return Err(SnapshotError::CardinalityConflict {
                     parent_card: format!("{}..{}", parent.min.unwrap_or(0), parent_max),
@@ -456,10 +535,13 @@ impl SnapshotGenerator {
             merged.is_summary = child.is_summary;
         }
 
-        // Override binding
+        // Override binding and slicing
         if child.binding.is_some() {
             merged.binding = child.binding.clone();
         }
+        if child.slicing.is_some() {
+            merged.slicing = child.slicing.clone();
+        }
 
         // Concatenate constraints (don't replace)
         if let Some(ref child_constraints) = child.constraint {
@@ -482,6 +564,17 @@ impl SnapshotGenerator {
             }
         }
 
+        // Concatenate mappings, skipping ones the base already declares
+        if let Some(ref child_mappings) = child.mapping {
+            let mut mappings = merged.mapping.take().unwrap_or_default();
+            for mapping in child_mappings {
+                if !mappings.contains(mapping) {
+                    mappings.push(mapping.clone());
+                }
+            }
+            merged.mapping = Some(mappings);
+        }
+
         // Override fixed/pattern values
         if child.fixed.is_some() {
             merged.fixed = child.fixed.clone();
@@ -511,75 +604,6 @@ impl SnapshotGenerator {
         base.is_modified_from(modified)
     }
 
-    /// Insert a new element into snapshot at the correct position
-    ///
-    /// Maintains element tree order by finding the correct insertion point.
-    ///
-    /// # Arguments
-    ///
-    /// * `snapshot` - Current snapshot elements
-    /// * `element` - New element to insert
-    fn insert_element(
-        &self,
-        snapshot: &mut Vec<ElementDefinition>,
-        element: ElementDefinition,
-    ) -> Result<(), SnapshotError> {
-        // Find correct insertion position
-        // Elements must be in tree order: parent before children
-        let insert_pos = snapshot
-            .iter()
-            .position(|e| self.compare_element_paths(&element.path, &e.path) == Ordering::Less)
-            .unwrap_or(snapshot.len());
-
-        snapshot.insert(insert_pos, element);
-        Ok(())
-    }
-
-    /// Sort elements in tree order
-    ///
-    /// Elements must appear in depth-first order:
-    /// - Parent before children
-    /// - Siblings in alphabetical order
-    ///
-    /// # Arguments
-    ///
-    /// * `elements` - Elements to sort
-    fn sort_elements(&self, elements: &mut [ElementDefinition]) -> Result<(), SnapshotError> {
-        elements.sort_by(|a, b| self.compare_element_paths(&a.path, &b.path));
-        Ok(())
-    }
-
-    /// Compare two element paths for sorting
-    ///
-    /// Implements tree ordering:
-    /// - "Patient" < "Patient.name"
-    /// - "Patient.name" < "Patient.name.given"
-    /// - "Patient.name" < "Patient.telecom"
-    ///
-    /// # Arguments
-    ///
-    /// * `a` - First path
-    /// * `b` - Second path
-    ///
-    /// # Returns
-    ///
-    /// Ordering relationship
-    fn compare_element_paths(&self, a: &str, b: &str) -> Ordering {
-        let a_parts: Vec<&str> = a.split('.').collect();
-        let b_parts: Vec<&str> = b.split('.').collect();
-
-        // Compare part by part
-        for i in 0..a_parts.len().min(b_parts.len()) {
-            match a_parts[i].cmp(b_parts[i]) {
-                Ordering::Equal => continue,
-                other => return other,
-            }
-        }
-
-        // If all parts equal so far, shorter path comes first
-        a_parts.len().cmp(&b_parts.len())
-    }
-
     /// Check if child cardinality is compatible with parent
     ///
     /// Child max must be <= parent max
@@ -592,7 +616,7 @@ impl SnapshotGenerator {
     /// # Returns
     ///
     /// `true` if compatible, `false` if conflict
-    fn is_cardinality_compatible(&self, parent_max: &str, child_max: &str) -> bool {
+    fn is_cardinality_compatible(parent_max: &str, child_max: &str) -> bool {
         // If parent is unbounded (*), child can be anything
         if parent_max == "*" {
             return true;
@@ -616,7 +640,7 @@ impl SnapshotGenerator {
     /// - All elements have paths
     /// - Root element exists
     /// - Elements are in tree order
-    /// - No duplicate paths
+    /// - No duplicate element ids (paths repeat across slices)
     ///
     /// # Arguments
     ///
@@ -642,13 +666,14 @@ impl SnapshotGenerator {
             }
         }
 
-        // Check for duplicate paths
-        let mut seen_paths = HashSet::new();
+        // Check for duplicate ids
+        let mut seen_ids = HashSet::new();
         for elem in snapshot {
-            if !seen_paths.insert(&elem.path) {
+            let id = elem.id.as_deref().unwrap_or(&elem.path);
+            if !seen_ids.insert(id) {
                 return Err(SnapshotError::ValidationFailed(format!(
-                    "Duplicate element path: {}",
-                    elem.path
+                    "Duplicate element id: {}",
+                    id
                 )));
             }
         }
@@ -659,6 +684,435 @@ impl SnapshotGenerator {
     }
 }
 
+// ============================================================================
+// Snapshot Builder
+// ============================================================================
+
+/// Canonical URL prefix of the FHIR core StructureDefinitions
+const FHIR_STRUCTURE_DEFINITION_BASE: &str = "http://hl7.org/fhir/StructureDefinition/";
+
+/// Type snapshots fetched for unfolding, keyed by canonical URL
+/// (`None` = the type could not be resolved)
+type TypeSnapshots = HashMap<String, Option<Vec<ElementDefinition>>>;
+
+/// Result of one pass of the [`SnapshotBuilder`]
+#[derive(Debug)]
+enum BuildOutcome {
+    /// All differential elements were applied
+    Complete(Vec<ElementDefinition>),
+    /// Unfolding needs the snapshot of this type, which has not been fetched yet
+    NeedsType(String),
+}
+
+/// Result of looking up (and if needed creating) an element by id
+#[derive(Debug, PartialEq)]
+enum Lookup {
+    Found(usize),
+    NeedsType(String),
+    Missing,
+}
+
+/// Applies a differential to a parent snapshot
+///
+/// Works purely on the element list and the type snapshots fetched so far, so
+/// the async [`SnapshotGenerator`] can re-run it after fetching whatever type
+/// it reports as missing. Elements are addressed by their `id`, which keeps
+/// slices (`Observation.component:systolic.code`) and reslices
+/// (`Observation.category:us-core/lab`) apart even though they share paths.
+struct SnapshotBuilder<'a> {
+    elements: Vec<ElementDefinition>,
+    types: &'a TypeSnapshots,
+}
+
+impl<'a> SnapshotBuilder<'a> {
+    fn new(mut elements: Vec<ElementDefinition>, types: &'a TypeSnapshots) -> Self {
+        assign_ids(&mut elements);
+        Self { elements, types }
+    }
+
+    fn build(mut self, differential: &[ElementDefinition]) -> Result<BuildOutcome, SnapshotError> {
+        let mut differential = differential.to_vec();
+        assign_ids(&mut differential);
+
+        for diff_elem in &differential {
+            let id = element_id(diff_elem).to_string();
+            trace!("Applying differential element: {}", id);
+
+            let index = match self.locate(&id) {
+                Lookup::Found(index) => index,
+                Lookup::NeedsType(url) => return Ok(BuildOutcome::NeedsType(url)),
+                Lookup::Missing => {
+                    debug!("No base element for {}, inserting as-is", id);
+                    self.insert_orphan(diff_elem.clone());
+                    continue;
+                }
+            };
+
+            self.elements[index] =
+                SnapshotGenerator::merge_element(&self.elements[index], diff_elem)?;
+            self.propagate_to_slices(index, diff_elem);
+        }
+
+        Ok(BuildOutcome::Complete(self.elements))
+    }
+
+    /// Find the element with `id`, creating slices and unfolding types on the way
+    fn locate(&mut self, id: &str) -> Lookup {
+        if let Some(index) = self.index_of(id) {
+            return Lookup::Found(index);
+        }
+        let Some((parent_id, segment)) = id.rsplit_once('.') else {
+            return Lookup::Missing;
+        };
+
+        // New slice (`name:slice`) or reslice (`name:slice/reslice`)
+        if let Some((name, slice_name)) = segment.split_once(':') {
+            let (base_id, separator, own_name) = match slice_name.rsplit_once('/') {
+                Some((outer, inner)) => (format!("{}.{}:{}", parent_id, name, outer), '/', inner),
+                None => (format!("{}.{}", parent_id, name), ':', slice_name),
+            };
+            return match self.locate(&base_id) {
+                Lookup::Found(base) => {
+                    let slice_id = format!(
+                        "{}{}{}",
+                        element_id(&self.elements[base]),
+                        separator,
+                        own_name
+                    );
+                    match self.index_of(&slice_id) {
+                        Some(index) => Lookup::Found(index),
+                        None => Lookup::Found(self.add_slice(base, &slice_id, slice_name)),
+                    }
+                }
+                other => other,
+            };
+        }
+
+        let parent = match self.locate(parent_id) {
+            Lookup::Found(parent) => parent,
+            other => return other,
+        };
+        // The parent may live under a different id (e.g. a choice type slice)
+        let child_id = format!("{}.{}", element_id(&self.elements[parent]), segment);
+        if let Some(index) = self.index_of(&child_id) {
+            return Lookup::Found(index);
+        }
+        if let Some(index) = self.choice_slice(parent, segment) {
+            return Lookup::Found(index);
+        }
+        if self.has_children(parent) {
+            return Lookup::Missing;
+        }
+
+        match self.unfold(parent) {
+            Lookup::Found(_) => self
+                .index_of(&child_id)
+                .map(Lookup::Found)
+                .unwrap_or(Lookup::Missing),
+            other => other,
+        }
+    }
+
+    /// Create a slice of the element at `base` by copying its subtree
+    ///
+    /// The slice is inserted after the base element's subtree and any slices
+    /// already defined on it, and starts out optional with no slicing of its own.
+    fn add_slice(&mut self, base: usize, slice_id: &str, slice_name: &str) -> usize {
+        let base_id = element_id(&self.elements[base]).to_string();
+        let own_end = self.subtree_end(base, &[format!("{}.", base_id)]);
+        let insert_at = self.subtree_end(
+            base,
+            &[
+                format!("{}.", base_id),
+                format!("{}:", base_id),
+                format!("{}/", base_id),
+            ],
+        );
+
+        let mut slice: Vec<ElementDefinition> = self.elements[base..own_end]
+            .iter()
+            .map(|element| {
+                let mut element = element.clone();
+                let id = element_id(&element).to_string();
+                element.id = Some(format!("{}{}", slice_id, &id[base_id.len()..]));
+                element
+            })
+            .collect();
+        slice[0].slice_name = Some(slice_name.to_string());
+        slice[0].min = Some(0);
+        slice[0].slicing = None;
+
+        debug!("Created slice {} ({} elements)", slice_id, slice.len());
+        self.elements.splice(insert_at..insert_at, slice);
+        insert_at
+    }
+
+    /// Map a type-specific choice name (`valueQuantity`) onto a type slice of
+    /// the choice element (`value[x]:valueQuantity`)
+    fn choice_slice(&mut self, parent: usize, segment: &str) -> Option<usize> {
+        let prefix = format!("{}.", element_id(&self.elements[parent]));
+        let (choice, type_) = self
+            .elements
+            .iter()
+            .enumerate()
+            .find_map(|(index, element)| {
+                let name = element_id(element)
+                    .strip_prefix(&prefix)?
+                    .strip_suffix("[x]")?;
+                if name.contains(['.', ':']) {
+                    return None;
+                }
+                let type_name = segment.strip_prefix(name)?;
+                let type_ = element
+                    .type_
+                    .as_ref()?
+                    .iter()
+                    .find(|t| upper_first(&t.code) == type_name)?;
+                Some((index, type_.clone()))
+            })?;
+
+        let slice_id = format!("{}:{}", element_id(&self.elements[choice]), segment);
+        if let Some(index) = self.index_of(&slice_id) {
+            return Some(index);
+        }
+        let index = self.add_slice(choice, &slice_id, segment);
+        self.elements[index].type_ = Some(vec![type_]);
+        Some(index)
+    }
+
+    /// Add the children of the element at `index`, from its `contentReference`
+    /// target or from the snapshot of its (single) type
+    fn unfold(&mut self, index: usize) -> Lookup {
+        let element = &self.elements[index];
+        let root_id = element_id(element).to_string();
+        let root_path = element.path.clone();
+
+        let children: Vec<ElementDefinition> = if let Some(reference) = &element.content_reference {
+            let target_id = reference
+                .rsplit_once('#')
+                .map_or(reference.as_str(), |(_, id)| id);
+            let Some(target) = self.index_of(target_id) else {
+                return Lookup::Missing;
+            };
+            let target_path = self.elements[target].path.clone();
+            let end = self.subtree_end(target, &[format!("{}.", target_id)]);
+            self.elements[target + 1..end]
+                .iter()
+                .map(|e| rebase(e, target_id, &target_path, &root_id, &root_path))
+                .collect()
+        } else {
+            let mut snapshot = None;
+            for url in type_urls(element) {
+                match self.types.get(&url) {
+                    None => return Lookup::NeedsType(url),
+                    Some(None) => continue,
+                    Some(Some(elements)) => {
+                        snapshot = Some(elements);
+                        break;
+                    }
+                }
+            }
+            let Some((type_root, type_children)) = snapshot.and_then(|s| s.split_first()) else {
+                return Lookup::Missing;
+            };
+            let type_root_id = element_id(type_root).to_string();
+            type_children
+                .iter()
+                .map(|e| rebase(e, &type_root_id, &type_root.path, &root_id, &root_path))
+                .collect()
+        };
+
+        if children.is_empty() {
+            return Lookup::Missing;
+        }
+        debug!("Unfolded {} ({} children)", root_id, children.len());
+        self.elements.splice(index + 1..index + 1, children);
+        Lookup::Found(index)
+    }
+
+    /// Apply a differential element that constrains a descendant of a sliced
+    /// element to the same descendant in every slice
+    fn propagate_to_slices(&mut self, index: usize, diff_elem: &ElementDefinition) {
+        let id = element_id(&self.elements[index]).to_string();
+        for other in 0..self.elements.len() {
+            if other == index || !is_slice_copy(element_id(&self.elements[other]), &id) {
+                continue;
+            }
+            match SnapshotGenerator::merge_element(&self.elements[other], diff_elem) {
+                Ok(merged) => self.elements[other] = merged,
+                Err(e) => trace!(
+                    "Not propagating {} into {}: {}",
+                    id,
+                    element_id(&self.elements[other]),
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Insert an element no base element could be found or unfolded for,
+    /// after the subtree of its nearest existing ancestor
+    fn insert_orphan(&mut self, element: ElementDefinition) {
+        let mut ancestor = element_id(&element).to_string();
+        let position = loop {
+            let Some((parent, _)) = ancestor.rsplit_once('.') else {
+                break self.elements.len();
+            };
+            ancestor = parent.to_string();
+            if let Some(index) = self.index_of(&ancestor) {
+                break self.subtree_end(index, &[format!("{}.", ancestor)]);
+            }
+        };
+        self.elements.insert(position, element);
+    }
+
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.elements.iter().position(|e| element_id(e) == id)
+    }
+
+    fn has_children(&self, index: usize) -> bool {
+        let prefix = format!("{}.", element_id(&self.elements[index]));
+        self.elements
+            .get(index + 1)
+            .is_some_and(|next| element_id(next).starts_with(&prefix))
+    }
+
+    /// Index just past the contiguous run of elements after `index` whose ids
+    /// start with one of `prefixes`
+    fn subtree_end(&self, index: usize, prefixes: &[String]) -> usize {
+        let mut end = index + 1;
+        while end < self.elements.len()
+            && prefixes
+                .iter()
+                .any(|prefix| element_id(&self.elements[end]).starts_with(prefix.as_str()))
+        {
+            end += 1;
+        }
+        end
+    }
+}
+
+/// Element id, falling back to the path for elements without one
+fn element_id(element: &ElementDefinition) -> &str {
+    element.id.as_deref().unwrap_or(&element.path)
+}
+
+/// Fill in missing element ids following the FHIR id conventions
+///
+/// `Observation.component:systolic.code` – ancestors contribute their slice
+/// names, so a child listed after a slice gets an id inside that slice.
+fn assign_ids(elements: &mut [ElementDefinition]) {
+    let mut current: HashMap<String, String> = HashMap::new();
+    for element in elements {
+        let id = match &element.id {
+            Some(id) => id.clone(),
+            None => {
+                let mut id = match element.path.rsplit_once('.') {
+                    Some((parent_path, name)) => {
+                        let parent_id = current.get(parent_path).map_or(parent_path, |id| id);
+                        format!("{}.{}", parent_id, name)
+                    }
+                    None => element.path.clone(),
+                };
+                if let Some(slice_name) = &element.slice_name {
+                    id.push(':');
+                    id.push_str(slice_name);
+                }
+                element.id = Some(id.clone());
+                id
+            }
+        };
+        current.insert(element.path.clone(), id);
+    }
+}
+
+/// Re-root an element copied from another subtree
+fn rebase(
+    element: &ElementDefinition,
+    from_id: &str,
+    from_path: &str,
+    to_id: &str,
+    to_path: &str,
+) -> ElementDefinition {
+    let mut element = element.clone();
+    let id = element_id(&element);
+    element.id = Some(format!(
+        "{}{}",
+        to_id,
+        id.strip_prefix(from_id).unwrap_or(id)
+    ));
+    element.path = format!(
+        "{}{}",
+        to_path,
+        element
+            .path
+            .strip_prefix(from_path)
+            .unwrap_or(&element.path)
+    );
+    element
+}
+
+/// Candidate snapshot URLs for unfolding an element: its profile (if any),
+/// then the base type
+///
+/// Elements with several different types cannot be unfolded (they need a
+/// choice type slice first) and FHIRPath system types have no children.
+fn type_urls(element: &ElementDefinition) -> Vec<String> {
+    let Some(types) = element.type_.as_deref() else {
+        return Vec::new();
+    };
+    let Some(first) = types.first() else {
+        return Vec::new();
+    };
+    if types.iter().any(|t| t.code != first.code)
+        || first.code.starts_with("http://hl7.org/fhirpath/")
+    {
+        return Vec::new();
+    }
+
+    let base = if first.code.contains("://") {
+        first.code.clone()
+    } else {
+        format!("{}{}", FHIR_STRUCTURE_DEFINITION_BASE, first.code)
+    };
+    let mut urls: Vec<String> = first.profile.iter().flatten().take(1).cloned().collect();
+    if !urls.contains(&base) {
+        urls.push(base);
+    }
+    urls
+}
+
+/// Whether `candidate` is the copy, inside one or more slices, of the element
+/// `id` (e.g. `Observation.component:systolic.code` for `Observation.component.code`)
+fn is_slice_copy(candidate: &str, id: &str) -> bool {
+    let candidate: Vec<&str> = candidate.split('.').collect();
+    let id: Vec<&str> = id.split('.').collect();
+    if candidate.len() != id.len() || candidate.last() != id.last() {
+        return false;
+    }
+
+    let mut sliced = false;
+    for (c, i) in candidate.iter().zip(&id) {
+        if c == i {
+            continue;
+        }
+        let separator = if i.contains(':') { '/' } else { ':' };
+        match c.strip_prefix(i) {
+            Some(rest) if rest.starts_with(separator) => sliced = true,
+            _ => return false,
+        }
+    }
+    sliced
+}
+
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -666,6 +1120,7 @@ impl SnapshotGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cmp::Ordering;
 
     // Helper to create a test generator without needing a real session
     // Most tests don't need session functionality
@@ -1060,4 +1515,275 @@ mod tests {
         assert_eq!(differential[0].path, "Patient.name");
         assert_eq!(differential[0].min, Some(1));
     }
+
+    fn typed(path: &str, id: &str, code: &str) -> ElementDefinition {
+        let mut elem = ElementDefinition::new(path.to_string());
+        elem.id = Some(id.to_string());
+        elem.type_ = Some(vec![ElementDefinitionType::new(code)]);
+        elem
+    }
+
+    fn observation_base() -> Vec<ElementDefinition> {
+        let mut value = typed("Observation.value[x]", "Observation.value[x]", "Quantity");
+        value
+            .type_
+            .as_mut()
+            .unwrap()
+            .push(ElementDefinitionType::new("string"));
+        vec![
+            create_test_element("Observation"),
+            create_test_element_with_card("Observation.status", 1, "1"),
+            typed("Observation.code", "Observation.code", "CodeableConcept"),
+            value,
+            create_test_element_with_card("Observation.component", 0, "*"),
+            typed(
+                "Observation.component.code",
+                "Observation.component.code",
+                "CodeableConcept",
+            ),
+        ]
+    }
+
+    fn ids(elements: &[ElementDefinition]) -> Vec<&str> {
+        elements.iter().map(element_id).collect()
+    }
+
+    fn build(
+        base: Vec<ElementDefinition>,
+        differential: Vec<ElementDefinition>,
+        types: &TypeSnapshots,
+    ) -> BuildOutcome {
+        SnapshotBuilder::new(base, types)
+            .build(&differential)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_builder_slices_and_reslices() {
+        let types = TypeSnapshots::new();
+
+        let mut sliced = create_test_element("Observation.component");
+        sliced.slicing = Some(ElementDefinitionSlicing {
+            description: None,
+            ordered: Some(false),
+            rules: Some("open".to_string()),
+            discriminator: None,
+        });
+        let mut systolic = create_test_element_with_card("Observation.component", 1, "1");
+        systolic.slice_name = Some("systolic".to_string());
+        let mut reslice = create_test_element("Observation.component");
+        reslice.slice_name = Some("systolic/exact".to_string());
+        let mut code = create_test_element("Observation.component.code");
+        code.id = Some("Observation.component:systolic.code".to_string());
+        code.short = Some("Systolic code".to_string());
+
+        let BuildOutcome::Complete(snapshot) = build(
+            observation_base(),
+            vec![sliced, systolic, reslice, code],
+            &types,
+        ) else {
+            panic!("unexpected type request");
+        };
+
+        assert_eq!(
+            ids(&snapshot),
+            vec![
+                "Observation",
+                "Observation.status",
+                "Observation.code",
+                "Observation.value[x]",
+                "Observation.component",
+                "Observation.component.code",
+                "Observation.component:systolic",
+                "Observation.component:systolic.code",
+                "Observation.component:systolic/exact",
+                "Observation.component:systolic/exact.code",
+            ]
+        );
+        let systolic = &snapshot[6];
+        assert_eq!(systolic.slice_name.as_deref(), Some("systolic"));
+        assert_eq!(systolic.min, Some(1));
+        assert!(systolic.slicing.is_none());
+        assert!(snapshot[4].slicing.is_some());
+        assert_eq!(snapshot[7].short.as_deref(), Some("Systolic code"));
+        assert_eq!(snapshot[8].slice_name.as_deref(), Some("systolic/exact"));
+        assert_eq!(snapshot[7].path, "Observation.component.code");
+    }
+
+    #[test]
+    fn test_builder_propagates_to_slices() {
+        let types = TypeSnapshots::new();
+
+        let mut slice = create_test_element("Observation.component");
+        slice.slice_name = Some("a".to_string());
+        let mut code = create_test_element("Observation.component.code");
+        code.id = Some("Observation.component.code".to_string());
+        code.must_support = Some(true);
+
+        let BuildOutcome::Complete(snapshot) = build(observation_base(), vec![slice, code], &types)
+        else {
+            panic!("unexpected type request");
+        };
+        let slice_code = snapshot
+            .iter()
+            .find(|e| element_id(e) == "Observation.component:a.code")
+            .unwrap();
+        assert_eq!(slice_code.must_support, Some(true));
+    }
+
+    #[test]
+    fn test_builder_unfolds_types() {
+        let mut types = TypeSnapshots::new();
+        let mut diff = create_test_element("Observation.code.coding");
+        diff.min = Some(1);
+
+        // First pass asks for the profiled type, then falls back to the base type
+        let mut code = typed("Observation.code", "Observation.code", "CodeableConcept");
+        code.type_.as_mut().unwrap()[0].profile = Some(vec![
+            "http://example.org/StructureDefinition/MyCC".to_string(),
+        ]);
+        let mut base = observation_base();
+        base[2] = code;
+
+        match build(base.clone(), vec![diff.clone()], &types) {
+            BuildOutcome::NeedsType(url) => {
+                assert_eq!(url, "http://example.org/StructureDefinition/MyCC");
+                types.insert(url, None);
+            }
+            other => panic!("expected type request, got {:?}", other),
+        }
+        match build(base.clone(), vec![diff.clone()], &types) {
+            BuildOutcome::NeedsType(url) => {
+                assert_eq!(
+                    url,
+                    "http://hl7.org/fhir/StructureDefinition/CodeableConcept"
+                );
+                types.insert(
+                    url,
+                    Some(vec![
+                        create_test_element("CodeableConcept"),
+                        typed("CodeableConcept.coding", "CodeableConcept.coding", "Coding"),
+                        typed("CodeableConcept.text", "CodeableConcept.text", "string"),
+                    ]),
+                );
+            }
+            other => panic!("expected type request, got {:?}", other),
+        }
+
+        let BuildOutcome::Complete(snapshot) = build(base, vec![diff], &types) else {
+            panic!("unexpected type request");
+        };
+        assert_eq!(
+            &ids(&snapshot)[2..5],
+            &[
+                "Observation.code",
+                "Observation.code.coding",
+                "Observation.code.text"
+            ]
+        );
+        assert_eq!(snapshot[3].path, "Observation.code.coding");
+        assert_eq!(snapshot[3].min, Some(1));
+    }
+
+    #[test]
+    fn test_builder_choice_and_content_reference() {
+        let mut types = TypeSnapshots::new();
+        types.insert(
+            "http://hl7.org/fhir/StructureDefinition/Quantity".to_string(),
+            Some(vec![
+                create_test_element("Quantity"),
+                typed("Quantity.value", "Quantity.value", "decimal"),
+            ]),
+        );
+
+        let mut base = observation_base();
+        let mut nested = create_test_element("Observation.component.component");
+        nested.id = Some("Observation.component.component".to_string());
+        nested.content_reference = Some("#Observation.component".to_string());
+        base.push(nested);
+
+        let mut quantity_value = create_test_element("Observation.valueQuantity.value");
+        quantity_value.min = Some(1);
+        let mut nested_code = create_test_element("Observation.component.component.code");
+        nested_code.must_support = Some(true);
+
+        let BuildOutcome::Complete(snapshot) =
+            build(base, vec![quantity_value, nested_code], &types)
+        else {
+            panic!("unexpected type request");
+        };
+
+        let slice = snapshot
+            .iter()
+            .find(|e| element_id(e) == "Observation.value[x]:valueQuantity")
+            .unwrap();
+        assert_eq!(slice.path, "Observation.value[x]");
+        assert_eq!(slice.type_.as_ref().unwrap().len(), 1);
+        let value = snapshot
+            .iter()
+            .find(|e| element_id(e) == "Observation.value[x]:valueQuantity.value")
+            .unwrap();
+        assert_eq!(value.path, "Observation.value[x].value");
+        assert_eq!(value.min, Some(1));
+
+        let nested_code = snapshot
+            .iter()
+            .find(|e| element_id(e) == "Observation.component.component.code")
+            .unwrap();
+        assert_eq!(nested_code.must_support, Some(true));
+    }
+
+    #[test]
+    fn test_assign_ids_and_merge_mappings() {
+        let mut slice = create_test_element("Observation.component");
+        slice.slice_name = Some("a".to_string());
+        let mut elements = vec![
+            create_test_element("Observation"),
+            slice,
+            create_test_element("Observation.component.code"),
+        ];
+        assign_ids(&mut elements);
+        assert_eq!(
+            ids(&elements),
+            vec![
+                "Observation",
+                "Observation.component:a",
+                "Observation.component:a.code"
+            ]
+        );
+
+        let mut parent = create_test_element("Observation.code");
+        parent.mapping = Some(vec![ElementDefinitionMapping::new("v2", "OBX-3")]);
+        let mut child = create_test_element("Observation.code");
+        child.mapping = Some(vec![
+            ElementDefinitionMapping::new("v2", "OBX-3"),
+            ElementDefinitionMapping::new("rim", "code"),
+        ]);
+        let merged = SnapshotGenerator::merge_element(&parent, &child).unwrap();
+        assert_eq!(merged.mapping.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parent_snapshot_not_found_reports_chain() {
+        let error = SnapshotError::ParentSnapshotNotFound {
+            url: "http://example.org/C".to_string(),
+            chain: vec![
+                "http://example.org/A".to_string(),
+                "http://example.org/B".to_string(),
+                "http://example.org/C".to_string(),
+            ],
+        };
+        assert_eq!(
+            error.to_string(),
+            "Parent snapshot not found for: http://example.org/C (chain: http://example.org/A -> http://example.org/B -> http://example.org/C)"
+        );
+        assert!(is_slice_copy(
+            "Observation.component:a/b.code",
+            "Observation.component:a.code"
+        ));
+        assert!(!is_slice_copy(
+            "Observation.component.code",
+            "Observation.component.code"
+        ));
+    }
 }
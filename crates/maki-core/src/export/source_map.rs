@@ -0,0 +1,571 @@
+//! FSH → generated JSON source maps
+//!
+//! Records which FSH rule produced each part of an exported resource so tools
+//! can jump from a validator message about `StructureDefinition-foo.json` back
+//! to the originating rule (`maki locate`).
+//!
+//! Source maps are keyed by JSON pointer (RFC 6901):
+//!
+//! - Profiles/Extensions: differential elements (`/differential/element/3`) and
+//!   caret-assigned properties (`/differential/element/3/short`, `/status`)
+//! - Instances: assigned properties (`/code/coding/0/system`)
+//! - ValueSets/CodeSystems: caret-assigned properties
+//!
+//! The empty pointer maps to the entity declaration itself.
+
+use crate::cst::FshSyntaxNode;
+use crate::cst::ast::{AstNode, Rule};
+use crate::export::build_report::SourceSpan;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// File suffix of source map sidecars
+pub const FSHMAP_SUFFIX: &str = ".fshmap.json";
+
+/// Location of the FSH rule that produced a JSON value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FshRuleLocation {
+    /// FSH file path relative to the input directory
+    pub file: String,
+    /// 1-based first line of the rule
+    pub start_line: usize,
+    /// 1-based last line of the rule
+    pub end_line: usize,
+    /// Rule text as written in FSH
+    pub rule: String,
+}
+
+/// Source map for one exported resource
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FshSourceMap {
+    /// Output filename of the mapped resource
+    pub resource: String,
+    pub fsh_name: String,
+    pub fsh_type: String,
+    /// JSON pointer → rules that set it (in FSH order)
+    pub mappings: BTreeMap<String, Vec<FshRuleLocation>>,
+}
+
+impl FshSourceMap {
+    /// Sidecar filename for an output file (`Patient-a.json` → `Patient-a.fshmap.json`)
+    pub fn sidecar_filename(output_file: &str) -> String {
+        let stem = output_file.strip_suffix(".json").unwrap_or(output_file);
+        format!("{}{}", stem, FSHMAP_SUFFIX)
+    }
+
+    /// Load a source map sidecar
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(std::io::Error::other)
+    }
+
+    /// Find the rules for a pointer, falling back to the nearest mapped ancestor
+    ///
+    /// Returns the pointer that matched together with its rules.
+    pub fn locate(&self, pointer: &str) -> Option<(&str, &[FshRuleLocation])> {
+        let mut current = pointer.trim_end_matches('/');
+        loop {
+            if let Some((key, rules)) = self.mappings.get_key_value(current) {
+                return Some((key.as_str(), rules.as_slice()));
+            }
+            if current.is_empty() {
+                return None;
+            }
+            current = current.rfind('/').map(|idx| &current[..idx]).unwrap_or("");
+        }
+    }
+}
+
+/// Builds a [`FshSourceMap`] by resolving FSH rule paths against the exported JSON
+pub struct SourceMapBuilder<'a> {
+    map: FshSourceMap,
+    fsh_file: String,
+    source: &'a str,
+    resource: &'a Value,
+    /// Last array index used per array pointer, for `[+]` / `[=]`
+    soft_indices: HashMap<String, usize>,
+}
+
+impl<'a> SourceMapBuilder<'a> {
+    /// Create a builder
+    ///
+    /// `source` is the full text of the FSH file the entity was parsed from and
+    /// `resource` the exported JSON.
+    pub fn new(
+        output_file: impl Into<String>,
+        fsh_name: impl Into<String>,
+        fsh_type: impl Into<String>,
+        fsh_file: impl Into<String>,
+        source: &'a str,
+        resource: &'a Value,
+    ) -> Self {
+        Self {
+            map: FshSourceMap {
+                resource: output_file.into(),
+                fsh_name: fsh_name.into(),
+                fsh_type: fsh_type.into(),
+                mappings: BTreeMap::new(),
+            },
+            fsh_file: fsh_file.into(),
+            source,
+            resource,
+            soft_indices: HashMap::new(),
+        }
+    }
+
+    /// Map the resource root to the entity declaration
+    pub fn add_entity(&mut self, entity: &FshSyntaxNode) {
+        let location = self.location(entity);
+        let rule = location.rule.lines().next().unwrap_or_default().to_string();
+        self.push(String::new(), FshRuleLocation { rule, ..location });
+    }
+
+    /// Map every rule of the entity
+    pub fn add_rules(&mut self, rules: impl IntoIterator<Item = Rule>) {
+        let is_structure_definition = self.resource.get("resourceType").and_then(Value::as_str)
+            == Some("StructureDefinition");
+        for rule in rules {
+            let pointers = if is_structure_definition {
+                self.structure_definition_pointers(&rule)
+            } else {
+                self.resource_pointers(&rule)
+            };
+            if pointers.is_empty() {
+                continue;
+            }
+            let location = self.location(rule.syntax());
+            for pointer in pointers {
+                self.push(pointer, location.clone());
+            }
+        }
+    }
+
+    pub fn finish(self) -> FshSourceMap {
+        self.map
+    }
+
+    fn push(&mut self, pointer: String, location: FshRuleLocation) {
+        let rules = self.map.mappings.entry(pointer).or_default();
+        if !rules.contains(&location) {
+            rules.push(location);
+        }
+    }
+
+    fn location(&self, node: &FshSyntaxNode) -> FshRuleLocation {
+        let range = node.text_range();
+        let start = rule_start(node).unwrap_or_else(|| range.start().into());
+        let end = range.end().into();
+        let span = SourceSpan::from_offsets(self.source, start, end);
+        FshRuleLocation {
+            file: self.fsh_file.clone(),
+            start_line: span.start_line,
+            end_line: span.end_line,
+            rule: self.source[span.start_offset..span.end_offset]
+                .trim()
+                .to_string(),
+        }
+    }
+
+    /// Pointers for a rule on a StructureDefinition (element-based paths)
+    fn structure_definition_pointers(&mut self, rule: &Rule) -> Vec<String> {
+        let element_path = match rule {
+            // `* ^status = #draft` parses as an assignment to a caret path
+            Rule::FixedValue(r) if r.path().is_some_and(|p| p.as_string().starts_with('^')) => {
+                return self.resource_pointers(rule);
+            }
+            Rule::Card(r) => r.path(),
+            Rule::Flag(r) => r.path(),
+            Rule::ValueSet(r) => r.path(),
+            Rule::FixedValue(r) => r.path(),
+            Rule::Path(r) => r.path(),
+            Rule::Only(r) => r.path(),
+            Rule::Obeys(r) => r.path(),
+            Rule::AddElement(r) => r.path(),
+            Rule::Contains(r) => {
+                let Some(path) = r.path().map(|p| p.as_string()) else {
+                    return Vec::new();
+                };
+                let mut pointers: Vec<String> = self.element_pointer(&path).into_iter().collect();
+                for slice in r.items() {
+                    pointers.extend(self.element_pointer(&format!("{}[{}]", path, slice)));
+                }
+                return pointers;
+            }
+            Rule::CaretValue(r) => {
+                let Some(field) = r.field() else {
+                    return Vec::new();
+                };
+                let base = match r.element_path() {
+                    Some(path) => match self.element_pointer(&path.as_string()) {
+                        Some(pointer) => pointer,
+                        None => return Vec::new(),
+                    },
+                    None => String::new(),
+                };
+                return self.value_pointer(&base, &field).into_iter().collect();
+            }
+            _ => None,
+        };
+        element_path
+            .and_then(|path| self.element_pointer(&path.as_string()))
+            .into_iter()
+            .collect()
+    }
+
+    /// Pointers for a rule on any other resource (property paths)
+    fn resource_pointers(&mut self, rule: &Rule) -> Vec<String> {
+        match rule {
+            Rule::FixedValue(r) => r
+                .path()
+                .and_then(|path| self.value_pointer("", &path.as_string()))
+                .into_iter()
+                .collect(),
+            Rule::CaretValue(r) if r.element_path().is_none() => r
+                .field()
+                .and_then(|field| self.value_pointer("", &field))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Pointer to the differential element a FSH element path refers to
+    fn element_pointer(&self, fsh_path: &str) -> Option<String> {
+        let elements = self.resource.pointer("/differential/element")?.as_array()?;
+        let base = self.resource.get("type").and_then(Value::as_str)?;
+        let id = element_id(base, fsh_path);
+        let path = strip_slices(&id);
+
+        elements
+            .iter()
+            .position(|el| el.get("id").and_then(Value::as_str) == Some(id.as_str()))
+            .or_else(|| {
+                elements
+                    .iter()
+                    .position(|el| el.get("path").and_then(Value::as_str) == Some(path.as_str()))
+            })
+            .map(|idx| format!("/differential/element/{}", idx))
+    }
+
+    /// Pointer to the value a FSH property path assigns, relative to `base`
+    ///
+    /// Walks the exported JSON so implicit `[0]` indices and choice types
+    /// resolve to what was actually written. Segments that can't be resolved
+    /// (e.g. slice names on instances) stop at the deepest existing value.
+    fn value_pointer(&mut self, base: &str, fsh_path: &str) -> Option<String> {
+        let resource: &'a Value = self.resource;
+        let mut pointer = base.to_string();
+        let mut current = resource.pointer(base)?;
+
+        'segments: for segment in split_path(fsh_path) {
+            let (name, brackets) = parse_segment(&segment);
+            let key = resolve_key(current, &name)?;
+            pointer = format!("{}/{}", pointer, escape_pointer_token(&key));
+            current = &current[&key];
+
+            let mut brackets = brackets.into_iter().peekable();
+            if brackets.peek().is_none() && current.is_array() {
+                // FSH allows omitting [0] on arrays
+                brackets = vec!["0".to_string()].into_iter().peekable();
+            }
+            for bracket in brackets {
+                let Some(items) = current.as_array() else {
+                    break 'segments;
+                };
+                let last = self.soft_indices.get(&pointer).copied();
+                let index = match bracket.as_str() {
+                    "+" => last.map(|i| i + 1).unwrap_or(0),
+                    "=" => last.unwrap_or(0),
+                    other => match other.parse::<usize>() {
+                        Ok(index) => index,
+                        Err(_) => match find_slice(items, other) {
+                            Some(index) => index,
+                            None => break 'segments,
+                        },
+                    },
+                };
+                self.soft_indices.insert(pointer.clone(), index);
+                let Some(item) = items.get(index) else {
+                    break 'segments;
+                };
+                pointer = format!("{}/{}", pointer, index);
+                current = item;
+            }
+        }
+
+        (!pointer.is_empty()).then_some(pointer)
+    }
+}
+
+/// Start offset of a rule including its leading `* path`
+///
+/// Rule nodes only cover the part after the path; the `*` and path are
+/// preceding siblings in the entity node.
+fn rule_start(node: &FshSyntaxNode) -> Option<usize> {
+    use crate::cst::FshSyntaxKind;
+
+    for sibling in node.siblings_with_tokens(rowan::Direction::Prev).skip(1) {
+        match sibling.kind() {
+            FshSyntaxKind::Asterisk => return Some(sibling.text_range().start().into()),
+            FshSyntaxKind::Path | FshSyntaxKind::Whitespace => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Convert a FSH element path to an ElementDefinition id (`code.coding[loinc]` → `Observation.code.coding:loinc`)
+fn element_id(base: &str, fsh_path: &str) -> String {
+    let fsh_path = fsh_path.trim();
+    if fsh_path == "." || fsh_path.is_empty() {
+        return base.to_string();
+    }
+    let mut id = base.to_string();
+    for segment in split_path(fsh_path) {
+        let (name, brackets) = parse_segment(&segment);
+        id.push('.');
+        id.push_str(&name);
+        for bracket in brackets {
+            match bracket.as_str() {
+                "x" => id.push_str("[x]"),
+                "+" | "=" => {}
+                other if other.parse::<usize>().is_ok() => {}
+                slice => {
+                    id.push(':');
+                    id.push_str(slice);
+                }
+            }
+        }
+    }
+    id
+}
+
+/// Remove `:slice` parts from an element id
+fn strip_slices(id: &str) -> String {
+    id.split('.')
+        .map(|segment| segment.split(':').next().unwrap_or(segment))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Split a FSH path on dots that aren't inside brackets
+fn split_path(path: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in path.trim().chars() {
+        match c {
+            '[' => {
+                depth += 1;
+                current.push(c);
+            }
+            ']' => {
+                depth = depth.saturating_sub(1);
+                current.push(c);
+            }
+            '.' if depth == 0 => {
+                if !current.is_empty() {
+                    segments.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// Split `name[a][b]` into `name` and its bracket contents
+fn parse_segment(segment: &str) -> (String, Vec<String>) {
+    let segment = segment.trim().trim_start_matches('^');
+    let Some(open) = segment.find('[') else {
+        return (segment.to_string(), Vec::new());
+    };
+    let brackets = segment[open..]
+        .split('[')
+        .filter_map(|part| part.strip_suffix(']'))
+        .map(str::to_string)
+        .collect();
+    (segment[..open].to_string(), brackets)
+}
+
+/// Find the JSON key a FSH property name refers to (handles `value[x]` style names)
+fn resolve_key(value: &Value, name: &str) -> Option<String> {
+    let object = value.as_object()?;
+    if object.contains_key(name) {
+        return Some(name.to_string());
+    }
+    object
+        .keys()
+        .find(|key| {
+            key.strip_prefix(name)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(char::is_uppercase)
+        })
+        .cloned()
+}
+
+/// Find an array item by slice name (extension slices are matched by URL)
+fn find_slice(items: &[Value], slice: &str) -> Option<usize> {
+    items.iter().position(|item| {
+        item.get("url")
+            .and_then(Value::as_str)
+            .is_some_and(|url| url == slice || url.ends_with(&format!("/{}", slice)))
+    })
+}
+
+/// Escape a JSON pointer reference token (RFC 6901)
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Map a validator-style element id (`Observation.code.coding:loinc`) to a differential pointer
+pub fn element_id_pointer(resource: &Value, element_id: &str) -> Option<String> {
+    resource
+        .pointer("/differential/element")?
+        .as_array()?
+        .iter()
+        .position(|el| el.get("id").and_then(Value::as_str) == Some(element_id))
+        .map(|idx| format!("/differential/element/{}", idx))
+}
+
+/// Rules of an entity node, for entity kinds that carry rules
+pub fn entity_rules(entity: &FshSyntaxNode) -> Vec<Rule> {
+    use crate::cst::ast::{CodeSystem, Extension, Instance, Profile, ValueSet};
+
+    if let Some(profile) = Profile::cast(entity.clone()) {
+        profile.rules().collect()
+    } else if let Some(extension) = Extension::cast(entity.clone()) {
+        extension.rules().collect()
+    } else if let Some(instance) = Instance::cast(entity.clone()) {
+        instance.rules().collect()
+    } else if let Some(value_set) = ValueSet::cast(entity.clone()) {
+        value_set.rules().collect()
+    } else if let Some(code_system) = CodeSystem::cast(entity.clone()) {
+        code_system.rules().collect()
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cst::ast::{Instance, Profile};
+    use crate::cst::parse_fsh;
+    use serde_json::json;
+
+    fn build_map(source: &str, resource: &Value) -> FshSourceMap {
+        let (root, _, _) = parse_fsh(source);
+        let entity = root
+            .children()
+            .find(|node| Profile::can_cast(node.kind()) || Instance::can_cast(node.kind()))
+            .unwrap();
+        let mut builder =
+            SourceMapBuilder::new("out.json", "Test", "Profile", "test.fsh", source, resource);
+        builder.add_entity(&entity);
+        builder.add_rules(entity_rules(&entity));
+        builder.finish()
+    }
+
+    #[test]
+    fn test_profile_rules_map_to_differential_elements() {
+        let source = "Profile: MyObs\nParent: Observation\n* ^status = #draft\n* code 1..1 MS\n* code.coding contains loinc 1..1\n* code.coding[loinc].system = \"http://loinc.org\"\n* code ^short = \"The code\"\n";
+        let sd = json!({
+            "resourceType": "StructureDefinition",
+            "type": "Observation",
+            "status": "draft",
+            "differential": { "element": [
+                { "id": "Observation.code", "path": "Observation.code", "short": "The code" },
+                { "id": "Observation.code.coding:loinc", "path": "Observation.code.coding", "sliceName": "loinc" },
+                { "id": "Observation.code.coding:loinc.system", "path": "Observation.code.coding.system" }
+            ]}
+        });
+
+        let map = build_map(source, &sd);
+
+        assert_eq!(map.mappings[""][0].rule, "Profile: MyObs");
+        assert_eq!(map.mappings["/status"][0].start_line, 3);
+        let code = &map.mappings["/differential/element/0"];
+        assert_eq!(code[0].rule, "* code 1..1 MS");
+        assert_eq!(map.mappings["/differential/element/1"][0].start_line, 5);
+        assert_eq!(map.mappings["/differential/element/2"][0].start_line, 6);
+        assert_eq!(
+            map.mappings["/differential/element/0/short"][0].start_line,
+            7
+        );
+    }
+
+    #[test]
+    fn test_instance_rules_map_to_properties() {
+        let source = "Instance: ex\nInstanceOf: Patient\n* name[0].family = \"Doe\"\n* name[=].given[+] = \"Jane\"\n* name[=].given[+] = \"Q\"\n* gender = #female\n* telecom.value = \"555\"\n";
+        let patient = json!({
+            "resourceType": "Patient",
+            "name": [{ "family": "Doe", "given": ["Jane", "Q"] }],
+            "gender": "female",
+            "telecom": [{ "value": "555" }]
+        });
+
+        let map = build_map(source, &patient);
+
+        assert_eq!(map.mappings["/name/0/family"][0].start_line, 3);
+        assert_eq!(map.mappings["/name/0/given/0"][0].start_line, 4);
+        assert_eq!(map.mappings["/name/0/given/1"][0].start_line, 5);
+        assert_eq!(map.mappings["/gender"][0].start_line, 6);
+        assert_eq!(map.mappings["/telecom/0/value"][0].start_line, 7);
+    }
+
+    #[test]
+    fn test_locate_falls_back_to_ancestor() {
+        let mut mappings = BTreeMap::new();
+        let location = FshRuleLocation {
+            file: "a.fsh".to_string(),
+            start_line: 4,
+            end_line: 4,
+            rule: "* code 1..1".to_string(),
+        };
+        mappings.insert(
+            "/differential/element/0".to_string(),
+            vec![location.clone()],
+        );
+        let map = FshSourceMap {
+            resource: "StructureDefinition-a.json".to_string(),
+            fsh_name: "A".to_string(),
+            fsh_type: "Profile".to_string(),
+            mappings,
+        };
+
+        let (pointer, rules) = map.locate("/differential/element/0/min").unwrap();
+        assert_eq!(pointer, "/differential/element/0");
+        assert_eq!(rules[0], location);
+        assert!(map.locate("/snapshot").is_none());
+    }
+
+    #[test]
+    fn test_path_helpers() {
+        assert_eq!(
+            element_id("Observation", "code.coding[loinc].system"),
+            "Observation.code.coding:loinc.system"
+        );
+        assert_eq!(
+            element_id("Observation", "value[x]"),
+            "Observation.value[x]"
+        );
+        assert_eq!(element_id("Extension", "."), "Extension");
+        assert_eq!(
+            split_path("extension[http://a.b/c].value[x]"),
+            vec!["extension[http://a.b/c]", "value[x]"]
+        );
+        assert_eq!(
+            FshSourceMap::sidecar_filename("Patient-a.json"),
+            "Patient-a.fshmap.json"
+        );
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+    }
+}
@@ -0,0 +1,774 @@
+//! FHIR XML serializer
+//!
+//! Converts exported FHIR JSON resources into spec-conformant FHIR XML.
+//!
+//! ## XML Representation Rules
+//!
+//! - Primitive values become `value` attributes (`<status value="draft"/>`)
+//! - Primitive extensions (`_field`) are merged into the same element as
+//!   `id` attribute and `<extension>` children
+//! - `Element.id` and `Extension.url` are written as attributes
+//! - The narrative `div` is written as inline xhtml
+//! - Contained resources are wrapped in an element named after their type
+//! - Child elements follow ElementDefinition order from the base
+//!   StructureDefinitions (JSON key order is used for anything unknown)
+//!
+//! **Reference**: <https://hl7.org/fhir/R4/xml.html>
+
+use crate::canonical::DefinitionSession;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::{HashMap, HashSet, VecDeque};
+use thiserror::Error;
+use tracing::{debug, trace};
+
+/// FHIR XML namespace
+pub const FHIR_XML_NAMESPACE: &str = "http://hl7.org/fhir";
+
+/// XHTML namespace used by narrative `div` elements
+pub const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// Base URL for core FHIR StructureDefinitions
+const CORE_SD_BASE: &str = "http://hl7.org/fhir/StructureDefinition/";
+
+/// Errors that can occur during XML serialization
+#[derive(Debug, Error)]
+pub enum XmlSerializeError {
+    #[error("Resource is not a JSON object")]
+    NotAnObject,
+
+    #[error("Resource has no resourceType")]
+    MissingResourceType,
+}
+
+/// A child element declared by an ElementDefinition
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChildElement {
+    /// Element name (last path segment, may end with `[x]`)
+    name: String,
+    /// Full element path (used as context for backbone elements)
+    path: String,
+    /// Type codes declared for the element
+    types: Vec<String>,
+    /// Target path of a `contentReference` (e.g. `Questionnaire.item`)
+    content_reference: Option<String>,
+}
+
+impl ChildElement {
+    /// Match a JSON property name against this element
+    ///
+    /// Returns the concrete type for choice elements (`valueQuantity` → `Quantity`).
+    fn matches(&self, key: &str) -> Option<Option<String>> {
+        if self.name == key {
+            return Some(None);
+        }
+
+        let prefix = self.name.strip_suffix("[x]")?;
+        let suffix = key.strip_prefix(prefix)?;
+        if !suffix.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return None;
+        }
+
+        let concrete = self
+            .types
+            .iter()
+            .find(|t| upper_first(t) == suffix)
+            .cloned()
+            .unwrap_or_else(|| suffix.to_string());
+        Some(Some(concrete))
+    }
+
+    /// Determine the ordering context for a complex value of this element
+    fn child_context(&self, concrete_type: Option<&str>) -> Option<String> {
+        if let Some(target) = &self.content_reference {
+            return Some(target.clone());
+        }
+
+        let type_code = concrete_type.or_else(|| self.types.first().map(String::as_str))?;
+        if matches!(type_code, "BackboneElement" | "Element") {
+            Some(self.path.clone())
+        } else {
+            Some(type_code.to_string())
+        }
+    }
+}
+
+/// Element ordering extracted from StructureDefinition snapshots
+///
+/// Maps a context (type name such as `Coding`, or a backbone path such as
+/// `Patient.contact`) to its child elements in ElementDefinition order.
+#[derive(Debug, Default, Clone)]
+pub struct ElementOrderIndex {
+    children: HashMap<String, Vec<ChildElement>>,
+    loaded_types: HashSet<String>,
+}
+
+impl ElementOrderIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load element ordering for the given types and every complex type they reference
+    ///
+    /// StructureDefinitions are resolved through the session, so definitions
+    /// already loaded by the exporters are served from its cache.
+    pub async fn load<I>(session: &DefinitionSession, types: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut index = Self::new();
+        let mut queue: VecDeque<String> = types.into_iter().collect();
+        let mut attempted = HashSet::new();
+
+        while let Some(type_name) = queue.pop_front() {
+            if !attempted.insert(type_name.clone()) {
+                continue;
+            }
+
+            let url = format!("{}{}", CORE_SD_BASE, type_name);
+            match session.resolve(&url).await {
+                Ok(resource) => {
+                    for referenced in index.add_structure_definition(&resource.content) {
+                        if !attempted.contains(&referenced) {
+                            queue.push_back(referenced);
+                        }
+                    }
+                }
+                Err(e) => {
+                    debug!("No element order available for {}: {}", type_name, e);
+                }
+            }
+        }
+
+        debug!(
+            "Loaded element order for {} types ({} contexts)",
+            index.loaded_types.len(),
+            index.children.len()
+        );
+        index
+    }
+
+    /// Add a StructureDefinition to the index
+    ///
+    /// Returns the complex types referenced by its elements, so callers can
+    /// load them as well.
+    pub fn add_structure_definition(&mut self, sd: &JsonValue) -> Vec<String> {
+        let Some(type_name) = sd.get("type").and_then(|t| t.as_str()) else {
+            return Vec::new();
+        };
+        if !self.loaded_types.insert(type_name.to_string()) {
+            return Vec::new();
+        }
+
+        let elements = sd
+            .get("snapshot")
+            .or_else(|| sd.get("differential"))
+            .and_then(|s| s.get("element"))
+            .and_then(|e| e.as_array());
+        let Some(elements) = elements else {
+            return Vec::new();
+        };
+
+        let mut referenced = Vec::new();
+        for element in elements {
+            // Slices never introduce new element names
+            if element.get("sliceName").is_some() {
+                continue;
+            }
+            let Some(path) = element.get("path").and_then(|p| p.as_str()) else {
+                continue;
+            };
+            let Some((parent, name)) = path.rsplit_once('.') else {
+                continue;
+            };
+
+            let types: Vec<String> = element
+                .get("type")
+                .and_then(|t| t.as_array())
+                .map(|types| {
+                    types
+                        .iter()
+                        .filter_map(|t| t.get("code").and_then(|c| c.as_str()))
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let content_reference = element
+                .get("contentReference")
+                .and_then(|c| c.as_str())
+                .and_then(|c| c.rsplit_once('#'))
+                .map(|(_, target)| target.to_string());
+
+            for type_code in &types {
+                if is_complex_type_code(type_code) {
+                    referenced.push(type_code.clone());
+                }
+            }
+
+            let siblings = self.children.entry(parent.to_string()).or_default();
+            if !siblings.iter().any(|c| c.name == name) {
+                siblings.push(ChildElement {
+                    name: name.to_string(),
+                    path: path.to_string(),
+                    types,
+                    content_reference,
+                });
+            }
+        }
+
+        trace!("Indexed element order for {}", type_name);
+        referenced
+    }
+
+    /// Whether ordering information is available for a context
+    pub fn has_context(&self, context: &str) -> bool {
+        self.children.contains_key(context)
+    }
+
+    /// Order property names by the element order declared for `context`
+    ///
+    /// Choice elements match their typed names (`valueQuantity`); names the
+    /// context does not declare keep their relative order at the end.
+    pub fn order_names<'n>(&self, context: &str, names: &[&'n str]) -> Vec<&'n str> {
+        let Some(children) = self.children(context) else {
+            return names.to_vec();
+        };
+
+        let mut remaining = names.to_vec();
+        let mut ordered = Vec::with_capacity(names.len());
+        for child in children {
+            remaining.retain(|name| {
+                let matched = child.matches(name).is_some();
+                if matched {
+                    ordered.push(*name);
+                }
+                !matched
+            });
+        }
+        ordered.extend(remaining);
+        ordered
+    }
+
+    fn children(&self, context: &str) -> Option<&[ChildElement]> {
+        self.children.get(context).map(Vec::as_slice)
+    }
+}
+
+/// Collect every `resourceType` used by a resource (including contained and bundled resources)
+pub fn collect_resource_types(resource: &JsonValue, types: &mut HashSet<String>) {
+    match resource {
+        JsonValue::Object(map) => {
+            if let Some(rt) = map.get("resourceType").and_then(|v| v.as_str()) {
+                types.insert(rt.to_string());
+            }
+            for value in map.values() {
+                collect_resource_types(value, types);
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_resource_types(item, types);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// FHIR XML serializer
+///
+/// Uses an [`ElementOrderIndex`] to order child elements. When no ordering is
+/// known for a context, the JSON property order is preserved.
+pub struct FhirXmlSerializer<'a> {
+    index: &'a ElementOrderIndex,
+}
+
+impl<'a> FhirXmlSerializer<'a> {
+    /// Create a serializer backed by an element order index
+    pub fn new(index: &'a ElementOrderIndex) -> Self {
+        Self { index }
+    }
+
+    /// Serialize a FHIR JSON resource to an XML document
+    pub fn serialize(&self, resource: &JsonValue) -> Result<String, XmlSerializeError> {
+        let map = resource.as_object().ok_or(XmlSerializeError::NotAnObject)?;
+        let resource_type = map
+            .get("resourceType")
+            .and_then(|v| v.as_str())
+            .ok_or(XmlSerializeError::MissingResourceType)?;
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!(
+            "<{} xmlns=\"{}\">\n",
+            resource_type, FHIR_XML_NAMESPACE
+        ));
+        self.write_properties(&mut out, map, Some(resource_type), true, 1);
+        out.push_str(&format!("</{}>\n", resource_type));
+        Ok(out)
+    }
+
+    /// Write a resource nested inside another element (contained, Bundle.entry.resource)
+    fn write_resource(&self, out: &mut String, map: &Map<String, JsonValue>, depth: usize) {
+        let resource_type = map
+            .get("resourceType")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Resource");
+        indent(out, depth);
+        out.push_str(&format!("<{}>\n", resource_type));
+        self.write_properties(out, map, Some(resource_type), true, depth + 1);
+        indent(out, depth);
+        out.push_str(&format!("</{}>\n", resource_type));
+    }
+
+    /// Write the child elements of an object in ElementDefinition order
+    fn write_properties(
+        &self,
+        out: &mut String,
+        map: &Map<String, JsonValue>,
+        context: Option<&str>,
+        is_resource: bool,
+        depth: usize,
+    ) {
+        for (name, element) in self.ordered_properties(map, context, is_resource) {
+            let value = map.get(&name);
+            let primitive_ext = map.get(&format!("_{}", name));
+            let child_context = element
+                .as_ref()
+                .and_then(|(child, concrete)| child.child_context(concrete.as_deref()));
+            let child_context = child_context.or_else(|| implied_context(&name));
+
+            if name == "div" && !is_resource {
+                if let Some(JsonValue::String(xhtml)) = value {
+                    write_xhtml(out, xhtml, depth);
+                }
+                continue;
+            }
+
+            for (item, ext) in pair_values(value, primitive_ext) {
+                self.write_element(out, &name, item, ext, child_context.as_deref(), depth);
+            }
+        }
+    }
+
+    /// Determine the output order of the properties of an object
+    ///
+    /// Returns each logical property name (with `_` prefixes merged) paired
+    /// with the matching ElementDefinition, if known.
+    #[allow(clippy::type_complexity)]
+    fn ordered_properties(
+        &self,
+        map: &Map<String, JsonValue>,
+        context: Option<&str>,
+        is_resource: bool,
+    ) -> Vec<(String, Option<(ChildElement, Option<String>)>)> {
+        let mut names: Vec<String> = Vec::new();
+        for key in map.keys() {
+            let name = key.strip_prefix('_').unwrap_or(key);
+            if name == "resourceType" || names.iter().any(|n| n == name) {
+                continue;
+            }
+            // Element.id and Extension.url are attributes, not child elements
+            if !is_resource && name == "id" && map.get(name).is_some_and(|v| v.is_string()) {
+                continue;
+            }
+            if name == "url" && context == Some("Extension") {
+                continue;
+            }
+            names.push(name.to_string());
+        }
+
+        let Some(children) = context.and_then(|c| self.index.children(c)) else {
+            return names.into_iter().map(|n| (n, None)).collect();
+        };
+
+        let mut ordered = Vec::with_capacity(names.len());
+        for child in children {
+            let mut matched: Vec<usize> = Vec::new();
+            for (i, name) in names.iter().enumerate() {
+                if let Some(concrete) = child.matches(name) {
+                    ordered.push((name.clone(), Some((child.clone(), concrete))));
+                    matched.push(i);
+                }
+            }
+            for i in matched.into_iter().rev() {
+                names.remove(i);
+            }
+        }
+
+        // Anything the StructureDefinition doesn't declare keeps its JSON order
+        ordered.extend(names.into_iter().map(|n| (n, None)));
+        ordered
+    }
+
+    /// Write a single element (primitive or complex)
+    fn write_element(
+        &self,
+        out: &mut String,
+        name: &str,
+        value: Option<&JsonValue>,
+        primitive_ext: Option<&JsonValue>,
+        context: Option<&str>,
+        depth: usize,
+    ) {
+        match value {
+            Some(JsonValue::Object(obj)) if obj.contains_key("resourceType") => {
+                indent(out, depth);
+                out.push_str(&format!("<{}>\n", name));
+                self.write_resource(out, obj, depth + 1);
+                indent(out, depth);
+                out.push_str(&format!("</{}>\n", name));
+            }
+            Some(JsonValue::Object(obj)) => {
+                indent(out, depth);
+                out.push_str(&format!("<{}", name));
+                if let Some(JsonValue::String(id)) = obj.get("id") {
+                    push_attribute(out, "id", id);
+                }
+                if context == Some("Extension")
+                    && let Some(JsonValue::String(url)) = obj.get("url")
+                {
+                    push_attribute(out, "url", url);
+                }
+
+                let mut body = String::new();
+                self.write_properties(&mut body, obj, context, false, depth + 1);
+                if body.is_empty() {
+                    out.push_str("/>\n");
+                } else {
+                    out.push_str(">\n");
+                    out.push_str(&body);
+                    indent(out, depth);
+                    out.push_str(&format!("</{}>\n", name));
+                }
+            }
+            Some(JsonValue::Null) | None if primitive_ext.is_none() => {}
+            _ => self.write_primitive(out, name, value, primitive_ext, depth),
+        }
+    }
+
+    /// Write a primitive element with its optional `_field` id and extensions
+    fn write_primitive(
+        &self,
+        out: &mut String,
+        name: &str,
+        value: Option<&JsonValue>,
+        primitive_ext: Option<&JsonValue>,
+        depth: usize,
+    ) {
+        indent(out, depth);
+        out.push_str(&format!("<{}", name));
+
+        let ext_obj = primitive_ext.and_then(|e| e.as_object());
+        if let Some(JsonValue::String(id)) = ext_obj.and_then(|e| e.get("id")) {
+            push_attribute(out, "id", id);
+        }
+        if let Some(text) = value.and_then(primitive_to_string) {
+            push_attribute(out, "value", &text);
+        }
+
+        let extensions = ext_obj
+            .and_then(|e| e.get("extension"))
+            .and_then(|e| e.as_array());
+        match extensions {
+            Some(extensions) if !extensions.is_empty() => {
+                out.push_str(">\n");
+                for extension in extensions {
+                    self.write_element(
+                        out,
+                        "extension",
+                        Some(extension),
+                        None,
+                        Some("Extension"),
+                        depth + 1,
+                    );
+                }
+                indent(out, depth);
+                out.push_str(&format!("</{}>\n", name));
+            }
+            _ => out.push_str("/>\n"),
+        }
+    }
+}
+
+/// Contexts implied by the property name when no ElementDefinition is known
+fn implied_context(name: &str) -> Option<String> {
+    match name {
+        "extension" | "modifierExtension" => Some("Extension".to_string()),
+        _ => None,
+    }
+}
+
+/// Pair each value with its matching `_field` entry
+///
+/// Arrays are aligned by index; `null` entries in either array are allowed.
+fn pair_values<'v>(
+    value: Option<&'v JsonValue>,
+    primitive_ext: Option<&'v JsonValue>,
+) -> Vec<(Option<&'v JsonValue>, Option<&'v JsonValue>)> {
+    match (value, primitive_ext) {
+        (Some(JsonValue::Array(values)), Some(JsonValue::Array(exts))) => {
+            let len = values.len().max(exts.len());
+            (0..len)
+                .map(|i| {
+                    (
+                        values.get(i).filter(|v| !v.is_null()),
+                        exts.get(i).filter(|e| !e.is_null()),
+                    )
+                })
+                .collect()
+        }
+        (Some(JsonValue::Array(values)), _) => values.iter().map(|v| (Some(v), None)).collect(),
+        (None, Some(JsonValue::Array(exts))) => exts
+            .iter()
+            .map(|e| (None, Some(e).filter(|e| !e.is_null())))
+            .collect(),
+        (value, ext) => vec![(value, ext)],
+    }
+}
+
+/// Write the narrative xhtml, ensuring the xhtml namespace is declared
+fn write_xhtml(out: &mut String, xhtml: &str, depth: usize) {
+    indent(out, depth);
+    let trimmed = xhtml.trim();
+    if trimmed.starts_with("<div") && !trimmed.contains("xmlns=") {
+        out.push_str(&format!(
+            "<div xmlns=\"{}\"{}",
+            XHTML_NAMESPACE,
+            &trimmed["<div".len()..]
+        ));
+    } else {
+        out.push_str(trimmed);
+    }
+    out.push('\n');
+}
+
+/// Convert a JSON primitive into its XML `value` attribute text
+fn primitive_to_string(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Bool(b) => Some(b.to_string()),
+        JsonValue::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Type codes that have their own StructureDefinition with child elements
+fn is_complex_type_code(code: &str) -> bool {
+    code.starts_with(|c: char| c.is_ascii_uppercase())
+        && !matches!(code, "BackboneElement" | "Element" | "Resource")
+        && !code.contains('/')
+}
+
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+fn push_attribute(out: &mut String, name: &str, value: &str) {
+    out.push(' ');
+    out.push_str(name);
+    out.push_str("=\"");
+    out.push_str(&escape_attribute(value));
+    out.push('"');
+}
+
+/// Escape XML special characters in an attribute value
+fn escape_attribute(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\n' => result.push_str("&#10;"),
+            '\r' => result.push_str("&#13;"),
+            '\t' => result.push_str("&#9;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patient_index() -> ElementOrderIndex {
+        let mut index = ElementOrderIndex::new();
+        index.add_structure_definition(&json!({
+            "resourceType": "StructureDefinition",
+            "type": "Patient",
+            "snapshot": { "element": [
+                { "path": "Patient" },
+                { "path": "Patient.id", "type": [{ "code": "id" }] },
+                { "path": "Patient.meta", "type": [{ "code": "Meta" }] },
+                { "path": "Patient.text", "type": [{ "code": "Narrative" }] },
+                { "path": "Patient.extension", "type": [{ "code": "Extension" }] },
+                { "path": "Patient.active", "type": [{ "code": "boolean" }] },
+                { "path": "Patient.name", "type": [{ "code": "HumanName" }] },
+                { "path": "Patient.birthDate", "type": [{ "code": "date" }] },
+                { "path": "Patient.deceased[x]", "type": [{ "code": "boolean" }, { "code": "dateTime" }] },
+                { "path": "Patient.contact", "type": [{ "code": "BackboneElement" }] },
+                { "path": "Patient.contact.name", "type": [{ "code": "HumanName" }] },
+                { "path": "Patient.contact.gender", "type": [{ "code": "code" }] }
+            ]}
+        }));
+        index.add_structure_definition(&json!({
+            "resourceType": "StructureDefinition",
+            "type": "HumanName",
+            "snapshot": { "element": [
+                { "path": "HumanName" },
+                { "path": "HumanName.id", "type": [{ "code": "string" }] },
+                { "path": "HumanName.family", "type": [{ "code": "string" }] },
+                { "path": "HumanName.given", "type": [{ "code": "string" }] }
+            ]}
+        }));
+        index
+    }
+
+    #[test]
+    fn test_orders_elements_by_element_definition() {
+        let index = patient_index();
+        let serializer = FhirXmlSerializer::new(&index);
+        let resource = json!({
+            "resourceType": "Patient",
+            "birthDate": "1970-01-01",
+            "name": [{ "given": ["Jane"], "family": "Doe" }],
+            "active": true,
+            "id": "example"
+        });
+
+        let xml = serializer.serialize(&resource).unwrap();
+        let id = xml.find("<id value=\"example\"/>").unwrap();
+        let active = xml.find("<active value=\"true\"/>").unwrap();
+        let family = xml.find("<family value=\"Doe\"/>").unwrap();
+        let given = xml.find("<given value=\"Jane\"/>").unwrap();
+        let birth = xml.find("<birthDate value=\"1970-01-01\"/>").unwrap();
+        assert!(id < active && active < family && family < given && given < birth);
+        assert!(xml.starts_with(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Patient xmlns=\"http://hl7.org/fhir\">"
+        ));
+    }
+
+    #[test]
+    fn test_choice_and_backbone_elements() {
+        let index = patient_index();
+        let serializer = FhirXmlSerializer::new(&index);
+        let resource = json!({
+            "resourceType": "Patient",
+            "contact": [{ "gender": "female", "name": { "family": "Roe" }, "id": "c1" }],
+            "deceasedBoolean": false
+        });
+
+        let xml = serializer.serialize(&resource).unwrap();
+        assert!(xml.contains("<deceasedBoolean value=\"false\"/>"));
+        assert!(xml.contains("<contact id=\"c1\">"));
+        let name = xml.find("<family value=\"Roe\"/>").unwrap();
+        let gender = xml.find("<gender value=\"female\"/>").unwrap();
+        assert!(xml.find("<deceasedBoolean").unwrap() < xml.find("<contact").unwrap());
+        assert!(name < gender);
+    }
+
+    #[test]
+    fn test_primitive_extensions() {
+        let index = patient_index();
+        let serializer = FhirXmlSerializer::new(&index);
+        let resource = json!({
+            "resourceType": "Patient",
+            "birthDate": "1970-01-01",
+            "_birthDate": {
+                "id": "bd",
+                "extension": [{
+                    "url": "http://hl7.org/fhir/StructureDefinition/patient-birthTime",
+                    "valueDateTime": "1970-01-01T10:00:00Z"
+                }]
+            },
+            "name": [{
+                "given": ["Jane", null],
+                "_given": [null, { "extension": [{ "url": "http://example.org/ext", "valueString": "x" }] }]
+            }]
+        });
+
+        let xml = serializer.serialize(&resource).unwrap();
+        assert!(xml.contains("<birthDate id=\"bd\" value=\"1970-01-01\">"));
+        assert!(xml.contains(
+            "<extension url=\"http://hl7.org/fhir/StructureDefinition/patient-birthTime\">"
+        ));
+        assert!(xml.contains("<valueDateTime value=\"1970-01-01T10:00:00Z\"/>"));
+        assert!(xml.contains("<given value=\"Jane\"/>"));
+        assert!(xml.contains("<given>\n"));
+        assert!(!xml.contains("_given"));
+        assert!(!xml.contains("<url"));
+    }
+
+    #[test]
+    fn test_narrative_div_and_escaping() {
+        let index = ElementOrderIndex::new();
+        let serializer = FhirXmlSerializer::new(&index);
+        let resource = json!({
+            "resourceType": "Basic",
+            "text": { "status": "generated", "div": "<div>Hello &amp; welcome</div>" },
+            "code": { "text": "A \"quoted\" <value> & more" }
+        });
+
+        let xml = serializer.serialize(&resource).unwrap();
+        assert!(
+            xml.contains("<div xmlns=\"http://www.w3.org/1999/xhtml\">Hello &amp; welcome</div>")
+        );
+        assert!(xml.contains("<text value=\"A &quot;quoted&quot; &lt;value&gt; &amp; more\"/>"));
+    }
+
+    #[test]
+    fn test_contained_resources() {
+        let index = patient_index();
+        let serializer = FhirXmlSerializer::new(&index);
+        let resource = json!({
+            "resourceType": "Observation",
+            "id": "obs",
+            "contained": [{ "resourceType": "Patient", "id": "p1", "active": true }]
+        });
+
+        let xml = serializer.serialize(&resource).unwrap();
+        assert!(xml.contains("<contained>\n    <Patient>\n      <id value=\"p1\"/>"));
+        assert!(xml.contains("<id value=\"obs\"/>"));
+    }
+
+    #[test]
+    fn test_requires_resource_type() {
+        let index = ElementOrderIndex::new();
+        let serializer = FhirXmlSerializer::new(&index);
+        assert!(matches!(
+            serializer.serialize(&json!({ "id": "x" })),
+            Err(XmlSerializeError::MissingResourceType)
+        ));
+        assert!(matches!(
+            serializer.serialize(&json!([])),
+            Err(XmlSerializeError::NotAnObject)
+        ));
+    }
+
+    #[test]
+    fn test_collect_resource_types() {
+        let mut types = HashSet::new();
+        collect_resource_types(
+            &json!({
+                "resourceType": "Bundle",
+                "entry": [{ "resource": { "resourceType": "Patient" } }]
+            }),
+            &mut types,
+        );
+        assert!(types.contains("Bundle"));
+        assert!(types.contains("Patient"));
+    }
+}
@@ -48,18 +48,19 @@ pub use config::{
 // Console utilities for rich terminal output
 pub use console::{Color, Console};
 pub use diagnostics::{
-    Advices, Applicability, CodeSuggestion, DefaultDiagnosticCollector, DefaultOutputFormatter,
-    Diagnostic, DiagnosticCategory, DiagnosticCollector, DiagnosticFormatter,
-    DiagnosticOutputFormatter, DiagnosticRenderer, DiffRenderer, Label, ListAdvice, Location,
-    LogAdvice, LogCategory, OutputFormat, Severity, SourceMap, Visit,
+    Advices, Applicability, Baseline, BaselineComparison, BaselineEntry, CodeSuggestion,
+    DefaultDiagnosticCollector, DefaultOutputFormatter, Diagnostic, DiagnosticCategory,
+    DiagnosticCollector, DiagnosticFormatter, DiagnosticOutputFormatter, DiagnosticRenderer,
+    DiffRenderer, Label, ListAdvice, Location, LogAdvice, LogCategory, OutputFormat, Severity,
+    SourceMap, Visit,
 };
 pub use discovery::{
     DefaultFileDiscovery, FileChangeEvent, FileChangeKind, FileDiscovery, FileWatcher,
 };
 pub use error::{ErrorKind, MakiError};
 pub use executor::{
-    DefaultExecutor, ExecutionContext, Executor, FileExecutionResult, ProgressCallback,
-    ProgressInfo, ResourceStats,
+    AnalyzedFile, DefaultExecutor, ExecutionContext, Executor, FileExecutionResult,
+    ProgressCallback, ProgressInfo, ResourceStats,
 };
 pub use export::{InstanceExporter, ProfileExporter};
 pub use formatter::{
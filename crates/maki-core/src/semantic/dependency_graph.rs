@@ -217,6 +217,19 @@ impl DependencyGraph {
         }
     }
 
+    /// Get the direct dependencies of a node with the given type
+    pub fn get_dependencies_of_type(&self, name: &str, dep_type: DependencyType) -> Vec<&str> {
+        if let Some(&idx) = self.node_map.get(name) {
+            self.graph
+                .edges_directed(idx, Direction::Outgoing)
+                .filter(|edge| edge.weight().dep_type == dep_type)
+                .map(|edge| self.graph[edge.target()].as_str())
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
     /// Get all direct dependents of a node (reverse dependencies)
     ///
     /// Returns the names of definitions that depend on this definition.
@@ -301,6 +314,20 @@ impl DependencyGraph {
             .collect()
     }
 
+    /// Find cycles formed only by edges of the given type
+    ///
+    /// Same format as [`find_cycles`](Self::find_cycles); e.g. with
+    /// [`DependencyType::Parent`] this finds parent chains that loop.
+    pub fn find_cycles_of_type(&self, dep_type: DependencyType) -> Vec<Vec<String>> {
+        let mut filtered = DependencyGraph::new();
+        for edge in self.graph.edge_weights() {
+            if edge.dep_type == dep_type {
+                filtered.add_edge(&edge.from, &edge.to, dep_type, edge.source_location.clone());
+            }
+        }
+        filtered.find_cycles()
+    }
+
     /// Get strongly connected components
     ///
     /// Returns groups of nodes that are mutually reachable.
@@ -479,7 +506,7 @@ impl DependencyAnalyzer {
 
         // Analyze each resource for dependencies
         for resource in &self.model.resources {
-            let edges = self.analyze_resource(resource);
+            let edges = Self::analyze_resource(resource);
 
             for (target, dep_type, location) in edges {
                 trace!(
@@ -501,11 +528,43 @@ impl DependencyAnalyzer {
         Ok(graph)
     }
 
+    /// Build one dependency graph from the models of all project files
+    ///
+    /// Nodes are resource ids, as in [`build_graph`](Self::build_graph).
+    /// References by name are mapped to the id of the resource with that
+    /// name, so cross-file dependencies connect regardless of how they are
+    /// written.
+    pub fn build_project_graph(models: &[SemanticModel]) -> DependencyGraph {
+        let resources = || models.iter().flat_map(|model| &model.resources);
+        let ids_by_name: HashMap<&str, &str> = resources()
+            .filter_map(|r| r.name.as_deref().map(|name| (name, r.id.as_str())))
+            .collect();
+
+        let mut graph = DependencyGraph::new();
+        for resource in resources() {
+            graph.add_node(resource.id.clone());
+        }
+        for resource in resources() {
+            for (target, dep_type, location) in Self::analyze_resource(resource) {
+                let target = ids_by_name
+                    .get(target.as_str())
+                    .copied()
+                    .unwrap_or(target.as_str());
+                graph.add_edge(&resource.id, target, dep_type, location);
+            }
+        }
+
+        debug!(
+            "Built project dependency graph from {} files: {} nodes, {} edges",
+            models.len(),
+            graph.node_count(),
+            graph.edge_count()
+        );
+        graph
+    }
+
     /// Analyze a single resource for dependencies
-    fn analyze_resource(
-        &self,
-        resource: &FhirResource,
-    ) -> Vec<(String, DependencyType, Range<usize>)> {
+    fn analyze_resource(resource: &FhirResource) -> Vec<(String, DependencyType, Range<usize>)> {
         let mut dependencies = Vec::new();
 
         // Parent dependency
@@ -768,6 +827,50 @@ mod tests {
         assert!(cycles[0].contains(&"C".to_string()));
     }
 
+    #[test]
+    fn test_cycles_of_type() {
+        let mut graph = DependencyGraph::new();
+        graph.add_edge("A", "B", DependencyType::Parent, 0..10);
+        graph.add_edge("B", "A", DependencyType::ValueSetBinding, 10..20);
+        assert_eq!(graph.find_cycles().len(), 1);
+        assert!(graph.find_cycles_of_type(DependencyType::Parent).is_empty());
+
+        graph.add_edge("B", "A", DependencyType::Parent, 20..30);
+        assert_eq!(graph.find_cycles_of_type(DependencyType::Parent).len(), 1);
+        assert_eq!(
+            graph.get_dependencies_of_type("B", DependencyType::Parent),
+            vec!["A"]
+        );
+    }
+
+    #[test]
+    fn test_project_graph_links_files() {
+        let (cst, _, _) = crate::cst::parse_fsh("");
+        let model = |file: &str, id: &str, name: &str, parent: &str| {
+            let mut model = SemanticModel::from_cst(cst.clone(), String::new(), file.into());
+            model.add_resource(FhirResource {
+                resource_type: ResourceType::Profile,
+                id: id.to_string(),
+                name: Some(name.to_string()),
+                title: None,
+                description: None,
+                parent: Some(parent.to_string()),
+                elements: Vec::new(),
+                location: crate::Location::default(),
+                metadata: Default::default(),
+            });
+            model
+        };
+        let models = vec![
+            model("a.fsh", "profile-a", "ProfileA", "ProfileB"),
+            model("b.fsh", "profile-b", "ProfileB", "Patient"),
+        ];
+
+        let graph = DependencyAnalyzer::build_project_graph(&models);
+        assert_eq!(graph.get_dependencies("profile-a"), vec!["profile-b"]);
+        assert_eq!(graph.get_dependencies("profile-b"), vec!["Patient"]);
+    }
+
     #[test]
     fn test_processing_batches() {
         let mut graph = DependencyGraph::new();
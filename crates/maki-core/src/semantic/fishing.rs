@@ -9,6 +9,7 @@
 //! None rather than falling through to tier 3. This prevents external definitions
 //! from being used when a local FSH definition exists but hasn't been exported yet.
 
+use crate::canonical::cross_version::is_cross_version_extension_url;
 use crate::canonical::{CanonicalResult, DefinitionSession};
 use crate::export::fhir_types::{StructureDefinition, StructureDefinitionKind};
 use crate::semantic::{FhirResource, ResourceType};
@@ -101,13 +102,13 @@ impl FshTank {
 
         // For Profiles, Extensions, ValueSets, CodeSystems - index by canonical URL
         // The canonical URL is typically constructed from metadata
-        if let Some(url) = self.construct_canonical_url(&resource) {
+        if let Some(url) = self.canonical_url(&resource) {
             self.resources_by_url.insert(url, resource.clone());
         }
     }
 
     /// Construct canonical URL for a resource based on metadata
-    fn construct_canonical_url(&self, resource: &FhirResource) -> Option<String> {
+    pub fn canonical_url(&self, resource: &FhirResource) -> Option<String> {
         // This is a simplified version - in reality, this would use
         // the configured canonical URL base from the IG configuration
         match resource.resource_type {
@@ -198,7 +199,8 @@ impl FshTank {
 }
 
 impl ResourceType {
-    fn as_str(&self) -> &'static str {
+    /// FHIR resource type the FSH definition exports to
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             ResourceType::Profile => "StructureDefinition",
             ResourceType::Extension => "StructureDefinition",
@@ -234,9 +236,19 @@ impl Package {
         self.resources.insert(canonical_url, Arc::new(resource));
     }
 
-    /// Fish for a resource in the package
+    /// Fish for a resource in the package by canonical URL, id or name
     pub fn fish(&self, identifier: &str) -> Option<Arc<JsonValue>> {
-        self.resources.get(identifier).cloned()
+        if let Some(resource) = self.resources.get(identifier) {
+            return Some(resource.clone());
+        }
+        self.resources
+            .values()
+            .find(|resource| {
+                ["id", "name"]
+                    .iter()
+                    .any(|key| resource.get(*key).and_then(|v| v.as_str()) == Some(identifier))
+            })
+            .cloned()
     }
 
     /// Get all resources in the package (for iteration)
@@ -390,8 +402,12 @@ impl FishingContext {
         ];
 
         // Helper to skip unexpected Extension hits (SUSHI behavior for non-extension parents)
-        let is_unexpected_extension =
-            |sd: &StructureDefinition| sd.type_field == "Extension" && identifier != "Extension";
+        // Cross-version extension URLs always name an Extension, so those are expected
+        let is_unexpected_extension = |sd: &StructureDefinition| {
+            sd.type_field == "Extension"
+                && identifier != "Extension"
+                && !is_cross_version_extension_url(&identifier)
+        };
 
         // Check package first
         if let Some(json) = self.fish_in_package(identifier.as_str()).await?
@@ -551,6 +567,9 @@ impl FishingContext {
     ///
     /// Unlike `fish_structure_definition`, this method specifically looks for Extensions
     /// and does NOT skip them. Use this when resolving extension URLs.
+    ///
+    /// Cross-version extension URLs (`http://hl7.org/fhir/5.0/StructureDefinition/extension-*`)
+    /// resolve to the definition synthesized by the canonical session.
     pub async fn fish_extension(
         &self,
         identifier: &str,
@@ -577,6 +596,24 @@ impl FishingContext {
             return Ok(Some(sd));
         }
 
+        // Cross-version extensions are synthesized by the canonical session
+        if is_cross_version_extension_url(&identifier) {
+            return match self
+                .canonical_session
+                .resolve_structure_definition(&identifier)
+                .await
+            {
+                Ok(sd) => Ok(sd),
+                Err(e) => {
+                    debug!(
+                        "Cross-version extension {} not available: {}",
+                        identifier, e
+                    );
+                    Ok(None)
+                }
+            };
+        }
+
         // Check tank for Extension type
         if self
             .is_in_tank(identifier.as_str(), &[ResourceType::Extension])
@@ -1013,6 +1050,11 @@ mod tests {
         let found = package.fish("http://example.org/fhir/StructureDefinition/patient-profile");
         assert!(found.is_some());
         assert_eq!(found.unwrap()["id"], "patient-profile");
+
+        // Predefined resources are referenced by id or name as well
+        assert!(package.fish("patient-profile").is_some());
+        assert!(package.fish("PatientProfile").is_some());
+        assert!(package.fish("OtherProfile").is_none());
     }
 
     #[test]
@@ -140,6 +140,8 @@ fn format_cycle(cycle: &[String]) -> String {
 pub struct RuleSetExpander {
     /// Registered RuleSets by name
     rulesets: HashMap<String, Arc<RuleSet>>,
+    /// RuleSet replacements (inserted name -> expanded name)
+    variants: HashMap<String, String>,
 }
 
 impl RuleSetExpander {
@@ -147,6 +149,7 @@ impl RuleSetExpander {
     pub fn new() -> Self {
         Self {
             rulesets: HashMap::new(),
+            variants: HashMap::new(),
         }
     }
 
@@ -163,6 +166,19 @@ impl RuleSetExpander {
         self.rulesets.insert(name, Arc::new(ruleset));
     }
 
+    /// Expand `variant` wherever `name` is inserted
+    ///
+    /// Used to select per-release RuleSets. Returns `false` (and changes
+    /// nothing) when `variant` is not registered.
+    pub fn set_variant(&mut self, name: impl Into<String>, variant: impl Into<String>) -> bool {
+        let variant = variant.into();
+        if !self.rulesets.contains_key(&variant) {
+            return false;
+        }
+        self.variants.insert(name.into(), variant);
+        true
+    }
+
     /// Expand a RuleSet insert
     ///
     /// Performs parameter substitution and handles nested RuleSet calls.
@@ -198,9 +214,13 @@ impl RuleSetExpander {
         }
 
         // Get RuleSet
+        let name = self
+            .variants
+            .get(&insert.ruleset_name)
+            .unwrap_or(&insert.ruleset_name);
         let ruleset = self
             .rulesets
-            .get(&insert.ruleset_name)
+            .get(name)
             .ok_or_else(|| RuleSetError::RuleSetNotFound(insert.ruleset_name.clone()))?;
 
         // Validate parameter count
@@ -579,6 +599,33 @@ mod tests {
         assert_eq!(expanded[0], "* status = #final");
     }
 
+    #[test]
+    fn test_ruleset_variant() {
+        let mut expander = RuleSetExpander::new();
+        for (name, rule) in [
+            ("Contact", "* telecom 1..1"),
+            ("ContactR5", "* contact 1..1"),
+        ] {
+            expander.register_ruleset(RuleSet {
+                name: name.to_string(),
+                parameters: vec![],
+                rules: vec![rule.to_string()],
+                source_file: PathBuf::from("test.fsh"),
+                source_range: 0..10,
+            });
+        }
+
+        assert!(!expander.set_variant("Contact", "Missing"));
+        assert!(expander.set_variant("Contact", "ContactR5"));
+
+        let insert = RuleSetInsert {
+            ruleset_name: "Contact".to_string(),
+            arguments: vec![],
+            source_range: 10..20,
+        };
+        assert_eq!(expander.expand(&insert).unwrap(), vec!["* contact 1..1"]);
+    }
+
     #[test]
     fn test_complex_substitution() {
         let ruleset = RuleSet {
@@ -83,25 +83,31 @@ impl ConfigGenerator {
         let mut blocking_rules = HashMap::new();
         blocking_rules.insert(
             "validate-critical-requirements".to_string(),
-            RuleSeverity::Error,
+            RuleSeverity::Error.into(),
         );
 
         let mut correctness_rules = HashMap::new();
-        correctness_rules.insert("duplicate-definition".to_string(), RuleSeverity::Error);
-        correctness_rules.insert("invalid-reference".to_string(), RuleSeverity::Error);
-        correctness_rules.insert("missing-parent".to_string(), RuleSeverity::Error);
+        correctness_rules.insert(
+            "duplicate-definition".to_string(),
+            RuleSeverity::Error.into(),
+        );
+        correctness_rules.insert("invalid-reference".to_string(), RuleSeverity::Error.into());
+        correctness_rules.insert("missing-parent".to_string(), RuleSeverity::Error.into());
 
         let mut suspicious_rules = HashMap::new();
-        suspicious_rules.insert("unused-alias".to_string(), RuleSeverity::Warn);
-        suspicious_rules.insert("implicit-cardinality".to_string(), RuleSeverity::Warn);
+        suspicious_rules.insert("unused-alias".to_string(), RuleSeverity::Warn.into());
+        suspicious_rules.insert(
+            "implicit-cardinality".to_string(),
+            RuleSeverity::Warn.into(),
+        );
 
         let mut style_rules = HashMap::new();
-        style_rules.insert("naming-convention".to_string(), RuleSeverity::Warn);
-        style_rules.insert("prefer-title-case".to_string(), RuleSeverity::Info);
+        style_rules.insert("naming-convention".to_string(), RuleSeverity::Warn.into());
+        style_rules.insert("prefer-title-case".to_string(), RuleSeverity::Info.into());
 
         let mut documentation_rules = HashMap::new();
-        documentation_rules.insert("require-description".to_string(), RuleSeverity::Warn);
-        documentation_rules.insert("require-purpose".to_string(), RuleSeverity::Info);
+        documentation_rules.insert("require-description".to_string(), RuleSeverity::Warn.into());
+        documentation_rules.insert("require-purpose".to_string(), RuleSeverity::Info.into());
 
         let rules = RulesConfiguration {
             recommended: Some(true),
@@ -118,6 +124,7 @@ impl ConfigGenerator {
             enabled: Some(true),
             rules: Some(rules),
             rule_directories: Some(vec!["custom-rules/".to_string()]),
+            ignore_public_definitions: None,
         };
 
         // Create formatter configuration
@@ -204,6 +211,7 @@ impl ConfigGenerator {
             fsh_only: None,
             apply_extension_metadata_to_root: None,
             instance_options: None,
+            output_format: None,
             meta: None,
             implicit_rules: None,
             language: None,
@@ -224,6 +232,8 @@ impl ConfigGenerator {
             linter: Some(linter),
             formatter: Some(formatter),
             files: Some(files),
+            releases: None,
+            overrides: None,
         }
     }
 
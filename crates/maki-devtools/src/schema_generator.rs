@@ -5,7 +5,7 @@
 use anyhow::Result;
 use maki_core::config::UnifiedConfig;
 use schemars::schema_for;
-use serde_json::json;
+use serde_json::{Value, json};
 use std::fs;
 use std::path::Path;
 
@@ -25,6 +25,7 @@ impl SchemaGenerator {
 
         // Convert to JSON value for manipulation
         let mut schema_json = serde_json::to_value(schema)?;
+        Self::add_rule_options(&mut schema_json);
 
         // Add metadata
         schema_json["$id"] = json!("https://octofhir.github.io/maki/schema/v1.json");
@@ -48,6 +49,62 @@ impl SchemaGenerator {
         Ok(())
     }
 
+    /// Describe the options of configurable builtin rules
+    ///
+    /// Each rule with an options struct gets a property in its category of
+    /// `linter.rules` that accepts a severity or `{ severity, options }` with
+    /// the rule's options schema.
+    fn add_rule_options(schema_json: &mut Value) {
+        for (rule_id, options_schema) in maki_rules::options::options_schemas() {
+            let Some((category, name)) = rule_id.split_once('/') else {
+                continue;
+            };
+            let mut options = options_schema.to_value();
+            let definition = format!("{}Options", Self::pascal_case(name));
+
+            // Nested definitions of the options move to the root
+            if let Some(Value::Object(definitions)) = options
+                .as_object_mut()
+                .and_then(|options| options.remove("$defs"))
+            {
+                for (key, value) in definitions {
+                    schema_json["$defs"][key] = value;
+                }
+            }
+            if let Some(options) = options.as_object_mut() {
+                options.remove("$schema");
+            }
+            schema_json["$defs"][&definition] = options;
+
+            schema_json["$defs"]["RulesConfiguration"]["properties"][category]["properties"]
+                [name] = json!({
+                "anyOf": [
+                    { "$ref": "#/$defs/RuleSeverity" },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "severity": { "$ref": "#/$defs/RuleSeverity" },
+                            "options": { "$ref": format!("#/$defs/{definition}") }
+                        },
+                        "additionalProperties": false
+                    }
+                ]
+            });
+        }
+    }
+
+    fn pascal_case(name: &str) -> String {
+        name.split('-')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
     /// Validate that the schema can be generated without errors
     ///
     /// Useful for CI/CD to ensure schema generation doesn't break
@@ -85,6 +142,24 @@ mod tests {
         assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
     }
 
+    #[test]
+    fn test_rule_options_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("schema.json");
+        SchemaGenerator::generate(&output_path).unwrap();
+        let schema: Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+
+        let naming = &schema["$defs"]["RulesConfiguration"]["properties"]["style"]["properties"]["naming-convention"];
+        assert_eq!(
+            naming["anyOf"][1]["properties"]["options"]["$ref"],
+            "#/$defs/NamingConventionOptions"
+        );
+        assert!(schema["$defs"]["NamingConventionOptions"]["properties"]["names"].is_object());
+        assert!(schema["$defs"]["EntityPatterns"].is_object());
+        assert!(schema["$defs"]["MissingMetadataOptions"].is_object());
+    }
+
     #[test]
     fn test_schema_validation() {
         SchemaGenerator::validate().unwrap();
@@ -1,15 +1,20 @@
 //! Built-in rules for FSH linting
 
+use crate::suppression;
 use maki_core::{AutofixTemplate, FixSafety, Rule, RuleCategory, RuleMetadata, Severity};
 
 pub mod binding;
 pub mod cardinality;
 pub mod caret_path;
 pub mod duplicates;
+pub mod extension_context;
 pub mod metadata;
 pub mod naming;
 pub mod profile;
 pub mod required_fields;
+pub mod slicing;
+pub mod terminology;
+pub mod unused;
 
 /// Collection of built-in FSH linting rules
 pub struct BuiltinRules;
@@ -46,6 +51,7 @@ impl BuiltinRules {
             Self::missing_parent_profile_rule(),
             Self::invalid_status_rule(),
             Self::profile_assignment_present_rule(),
+            Self::unresolved_reference_rule(),
             Self::extension_context_missing_rule(),
             Self::instance_required_fields_rule(),
             Self::required_field_override_rule(),
@@ -54,6 +60,17 @@ impl BuiltinRules {
             Self::duplicate_rule_rule(),
             Self::duplicate_alias_rule(),
             Self::slice_name_collision_rule(),
+            Self::slicing_missing_discriminator_rule(),
+            Self::slicing_unresolved_discriminator_rule(),
+            Self::slice_unconstrained_discriminator_rule(),
+            Self::slicing_closed_conflict_rule(),
+            Self::slice_cardinality_exceeds_max_rule(),
+            Self::binding_code_not_in_valueset_rule(),
+            Self::code_not_in_codesystem_rule(),
+            Self::code_display_mismatch_rule(),
+            Self::extension_context_invalid_rule(),
+            Self::extension_outside_context_rule(),
+            Self::profile_circular_parent_rule(),
         ]
     }
 
@@ -64,6 +81,11 @@ impl BuiltinRules {
             Self::inconsistent_metadata_rule(),
             Self::binding_strength_inconsistent_rule(),
             Self::must_support_propagation_rule(),
+            Self::unused_ruleset_rule(),
+            Self::unused_alias_rule(),
+            Self::unused_invariant_rule(),
+            Self::unused_valueset_rule(),
+            Self::unused_codesystem_rule(),
         ]
     }
 
@@ -87,6 +109,15 @@ impl BuiltinRules {
         ]
     }
 
+    /// Get the rules reported for `maki-ignore` comments themselves
+    pub fn suppression_rules() -> Vec<Rule> {
+        vec![
+            Self::unused_suppression_rule(),
+            Self::unknown_suppression_rule_rule(),
+            Self::invalid_suppression_rule(),
+        ]
+    }
+
     /// Rule for detecting invalid FSH keywords
     fn invalid_keyword_rule() -> Rule {
         Rule {
@@ -752,6 +783,7 @@ impl BuiltinRules {
         rules.extend(Self::suspicious_rules());
         rules.extend(Self::style_rules());
         rules.extend(Self::documentation_rules());
+        rules.extend(Self::suppression_rules());
         rules
     }
 
@@ -957,7 +989,7 @@ impl BuiltinRules {
             metadata: RuleMetadata {
                 id: profile::PROFILE_ASSIGNMENT_PRESENT.to_string(),
                 name: "Profile Assignment Present".to_string(),
-                description: "Ensures that profiles have ^status and ^abstract assignments, and Parent declarations".to_string(),
+                description: "Ensures that profiles have ^status and ^abstract assignments and that Parent references resolve".to_string(),
                 severity: Severity::Warning,
                 category: RuleCategory::Correctness,
                 tags: vec![
@@ -975,6 +1007,36 @@ impl BuiltinRules {
         }
     }
 
+    /// Rule for detecting InstanceOf, `only` and extension references that don't resolve
+    fn unresolved_reference_rule() -> Rule {
+        Rule {
+            id: profile::UNRESOLVED_REFERENCE.to_string(),
+            severity: Severity::Error,
+            description: "Detects InstanceOf, only and extension references that don't resolve"
+                .to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: profile::UNRESOLVED_REFERENCE.to_string(),
+                name: "Unresolved Reference".to_string(),
+                description: "InstanceOf, only and extension references must name FSH definitions or resources of the loaded packages".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "profile".to_string(),
+                    "packages".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/unresolved-reference"
+                        .to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
     /// Rule for detecting missing extension context
     fn extension_context_missing_rule() -> Rule {
         Rule {
@@ -1035,6 +1097,293 @@ impl BuiltinRules {
         }
     }
 
+    /// Rule for contains rules without a slicing discriminator
+    fn slicing_missing_discriminator_rule() -> Rule {
+        Rule {
+            id: slicing::SLICING_MISSING_DISCRIMINATOR.to_string(),
+            severity: Severity::Warning,
+            description: "Detects contains rules on elements without a slicing discriminator".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Insert ^slicing rules before the contains rule".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Unsafe,
+            }),
+            metadata: RuleMetadata {
+                id: slicing::SLICING_MISSING_DISCRIMINATOR.to_string(),
+                name: "Slicing Missing Discriminator".to_string(),
+                description: "Requires ^slicing.discriminator to be declared before the first contains rule unless the parent already slices the element".to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "slicing".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/slicing-missing-discriminator".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for discriminator paths that don't exist on the sliced element
+    fn slicing_unresolved_discriminator_rule() -> Rule {
+        Rule {
+            id: slicing::SLICING_UNRESOLVED_DISCRIMINATOR.to_string(),
+            severity: Severity::Warning,
+            description: "Detects discriminator paths that don't resolve on the sliced element".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: slicing::SLICING_UNRESOLVED_DISCRIMINATOR.to_string(),
+                name: "Slicing Unresolved Discriminator".to_string(),
+                description: "Resolves discriminator paths against the sliced element and the types of its slices using loaded packages".to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "slicing".to_string(),
+                    "fhir-definitions".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/slicing-unresolved-discriminator".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for slices that don't constrain their discriminator path
+    fn slice_unconstrained_discriminator_rule() -> Rule {
+        Rule {
+            id: slicing::SLICE_UNCONSTRAINED_DISCRIMINATOR.to_string(),
+            severity: Severity::Warning,
+            description: "Detects slices that don't fix or constrain their discriminator path".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: slicing::SLICE_UNCONSTRAINED_DISCRIMINATOR.to_string(),
+                name: "Slice Unconstrained Discriminator".to_string(),
+                description: "Requires each new slice to assign, bind, type or bound the element its discriminator looks at".to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "slicing".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/slice-unconstrained-discriminator".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for slices added to slicing the parent closed
+    fn slicing_closed_conflict_rule() -> Rule {
+        Rule {
+            id: slicing::SLICING_CLOSED_CONFLICT.to_string(),
+            severity: Severity::Error,
+            description: "Detects slices added to an element whose parent slicing is closed"
+                .to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: slicing::SLICING_CLOSED_CONFLICT.to_string(),
+                name: "Slicing Closed Conflict".to_string(),
+                description:
+                    "Reports new slices on elements the parent sliced with ^slicing.rules = #closed"
+                        .to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "slicing".to_string(),
+                    "inheritance".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/slicing-closed-conflict"
+                        .to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for slice minimums exceeding the sliced element's max
+    fn slice_cardinality_exceeds_max_rule() -> Rule {
+        Rule {
+            id: slicing::SLICE_CARDINALITY_EXCEEDS_MAX.to_string(),
+            severity: Severity::Error,
+            description: "Detects slices whose minimum cardinalities add up to more than the element allows".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: slicing::SLICE_CARDINALITY_EXCEEDS_MAX.to_string(),
+                name: "Slice Cardinality Exceeds Max".to_string(),
+                description: "Sums the minimum cardinality of inherited and new slices and compares it with the sliced element's max".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "slicing".to_string(),
+                    "cardinality".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/slice-cardinality-exceeds-max".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for assigned codes outside the bound ValueSet
+    fn binding_code_not_in_valueset_rule() -> Rule {
+        Rule {
+            id: terminology::BINDING_CODE_NOT_IN_VALUESET.to_string(),
+            severity: Severity::Error,
+            description: "Detects codes assigned to bound elements that are not in the bound ValueSet".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: terminology::BINDING_CODE_NOT_IN_VALUESET.to_string(),
+                name: "Binding Code Not In ValueSet".to_string(),
+                description: "Expands bound ValueSets from local FSH or loaded packages and reports assigned codes outside them, with severity following the binding strength".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "binding".to_string(),
+                    "terminology".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/binding-code-not-in-valueset".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for codes missing from their CodeSystem
+    fn code_not_in_codesystem_rule() -> Rule {
+        Rule {
+            id: terminology::CODE_NOT_IN_CODESYSTEM.to_string(),
+            severity: Severity::Error,
+            description: "Detects codes that don't exist in their CodeSystem".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: terminology::CODE_NOT_IN_CODESYSTEM.to_string(),
+                name: "Code Not In CodeSystem".to_string(),
+                description: "Checks system#code values against complete CodeSystems from local FSH or loaded packages".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "terminology".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/code-not-in-codesystem".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for code displays that differ from the CodeSystem
+    fn code_display_mismatch_rule() -> Rule {
+        Rule {
+            id: terminology::CODE_DISPLAY_MISMATCH.to_string(),
+            severity: Severity::Warning,
+            description: "Detects code displays that differ from the CodeSystem display".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Use the CodeSystem display".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Safe,
+            }),
+            metadata: RuleMetadata {
+                id: terminology::CODE_DISPLAY_MISMATCH.to_string(),
+                name: "Code Display Mismatch".to_string(),
+                description: "Compares the display of system#code \"display\" values with the concept display in the CodeSystem".to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "terminology".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/code-display-mismatch".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for extension contexts that don't resolve
+    fn extension_context_invalid_rule() -> Rule {
+        Rule {
+            id: extension_context::EXTENSION_CONTEXT_INVALID.to_string(),
+            severity: Severity::Error,
+            description: "Detects extension contexts that don't resolve to an element, extension or valid FHIRPath".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: extension_context::EXTENSION_CONTEXT_INVALID.to_string(),
+                name: "Extension Context Invalid".to_string(),
+                description: "Checks element contexts against StructureDefinitions, fhirpath contexts for FHIRPath syntax and extension contexts for existing extensions".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "extension".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/extension-context-invalid".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for extensions added outside their declared contexts
+    fn extension_outside_context_rule() -> Rule {
+        Rule {
+            id: extension_context::EXTENSION_OUTSIDE_CONTEXT.to_string(),
+            severity: Severity::Error,
+            description: "Detects profiles that add an extension to an element outside the extension's contexts".to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: extension_context::EXTENSION_OUTSIDE_CONTEXT.to_string(),
+                name: "Extension Outside Context".to_string(),
+                description: "Compares the element an extension is added to with the element contexts declared by the extension".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "extension".to_string(),
+                    "profile".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/extension-outside-context".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
     /// Rule for checking MustSupport propagation
     fn must_support_propagation_rule() -> Rule {
         Rule {
@@ -1067,6 +1416,204 @@ impl BuiltinRules {
         }
     }
 
+    /// Rule for detecting parent chains that loop (project rule)
+    fn profile_circular_parent_rule() -> Rule {
+        Rule {
+            id: profile::PROFILE_CIRCULAR_PARENT.to_string(),
+            severity: Severity::Error,
+            description: "Detects profiles whose parent chain leads back to themselves"
+                .to_string(),
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: profile::PROFILE_CIRCULAR_PARENT.to_string(),
+                name: "Profile Circular Parent".to_string(),
+                description: "Profiles, extensions and logical models cannot inherit from themselves, directly or through other files".to_string(),
+                severity: Severity::Error,
+                category: RuleCategory::Correctness,
+                tags: vec![
+                    "correctness".to_string(),
+                    "profile".to_string(),
+                    "project".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/correctness/profile-circular-parent"
+                        .to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for detecting RuleSets that are never inserted (project rule)
+    fn unused_ruleset_rule() -> Rule {
+        Rule {
+            id: unused::UNUSED_RULESET.to_string(),
+            severity: Severity::Warning,
+            description: "Detects RuleSets that no insert rule uses".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Remove the unused definition".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Unsafe,
+            }),
+            metadata: RuleMetadata {
+                id: unused::UNUSED_RULESET.to_string(),
+                name: "Unused RuleSet".to_string(),
+                description: "RuleSets that nothing inserts, in any project file, can be deleted"
+                    .to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Suspicious,
+                tags: vec![
+                    "suspicious".to_string(),
+                    "unused".to_string(),
+                    "ruleset".to_string(),
+                    "project".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/suspicious/unused-ruleset".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for detecting aliases that are never referenced (project rule)
+    fn unused_alias_rule() -> Rule {
+        Rule {
+            id: unused::UNUSED_ALIAS.to_string(),
+            severity: Severity::Warning,
+            description: "Detects aliases that nothing references".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Remove the unused definition".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Safe,
+            }),
+            metadata: RuleMetadata {
+                id: unused::UNUSED_ALIAS.to_string(),
+                name: "Unused Alias".to_string(),
+                description: "Aliases that no project file references can be deleted".to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Suspicious,
+                tags: vec![
+                    "suspicious".to_string(),
+                    "unused".to_string(),
+                    "alias".to_string(),
+                    "project".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/suspicious/unused-alias".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for detecting Invariants that no obeys rule uses (project rule)
+    fn unused_invariant_rule() -> Rule {
+        Rule {
+            id: unused::UNUSED_INVARIANT.to_string(),
+            severity: Severity::Warning,
+            description: "Detects Invariants that no obeys rule uses".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Remove the unused definition".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Unsafe,
+            }),
+            metadata: RuleMetadata {
+                id: unused::UNUSED_INVARIANT.to_string(),
+                name: "Unused Invariant".to_string(),
+                description:
+                    "Invariants that no profile or extension obeys have no effect on validation"
+                        .to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Suspicious,
+                tags: vec![
+                    "suspicious".to_string(),
+                    "unused".to_string(),
+                    "invariant".to_string(),
+                    "project".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/suspicious/unused-invariant".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for detecting ValueSets nothing binds to or includes (project rule)
+    fn unused_valueset_rule() -> Rule {
+        Rule {
+            id: unused::UNUSED_VALUESET.to_string(),
+            severity: Severity::Info,
+            description: "Detects ValueSets that nothing binds to or includes".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Remove the unused definition".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Unsafe,
+            }),
+            metadata: RuleMetadata {
+                id: unused::UNUSED_VALUESET.to_string(),
+                name: "Unused ValueSet".to_string(),
+                description: "ValueSets that no binding or other ValueSet uses, and that the build configuration doesn't list".to_string(),
+                severity: Severity::Info,
+                category: RuleCategory::Suspicious,
+                tags: vec![
+                    "suspicious".to_string(),
+                    "unused".to_string(),
+                    "terminology".to_string(),
+                    "project".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/suspicious/unused-valueset".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for detecting CodeSystems no ValueSet or code uses (project rule)
+    fn unused_codesystem_rule() -> Rule {
+        Rule {
+            id: unused::UNUSED_CODESYSTEM.to_string(),
+            severity: Severity::Info,
+            description: "Detects CodeSystems that no ValueSet or code uses".to_string(),
+            gritql_pattern: String::new(),
+            autofix: Some(AutofixTemplate {
+                description: "Remove the unused definition".to_string(),
+                replacement_template: String::new(),
+                safety: FixSafety::Unsafe,
+            }),
+            metadata: RuleMetadata {
+                id: unused::UNUSED_CODESYSTEM.to_string(),
+                name: "Unused CodeSystem".to_string(),
+                description: "CodeSystems that no ValueSet includes and no code references, and that the build configuration doesn't list".to_string(),
+                severity: Severity::Info,
+                category: RuleCategory::Suspicious,
+                tags: vec![
+                    "suspicious".to_string(),
+                    "unused".to_string(),
+                    "terminology".to_string(),
+                    "project".to_string(),
+                ],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/rules/suspicious/unused-codesystem".to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
     /// Rule for detecting duplicate definitions
     /// This is a BLOCKING rule - detects duplicate names, IDs, and URLs
     fn duplicate_definition_rule() -> Rule {
@@ -1370,4 +1917,90 @@ impl BuiltinRules {
             is_ast_rule: true,
         }
     }
+
+    /// Rule for suppression comments that silence nothing
+    fn unused_suppression_rule() -> Rule {
+        Rule {
+            id: suppression::UNUSED_SUPPRESSION.to_string(),
+            severity: Severity::Warning,
+            description: "Detects suppression comments that don't suppress any diagnostic"
+                .to_string(),
+            // Empty GritQL pattern - reported by the suppression pass
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: suppression::UNUSED_SUPPRESSION.to_string(),
+                name: "Unused Suppression".to_string(),
+                description:
+                    "maki-ignore comments that no longer match a diagnostic can be removed"
+                        .to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Custom("suppressions".to_string()),
+                tags: vec!["suppressions".to_string()],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/configuration/rules#disabling-rules-inline"
+                        .to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for suppression comments naming unknown rules
+    fn unknown_suppression_rule_rule() -> Rule {
+        Rule {
+            id: suppression::UNKNOWN_SUPPRESSION_RULE.to_string(),
+            severity: Severity::Warning,
+            description: "Detects suppression comments naming a rule that doesn't exist"
+                .to_string(),
+            // Empty GritQL pattern - reported by the suppression pass
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: suppression::UNKNOWN_SUPPRESSION_RULE.to_string(),
+                name: "Unknown Suppression Rule".to_string(),
+                description: "maki-ignore comments must name registered rules or rule categories"
+                    .to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Custom("suppressions".to_string()),
+                tags: vec!["suppressions".to_string()],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/configuration/rules#disabling-rules-inline"
+                        .to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
+
+    /// Rule for malformed suppression comments
+    fn invalid_suppression_rule() -> Rule {
+        Rule {
+            id: suppression::INVALID_SUPPRESSION.to_string(),
+            severity: Severity::Warning,
+            description: "Detects unmatched maki-ignore-start and maki-ignore-end comments"
+                .to_string(),
+            // Empty GritQL pattern - reported by the suppression pass
+            gritql_pattern: String::new(),
+            autofix: None,
+            metadata: RuleMetadata {
+                id: suppression::INVALID_SUPPRESSION.to_string(),
+                name: "Invalid Suppression".to_string(),
+                description:
+                    "Every maki-ignore-start needs a matching maki-ignore-end, and the reverse"
+                        .to_string(),
+                severity: Severity::Warning,
+                category: RuleCategory::Custom("suppressions".to_string()),
+                tags: vec!["suppressions".to_string()],
+                version: Some("1.0.0".to_string()),
+                docs_url: Some(
+                    "https://octofhir.github.io/maki/configuration/rules#disabling-rules-inline"
+                        .to_string(),
+                ),
+            },
+            is_ast_rule: true,
+        }
+    }
 }
@@ -3,6 +3,8 @@
 //! Validates that bindings to value sets have proper strength specifications,
 //! don't weaken parent bindings, and are used consistently across profiles.
 
+use super::terminology::ProjectTerminology;
+use maki_core::canonical::fishable::{FhirType, Fishable};
 use maki_core::cst::ast::{AstNode, Document, Extension, Profile, ValueSetRule};
 use maki_core::{CodeSuggestion, Diagnostic, SemanticModel, Severity};
 use std::collections::HashMap;
@@ -482,108 +484,101 @@ fn extract_element_name(path: &str) -> String {
         .to_string()
 }
 
-/// Check for bindings that reference non-existent ValueSets
+/// Check for bindings that reference ValueSets that can't be found
 ///
 /// # Arguments
 /// * `model` - The semantic model for the current file
 /// * `global_valuesets` - Optional set of all ValueSet names defined across all project files
+/// * `terminology` - Optional aliases, ValueSets and CodeSystems of all project files
+/// * `lazy_session` - Optional session for ValueSets from loaded packages
 ///
-/// When `global_valuesets` is provided, the rule checks if a referenced ValueSet
-/// exists in ANY file in the project, not just the current document.
-pub fn check_binding_without_valueset(
+/// A ValueSet is found when it is defined in any project file, by name, id or
+/// url, or in the loaded packages. References by url are only reported when
+/// packages are loaded, since they usually point outside the project.
+pub async fn check_binding_without_valueset(
     model: &SemanticModel,
     global_valuesets: Option<&std::collections::HashSet<String>>,
+    terminology: Option<&ProjectTerminology>,
+    lazy_session: Option<&std::sync::Arc<maki_core::LazySession>>,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
     let Some(document) = Document::cast(model.cst.clone()) else {
         return diagnostics;
     };
+    let own;
+    let terminology = match terminology {
+        Some(terminology) => terminology,
+        None => {
+            own = ProjectTerminology::from_models(std::slice::from_ref(model));
+            &own
+        }
+    };
 
-    // Collect all defined ValueSets in the current document
-    let local_valuesets: std::collections::HashSet<_> =
-        document.value_sets().filter_map(|vs| vs.name()).collect();
-
-    // Check profiles for undefined ValueSet references
-    for profile in document.profiles() {
-        for rule in profile.rules() {
-            if let maki_core::cst::ast::Rule::ValueSet(vs_rule) = rule
-                && let Some(vs_name) = vs_rule.value_set()
-            {
-                // Check if ValueSet is defined locally
-                let is_defined_locally = local_valuesets.contains(&vs_name);
-
-                // Check if ValueSet is defined in any project file (if global registry provided)
-                let is_defined_globally = global_valuesets
-                    .map(|global| global.contains(&vs_name))
-                    .unwrap_or(false);
-
-                // Only warn if not found in local OR global scope
-                if !is_defined_locally && !is_defined_globally {
-                    // ValueSet might be from FHIR spec or external package
-                    // Skip URLs since those reference external ValueSets
-                    if !vs_name.starts_with("http://") && !vs_name.starts_with("https://") {
-                        let location = model.source_map.node_to_diagnostic_location(
-                            vs_rule.syntax(),
-                            &model.source,
-                            &model.source_file,
-                        );
-
-                        diagnostics.push(
-                            Diagnostic::new(
-                                BINDING_WITHOUT_VALUESET,
-                                Severity::Warning,
-                                format!(
-                                    "Binding references ValueSet '{}' which is not defined in this project",
-                                    vs_name
-                                ),
-                                location,
-                            )
-                            .with_code("undefined-valueset".to_string()),
-                        );
-                    }
-                }
+    // Collect references first to avoid holding CST iterators across await points
+    let rules = document
+        .profiles()
+        .flat_map(|profile| profile.rules().collect::<Vec<_>>())
+        .chain(
+            document
+                .extensions()
+                .flat_map(|extension| extension.rules().collect::<Vec<_>>()),
+        );
+    let mut unresolved = Vec::new();
+    for rule in rules {
+        if let maki_core::cst::ast::Rule::ValueSet(vs_rule) = rule
+            && let Some(vs_name) = vs_rule.value_set()
+        {
+            let is_defined = terminology.has_value_set(&vs_name)
+                || global_valuesets.is_some_and(|global| global.contains(&vs_name));
+            if !is_defined {
+                let location = model.source_map.node_to_diagnostic_location(
+                    vs_rule.syntax(),
+                    &model.source,
+                    &model.source_file,
+                );
+                unresolved.push((vs_name, location));
             }
         }
     }
+    if unresolved.is_empty() {
+        return diagnostics;
+    }
 
-    // Also check extensions for undefined ValueSet references
-    for extension in document.extensions() {
-        for rule in extension.rules() {
-            if let maki_core::cst::ast::Rule::ValueSet(vs_rule) = rule
-                && let Some(vs_name) = vs_rule.value_set()
-            {
-                let is_defined_locally = local_valuesets.contains(&vs_name);
-                let is_defined_globally = global_valuesets
-                    .map(|global| global.contains(&vs_name))
-                    .unwrap_or(false);
-
-                if !is_defined_locally
-                    && !is_defined_globally
-                    && !vs_name.starts_with("http://")
-                    && !vs_name.starts_with("https://")
-                {
-                    let location = model.source_map.node_to_diagnostic_location(
-                        vs_rule.syntax(),
-                        &model.source,
-                        &model.source_file,
-                    );
-
-                    diagnostics.push(
-                        Diagnostic::new(
-                            BINDING_WITHOUT_VALUESET,
-                            Severity::Warning,
-                            format!(
-                                "Binding references ValueSet '{}' which is not defined in this project",
-                                vs_name
-                            ),
-                            location,
-                        )
-                        .with_code("undefined-valueset".to_string()),
-                    );
+    let session = match lazy_session {
+        Some(lazy) => lazy.get().await.ok(),
+        None => None,
+    };
+    for (vs_name, location) in unresolved {
+        let target = terminology.resolve_alias(&vs_name);
+        let is_url = target.starts_with("http://") || target.starts_with("https://");
+        let message = match session {
+            Some(session) => {
+                if let Ok(Some(_)) = session.fish(target, &[FhirType::ValueSet]).await {
+                    continue;
                 }
+                format!(
+                    "Binding references ValueSet '{}' which is not defined in this project or the loaded packages",
+                    vs_name
+                )
             }
-        }
+            // ValueSet might be from FHIR spec or external package
+            None if is_url => continue,
+            None => format!(
+                "Binding references ValueSet '{}' which is not defined in this project",
+                vs_name
+            ),
+        };
+
+        diagnostics.push(
+            Diagnostic::new(
+                BINDING_WITHOUT_VALUESET,
+                Severity::Warning,
+                message,
+                location,
+            )
+            .with_code("undefined-valueset".to_string()),
+        );
     }
 
     diagnostics
@@ -1,6 +1,8 @@
 //! Duplicate definition detection rules
 //!
 //! Detects duplicate resource definitions, aliases, and conflicting rules in FSH files.
+//! Definitions and aliases are checked across all project files as project rules
+//! (see [`crate::project`]).
 //!
 //! This module implements comprehensive duplicate detection:
 //! - Duplicate entity names (Profiles, Extensions, ValueSets, CodeSystems)
@@ -8,6 +10,7 @@
 //! - Duplicate/conflicting rules within profiles
 //! - Duplicate aliases with different values
 
+use crate::project::ProjectContext;
 use maki_core::cst::FshSyntaxNode;
 use maki_core::cst::ast::{AstNode, Document, Rule};
 use maki_core::{Diagnostic, SemanticModel, Severity};
@@ -28,107 +31,71 @@ pub const DUPLICATE_RULE: &str = "correctness/duplicate-rule";
 /// Rule ID for duplicate aliases
 pub const DUPLICATE_ALIAS: &str = "correctness/duplicate-alias";
 
-/// Check for duplicate resource definitions (main blocking rule)
-/// This checks both entity names and IDs in a single pass
+/// Where an entity name, id or alias is defined
+type Occurrence<'a, T> = (T, &'a SemanticModel, FshSyntaxNode);
+
+/// Check for duplicate resource definitions within one file (main blocking rule)
+///
+/// This checks both entity names and IDs in a single pass. Used when no
+/// project pass ran; see [`check_project_duplicates`].
 pub fn check_duplicates(model: &SemanticModel) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
+    find_duplicates(std::slice::from_ref(model))
+}
 
-    let Some(document) = Document::cast(model.cst.clone()) else {
-        return diagnostics;
-    };
+/// Check for duplicate entity names and IDs across all project files
+pub fn check_project_duplicates(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    find_duplicates(project.models)
+}
+
+fn find_duplicates(models: &[SemanticModel]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
 
     // Track resource IDs and names with all their occurrences
     // In FHIR, all entity names share the same namespace, so we track them together
-    let mut entity_names: HashMap<String, Vec<(String, FshSyntaxNode)>> = HashMap::new(); // name -> [(resource_type, location)]
-    let mut ids: HashMap<String, Vec<(String, FshSyntaxNode)>> = HashMap::new(); // id -> [(resource_type, location)]
-
-    // Collect all profiles
-    for profile in document.profiles() {
-        if let Some(name) = profile.name() {
-            entity_names
-                .entry(name.clone())
-                .or_default()
-                .push(("Profile".to_string(), profile.syntax().clone()));
-        }
-
-        if let Some(id_clause) = profile.id()
-            && let Some(id) = id_clause.value()
-        {
-            ids.entry(id.clone())
-                .or_default()
-                .push(("Profile".to_string(), profile.syntax().clone()));
-        }
-    }
-
-    // Collect all extensions
-    for extension in document.extensions() {
-        if let Some(name) = extension.name() {
-            entity_names
-                .entry(name.clone())
-                .or_default()
-                .push(("Extension".to_string(), extension.syntax().clone()));
-        }
-
-        if let Some(id_clause) = extension.id()
-            && let Some(id) = id_clause.value()
-        {
-            ids.entry(id.clone())
-                .or_default()
-                .push(("Extension".to_string(), extension.syntax().clone()));
-        }
-    }
-
-    // Collect all value sets
-    for value_set in document.value_sets() {
-        if let Some(name) = value_set.name() {
-            entity_names
-                .entry(name.clone())
-                .or_default()
-                .push(("ValueSet".to_string(), value_set.syntax().clone()));
-        }
+    let mut entity_names: HashMap<String, Vec<Occurrence<String>>> = HashMap::new(); // name -> [(resource_type, model, node)]
+    let mut ids: HashMap<String, Vec<Occurrence<String>>> = HashMap::new(); // id -> [(resource_type, model, node)]
+    let mut record =
+        |res_type: &str, model, name: Option<String>, id: Option<String>, node: &FshSyntaxNode| {
+            if let Some(name) = name {
+                entity_names.entry(name).or_default().push((
+                    res_type.to_string(),
+                    model,
+                    node.clone(),
+                ));
+            }
+            if let Some(id) = id {
+                ids.entry(id)
+                    .or_default()
+                    .push((res_type.to_string(), model, node.clone()));
+            }
+        };
 
-        if let Some(id_clause) = value_set.id()
-            && let Some(id) = id_clause.value()
-        {
-            ids.entry(id.clone())
-                .or_default()
-                .push(("ValueSet".to_string(), value_set.syntax().clone()));
+    for (model, document) in documents(models) {
+        for profile in document.profiles() {
+            let id = profile.id().and_then(|c| c.value());
+            record("Profile", model, profile.name(), id, profile.syntax());
         }
-    }
-
-    // Collect all code systems
-    for code_system in document.code_systems() {
-        if let Some(name) = code_system.name() {
-            entity_names
-                .entry(name.clone())
-                .or_default()
-                .push(("CodeSystem".to_string(), code_system.syntax().clone()));
+        for extension in document.extensions() {
+            let id = extension.id().and_then(|c| c.value());
+            record("Extension", model, extension.name(), id, extension.syntax());
         }
-
-        if let Some(id_clause) = code_system.id()
-            && let Some(id) = id_clause.value()
-        {
-            ids.entry(id.clone())
-                .or_default()
-                .push(("CodeSystem".to_string(), code_system.syntax().clone()));
+        for value_set in document.value_sets() {
+            let id = value_set.id().and_then(|c| c.value());
+            record("ValueSet", model, value_set.name(), id, value_set.syntax());
         }
-    }
-
-    // Collect all instances
-    for instance in document.instances() {
-        if let Some(name) = instance.name() {
-            entity_names
-                .entry(name.clone())
-                .or_default()
-                .push(("Instance".to_string(), instance.syntax().clone()));
+        for code_system in document.code_systems() {
+            let id = code_system.id().and_then(|c| c.value());
+            record(
+                "CodeSystem",
+                model,
+                code_system.name(),
+                id,
+                code_system.syntax(),
+            );
         }
-
-        if let Some(id_clause) = instance.id()
-            && let Some(id) = id_clause.value()
-        {
-            ids.entry(id.clone())
-                .or_default()
-                .push(("Instance".to_string(), instance.syntax().clone()));
+        for instance in document.instances() {
+            let id = instance.id().and_then(|c| c.value());
+            record("Instance", model, instance.name(), id, instance.syntax());
         }
     }
 
@@ -136,7 +103,6 @@ pub fn check_duplicates(model: &SemanticModel) -> Vec<Diagnostic> {
     for (name, occurrences) in entity_names {
         if occurrences.len() > 1 {
             diagnostics.extend(create_unified_duplicate_name_diagnostics(
-                model,
                 &name,
                 &occurrences,
             ));
@@ -146,7 +112,7 @@ pub fn check_duplicates(model: &SemanticModel) -> Vec<Diagnostic> {
     // Report duplicate IDs
     for (id, occurrences) in ids {
         if occurrences.len() > 1 {
-            diagnostics.extend(create_duplicate_id_diagnostics(model, &id, &occurrences));
+            diagnostics.extend(create_duplicate_id_diagnostics(&id, &occurrences));
         }
     }
 
@@ -155,13 +121,12 @@ pub fn check_duplicates(model: &SemanticModel) -> Vec<Diagnostic> {
 
 /// Create diagnostics for duplicate entity names (unified across all types)
 fn create_unified_duplicate_name_diagnostics(
-    model: &SemanticModel,
     name: &str,
-    occurrences: &[(String, FshSyntaxNode)],
+    occurrences: &[Occurrence<String>],
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
-    for (i, (res_type, node)) in occurrences.iter().enumerate() {
+    for (i, (res_type, model, node)) in occurrences.iter().enumerate() {
         let location =
             model
                 .source_map
@@ -172,7 +137,7 @@ fn create_unified_duplicate_name_diagnostics(
                 "Duplicate entity name '{}' (used by {} {} entities)",
                 name,
                 occurrences.len(),
-                if occurrences.iter().all(|(t, _)| t == res_type) {
+                if occurrences.iter().all(|(t, _, _)| t == res_type) {
                     res_type.as_str()
                 } else {
                     "different"
@@ -189,8 +154,13 @@ fn create_unified_duplicate_name_diagnostics(
         };
 
         diagnostics.push(
-            Diagnostic::new(DUPLICATE_DEFINITION, Severity::Error, message, location)
-                .with_code(format!("duplicate-{}-name", res_type.to_lowercase())),
+            Diagnostic::new(
+                DUPLICATE_DEFINITION,
+                Severity::Error,
+                message + &other_files_note(i, occurrences),
+                location,
+            )
+            .with_code(format!("duplicate-{}-name", res_type.to_lowercase())),
         );
     }
 
@@ -199,13 +169,12 @@ fn create_unified_duplicate_name_diagnostics(
 
 /// Create diagnostics for duplicate entity IDs
 fn create_duplicate_id_diagnostics(
-    model: &SemanticModel,
     id: &str,
-    occurrences: &[(String, FshSyntaxNode)],
+    occurrences: &[Occurrence<String>],
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
-    for (i, (res_type, node)) in occurrences.iter().enumerate() {
+    for (i, (res_type, model, node)) in occurrences.iter().enumerate() {
         let location =
             model
                 .source_map
@@ -216,7 +185,7 @@ fn create_duplicate_id_diagnostics(
                 "Duplicate resource ID '{}' (used by {} {} entities)",
                 id,
                 occurrences.len(),
-                if occurrences.iter().all(|(t, _)| t == res_type) {
+                if occurrences.iter().all(|(t, _, _)| t == res_type) {
                     res_type.as_str()
                 } else {
                     "different"
@@ -233,14 +202,41 @@ fn create_duplicate_id_diagnostics(
         };
 
         diagnostics.push(
-            Diagnostic::new(DUPLICATE_DEFINITION, Severity::Error, message, location)
-                .with_code("duplicate-resource-id".to_string()),
+            Diagnostic::new(
+                DUPLICATE_DEFINITION,
+                Severity::Error,
+                message + &other_files_note(i, occurrences),
+                location,
+            )
+            .with_code("duplicate-resource-id".to_string()),
         );
     }
 
     diagnostics
 }
 
+/// Note listing the other files of a duplicate, empty when all are in one file
+fn other_files_note<T>(index: usize, occurrences: &[Occurrence<T>]) -> String {
+    let own_file = &occurrences[index].1.source_file;
+    let mut others: Vec<String> = occurrences
+        .iter()
+        .filter(|(_, model, _)| &model.source_file != own_file)
+        .map(|(_, model, node)| {
+            let location = model.source_map.node_to_diagnostic_location(
+                node,
+                &model.source,
+                &model.source_file,
+            );
+            format!("{}:{}", model.source_file.display(), location.line)
+        })
+        .collect();
+    if others.is_empty() {
+        return String::new();
+    }
+    others.dedup();
+    format!("\n  Note: also defined at {}", others.join(", "))
+}
+
 /// Check for conflicting rules within profiles and extensions
 /// This detects when the same element path has multiple conflicting rules
 pub fn check_duplicate_rules(model: &SemanticModel) -> Vec<Diagnostic> {
@@ -474,24 +470,35 @@ fn check_rule_conflicts(
     }
 }
 
-/// Check for duplicate alias definitions
+/// Check for duplicate alias definitions within one file
 pub fn check_duplicate_aliases(model: &SemanticModel) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
+    find_duplicate_aliases(std::slice::from_ref(model))
+}
 
-    let Some(document) = Document::cast(model.cst.clone()) else {
-        return diagnostics;
-    };
+/// Check for duplicate alias definitions across all project files
+///
+/// Aliases are global to a project, so the same alias defined in two files
+/// is a duplicate too.
+pub fn check_project_duplicate_aliases(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    find_duplicate_aliases(project.models)
+}
 
-    // Collect all aliases with their names and values
-    let mut aliases_by_name: HashMap<String, Vec<(Option<String>, FshSyntaxNode)>> = HashMap::new();
+fn find_duplicate_aliases(models: &[SemanticModel]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
 
-    for alias in document.aliases() {
-        if let Some(name) = alias.name() {
-            let value = alias.value();
-            aliases_by_name
-                .entry(name)
-                .or_default()
-                .push((value, alias.syntax().clone()));
+    // Collect all aliases with their names and values
+    let mut aliases_by_name: HashMap<String, Vec<Occurrence<Option<String>>>> = HashMap::new();
+
+    for (model, document) in documents(models) {
+        for alias in document.aliases() {
+            if let Some(name) = alias.name() {
+                let value = alias.value();
+                aliases_by_name.entry(name).or_default().push((
+                    value,
+                    model,
+                    alias.syntax().clone(),
+                ));
+            }
         }
     }
 
@@ -499,13 +506,14 @@ pub fn check_duplicate_aliases(model: &SemanticModel) -> Vec<Diagnostic> {
     for (name, occurrences) in aliases_by_name {
         if occurrences.len() > 1 {
             // Check if all values are the same
-            let values: Vec<Option<String>> = occurrences.iter().map(|(v, _)| v.clone()).collect();
+            let values: Vec<Option<String>> =
+                occurrences.iter().map(|(v, _, _)| v.clone()).collect();
 
             let all_same_value = values.windows(2).all(|w| w[0] == w[1]);
 
             if !all_same_value {
                 // Different values - this is an error
-                for (i, (value, node)) in occurrences.iter().enumerate() {
+                for (i, (value, model, node)) in occurrences.iter().enumerate() {
                     let location = model.source_map.node_to_diagnostic_location(
                         node,
                         &model.source,
@@ -530,13 +538,18 @@ pub fn check_duplicate_aliases(model: &SemanticModel) -> Vec<Diagnostic> {
                     };
 
                     diagnostics.push(
-                        Diagnostic::new(DUPLICATE_ALIAS, Severity::Error, message, location)
-                            .with_code("duplicate-alias-different-values".to_string()),
+                        Diagnostic::new(
+                            DUPLICATE_ALIAS,
+                            Severity::Error,
+                            message + &other_files_note(i, &occurrences),
+                            location,
+                        )
+                        .with_code("duplicate-alias-different-values".to_string()),
                     );
                 }
             } else {
                 // Same value - this is a warning (redundant but not wrong)
-                for (_i, (value, node)) in occurrences.iter().enumerate().skip(1) {
+                for (value, model, node) in occurrences.iter().skip(1) {
                     let location = model.source_map.node_to_diagnostic_location(
                         node,
                         &model.source,
@@ -570,6 +583,13 @@ pub fn check_duplicate_aliases(model: &SemanticModel) -> Vec<Diagnostic> {
 }
 
 /// Helper function to check if all strings in a vec are the same
+/// Each model with its document
+fn documents(models: &[SemanticModel]) -> impl Iterator<Item = (&SemanticModel, Document)> {
+    models
+        .iter()
+        .filter_map(|model| Document::cast(model.cst.clone()).map(|document| (model, document)))
+}
+
 fn all_same(values: &[String]) -> bool {
     if values.is_empty() {
         return true;
@@ -0,0 +1,726 @@
+//! Extension context rules
+//!
+//! Validates where extensions may be used, beyond the presence check of
+//! [`super::profile::EXTENSION_CONTEXT_MISSING`]:
+//!
+//! - `element` contexts must name an element of a known StructureDefinition
+//! - `fhirpath` contexts must be syntactically valid FHIRPath
+//! - `extension` contexts must point at an extension that exists
+//! - profiles may only add an extension to elements its contexts allow
+//!
+//! Contexts come from `^context[n].type` / `^context[n].expression` caret
+//! rules and from the `Context:` keyword. Checks that need StructureDefinitions
+//! are skipped when no packages are loaded.
+
+use crate::fhir_registry::{FhirVersion, is_fhir_resource};
+use maki_core::canonical::DefinitionSession;
+use maki_core::canonical::extension::{
+    ContextType, ExtensionContext, ExtensionDefinition, ExtensionValidator,
+};
+use maki_core::canonical::fishable::{FhirType, Fishable};
+use maki_core::cst::ast::{AstNode, CaretValueRule, Document, FixedValueRule, Rule};
+use maki_core::cst::{FshSyntaxKind, FshSyntaxNode};
+use maki_core::export::StructureDefinition;
+use maki_core::semantic::invariant::InvariantRegistry;
+use maki_core::{Diagnostic, LazySession, SemanticModel, Severity};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::terminology::alias_name;
+
+/// Rule ID for context expressions that don't resolve
+pub const EXTENSION_CONTEXT_INVALID: &str = "correctness/extension-context-invalid";
+/// Rule ID for extensions added to elements outside their contexts
+pub const EXTENSION_OUTSIDE_CONTEXT: &str = "correctness/extension-outside-context";
+
+/// All extension context rule IDs
+pub const EXTENSION_CONTEXT_RULES: [&str; 2] =
+    [EXTENSION_CONTEXT_INVALID, EXTENSION_OUTSIDE_CONTEXT];
+
+/// Element contexts that stand for any element or any resource
+const ABSTRACT_CONTEXTS: [&str; 5] = [
+    "Element",
+    "BackboneElement",
+    "Base",
+    "Resource",
+    "DomainResource",
+];
+
+/// A context declared by an extension in FSH
+#[derive(Debug, Clone)]
+struct DeclaredContext {
+    /// `None` when the declared type is not a known context type
+    context_type: Option<ContextType>,
+    written_type: String,
+    expression: String,
+    span: Range<usize>,
+}
+
+/// Type and expression (with the span of its rule) of a context being declared
+type PartialContext = (Option<String>, Option<(String, Range<usize>)>);
+
+#[derive(Debug, Clone)]
+struct LocalExtension {
+    name: String,
+    id: Option<String>,
+    url: Option<String>,
+    contexts: Vec<DeclaredContext>,
+}
+
+impl LocalExtension {
+    fn is(&self, reference: &str) -> bool {
+        self.name == reference
+            || self.id.as_deref() == Some(reference)
+            || self.url.as_deref() == Some(reference)
+            || self.id.as_deref().is_some_and(|id| {
+                reference
+                    .rsplit_once("/StructureDefinition/")
+                    .is_some_and(|(_, tail)| tail == id)
+            })
+    }
+
+    fn definition(&self) -> ExtensionDefinition {
+        let url = self.url.clone().unwrap_or_else(|| self.name.clone());
+        self.contexts
+            .iter()
+            .filter_map(|declared| {
+                Some(ExtensionContext::new(
+                    declared.context_type?,
+                    declared.expression.clone(),
+                ))
+            })
+            .fold(
+                ExtensionDefinition::new(url, self.name.clone()),
+                |ext, ctx| ext.add_context(ctx),
+            )
+    }
+}
+
+/// An extension added to a profile element
+#[derive(Debug, Clone)]
+struct ExtensionUse {
+    profile: String,
+    /// Element path the extension is added to, empty for the root
+    element: String,
+    /// Extension name, id, alias or url as written
+    extension: String,
+    span: Range<usize>,
+}
+
+/// Check extension contexts and where extensions are added
+///
+/// Reports diagnostics for all [`EXTENSION_CONTEXT_RULES`]; callers filter by
+/// rule ID. `global_definitions` holds the names and ids of StructureDefinitions
+/// defined anywhere in the project, which element contexts may refer to.
+pub async fn check_extension_contexts(
+    model: &SemanticModel,
+    lazy_session: Option<&Arc<LazySession>>,
+    global_definitions: Option<&HashSet<String>>,
+) -> Vec<Diagnostic> {
+    let Some(document) = Document::cast(model.cst.clone()) else {
+        return Vec::new();
+    };
+
+    // Collect before any await; CST iterators are not Send
+    let aliases: HashMap<String, String> = document
+        .aliases()
+        .filter_map(|alias| Some((alias_name(alias.syntax())?, alias.value()?)))
+        .collect();
+    let mut local_definitions = super::profile::definition_names(&document);
+    local_definitions.extend(global_definitions.into_iter().flatten().cloned());
+    let extension_names: HashSet<String> = document
+        .extensions()
+        .flat_map(|extension| [extension.name(), extension.id().and_then(|c| c.value())])
+        .flatten()
+        .collect();
+    let extensions: Vec<LocalExtension> = document
+        .extensions()
+        .filter_map(|extension| {
+            let name = extension.name()?;
+            let id = extension.id().and_then(|c| c.value());
+            Some(collect_extension(
+                model,
+                name,
+                id,
+                extension.syntax(),
+                &extension_names,
+            ))
+        })
+        .collect();
+    let mut parents: HashMap<String, String> = HashMap::new();
+    let mut uses: Vec<ExtensionUse> = Vec::new();
+    for profile in document.profiles() {
+        let Some(name) = profile.name() else { continue };
+        if let Some(parent) = profile.parent().and_then(|p| p.value()) {
+            parents.insert(name.clone(), parent);
+        }
+        for rule in profile.rules() {
+            if let Rule::Contains(contains) = rule {
+                uses.extend(extension_uses(model, &name, contains.syntax(), &aliases));
+            }
+        }
+    }
+
+    let needs_session = uses
+        .iter()
+        .any(|u| !extensions.iter().any(|e| e.is(&u.extension)))
+        || extensions.iter().flat_map(|e| &e.contexts).any(|c| {
+            matches!(
+                c.context_type,
+                Some(ContextType::Element) | Some(ContextType::Extension)
+            )
+        });
+    let session = match lazy_session {
+        Some(lazy) if needs_session => lazy.get().await.ok(),
+        _ => None,
+    };
+    let session = session.map(|s| s.as_ref());
+
+    let mut diagnostics = Vec::new();
+    for extension in &extensions {
+        for declared in &extension.contexts {
+            if let Some(problem) =
+                context_problem(declared, &extensions, &local_definitions, session).await
+            {
+                diagnostics.push(
+                    Diagnostic::new(
+                        EXTENSION_CONTEXT_INVALID,
+                        Severity::Error,
+                        format!(
+                            "Context '{}' of extension '{}' is invalid: {}",
+                            declared.expression, extension.name, problem
+                        ),
+                        location(model, &declared.span),
+                    )
+                    .with_code("invalid-extension-context".to_string()),
+                );
+            }
+        }
+    }
+
+    for extension_use in &uses {
+        if let Some(diagnostic) =
+            check_use(model, extension_use, &extensions, &parents, session).await
+        {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+fn collect_extension(
+    model: &SemanticModel,
+    name: String,
+    id: Option<String>,
+    node: &FshSyntaxNode,
+    extension_names: &HashSet<String>,
+) -> LocalExtension {
+    let mut url = None;
+    let mut contexts = Vec::new();
+    // Caret contexts by index
+    let mut indexed: Vec<PartialContext> = Vec::new();
+    let mut last: Option<usize> = None;
+
+    for child in node.children() {
+        if child.kind() == FshSyntaxKind::PathRule
+            && child
+                .children_with_tokens()
+                .any(|element| element.kind() == FshSyntaxKind::ContextKw)
+        {
+            contexts.extend(context_clause(model, &child, extension_names));
+            continue;
+        }
+        let Some((field, value, span)) = entity_caret(&child) else {
+            continue;
+        };
+        if field == "url" {
+            url = Some(value);
+            continue;
+        }
+        let Some(rest) = field.strip_prefix("context") else {
+            continue;
+        };
+        let (index, attribute) = match rest.split_once('.') {
+            Some((index, attribute)) => (index, attribute),
+            None => continue,
+        };
+        let index = match index {
+            "" => 0,
+            "[=]" => last.unwrap_or(0),
+            "[+]" => last.map_or(0, |i| i + 1),
+            other => other
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse()
+                .unwrap_or(0),
+        };
+        last = Some(index);
+        if indexed.len() <= index {
+            indexed.resize(index + 1, (None, None));
+        }
+        match attribute {
+            "type" => indexed[index].0 = Some(value.trim_start_matches('#').to_string()),
+            "expression" => indexed[index].1 = Some((value, span)),
+            _ => {}
+        }
+    }
+
+    for (written_type, expression) in indexed {
+        let Some((expression, span)) = expression else {
+            continue;
+        };
+        let written_type = written_type.unwrap_or_else(|| "element".to_string());
+        contexts.push(DeclaredContext {
+            context_type: ContextType::parse(&written_type).ok(),
+            written_type,
+            expression,
+            span: trim_span(model, span),
+        });
+    }
+
+    LocalExtension {
+        name,
+        id,
+        url,
+        contexts,
+    }
+}
+
+/// Field, value and span of an entity caret rule like `* ^context[0].type = #element`
+///
+/// Outside of profiles these parse as a `^`-prefixed `Path` followed by a
+/// `FixedValueRule`, so both shapes are accepted.
+pub(crate) fn entity_caret(rule: &FshSyntaxNode) -> Option<(String, String, Range<usize>)> {
+    if let Some(caret) = CaretValueRule::cast(rule.clone()) {
+        let range = caret.syntax().text_range();
+        let span = usize::from(range.start())..usize::from(range.end());
+        return Some((caret.field()?, caret.value()?, span));
+    }
+    let fixed = FixedValueRule::cast(rule.clone())?;
+    let path = fixed.path()?;
+    let field = path.syntax().text().to_string();
+    let field = field.trim().strip_prefix('^')?.to_string();
+    let value = fixed.value()?.trim_matches('"').to_string();
+    let span = usize::from(path.syntax().text_range().start())
+        ..usize::from(fixed.syntax().text_range().end());
+    Some((field, value, span))
+}
+
+/// Contexts of a `Context:` clause
+///
+/// The clause is read from the source line, since urls and choice paths
+/// don't parse as single tokens. Quoted values are FHIRPath; urls and names
+/// of local extensions are extension contexts; anything else is an element.
+fn context_clause(
+    model: &SemanticModel,
+    clause: &FshSyntaxNode,
+    extension_names: &HashSet<String>,
+) -> Vec<DeclaredContext> {
+    let start = usize::from(clause.text_range().start());
+    let line = model.source[start..].lines().next().unwrap_or("");
+    let Some(colon) = line.find(':') else {
+        return Vec::new();
+    };
+
+    let mut contexts = Vec::new();
+    let mut item_start = colon + 1;
+    let mut in_string = false;
+    let separators = line.char_indices().filter(|(offset, _)| *offset > colon);
+    for (offset, ch) in separators.chain([(line.len(), ',')]) {
+        match ch {
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                let raw = &line[item_start..offset];
+                let text = raw.trim();
+                let span_start = start + item_start + (raw.len() - raw.trim_start().len());
+                item_start = offset + 1;
+                if text.is_empty() {
+                    continue;
+                }
+                let (context_type, expression) = if text.starts_with('"') {
+                    (ContextType::FhirPath, text.trim_matches('"').to_string())
+                } else if text.contains("://") || extension_names.contains(text) {
+                    (ContextType::Extension, text.to_string())
+                } else {
+                    (ContextType::Element, text.to_string())
+                };
+                contexts.push(DeclaredContext {
+                    context_type: Some(context_type),
+                    written_type: context_type.as_str().to_string(),
+                    expression,
+                    span: span_start..span_start + text.len(),
+                });
+            }
+            _ => {}
+        }
+    }
+    contexts
+}
+
+/// Span of a rule without the trailing newline
+fn trim_span(model: &SemanticModel, span: Range<usize>) -> Range<usize> {
+    let text = &model.source[span.clone()];
+    span.start..span.start + text.trim_end().len()
+}
+
+/// Extensions added by a `contains` rule on an `extension` element
+fn extension_uses(
+    model: &SemanticModel,
+    profile: &str,
+    contains: &FshSyntaxNode,
+    aliases: &HashMap<String, String>,
+) -> Vec<ExtensionUse> {
+    let Some(path) = std::iter::successors(contains.prev_sibling_or_token(), |element| {
+        element.prev_sibling_or_token()
+    })
+    .find(|element| element.kind() != FshSyntaxKind::Whitespace)
+    .and_then(|element| element.into_node())
+    .filter(|node| node.kind() == FshSyntaxKind::Path) else {
+        return Vec::new();
+    };
+    let path = path.text().to_string();
+    let segments: Vec<&str> = path
+        .trim()
+        .split('.')
+        .map(|segment| segment.split('[').next().unwrap_or(segment))
+        .collect();
+    let Some((last, element)) = segments.split_last() else {
+        return Vec::new();
+    };
+    if *last != "extension" && *last != "modifierExtension" {
+        return Vec::new();
+    }
+    let range = contains.text_range();
+    let span = trim_span(model, usize::from(range.start())..usize::from(range.end()));
+
+    let text = contains.text().to_string();
+    let text = text.trim_start().strip_prefix("contains").unwrap_or(&text);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words
+        .split(|word| *word == "and")
+        .filter_map(|item| {
+            let reference = match item.iter().position(|word| *word == "named") {
+                Some(named) => item[..named].join(""),
+                // Without `named`, the item is the slice name of an inline extension
+                None => return None,
+            };
+            let extension = aliases.get(&reference).cloned().unwrap_or(reference);
+            Some(ExtensionUse {
+                profile: profile.to_string(),
+                element: element.join("."),
+                extension,
+                span: span.clone(),
+            })
+        })
+        .collect()
+}
+
+/// What is wrong with a declared context, if anything
+async fn context_problem(
+    declared: &DeclaredContext,
+    extensions: &[LocalExtension],
+    local_definitions: &HashSet<String>,
+    session: Option<&DefinitionSession>,
+) -> Option<String> {
+    let Some(context_type) = declared.context_type else {
+        return Some(format!(
+            "'{}' is not a context type (element, extension or fhirpath)",
+            declared.written_type
+        ));
+    };
+    let expression = declared.expression.trim();
+    match context_type {
+        ContextType::FhirPath => InvariantRegistry::new()
+            .validate_fhirpath(expression)
+            .err()
+            .map(|error| error.to_string()),
+        ContextType::Extension => {
+            if extensions.iter().any(|e| e.is(expression)) || local_definitions.contains(expression)
+            {
+                return None;
+            }
+            let session = session?;
+            match session.fish(expression, &[FhirType::Extension]).await {
+                Ok(Some(_)) | Err(_) => None,
+                Ok(None) => Some("no extension with this url exists".to_string()),
+            }
+        }
+        ContextType::Element | ContextType::Resource => {
+            // `url#path` names an element of a specific StructureDefinition
+            let (base, path) = match expression.split_once('#') {
+                Some((url, path)) => (url, path),
+                None => (
+                    expression.split('.').next().unwrap_or(expression),
+                    expression,
+                ),
+            };
+            if local_definitions.contains(base) {
+                return None;
+            }
+            let session = session?;
+            let sd = match structure_definition(session, base).await {
+                Ok(Some(sd)) => sd,
+                Ok(None) => return Some(format!("'{}' is not a known type", base)),
+                Err(()) => return None,
+            };
+            let root = path.split('.').next().unwrap_or(path);
+            let path = if root == base {
+                path.to_string()
+            } else {
+                path.replacen(root, &sd.type_field, 1)
+            };
+            (find_element(&sd, &path).is_none())
+                .then(|| format!("'{}' has no element '{}'", sd.type_field, path))
+        }
+    }
+}
+
+async fn check_use(
+    model: &SemanticModel,
+    extension_use: &ExtensionUse,
+    extensions: &[LocalExtension],
+    parents: &HashMap<String, String>,
+    session: Option<&DefinitionSession>,
+) -> Option<Diagnostic> {
+    let definition = match extensions.iter().find(|e| e.is(&extension_use.extension)) {
+        Some(local) => {
+            // Undeclared types are reported as invalid contexts instead
+            if local.contexts.iter().any(|c| c.context_type.is_none()) {
+                return None;
+            }
+            local.definition()
+        }
+        None => {
+            let resource = session?
+                .fish(&extension_use.extension, &[FhirType::Extension])
+                .await
+                .ok()??;
+            ExtensionDefinition::from_fhir_json(&resource.content).ok()?
+        }
+    };
+    if definition.context.is_empty()
+        || definition
+            .context
+            .iter()
+            .any(|c| c.context_type == ContextType::FhirPath)
+    {
+        return None;
+    }
+
+    // Type the profile constrains, following parents defined in this file
+    let mut base = extension_use.profile.clone();
+    let mut depth = 0;
+    while let Some(parent) = parents.get(&base) {
+        base = parent.clone();
+        depth += 1;
+        if depth > 16 {
+            return None;
+        }
+    }
+    let sd = match session {
+        Some(session) => structure_definition(session, &base).await.ok()?,
+        None => None,
+    };
+    let base_type = match &sd {
+        Some(sd) => sd.type_field.clone(),
+        None if is_fhir_resource(&base, FhirVersion::R4) => base,
+        None => return None,
+    };
+    let target = if extension_use.element.is_empty() {
+        base_type.clone()
+    } else {
+        format!("{}.{}", base_type, extension_use.element)
+    };
+
+    let mut targets = vec![target.clone()];
+    match sd.as_ref().and_then(|sd| find_element(sd, &target)) {
+        Some(element) => targets.extend(
+            element
+                .type_
+                .iter()
+                .flatten()
+                .map(|element_type| element_type.code.clone()),
+        ),
+        // Without the element's types, datatype contexts can't be decided
+        None if definition.context.iter().any(|c| {
+            c.context_type == ContextType::Element
+                && !c.expression.contains('.')
+                && !is_fhir_resource(&c.expression, FhirVersion::R4)
+                && !ABSTRACT_CONTEXTS.contains(&c.expression.as_str())
+        }) =>
+        {
+            return None;
+        }
+        None => {}
+    }
+    let is_root = extension_use.element.is_empty();
+
+    let url = definition.url.clone();
+    let contexts: Vec<String> = definition
+        .context
+        .iter()
+        .map(|c| c.expression.clone())
+        .collect();
+    let allowed = definition.context.iter().any(|c| {
+        c.context_type == ContextType::Element
+            && (c.expression == "Element"
+                || c.expression == "Base"
+                || (is_root && (c.expression == "Resource" || c.expression == "DomainResource")))
+    });
+    if allowed {
+        return None;
+    }
+    let mut validator = ExtensionValidator::new();
+    validator.load_extension(definition);
+    if targets
+        .iter()
+        .any(|target| validator.validate_context(&url, target).is_ok())
+    {
+        return None;
+    }
+
+    Some(
+        Diagnostic::new(
+            EXTENSION_OUTSIDE_CONTEXT,
+            Severity::Error,
+            format!(
+                "Extension '{}' is added to '{}', outside its contexts: {}",
+                extension_use.extension,
+                target,
+                contexts.join(", ")
+            ),
+            location(model, &extension_use.span),
+        )
+        .with_code("extension-outside-context".to_string()),
+    )
+}
+
+/// StructureDefinition by name, id or url; `Err` when the lookup itself failed
+async fn structure_definition(
+    session: &DefinitionSession,
+    name: &str,
+) -> Result<Option<StructureDefinition>, ()> {
+    let resource = session
+        .fish(name, &[FhirType::StructureDefinition])
+        .await
+        .map_err(|_| ())?;
+    Ok(resource.and_then(|resource| serde_json::from_value((*resource.content).clone()).ok()))
+}
+
+/// Snapshot element by path or id, matching choice elements (`value[x]`)
+fn find_element<'a>(
+    sd: &'a StructureDefinition,
+    path: &str,
+) -> Option<&'a maki_core::export::ElementDefinition> {
+    let elements = sd.snapshot.as_ref()?.element.as_slice();
+    elements.iter().find(|e| {
+        e.path == path
+            || e.id.as_deref() == Some(path)
+            || e.path.strip_suffix("[x]").is_some_and(|choice| {
+                path.starts_with(choice) && !path[choice.len()..].contains('.')
+            })
+    })
+}
+
+fn location(model: &SemanticModel, span: &Range<usize>) -> maki_core::diagnostics::Location {
+    model
+        .source_map
+        .span_to_diagnostic_location(span, &model.source, &model.source_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maki_core::semantic::SemanticAnalyzer;
+    use maki_core::{DefaultSemanticAnalyzer, FshParser};
+    use std::path::PathBuf;
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let parsed = FshParser::parse_content(source).unwrap();
+        let model = DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from("test.fsh"))
+            .unwrap();
+        tokio_test::block_on(check_extension_contexts(&model, None, None))
+    }
+
+    fn rule_ids(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fhirpath_context_syntax() {
+        let source = "Extension: E\n* ^context[+].type = #fhirpath\n* ^context[=].expression = \"Patient.name.where(use = 'official'\"\n* ^context[+].type = #fhirpath\n* ^context[=].expression = \"Observation.code.exists()\"\n* value[x] only string\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![EXTENSION_CONTEXT_INVALID]);
+        assert!(diagnostics[0].message.contains("Unbalanced parentheses"));
+        assert_eq!(diagnostics[0].location.line, 3);
+    }
+
+    #[test]
+    fn test_unknown_context_type() {
+        let source = "Extension: E\n* ^context[0].type = #resource-type\n* ^context[0].expression = \"Patient\"\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![EXTENSION_CONTEXT_INVALID]);
+        assert!(diagnostics[0].message.contains("'resource-type'"));
+    }
+
+    #[test]
+    fn test_extension_added_outside_context() {
+        let source = "Extension: PatientOnly\n* ^context[+].type = #element\n* ^context[=].expression = \"Patient\"\n* value[x] only string\n\nExtension: NameOnly\nContext: Patient.name\n* value[x] only string\n\nProfile: MyPatient\nParent: Patient\n* extension contains PatientOnly named patientOnly 0..1\n* name.extension contains NameOnly named nameOnly 0..1\n\nProfile: MyObservation\nParent: Observation\n* extension contains PatientOnly named patientOnly 0..1 and NameOnly named nameOnly 0..1\n";
+        let diagnostics = check(source);
+
+        assert_eq!(
+            rule_ids(&diagnostics),
+            vec![EXTENSION_OUTSIDE_CONTEXT, EXTENSION_OUTSIDE_CONTEXT]
+        );
+        assert!(
+            diagnostics[0]
+                .message
+                .contains("'PatientOnly' is added to 'Observation'")
+        );
+        assert!(diagnostics[1].message.contains("'NameOnly'"));
+        assert_eq!(diagnostics[0].location.line, 17);
+    }
+
+    #[test]
+    fn test_parent_chain_and_element_context() {
+        let source = "Extension: AnyElement\n* ^context[+].type = #element\n* ^context[=].expression = \"Element\"\n\nExtension: NameOnly\n* ^context.type = #element\n* ^context.expression = \"Patient.name\"\n\nProfile: BasePatient\nParent: Patient\n\nProfile: ChildPatient\nParent: BasePatient\n* extension contains NameOnly named nameOnly 0..1\n* name.extension contains NameOnly named nameOnly 0..1 and AnyElement named any 0..1\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![EXTENSION_OUTSIDE_CONTEXT]);
+        assert!(diagnostics[0].message.contains("added to 'Patient'"));
+    }
+
+    #[test]
+    fn test_datatype_contexts_need_definitions() {
+        let source = "Extension: OnHumanName\n* ^context[+].type = #element\n* ^context[=].expression = \"HumanName\"\n\nProfile: MyPatient\nParent: Patient\n* name.extension contains OnHumanName named onName 0..1\n";
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn test_context_keyword() {
+        let source = "Extension: Mixed
+Context: http://example.org/StructureDefinition/other, \"Patient.name.where(use = 'x'\", Observation.code
+
+Extension: OnValue
+Context: Observation.value[x]
+
+Profile: MyObservation
+Parent: Observation
+* valueQuantity.extension contains OnValue named onValue 0..1
+* code.extension contains OnValue named onCode 0..1
+";
+        let diagnostics = check(source);
+
+        assert_eq!(
+            rule_ids(&diagnostics),
+            vec![EXTENSION_CONTEXT_INVALID, EXTENSION_OUTSIDE_CONTEXT]
+        );
+        assert_eq!(diagnostics[0].location.line, 2);
+        assert_eq!(diagnostics[0].location.column, 56);
+        assert!(diagnostics[1].message.contains("'Observation.code'"));
+    }
+}
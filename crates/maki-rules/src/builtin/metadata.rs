@@ -2,15 +2,96 @@
 //!
 //! Validates that FHIR resources have proper documentation metadata.
 //! These are warning-level rules that encourage good documentation practices.
+//! Which fields are required, and which publishers are allowed, is set
+//! through [`MissingMetadataOptions`].
 
+use crate::builtin::extension_context::entity_caret;
+use crate::options::RuleOptions;
+use maki_core::cst::FshSyntaxNode;
 use maki_core::cst::ast::{AstNode, CodeSystem, Document, Extension, Profile, ValueSet};
 use maki_core::{Diagnostic, SemanticModel, Severity};
+use schemars::JsonSchema;
+use serde::Deserialize;
 
 /// Rule ID for missing metadata validation
 pub const MISSING_METADATA: &str = "documentation/missing-metadata";
 
+/// Options of [`MISSING_METADATA`]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MissingMetadataOptions {
+    /// Fields every Profile, Extension, ValueSet and CodeSystem must have
+    #[serde(default = "default_required_fields")]
+    pub required_fields: Vec<MetadataField>,
+    /// Publishers allowed in `^publisher`; any publisher when not set
+    #[serde(default)]
+    pub allowed_publishers: Option<Vec<String>>,
+}
+
+impl Default for MissingMetadataOptions {
+    fn default() -> Self {
+        Self {
+            required_fields: default_required_fields(),
+            allowed_publishers: None,
+        }
+    }
+}
+
+fn default_required_fields() -> Vec<MetadataField> {
+    vec![MetadataField::Description]
+}
+
+impl RuleOptions for MissingMetadataOptions {
+    const RULE_ID: &'static str = MISSING_METADATA;
+}
+
+/// A metadata field of a definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataField {
+    /// `Id:` keyword
+    Id,
+    /// `Title:` keyword
+    Title,
+    /// `Description:` keyword
+    Description,
+    /// `^publisher` caret rule
+    Publisher,
+    /// `^status` caret rule
+    Status,
+    /// `^version` caret rule
+    Version,
+}
+
+impl MetadataField {
+    /// Name as written in FSH
+    fn fsh_name(self) -> &'static str {
+        match self {
+            MetadataField::Id => "Id",
+            MetadataField::Title => "Title",
+            MetadataField::Description => "Description",
+            MetadataField::Publisher => "^publisher",
+            MetadataField::Status => "^status",
+            MetadataField::Version => "^version",
+        }
+    }
+}
+
+/// Metadata of one definition, as written
+struct Metadata<'a> {
+    resource_type: &'a str,
+    name: String,
+    node: &'a FshSyntaxNode,
+    has_id: bool,
+    has_title: bool,
+    has_description: bool,
+}
+
 /// Check for missing metadata documentation in FSH document
-pub fn check_missing_metadata(model: &SemanticModel) -> Vec<Diagnostic> {
+pub fn check_missing_metadata(
+    model: &SemanticModel,
+    options: &MissingMetadataOptions,
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
     let Some(document) = Document::cast(model.cst.clone()) else {
@@ -19,105 +100,202 @@ pub fn check_missing_metadata(model: &SemanticModel) -> Vec<Diagnostic> {
 
     // Check profiles
     for profile in document.profiles() {
-        diagnostics.extend(check_profile_metadata(&profile, model));
+        diagnostics.extend(check_profile_metadata(&profile, model, options));
     }
 
     // Check extensions
     for extension in document.extensions() {
-        diagnostics.extend(check_extension_metadata(&extension, model));
+        diagnostics.extend(check_extension_metadata(&extension, model, options));
     }
 
     // Check value sets
     for value_set in document.value_sets() {
-        diagnostics.extend(check_value_set_metadata(&value_set, model));
+        diagnostics.extend(check_value_set_metadata(&value_set, model, options));
     }
 
     // Check code systems
     for code_system in document.code_systems() {
-        diagnostics.extend(check_code_system_metadata(&code_system, model));
+        diagnostics.extend(check_code_system_metadata(&code_system, model, options));
     }
 
     diagnostics
 }
 
 /// Check metadata for Profile
-fn check_profile_metadata(profile: &Profile, model: &SemanticModel) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
-
+fn check_profile_metadata(
+    profile: &Profile,
+    model: &SemanticModel,
+    options: &MissingMetadataOptions,
+) -> Vec<Diagnostic> {
+    let Some(name) = profile.name() else {
+        return Vec::new();
+    };
+    let metadata = Metadata {
+        resource_type: "Profile",
+        name,
+        node: profile.syntax(),
+        has_id: profile.id().is_some(),
+        has_title: profile.title().is_some(),
+        has_description: profile.description().is_some(),
+    };
     // Description is important for understanding the profile
-    if profile.description().is_none()
-        && let Some(name) = profile.name()
-    {
-        diagnostics.push(create_missing_metadata_diagnostic(
-            "Profile",
-            &name,
-            "Description",
-            profile.syntax(),
-            model,
-            "Profiles should have a Description field for documentation",
-        ));
-    }
-
-    diagnostics
+    check_metadata(
+        &metadata,
+        model,
+        options,
+        "Profiles should have a Description field for documentation",
+    )
 }
 
 /// Check metadata for Extension
-fn check_extension_metadata(extension: &Extension, model: &SemanticModel) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
-
+fn check_extension_metadata(
+    extension: &Extension,
+    model: &SemanticModel,
+    options: &MissingMetadataOptions,
+) -> Vec<Diagnostic> {
+    let Some(name) = extension.name() else {
+        return Vec::new();
+    };
+    let metadata = Metadata {
+        resource_type: "Extension",
+        name,
+        node: extension.syntax(),
+        has_id: extension.id().is_some(),
+        has_title: extension.title().is_some(),
+        has_description: extension.description().is_some(),
+    };
     // Description is important for understanding the extension
-    if extension.description().is_none()
-        && let Some(name) = extension.name()
-    {
-        diagnostics.push(create_missing_metadata_diagnostic(
-            "Extension",
-            &name,
-            "Description",
-            extension.syntax(),
-            model,
-            "Extensions should have a Description field for documentation",
-        ));
-    }
-
-    diagnostics
+    check_metadata(
+        &metadata,
+        model,
+        options,
+        "Extensions should have a Description field for documentation",
+    )
 }
 
 /// Check metadata for ValueSet
-fn check_value_set_metadata(value_set: &ValueSet, model: &SemanticModel) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
-
+fn check_value_set_metadata(
+    value_set: &ValueSet,
+    model: &SemanticModel,
+    options: &MissingMetadataOptions,
+) -> Vec<Diagnostic> {
+    let Some(name) = value_set.name() else {
+        return Vec::new();
+    };
+    let metadata = Metadata {
+        resource_type: "ValueSet",
+        name,
+        node: value_set.syntax(),
+        has_id: value_set.id().is_some(),
+        has_title: value_set.title().is_some(),
+        has_description: value_set.description().is_some(),
+    };
     // Description is important for understanding what values are included
-    if value_set.description().is_none()
-        && let Some(name) = value_set.name()
-    {
-        diagnostics.push(create_missing_metadata_diagnostic(
-            "ValueSet",
-            &name,
-            "Description",
-            value_set.syntax(),
-            model,
-            "ValueSets should have a Description field explaining the purpose and contents",
-        ));
-    }
-
-    diagnostics
+    check_metadata(
+        &metadata,
+        model,
+        options,
+        "ValueSets should have a Description field explaining the purpose and contents",
+    )
 }
 
 /// Check metadata for CodeSystem
-fn check_code_system_metadata(code_system: &CodeSystem, model: &SemanticModel) -> Vec<Diagnostic> {
+fn check_code_system_metadata(
+    code_system: &CodeSystem,
+    model: &SemanticModel,
+    options: &MissingMetadataOptions,
+) -> Vec<Diagnostic> {
+    let Some(name) = code_system.name() else {
+        return Vec::new();
+    };
+    let metadata = Metadata {
+        resource_type: "CodeSystem",
+        name,
+        node: code_system.syntax(),
+        has_id: code_system.id().is_some(),
+        has_title: code_system.title().is_some(),
+        has_description: code_system.description().is_some(),
+    };
+    // Description is important for understanding the code system
+    check_metadata(
+        &metadata,
+        model,
+        options,
+        "CodeSystems should have a Description field explaining the codes",
+    )
+}
+
+/// Check the required fields and the publisher of a definition
+fn check_metadata(
+    metadata: &Metadata,
+    model: &SemanticModel,
+    options: &MissingMetadataOptions,
+    description_help: &str,
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
+    let carets: Vec<(String, String)> = metadata
+        .node
+        .children()
+        .filter_map(|rule| entity_caret(&rule))
+        .map(|(field, value, _)| (field, value))
+        .collect();
+    let caret = |field: &str| {
+        carets
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value.as_str())
+    };
 
-    // Description is important for understanding the code system
-    if code_system.description().is_none()
-        && let Some(name) = code_system.name()
-    {
+    for field in &options.required_fields {
+        let present = match field {
+            MetadataField::Id => metadata.has_id,
+            MetadataField::Title => metadata.has_title,
+            MetadataField::Description => metadata.has_description,
+            MetadataField::Publisher => caret("publisher").is_some(),
+            MetadataField::Status => caret("status").is_some(),
+            MetadataField::Version => caret("version").is_some(),
+        };
+        if present {
+            continue;
+        }
+        let help = match field {
+            MetadataField::Description => description_help.to_string(),
+            _ => format!(
+                "The project requires {} on every {}",
+                field.fsh_name(),
+                metadata.resource_type
+            ),
+        };
         diagnostics.push(create_missing_metadata_diagnostic(
-            "CodeSystem",
-            &name,
-            "Description",
-            code_system.syntax(),
+            metadata.resource_type,
+            &metadata.name,
+            *field,
+            metadata.node,
             model,
-            "CodeSystems should have a Description field explaining the codes",
+            &help,
+        ));
+    }
+
+    if let Some(allowed) = &options.allowed_publishers
+        && let Some(publisher) = caret("publisher")
+        && !allowed.iter().any(|allowed| allowed == publisher)
+    {
+        let location = model.source_map.node_to_diagnostic_location(
+            metadata.node,
+            &model.source,
+            &model.source_file,
+        );
+        diagnostics.push(Diagnostic::new(
+            MISSING_METADATA,
+            Severity::Warning,
+            format!(
+                "{} '{}' has publisher '{}', which is not an allowed publisher ({})",
+                metadata.resource_type,
+                metadata.name,
+                publisher,
+                allowed.join(", ")
+            ),
+            location,
         ));
     }
 
@@ -128,11 +306,12 @@ fn check_code_system_metadata(code_system: &CodeSystem, model: &SemanticModel) -
 fn create_missing_metadata_diagnostic(
     resource_type: &str,
     resource_name: &str,
-    field_name: &str,
-    node: &maki_core::cst::FshSyntaxNode,
+    field: MetadataField,
+    node: &FshSyntaxNode,
     model: &SemanticModel,
     message: &str,
 ) -> Diagnostic {
+    let field_name = field.fsh_name();
     // Use SourceMap for precise location!
     let location =
         model
@@ -162,19 +341,25 @@ fn create_missing_metadata_diagnostic(
         )),
     };
 
-    Diagnostic::new(
+    let diagnostic = Diagnostic::new(
         MISSING_METADATA,
         Severity::Warning, // Warning, not error - documentation is encouraged but not required
         format!(
             "{resource_type} '{resource_name}' is missing recommended field: {field_name}. {message}"
         ),
         location.clone(),
-    )
-    .with_suggestion(maki_core::CodeSuggestion::safe(
-        format!("Add {field_name} field"),
-        format!("\n{field_name}: \"\""),
-        insert_location,
-    ))
+    );
+    // Caret rules can't go right after the declaration line, before the keywords
+    match field {
+        MetadataField::Title | MetadataField::Description => {
+            diagnostic.with_suggestion(maki_core::CodeSuggestion::safe(
+                format!("Add {field_name} field"),
+                format!("\n{field_name}: \"\""),
+                insert_location,
+            ))
+        }
+        _ => diagnostic,
+    }
 }
 
 #[cfg(test)]
@@ -211,7 +396,8 @@ Description: "A test profile"
         let document = Document::cast(model.cst.clone()).expect("Should parse as document");
         let profile = document.profiles().next().expect("Should have profile");
 
-        let diagnostics = check_profile_metadata(&profile, &model);
+        let diagnostics =
+            check_profile_metadata(&profile, &model, &MissingMetadataOptions::default());
         assert_eq!(
             diagnostics.len(),
             0,
@@ -229,7 +415,8 @@ Title: "Test Profile"
         let document = Document::cast(model.cst.clone()).expect("Should parse as document");
         let profile = document.profiles().next().expect("Should have profile");
 
-        let diagnostics = check_profile_metadata(&profile, &model);
+        let diagnostics =
+            check_profile_metadata(&profile, &model, &MissingMetadataOptions::default());
         assert_eq!(diagnostics.len(), 1);
         assert!(diagnostics[0].message.contains("Description"));
         assert_eq!(diagnostics[0].severity, Severity::Warning);
@@ -245,7 +432,8 @@ Title: "Test Extension"
         let document = Document::cast(model.cst.clone()).expect("Should parse as document");
         let extension = document.extensions().next().expect("Should have extension");
 
-        let diagnostics = check_extension_metadata(&extension, &model);
+        let diagnostics =
+            check_extension_metadata(&extension, &model, &MissingMetadataOptions::default());
         assert_eq!(diagnostics.len(), 1);
         assert!(diagnostics[0].message.contains("Description"));
     }
@@ -260,7 +448,8 @@ Title: "Test VS"
         let document = Document::cast(model.cst.clone()).expect("Should parse as document");
         let value_set = document.value_sets().next().expect("Should have value set");
 
-        let diagnostics = check_value_set_metadata(&value_set, &model);
+        let diagnostics =
+            check_value_set_metadata(&value_set, &model, &MissingMetadataOptions::default());
         assert_eq!(diagnostics.len(), 1);
         assert!(diagnostics[0].message.contains("Description"));
     }
@@ -278,7 +467,8 @@ Title: "Test CS"
             .next()
             .expect("Should have code system");
 
-        let diagnostics = check_code_system_metadata(&code_system, &model);
+        let diagnostics =
+            check_code_system_metadata(&code_system, &model, &MissingMetadataOptions::default());
         assert_eq!(diagnostics.len(), 1);
         assert!(diagnostics[0].message.contains("Description"));
     }
@@ -295,8 +485,36 @@ Title: "Test VS"
 "#;
         let model = create_test_model_from_source(source);
 
-        let diagnostics = check_missing_metadata(&model);
+        let diagnostics = check_missing_metadata(&model, &MissingMetadataOptions::default());
         assert_eq!(diagnostics.len(), 2, "Should find 2 missing descriptions");
         assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
     }
+
+    #[test]
+    fn test_required_fields_and_allowed_publishers() {
+        let source = r#"Profile: TestProfile
+Id: test
+Description: "A test profile"
+* ^publisher = "Someone Else"
+* ^status = #draft
+
+ValueSet: TestVS
+Title: "Test VS"
+* ^publisher = "HL7 International"
+"#;
+        let model = create_test_model_from_source(source);
+        let options: MissingMetadataOptions = serde_json::from_value(serde_json::json!({
+            "requiredFields": ["title", "publisher", "status"],
+            "allowedPublishers": ["HL7 International"]
+        }))
+        .unwrap();
+
+        let diagnostics = check_missing_metadata(&model, &options);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages.len(), 3, "{messages:?}");
+        assert!(messages[0].contains("'TestProfile' is missing recommended field: Title"));
+        assert!(messages[1].contains("'TestProfile' has publisher 'Someone Else'"));
+        assert!(messages[2].contains("'TestVS' is missing recommended field: ^status"));
+        assert!(diagnostics[2].suggestions.is_empty());
+    }
 }
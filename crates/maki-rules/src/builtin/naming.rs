@@ -1,254 +1,285 @@
 //! Naming convention validation rules
 //!
 //! Enforces FSH naming conventions for better code consistency and readability.
+//! By default names use PascalCase and ids kebab-case; both can be replaced by
+//! regexes per entity kind through [`NamingConventionOptions`].
 
-use maki_core::cst::ast::{AstNode, Document};
+use crate::options::RuleOptions;
+use maki_core::cst::FshSyntaxNode;
+use maki_core::cst::ast::{AstNode, Document, IdClause};
 use maki_core::{Diagnostic, SemanticModel, Severity};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
 
 /// Rule ID for naming convention violations
 pub const NAMING_CONVENTION: &str = "style/naming-convention";
 
-/// Check naming conventions across all FSH resources
-pub fn check_naming_conventions(model: &SemanticModel) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
-
-    let Some(document) = Document::cast(model.cst.clone()) else {
-        return diagnostics;
-    };
+/// Options of [`NAMING_CONVENTION`]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NamingConventionOptions {
+    /// Regexes entity names must match, replacing the PascalCase check
+    #[serde(default)]
+    pub names: EntityPatterns,
+    /// Regexes entity ids must match, replacing the kebab-case check
+    #[serde(default)]
+    pub ids: EntityPatterns,
+}
 
-    // Check Profile names (should be PascalCase)
-    for profile in document.profiles() {
-        if let Some(name) = profile.name()
-            && !is_pascal_case(&name)
-        {
-            let location = model.source_map.node_to_diagnostic_location(
-                profile.syntax(),
-                &model.source,
-                &model.source_file,
-            );
+/// A regex per entity kind, matched against the whole name or id
+///
+/// Kinds without a regex keep the default convention; Instances, Logicals,
+/// Resources, Invariants and RuleSets are only checked when given one.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EntityPatterns {
+    pub profile: Option<String>,
+    pub extension: Option<String>,
+    pub value_set: Option<String>,
+    pub code_system: Option<String>,
+    pub instance: Option<String>,
+    pub logical: Option<String>,
+    pub resource: Option<String>,
+    pub invariant: Option<String>,
+    pub rule_set: Option<String>,
+}
 
-            diagnostics.push(
-                    Diagnostic::new(
-                        NAMING_CONVENTION,
-                        Severity::Warning,
-                        format!(
-                            "Profile name '{name}' should use PascalCase (e.g., 'MyProfile' or 'MySpecialProfile')"
-                        ),
-                        location.clone(),
-                    )
-                    .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                        "Convert to PascalCase",
-                        to_pascal_case(&name),
-                        location,
-                    )),
-                );
-        }
+impl EntityPatterns {
+    fn all(&self) -> [&Option<String>; 9] {
+        [
+            &self.profile,
+            &self.extension,
+            &self.value_set,
+            &self.code_system,
+            &self.instance,
+            &self.logical,
+            &self.resource,
+            &self.invariant,
+            &self.rule_set,
+        ]
+    }
+}
 
-        // Check ID naming (should be kebab-case)
-        if let Some(id_clause) = profile.id()
-            && let Some(id) = id_clause.value()
-            && !is_kebab_case(&id)
-        {
-            // Use id_clause.syntax() for the location, not profile.syntax()
-            // This ensures the autofix targets the Id line, not the Profile line
-            let location = model.source_map.node_to_diagnostic_location(
-                id_clause.syntax(),
-                &model.source,
-                &model.source_file,
-            );
+impl RuleOptions for NamingConventionOptions {
+    const RULE_ID: &'static str = NAMING_CONVENTION;
 
-            // Create a replacement that replaces the entire Id line value
-            let suggested_id = to_kebab_case(&id);
-            let full_replacement = format!("Id: {}", suggested_id);
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!("Profile ID '{id}' should use kebab-case (e.g., 'my-profile-id')"),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to kebab-case",
-                    full_replacement,
-                    location,
-                )),
-            );
+    fn validate(&self) -> Result<(), String> {
+        for pattern in self.names.all().into_iter().chain(self.ids.all()).flatten() {
+            compile(pattern)?;
         }
+        Ok(())
     }
+}
 
-    // Check Extension names (should be PascalCase)
-    for extension in document.extensions() {
-        if let Some(name) = extension.name()
-            && !is_pascal_case(&name)
-        {
-            let location = model.source_map.node_to_diagnostic_location(
-                extension.syntax(),
-                &model.source,
-                &model.source_file,
-            );
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!("Extension name '{name}' should use PascalCase (e.g., 'MyExtension')"),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to PascalCase",
-                    to_pascal_case(&name),
-                    location,
-                )),
-            );
-        }
+/// Compile `pattern` to match whole names
+fn compile(pattern: &str) -> Result<Regex, String> {
+    Regex::new(&format!("^(?:{pattern})$")).map_err(|e| format!("invalid regex '{pattern}': {e}"))
+}
 
-        if let Some(id_clause) = extension.id()
-            && let Some(id) = id_clause.value()
-            && !is_kebab_case(&id)
-        {
-            let location = model.source_map.node_to_diagnostic_location(
-                id_clause.syntax(),
-                &model.source,
-                &model.source_file,
-            );
+/// The convention a name or id is checked against
+enum Convention<'a> {
+    PascalCase { example: &'a str },
+    KebabCase { example: &'a str },
+    Pattern { pattern: &'a str, regex: Regex },
+    Unchecked,
+}
 
-            let suggested_id = to_kebab_case(&id);
-            let full_replacement = format!("Id: {}", suggested_id);
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!("Extension ID '{id}' should use kebab-case (e.g., 'my-extension-id')"),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to kebab-case",
-                    full_replacement,
-                    location,
-                )),
-            );
+impl<'a> Convention<'a> {
+    fn new(pattern: &'a Option<String>, default: Convention<'a>) -> Self {
+        match pattern.as_deref().map(|p| (p, compile(p))) {
+            Some((pattern, Ok(regex))) => Convention::Pattern { pattern, regex },
+            Some((pattern, Err(error))) => {
+                tracing::warn!("Ignoring naming pattern '{}': {}", pattern, error);
+                default
+            }
+            None => default,
         }
     }
+}
 
-    // Check ValueSet names (should be PascalCase)
-    for value_set in document.value_sets() {
-        if let Some(name) = value_set.name()
-            && !is_pascal_case(&name)
-        {
-            let location = model.source_map.node_to_diagnostic_location(
-                value_set.syntax(),
-                &model.source,
-                &model.source_file,
-            );
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!("ValueSet name '{name}' should use PascalCase (e.g., 'MyValueSet')"),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to PascalCase",
-                    to_pascal_case(&name),
-                    location,
-                )),
-            );
-        }
-
-        if let Some(id_clause) = value_set.id()
-            && let Some(id) = id_clause.value()
-            && !is_kebab_case(&id)
-        {
-            let location = model.source_map.node_to_diagnostic_location(
-                id_clause.syntax(),
-                &model.source,
-                &model.source_file,
-            );
+/// Check naming conventions across all FSH resources
+pub fn check_naming_conventions(
+    model: &SemanticModel,
+    options: &NamingConventionOptions,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
 
-            let suggested_id = to_kebab_case(&id);
-            let full_replacement = format!("Id: {}", suggested_id);
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!("ValueSet ID '{id}' should use kebab-case (e.g., 'my-value-set-id')"),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to kebab-case",
-                    full_replacement,
-                    location,
-                )),
-            );
-        }
-    }
+    let Some(document) = Document::cast(model.cst.clone()) else {
+        return diagnostics;
+    };
 
-    // Check CodeSystem names (should be PascalCase)
-    for code_system in document.code_systems() {
-        if let Some(name) = code_system.name()
-            && !is_pascal_case(&name)
-        {
+    let names = &options.names;
+    let ids = &options.ids;
+    let mut entities: Vec<(&str, Option<String>, Option<IdClause>, FshSyntaxNode)> = Vec::new();
+    entities.extend(
+        document
+            .profiles()
+            .map(|e| ("Profile", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .extensions()
+            .map(|e| ("Extension", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .value_sets()
+            .map(|e| ("ValueSet", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .code_systems()
+            .map(|e| ("CodeSystem", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .instances()
+            .map(|e| ("Instance", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .logicals()
+            .map(|e| ("Logical", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .resources()
+            .map(|e| ("Resource", e.name(), e.id(), e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .invariants()
+            .map(|e| ("Invariant", e.name(), None, e.syntax().clone())),
+    );
+    entities.extend(
+        document
+            .rule_sets()
+            .map(|e| ("RuleSet", e.name(), None, e.syntax().clone())),
+    );
+
+    for (kind, name, id_clause, node) in entities {
+        let (name_pattern, id_pattern, name_example, id_example) = match kind {
+            "Profile" => (
+                &names.profile,
+                &ids.profile,
+                "'MyProfile' or 'MySpecialProfile'",
+                "'my-profile-id'",
+            ),
+            "Extension" => (
+                &names.extension,
+                &ids.extension,
+                "'MyExtension'",
+                "'my-extension-id'",
+            ),
+            "ValueSet" => (
+                &names.value_set,
+                &ids.value_set,
+                "'MyValueSet'",
+                "'my-value-set-id'",
+            ),
+            "CodeSystem" => (
+                &names.code_system,
+                &ids.code_system,
+                "'MyCodeSystem'",
+                "'my-code-system-id'",
+            ),
+            "Instance" => (&names.instance, &ids.instance, "", ""),
+            "Logical" => (&names.logical, &ids.logical, "", ""),
+            "Resource" => (&names.resource, &ids.resource, "", ""),
+            "Invariant" => (&names.invariant, &None, "", ""),
+            _ => (&names.rule_set, &None, "", ""),
+        };
+        let (default_name, default_id) = if name_example.is_empty() {
+            (Convention::Unchecked, Convention::Unchecked)
+        } else {
+            (
+                Convention::PascalCase {
+                    example: name_example,
+                },
+                Convention::KebabCase {
+                    example: id_example,
+                },
+            )
+        };
+
+        if let Some(name) = name {
             let location = model.source_map.node_to_diagnostic_location(
-                code_system.syntax(),
+                &node,
                 &model.source,
                 &model.source_file,
             );
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!(
-                        "CodeSystem name '{name}' should use PascalCase (e.g., 'MyCodeSystem')"
-                    ),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to PascalCase",
-                    to_pascal_case(&name),
-                    location,
-                )),
-            );
+            let convention = Convention::new(name_pattern, default_name);
+            diagnostics.extend(check_value(kind, "name", &name, "", convention, location));
         }
 
-        if let Some(id_clause) = code_system.id()
+        // Use id_clause.syntax() for the location, not the entity syntax
+        // This ensures the autofix targets the Id line, not the entity line
+        if let Some(id_clause) = id_clause
             && let Some(id) = id_clause.value()
-            && !is_kebab_case(&id)
         {
             let location = model.source_map.node_to_diagnostic_location(
                 id_clause.syntax(),
                 &model.source,
                 &model.source_file,
             );
-
-            let suggested_id = to_kebab_case(&id);
-            let full_replacement = format!("Id: {}", suggested_id);
-
-            diagnostics.push(
-                Diagnostic::new(
-                    NAMING_CONVENTION,
-                    Severity::Warning,
-                    format!(
-                        "CodeSystem ID '{id}' should use kebab-case (e.g., 'my-code-system-id')"
-                    ),
-                    location.clone(),
-                )
-                .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
-                    "Convert to kebab-case",
-                    full_replacement,
-                    location,
-                )),
-            );
+            let convention = Convention::new(id_pattern, default_id);
+            diagnostics.extend(check_value(kind, "ID", &id, "Id: ", convention, location));
         }
     }
 
     diagnostics
 }
 
+/// Check a name or id against its convention
+///
+/// `prefix` goes before the converted value in the autofix, so ids replace
+/// the whole `Id:` line.
+fn check_value(
+    kind: &str,
+    what: &str,
+    value: &str,
+    prefix: &str,
+    convention: Convention,
+    location: maki_core::diagnostics::Location,
+) -> Option<Diagnostic> {
+    match convention {
+        Convention::PascalCase { example } if !is_pascal_case(value) => Some(
+            Diagnostic::new(
+                NAMING_CONVENTION,
+                Severity::Warning,
+                format!("{kind} {what} '{value}' should use PascalCase (e.g., {example})"),
+                location.clone(),
+            )
+            .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
+                "Convert to PascalCase",
+                format!("{prefix}{}", to_pascal_case(value)),
+                location,
+            )),
+        ),
+        Convention::KebabCase { example } if !is_kebab_case(value) => Some(
+            Diagnostic::new(
+                NAMING_CONVENTION,
+                Severity::Warning,
+                format!("{kind} {what} '{value}' should use kebab-case (e.g., {example})"),
+                location.clone(),
+            )
+            .with_suggestion(maki_core::CodeSuggestion::unsafe_fix(
+                "Convert to kebab-case",
+                format!("{prefix}{}", to_kebab_case(value)),
+                location,
+            )),
+        ),
+        Convention::Pattern { pattern, regex } if !regex.is_match(value) => Some(Diagnostic::new(
+            NAMING_CONVENTION,
+            Severity::Warning,
+            format!("{kind} {what} '{value}' doesn't match the configured pattern '{pattern}'"),
+            location,
+        )),
+        _ => None,
+    }
+}
+
 /// Check if a string follows PascalCase convention
 fn is_pascal_case(s: &str) -> bool {
     if s.is_empty() {
@@ -399,7 +430,7 @@ mod tests {
         let source = "Profile: MyProfile\nId: my-profile\n";
         let model = create_test_model(source);
 
-        let diagnostics = check_naming_conventions(&model);
+        let diagnostics = check_naming_conventions(&model, &NamingConventionOptions::default());
         assert_eq!(
             diagnostics.len(),
             0,
@@ -412,7 +443,7 @@ mod tests {
         let source = "Profile: my_bad_profile\n";
         let model = create_test_model(source);
 
-        let diagnostics = check_naming_conventions(&model);
+        let diagnostics = check_naming_conventions(&model, &NamingConventionOptions::default());
         assert_eq!(diagnostics.len(), 1);
         assert_eq!(diagnostics[0].rule_id, NAMING_CONVENTION);
         assert_eq!(diagnostics[0].severity, Severity::Warning);
@@ -425,7 +456,7 @@ mod tests {
         let source = "Profile: MyProfile\nId: My_Bad_ID\n";
         let model = create_test_model(source);
 
-        let diagnostics = check_naming_conventions(&model);
+        let diagnostics = check_naming_conventions(&model, &NamingConventionOptions::default());
         assert_eq!(diagnostics.len(), 1);
         assert_eq!(diagnostics[0].rule_id, NAMING_CONVENTION);
         assert!(diagnostics[0].message.contains("kebab-case"));
@@ -437,7 +468,7 @@ mod tests {
         let source = "Extension: bad_extension\nId: BadID\n";
         let model = create_test_model(source);
 
-        let diagnostics = check_naming_conventions(&model);
+        let diagnostics = check_naming_conventions(&model, &NamingConventionOptions::default());
         assert_eq!(diagnostics.len(), 2, "Should flag both bad name and bad ID");
 
         // Check that both violations are reported
@@ -453,12 +484,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_configured_patterns() {
+        let source = "Profile: MyPatient\nId: my-patient\n\nProfile: USCorePatientProfile\nId: us-core-patient\n\nInstance: patient_example\nInstanceOf: MyProfile\n\nExtension: bad_extension\n";
+        let model = create_test_model(source);
+        let options: NamingConventionOptions = serde_json::from_value(serde_json::json!({
+            "names": { "profile": "[A-Z][A-Za-z]*Profile", "instance": "[a-z][a-z-]*" },
+            "ids": { "profile": "us-core-[a-z-]+" }
+        }))
+        .unwrap();
+
+        let diagnostics = check_naming_conventions(&model, &options);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages.len(), 4, "{messages:?}");
+        assert!(messages[0].starts_with("Profile name 'MyPatient' doesn't match"));
+        assert!(messages[1].starts_with("Profile ID 'my-patient' doesn't match"));
+        // Kinds without a pattern keep the default convention
+        assert!(messages[2].contains("should use PascalCase"));
+        assert!(messages[3].starts_with("Instance name 'patient_example' doesn't match"));
+        assert!(diagnostics[0].suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let options: NamingConventionOptions =
+            serde_json::from_value(serde_json::json!({ "names": { "profile": "([A-Z]" } }))
+                .unwrap();
+        assert!(options.validate().unwrap_err().contains("invalid regex"));
+        assert!(
+            serde_json::from_value::<NamingConventionOptions>(
+                serde_json::json!({ "names": { "profiles": "[A-Z]" } })
+            )
+            .is_err()
+        );
+    }
+
     #[test]
     fn test_value_set_and_code_system_naming() {
         let source = "ValueSet: bad_value_set\nCodeSystem: Bad_Code_System\n";
         let model = create_test_model(source);
 
-        let diagnostics = check_naming_conventions(&model);
+        let diagnostics = check_naming_conventions(&model, &NamingConventionOptions::default());
         assert_eq!(
             diagnostics.len(),
             2,
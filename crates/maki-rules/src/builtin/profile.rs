@@ -50,8 +50,16 @@ use crate::fhir_registry::{
     FhirVersion, is_canonical_url, is_fhir_resource, is_likely_external_profile,
     validate_canonical_url,
 };
-use maki_core::cst::ast::{AstNode, Document};
-use maki_core::{Diagnostic, SemanticModel, Severity};
+use crate::project::ProjectContext;
+use maki_core::LazySession;
+use maki_core::canonical::fishable::{FhirType, Fishable};
+use maki_core::cst::FshSyntaxNode;
+use maki_core::cst::ast::{AstNode, ContainsRule, Document, Profile, Rule};
+use maki_core::semantic::{DependencyType, ResourceType};
+use maki_core::{CodeSuggestion, Diagnostic, SemanticModel, Severity};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Rule ID for profile assignment validation
 pub const PROFILE_PARENT_VALID: &str = "correctness/profile-parent-valid";
@@ -59,11 +67,12 @@ pub const PROFILE_ASSIGNMENT_PRESENT: &str = "correctness/profile-assignment-pre
 pub const EXTENSION_CONTEXT_MISSING: &str = "correctness/extension-context-missing";
 pub const SLICE_NAME_COLLISION: &str = "correctness/slice-name-collision";
 pub const MUST_SUPPORT_PROPAGATION: &str = "suspicious/must-support-propagation";
-
-/// Check profile assignments
-pub fn check_profile_assignments(model: &SemanticModel) -> Vec<Diagnostic> {
-    check_profile_assignments_with_version(model, FhirVersion::R4)
-}
+/// Rule ID for InstanceOf, `only` and extension references missing from loaded packages
+pub const UNRESOLVED_REFERENCE: &str = "correctness/unresolved-reference";
+/// Rule IDs reported by [`check_profile_assignments`]
+pub const PROFILE_REFERENCE_RULES: [&str; 2] = [PROFILE_ASSIGNMENT_PRESENT, UNRESOLVED_REFERENCE];
+/// Rule ID for profiles whose parent chain loops back to themselves
+pub const PROFILE_CIRCULAR_PARENT: &str = "correctness/profile-circular-parent";
 
 /// Check profile assignments with specific FHIR version
 ///
@@ -145,28 +154,517 @@ pub fn check_profile_assignments_with_version(
             }
         } else {
             // Profile missing parent - this is an error
-            if let Some(name) = profile.name() {
-                let location = model.source_map.node_to_diagnostic_location(
-                    profile.syntax(),
-                    &model.source,
-                    &model.source_file,
-                );
-                diagnostics.push(
-                    Diagnostic::new(
-                        PROFILE_ASSIGNMENT_PRESENT,
-                        Severity::Error,
-                        format!("Profile '{name}' must specify a Parent"),
-                        location,
-                    )
-                    .with_code("missing-profile-parent".to_string()),
-                );
+            diagnostics.extend(missing_parent_diagnostic(&profile, model));
+        }
+    }
+
+    diagnostics
+}
+
+fn missing_parent_diagnostic(profile: &Profile, model: &SemanticModel) -> Option<Diagnostic> {
+    let name = profile.name()?;
+    let location = model.source_map.node_to_diagnostic_location(
+        profile.syntax(),
+        &model.source,
+        &model.source_file,
+    );
+    Some(
+        Diagnostic::new(
+            PROFILE_ASSIGNMENT_PRESENT,
+            Severity::Error,
+            format!("Profile '{name}' must specify a Parent"),
+            location,
+        )
+        .with_code("missing-profile-parent".to_string()),
+    )
+}
+
+/// Check for parent chains that loop, across all project files
+pub fn check_circular_parents(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let display_name = |id: &str| {
+        project
+            .resource(id)
+            .and_then(|r| r.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    };
+
+    for cycle in project
+        .dependencies
+        .find_cycles_of_type(DependencyType::Parent)
+    {
+        let members: HashSet<&String> = cycle.iter().collect();
+        for (_, resource) in project.resources() {
+            if !members.contains(&resource.id) || resource.parent.is_none() {
+                continue;
             }
+
+            // Follow the parents from this resource until the chain closes
+            let mut chain = vec![resource.id.clone()];
+            while let Some(parent) = project
+                .dependencies
+                .get_dependencies_of_type(chain.last().unwrap(), DependencyType::Parent)
+                .into_iter()
+                .find(|parent| members.contains(&parent.to_string()))
+            {
+                let closed = parent == resource.id || chain.iter().any(|id| id == parent);
+                chain.push(parent.to_string());
+                if closed {
+                    break;
+                }
+            }
+            let chain: Vec<String> = chain.iter().map(|id| display_name(id)).collect();
+
+            let kind = match resource.resource_type {
+                ResourceType::Extension => "Extension",
+                ResourceType::Logical => "Logical",
+                _ => "Profile",
+            };
+            diagnostics.push(
+                Diagnostic::new(
+                    PROFILE_CIRCULAR_PARENT,
+                    Severity::Error,
+                    format!(
+                        "{} '{}' has a circular parent chain: {}",
+                        kind,
+                        display_name(&resource.id),
+                        chain.join(" → ")
+                    ),
+                    resource.location.clone(),
+                )
+                .with_code("circular-parent".to_string()),
+            );
         }
     }
 
     diagnostics
 }
 
+/// Where a definition is referenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReferenceKind {
+    Parent,
+    InstanceOf,
+    OnlyTarget,
+    /// `only Canonical(...)` target, which may be any kind of resource
+    CanonicalTarget,
+    Extension,
+}
+
+impl ReferenceKind {
+    fn label(self) -> &'static str {
+        match self {
+            ReferenceKind::Parent => "Parent",
+            ReferenceKind::InstanceOf => "InstanceOf",
+            ReferenceKind::OnlyTarget | ReferenceKind::CanonicalTarget => "Type",
+            ReferenceKind::Extension => "Extension",
+        }
+    }
+
+    /// Resources the reference may resolve to
+    fn fhir_types(self) -> &'static [FhirType] {
+        match self {
+            ReferenceKind::CanonicalTarget => &[FhirType::Any],
+            _ => &[FhirType::StructureDefinition],
+        }
+    }
+
+    /// Resource types listed for "did you mean" suggestions
+    fn candidate_resource_types(self) -> &'static [&'static str] {
+        match self {
+            ReferenceKind::CanonicalTarget => &["StructureDefinition", "ValueSet", "CodeSystem"],
+            _ => &["StructureDefinition"],
+        }
+    }
+
+    fn rule_id(self) -> &'static str {
+        match self {
+            ReferenceKind::Parent => PROFILE_PARENT_VALID,
+            _ => UNRESOLVED_REFERENCE,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            ReferenceKind::Parent => "unresolved-profile-parent",
+            ReferenceKind::InstanceOf => "unresolved-instance-of",
+            ReferenceKind::OnlyTarget | ReferenceKind::CanonicalTarget => "unresolved-type",
+            ReferenceKind::Extension => "unresolved-extension",
+        }
+    }
+}
+
+/// A definition reference by name, id or URL
+struct DefinitionReference {
+    kind: ReferenceKind,
+    /// Reference as written in FSH
+    value: String,
+    node: FshSyntaxNode,
+}
+
+/// Check Parent, InstanceOf, `only` and extension references
+///
+/// References that aren't FHIR base resources or FSH definitions (in this
+/// file or in `global_definitions`) are resolved against the loaded
+/// packages by URL, id and name: `Canonical(...)` targets against resources
+/// of any type, the others against StructureDefinitions. Unresolved references are errors with "did
+/// you mean" suggestions. Without packages, Parent values fall back to the
+/// name heuristics of [`check_profile_assignments_with_version`].
+pub async fn check_profile_assignments(
+    model: &SemanticModel,
+    lazy_session: Option<&Arc<LazySession>>,
+    global_definitions: Option<&HashSet<String>>,
+    fhir_version: FhirVersion,
+) -> Vec<Diagnostic> {
+    let Some(document) = Document::cast(model.cst.clone()) else {
+        return Vec::new();
+    };
+
+    let mut local = definition_names(&document);
+    if let Some(global) = global_definitions {
+        local.extend(global.iter().cloned());
+    }
+
+    // Collect before any await; CST iterators are not Send
+    let mut diagnostics: Vec<Diagnostic> = document
+        .profiles()
+        .filter(|profile| profile.parent().is_none())
+        .filter_map(|profile| missing_parent_diagnostic(&profile, model))
+        .collect();
+    let external: Vec<DefinitionReference> = collect_references(&document, model)
+        .into_iter()
+        .filter(|reference| !is_local_reference(&reference.value, model, &local, fhir_version))
+        .collect();
+    if external.is_empty() {
+        return diagnostics;
+    }
+
+    let session = match lazy_session {
+        Some(lazy) => lazy.get().await.ok(),
+        None => None,
+    };
+    let Some(session) = session else {
+        return check_profile_assignments_with_version(model, fhir_version);
+    };
+
+    // Suggestion candidates by the resource types they list
+    let mut candidates: HashMap<&[&str], Vec<String>> = HashMap::new();
+    for reference in external {
+        let target = model.aliases.resolve_or_original(&reference.value);
+        if is_canonical_url(target)
+            && reference.kind == ReferenceKind::Parent
+            && let Err(error_msg) = validate_canonical_url(target)
+        {
+            let location = model.source_map.node_to_diagnostic_location(
+                &reference.node,
+                &model.source,
+                &model.source_file,
+            );
+            diagnostics.push(
+                Diagnostic::new(
+                    PROFILE_PARENT_VALID,
+                    Severity::Error,
+                    format!("Invalid canonical URL format: {error_msg}\n  Help: Ensure the URL follows the pattern: http(s)://domain/StructureDefinition/profile-id"),
+                    location,
+                )
+                .with_code("invalid-profile-parent".to_string()),
+            );
+            continue;
+        }
+
+        // Versioned canonicals (`url|4.0.1`) resolve by URL
+        let target = target.split('|').next().unwrap_or(target);
+        match session.fish(target, reference.kind.fhir_types()).await {
+            Ok(Some(_)) => continue,
+            Ok(None) => {}
+            Err(e) => {
+                tracing::debug!("Failed to fish for '{}': {}", target, e);
+                continue;
+            }
+        }
+
+        let resource_types = reference.kind.candidate_resource_types();
+        let candidates = match candidates.entry(resource_types) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut names: Vec<String> = local.iter().cloned().collect();
+                for resource_type in resource_types {
+                    match session
+                        .fishable_metadata(resource_type, reference.kind.fhir_types())
+                        .await
+                    {
+                        Ok(metadata) => names.extend(
+                            metadata
+                                .into_iter()
+                                .flat_map(|m| [m.name, m.id, m.url])
+                                .flatten(),
+                        ),
+                        Err(e) => tracing::debug!("Failed to list {}s: {}", resource_type, e),
+                    }
+                }
+                names.sort();
+                names.dedup();
+                entry.insert(names)
+            }
+        };
+        let suggestions = did_you_mean(&reference.value, candidates);
+        diagnostics.push(unresolved_reference_diagnostic(
+            &reference,
+            &suggestions,
+            model,
+        ));
+    }
+
+    diagnostics
+}
+
+fn unresolved_reference_diagnostic(
+    reference: &DefinitionReference,
+    suggestions: &[String],
+    model: &SemanticModel,
+) -> Diagnostic {
+    let mut message = format!(
+        "{} '{}' was not found in FSH definitions or loaded packages",
+        reference.kind.label(),
+        reference.value
+    );
+    if !suggestions.is_empty() {
+        let quoted: Vec<String> = suggestions.iter().map(|s| format!("'{s}'")).collect();
+        message.push_str(&format!("\n  Help: Did you mean {}?", quoted.join(", ")));
+    }
+
+    let location = model.source_map.node_to_diagnostic_location(
+        &reference.node,
+        &model.source,
+        &model.source_file,
+    );
+    let mut diagnostic =
+        Diagnostic::new(reference.kind.rule_id(), Severity::Error, message, location)
+            .with_code(reference.kind.code().to_string());
+
+    // Replace just the reference text within the clause or rule
+    let node_start = usize::from(reference.node.text_range().start());
+    if let Some(best) = suggestions.first()
+        && let Some(offset) = reference.node.text().to_string().find(&reference.value)
+    {
+        let span = node_start + offset..node_start + offset + reference.value.len();
+        let value_location =
+            model
+                .source_map
+                .span_to_diagnostic_location(&span, &model.source, &model.source_file);
+        diagnostic = diagnostic.with_suggestion(CodeSuggestion::unsafe_fix(
+            format!("Replace with '{best}'"),
+            best.clone(),
+            value_location,
+        ));
+    }
+    diagnostic
+}
+
+/// Names and ids of the definitions in `document`
+///
+/// Besides StructureDefinitions this includes ValueSets, CodeSystems and
+/// instances, which `Canonical(...)` types may point at.
+pub fn definition_names(document: &Document) -> HashSet<String> {
+    let mut local = HashSet::new();
+    for profile in document.profiles() {
+        local.extend(profile.name());
+        local.extend(profile.id().and_then(|id| id.value()));
+    }
+    for extension in document.extensions() {
+        local.extend(extension.name());
+        local.extend(extension.id().and_then(|id| id.value()));
+    }
+    for logical in document.logicals() {
+        local.extend(logical.name());
+        local.extend(logical.id().and_then(|id| id.value()));
+    }
+    for resource in document.resources() {
+        local.extend(resource.name());
+        local.extend(resource.id().and_then(|id| id.value()));
+    }
+    for value_set in document.value_sets() {
+        local.extend(value_set.name());
+        local.extend(value_set.id().and_then(|id| id.value()));
+    }
+    for code_system in document.code_systems() {
+        local.extend(code_system.name());
+        local.extend(code_system.id().and_then(|id| id.value()));
+    }
+    local.extend(document.instances().filter_map(|instance| instance.name()));
+    local
+}
+
+/// Parent, InstanceOf, `only` and extension references in `document`
+///
+/// Inside `Extension:` definitions, a bare `contains` item names an inline
+/// sub-extension rather than another definition, so only items using `named`
+/// or an alias or URL are references there.
+fn collect_references(document: &Document, model: &SemanticModel) -> Vec<DefinitionReference> {
+    let mut references = Vec::new();
+    let mut push = |kind, value: String, node: &FshSyntaxNode| {
+        references.push(DefinitionReference {
+            kind,
+            value,
+            node: node.clone(),
+        })
+    };
+
+    // Rules of each definition, and whether the definition is an Extension
+    let mut rule_lists: Vec<(Vec<Rule>, bool)> = Vec::new();
+    for profile in document.profiles() {
+        if let Some(parent) = profile.parent()
+            && let Some(value) = parent.value()
+        {
+            push(ReferenceKind::Parent, value, parent.syntax());
+        }
+        rule_lists.push((profile.rules().collect(), false));
+    }
+    for extension in document.extensions() {
+        if let Some(parent) = extension.parent()
+            && let Some(value) = parent.value()
+        {
+            push(ReferenceKind::Parent, value, parent.syntax());
+        }
+        rule_lists.push((extension.rules().collect(), true));
+    }
+    for instance in document.instances() {
+        if let Some(instance_of) = instance.instance_of()
+            && let Some(value) = instance_of.value()
+        {
+            push(ReferenceKind::InstanceOf, value, instance_of.syntax());
+        }
+    }
+
+    for (rules, in_extension) in rule_lists {
+        for rule in rules {
+            match rule {
+                Rule::Only(only) => {
+                    for (target, canonical) in only_targets(&only.syntax().text().to_string()) {
+                        let kind = if canonical {
+                            ReferenceKind::CanonicalTarget
+                        } else {
+                            ReferenceKind::OnlyTarget
+                        };
+                        push(kind, target, only.syntax());
+                    }
+                }
+                Rule::Contains(contains) if is_extension_path(&contains) => {
+                    for (extension, slice_name) in contains.items_with_types() {
+                        let inline = extension == slice_name
+                            && !model.aliases.is_alias(&extension)
+                            && !is_canonical_url(&extension);
+                        if !(in_extension && inline) {
+                            push(ReferenceKind::Extension, extension, contains.syntax());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    references
+}
+
+/// Types named by an `only` rule
+///
+/// `only Quantity or Reference(Patient | Group)` gives `Quantity`, `Patient`
+/// and `Group`; `Reference`, `Canonical` and `CodeableReference` wrappers are
+/// skipped. Each target is paired with whether it sits in a `Canonical(...)`.
+fn only_targets(text: &str) -> Vec<(String, bool)> {
+    let text = text.split("//").next().unwrap_or(text);
+    let text = text.trim_start().strip_prefix("only").unwrap_or(text);
+    let spaced = text
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('|', " ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+
+    let mut wrappers: Vec<&str> = Vec::new();
+    let mut targets = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match *token {
+            "or" | "(" => {}
+            ")" => {
+                wrappers.pop();
+            }
+            _ if tokens.get(index + 1) == Some(&"(") => wrappers.push(token),
+            _ => targets.push((token.to_string(), wrappers.last() == Some(&"Canonical"))),
+        }
+    }
+    targets
+}
+
+fn is_extension_path(contains: &ContainsRule) -> bool {
+    contains.path().is_some_and(|path| {
+        let path = path.syntax().text().to_string();
+        let last = path.trim().rsplit('.').next().unwrap_or("");
+        let last = last.split('[').next().unwrap_or(last);
+        last == "extension" || last == "modifierExtension"
+    })
+}
+
+/// Whether `value` is a FHIR base resource or an FSH definition
+fn is_local_reference(
+    value: &str,
+    model: &SemanticModel,
+    local: &HashSet<String>,
+    fhir_version: FhirVersion,
+) -> bool {
+    let value = model.aliases.resolve_or_original(value);
+    let name = if is_canonical_url(value) {
+        // Canonicals of this project end in the id of a local definition
+        value
+            .split('|')
+            .next()
+            .and_then(|url| url.rsplit('/').next())
+            .unwrap_or(value)
+    } else {
+        value
+    };
+    (!is_canonical_url(value) && is_fhir_resource(name, fhir_version))
+        || local.contains(name)
+        || model.symbols.contains_symbol(name)
+        || model
+            .resources
+            .iter()
+            .any(|r| r.name.as_deref() == Some(name))
+}
+
+/// Up to three `candidates` close to `value`, closest first
+fn did_you_mean(value: &str, candidates: &[String]) -> Vec<String> {
+    let value_lower = value.to_lowercase();
+    let max_distance = (value.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &String)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&value_lower, &candidate.to_lowercase());
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 /// Result of parent validation
 enum ParentValidationResult {
     Valid,
@@ -630,4 +1128,113 @@ Parent: Patient
             "Should not warn when parent has no MS"
         );
     }
+
+    #[test]
+    fn test_only_targets() {
+        assert_eq!(
+            only_targets("only Quantity or Reference(Patient | Group) // units"),
+            vec![
+                ("Quantity".to_string(), false),
+                ("Patient".to_string(), false),
+                ("Group".to_string(), false)
+            ]
+        );
+        assert_eq!(
+            only_targets("only CodeableReference(DiagnosticReport or Observation)"),
+            vec![
+                ("DiagnosticReport".to_string(), false),
+                ("Observation".to_string(), false)
+            ]
+        );
+        assert_eq!(
+            only_targets("only Canonical(MyValueSet or Questionnaire)"),
+            vec![
+                ("MyValueSet".to_string(), true),
+                ("Questionnaire".to_string(), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = vec![
+            "USCorePatientProfile".to_string(),
+            "USCoreEncounterProfile".to_string(),
+            "Patient".to_string(),
+        ];
+        assert_eq!(
+            did_you_mean("UsCorePatientProfil", &candidates),
+            vec!["USCorePatientProfile"]
+        );
+        assert!(did_you_mean("Medication", &candidates).is_empty());
+    }
+
+    #[test]
+    fn test_collect_references_skips_local_definitions() {
+        let source = r#"
+Extension: MyExt
+Context: Patient
+
+Profile: MyPatient
+Parent: USCorePatientProfile
+* extension contains MyExt named myExt 0..1 and OtherExt named other 0..1
+* managingOrganization only Reference(MyOrg)
+* extension[myExt].valueCanonical only Canonical(MyValueSet or OtherValueSet)
+
+ValueSet: MyValueSet
+
+Instance: Example
+InstanceOf: MyPatient
+"#;
+        let model = create_test_model(source);
+        let document = Document::cast(model.cst.clone()).unwrap();
+        let local = definition_names(&document);
+
+        let external: Vec<(ReferenceKind, String)> = collect_references(&document, &model)
+            .into_iter()
+            .filter(|r| !is_local_reference(&r.value, &model, &local, FhirVersion::R4))
+            .map(|r| (r.kind, r.value))
+            .collect();
+        assert_eq!(
+            external,
+            vec![
+                (ReferenceKind::Parent, "USCorePatientProfile".to_string()),
+                (ReferenceKind::Extension, "OtherExt".to_string()),
+                (ReferenceKind::OnlyTarget, "MyOrg".to_string()),
+                (ReferenceKind::CanonicalTarget, "OtherValueSet".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_references_skips_inline_sub_extensions() {
+        let source = r#"
+Alias: $Race = http://hl7.org/fhir/us/core/StructureDefinition/us-core-race
+
+Extension: ComplexExt
+Context: Patient
+* extension contains component1 0..1 and BrandName 0..1 and $Race named race 0..1
+* extension contains http://example.org/StructureDefinition/shared named shared 0..1
+
+Profile: MyPatient
+Parent: Patient
+* extension contains handedness 0..1
+"#;
+        let model = create_test_model(source);
+        let document = Document::cast(model.cst.clone()).unwrap();
+
+        let extensions: Vec<String> = collect_references(&document, &model)
+            .into_iter()
+            .filter(|r| r.kind == ReferenceKind::Extension)
+            .map(|r| r.value)
+            .collect();
+        assert_eq!(
+            extensions,
+            vec![
+                "handedness",
+                "$Race",
+                "http://example.org/StructureDefinition/shared"
+            ]
+        );
+    }
 }
@@ -7,6 +7,7 @@
 //! - Description: Recommended (auto-generated with TODO placeholder)
 //! - Context: Required for Extensions (defines where they can be used)
 
+use maki_core::cst::FshSyntaxKind;
 use maki_core::cst::ast::{AstNode, Document};
 use maki_core::diagnostics::Location;
 use maki_core::{CodeSuggestion, Diagnostic, SemanticModel, Severity};
@@ -401,9 +402,14 @@ pub fn check_extension_context(model: &SemanticModel) -> Vec<Diagnostic> {
         // Check if extension has ^context rules
         // In FSH, context is defined with: * ^context[+].type = #element
         // We check the entire extension's syntax text since the ^context appears
-        // in the rule statement, not just the rule value
+        // in the rule statement, not just the rule value. The `Context:` keyword
+        // declares contexts too.
         let extension_text = extension.syntax().text().to_string();
-        let has_context = extension_text.contains("^context");
+        let has_context = extension_text.contains("^context")
+            || extension
+                .syntax()
+                .descendants_with_tokens()
+                .any(|element| element.kind() == FshSyntaxKind::ContextKw);
 
         if !has_context {
             let extension_name = extension
@@ -1053,6 +1059,22 @@ Description: "An extension with proper context"
         assert_eq!(diagnostics.len(), 0, "Extension with context should pass");
     }
 
+    #[test]
+    fn test_extension_with_context_keyword() {
+        let source = r#"Extension: MyExtension
+Context: Patient, Observation.code
+* value[x] only string
+"#;
+        let model = create_test_model(source);
+        let diagnostics = check_extension_context(&model);
+
+        assert_eq!(
+            diagnostics.len(),
+            0,
+            "Context: keyword should count as context"
+        );
+    }
+
     #[test]
     fn test_extension_context_autofix_suggestion() {
         let source = r#"Extension: BirthPlace
@@ -0,0 +1,1059 @@
+//! Semantic slicing rules
+//!
+//! Builds a [`SlicingConfiguration`] for every element a profile or extension
+//! slices, from its `contains` rules and `^slicing` caret rules, and checks it
+//! against the slicing inherited from the parent:
+//!
+//! - every `contains` is preceded by, or inherits, a `^slicing.discriminator`
+//! - discriminator paths resolve on the sliced element and on each typed slice
+//! - each slice fixes or constrains its discriminator path
+//! - no slices are added under `^slicing.rules = #closed` inherited from the parent
+//! - the slice minimums fit within the sliced element's max
+//!
+//! Parents defined in the same file are followed through FSH; other parents
+//! are looked up in the loaded packages. When neither works (a parent in
+//! another file, no packages) the checks that need the parent are skipped.
+
+use crate::fhir_registry::{FhirVersion, is_fhir_resource};
+use maki_core::canonical::DefinitionSession;
+use maki_core::canonical::fishable::{FhirType, Fishable};
+use maki_core::cst::ast::{AstNode, Document, Rule};
+use maki_core::cst::{FshSyntaxKind, FshSyntaxNode};
+use maki_core::export::{ElementDefinition, StructureDefinition};
+use maki_core::semantic::slicing::{
+    Discriminator, DiscriminatorType, SliceDefinition, SlicingConfiguration, SlicingHandler,
+    SlicingRules,
+};
+use maki_core::{CodeSuggestion, Diagnostic, LazySession, SemanticModel, Severity};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Rule ID for `contains` rules without a discriminator
+pub const SLICING_MISSING_DISCRIMINATOR: &str = "correctness/slicing-missing-discriminator";
+/// Rule ID for discriminator paths that don't exist on the sliced element
+pub const SLICING_UNRESOLVED_DISCRIMINATOR: &str = "correctness/slicing-unresolved-discriminator";
+/// Rule ID for slices that don't constrain their discriminator path
+pub const SLICE_UNCONSTRAINED_DISCRIMINATOR: &str = "correctness/slice-unconstrained-discriminator";
+/// Rule ID for slices added to slicing the parent closed
+pub const SLICING_CLOSED_CONFLICT: &str = "correctness/slicing-closed-conflict";
+/// Rule ID for slice minimums exceeding the sliced element's max
+pub const SLICE_CARDINALITY_EXCEEDS_MAX: &str = "correctness/slice-cardinality-exceeds-max";
+
+/// All slicing rule IDs
+pub const SLICING_RULES: [&str; 5] = [
+    SLICING_MISSING_DISCRIMINATOR,
+    SLICING_UNRESOLVED_DISCRIMINATOR,
+    SLICE_UNCONSTRAINED_DISCRIMINATOR,
+    SLICING_CLOSED_CONFLICT,
+    SLICE_CARDINALITY_EXCEEDS_MAX,
+];
+
+/// A `contains` rule
+#[derive(Debug, Clone)]
+struct ContainsSite {
+    /// Whole rule line, from `*` to the end of the rule
+    span: Range<usize>,
+    /// Start of the rule's line
+    line_start: usize,
+    /// Indentation of the rule's line
+    indent: String,
+    slices: Vec<SliceDefinition>,
+}
+
+/// Slicing an entity declares on one element
+#[derive(Debug, Clone)]
+struct DeclaredSlicing {
+    config: SlicingConfiguration,
+    /// `^slicing.rules`, when set
+    rules: Option<SlicingRules>,
+    /// Start of the first `^slicing.discriminator` rule
+    discriminator_offset: Option<usize>,
+    contains: Vec<ContainsSite>,
+}
+
+impl DeclaredSlicing {
+    fn new(path: &str) -> Self {
+        Self {
+            config: SlicingConfiguration::new(path.to_string()),
+            rules: None,
+            discriminator_offset: None,
+            contains: Vec::new(),
+        }
+    }
+}
+
+/// Type and path of a discriminator being declared
+type PartialDiscriminator = (Option<String>, Option<String>);
+
+/// How a rule constrains its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Constraint {
+    /// `= value`, `^fixed[x]`, `^pattern[x]`
+    Assigned,
+    /// `from ValueSet`
+    Bound,
+    /// `only Type`
+    Typed,
+    /// `min..max`
+    Card,
+}
+
+/// A profile or extension, reduced to what the slicing checks need
+///
+/// Owned so that it can be held across awaits, unlike CST nodes.
+#[derive(Debug, Clone)]
+struct EntitySlicing {
+    name: String,
+    id: Option<String>,
+    parent: Option<String>,
+    slicings: Vec<DeclaredSlicing>,
+    /// Path and kind of every rule that may fix a discriminator
+    constraints: Vec<(String, Constraint)>,
+    /// `only` types per path
+    types: HashMap<String, Vec<String>>,
+    /// Last cardinality per path, as (min, max)
+    cards: HashMap<String, (u32, String)>,
+    /// Whether RuleSets are inserted, which may constrain slices out of sight
+    has_insert: bool,
+}
+
+impl EntitySlicing {
+    fn slicing(&self, path: &str) -> Option<&DeclaredSlicing> {
+        self.slicings.iter().find(|s| s.config.element_path == path)
+    }
+
+    fn slicing_mut(&mut self, path: &str) -> &mut DeclaredSlicing {
+        match self
+            .slicings
+            .iter()
+            .position(|s| s.config.element_path == path)
+        {
+            Some(index) => &mut self.slicings[index],
+            None => {
+                self.slicings.push(DeclaredSlicing::new(path));
+                self.slicings.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// Slicing of an element as defined by the ancestors of an entity
+#[derive(Debug, Clone, Default)]
+struct Inherited {
+    discriminators: Vec<Discriminator>,
+    rules: Option<SlicingRules>,
+    /// Names and minimums of the inherited slices
+    slices: Vec<(String, u32)>,
+    /// Max of the sliced element
+    max: Option<String>,
+    /// StructureDefinition the ancestry ends in, with the element's path in it
+    base: Option<(Arc<StructureDefinition>, String)>,
+}
+
+/// Check slicing in all profiles and extensions of a file
+///
+/// Reports diagnostics for all [`SLICING_RULES`]; callers filter by rule ID.
+pub async fn check_slicing(
+    model: &SemanticModel,
+    lazy_session: Option<&Arc<LazySession>>,
+) -> Vec<Diagnostic> {
+    let Some(document) = Document::cast(model.cst.clone()) else {
+        return Vec::new();
+    };
+
+    // Collect before any await; CST iterators are not Send
+    let mut entities: Vec<EntitySlicing> = Vec::new();
+    for profile in document.profiles() {
+        if let Some(name) = profile.name() {
+            let id = profile.id().and_then(|c| c.value());
+            let parent = profile.parent().and_then(|p| p.value());
+            entities.push(collect_entity(model, name, id, parent, profile.syntax()));
+        }
+    }
+    for extension in document.extensions() {
+        if let Some(name) = extension.name() {
+            let id = extension.id().and_then(|c| c.value());
+            let parent = extension
+                .parent()
+                .and_then(|p| p.value())
+                .or_else(|| Some("Extension".to_string()));
+            entities.push(collect_entity(model, name, id, parent, extension.syntax()));
+        }
+    }
+    if entities.iter().all(|entity| entity.slicings.is_empty()) {
+        return Vec::new();
+    }
+
+    let session = match lazy_session {
+        Some(lazy) => lazy.get().await.ok(),
+        None => None,
+    };
+
+    let mut diagnostics = Vec::new();
+    for entity in &entities {
+        for slicing in &entity.slicings {
+            if slicing.contains.is_empty() {
+                continue;
+            }
+            let inherited = inherited_slicing(
+                entity,
+                &slicing.config.element_path,
+                &entities,
+                model,
+                session.map(|s| s.as_ref()),
+            )
+            .await;
+            check_element(
+                entity,
+                slicing,
+                inherited.as_ref(),
+                session.map(|s| s.as_ref()),
+                model,
+                &mut diagnostics,
+            )
+            .await;
+        }
+    }
+    diagnostics
+}
+
+fn collect_entity(
+    model: &SemanticModel,
+    name: String,
+    id: Option<String>,
+    parent: Option<String>,
+    node: &FshSyntaxNode,
+) -> EntitySlicing {
+    let mut entity = EntitySlicing {
+        name,
+        id,
+        parent,
+        slicings: Vec::new(),
+        constraints: Vec::new(),
+        types: HashMap::new(),
+        cards: HashMap::new(),
+        has_insert: false,
+    };
+    // Position of the last discriminator per element, for `[=]` and `[+]`
+    let mut discriminator_index: HashMap<String, usize> = HashMap::new();
+    // Type and path of each discriminator per element, until both are known
+    let mut partial: HashMap<String, Vec<PartialDiscriminator>> = HashMap::new();
+
+    for rule in node.children().filter_map(Rule::cast) {
+        let path = rule_path(rule.syntax());
+        match &rule {
+            Rule::Contains(contains) => {
+                let Some(path) = path else { continue };
+                let site =
+                    contains_site(model, rule.syntax(), &contains.syntax().text().to_string());
+                entity.slicing_mut(&path).contains.push(site);
+            }
+            Rule::CaretValue(caret) => {
+                let (Some(path), Some(field)) = (path, caret.field()) else {
+                    continue;
+                };
+                let value = caret.value().unwrap_or_default();
+                let value = value.trim_start_matches('#').to_string();
+                if let Some(rest) = field.strip_prefix("slicing.discriminator") {
+                    let last = discriminator_index.get(&path).copied();
+                    let entries = partial.entry(path.clone()).or_default();
+                    let (index, attribute) = match rest.split_once('.') {
+                        Some((index, attribute)) => (index, attribute),
+                        None => continue,
+                    };
+                    let index = match index {
+                        "" => 0,
+                        "[=]" => last.unwrap_or(0),
+                        "[+]" => last.map_or(0, |i| i + 1),
+                        other => other
+                            .trim_start_matches('[')
+                            .trim_end_matches(']')
+                            .parse()
+                            .unwrap_or(0),
+                    };
+                    discriminator_index.insert(path.clone(), index);
+                    if entries.len() <= index {
+                        entries.resize(index + 1, (None, None));
+                    }
+                    match attribute {
+                        "type" => entries[index].0 = Some(value),
+                        "path" => entries[index].1 = Some(value),
+                        _ => {}
+                    }
+                    let start = usize::from(rule.syntax().text_range().start());
+                    let slicing = entity.slicing_mut(&path);
+                    slicing.discriminator_offset.get_or_insert(start);
+                } else if field == "slicing.rules" {
+                    if let Ok(rules) = SlicingRules::parse(&value) {
+                        entity.slicing_mut(&path).rules = Some(rules);
+                    }
+                } else if field.starts_with("fixed") || field.starts_with("pattern") {
+                    entity.constraints.push((path, Constraint::Assigned));
+                }
+            }
+            Rule::FixedValue(_) => {
+                if let Some(path) = path {
+                    entity.constraints.push((path, Constraint::Assigned));
+                }
+            }
+            Rule::ValueSet(_) => {
+                if let Some(path) = path {
+                    entity.constraints.push((path, Constraint::Bound));
+                }
+            }
+            Rule::Only(only) => {
+                if let Some(path) = path {
+                    entity.types.insert(path.clone(), only.types());
+                    entity.constraints.push((path, Constraint::Typed));
+                }
+            }
+            Rule::Card(card) => {
+                if let (Some(path), Some(cardinality)) = (path, card.cardinality()) {
+                    let min = cardinality.min().unwrap_or(0);
+                    let max = cardinality.max().unwrap_or_else(|| "*".to_string());
+                    entity.cards.insert(path.clone(), (min, max));
+                    entity.constraints.push((path, Constraint::Card));
+                }
+            }
+            Rule::Insert(_) => entity.has_insert = true,
+            _ => {}
+        }
+    }
+
+    for (path, entries) in partial {
+        let slicing = entity.slicing_mut(&path);
+        for (discriminator_type, discriminator_path) in entries {
+            if let (Some(discriminator_type), Some(discriminator_path)) =
+                (discriminator_type, discriminator_path)
+                && let Ok(discriminator_type) = DiscriminatorType::parse(&discriminator_type)
+            {
+                slicing
+                    .config
+                    .discriminators
+                    .push(Discriminator::new(discriminator_type, discriminator_path));
+            }
+        }
+    }
+    for slicing in &mut entity.slicings {
+        if let Some(rules) = slicing.rules {
+            slicing.config.rules = rules;
+            slicing.config.closed = rules == SlicingRules::Closed;
+        }
+        for site in &slicing.contains {
+            slicing.config.slices.extend(site.slices.iter().cloned());
+        }
+    }
+    entity
+}
+
+/// Element path a rule applies to: the `Path` node right before the rule
+fn rule_path(rule: &FshSyntaxNode) -> Option<String> {
+    std::iter::successors(rule.prev_sibling_or_token(), |element| {
+        element.prev_sibling_or_token()
+    })
+    .find(|element| element.kind() != FshSyntaxKind::Whitespace)
+    .and_then(|element| element.into_node())
+    .filter(|node| node.kind() == FshSyntaxKind::Path)
+    .map(|node| node.text().to_string().trim().to_string())
+}
+
+fn contains_site(model: &SemanticModel, rule: &FshSyntaxNode, text: &str) -> ContainsSite {
+    let range = rule.text_range();
+    let start = usize::from(range.start());
+    let line_start = model.source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let end = line_start
+        + model.source[line_start..usize::from(range.end())]
+            .trim_end()
+            .len();
+    let indent: String = model.source[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+
+    ContainsSite {
+        span: line_start + indent.len()..end,
+        line_start,
+        indent,
+        slices: parse_contains_items(text),
+    }
+}
+
+/// Slices of a `contains` rule: `a 1..1 MS and Ext named b 0..1`
+fn parse_contains_items(text: &str) -> Vec<SliceDefinition> {
+    let text = text.trim_start().strip_prefix("contains").unwrap_or(text);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words
+        .split(|word| *word == "and")
+        .filter_map(|item| {
+            let name = match item.iter().position(|word| *word == "named") {
+                Some(named) => item.get(named + 1)?,
+                None => item.first()?,
+            };
+            let (min, max) = item
+                .iter()
+                .find(|word| word.contains(".."))
+                .and_then(|card| SliceDefinition::parse_cardinality(card).ok())
+                .unwrap_or((0, "*".to_string()));
+            Some(SliceDefinition::new(name.to_string(), min, max))
+        })
+        .collect()
+}
+
+fn is_extension_path(path: &str) -> bool {
+    let last = path.rsplit('.').next().unwrap_or(path);
+    let last = last.split('[').next().unwrap_or(last);
+    last == "extension" || last == "modifierExtension"
+}
+
+/// Slicing of `path` as defined by the ancestors of `entity`
+///
+/// Returns `None` when an ancestor can't be resolved.
+async fn inherited_slicing(
+    entity: &EntitySlicing,
+    path: &str,
+    entities: &[EntitySlicing],
+    model: &SemanticModel,
+    session: Option<&DefinitionSession>,
+) -> Option<Inherited> {
+    let mut inherited = Inherited::default();
+    let mut parent = entity.parent.clone();
+    let mut depth = 0;
+
+    while let Some(name) = parent {
+        depth += 1;
+        if depth > 16 {
+            return None;
+        }
+        let Some(local) = entities
+            .iter()
+            .find(|e| e.name == name || e.id.as_deref() == Some(name.as_str()))
+        else {
+            let target = model.aliases.resolve_or_original(&name).to_string();
+            return external_slicing(&target, path, inherited, session).await;
+        };
+
+        if let Some(slicing) = local.slicing(path) {
+            if inherited.discriminators.is_empty() {
+                inherited.discriminators = slicing.config.discriminators.clone();
+            }
+            if inherited.rules.is_none() {
+                inherited.rules = slicing.rules;
+            }
+            inherited.slices.extend(
+                slicing
+                    .config
+                    .slices
+                    .iter()
+                    .map(|slice| (slice.name.clone(), slice.min)),
+            );
+        }
+        if inherited.max.is_none()
+            && let Some((_, max)) = local.cards.get(path)
+        {
+            inherited.max = Some(max.clone());
+        }
+        parent = local.parent.clone();
+    }
+    None
+}
+
+/// Complete `inherited` from the StructureDefinition `parent`
+async fn external_slicing(
+    parent: &str,
+    path: &str,
+    mut inherited: Inherited,
+    session: Option<&DefinitionSession>,
+) -> Option<Inherited> {
+    let Some(session) = session else {
+        // Base resources slice nothing but their extensions
+        if !is_fhir_resource(parent, FhirVersion::R4) && parent != "Extension" {
+            return None;
+        }
+        if inherited.discriminators.is_empty() && is_extension_path(path) {
+            inherited.discriminators = vec![Discriminator::new(
+                DiscriminatorType::Value,
+                "url".to_string(),
+            )];
+        }
+        return Some(inherited);
+    };
+
+    let resource = session
+        .fish(parent, &[FhirType::StructureDefinition])
+        .await
+        .ok()??;
+    let sd: StructureDefinition = serde_json::from_value((*resource.content).clone()).ok()?;
+    let elements = sd
+        .snapshot
+        .as_ref()
+        .map(|s| s.element.as_slice())
+        .unwrap_or(&[]);
+    let Some(element) = find_element(elements, &sd.type_field, path) else {
+        return Some(inherited);
+    };
+
+    if inherited.discriminators.is_empty()
+        && let Some(slicing) = &element.slicing
+    {
+        inherited.discriminators = slicing
+            .discriminator
+            .iter()
+            .flatten()
+            .filter_map(|d| {
+                let discriminator_type = DiscriminatorType::parse(&d.discriminator_type).ok()?;
+                Some(Discriminator::new(discriminator_type, d.path.clone()))
+            })
+            .collect();
+    }
+    if inherited.rules.is_none() {
+        inherited.rules = element
+            .slicing
+            .as_ref()
+            .and_then(|s| s.rules.as_deref())
+            .and_then(|rules| SlicingRules::parse(rules).ok());
+    }
+    if inherited.max.is_none() {
+        inherited.max = element.max.clone();
+    }
+    let element_id = element.id.clone().unwrap_or_else(|| element.path.clone());
+    inherited.slices.extend(elements.iter().filter_map(|e| {
+        let slice =
+            e.id.as_deref()?
+                .strip_prefix(&element_id)?
+                .strip_prefix(':')?;
+        (!slice.contains('.') && !slice.contains(':'))
+            .then(|| (slice.to_string(), e.min.unwrap_or(0)))
+    }));
+    let element_path = element.path.clone();
+    inherited.base = Some((Arc::new(sd), element_path));
+    Some(inherited)
+}
+
+/// Element of a snapshot for an FSH path like `component[sys].code`
+fn find_element<'a>(
+    elements: &'a [ElementDefinition],
+    type_name: &str,
+    path: &str,
+) -> Option<&'a ElementDefinition> {
+    let id = format!("{}.{}", type_name, path.replace('[', ":").replace(']', ""));
+    let plain_path = format!(
+        "{}.{}",
+        type_name,
+        path.split('.')
+            .map(|segment| segment.split('[').next().unwrap_or(segment))
+            .collect::<Vec<_>>()
+            .join(".")
+    );
+    elements
+        .iter()
+        .find(|e| e.id.as_deref() == Some(id.as_str()))
+        .or_else(|| {
+            elements
+                .iter()
+                .find(|e| e.path == plain_path && e.slice_name.is_none())
+        })
+}
+
+async fn check_element(
+    entity: &EntitySlicing,
+    slicing: &DeclaredSlicing,
+    inherited: Option<&Inherited>,
+    session: Option<&DefinitionSession>,
+    model: &SemanticModel,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let path = slicing.config.element_path.as_str();
+    let first_contains = &slicing.contains[0];
+    let local = &slicing.config.discriminators;
+
+    // Every contains needs a discriminator, declared first or inherited
+    if let Some(inherited) = inherited
+        && inherited.discriminators.is_empty()
+    {
+        match slicing.discriminator_offset {
+            None => diagnostics.push(missing_discriminator(path, first_contains, model)),
+            Some(offset) if offset > first_contains.span.start => diagnostics.push(
+                Diagnostic::new(
+                    SLICING_MISSING_DISCRIMINATOR,
+                    Severity::Warning,
+                    format!(
+                        "The `^slicing.discriminator` of '{}' is set after its first `contains` rule\n  Help: Move the `^slicing` rules before `* {} contains ...`",
+                        path, path
+                    ),
+                    location(model, &first_contains.span),
+                )
+                .with_code("slicing-discriminator-order".to_string()),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    let discriminators: &[Discriminator] = if local.is_empty() {
+        inherited.map_or(&[], |i| i.discriminators.as_slice())
+    } else {
+        local
+    };
+    let new_slices: Vec<(&ContainsSite, &SliceDefinition)> = slicing
+        .contains
+        .iter()
+        .flat_map(|site| site.slices.iter().map(move |slice| (site, slice)))
+        .filter(|(_, slice)| {
+            !inherited.is_some_and(|i| i.slices.iter().any(|(name, _)| *name == slice.name))
+        })
+        .collect();
+
+    // Local discriminator paths must exist on the element and on typed slices
+    if let (Some(session), Some((sd, element_path))) =
+        (session, inherited.and_then(|i| i.base.as_ref()))
+    {
+        for discriminator in local {
+            if !is_checkable_path(&discriminator.path) {
+                continue;
+            }
+            if resolve_path(session, sd, element_path, &discriminator.path).await == Some(false) {
+                diagnostics.push(unresolved_discriminator(
+                    path,
+                    discriminator,
+                    None,
+                    first_contains,
+                    model,
+                ));
+                continue;
+            }
+            for (site, slice) in &new_slices {
+                let Some(types) = entity.types.get(&format!("{}[{}]", path, slice.name)) else {
+                    continue;
+                };
+                let mut resolved = None;
+                for type_name in types {
+                    let Some(type_sd) = structure_definition(session, type_name).await else {
+                        continue;
+                    };
+                    let start = type_sd.type_field.clone();
+                    let found = resolve_path(session, &type_sd, &start, &discriminator.path).await;
+                    resolved = resolved.or(found);
+                    if found == Some(true) {
+                        break;
+                    }
+                }
+                if resolved == Some(false) {
+                    diagnostics.push(unresolved_discriminator(
+                        path,
+                        discriminator,
+                        Some(&slice.name),
+                        site,
+                        model,
+                    ));
+                }
+            }
+        }
+    }
+
+    // Each new slice must fix what the discriminators look at
+    if !entity.has_insert {
+        for (site, slice) in &new_slices {
+            for discriminator in discriminators {
+                if !slice_constrains(entity, path, slice, discriminator) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            SLICE_UNCONSTRAINED_DISCRIMINATOR,
+                            Severity::Warning,
+                            format!(
+                                "Slice '{}' of '{}' does not constrain its {} discriminator path '{}'\n  Help: {}",
+                                slice.name,
+                                path,
+                                discriminator.discriminator_type,
+                                discriminator.path,
+                                constraint_help(path, &slice.name, discriminator)
+                            ),
+                            location(model, &site.span),
+                        )
+                        .with_code("slice-unconstrained-discriminator".to_string()),
+                    );
+                }
+            }
+        }
+    }
+
+    let Some(inherited) = inherited else {
+        return;
+    };
+
+    // Closed slicing can't take new slices
+    if inherited.rules == Some(SlicingRules::Closed) {
+        for (site, slice) in &new_slices {
+            diagnostics.push(
+                Diagnostic::new(
+                    SLICING_CLOSED_CONFLICT,
+                    Severity::Error,
+                    format!(
+                        "Slice '{}' is added to '{}', but the parent closed its slicing (`^slicing.rules = #closed`)",
+                        slice.name, path
+                    ),
+                    location(model, &site.span),
+                )
+                .with_code("slice-added-to-closed-slicing".to_string()),
+            );
+        }
+    }
+
+    // The slice minimums must fit in the element
+    let max = entity
+        .cards
+        .get(path)
+        .map(|(_, max)| max.clone())
+        .or_else(|| inherited.max.clone());
+    if let Some(max) = max.and_then(|max| max.parse::<u32>().ok()) {
+        let mut minimums: Vec<(String, u32)> = inherited.slices.clone();
+        for slice in &slicing.config.slices {
+            match minimums.iter_mut().find(|(name, _)| *name == slice.name) {
+                Some(existing) => existing.1 = slice.min,
+                None => minimums.push((slice.name.clone(), slice.min)),
+            }
+        }
+        for (name, min) in &mut minimums {
+            if let Some((card_min, _)) = entity.cards.get(&format!("{}[{}]", path, name)) {
+                *min = *card_min;
+            }
+        }
+        let total: u32 = minimums.iter().map(|(_, min)| min).sum();
+        if total > max {
+            let site = slicing.contains.last().unwrap_or(first_contains);
+            diagnostics.push(
+                Diagnostic::new(
+                    SLICE_CARDINALITY_EXCEEDS_MAX,
+                    Severity::Error,
+                    format!(
+                        "Slices of '{}' require at least {} repetitions, but '{}' allows at most {}",
+                        path, total, path, max
+                    ),
+                    location(model, &site.span),
+                )
+                .with_code("slice-cardinality-exceeds-max".to_string()),
+            );
+        }
+    }
+}
+
+fn location(model: &SemanticModel, span: &Range<usize>) -> maki_core::diagnostics::Location {
+    model
+        .source_map
+        .span_to_diagnostic_location(span, &model.source, &model.source_file)
+}
+
+/// Diagnostic for a `contains` without a discriminator, with a fix inserting
+/// the standard slicing rules before it
+fn missing_discriminator(path: &str, site: &ContainsSite, model: &SemanticModel) -> Diagnostic {
+    let element = path.rsplit('.').next().unwrap_or(path);
+    let element = element.split('[').next().unwrap_or(element);
+    let discriminator = if is_extension_path(path) {
+        Discriminator::new(DiscriminatorType::Value, "url".to_string())
+    } else {
+        let inferred_path = SlicingHandler::new()
+            .infer_discriminator(element, "")
+            .map(|d| d.path)
+            .unwrap_or_else(|| "$this".to_string());
+        Discriminator::new(DiscriminatorType::Pattern, inferred_path)
+    };
+    let rules = format!(
+        "{indent}* {path} ^slicing.discriminator.type = #{}\n{indent}* {path} ^slicing.discriminator.path = \"{}\"\n{indent}* {path} ^slicing.rules = #open\n",
+        discriminator.discriminator_type,
+        discriminator.path,
+        indent = site.indent,
+        path = path,
+    );
+    let insert_at = location(model, &(site.line_start..site.line_start));
+
+    Diagnostic::new(
+        SLICING_MISSING_DISCRIMINATOR,
+        Severity::Warning,
+        format!(
+            "'{}' is sliced without a discriminator, and the parent doesn't define one",
+            path
+        ),
+        location(model, &site.span),
+    )
+    .with_code("slicing-missing-discriminator".to_string())
+    .with_suggestion(CodeSuggestion::unsafe_fix(
+        format!(
+            "Slice '{}' by {} on '{}'",
+            path, discriminator.discriminator_type, discriminator.path
+        ),
+        rules,
+        insert_at,
+    ))
+}
+
+fn unresolved_discriminator(
+    path: &str,
+    discriminator: &Discriminator,
+    slice: Option<&str>,
+    site: &ContainsSite,
+    model: &SemanticModel,
+) -> Diagnostic {
+    let target = match slice {
+        Some(slice) => format!("slice '{}' of '{}'", slice, path),
+        None => format!("'{}'", path),
+    };
+    Diagnostic::new(
+        SLICING_UNRESOLVED_DISCRIMINATOR,
+        Severity::Warning,
+        format!(
+            "Discriminator path '{}' does not exist on {}",
+            discriminator.path, target
+        ),
+        location(model, &site.span),
+    )
+    .with_code("slicing-unresolved-discriminator".to_string())
+}
+
+/// Whether a discriminator path can be checked element by element
+///
+/// `$this` always resolves; FHIRPath functions such as `resolve()` or
+/// `extension('url')` are left alone.
+fn is_checkable_path(path: &str) -> bool {
+    path != "$this" && !path.contains('(')
+}
+
+/// Whether `slice` has a rule fixing what `discriminator` looks at
+fn slice_constrains(
+    entity: &EntitySlicing,
+    path: &str,
+    slice: &SliceDefinition,
+    discriminator: &Discriminator,
+) -> bool {
+    // Extension slices are typed by their `contains` item, which fixes the url
+    if is_extension_path(path) && discriminator.path == "url" {
+        return true;
+    }
+    if discriminator.path.contains('(') {
+        return true;
+    }
+
+    let target = if discriminator.path == "$this" {
+        format!("{}[{}]", path, slice.name)
+    } else {
+        format!("{}[{}].{}", path, slice.name, discriminator.path)
+    };
+    let accepted: &[Constraint] = match discriminator.discriminator_type {
+        DiscriminatorType::Value | DiscriminatorType::Pattern => {
+            &[Constraint::Assigned, Constraint::Bound]
+        }
+        DiscriminatorType::Exists => &[Constraint::Card],
+        DiscriminatorType::Type | DiscriminatorType::Profile => &[Constraint::Typed],
+    };
+
+    entity.constraints.iter().any(|(rule_path, constraint)| {
+        accepted.contains(constraint)
+            && (*rule_path == target
+                || rule_path
+                    .strip_prefix(&target)
+                    .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('[')))
+    })
+}
+
+fn constraint_help(path: &str, slice: &str, discriminator: &Discriminator) -> String {
+    let target = if discriminator.path == "$this" {
+        format!("{}[{}]", path, slice)
+    } else {
+        format!("{}[{}].{}", path, slice, discriminator.path)
+    };
+    match discriminator.discriminator_type {
+        DiscriminatorType::Value | DiscriminatorType::Pattern => {
+            format!("Assign or bind it, e.g. `* {} = ...`", target)
+        }
+        DiscriminatorType::Exists => format!("Set its cardinality, e.g. `* {} 1..`", target),
+        DiscriminatorType::Type | DiscriminatorType::Profile => {
+            format!("Restrict its type, e.g. `* {} only ...`", target)
+        }
+    }
+}
+
+async fn structure_definition(
+    session: &DefinitionSession,
+    name: &str,
+) -> Option<StructureDefinition> {
+    let resource = session
+        .fish(name, &[FhirType::StructureDefinition])
+        .await
+        .ok()??;
+    serde_json::from_value((*resource.content).clone()).ok()
+}
+
+/// Whether the dotted `path` exists below `element_path` of `sd`
+///
+/// Steps into the StructureDefinition of an element's datatype when its
+/// children aren't in `sd`. Returns `None` when a datatype can't be loaded.
+async fn resolve_path(
+    session: &DefinitionSession,
+    sd: &StructureDefinition,
+    element_path: &str,
+    path: &str,
+) -> Option<bool> {
+    let mut owned: Option<StructureDefinition> = None;
+    let mut current = element_path.to_string();
+
+    for segment in path.split('.') {
+        let elements = owned
+            .as_ref()
+            .unwrap_or(sd)
+            .snapshot
+            .as_ref()
+            .map(|s| s.element.clone())
+            .unwrap_or_default();
+        if let Some(found) = child_element(&elements, &current, segment) {
+            current = found;
+            continue;
+        }
+
+        // Children of a datatype live in the datatype's StructureDefinition
+        let types: Vec<String> = elements
+            .iter()
+            .find(|e| e.path == current && e.slice_name.is_none())
+            .and_then(|e| e.type_.as_ref())
+            .map(|types| types.iter().map(|t| t.code.clone()).collect())
+            .unwrap_or_default();
+        let [type_name] = types.as_slice() else {
+            return Some(false);
+        };
+        if type_name == "BackboneElement" || type_name == "Element" {
+            return Some(false);
+        }
+        let type_sd = structure_definition(session, type_name).await?;
+        let type_elements = type_sd
+            .snapshot
+            .as_ref()
+            .map(|s| s.element.clone())
+            .unwrap_or_default();
+        match child_element(&type_elements, &type_sd.type_field, segment) {
+            Some(found) => {
+                current = found;
+                owned = Some(type_sd);
+            }
+            None => return Some(false),
+        }
+    }
+    Some(true)
+}
+
+/// Path of the child `segment` of `parent`, matching choice elements (`value[x]`)
+fn child_element(elements: &[ElementDefinition], parent: &str, segment: &str) -> Option<String> {
+    let wanted = format!("{}.{}", parent, segment);
+    elements
+        .iter()
+        .filter(|e| e.slice_name.is_none())
+        .find(|e| {
+            e.path == wanted
+                || e.path.strip_suffix("[x]").is_some_and(|choice| {
+                    wanted.starts_with(choice) && !wanted[choice.len()..].contains('.')
+                })
+        })
+        .map(|e| e.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maki_core::semantic::SemanticAnalyzer;
+    use maki_core::{DefaultSemanticAnalyzer, FshParser};
+    use std::path::PathBuf;
+
+    fn model(source: &str) -> SemanticModel {
+        let parsed = FshParser::parse_content(source).unwrap();
+        DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from("test.fsh"))
+            .unwrap()
+    }
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let model = model(source);
+        tokio_test::block_on(check_slicing(&model, None))
+    }
+
+    fn rule_ids(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_contains_items() {
+        let slices =
+            parse_contains_items("contains systolic 1..1 MS and Ext named ext 0..* and band");
+        let summary: Vec<(&str, u32, &str)> = slices
+            .iter()
+            .map(|s| (s.name.as_str(), s.min, s.max.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("systolic", 1, "1"), ("ext", 0, "*"), ("band", 0, "*")]
+        );
+    }
+
+    #[test]
+    fn test_missing_discriminator_with_fix() {
+        let source = "Profile: BP\nParent: Observation\n* component contains systolic 1..1\n* component[systolic].code = http://loinc.org#8480-6\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![SLICING_MISSING_DISCRIMINATOR]);
+        let fix = &diagnostics[0].suggestions[0];
+        assert_eq!(
+            fix.replacement,
+            "* component ^slicing.discriminator.type = #pattern\n* component ^slicing.discriminator.path = \"$this\"\n* component ^slicing.rules = #open\n"
+        );
+        assert_eq!(fix.location.length, 0);
+        assert_eq!(fix.location.line, 3);
+    }
+
+    #[test]
+    fn test_discriminator_after_contains() {
+        let source = "Profile: BP\nParent: Observation\n* component contains systolic 1..1\n* component ^slicing.discriminator.type = #pattern\n* component ^slicing.discriminator.path = \"code\"\n* component[systolic].code = http://loinc.org#8480-6\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![SLICING_MISSING_DISCRIMINATOR]);
+        assert!(diagnostics[0].message.contains("after its first"));
+    }
+
+    #[test]
+    fn test_well_formed_slicing() {
+        let source = "Profile: BP\nParent: Observation\n* component ^slicing.discriminator[0].type = #pattern\n* component ^slicing.discriminator[=].path = \"code\"\n* component ^slicing.rules = #open\n* component contains systolic 1..1 and diastolic 0..1\n* component[systolic].code = http://loinc.org#8480-6\n* component[diastolic].code from DiastolicVS (required)\n* extension contains MyExt named myExt 0..1\n";
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn test_unconstrained_slice() {
+        let source = "Profile: BP\nParent: Observation\n* component ^slicing.discriminator.type = #pattern\n* component ^slicing.discriminator.path = \"code\"\n* component contains systolic 1..1 and diastolic 0..1\n* component[systolic].code = http://loinc.org#8480-6\n* component[diastolic].value[x] only Quantity\n";
+        let diagnostics = check(source);
+
+        assert_eq!(
+            rule_ids(&diagnostics),
+            vec![SLICE_UNCONSTRAINED_DISCRIMINATOR]
+        );
+        assert!(diagnostics[0].message.contains("'diastolic'"));
+    }
+
+    #[test]
+    fn test_closed_slicing_inherited_from_local_parent() {
+        let source = "Profile: Base\nParent: Observation\n* component ^slicing.discriminator.type = #pattern\n* component ^slicing.discriminator.path = \"code\"\n* component ^slicing.rules = #closed\n* component contains systolic 1..1\n* component[systolic].code = http://loinc.org#8480-6\n\nProfile: Child\nParent: Base\n* component contains extra 0..1\n* component[extra].code = http://loinc.org#1234-5\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![SLICING_CLOSED_CONFLICT]);
+        assert!(diagnostics[0].message.contains("'extra'"));
+        assert_eq!(diagnostics[0].location.line, 11);
+    }
+
+    #[test]
+    fn test_slice_minimums_exceed_max() {
+        let source = "Profile: P\nParent: Patient\n* identifier 0..2\n* identifier ^slicing.discriminator.type = #value\n* identifier ^slicing.discriminator.path = \"system\"\n* identifier contains a 1..1 and b 1..1 and c 0..1\n* identifier[a].system = \"http://a\"\n* identifier[b].system = \"http://b\"\n* identifier[c].system = \"http://c\"\n* identifier[c] 1..1\n";
+        let diagnostics = check(source);
+
+        assert_eq!(rule_ids(&diagnostics), vec![SLICE_CARDINALITY_EXCEEDS_MAX]);
+        assert!(diagnostics[0].message.contains("at least 3"));
+    }
+
+    #[test]
+    fn test_unknown_parent_skips_inheritance_checks() {
+        let source =
+            "Profile: P\nParent: SomeProfileElsewhere\n* component contains systolic 1..1\n";
+        assert!(check(source).is_empty());
+    }
+}
@@ -0,0 +1,947 @@
+//! Terminology rules
+//!
+//! Checks coded values against the ValueSets and CodeSystems they refer to:
+//!
+//! - codes assigned to bound elements must be members of the bound ValueSet,
+//!   when it can be expanded from local FSH or the loaded packages
+//! - codes must exist in their CodeSystem, and displays must match it
+//!
+//! ValueSets and CodeSystems defined in FSH are collected project-wide into a
+//! [`ProjectTerminology`]; package resources are fetched through the
+//! definition session. CodeSystems that aren't complete (`content` other than
+//! `complete`, or built from RuleSets) are never used to report a code.
+
+use maki_core::canonical::DefinitionSession;
+use maki_core::canonical::codesystem::{Code, CodeError, CodeSystem, CodeSystemValidator};
+use maki_core::canonical::fishable::{FhirType, Fishable};
+use maki_core::cst::ast::{AstNode, CaretValueRule, Document, Rule};
+use maki_core::cst::{FshSyntaxKind, FshSyntaxNode};
+use maki_core::export::{LocalConcept, LocalTerminology};
+use maki_core::{CodeSuggestion, Diagnostic, LazySession, SemanticModel, Severity};
+use serde_json::{Value as JsonValue, json};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::binding::BindingStrength;
+
+/// Rule ID for codes outside the ValueSet bound to their element
+pub const BINDING_CODE_NOT_IN_VALUESET: &str = "correctness/binding-code-not-in-valueset";
+/// Rule ID for codes that don't exist in their CodeSystem
+pub const CODE_NOT_IN_CODESYSTEM: &str = "correctness/code-not-in-codesystem";
+/// Rule ID for displays that differ from the CodeSystem
+pub const CODE_DISPLAY_MISMATCH: &str = "correctness/code-display-mismatch";
+
+/// All terminology rule IDs
+pub const TERMINOLOGY_RULES: [&str; 3] = [
+    BINDING_CODE_NOT_IN_VALUESET,
+    CODE_NOT_IN_CODESYSTEM,
+    CODE_DISPLAY_MISMATCH,
+];
+
+/// Maximum depth of ValueSets and CodeSystems fetched for one expansion
+const MAX_FETCH_DEPTH: usize = 8;
+
+/// Aliases, ValueSets and CodeSystems defined in the project's FSH
+///
+/// ValueSets and CodeSystems are kept as FHIR JSON so that
+/// [`LocalTerminology`] can expand them. Those without `^url` get a
+/// placeholder canonical and are found by name or id instead.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectTerminology {
+    aliases: HashMap<String, String>,
+    resources: Vec<JsonValue>,
+    /// Name, id and url of every local ValueSet and CodeSystem, by type
+    canonicals: HashMap<(&'static str, String), String>,
+}
+
+impl ProjectTerminology {
+    /// Collect the terminology of all project files
+    pub fn from_models(models: &[SemanticModel]) -> Self {
+        let documents: Vec<Document> = models
+            .iter()
+            .filter_map(|model| Document::cast(model.cst.clone()))
+            .collect();
+
+        // Aliases first: they are usually in a file of their own
+        let mut terminology = Self::default();
+        for document in &documents {
+            for alias in document.aliases() {
+                if let (Some(name), Some(value)) = (alias_name(alias.syntax()), alias.value()) {
+                    terminology.aliases.insert(name, value);
+                }
+            }
+        }
+        for document in &documents {
+            for code_system in document.code_systems() {
+                terminology.register(
+                    "CodeSystem",
+                    code_system.name(),
+                    code_system.id().and_then(|c| c.value()),
+                    code_system.syntax(),
+                );
+            }
+            for value_set in document.value_sets() {
+                terminology.register(
+                    "ValueSet",
+                    value_set.name(),
+                    value_set.id().and_then(|c| c.value()),
+                    value_set.syntax(),
+                );
+            }
+        }
+        // ValueSets refer to CodeSystems by name, so build them once all are known
+        for document in &documents {
+            for code_system in document.code_systems() {
+                if let Some(resource) = terminology.code_system_json(code_system.syntax()) {
+                    terminology.resources.push(resource);
+                }
+            }
+            for value_set in document.value_sets() {
+                if let Some(resource) = terminology.value_set_json(value_set.syntax()) {
+                    terminology.resources.push(resource);
+                }
+            }
+        }
+        terminology
+    }
+
+    /// Resolve an alias, returning other names unchanged
+    pub fn resolve_alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    /// Whether a ValueSet name, id, alias or url is defined in the project
+    pub fn has_value_set(&self, reference: &str) -> bool {
+        self.canonical("ValueSet", reference).is_some()
+    }
+
+    /// Canonical of a local resource referred to by name, id, alias or url
+    ///
+    /// Urls also match resources without `^url` whose canonical ends in
+    /// `/<type>/<id>`, the form generated from the project canonical.
+    fn canonical(&self, resource_type: &'static str, reference: &str) -> Option<String> {
+        let reference = self.resolve_alias(reference);
+        let reference = reference.split('|').next().unwrap_or(reference);
+        if let Some(url) = self.canonicals.get(&(resource_type, reference.to_string())) {
+            return Some(url.clone());
+        }
+        let (_, id) = reference.rsplit_once(&format!("/{}/", resource_type))?;
+        self.canonicals
+            .get(&(resource_type, id.to_string()))
+            .cloned()
+    }
+
+    fn register(
+        &mut self,
+        resource_type: &'static str,
+        name: Option<String>,
+        id: Option<String>,
+        node: &FshSyntaxNode,
+    ) {
+        let Some(name) = name else { return };
+        let url =
+            entity_url(node).unwrap_or_else(|| format!("urn:maki:{}:{}", resource_type, name));
+        for key in [Some(name), id, Some(url.clone())].into_iter().flatten() {
+            self.canonicals.insert((resource_type, key), url.clone());
+        }
+    }
+
+    fn entity_canonical(
+        &self,
+        resource_type: &'static str,
+        node: &FshSyntaxNode,
+    ) -> Option<String> {
+        let name = node
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| token.kind() == FshSyntaxKind::Ident)?;
+        self.canonical(resource_type, name.text())
+    }
+
+    /// CodeSystem JSON with the concepts of a FSH CodeSystem
+    ///
+    /// Returns `None` when the concepts may be incomplete: RuleSets are
+    /// inserted, or `^content` is set to something other than `#complete`.
+    fn code_system_json(&self, node: &FshSyntaxNode) -> Option<JsonValue> {
+        let url = self.entity_canonical("CodeSystem", node)?;
+        let mut concepts = Vec::new();
+        for child in node.children() {
+            match child.kind() {
+                FshSyntaxKind::InsertRule => return None,
+                FshSyntaxKind::CaretValueRule => {
+                    let caret = CaretValueRule::cast(child)?;
+                    if caret.field().as_deref() == Some("content")
+                        && caret.value().as_deref() != Some("#complete")
+                    {
+                        return None;
+                    }
+                }
+                FshSyntaxKind::Path => {
+                    let Some(code) = child
+                        .children_with_tokens()
+                        .filter_map(|element| element.into_token())
+                        .find(|token| token.kind() == FshSyntaxKind::Code)
+                    else {
+                        continue;
+                    };
+                    let display = child
+                        .next_sibling()
+                        .filter(|rule| rule.kind() == FshSyntaxKind::PathRule)
+                        .and_then(|rule| {
+                            rule.children_with_tokens()
+                                .filter_map(|element| element.into_token())
+                                .find(|token| token.kind() == FshSyntaxKind::String)
+                        })
+                        .map(|token| unquote(token.text()));
+                    let mut concept = json!({ "code": code_value(code.text()) });
+                    if let Some(display) = display {
+                        concept["display"] = json!(display);
+                    }
+                    concepts.push(concept);
+                }
+                _ => {}
+            }
+        }
+        Some(json!({
+            "resourceType": "CodeSystem",
+            "url": url,
+            "content": "complete",
+            "concept": concepts,
+        }))
+    }
+
+    /// ValueSet JSON with the compose of a FSH ValueSet
+    ///
+    /// Filters are kept as a marker, which makes the ValueSet unexpandable.
+    /// Returns `None` when RuleSets are inserted.
+    fn value_set_json(&self, node: &FshSyntaxNode) -> Option<JsonValue> {
+        let url = self.entity_canonical("ValueSet", node)?;
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for child in node.children() {
+            if child.kind() == FshSyntaxKind::InsertRule {
+                return None;
+            }
+            let Some(component) = maki_core::cst::ast::VsComponent::cast(child) else {
+                continue;
+            };
+            let mut entry = serde_json::Map::new();
+            if let Some(concept) = component.concept() {
+                let code_ref = concept.code()?;
+                let system = code_ref
+                    .system()
+                    .or_else(|| concept.from_clause()?.systems().into_iter().next());
+                if let Some(system) = system {
+                    entry.insert("system".to_string(), json!(self.system_url(&system)));
+                }
+                let mut listed = json!({ "code": code_ref.code()? });
+                if let Some(display) = concept.display() {
+                    listed["display"] = json!(display);
+                }
+                entry.insert("concept".to_string(), json!([listed]));
+            } else if let Some(filter) = component.filter() {
+                let from = filter.from_clause()?;
+                if let Some(system) = from.systems().first() {
+                    entry.insert("system".to_string(), json!(self.system_url(system)));
+                }
+                let value_sets: Vec<String> = from
+                    .value_sets()
+                    .iter()
+                    .map(|vs| {
+                        self.canonical("ValueSet", vs)
+                            .unwrap_or_else(|| self.resolve_alias(vs).to_string())
+                    })
+                    .collect();
+                if !value_sets.is_empty() {
+                    entry.insert("valueSet".to_string(), json!(value_sets));
+                }
+                if !filter.filters().is_empty() {
+                    entry.insert("filter".to_string(), json!([]));
+                }
+            } else {
+                continue;
+            }
+            if component.is_exclude() {
+                exclude.push(JsonValue::Object(entry));
+            } else {
+                include.push(JsonValue::Object(entry));
+            }
+        }
+        Some(json!({
+            "resourceType": "ValueSet",
+            "url": url,
+            "compose": { "include": include, "exclude": exclude },
+        }))
+    }
+
+    /// Url of a code system written as a name, alias or url
+    fn system_url(&self, system: &str) -> String {
+        self.canonical("CodeSystem", system)
+            .unwrap_or_else(|| self.resolve_alias(system).to_string())
+    }
+}
+
+/// Alias name as written, including a leading `$`
+pub(crate) fn alias_name(alias: &FshSyntaxNode) -> Option<String> {
+    let text = alias.text().to_string();
+    let name = text.split_once(':')?.1.split('=').next()?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// `^url` of an entity
+fn entity_url(node: &FshSyntaxNode) -> Option<String> {
+    node.children()
+        .filter_map(CaretValueRule::cast)
+        .filter(|caret| is_entity_caret(caret.syntax()))
+        .find(|caret| caret.field().as_deref() == Some("url"))
+        .and_then(|caret| caret.value())
+}
+
+/// Whether a caret rule applies to the entity itself (`* ^url = ...`)
+fn is_entity_caret(rule: &FshSyntaxNode) -> bool {
+    std::iter::successors(rule.prev_sibling_or_token(), |element| {
+        element.prev_sibling_or_token()
+    })
+    .find(|element| element.kind() != FshSyntaxKind::Whitespace)
+    .is_none_or(|element| element.kind() != FshSyntaxKind::Path)
+}
+
+fn unquote(text: &str) -> String {
+    text.trim()
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+        .replace("\\\"", "\"")
+}
+
+fn code_value(text: &str) -> String {
+    unquote(text.trim_start_matches('#'))
+}
+
+/// A coded value written in FSH: `system#code "display"`
+#[derive(Debug, Clone)]
+struct CodedValue {
+    /// System as written (name, alias or url)
+    system: Option<String>,
+    code: String,
+    display: Option<String>,
+    /// Span of the whole value
+    span: Range<usize>,
+    /// Span of the display string, with quotes
+    display_span: Option<Range<usize>>,
+}
+
+/// Parse the coded value of an assignment starting at `start`, if any
+///
+/// Reads to the end of the line: values such as `$LNC#123` are not always
+/// inside the rule node.
+fn coded_value(source: &str, start: usize) -> Option<CodedValue> {
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line = &source[start..line_end];
+    let value_offset = start + line.find('=')? + 1;
+    let value = &source[value_offset..line_end];
+    let code_start = value_offset + (value.len() - value.trim_start().len());
+
+    let rest = &source[code_start..line_end];
+    let code_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let code_text = &rest[..code_len];
+    if code_text.starts_with('"') || code_text.contains("#\"") || !code_text.contains('#') {
+        return None;
+    }
+    let parsed = Code::from_fsh(code_text).ok()?;
+
+    let after = &rest[code_len..];
+    let display_start = code_start + code_len + (after.len() - after.trim_start().len());
+    let display_span = after.trim_start().strip_prefix('"').and_then(|quoted| {
+        let mut escaped = false;
+        quoted.char_indices().find_map(|(i, c)| match c {
+            '\\' if !escaped => {
+                escaped = true;
+                None
+            }
+            '"' if !escaped => Some(display_start..display_start + i + 2),
+            _ => {
+                escaped = false;
+                None
+            }
+        })
+    });
+    let display = display_span.clone().map(|span| unquote(&source[span]));
+    let end = display_span
+        .as_ref()
+        .map_or(code_start + code_len, |span| span.end);
+
+    Some(CodedValue {
+        system: parsed.system,
+        code: parsed.code,
+        display,
+        span: code_start..end,
+        display_span,
+    })
+}
+
+/// A binding in a profile or extension
+#[derive(Debug, Clone)]
+struct Binding {
+    value_set: String,
+    strength: BindingStrength,
+}
+
+/// An assignment of a coded value
+#[derive(Debug, Clone)]
+struct Assignment {
+    /// Entity the assignment is in, and the profile it is checked against
+    bindings_of: String,
+    path: String,
+    value: CodedValue,
+}
+
+/// Everything the checks need from a file, owned so it can be held across awaits
+#[derive(Debug, Default)]
+struct FileTerminology {
+    /// Bindings per entity name and element path
+    bindings: HashMap<String, HashMap<String, Binding>>,
+    assignments: Vec<Assignment>,
+    /// Codes listed in ValueSets, checked against their CodeSystems only
+    listed: Vec<CodedValue>,
+}
+
+fn collect_file(model: &SemanticModel) -> FileTerminology {
+    let mut file = FileTerminology::default();
+    let Some(document) = Document::cast(model.cst.clone()) else {
+        return file;
+    };
+
+    let mut entities: Vec<(String, FshSyntaxNode)> = Vec::new();
+    for profile in document.profiles() {
+        if let Some(name) = profile.name() {
+            entities.push((name, profile.syntax().clone()));
+        }
+    }
+    for extension in document.extensions() {
+        if let Some(name) = extension.name() {
+            entities.push((name, extension.syntax().clone()));
+        }
+    }
+    for (name, node) in &entities {
+        for rule in node.children().filter_map(Rule::cast) {
+            match rule {
+                Rule::ValueSet(binding) => {
+                    let (Some(path), Some(value_set)) = (binding.path(), binding.value_set())
+                    else {
+                        continue;
+                    };
+                    let strength = binding
+                        .strength()
+                        .and_then(|s| BindingStrength::parse(&s))
+                        .unwrap_or(BindingStrength::Required);
+                    file.bindings.entry(name.clone()).or_default().insert(
+                        normalize_path(&path.syntax().text().to_string()),
+                        Binding {
+                            value_set,
+                            strength,
+                        },
+                    );
+                }
+                Rule::FixedValue(fixed) => {
+                    if let Some(assignment) = assignment(model, name, &fixed) {
+                        file.assignments.push(assignment);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for instance in document.instances() {
+        let Some(profile) = instance.instance_of().and_then(|clause| clause.value()) else {
+            continue;
+        };
+        for rule in instance.rules() {
+            if let Rule::FixedValue(fixed) = rule
+                && let Some(assignment) = assignment(model, &profile, &fixed)
+            {
+                file.assignments.push(assignment);
+            }
+        }
+    }
+
+    for value_set in document.value_sets() {
+        for component in value_set
+            .syntax()
+            .children()
+            .filter_map(maki_core::cst::ast::VsComponent::cast)
+        {
+            let Some(concept) = component.concept() else {
+                continue;
+            };
+            let start = usize::from(concept.syntax().text_range().start());
+            // Parse the concept as if it were assigned, to share the display handling
+            let text = format!("={}", &model.source[start..]);
+            if let Some(mut value) = coded_value(&text, 0) {
+                value.span = value.span.start - 1 + start..value.span.end - 1 + start;
+                value.display_span = value
+                    .display_span
+                    .map(|span| span.start - 1 + start..span.end - 1 + start);
+                file.listed.push(value);
+            }
+        }
+    }
+    file
+}
+
+fn assignment(
+    model: &SemanticModel,
+    bindings_of: &str,
+    fixed: &maki_core::cst::ast::FixedValueRule,
+) -> Option<Assignment> {
+    let path = fixed.path()?;
+    let start = usize::from(fixed.syntax().text_range().start());
+    Some(Assignment {
+        bindings_of: bindings_of.to_string(),
+        path: normalize_path(&path.syntax().text().to_string()),
+        value: coded_value(&model.source, start)?,
+    })
+}
+
+/// Element path without instance indices, and with `.coding` of a
+/// CodeableConcept folded into the element the binding is on
+fn normalize_path(path: &str) -> String {
+    let segments: Vec<String> = path
+        .trim()
+        .split('.')
+        .map(|segment| {
+            let base = segment.split('[').next().unwrap_or(segment);
+            match segment[base.len()..]
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+            {
+                "" | "+" | "=" => base.to_string(),
+                index if index.parse::<usize>().is_ok() => base.to_string(),
+                _ => segment.to_string(),
+            }
+        })
+        .collect();
+    let mut path = segments.join(".");
+    if let Some(element) = path.strip_suffix(".coding") {
+        path = element.to_string();
+    }
+    path
+}
+
+/// Check assigned and listed codes against ValueSets and CodeSystems
+///
+/// Reports diagnostics for all [`TERMINOLOGY_RULES`]; callers filter by rule ID.
+/// Without `terminology`, only the current file's FSH terminology is known.
+pub async fn check_terminology(
+    model: &SemanticModel,
+    terminology: Option<&ProjectTerminology>,
+    lazy_session: Option<&Arc<LazySession>>,
+) -> Vec<Diagnostic> {
+    let own;
+    let terminology = match terminology {
+        Some(terminology) => terminology,
+        None => {
+            own = ProjectTerminology::from_models(std::slice::from_ref(model));
+            &own
+        }
+    };
+    let file = collect_file(model);
+    if file.assignments.is_empty() && file.listed.is_empty() {
+        return Vec::new();
+    }
+
+    let session = match lazy_session {
+        Some(lazy) => lazy.get().await.ok(),
+        None => None,
+    };
+    let mut checker = Checker {
+        terminology,
+        session: session.map(|s| s.as_ref()),
+        expansions: HashMap::new(),
+        validator: CodeSystemValidator::new(),
+        unavailable: HashSet::new(),
+    };
+
+    let mut diagnostics = Vec::new();
+    for assignment in &file.assignments {
+        let value = &assignment.value;
+        if let Some(binding) = file
+            .bindings
+            .get(&assignment.bindings_of)
+            .and_then(|bindings| bindings.get(&assignment.path))
+        {
+            checker
+                .check_membership(model, assignment, binding, &mut diagnostics)
+                .await;
+        }
+        checker.check_code(model, value, &mut diagnostics).await;
+    }
+    for value in &file.listed {
+        checker.check_code(model, value, &mut diagnostics).await;
+    }
+    diagnostics
+}
+
+struct Checker<'a> {
+    terminology: &'a ProjectTerminology,
+    session: Option<&'a DefinitionSession>,
+    /// Expansions by ValueSet reference; `None` when not expandable
+    expansions: HashMap<String, Option<Vec<LocalConcept>>>,
+    validator: CodeSystemValidator,
+    /// CodeSystem urls that aren't available complete
+    unavailable: HashSet<String>,
+}
+
+impl Checker<'_> {
+    async fn check_membership(
+        &mut self,
+        model: &SemanticModel,
+        assignment: &Assignment,
+        binding: &Binding,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let severity = match binding.strength {
+            BindingStrength::Required => Severity::Error,
+            BindingStrength::Extensible => Severity::Warning,
+            BindingStrength::Preferred => Severity::Info,
+            BindingStrength::Example => return,
+        };
+        let Some(concepts) = self.expand(&binding.value_set).await else {
+            return;
+        };
+        let value = &assignment.value;
+        let system = value
+            .system
+            .as_deref()
+            .map(|system| self.terminology.system_url(system));
+        let member = concepts.iter().any(|concept| {
+            concept.code == value.code
+                && (system.is_none() || concept.system.is_none() || concept.system == system)
+        });
+        if member {
+            return;
+        }
+
+        let written = match &value.system {
+            Some(system) => format!("{}#{}", system, value.code),
+            None => format!("#{}", value.code),
+        };
+        diagnostics.push(
+            Diagnostic::new(
+                BINDING_CODE_NOT_IN_VALUESET,
+                severity,
+                format!(
+                    "Code '{}' is not in ValueSet '{}', bound ({}) to '{}'",
+                    written,
+                    binding.value_set,
+                    binding.strength.as_str(),
+                    assignment.path
+                ),
+                location(model, &value.span),
+            )
+            .with_code("code-not-in-valueset".to_string()),
+        );
+    }
+
+    async fn check_code(
+        &mut self,
+        model: &SemanticModel,
+        value: &CodedValue,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(system) = &value.system else {
+            return;
+        };
+        let url = self.terminology.system_url(system);
+        if !self.load_code_system(&url).await {
+            return;
+        }
+
+        let code = Code::new(Some(url), value.code.clone(), value.display.clone());
+        match self.validator.validate_code(&code) {
+            Err(CodeError::CodeNotFound { .. }) => diagnostics.push(
+                Diagnostic::new(
+                    CODE_NOT_IN_CODESYSTEM,
+                    Severity::Error,
+                    format!(
+                        "Code '{}' does not exist in CodeSystem '{}'",
+                        value.code, system
+                    ),
+                    location(model, &value.span),
+                )
+                .with_code("unknown-code".to_string()),
+            ),
+            Err(CodeError::DisplayMismatch { expected, actual }) => {
+                let span = value.display_span.clone().unwrap_or(value.span.clone());
+                diagnostics.push(
+                    Diagnostic::new(
+                        CODE_DISPLAY_MISMATCH,
+                        Severity::Warning,
+                        format!(
+                            "Display \"{}\" of code '{}' differs from \"{}\" in CodeSystem '{}'",
+                            actual, value.code, expected, system
+                        ),
+                        location(model, &span),
+                    )
+                    .with_code("display-mismatch".to_string())
+                    .with_suggestion(CodeSuggestion::safe(
+                        format!("Use the CodeSystem display \"{}\"", expected),
+                        format!("\"{}\"", expected.replace('"', "\\\"")),
+                        location(model, &span),
+                    )),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Load a complete CodeSystem into the validator; `false` when unavailable
+    async fn load_code_system(&mut self, url: &str) -> bool {
+        if self.validator.get_code_system(url).is_some() {
+            return true;
+        }
+        if self.unavailable.contains(url) {
+            return false;
+        }
+        let resource =
+            match LocalTerminology::new(&self.terminology.resources).find("CodeSystem", url) {
+                Some(local) => Some(local.clone()),
+                None => self.fetch(url, FhirType::CodeSystem).await,
+            };
+        match resource
+            .filter(is_complete)
+            .and_then(|json| CodeSystem::from_fhir_json(&json).ok())
+        {
+            Some(mut code_system) => {
+                // Fetched by name or version, the validator looks it up by url
+                code_system.url = url.to_string();
+                self.validator.load_code_system(code_system);
+                true
+            }
+            None => {
+                self.unavailable.insert(url.to_string());
+                false
+            }
+        }
+    }
+
+    /// Expand a ValueSet from local FSH, falling back to the loaded packages
+    async fn expand(&mut self, reference: &str) -> Option<Vec<LocalConcept>> {
+        if let Some(expansion) = self.expansions.get(reference) {
+            return expansion.clone();
+        }
+        let expansion = match self.terminology.canonical("ValueSet", reference) {
+            Some(url) => LocalTerminology::new(&self.terminology.resources).expand(&url),
+            None => self.expand_from_packages(reference).await,
+        };
+        self.expansions
+            .insert(reference.to_string(), expansion.clone());
+        expansion
+    }
+
+    /// Fetch a ValueSet and what it includes, then expand them with the local resources
+    async fn expand_from_packages(&self, reference: &str) -> Option<Vec<LocalConcept>> {
+        let value_set = self
+            .fetch(
+                self.terminology.resolve_alias(reference),
+                FhirType::ValueSet,
+            )
+            .await?;
+        let url = value_set.get("url")?.as_str()?.to_string();
+
+        let mut resources = self.terminology.resources.clone();
+        let mut pending = vec![(value_set, 0)];
+        while let Some((resource, depth)) = pending.pop() {
+            if depth < MAX_FETCH_DEPTH {
+                for include in resource
+                    .pointer("/compose/include")
+                    .and_then(JsonValue::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    if let Some(system) = include.get("system").and_then(JsonValue::as_str)
+                        && include.get("concept").is_none()
+                        && !has_resource(&resources, "CodeSystem", system)
+                        && let Some(code_system) = self.fetch(system, FhirType::CodeSystem).await
+                        && is_complete(&code_system)
+                    {
+                        resources.push(code_system);
+                    }
+                    for nested in include
+                        .get("valueSet")
+                        .and_then(JsonValue::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(JsonValue::as_str)
+                    {
+                        if !has_resource(&resources, "ValueSet", nested)
+                            && let Some(nested) = self.fetch(nested, FhirType::ValueSet).await
+                        {
+                            pending.push((nested, depth + 1));
+                        }
+                    }
+                }
+            }
+            resources.push(resource);
+        }
+        LocalTerminology::new(&resources).expand(&url)
+    }
+
+    async fn fetch(&self, reference: &str, fhir_type: FhirType) -> Option<JsonValue> {
+        let session = self.session?;
+        let resource = session.fish(reference, &[fhir_type]).await.ok()??;
+        Some((*resource.content).clone())
+    }
+}
+
+fn has_resource(resources: &[JsonValue], resource_type: &str, url: &str) -> bool {
+    LocalTerminology::new(resources)
+        .find(resource_type, url)
+        .is_some()
+}
+
+/// Whether a CodeSystem lists all its concepts
+fn is_complete(code_system: &JsonValue) -> bool {
+    code_system
+        .get("content")
+        .and_then(JsonValue::as_str)
+        .is_none_or(|content| content == "complete")
+}
+
+fn location(model: &SemanticModel, span: &Range<usize>) -> maki_core::diagnostics::Location {
+    model
+        .source_map
+        .span_to_diagnostic_location(span, &model.source, &model.source_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maki_core::semantic::SemanticAnalyzer;
+    use maki_core::{DefaultSemanticAnalyzer, FshParser};
+    use std::path::PathBuf;
+
+    const TERMINOLOGY: &str = "Alias: $MyCS = http://example.org/CodeSystem/my-cs\n\nCodeSystem: MyCS\nId: my-cs\n* ^url = \"http://example.org/CodeSystem/my-cs\"\n* #a \"Alpha\"\n  * #a1 \"Alpha one\"\n* #b \"Beta\"\n* #c \"Gamma\"\n\nValueSet: MyVS\n* include codes from system MyCS\n* exclude MyCS#c\n\nValueSet: FilteredVS\n* include codes from system MyCS where concept is-a #a\n";
+
+    fn model(file: &str, source: &str) -> SemanticModel {
+        let parsed = FshParser::parse_content(source).unwrap();
+        DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from(file))
+            .unwrap()
+    }
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let model = model("test.fsh", source);
+        tokio_test::block_on(check_terminology(&model, None, None))
+    }
+
+    fn summary(diagnostics: &[Diagnostic]) -> Vec<(&str, Severity)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.rule_id.as_str(), d.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_coded_value() {
+        let source = "* code = $LNC#1234-5 \"Some \\\"quoted\\\" display\" // note";
+        let value = coded_value(source, 7).unwrap();
+        assert_eq!(value.system.as_deref(), Some("$LNC"));
+        assert_eq!(value.code, "1234-5");
+        assert_eq!(value.display.as_deref(), Some("Some \"quoted\" display"));
+        assert_eq!(
+            &source[value.span],
+            "$LNC#1234-5 \"Some \\\"quoted\\\" display\""
+        );
+
+        assert!(coded_value("* text = \"a#b\"", 7).is_none());
+        assert!(coded_value("* valueInteger = 5", 15).is_none());
+    }
+
+    #[test]
+    fn test_local_value_set_membership_by_strength() {
+        let source = format!(
+            "{}\nProfile: P\nParent: Observation\n* code from MyVS (required)\n* code = $MyCS#c\n* category from MyVS (extensible)\n* category = MyCS#a1\n* category.coding = MyCS#c\n* method from MyVS (example)\n* method = MyCS#c\n",
+            TERMINOLOGY
+        );
+        let diagnostics = check(&source);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![
+                (BINDING_CODE_NOT_IN_VALUESET, Severity::Error),
+                (BINDING_CODE_NOT_IN_VALUESET, Severity::Warning),
+            ]
+        );
+        assert!(diagnostics[0].message.contains("'$MyCS#c'"));
+        assert!(diagnostics[1].message.contains("'category'"));
+    }
+
+    #[test]
+    fn test_instance_assignments_use_profile_bindings() {
+        let source = format!(
+            "{}\nProfile: P\nParent: Observation\n* code from MyVS\n\nInstance: I\nInstanceOf: P\n* code.coding[0] = MyCS#c\n* status = #final\n",
+            TERMINOLOGY
+        );
+        assert_eq!(
+            summary(&check(&source)),
+            vec![(BINDING_CODE_NOT_IN_VALUESET, Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn test_unexpandable_value_sets_are_skipped() {
+        let source = format!(
+            "{}\nProfile: P\nParent: Observation\n* code from FilteredVS (required)\n* code = MyCS#b\n* method from http://loinc.org/vs/LL1-1 (required)\n* method = http://loinc.org#1234-5\n",
+            TERMINOLOGY
+        );
+        assert!(check(&source).is_empty());
+    }
+
+    #[test]
+    fn test_codes_and_displays_against_code_system() {
+        let source = format!(
+            "{}\nValueSet: Listed\n* MyCS#b \"Bee\"\n\nInstance: I\nInstanceOf: Observation\n* code = MyCS#zzz\n* category = $MyCS#a1 \"Alpha 1\"\n* method = MyCS#b \"Beta\"\n",
+            TERMINOLOGY
+        );
+        let diagnostics = check(&source);
+
+        assert_eq!(
+            summary(&diagnostics),
+            vec![
+                (CODE_NOT_IN_CODESYSTEM, Severity::Error),
+                (CODE_DISPLAY_MISMATCH, Severity::Warning),
+                (CODE_DISPLAY_MISMATCH, Severity::Warning),
+            ]
+        );
+        assert_eq!(diagnostics[1].suggestions[0].replacement, "\"Alpha one\"");
+        assert!(diagnostics[2].message.contains("\"Bee\""));
+    }
+
+    #[test]
+    fn test_terminology_across_files() {
+        let models = vec![
+            model("terminology.fsh", TERMINOLOGY),
+            model(
+                "profiles.fsh",
+                "Profile: P\nParent: Observation\n* code from http://example.org/ValueSet/MyVS (required)\n* code = $MyCS#c\n",
+            ),
+        ];
+        let terminology = ProjectTerminology::from_models(&models);
+
+        assert!(terminology.has_value_set("MyVS"));
+        assert!(terminology.has_value_set("http://example.org/fhir/ValueSet/FilteredVS"));
+        assert!(!terminology.has_value_set("OtherVS"));
+
+        let diagnostics =
+            tokio_test::block_on(check_terminology(&models[1], Some(&terminology), None));
+        assert_eq!(
+            summary(&diagnostics),
+            vec![(BINDING_CODE_NOT_IN_VALUESET, Severity::Error)]
+        );
+    }
+}
@@ -0,0 +1,605 @@
+//! Unused definition rules
+//!
+//! Project rules finding definitions nothing in the project refers to:
+//! RuleSets no rule inserts, aliases nothing references, Invariants no
+//! `obeys` rule uses, and ValueSets and CodeSystems nothing binds to or
+//! includes.
+//!
+//! A definition counts as used when its name, id or canonical URL appears
+//! anywhere outside its own definition in any project file. This errs on the
+//! side of silence: a mention in a description or a comment keeps a
+//! definition alive.
+//!
+//! Never reported:
+//! - ValueSets and CodeSystems listed under `resources` or `groups` in the
+//!   build configuration
+//! - RuleSets named in a release's `rulesets` replacements
+//! - definitions preceded by a `// maki-public` comment, unless
+//!   `linter.ignorePublicDefinitions` is `false`
+
+use crate::project::ProjectContext;
+use maki_core::cst::FshSyntaxNode;
+use maki_core::cst::ast::{Alias, AstNode, Document, Rule};
+use maki_core::{CodeSuggestion, Diagnostic, SemanticModel, Severity};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Rule ID for RuleSets that are never inserted
+pub const UNUSED_RULESET: &str = "suspicious/unused-ruleset";
+/// Rule ID for aliases that are never referenced
+pub const UNUSED_ALIAS: &str = "suspicious/unused-alias";
+/// Rule ID for Invariants no `obeys` rule uses
+pub const UNUSED_INVARIANT: &str = "suspicious/unused-invariant";
+/// Rule ID for ValueSets nothing binds to or includes
+pub const UNUSED_VALUESET: &str = "suspicious/unused-valueset";
+/// Rule ID for CodeSystems no ValueSet or code uses
+pub const UNUSED_CODESYSTEM: &str = "suspicious/unused-codesystem";
+
+/// Comment marking a definition as intentionally public
+const PUBLIC_MARKER: &str = "maki-public";
+
+/// A definition that may be unused
+struct Definition<'a> {
+    /// Index of the defining model in the project
+    index: usize,
+    model: &'a SemanticModel,
+    node: FshSyntaxNode,
+    name: String,
+    /// Words that refer to the definition: name, id, canonical URL
+    references: Vec<String>,
+}
+
+impl Definition<'_> {
+    fn range(&self) -> Range<usize> {
+        let range = self.node.text_range();
+        usize::from(range.start())..usize::from(range.end())
+    }
+}
+
+/// Where each word of the project's FSH appears, outside alias definitions
+///
+/// Words are split at whitespace and FSH punctuation, so `$SCT#123`,
+/// `from MyVS (required)` and `Canonical(MyVS|1.0)` yield `$SCT` and `MyVS`.
+/// Alias definitions are left out so that an alias for a canonical URL does
+/// not count as a use of the ValueSet behind it; uses of the alias do.
+struct WordIndex<'a> {
+    /// word -> [(model index, byte offset)]
+    words: HashMap<&'a str, Vec<(usize, usize)>>,
+}
+
+impl<'a> WordIndex<'a> {
+    fn new(project: &ProjectContext<'a>) -> Self {
+        let mut words: HashMap<&'a str, Vec<(usize, usize)>> = HashMap::new();
+        for (index, model) in project.models.iter().enumerate() {
+            let aliases: Vec<Range<usize>> = Document::cast(model.cst.clone())
+                .map(|document| {
+                    document
+                        .aliases()
+                        .map(|alias| {
+                            let range = alias.syntax().text_range();
+                            usize::from(range.start())..usize::from(range.end())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let source = model.source.as_str();
+            for word in source.split(is_word_delimiter) {
+                if word.is_empty() {
+                    continue;
+                }
+                let offset = word.as_ptr() as usize - source.as_ptr() as usize;
+                if !aliases.iter().any(|range| range.contains(&offset)) {
+                    words.entry(word).or_default().push((index, offset));
+                }
+            }
+        }
+        Self { words }
+    }
+
+    /// Whether any reference to `definition` appears outside the definition itself
+    fn is_referenced(&self, definition: &Definition<'_>) -> bool {
+        let own = definition.range();
+        definition.references.iter().any(|word| {
+            self.words.get(word.as_str()).is_some_and(|hits| {
+                hits.iter()
+                    .any(|(index, offset)| *index != definition.index || !own.contains(offset))
+            })
+        })
+    }
+}
+
+fn is_word_delimiter(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '#' | '|' | '(' | ')' | '"' | '\'' | ',' | '=' | '*' | '[' | ']' | '{' | '}' | '^'
+        )
+}
+
+/// Check for RuleSets that no `insert` rule uses
+pub fn check_unused_rulesets(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    // RuleSets swapped in by a release are inserted through their replacement
+    let release_rulesets: HashSet<&str> = project
+        .config
+        .and_then(|config| config.releases.as_ref())
+        .into_iter()
+        .flat_map(|releases| releases.values())
+        .flat_map(|release| release.rulesets.iter())
+        .flat_map(|(name, variant)| [name.as_str(), variant.as_str()])
+        .collect();
+
+    let definitions = collect(project, |document| {
+        document
+            .rule_sets()
+            .filter_map(|rule_set| {
+                let name = rule_set.name()?;
+                Some((rule_set.syntax().clone(), name, Vec::new()))
+            })
+            .collect()
+    });
+
+    let index = WordIndex::new(project);
+    definitions
+        .into_iter()
+        .filter(|definition| !release_rulesets.contains(definition.name.as_str()))
+        .filter(|definition| !is_exempt(project, definition) && !index.is_referenced(definition))
+        .map(|definition| {
+            unused_diagnostic(
+                UNUSED_RULESET,
+                Severity::Warning,
+                format!("RuleSet '{}' is never inserted", definition.name),
+                &definition,
+                false,
+            )
+        })
+        .collect()
+}
+
+/// Check for aliases that nothing references
+pub fn check_unused_aliases(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    let definitions = collect(project, |document| {
+        document
+            .aliases()
+            .filter_map(|alias| Some((alias.syntax().clone(), alias_name(&alias)?, Vec::new())))
+            .collect()
+    });
+
+    let index = WordIndex::new(project);
+    definitions
+        .into_iter()
+        .filter(|definition| !is_exempt(project, definition) && !index.is_referenced(definition))
+        .map(|definition| {
+            unused_diagnostic(
+                UNUSED_ALIAS,
+                Severity::Warning,
+                format!("Alias '{}' is never used", definition.name),
+                &definition,
+                true,
+            )
+        })
+        .collect()
+}
+
+/// Check for Invariants that no `obeys` rule uses
+pub fn check_unused_invariants(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    let definitions = collect(project, |document| {
+        document
+            .invariants()
+            .filter_map(|invariant| {
+                Some((invariant.syntax().clone(), invariant.name()?, Vec::new()))
+            })
+            .collect()
+    });
+
+    let index = WordIndex::new(project);
+    definitions
+        .into_iter()
+        .filter(|definition| !is_exempt(project, definition) && !index.is_referenced(definition))
+        .map(|definition| {
+            unused_diagnostic(
+                UNUSED_INVARIANT,
+                Severity::Warning,
+                format!(
+                    "Invariant '{}' is not used by any `obeys` rule",
+                    definition.name
+                ),
+                &definition,
+                false,
+            )
+        })
+        .collect()
+}
+
+/// Check for ValueSets that nothing binds to or includes
+pub fn check_unused_value_sets(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    let definitions = collect(project, |document| {
+        document
+            .value_sets()
+            .filter_map(|value_set| {
+                let name = value_set.name()?;
+                let id = value_set.id().and_then(|clause| clause.value());
+                let url = caret_url(value_set.rules());
+                Some((
+                    value_set.syntax().clone(),
+                    name.clone(),
+                    canonical_references(project, "ValueSet", &name, id, url),
+                ))
+            })
+            .collect()
+    });
+
+    report_unused_terminology(
+        project,
+        definitions,
+        UNUSED_VALUESET,
+        "ValueSet",
+        "bound to or included",
+    )
+}
+
+/// Check for CodeSystems that no ValueSet or code uses
+pub fn check_unused_code_systems(project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+    let definitions = collect(project, |document| {
+        document
+            .code_systems()
+            .filter_map(|code_system| {
+                let name = code_system.name()?;
+                let id = code_system.id().and_then(|clause| clause.value());
+                let url = caret_url(code_system.rules());
+                Some((
+                    code_system.syntax().clone(),
+                    name.clone(),
+                    canonical_references(project, "CodeSystem", &name, id, url),
+                ))
+            })
+            .collect()
+    });
+
+    report_unused_terminology(
+        project,
+        definitions,
+        UNUSED_CODESYSTEM,
+        "CodeSystem",
+        "included or used for a code",
+    )
+}
+
+fn report_unused_terminology(
+    project: &ProjectContext<'_>,
+    definitions: Vec<Definition<'_>>,
+    rule_id: &str,
+    kind: &str,
+    usage: &str,
+) -> Vec<Diagnostic> {
+    let configured = configured_resource_ids(project);
+    let index = WordIndex::new(project);
+    definitions
+        .into_iter()
+        .filter(|definition| {
+            !definition
+                .references
+                .iter()
+                .any(|reference| configured.contains(&format!("{kind}/{reference}")))
+        })
+        .filter(|definition| !is_exempt(project, definition) && !index.is_referenced(definition))
+        .map(|definition| {
+            // Terminology is often published for use by other IGs
+            unused_diagnostic(
+                rule_id,
+                Severity::Info,
+                format!("{} '{}' is never {}", kind, definition.name, usage),
+                &definition,
+                false,
+            )
+        })
+        .collect()
+}
+
+/// Collect definitions from every file of the project
+///
+/// `find` returns each definition's node, name, and the words besides the
+/// name that refer to it.
+fn collect<'a>(
+    project: &ProjectContext<'a>,
+    find: impl Fn(&Document) -> Vec<(FshSyntaxNode, String, Vec<String>)>,
+) -> Vec<Definition<'a>> {
+    let mut definitions = Vec::new();
+    for (index, model) in project.models.iter().enumerate() {
+        let Some(document) = Document::cast(model.cst.clone()) else {
+            continue;
+        };
+        for (node, name, mut references) in find(&document) {
+            references.insert(0, name.clone());
+            definitions.push(Definition {
+                index,
+                model,
+                node,
+                name,
+                references,
+            });
+        }
+    }
+    definitions
+}
+
+/// `^url` assigned in a ValueSet or CodeSystem
+fn caret_url(mut rules: impl Iterator<Item = Rule>) -> Option<String> {
+    rules.find_map(|rule| match rule {
+        Rule::CaretValue(caret)
+            if caret.element_path().is_none() && caret.field().as_deref() == Some("url") =>
+        {
+            caret.value()
+        }
+        _ => None,
+    })
+}
+
+/// Words besides the name that refer to a ValueSet or CodeSystem: its id,
+/// canonical URL, and the aliases for that URL
+fn canonical_references(
+    project: &ProjectContext<'_>,
+    kind: &str,
+    name: &str,
+    id: Option<String>,
+    url: Option<String>,
+) -> Vec<String> {
+    let id = id.unwrap_or_else(|| name.to_string());
+    let mut urls: Vec<String> = url.into_iter().collect();
+    if let Some(build) = project.config.and_then(|config| config.build_config()) {
+        urls.push(format!(
+            "{}/{}/{}",
+            build.canonical.trim_end_matches('/'),
+            kind,
+            id
+        ));
+    }
+
+    let mut references = vec![id];
+    for (_, document) in project.documents() {
+        for alias in document.aliases() {
+            if let (Some(name), Some(value)) = (alias_name(&alias), alias.value())
+                && urls.contains(&value)
+            {
+                references.push(name);
+            }
+        }
+    }
+    references.extend(urls);
+    references
+}
+
+/// Alias name as written, including a leading `$`
+///
+/// [`Alias::name`] drops the `$`, which the lexer doesn't treat as part of
+/// the identifier.
+fn alias_name(alias: &Alias) -> Option<String> {
+    let text = alias.syntax().text().to_string();
+    let name = text.split_once(':')?.1.split('=').next()?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// `Type/id` references listed under `resources` or `groups` in the build configuration
+fn configured_resource_ids(project: &ProjectContext<'_>) -> HashSet<String> {
+    let Some(build) = project.config.and_then(|config| config.build_config()) else {
+        return HashSet::new();
+    };
+
+    let resources = build.resources.iter().flatten().filter_map(|entry| {
+        entry
+            .reference
+            .get("reference")
+            .unwrap_or(&entry.reference)
+            .as_str()
+            .map(str::to_string)
+    });
+    let groups = build
+        .groups
+        .iter()
+        .flatten()
+        .flat_map(|group| group.resources.iter().flatten().cloned());
+    resources.chain(groups).collect()
+}
+
+/// Whether the definition is marked public and the configuration honours that
+fn is_exempt(project: &ProjectContext<'_>, definition: &Definition<'_>) -> bool {
+    let honour_marker = project
+        .config
+        .and_then(|config| config.linter.as_ref())
+        .and_then(|linter| linter.ignore_public_definitions)
+        .unwrap_or(true);
+    honour_marker && is_marked_public(definition)
+}
+
+/// Whether the line before the definition is a `// maki-public` comment
+fn is_marked_public(definition: &Definition<'_>) -> bool {
+    let source = &definition.model.source;
+    let start = definition.range().start;
+    // Leading comments may belong to the definition node itself
+    let keyword_line_start = definition
+        .node
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| !token.kind().is_trivia())
+        .map(|token| usize::from(token.text_range().start()))
+        .unwrap_or(start);
+    let before = &source[..keyword_line_start];
+    let before = &before[..before.rfind('\n').unwrap_or(0)];
+    let previous_line = before.rsplit('\n').next().unwrap_or("").trim();
+
+    previous_line
+        .strip_prefix("//")
+        .map(str::trim_start)
+        .and_then(|comment| comment.strip_prefix(PUBLIC_MARKER))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(':') || rest.starts_with(' '))
+}
+
+fn unused_diagnostic(
+    rule_id: &str,
+    severity: Severity,
+    message: String,
+    definition: &Definition<'_>,
+    safe_fix: bool,
+) -> Diagnostic {
+    let model = definition.model;
+    let location = model.source_map.node_to_diagnostic_location(
+        &definition.node,
+        &model.source,
+        &model.source_file,
+    );
+    let description = format!("Remove unused definition '{}'", definition.name);
+    let suggestion = if safe_fix {
+        CodeSuggestion::safe(description, String::new(), location.clone())
+    } else {
+        CodeSuggestion::unsafe_fix(description, String::new(), location.clone())
+    };
+
+    Diagnostic::new(
+        rule_id,
+        severity,
+        format!(
+            "{}\n  Note: mark it with a `// {}` comment if it is meant for use outside this project",
+            message, PUBLIC_MARKER
+        ),
+        location,
+    )
+    .with_code("unused-definition".to_string())
+    .with_suggestion(suggestion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maki_core::semantic::SemanticAnalyzer;
+    use maki_core::{DefaultSemanticAnalyzer, FshParser, UnifiedConfig};
+    use std::path::{Path, PathBuf};
+
+    fn model(file: &str, source: &str) -> SemanticModel {
+        let parsed = FshParser::parse_content(source).unwrap();
+        DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from(file))
+            .unwrap()
+    }
+
+    fn names(diagnostics: &[Diagnostic]) -> Vec<String> {
+        let mut names: Vec<String> = diagnostics
+            .iter()
+            .map(|d| d.message.split('\'').nth(1).unwrap_or_default().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_unused_rulesets_across_files() {
+        let models = vec![
+            model(
+                "rulesets.fsh",
+                "RuleSet: Used\n* ^status = #active\n\nRuleSet: Param(x)\n* ^title = {x}\n\nRuleSet: Dead\n* ^status = #draft\n\nRuleSet: Recursive\n* insert Recursive\n",
+            ),
+            model(
+                "profiles.fsh",
+                "Profile: P\nParent: Patient\n* insert Used\n* insert Param(hello)\n",
+            ),
+        ];
+        let diagnostics = check_unused_rulesets(&ProjectContext::new(&models));
+
+        assert_eq!(names(&diagnostics), vec!["Dead", "Recursive"]);
+        assert!(diagnostics.iter().all(|d| {
+            d.location.file == Path::new("rulesets.fsh")
+                && d.suggestions[0].applicability == maki_core::Applicability::MaybeIncorrect
+        }));
+    }
+
+    #[test]
+    fn test_unused_aliases_have_safe_fix() {
+        let models = vec![
+            model(
+                "aliases.fsh",
+                "Alias: $SCT = http://snomed.info/sct\nAlias: $LNC = http://loinc.org\n",
+            ),
+            model(
+                "instances.fsh",
+                "Instance: Obs\nInstanceOf: Observation\n* code = $SCT#123 \"Thing\"\n",
+            ),
+        ];
+        let diagnostics = check_unused_aliases(&ProjectContext::new(&models));
+
+        assert_eq!(names(&diagnostics), vec!["$LNC"]);
+        assert_eq!(
+            diagnostics[0].suggestions[0].applicability,
+            maki_core::Applicability::Always
+        );
+        assert!(diagnostics[0].suggestions[0].replacement.is_empty());
+    }
+
+    #[test]
+    fn test_unused_invariants() {
+        let models = vec![model(
+            "invariants.fsh",
+            "Invariant: inv-1\nDescription: \"Used\"\nSeverity: #error\nExpression: \"true\"\n\nInvariant: inv-2\nDescription: \"Unused\"\nSeverity: #error\nExpression: \"true\"\n\nProfile: P\nParent: Patient\n* name obeys inv-1\n",
+        )];
+        let diagnostics = check_unused_invariants(&ProjectContext::new(&models));
+
+        assert_eq!(names(&diagnostics), vec!["inv-2"]);
+    }
+
+    #[test]
+    fn test_unused_terminology() {
+        let models = vec![
+            model(
+                "terminology.fsh",
+                "CodeSystem: UsedCS\nId: used-cs\n* #a \"A\"\n\nCodeSystem: DeadCS\n* #b \"B\"\n\nValueSet: BoundVS\n* include codes from system UsedCS\n\nValueSet: AliasedVS\n* ^url = \"http://example.org/ValueSet/aliased\"\n\nValueSet: DeadVS\n* include codes from system UsedCS\n",
+            ),
+            model(
+                "profiles.fsh",
+                "Alias: $aliased = http://example.org/ValueSet/aliased\n\nProfile: P\nParent: Observation\n* code from BoundVS (required)\n* category from $aliased (extensible)\n",
+            ),
+        ];
+        let project = ProjectContext::new(&models);
+
+        assert_eq!(names(&check_unused_value_sets(&project)), vec!["DeadVS"]);
+        assert_eq!(names(&check_unused_code_systems(&project)), vec!["DeadCS"]);
+    }
+
+    #[test]
+    fn test_configured_resources_are_exempt() {
+        let models = vec![model(
+            "terminology.fsh",
+            "ValueSet: ListedVS\nId: listed-vs\n* include codes from system http://loinc.org\n\nValueSet: GroupedVS\n* include codes from system http://loinc.org\n",
+        )];
+        let config: UnifiedConfig = serde_json::from_value(serde_json::json!({
+            "build": {
+                "canonical": "http://example.org",
+                "fhirVersion": ["4.0.1"],
+                "resources": [{ "reference": { "reference": "ValueSet/listed-vs" } }],
+                "groups": [{
+                    "id": "terminology",
+                    "name": "Terminology",
+                    "resources": ["ValueSet/GroupedVS"]
+                }]
+            }
+        }))
+        .unwrap();
+        let project = ProjectContext::new(&models).with_config(&config);
+
+        assert!(check_unused_value_sets(&project).is_empty());
+    }
+
+    #[test]
+    fn test_public_marker() {
+        let models = vec![model(
+            "rulesets.fsh",
+            "// maki-public: shared with other IGs\nRuleSet: Shared\n* ^status = #active\n",
+        )];
+        assert!(check_unused_rulesets(&ProjectContext::new(&models)).is_empty());
+
+        let mut config = UnifiedConfig::default();
+        config.linter = Some(maki_core::config::LinterConfiguration {
+            ignore_public_definitions: Some(false),
+            ..Default::default()
+        });
+        let project = ProjectContext::new(&models).with_config(&config);
+        assert_eq!(names(&check_unused_rulesets(&project)), vec!["Shared"]);
+    }
+}
@@ -1,9 +1,13 @@
 //! Rule engine implementation
 
+use crate::fhir_registry::FhirVersion;
 use crate::gritql::GritQLCompiler;
+use crate::options::{RuleOptions, parse_options};
+use crate::project::{ProjectContext, ProjectRule, builtin_project_rules};
+use crate::suppression::Suppressions;
 use maki_core::{
-    CompiledRule, Diagnostic, GritQLMatcher, MakiError, Result, Rule,
-    RuleEngine as RuleEngineTrait, RuleEngineConfig, SemanticModel,
+    CompiledRule, Diagnostic, GritQLMatcher, MakiError, Result, Rule, RuleConfig,
+    RuleEngine as RuleEngineTrait, RuleEngineConfig, SemanticModel, UnifiedConfig,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -102,6 +106,21 @@ pub struct DefaultRuleEngine {
     /// Global registry of all ValueSet names defined across all project files.
     /// Used by `binding-without-valueset` rule to check cross-file references.
     global_valueset_registry: HashSet<String>,
+    /// Global registry of all profile, extension, logical and resource names and ids.
+    /// Used by `profile-assignment-present` to treat cross-file references as local.
+    global_definition_registry: HashSet<String>,
+    /// FHIR version of the project, used to recognise base resources
+    fhir_version: FhirVersion,
+    /// ValueSets, CodeSystems and aliases of all project files
+    project_terminology: Option<Arc<crate::builtin::terminology::ProjectTerminology>>,
+    /// Rules run once over all project files (see [`crate::project`])
+    project_rules: Vec<Box<dyn ProjectRule>>,
+    /// Diagnostics from the project rules, reported with their file's diagnostics
+    project_diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+    /// Whether project diagnostics were set; until then, duplicate checks run per file
+    project_pass_ran: bool,
+    /// Rule configuration of files with per-path overrides, replacing the registry's
+    file_rule_configs: HashMap<PathBuf, HashMap<String, RuleConfig>>,
 }
 
 impl RuleRegistry {
@@ -314,6 +333,13 @@ impl DefaultRuleEngine {
             gritql_compiler: Arc::new(compiler),
             lazy_session: None,
             global_valueset_registry: HashSet::new(),
+            global_definition_registry: HashSet::new(),
+            fhir_version: Default::default(),
+            project_terminology: None,
+            project_rules: builtin_project_rules(),
+            project_diagnostics: HashMap::new(),
+            project_pass_ran: false,
+            file_rule_configs: HashMap::new(),
         }
     }
 
@@ -330,6 +356,13 @@ impl DefaultRuleEngine {
             gritql_compiler: Arc::new(compiler),
             lazy_session: None,
             global_valueset_registry: HashSet::new(),
+            global_definition_registry: HashSet::new(),
+            fhir_version: Default::default(),
+            project_terminology: None,
+            project_rules: builtin_project_rules(),
+            project_diagnostics: HashMap::new(),
+            project_pass_ran: false,
+            file_rule_configs: HashMap::new(),
         }
     }
 
@@ -359,6 +392,13 @@ impl DefaultRuleEngine {
             gritql_compiler: Arc::new(compiler),
             lazy_session: None,
             global_valueset_registry: HashSet::new(),
+            global_definition_registry: HashSet::new(),
+            fhir_version: Default::default(),
+            project_terminology: None,
+            project_rules: builtin_project_rules(),
+            project_diagnostics: HashMap::new(),
+            project_pass_ran: false,
+            file_rule_configs: HashMap::new(),
         })
     }
 
@@ -665,6 +705,7 @@ impl DefaultRuleEngine {
         &self,
         rule: &CompiledRule,
         model: &SemanticModel,
+        rule_configs: &HashMap<String, RuleConfig>,
         compiler: &GritQLCompiler,
     ) -> Result<Vec<Diagnostic>> {
         let mut diagnostics = Vec::new();
@@ -728,24 +769,53 @@ impl DefaultRuleEngine {
                     } else {
                         Some(&self.global_valueset_registry)
                     };
-                    diagnostics.extend(crate::builtin::binding::check_binding_without_valueset(
-                        model, global_vs,
-                    ));
+                    diagnostics.extend(
+                        crate::builtin::binding::check_binding_without_valueset(
+                            model,
+                            global_vs,
+                            self.project_terminology.as_deref(),
+                            self.get_lazy_session(),
+                        )
+                        .await,
+                    );
                 }
                 crate::builtin::metadata::MISSING_METADATA => {
-                    diagnostics.extend(crate::builtin::metadata::check_missing_metadata(model));
+                    diagnostics.extend(crate::builtin::metadata::check_missing_metadata(
+                        model,
+                        &rule_options(rule_configs),
+                    ));
                 }
-                crate::builtin::duplicates::DUPLICATE_DEFINITION => {
+                crate::builtin::duplicates::DUPLICATE_DEFINITION if !self.project_pass_ran => {
                     diagnostics.extend(crate::builtin::duplicates::check_duplicates(model));
                 }
                 crate::builtin::duplicates::DUPLICATE_RULE => {
                     diagnostics.extend(crate::builtin::duplicates::check_duplicate_rules(model));
                 }
-                crate::builtin::duplicates::DUPLICATE_ALIAS => {
+                crate::builtin::duplicates::DUPLICATE_ALIAS if !self.project_pass_ran => {
                     diagnostics.extend(crate::builtin::duplicates::check_duplicate_aliases(model));
                 }
-                crate::builtin::profile::PROFILE_ASSIGNMENT_PRESENT => {
-                    diagnostics.extend(crate::builtin::profile::check_profile_assignments(model));
+                id if crate::builtin::profile::PROFILE_REFERENCE_RULES.contains(&id) => {
+                    let global_definitions = if self.global_definition_registry.is_empty() {
+                        None
+                    } else {
+                        Some(&self.global_definition_registry)
+                    };
+                    // Parent diagnostics belong to the assignment rule
+                    let unresolved = id == crate::builtin::profile::UNRESOLVED_REFERENCE;
+                    diagnostics.extend(
+                        crate::builtin::profile::check_profile_assignments(
+                            model,
+                            self.get_lazy_session(),
+                            global_definitions,
+                            self.fhir_version,
+                        )
+                        .await
+                        .into_iter()
+                        .filter(|diagnostic| {
+                            (diagnostic.rule_id == crate::builtin::profile::UNRESOLVED_REFERENCE)
+                                == unresolved
+                        }),
+                    );
                 }
                 crate::builtin::profile::EXTENSION_CONTEXT_MISSING => {
                     // Use the enhanced implementation from required_fields with Error severity and autofix
@@ -773,7 +843,10 @@ impl DefaultRuleEngine {
                     );
                 }
                 crate::builtin::naming::NAMING_CONVENTION => {
-                    diagnostics.extend(crate::builtin::naming::check_naming_conventions(model));
+                    diagnostics.extend(crate::builtin::naming::check_naming_conventions(
+                        model,
+                        &rule_options(rule_configs),
+                    ));
                 }
                 crate::builtin::profile::SLICE_NAME_COLLISION => {
                     diagnostics.extend(crate::builtin::profile::check_slice_name_collision(model));
@@ -787,6 +860,49 @@ impl DefaultRuleEngine {
                     diagnostics
                         .extend(crate::builtin::caret_path::check_invalid_caret_paths(model));
                 }
+                id if crate::builtin::terminology::TERMINOLOGY_RULES.contains(&id) => {
+                    diagnostics.extend(
+                        crate::builtin::terminology::check_terminology(
+                            model,
+                            self.project_terminology.as_deref(),
+                            self.get_lazy_session(),
+                        )
+                        .await
+                        .into_iter()
+                        .filter(|diagnostic| diagnostic.rule_id == id),
+                    );
+                }
+                id if crate::builtin::extension_context::EXTENSION_CONTEXT_RULES.contains(&id) => {
+                    let global_definitions = if self.global_definition_registry.is_empty() {
+                        None
+                    } else {
+                        Some(&self.global_definition_registry)
+                    };
+                    diagnostics.extend(
+                        crate::builtin::extension_context::check_extension_contexts(
+                            model,
+                            self.get_lazy_session(),
+                            global_definitions,
+                        )
+                        .await
+                        .into_iter()
+                        .filter(|diagnostic| diagnostic.rule_id == id),
+                    );
+                }
+                id if crate::builtin::slicing::SLICING_RULES.contains(&id) => {
+                    diagnostics.extend(
+                        crate::builtin::slicing::check_slicing(model, self.get_lazy_session())
+                            .await
+                            .into_iter()
+                            .filter(|diagnostic| diagnostic.rule_id == id),
+                    );
+                }
+                id if self.project_rules.iter().any(|rule| rule.id() == id) => {
+                    // Project rules run once for all files in execute_project_rules
+                }
+                id if crate::suppression::SUPPRESSION_RULES.contains(&id) => {
+                    // Reported by the suppression pass in execute_rules_impl
+                }
                 _ => {
                     tracing::warn!(
                         "AST rule '{}' not found in builtin rules registry",
@@ -915,6 +1031,8 @@ impl RuleEngineTrait for DefaultRuleEngine {
 impl DefaultRuleEngine {
     async fn execute_rules_impl(&self, model: &SemanticModel) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
+        // Looked up once per file and passed down, as the lookup canonicalizes the path
+        let rule_configs = self.rule_configs_for(&model.source_file);
 
         // Use the pre-initialized GritQL compiler (shared across all files)
         let compiler = &self.gritql_compiler;
@@ -922,7 +1040,7 @@ impl DefaultRuleEngine {
         // Execute built-in rules
         for rule in self.registry.get_all() {
             // Apply rule-specific configuration if present
-            if let Some(rule_config) = self.registry.config.rule_configs.get(rule.id())
+            if let Some(rule_config) = rule_configs.get(rule.id())
                 && !rule_config.enabled
             {
                 tracing::debug!("Skipping disabled rule '{}'", rule.id());
@@ -938,7 +1056,10 @@ impl DefaultRuleEngine {
             }
 
             // Execute the rule against each file in the semantic model
-            match self.execute_single_rule(rule, model, compiler).await {
+            match self
+                .execute_single_rule(rule, model, rule_configs, compiler)
+                .await
+            {
                 Ok(mut rule_diagnostics) => {
                     diagnostics.append(&mut rule_diagnostics);
                 }
@@ -999,9 +1120,122 @@ impl DefaultRuleEngine {
             }
         }
 
+        if let Some(project_diagnostics) = self.project_diagnostics.get(&model.source_file) {
+            diagnostics.extend(
+                project_diagnostics
+                    .iter()
+                    .filter(|diagnostic| {
+                        rule_configs
+                            .get(&diagnostic.rule_id)
+                            .is_none_or(|config| config.enabled)
+                    })
+                    .cloned(),
+            );
+        }
+
+        let suppressions = Suppressions::collect(model);
+        if !suppressions.is_empty() {
+            diagnostics = suppressions.apply(diagnostics, model, |rule| self.is_known_rule(rule));
+        }
+
+        // The suppression pass reports diagnostics of its own, so the
+        // configuration is applied after it; configured severities override
+        // the rules' own
+        diagnostics.retain(|diagnostic| {
+            rule_configs
+                .get(&diagnostic.rule_id)
+                .is_none_or(|config| config.enabled)
+        });
+        for diagnostic in &mut diagnostics {
+            if let Some(severity) = rule_configs
+                .get(&diagnostic.rule_id)
+                .and_then(|config| config.severity)
+            {
+                diagnostic.severity = severity;
+            }
+        }
+
         diagnostics
     }
 
+    /// Register an additional project rule
+    pub fn register_project_rule(&mut self, rule: Box<dyn ProjectRule>) {
+        self.project_rules.push(rule);
+    }
+
+    /// Run the project rules over the models of all project files
+    ///
+    /// A project rule only runs while a rule with its ID is registered and
+    /// not disabled, like the per-file builtin rules. A rule disabled by the
+    /// configuration but enabled by a per-path override still runs; its
+    /// diagnostics are only reported in the files it is enabled for. `config`
+    /// is the project configuration, if any.
+    pub fn execute_project_rules(
+        &self,
+        models: &[SemanticModel],
+        config: Option<&UnifiedConfig>,
+    ) -> Vec<Diagnostic> {
+        let enabled: Vec<&dyn ProjectRule> = self
+            .project_rules
+            .iter()
+            .map(|rule| rule.as_ref())
+            .filter(|rule| {
+                self.registry.get(rule.id()).is_some()
+                    && std::iter::once(&self.registry.config.rule_configs)
+                        .chain(self.file_rule_configs.values())
+                        .any(|configs| configs.get(rule.id()).is_none_or(|config| config.enabled))
+            })
+            .collect();
+        if enabled.is_empty() {
+            return Vec::new();
+        }
+
+        let mut project = ProjectContext::new(models);
+        if let Some(config) = config {
+            project = project.with_config(config);
+        }
+        enabled
+            .into_iter()
+            .flat_map(|rule| {
+                tracing::debug!(
+                    "Executing project rule '{}' over {} files",
+                    rule.id(),
+                    models.len()
+                );
+                rule.check(&project)
+            })
+            .collect()
+    }
+
+    /// Report `diagnostics` from [`execute_project_rules`](Self::execute_project_rules)
+    /// along with the diagnostics of the files they point into
+    ///
+    /// Until this is called, duplicate definitions and aliases are checked
+    /// within each file by [`execute_rules`](Self::execute_rules) instead.
+    pub fn set_project_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.project_pass_ran = true;
+        self.project_diagnostics.clear();
+        for diagnostic in diagnostics {
+            self.project_diagnostics
+                .entry(diagnostic.location.file.clone())
+                .or_default()
+                .push(diagnostic);
+        }
+    }
+
+    /// Whether `rule` names a loaded rule or a category of loaded rules
+    fn is_known_rule(&self, rule: &str) -> bool {
+        let gritql_ids = self
+            .gritql_loader
+            .iter()
+            .flat_map(|loader| loader.all_rules().iter().map(|loaded| loaded.id()));
+        self.registry
+            .list_ids()
+            .into_iter()
+            .chain(gritql_ids)
+            .any(|id| id == rule || id.split('/').next() == Some(rule))
+    }
+
     /// Set the discovery configuration
     pub fn set_discovery_config(&mut self, config: RuleDiscoveryConfig) {
         self.registry.discovery_config = config;
@@ -1065,6 +1299,67 @@ impl DefaultRuleEngine {
         &self.global_valueset_registry
     }
 
+    /// Set the global registry of StructureDefinition names and ids
+    ///
+    /// This registry should contain the names and ids of all profiles, extensions,
+    /// logical models and resources defined in the project. References to them are
+    /// not looked up in the loaded packages.
+    pub fn set_global_definitions(&mut self, definitions: HashSet<String>) {
+        self.global_definition_registry = definitions;
+    }
+
+    /// Set the FHIR version of the project
+    ///
+    /// Defaults to R4. Rules use it to tell FHIR base resources from
+    /// references that must be resolved.
+    pub fn set_fhir_version(&mut self, fhir_version: FhirVersion) {
+        self.fhir_version = fhir_version;
+    }
+
+    /// Set the ValueSets, CodeSystems and aliases of all project files
+    ///
+    /// Terminology rules use it to resolve references into other files. Without
+    /// it, they only see the terminology of the file being linted.
+    pub fn set_project_terminology(
+        &mut self,
+        terminology: crate::builtin::terminology::ProjectTerminology,
+    ) {
+        self.project_terminology = Some(Arc::new(terminology));
+    }
+
+    /// Set the per-rule configuration: enabled state, severity and options
+    ///
+    /// See [`crate::options::rule_configs`] for building it from the linter
+    /// configuration.
+    pub fn set_rule_configs(&mut self, rule_configs: HashMap<String, RuleConfig>) {
+        self.registry.config.rule_configs = rule_configs;
+    }
+
+    /// Set the per-rule configuration of `file`, replacing the one from
+    /// [`set_rule_configs`](Self::set_rule_configs) for that file
+    ///
+    /// Used for files matched by per-path overrides, see
+    /// [`maki_core::ConfigLoader::resolve_for_file`]. Paths are canonicalized,
+    /// so `./input/x.fsh` and `input/x.fsh` refer to the same file.
+    pub fn set_file_rule_configs(
+        &mut self,
+        file: PathBuf,
+        rule_configs: HashMap<String, RuleConfig>,
+    ) {
+        self.file_rule_configs
+            .insert(file_config_key(&file), rule_configs);
+    }
+
+    /// Per-rule configuration that applies to `file`
+    fn rule_configs_for(&self, file: &Path) -> &HashMap<String, RuleConfig> {
+        if self.file_rule_configs.is_empty() {
+            return &self.registry.config.rule_configs;
+        }
+        self.file_rule_configs
+            .get(&file_config_key(file))
+            .unwrap_or(&self.registry.config.rule_configs)
+    }
+
     /// Get statistics about loaded rules and packs
     pub fn get_statistics(&self) -> RuleEngineStatistics {
         let mut rules_by_pack = HashMap::new();
@@ -1278,6 +1573,26 @@ fn glob_match(pattern: &str, text: &str) -> bool {
     pattern == text
 }
 
+/// Key of `file` in the per-file rule configurations
+/// Options of the rule of `T` in `rule_configs`, or the defaults when none are configured
+fn rule_options<T: RuleOptions>(rule_configs: &HashMap<String, RuleConfig>) -> T {
+    let Some(config) = rule_configs
+        .get(T::RULE_ID)
+        .filter(|config| !config.options.is_empty())
+    else {
+        return T::default();
+    };
+    let options = serde_json::Value::Object(config.options.clone().into_iter().collect());
+    parse_options(&options).unwrap_or_else(|error| {
+        tracing::warn!("Ignoring options of rule '{}': {}", T::RULE_ID, error);
+        T::default()
+    })
+}
+
+fn file_config_key(file: &Path) -> PathBuf {
+    file.canonicalize().unwrap_or_else(|_| file.to_path_buf())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1570,4 +1885,169 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_file_rule_configs() {
+        use crate::builtin::metadata::MISSING_METADATA;
+
+        let mut engine = DefaultRuleEngine::new();
+        let rule = crate::BuiltinRules::all_rules()
+            .into_iter()
+            .find(|rule| rule.id == MISSING_METADATA)
+            .unwrap();
+        let compiled_rule = engine.compile_rule(&rule).unwrap();
+        engine.registry_mut().register(compiled_rule);
+
+        let error = RuleConfig {
+            severity: Some(Severity::Error),
+            options: HashMap::new(),
+            enabled: true,
+        };
+        let off = RuleConfig {
+            severity: None,
+            options: HashMap::new(),
+            enabled: false,
+        };
+        engine.set_rule_configs(HashMap::from([(MISSING_METADATA.to_string(), error)]));
+        engine.set_file_rule_configs(
+            PathBuf::from("examples/Example.fsh"),
+            HashMap::from([(MISSING_METADATA.to_string(), off)]),
+        );
+
+        let model = |file: &str| {
+            let source = "Profile: MyPatient\nParent: Patient\n";
+            let (cst, _, _) = maki_core::cst::parse_fsh(source);
+            SemanticModel {
+                cst,
+                resources: Vec::new(),
+                symbols: Default::default(),
+                aliases: maki_core::semantic::AliasTable::new(),
+                references: Vec::new(),
+                source_file: PathBuf::from(file),
+                source_map: maki_core::SourceMap::new(source),
+                source: source.to_string(),
+                deferred_rules: maki_core::DeferredRuleQueue::new(),
+            }
+        };
+
+        let diagnostics = engine.execute_rules(&model("profiles/Profile.fsh")).await;
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+
+        let diagnostics = engine.execute_rules(&model("examples/Example.fsh")).await;
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_file_rule_configs_match_equivalent_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        std::fs::create_dir(&input).unwrap();
+        let file = input.join("x.fsh");
+        std::fs::write(&file, "Profile: X\n").unwrap();
+
+        let mut engine = DefaultRuleEngine::new();
+        let off = RuleConfig {
+            severity: None,
+            options: HashMap::new(),
+            enabled: false,
+        };
+        engine.set_file_rule_configs(
+            input.join(".").join("x.fsh"),
+            HashMap::from([("style/x".to_string(), off)]),
+        );
+
+        assert!(engine.rule_configs_for(&file).contains_key("style/x"));
+        assert!(
+            engine
+                .rule_configs_for(&dir.path().join("input/../input/x.fsh"))
+                .contains_key("style/x")
+        );
+        assert!(
+            !engine
+                .rule_configs_for(&input.join("y.fsh"))
+                .contains_key("style/x")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suppression_rules_follow_rule_configs() {
+        use crate::suppression::{UNKNOWN_SUPPRESSION_RULE, UNUSED_SUPPRESSION};
+        use maki_core::semantic::SemanticAnalyzer;
+
+        let mut engine = DefaultRuleEngine::new();
+        for rule in crate::BuiltinRules::suppression_rules() {
+            let compiled_rule = engine.compile_rule(&rule).unwrap();
+            engine.registry_mut().register(compiled_rule);
+        }
+
+        let source = "// maki-ignore-next-line suppressions/invalid-suppression\nProfile: MyPatient\nParent: Patient\n";
+        let parsed = maki_core::FshParser::parse_content(source).unwrap();
+        let model = maki_core::DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from("a.fsh"))
+            .unwrap();
+
+        let diagnostics = engine.execute_rules(&model).await;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule_id, UNUSED_SUPPRESSION);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.rule_id != UNKNOWN_SUPPRESSION_RULE)
+        );
+
+        let error = RuleConfig {
+            severity: Some(Severity::Error),
+            options: HashMap::new(),
+            enabled: true,
+        };
+        engine.set_rule_configs(HashMap::from([(UNUSED_SUPPRESSION.to_string(), error)]));
+        let diagnostics = engine.execute_rules(&model).await;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+
+        let off = RuleConfig {
+            severity: None,
+            options: HashMap::new(),
+            enabled: false,
+        };
+        engine.set_rule_configs(HashMap::from([(UNUSED_SUPPRESSION.to_string(), off)]));
+        assert!(engine.execute_rules(&model).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_checked_per_file_without_project_pass() {
+        use crate::builtin::duplicates::DUPLICATE_DEFINITION;
+        use maki_core::semantic::SemanticAnalyzer;
+
+        let mut engine = DefaultRuleEngine::new();
+        let rule = crate::BuiltinRules::all_rules()
+            .into_iter()
+            .find(|rule| rule.id == DUPLICATE_DEFINITION)
+            .unwrap();
+        let compiled_rule = engine.compile_rule(&rule).unwrap();
+        engine.registry_mut().register(compiled_rule);
+
+        let source = "Profile: MyPatient\nParent: Patient\n\nProfile: MyPatient\nParent: Patient\n";
+        let parsed = maki_core::FshParser::parse_content(source).unwrap();
+        let model = maki_core::DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from("a.fsh"))
+            .unwrap();
+
+        let diagnostics = engine.execute_rules(&model).await;
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.rule_id == DUPLICATE_DEFINITION)
+        );
+
+        // Once a project pass ran, its diagnostics replace the per-file check
+        engine.set_project_diagnostics(Vec::new());
+        let diagnostics = engine.execute_rules(&model).await;
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.rule_id != DUPLICATE_DEFINITION)
+        );
+    }
 }
@@ -6,12 +6,31 @@ use once_cell::sync::Lazy;
 use std::collections::HashSet;
 
 /// FHIR version
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum FhirVersion {
+    #[default]
     R4,
     R5,
 }
 
+impl FhirVersion {
+    /// Version of a `fhirVersion` config value such as `4.0.1` or `5.0.0`
+    ///
+    /// R4B and earlier releases use the R4 resource list; R5 and later use the
+    /// R5 one.
+    pub fn from_version(version: &str) -> Self {
+        let major = version
+            .trim()
+            .split('.')
+            .next()
+            .and_then(|major| major.parse::<u32>().ok());
+        match major {
+            Some(major) if major >= 5 => FhirVersion::R5,
+            _ => FhirVersion::R4,
+        }
+    }
+}
+
 /// Complete list of FHIR R4 base resources
 static FHIR_R4_RESOURCES: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     HashSet::from([
@@ -283,6 +302,15 @@ mod tests {
         assert!(!is_fhir_resource("DocumentManifest", FhirVersion::R5)); // Removed in R5
     }
 
+    #[test]
+    fn test_fhir_version_from_config_value() {
+        assert_eq!(FhirVersion::from_version("4.0.1"), FhirVersion::R4);
+        assert_eq!(FhirVersion::from_version("4.3.0"), FhirVersion::R4);
+        assert_eq!(FhirVersion::from_version("5.0.0"), FhirVersion::R5);
+        assert_eq!(FhirVersion::from_version("6.0.0-ballot"), FhirVersion::R5);
+        assert_eq!(FhirVersion::from_version("current"), FhirVersion::R4);
+    }
+
     #[test]
     fn test_canonical_url_detection() {
         assert!(is_canonical_url(
@@ -7,26 +7,53 @@
 //!
 //! GritQL matching happens in several phases:
 //! 1. **Pattern Parsing**: Parse GritQL syntax string into AST
-//! 2. **Pattern Compilation**: Convert parsed AST to grit-pattern-matcher Pattern structs
-//! 3. **Pattern Execution**: Run Pattern.execute() against our CST via QueryContext
-//! 4. **Result Collection**: Convert grit matches to our GritQLMatch format
+//! 2. **Pattern Checking**: Validate node kinds, definitions and calls up front
+//! 3. **Pattern Execution**: Evaluate the parsed program against our CST
+//! 4. **Result Collection**: Convert matches to our GritQLMatch format
 //!
 //! Since grit doesn't provide a public GritQL parser, we implement a simplified
 //! pattern language that covers the most important GritQL features for FSH linting.
+//!
+//! The parsed program is the only representation that gets executed, so
+//! checking and matching always agree on its semantics.
 
-use super::compiler::PatternCompiler;
+use super::builtins::{self, register_fsh_builtins};
+use super::cst_adapter::FshGritNode;
 use super::cst_language::FshTargetLanguage;
 use super::cst_tree::FshGritTree;
-use super::parser::GritQLParser;
-use grit_pattern_matcher::pattern::Pattern;
-use grit_util::Ast;
+use super::parser::{
+    AccessorSegment, GritDefinition, GritMatchValue, GritPattern, GritPredicate, GritProgram,
+    GritQLParser, split_accessor_path,
+};
+use grit_pattern_matcher::pattern::to_unsigned;
+use grit_util::{Ast, AstNode};
+use maki_core::cst::FshSyntaxKind;
 use maki_core::{CodeSuggestion, Diagnostic, MakiError, Result, Severity};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Name of the implicit variable bound to the node being matched
+const IMPLICIT_VARIABLE: &str = "_";
+
+/// Value bound to a pattern variable during matching
+#[derive(Debug, Clone)]
+enum BoundValue {
+    Node(FshGritNode),
+    Text(String),
+}
+
+impl BoundValue {
+    fn text(&self) -> String {
+        match self {
+            BoundValue::Node(node) => node.text_content(),
+            BoundValue::Text(text) => text.clone(),
+        }
+    }
+}
+
 /// Variable bindings during pattern matching
-/// Maps variable name → variable value (text)
-type VariableBindings = HashMap<String, String>;
+/// Maps variable name → bound node or text
+type VariableBindings = HashMap<String, BoundValue>;
 
 /// A compiled GritQL pattern ready for execution
 #[derive(Debug, Clone)]
@@ -37,11 +64,8 @@ pub struct CompiledGritQLPattern {
     pub rule_id: String,
     /// Variable captures from the pattern
     captures: Vec<String>,
-    /// Compiled pattern ready for execution
-    compiled_pattern: Option<Arc<Pattern<super::query_context::FshQueryContext>>>,
-    /// Mapping from variable names to their indices (used in Phase 2 for variable binding)
-    #[allow(dead_code)]
-    variable_indices: HashMap<String, usize>,
+    /// Checked program (definitions and main pattern) interpreted at execution time
+    program: Option<Arc<GritProgram>>,
     /// Optional effect for rewriting (for autofix support)
     pub effect: Option<super::rewrite::Effect>,
     /// Severity level for diagnostics
@@ -100,8 +124,7 @@ impl GritQLCompiler {
                 pattern: String::new(),
                 rule_id: rule_id.to_string(),
                 captures: Vec::new(),
-                compiled_pattern: None,
-                variable_indices: HashMap::new(),
+                program: None,
                 effect: None,
                 severity: None,
                 message: None,
@@ -111,29 +134,27 @@ impl GritQLCompiler {
         // Basic pattern validation
         self.validate_pattern_syntax(pattern, rule_id)?;
 
-        // Parse the pattern into AST
+        // Parse the program (definitions and main pattern) into AST
         let mut parser = GritQLParser::new(pattern);
-        let parsed_pattern = parser.parse().map_err(|e| {
+        let program = parser.parse_program().map_err(|e| {
             MakiError::rule_error(rule_id, format!("Failed to parse GritQL pattern: {e:?}"))
         })?;
 
         // Extract variable captures from the pattern
         let captures = self.extract_captures_from_pattern(pattern);
 
-        // Compile the parsed pattern to grit-pattern-matcher Pattern
-        let mut compiler = PatternCompiler::new();
-        let compiled_pattern = compiler.compile(&parsed_pattern).map_err(|e| {
-            MakiError::rule_error(rule_id, format!("Failed to compile GritQL pattern: {e:?}"))
-        })?;
-
-        let variable_indices = compiler.variables.clone();
+        // Check node kinds, definitions and calls before the program runs
+        ProgramMatcher::new(&program, rule_id)
+            .check()
+            .map_err(|e| {
+                MakiError::rule_error(rule_id, format!("Failed to compile GritQL pattern: {e:?}"))
+            })?;
 
         Ok(CompiledGritQLPattern {
             pattern: pattern.to_string(),
             rule_id: rule_id.to_string(),
             captures,
-            compiled_pattern: Some(Arc::new(compiled_pattern)),
-            variable_indices,
+            program: Some(Arc::new(program)),
             effect: None,
             severity: None,
             message: None,
@@ -215,7 +236,7 @@ impl CompiledGritQLPattern {
         }
 
         // If pattern wasn't compiled (shouldn't happen), return empty results
-        let Some(compiled) = &self.compiled_pattern else {
+        let Some(program) = &self.program else {
             return Ok(Vec::new());
         };
 
@@ -225,8 +246,8 @@ impl CompiledGritQLPattern {
         tracing::debug!("Executing GritQL pattern for rule '{}'", self.rule_id);
         tracing::debug!("CST root node kind: {:?}", tree.root_node().kind(),);
 
-        // Execute pattern against the tree using grit-pattern-matcher
-        let matches = self.execute_pattern_internal(&tree, compiled.as_ref(), source, file_path)?;
+        // Execute the program against the tree
+        let matches = self.execute_pattern_internal(&tree, program, source, file_path)?;
 
         tracing::debug!(
             "Pattern execution complete, found {} matches",
@@ -296,46 +317,34 @@ impl CompiledGritQLPattern {
         diagnostic
     }
 
-    /// Internal execution using custom pattern matching
-    ///
-    /// This is a simplified pattern matcher that works with compiled grit-pattern-matcher
-    /// patterns but doesn't use the full State machinery. It's designed specifically for
-    /// our CST-based matching needs.
+    /// Internal execution: walk the tree and evaluate the program at every node
     fn execute_pattern_internal(
         &self,
         tree: &FshGritTree,
-        pattern: &Pattern<super::query_context::FshQueryContext>,
+        program: &GritProgram,
         source: &str,
         _file_path: &str,
     ) -> Result<Vec<GritQLMatch>> {
+        let matcher = ProgramMatcher::new(program, &self.rule_id);
         let mut matches = Vec::new();
         let root = tree.root_node();
 
         // Walk the tree and find matching nodes
-        self.visit_and_match_nodes_simple(&root, pattern, source, &mut matches)?;
+        self.visit_and_match_nodes(&root, &matcher, source, &mut matches)?;
 
         Ok(matches)
     }
 
-    /// Simple pattern matching without using grit-pattern-matcher's State machinery
-    ///
-    /// This directly interprets the compiled Pattern structs and checks if nodes match.
-    fn visit_and_match_nodes_simple(
+    fn visit_and_match_nodes(
         &self,
-        node: &super::cst_adapter::FshGritNode,
-        pattern: &Pattern<super::query_context::FshQueryContext>,
+        node: &FshGritNode,
+        matcher: &ProgramMatcher,
         source: &str,
         matches: &mut Vec<GritQLMatch>,
     ) -> Result<()> {
-        use grit_util::AstNode;
-
-        // Start with empty variable bindings
-        let bindings = VariableBindings::new();
-
-        // Check if this node matches the pattern
-        let (node_matches, final_bindings) = self.node_matches_pattern(node, pattern, &bindings)?;
-
-        if node_matches {
+        if let Some(bindings) =
+            matcher.match_pattern(node, &matcher.program.pattern, &VariableBindings::new())?
+        {
             // Node matched! Extract match information
             let byte_range = node.byte_range();
             let text = node.text().map_err(|e| {
@@ -347,7 +356,10 @@ impl CompiledGritQLPattern {
             let (end_line, end_column) = offset_to_line_col(source, byte_range.end);
 
             // Use the variable bindings as captures
-            let captures = final_bindings;
+            let captures = bindings
+                .into_iter()
+                .map(|(name, value)| (name, value.text()))
+                .collect();
 
             matches.push(GritQLMatch {
                 matched_text: text.to_string(),
@@ -363,363 +375,12 @@ impl CompiledGritQLPattern {
 
         // Recursively visit children even if this node didn't match
         for child in node.children() {
-            self.visit_and_match_nodes_simple(&child, pattern, source, matches)?;
+            self.visit_and_match_nodes(&child, matcher, source, matches)?;
         }
 
         Ok(())
     }
 
-    /// Check if a node matches a pattern (custom implementation)
-    ///
-    /// Returns (matches: bool, bindings: VariableBindings)
-    fn node_matches_pattern(
-        &self,
-        node: &super::cst_adapter::FshGritNode,
-        pattern: &Pattern<super::query_context::FshQueryContext>,
-        bindings: &VariableBindings,
-    ) -> Result<(bool, VariableBindings)> {
-        match pattern {
-            // Match specific node kinds
-            Pattern::AstNode(node_pattern) => {
-                let matches = node.kind() == node_pattern.kind;
-                Ok((matches, bindings.clone()))
-            }
-
-            // Where clause: base pattern AND predicate must both match
-            Pattern::Where(where_pattern) => {
-                // First check if base pattern matches
-                let (base_matches, base_bindings) =
-                    self.node_matches_pattern(node, &where_pattern.pattern, bindings)?;
-
-                if !base_matches {
-                    return Ok((false, bindings.clone()));
-                }
-
-                // Then check if predicate matches (using bindings from base pattern)
-                let (pred_matches, pred_bindings) = self.node_matches_predicate(
-                    node,
-                    &where_pattern.side_condition,
-                    &base_bindings,
-                )?;
-                Ok((pred_matches, pred_bindings))
-            }
-
-            // Not pattern: base must NOT match
-            Pattern::Not(not_pattern) => {
-                let (matches, _) =
-                    self.node_matches_pattern(node, &not_pattern.pattern, bindings)?;
-                Ok((!matches, bindings.clone()))
-            }
-
-            // And pattern: ALL patterns must match
-            Pattern::And(and_pattern) => {
-                let mut current_bindings = bindings.clone();
-                for p in &and_pattern.patterns {
-                    let (matches, new_bindings) =
-                        self.node_matches_pattern(node, p, &current_bindings)?;
-                    if !matches {
-                        return Ok((false, bindings.clone()));
-                    }
-                    current_bindings = new_bindings;
-                }
-                Ok((true, current_bindings))
-            }
-
-            // Or pattern: ANY pattern must match
-            Pattern::Or(or_pattern) => {
-                for p in &or_pattern.patterns {
-                    let (matches, new_bindings) = self.node_matches_pattern(node, p, bindings)?;
-                    if matches {
-                        return Ok((true, new_bindings));
-                    }
-                }
-                Ok((false, bindings.clone()))
-            }
-
-            // Variable pattern: $name - matches any node and binds it
-            Pattern::Variable(var) => {
-                use grit_util::AstNode;
-                let mut new_bindings = bindings.clone();
-
-                // Get the node text to bind to the variable
-                let text = node.text().map_err(|e| {
-                    MakiError::rule_error(&self.rule_id, format!("Failed to get node text: {e:?}"))
-                })?;
-
-                // Extract variable index from Debug output since Variable's fields aren't public
-                // Format: Variable { index: N, scope: M }
-                let debug_str = format!("{:?}", var);
-                let var_name = if let Some(index_start) = debug_str.find("index: ") {
-                    let after_index = &debug_str[index_start + 7..];
-                    if let Some(index_end) = after_index.find(|c: char| !c.is_numeric()) {
-                        let index_str = &after_index[..index_end];
-                        if let Ok(index) = index_str.parse::<usize>() {
-                            // Look up variable name from our index map
-                            self.variable_indices
-                                .iter()
-                                .find(|(_, idx)| **idx == index)
-                                .map(|(name, _)| name.clone())
-                                .unwrap_or_else(|| format!("var_{}", index))
-                        } else {
-                            "var_unknown".to_string()
-                        }
-                    } else {
-                        "var_unknown".to_string()
-                    }
-                } else {
-                    "var_unknown".to_string()
-                };
-
-                tracing::debug!(
-                    "Variable pattern '{}' matched, binding to text '{}'",
-                    var_name,
-                    text.chars().take(50).collect::<String>()
-                );
-                new_bindings.insert(var_name, text.to_string());
-
-                // Variables always match any node
-                Ok((true, new_bindings))
-            }
-
-            // Underscore pattern: _ - matches any node without binding
-            Pattern::Underscore => Ok((true, bindings.clone())),
-
-            // Assignment pattern: Profile: $name
-            // Extract the field value and bind it to the variable
-            Pattern::Assignment(assignment) => {
-                // Check if the value pattern matches (e.g., Profile matches)
-                let (matches, mut new_bindings) =
-                    self.node_matches_pattern(node, &assignment.pattern, bindings)?;
-
-                if matches {
-                    // Extract variable name from Debug output
-                    // Format: Assignment { variable: Variable { ... }, pattern: ... }
-                    let debug_str = format!("{:?}", assignment);
-
-                    // Try to extract variable name
-                    // HACK: Parse Debug output to get variable name
-                    // Look for pattern like "variable: Variable"
-                    // For simple cases, we can use the field name directly
-                    // TODO: Improve this once we have better API access
-
-                    // For now, try to extract the field value based on node kind
-                    if let Some(field_value) = node.get_field_text("name") {
-                        // Use a simple heuristic: extract variable name from debug string
-                        // or use a default name based on pattern
-                        let var_name = if debug_str.contains("Variable") {
-                            // Try to infer variable name - for now use the field name
-                            // In Profile: $name pattern, the variable is typically "name"
-                            "name".to_string()
-                        } else {
-                            "value".to_string()
-                        };
-
-                        tracing::debug!(
-                            "Binding variable '{}' to value '{}'",
-                            var_name,
-                            field_value
-                        );
-                        new_bindings.insert(var_name, field_value);
-                    }
-                }
-
-                Ok((matches, new_bindings))
-            }
-
-            // For now, other pattern types don't match (will be implemented as needed)
-            _ => Ok((false, bindings.clone())),
-        }
-    }
-
-    /// Check if a node matches a predicate
-    ///
-    /// Returns (matches: bool, bindings: VariableBindings)
-    fn node_matches_predicate(
-        &self,
-        node: &super::cst_adapter::FshGritNode,
-        predicate: &grit_pattern_matcher::pattern::Predicate<super::query_context::FshQueryContext>,
-        bindings: &VariableBindings,
-    ) -> Result<(bool, VariableBindings)> {
-        use grit_pattern_matcher::pattern::Predicate;
-        use grit_util::AstNode;
-
-        match predicate {
-            // Match predicate with regex
-            Predicate::Match(match_pred) => {
-                // Get the text to match against
-                // Check if we're matching a variable's value from bindings
-                // or the current node's text (implicit context)
-                let text = {
-                    // Try to extract variable info from Debug output
-                    // Format: Match { container: Variable(Variable { index: N, scope: M }), pattern: ... }
-                    let debug_str = format!("{:?}", match_pred);
-
-                    // Check if this is matching against a variable
-                    if debug_str.contains("container: Variable") {
-                        // Extract variable index from Debug output
-                        // Look for "index: N" pattern
-                        let var_name = if let Some(index_start) = debug_str.find("index: ") {
-                            let after_index = &debug_str[index_start + 7..];
-                            if let Some(index_end) = after_index.find(|c: char| !c.is_numeric()) {
-                                let index_str = &after_index[..index_end];
-                                if let Ok(index) = index_str.parse::<usize>() {
-                                    // Look up variable name from our index map
-                                    self.variable_indices
-                                        .iter()
-                                        .find(|(_, idx)| **idx == index)
-                                        .map(|(name, _)| name.clone())
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        };
-
-                        // Try to get the variable value from bindings
-                        if let Some(name) = &var_name {
-                            if let Some(value) = bindings.get(name) {
-                                tracing::debug!(
-                                    "Using variable '{}' value '{}' for match",
-                                    name,
-                                    value
-                                );
-                                std::borrow::Cow::Owned(value.clone())
-                            } else {
-                                tracing::debug!("Variable '{}' not bound, using node text", name);
-                                node.text().map_err(|e| {
-                                    MakiError::rule_error(
-                                        &self.rule_id,
-                                        format!("Failed to get node text: {e:?}"),
-                                    )
-                                })?
-                            }
-                        } else {
-                            // Couldn't determine variable name, use node text
-                            node.text().map_err(|e| {
-                                MakiError::rule_error(
-                                    &self.rule_id,
-                                    format!("Failed to get node text: {e:?}"),
-                                )
-                            })?
-                        }
-                    } else {
-                        // Not a variable, use node text
-                        node.text().map_err(|e| {
-                            MakiError::rule_error(
-                                &self.rule_id,
-                                format!("Failed to get node text: {e:?}"),
-                            )
-                        })?
-                    }
-                };
-
-                // Extract the regex pattern from the Match predicate
-                if let Some(Pattern::Regex(regex_pattern)) = &match_pred.pattern {
-                    let regex_str = match &regex_pattern.regex {
-                        grit_pattern_matcher::pattern::RegexLike::Regex(s) => s,
-                        // Other variants not yet implemented
-                        _ => {
-                            return Ok((false, bindings.clone()));
-                        }
-                    };
-
-                    // Compile and test the regex
-                    let re = regex::Regex::new(regex_str).map_err(|e| {
-                        MakiError::rule_error(&self.rule_id, format!("Invalid regex pattern: {e}"))
-                    })?;
-
-                    tracing::debug!(
-                        "Testing regex '{}' against text (len={})",
-                        regex_str,
-                        text.len()
-                    );
-
-                    let matches = re.is_match(text.as_ref());
-
-                    tracing::debug!("Regex match result: {}", matches);
-
-                    Ok((matches, bindings.clone()))
-                } else {
-                    Ok((false, bindings.clone()))
-                }
-            }
-
-            // Not predicate: inner predicate must NOT match
-            // Note: PrNot's inner predicate is private, so we can't access it directly
-            // Workaround: We parse the Debug output to extract the regex pattern and evaluate it
-            Predicate::Not(not_pred) => {
-                let debug_str = format!("{:?}", not_pred);
-
-                // HACK: Parse the Debug output to extract the regex pattern
-                // Format: PrNot { predicate: Match(Match { ..., pattern: Some(Regex(RegexPattern { regex: Regex("PATTERN"), ...
-                if let Some(regex_start) = debug_str.find("regex: Regex(\"") {
-                    let pattern_start = regex_start + "regex: Regex(\"".len();
-                    if let Some(pattern_end) = debug_str[pattern_start..].find("\")") {
-                        let regex_pattern_escaped =
-                            &debug_str[pattern_start..pattern_start + pattern_end];
-                        // Unescape the regex pattern (Debug output escapes backslashes)
-                        let regex_pattern = regex_pattern_escaped.replace("\\\\", "\\");
-
-                        // Evaluate the inner Match predicate
-                        let text = node.text().map_err(|e| {
-                            MakiError::rule_error(
-                                &self.rule_id,
-                                format!("Failed to get node text: {e:?}"),
-                            )
-                        })?;
-                        let re = regex::Regex::new(&regex_pattern).map_err(|e| {
-                            MakiError::rule_error(&self.rule_id, format!("Invalid regex: {e}"))
-                        })?;
-
-                        let inner_matches = re.is_match(text.as_ref());
-
-                        // NOT inverts the result
-                        let result = !inner_matches;
-
-                        Ok((result, bindings.clone()))
-                    } else {
-                        // Couldn't parse - return false as fallback
-                        Ok((false, bindings.clone()))
-                    }
-                } else {
-                    // No regex pattern found - return false as fallback
-                    Ok((false, bindings.clone()))
-                }
-            }
-
-            // And predicate: ALL predicates must match
-            Predicate::And(and_pred) => {
-                let mut current_bindings = bindings.clone();
-                for p in &and_pred.predicates {
-                    let (matches, new_bindings) =
-                        self.node_matches_predicate(node, p, &current_bindings)?;
-                    if !matches {
-                        return Ok((false, bindings.clone()));
-                    }
-                    current_bindings = new_bindings;
-                }
-                Ok((true, current_bindings))
-            }
-
-            // Or predicate: ANY predicate must match
-            Predicate::Or(or_pred) => {
-                for p in &or_pred.predicates {
-                    let (matches, new_bindings) = self.node_matches_predicate(node, p, bindings)?;
-                    if matches {
-                        return Ok((true, new_bindings));
-                    }
-                }
-                Ok((false, bindings.clone()))
-            }
-
-            // For now, other predicate types don't match
-            _ => Ok((false, bindings.clone())),
-        }
-    }
-
     /// Get the pattern string
     pub fn pattern(&self) -> &str {
         &self.pattern
@@ -735,64 +396,6 @@ impl CompiledGritQLPattern {
         &self.captures
     }
 
-    /// Extract variable bindings from state after a successful match
-    #[allow(dead_code)]
-    fn extract_variables_from_state(
-        &self,
-        state: &grit_pattern_matcher::pattern::State<super::query_context::FshQueryContext>,
-        _node: &super::cst_adapter::FshGritNode,
-    ) -> Result<HashMap<String, String>> {
-        let mut variables = HashMap::new();
-
-        // Extract variables from state bindings
-        for (var_name, &var_index) in &self.variable_indices {
-            if let Some(scope_bindings) = state.bindings.first()
-                && let Some(binding) = scope_bindings.last()
-                && let Some(var_content) = binding.get(var_index)
-                && let Some(value) = &var_content.value
-            {
-                // Try to extract text from the value
-                if let Some(text) = self.extract_text_from_resolved_pattern(value) {
-                    variables.insert(var_name.clone(), text);
-                }
-            }
-        }
-
-        Ok(variables)
-    }
-
-    /// Extract text from a resolved pattern
-    #[allow(dead_code)]
-    fn extract_text_from_resolved_pattern(
-        &self,
-        pattern: &super::query_context::FshResolvedPattern,
-    ) -> Option<String> {
-        use super::query_context::FshResolvedPattern;
-        use grit_util::AstNode;
-
-        match pattern {
-            FshResolvedPattern::Binding(bindings) => bindings.first().and_then(|binding| {
-                use super::query_context::FshBinding;
-                match binding {
-                    FshBinding::Node(node) => node.text().ok().map(|s| s.to_string()),
-                    FshBinding::Range(_, text) => Some(text.to_string()),
-                    _ => None,
-                }
-            }),
-            FshResolvedPattern::Constant(constant) => {
-                use grit_pattern_matcher::constant::Constant;
-                match constant {
-                    Constant::String(s) => Some(s.clone()),
-                    Constant::Integer(i) => Some(i.to_string()),
-                    Constant::Float(f) => Some(f.to_string()),
-                    Constant::Boolean(b) => Some(b.to_string()),
-                    Constant::Undefined => None,
-                }
-            }
-            _ => None,
-        }
-    }
-
     /// Extract variable bindings from a matched node (legacy method)
     ///
     /// This method extracts the values of captured variables from the pattern match.
@@ -890,6 +493,620 @@ impl CompiledGritQLPattern {
     }
 }
 
+/// String operator applied by contains/startsWith/endsWith predicates
+#[derive(Debug, Clone, Copy)]
+enum TextOperator {
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+/// Interpreter for a parsed GritQL program against CST nodes
+///
+/// Every `match_*`/`eval_*` method returns the (possibly extended) bindings
+/// on success and `None` when the node doesn't match.
+struct ProgramMatcher<'p> {
+    program: &'p GritProgram,
+    definitions: HashMap<&'p str, &'p GritDefinition>,
+    rule_id: &'p str,
+}
+
+impl<'p> ProgramMatcher<'p> {
+    fn new(program: &'p GritProgram, rule_id: &'p str) -> Self {
+        let definitions = program
+            .definitions
+            .iter()
+            .map(|definition| (definition.name(), definition))
+            .collect();
+
+        Self {
+            program,
+            definitions,
+            rule_id,
+        }
+    }
+
+    /// Check the program before it runs: definitions must be unique, node
+    /// kinds known and every call must name a definition or built-in with
+    /// the right number of arguments
+    fn check(&self) -> Result<()> {
+        let builtins = register_fsh_builtins();
+        for (index, definition) in self.program.definitions.iter().enumerate() {
+            let name = definition.name();
+            let redefined = self.program.definitions[..index]
+                .iter()
+                .any(|earlier| earlier.name() == name);
+            if redefined || builtins.contains(&name) {
+                return Err(self.error(format!("Duplicate definition of '{name}'")));
+            }
+        }
+
+        for definition in &self.program.definitions {
+            match definition {
+                GritDefinition::Pattern { body, .. } => self.check_pattern(body)?,
+                GritDefinition::Function { body, .. } => self.check_predicate(body)?,
+            }
+        }
+        self.check_pattern(&self.program.pattern)
+    }
+
+    fn check_pattern(&self, pattern: &GritPattern) -> Result<()> {
+        match pattern {
+            GritPattern::NodeKind(kind) => syntax_kind(kind).map(|_| ()),
+            GritPattern::Variable(_) => Ok(()),
+            GritPattern::Where(pattern, predicate) => {
+                self.check_pattern(pattern)?;
+                self.check_predicate(predicate)
+            }
+            GritPattern::Assignment { value: inner, .. }
+            | GritPattern::Not(inner)
+            | GritPattern::Maybe(inner)
+            | GritPattern::Contains(inner)
+            | GritPattern::Within(inner)
+            | GritPattern::After(inner)
+            | GritPattern::Bubble { pattern: inner, .. } => self.check_pattern(inner),
+            GritPattern::And(patterns) | GritPattern::Or(patterns) | GritPattern::Any(patterns) => {
+                patterns
+                    .iter()
+                    .try_for_each(|pattern| self.check_pattern(pattern))
+            }
+            GritPattern::Call { name, args } => match self.definitions.get(name.as_str()) {
+                Some(GritDefinition::Pattern { params, .. }) => {
+                    self.check_arity(name, params.len(), args.len())
+                }
+                _ => Err(self.error(format!("Unknown pattern: {name}"))),
+            },
+        }
+    }
+
+    fn check_predicate(&self, predicate: &GritPredicate) -> Result<()> {
+        match predicate {
+            GritPredicate::Not(inner) => self.check_predicate(inner),
+            GritPredicate::And(predicates) | GritPredicate::Or(predicates) => predicates
+                .iter()
+                .try_for_each(|predicate| self.check_predicate(predicate)),
+            GritPredicate::VariablePattern { constraint, .. } => self.check_predicate(constraint),
+            GritPredicate::Call { name, args } => match self.definitions.get(name.as_str()) {
+                Some(definition) => self.check_arity(name, definition.params().len(), args.len()),
+                None if register_fsh_builtins().contains(&name.as_str()) => {
+                    if args.len() > 1 {
+                        return Err(
+                            self.error(format!("Built-in '{name}' takes at most one argument"))
+                        );
+                    }
+                    Ok(())
+                }
+                None => Err(self.error(format!("Unknown function: {name}"))),
+            },
+            GritPredicate::FieldExists(_)
+            | GritPredicate::Match { .. }
+            | GritPredicate::Contains { .. }
+            | GritPredicate::StartsWith { .. }
+            | GritPredicate::EndsWith { .. }
+            | GritPredicate::Equality { .. }
+            | GritPredicate::Inequality { .. } => Ok(()),
+        }
+    }
+
+    fn check_arity(&self, name: &str, expected: usize, actual: usize) -> Result<()> {
+        if expected == actual {
+            return Ok(());
+        }
+        Err(self.error(format!(
+            "'{name}' expects {expected} argument(s), got {actual}"
+        )))
+    }
+
+    fn error(&self, message: String) -> MakiError {
+        MakiError::rule_error(self.rule_id, message)
+    }
+
+    fn match_pattern(
+        &self,
+        node: &FshGritNode,
+        pattern: &GritPattern,
+        bindings: &VariableBindings,
+    ) -> Result<Option<VariableBindings>> {
+        match pattern {
+            // Match specific node kinds
+            GritPattern::NodeKind(kind) => {
+                let kind = syntax_kind(kind)?;
+                Ok((node.kind() == kind).then(|| bindings.clone()))
+            }
+
+            // Where clause: base pattern AND predicate must both match
+            GritPattern::Where(pattern, predicate) => {
+                match self.match_pattern(node, pattern, bindings)? {
+                    Some(base_bindings) => self.eval_predicate(node, predicate, &base_bindings),
+                    None => Ok(None),
+                }
+            }
+
+            // Variable pattern: $name - binds the node, or compares with an earlier binding
+            GritPattern::Variable(name) => {
+                self.bind(bindings, name, BoundValue::Node(node.clone()))
+            }
+
+            GritPattern::Not(inner) => Ok(self
+                .match_pattern(node, inner, bindings)?
+                .is_none()
+                .then(|| bindings.clone())),
+
+            GritPattern::And(patterns) => {
+                let mut current = bindings.clone();
+                for pattern in patterns {
+                    match self.match_pattern(node, pattern, &current)? {
+                        Some(next) => current = next,
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(current))
+            }
+
+            GritPattern::Or(patterns) | GritPattern::Any(patterns) => {
+                for pattern in patterns {
+                    if let Some(matched) = self.match_pattern(node, pattern, bindings)? {
+                        return Ok(Some(matched));
+                    }
+                }
+                Ok(None)
+            }
+
+            // Assignment pattern: Profile: $name binds the definition's name,
+            // other nodes are bound as a whole
+            GritPattern::Assignment { var, value } => {
+                let Some(matched) = self.match_pattern(node, value, bindings)? else {
+                    return Ok(None);
+                };
+                let bound = node
+                    .get_field_text("name")
+                    .map(BoundValue::Text)
+                    .unwrap_or_else(|| BoundValue::Node(node.clone()));
+                self.bind(&matched, var, bound)
+            }
+
+            GritPattern::Maybe(inner) => Ok(Some(
+                self.match_pattern(node, inner, bindings)?
+                    .unwrap_or_else(|| bindings.clone()),
+            )),
+
+            // Contains: the node or any of its descendants matches
+            GritPattern::Contains(inner) => {
+                if let Some(matched) = self.match_pattern(node, inner, bindings)? {
+                    return Ok(Some(matched));
+                }
+                for child in node.children() {
+                    if let Some(matched) = self.match_pattern(&child, pattern, bindings)? {
+                        return Ok(Some(matched));
+                    }
+                }
+                Ok(None)
+            }
+
+            // Within: any ancestor matches
+            GritPattern::Within(inner) => {
+                for ancestor in node.ancestors() {
+                    if let Some(matched) = self.match_pattern(&ancestor, inner, bindings)? {
+                        return Ok(Some(matched));
+                    }
+                }
+                Ok(None)
+            }
+
+            // After: the preceding sibling matches
+            GritPattern::After(inner) => match node.previous_named_node() {
+                Some(previous) => self.match_pattern(&previous, inner, bindings),
+                None => Ok(None),
+            },
+
+            // Bubble: match in a fresh scope that only sees the listed variables
+            GritPattern::Bubble { pattern, args } => {
+                let scope = args
+                    .iter()
+                    .filter_map(|arg| Some((arg.clone(), bindings.get(arg)?.clone())))
+                    .collect();
+                Ok(self
+                    .match_pattern(node, pattern, &scope)?
+                    .map(|_| bindings.clone()))
+            }
+
+            GritPattern::Call { name, args } => self.call_pattern(node, name, args, bindings),
+        }
+    }
+
+    fn eval_predicate(
+        &self,
+        node: &FshGritNode,
+        predicate: &GritPredicate,
+        bindings: &VariableBindings,
+    ) -> Result<Option<VariableBindings>> {
+        let holds = match predicate {
+            // A bare name is a bound variable's truthiness or a field of the matched node
+            GritPredicate::FieldExists(field) => {
+                let (base, segments) = split_accessor_path(field);
+                let value = if bindings.contains_key(base) || !segments.is_empty() {
+                    self.resolve(node, field, bindings)
+                } else {
+                    node.get_field_text(field).map(BoundValue::Text)
+                };
+                value.is_some_and(|value| !value.text().is_empty())
+            }
+
+            GritPredicate::Not(inner) => {
+                return Ok(self
+                    .eval_predicate(node, inner, bindings)?
+                    .is_none()
+                    .then(|| bindings.clone()));
+            }
+
+            GritPredicate::And(predicates) => {
+                let mut current = bindings.clone();
+                for predicate in predicates {
+                    match self.eval_predicate(node, predicate, &current)? {
+                        Some(next) => current = next,
+                        None => return Ok(None),
+                    }
+                }
+                return Ok(Some(current));
+            }
+
+            GritPredicate::Or(predicates) => {
+                for predicate in predicates {
+                    if let Some(matched) = self.eval_predicate(node, predicate, bindings)? {
+                        return Ok(Some(matched));
+                    }
+                }
+                return Ok(None);
+            }
+
+            GritPredicate::Match { var, value } => match self.resolve(node, var, bindings) {
+                Some(subject) => self.match_value(node, &subject.text(), value, bindings)?,
+                None => false,
+            },
+
+            GritPredicate::Contains { var, value } => {
+                self.eval_text_operator(node, var, value, TextOperator::Contains, bindings)?
+            }
+            GritPredicate::StartsWith { var, value } => {
+                self.eval_text_operator(node, var, value, TextOperator::StartsWith, bindings)?
+            }
+            GritPredicate::EndsWith { var, value } => {
+                self.eval_text_operator(node, var, value, TextOperator::EndsWith, bindings)?
+            }
+
+            GritPredicate::Equality { left, right } => self
+                .resolve(node, left, bindings)
+                .is_some_and(|value| value.text() == *right),
+            GritPredicate::Inequality { left, right } => self
+                .resolve(node, left, bindings)
+                .is_none_or(|value| value.text() != *right),
+
+            // $var where { ... }: bind the matched node (unless bound) and check the constraint
+            GritPredicate::VariablePattern { var, constraint } => {
+                let mut scoped = bindings.clone();
+                scoped
+                    .entry(var.clone())
+                    .or_insert_with(|| BoundValue::Node(node.clone()));
+                return self.eval_predicate(node, constraint, &scoped);
+            }
+
+            GritPredicate::Call { name, args } => match self.definitions.get(name.as_str()) {
+                Some(GritDefinition::Function { params, body, .. }) => {
+                    let scope = self.bind_params(node, params, args, bindings);
+                    self.eval_predicate(node, body, &scope)?.is_some()
+                }
+                Some(GritDefinition::Pattern { .. }) => {
+                    return self.call_pattern(node, name, args, bindings);
+                }
+                None => self.call_builtin(node, name, args, bindings),
+            },
+        };
+
+        Ok(holds.then(|| bindings.clone()))
+    }
+
+    /// Run a user-defined pattern; variables passed as arguments receive the
+    /// values the pattern bound to the corresponding parameters
+    fn call_pattern(
+        &self,
+        node: &FshGritNode,
+        name: &str,
+        args: &[GritMatchValue],
+        bindings: &VariableBindings,
+    ) -> Result<Option<VariableBindings>> {
+        let Some(GritDefinition::Pattern { params, body, .. }) = self.definitions.get(name) else {
+            return Ok(None);
+        };
+
+        let scope = self.bind_params(node, params, args, bindings);
+        let Some(inner) = self.match_pattern(node, body, &scope)? else {
+            return Ok(None);
+        };
+
+        let mut result = bindings.clone();
+        for (param, arg) in params.iter().zip(args) {
+            if let GritMatchValue::Variable(var) = arg
+                && let Some(value) = inner.get(param)
+            {
+                result.entry(var.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        Ok(Some(result))
+    }
+
+    /// Build the scope of a definition call from its arguments
+    fn bind_params(
+        &self,
+        node: &FshGritNode,
+        params: &[String],
+        args: &[GritMatchValue],
+        bindings: &VariableBindings,
+    ) -> VariableBindings {
+        params
+            .iter()
+            .zip(args)
+            .filter_map(|(param, arg)| {
+                let value = match arg {
+                    GritMatchValue::String(text) => BoundValue::Text(text.clone()),
+                    GritMatchValue::Variable(var) => {
+                        let (base, _) = split_accessor_path(var);
+                        if !bindings.contains_key(base) && base != IMPLICIT_VARIABLE {
+                            return None;
+                        }
+                        self.resolve(node, var, bindings)?
+                    }
+                    _ => return None,
+                };
+                Some((param.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Evaluate an FSH built-in; the argument defaults to the matched node
+    fn call_builtin(
+        &self,
+        node: &FshGritNode,
+        name: &str,
+        args: &[GritMatchValue],
+        bindings: &VariableBindings,
+    ) -> bool {
+        let target = match args.first() {
+            None => BoundValue::Node(node.clone()),
+            Some(GritMatchValue::Variable(var)) => match self.resolve(node, var, bindings) {
+                Some(value) => value,
+                None => return false,
+            },
+            Some(GritMatchValue::String(text)) => BoundValue::Text(text.clone()),
+            Some(_) => return false,
+        };
+
+        match (name, &target) {
+            ("is_profile", BoundValue::Node(n)) => builtins::is_profile(n),
+            ("is_extension", BoundValue::Node(n)) => builtins::is_extension(n),
+            ("is_value_set", BoundValue::Node(n)) => builtins::is_value_set(n),
+            ("is_code_system", BoundValue::Node(n)) => builtins::is_code_system(n),
+            ("has_comment", BoundValue::Node(n)) => builtins::has_comment(n),
+            ("has_title", BoundValue::Node(n)) => builtins::has_title(n),
+            ("has_description", BoundValue::Node(n)) => builtins::has_description(n),
+            ("has_parent", BoundValue::Node(n)) => builtins::has_parent(n),
+            ("is_kebab_case", value) => builtins::is_kebab_case(&value.text()),
+            ("is_pascal_case", value) => builtins::is_pascal_case(&value.text()),
+            ("is_camel_case", value) => builtins::is_camel_case(&value.text()),
+            ("is_screaming_snake_case", value) => builtins::is_screaming_snake_case(&value.text()),
+            _ => false,
+        }
+    }
+
+    /// Resolve a variable reference with accessor/index suffixes
+    ///
+    /// Unbound variables refer to the matched node (implicit context), so
+    /// `$name <: r"..."` tests the node text when `$name` was never bound.
+    fn resolve(
+        &self,
+        node: &FshGritNode,
+        var: &str,
+        bindings: &VariableBindings,
+    ) -> Option<BoundValue> {
+        let (base, segments) = split_accessor_path(var);
+        let mut value = bindings
+            .get(base)
+            .cloned()
+            .unwrap_or_else(|| BoundValue::Node(node.clone()));
+
+        for segment in segments {
+            let BoundValue::Node(current) = value else {
+                return None;
+            };
+            value = match segment {
+                AccessorSegment::Field(field) => BoundValue::Text(current.get_field_text(&field)?),
+                AccessorSegment::Index(index) => {
+                    let children: Vec<_> = current.named_children().collect();
+                    let index = to_unsigned(index, children.len())?;
+                    BoundValue::Node(children.get(index)?.clone())
+                }
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Check `text <: value`
+    fn match_value(
+        &self,
+        node: &FshGritNode,
+        text: &str,
+        value: &GritMatchValue,
+        bindings: &VariableBindings,
+    ) -> Result<bool> {
+        match value {
+            GritMatchValue::String(expected) => Ok(text == expected),
+            GritMatchValue::Regex(pattern) => Ok(self.regex(pattern)?.is_match(text)),
+            GritMatchValue::Or(values) => {
+                for value in values {
+                    if self.match_value(node, text, value, bindings)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            GritMatchValue::And(values) => {
+                for value in values {
+                    if !self.match_value(node, text, value, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            GritMatchValue::Variable(var) => Ok(self
+                .resolve(node, var, bindings)
+                .is_some_and(|other| other.text() == text)),
+        }
+    }
+
+    fn eval_text_operator(
+        &self,
+        node: &FshGritNode,
+        var: &str,
+        value: &GritMatchValue,
+        operator: TextOperator,
+        bindings: &VariableBindings,
+    ) -> Result<bool> {
+        let Some(subject) = self.resolve(node, var, bindings) else {
+            return Ok(false);
+        };
+        self.text_operator_holds(node, &subject.text(), value, operator, bindings)
+    }
+
+    fn text_operator_holds(
+        &self,
+        node: &FshGritNode,
+        text: &str,
+        value: &GritMatchValue,
+        operator: TextOperator,
+        bindings: &VariableBindings,
+    ) -> Result<bool> {
+        let needle = match value {
+            GritMatchValue::String(needle) => needle.clone(),
+            GritMatchValue::Variable(var) => match self.resolve(node, var, bindings) {
+                Some(value) => value.text(),
+                None => return Ok(false),
+            },
+            GritMatchValue::Regex(pattern) => {
+                let anchored = match operator {
+                    TextOperator::Contains => pattern.clone(),
+                    TextOperator::StartsWith => format!("^(?:{pattern})"),
+                    TextOperator::EndsWith => format!("(?:{pattern})$"),
+                };
+                return Ok(self.regex(&anchored)?.is_match(text));
+            }
+            GritMatchValue::Or(values) => {
+                for value in values {
+                    if self.text_operator_holds(node, text, value, operator, bindings)? {
+                        return Ok(true);
+                    }
+                }
+                return Ok(false);
+            }
+            GritMatchValue::And(values) => {
+                for value in values {
+                    if !self.text_operator_holds(node, text, value, operator, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                return Ok(true);
+            }
+        };
+
+        Ok(match operator {
+            TextOperator::Contains => text.contains(&needle),
+            TextOperator::StartsWith => text.starts_with(&needle),
+            TextOperator::EndsWith => text.ends_with(&needle),
+        })
+    }
+
+    /// Bind `name` to `value`, or require equal text if it is already bound
+    fn bind(
+        &self,
+        bindings: &VariableBindings,
+        name: &str,
+        value: BoundValue,
+    ) -> Result<Option<VariableBindings>> {
+        if let Some(existing) = bindings.get(name) {
+            return Ok((existing.text() == value.text()).then(|| bindings.clone()));
+        }
+        let mut bindings = bindings.clone();
+        bindings.insert(name.to_string(), value);
+        Ok(Some(bindings))
+    }
+
+    fn regex(&self, pattern: &str) -> Result<regex::Regex> {
+        regex::Regex::new(pattern)
+            .map_err(|e| MakiError::rule_error(self.rule_id, format!("Invalid regex pattern: {e}")))
+    }
+}
+
+/// Map a GritQL node kind name (e.g. `profile_declaration`) to its FSH syntax kind
+fn syntax_kind(kind: &str) -> Result<FshSyntaxKind> {
+    let normalized = kind.to_uppercase().replace('-', "_");
+
+    match normalized.as_str() {
+        "PROFILE" | "PROFILE_DECLARATION" => Ok(FshSyntaxKind::Profile),
+        "EXTENSION" | "EXTENSION_DECLARATION" => Ok(FshSyntaxKind::Extension),
+        "VALUESET" | "VALUE_SET" | "VALUESET_DECLARATION" => Ok(FshSyntaxKind::ValueSet),
+        "CODESYSTEM" | "CODE_SYSTEM" | "CODESYSTEM_DECLARATION" => Ok(FshSyntaxKind::CodeSystem),
+        "INSTANCE" | "INSTANCE_DECLARATION" => Ok(FshSyntaxKind::Instance),
+        "INVARIANT" | "INVARIANT_DECLARATION" => Ok(FshSyntaxKind::Invariant),
+        "MAPPING" | "MAPPING_DECLARATION" => Ok(FshSyntaxKind::Mapping),
+        "LOGICAL" | "LOGICAL_DECLARATION" => Ok(FshSyntaxKind::Logical),
+        "RESOURCE" | "RESOURCE_DECLARATION" => Ok(FshSyntaxKind::Resource),
+        "ALIAS" | "ALIAS_DECLARATION" => Ok(FshSyntaxKind::Alias),
+        "PARENT" => Ok(FshSyntaxKind::ParentKw),
+        "ID" => Ok(FshSyntaxKind::IdKw),
+        "TITLE" => Ok(FshSyntaxKind::TitleKw),
+        "DESCRIPTION" => Ok(FshSyntaxKind::DescriptionKw),
+        // Generic node types - map to closest equivalent
+        "IDENTIFIER" | "STATUS_FIELD" | "FIELD" => Ok(FshSyntaxKind::Ident),
+        "SLICING_RULE" | "SLICING" => Ok(FshSyntaxKind::PathRule), // closest match
+        "CARET_RULE" | "CARET_VALUE_RULE" | "CARET_PATH" => Ok(FshSyntaxKind::CaretValueRule),
+        "CARD_RULE" => Ok(FshSyntaxKind::CardRule),
+        "ASSIGNMENT_RULE" => Ok(FshSyntaxKind::PathRule), // closest match
+        "BINDING_RULE" => Ok(FshSyntaxKind::PathRule),    // closest match
+        "CARDINALITY_RULE" | "CARDINALITY" => Ok(FshSyntaxKind::CardRule),
+        "FLAG_RULE" => Ok(FshSyntaxKind::PathRule), // closest match
+        "VALUE_SET_COMPONENT" => Ok(FshSyntaxKind::ValueSet),
+        "CODE_CARDINALITY_RULE" => Ok(FshSyntaxKind::CardRule),
+        "CONTAINS_RULE" => Ok(FshSyntaxKind::ContainsRule),
+        "ONLY_RULE" => Ok(FshSyntaxKind::OnlyRule),
+        "OBEYS_RULE" => Ok(FshSyntaxKind::ObeysRule),
+        "INSERT_RULE" => Ok(FshSyntaxKind::InsertRule),
+        "PATH_RULE" => Ok(FshSyntaxKind::PathRule),
+        _ => Err(MakiError::rule_error(
+            "gritql-compiler",
+            format!("Unknown FSH syntax kind: {kind}"),
+        )),
+    }
+}
+
 /// Convert byte offset to line and column (1-indexed)
 fn offset_to_line_col(source: &str, offset: usize) -> (usize, usize) {
     let mut line = 1;
@@ -1102,4 +1319,143 @@ mod tests {
         assert_eq!(diagnostic.severity, Severity::Error);
         assert_eq!(diagnostic.message, "Test error message");
     }
+
+    const PROFILES: &str = r#"Profile: AlphaPatient
+Parent: Patient
+Title: "Alpha"
+
+Profile: BetaObservation
+Parent: Observation
+
+Profile: gammaPatient
+Parent: Patient
+"#;
+
+    fn matched_names(pattern: &str) -> Vec<String> {
+        let compiler = GritQLCompiler::new().unwrap();
+        let compiled = compiler.compile_pattern(pattern, "test-rule").unwrap();
+        compiled
+            .execute(PROFILES, "test.fsh")
+            .unwrap()
+            .into_iter()
+            .map(|m| {
+                m.matched_text
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_start_matches("Profile: ")
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_string_predicates_filter_matches() {
+        assert_eq!(
+            matched_names(r#"profile where { contains "Observation" }"#),
+            vec!["BetaObservation"]
+        );
+        assert_eq!(
+            matched_names(r#"Profile: $name where { $name startsWith "Alpha" }"#),
+            vec!["AlphaPatient"]
+        );
+        assert_eq!(
+            matched_names(r#"Profile: $name where { $name == "gammaPatient" }"#),
+            vec!["gammaPatient"]
+        );
+        assert!(matched_names(r#"profile where { contains "Nothing" }"#).is_empty());
+    }
+
+    #[test]
+    fn test_field_exists_checks_node_field() {
+        assert_eq!(
+            matched_names("profile where { title }"),
+            vec!["AlphaPatient"]
+        );
+        assert_eq!(
+            matched_names("profile where { not title }"),
+            vec!["BetaObservation", "gammaPatient"]
+        );
+    }
+
+    #[test]
+    fn test_parenthesized_not() {
+        assert_eq!(
+            matched_names(
+                r#"Profile: $name where { not ($name <: r"^[A-Z]" and contains "Parent: Patient") }"#
+            ),
+            vec!["BetaObservation", "gammaPatient"]
+        );
+    }
+
+    #[test]
+    fn test_accessor_and_list_index() {
+        assert_eq!(
+            matched_names(r#"profile where { $p where { $p.parent == "Observation" } }"#),
+            vec!["BetaObservation"]
+        );
+        assert_eq!(
+            matched_names(r#"profile where { $p where { $p.parent == "Observation" and $p[0] } }"#),
+            vec!["BetaObservation"]
+        );
+        assert_eq!(matched_names("profile where { $_[0] }").len(), 3);
+        assert!(matched_names("profile where { $_[100] }").is_empty());
+    }
+
+    #[test]
+    fn test_user_defined_pattern_and_function() {
+        let program = r#"
+            // Profiles deriving from Patient
+            pattern patient_profile($name) {
+                Profile: $name where { $_.parent == "Patient" }
+            }
+            function is_lower($text) { $text <: r"^[a-z]" }
+
+            patient_profile($name) where { is_lower($name) }
+        "#;
+        let compiler = GritQLCompiler::new().unwrap();
+        let compiled = compiler.compile_pattern(program, "test-rule").unwrap();
+        let matches = compiled.execute(PROFILES, "test.fsh").unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].captures.get("name").unwrap(), "gammaPatient");
+    }
+
+    #[test]
+    fn test_builtin_function_call() {
+        assert_eq!(
+            matched_names("Profile: $name where { not is_pascal_case($name) }"),
+            vec!["gammaPatient"]
+        );
+        assert_eq!(
+            matched_names("profile where { has_title() }"),
+            vec!["AlphaPatient"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_node_kind_is_rejected() {
+        let compiler = GritQLCompiler::new().unwrap();
+        let result = compiler.compile_pattern("not_a_node_kind", "test-rule");
+        assert!(result.is_err());
+
+        let result = compiler.compile_pattern(
+            "pattern p() { not_a_node_kind }\nprofile where { p() }",
+            "test-rule",
+        );
+        assert!(result.is_err(), "definition bodies should be checked");
+    }
+
+    #[test]
+    fn test_unknown_function_is_rejected() {
+        let compiler = GritQLCompiler::new().unwrap();
+        let result = compiler.compile_pattern("profile where { missing_fn($x) }", "test-rule");
+        assert!(result.is_err());
+
+        let result = compiler.compile_pattern(
+            "function f($a) { $a <: \"x\" }\nprofile where { f() }",
+            "test-rule",
+        );
+        assert!(result.is_err(), "arity mismatch should be rejected");
+    }
 }
@@ -38,8 +38,7 @@ pub mod cst_tree;
 // QueryContext - bridges our CST with grit-pattern-matcher
 pub mod query_context;
 
-// GritQL parser and compiler
-pub mod compiler;
+// GritQL parser
 pub mod parser;
 
 // Executor and built-ins
@@ -1,9 +1,92 @@
 //! GritQL pattern parser
 //!
 //! Parses GritQL syntax into an AST that can be compiled to Pattern structs.
+//!
+//! A `.grit` program is a list of `pattern`/`function` definitions followed by
+//! the main pattern:
+//!
+//! ```text
+//! pattern named_profile($name) { Profile: $name }
+//! function is_lower($text) { $text <: r"^[a-z]" }
+//!
+//! named_profile($name) where { is_lower($name) and not ($name <: "legacy") }
+//! ```
 
 use maki_core::{MakiError, Result};
 
+/// A parsed GritQL program: named definitions plus the pattern to execute
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GritProgram {
+    pub definitions: Vec<GritDefinition>,
+    pub pattern: GritPattern,
+}
+
+/// User-defined pattern or function declared at the top of a `.grit` file
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum GritDefinition {
+    /// `pattern name($a, $b) { <pattern> }` - reusable node pattern
+    Pattern {
+        name: String,
+        params: Vec<String>,
+        body: GritPattern,
+    },
+    /// `function name($a, $b) { <predicate> }` - reusable condition
+    Function {
+        name: String,
+        params: Vec<String>,
+        body: GritPredicate,
+    },
+}
+
+impl GritDefinition {
+    pub fn name(&self) -> &str {
+        match self {
+            GritDefinition::Pattern { name, .. } | GritDefinition::Function { name, .. } => name,
+        }
+    }
+
+    pub fn params(&self) -> &[String] {
+        match self {
+            GritDefinition::Pattern { params, .. } | GritDefinition::Function { params, .. } => {
+                params
+            }
+        }
+    }
+}
+
+/// One step of a variable accessor chain (`$profile.parent`, `$node[0]`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessorSegment {
+    Field(String),
+    Index(isize),
+}
+
+/// Split a variable reference such as `profile.parent` or `node[-1].name`
+/// into its base variable name and the accessor segments applied to it
+pub fn split_accessor_path(var: &str) -> (&str, Vec<AccessorSegment>) {
+    let base_end = var.find(['.', '[']).unwrap_or(var.len());
+    let (base, mut rest) = var.split_at(base_end);
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            segments.push(AccessorSegment::Field(after_dot[..end].to_string()));
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']').unwrap_or(after_bracket.len());
+            if let Ok(index) = after_bracket[..end].parse() {
+                segments.push(AccessorSegment::Index(index));
+            }
+            rest = after_bracket.get(end + 1..).unwrap_or("");
+        } else {
+            break;
+        }
+    }
+
+    (base, segments)
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum GritPattern {
     NodeKind(String),
@@ -25,6 +108,11 @@ pub enum GritPattern {
         pattern: Box<GritPattern>,
         args: Vec<String>,
     },
+    /// Call of a user-defined pattern: name($a, "value")
+    Call {
+        name: String,
+        args: Vec<GritMatchValue>,
+    },
 }
 
 /// Value expressions used in predicates (e.g., $var <: or { "a", "b" })
@@ -38,8 +126,14 @@ pub enum GritMatchValue {
     Or(Vec<GritMatchValue>),
     /// And expression: and { r"^A", r"Z$" }
     And(Vec<GritMatchValue>),
+    /// Another variable's value: $other
+    Variable(String),
 }
 
+/// Predicates used in `where` clauses
+///
+/// Variable names may carry accessor (`$profile.parent`) and list index
+/// (`$node[0]`) suffixes; see [`split_accessor_path`].
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum GritPredicate {
     /// Check if a field exists
@@ -67,6 +161,11 @@ pub enum GritPredicate {
         var: String,
         constraint: Box<GritPredicate>,
     },
+    /// Call of a user-defined function, pattern or FSH built-in: name($a, "value")
+    Call {
+        name: String,
+        args: Vec<GritMatchValue>,
+    },
 }
 
 pub struct GritQLParser {
@@ -87,10 +186,160 @@ impl GritQLParser {
         self.parse_pattern()
     }
 
+    /// Parse a whole `.grit` program: definitions followed by the main pattern
+    pub fn parse_program(&mut self) -> Result<GritProgram> {
+        let mut definitions = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.peek_keyword("pattern") {
+                definitions.push(self.parse_pattern_definition()?);
+            } else if self.peek_keyword("function") {
+                definitions.push(self.parse_function_definition()?);
+            } else {
+                break;
+            }
+        }
+
+        let pattern = self.parse_pattern()?;
+
+        self.skip_whitespace();
+        if self.pos < self.input.len() {
+            return Err(MakiError::rule_error(
+                "gritql-parser",
+                format!(
+                    "Unexpected input after pattern: {:?}",
+                    self.input[self.pos..].chars().take(20).collect::<String>()
+                ),
+            ));
+        }
+
+        Ok(GritProgram {
+            definitions,
+            pattern,
+        })
+    }
+
+    /// Parse: pattern name($a, $b) { <pattern> }
+    fn parse_pattern_definition(&mut self) -> Result<GritDefinition> {
+        self.consume_word("pattern")?;
+        let name = self.parse_identifier()?;
+        let params = self.parse_params()?;
+
+        self.skip_whitespace();
+        self.expect_char('{')?;
+        let body = self.parse_pattern()?;
+        self.skip_whitespace();
+        self.expect_char('}')?;
+
+        Ok(GritDefinition::Pattern { name, params, body })
+    }
+
+    /// Parse: function name($a, $b) { <predicate> }
+    fn parse_function_definition(&mut self) -> Result<GritDefinition> {
+        self.consume_word("function")?;
+        let name = self.parse_identifier()?;
+        let params = self.parse_params()?;
+        let body = self.parse_predicate()?;
+
+        Ok(GritDefinition::Function { name, params, body })
+    }
+
+    /// Parse a definition parameter list: ($a, $b)
+    fn parse_params(&mut self) -> Result<Vec<String>> {
+        self.skip_whitespace();
+        self.expect_char('(')?;
+        self.skip_whitespace();
+
+        let mut params = Vec::new();
+        while self.current_char() != Some(')') {
+            self.expect_char('$')?;
+            params.push(self.parse_identifier()?);
+            self.skip_whitespace();
+
+            if self.current_char() == Some(',') {
+                self.advance();
+                self.skip_whitespace();
+            } else {
+                break;
+            }
+        }
+
+        self.expect_char(')')?;
+        Ok(params)
+    }
+
+    /// Parse call arguments: ($a, "value", r"regex")
+    fn parse_call_args(&mut self) -> Result<Vec<GritMatchValue>> {
+        self.expect_char('(')?;
+        self.skip_whitespace();
+
+        let mut args = Vec::new();
+        while self.current_char() != Some(')') {
+            if self.current_char() == Some('$') {
+                self.advance();
+                args.push(GritMatchValue::Variable(self.parse_variable_reference()?));
+            } else {
+                args.push(self.parse_simple_match_value()?);
+            }
+            self.skip_whitespace();
+
+            if self.current_char() == Some(',') {
+                self.advance();
+                self.skip_whitespace();
+            } else {
+                break;
+            }
+        }
+
+        self.expect_char(')')?;
+        Ok(args)
+    }
+
+    /// Parse a variable name (after `$`) including accessor and index suffixes
+    fn parse_variable_reference(&mut self) -> Result<String> {
+        let mut reference = self.parse_identifier()?;
+
+        loop {
+            match self.current_char() {
+                Some('.')
+                    if self.input[self.pos + 1..]
+                        .chars()
+                        .next()
+                        .is_some_and(|ch| ch.is_alphanumeric() || ch == '_') =>
+                {
+                    self.advance();
+                    reference.push('.');
+                    reference.push_str(&self.parse_identifier()?);
+                }
+                Some('[') => {
+                    self.advance();
+                    self.skip_whitespace();
+                    let start = self.pos;
+                    if self.current_char() == Some('-') {
+                        self.advance();
+                    }
+                    while self.current_char().is_some_and(|ch| ch.is_ascii_digit()) {
+                        self.advance();
+                    }
+                    let index = self.input[start..self.pos].parse::<isize>().map_err(|_| {
+                        MakiError::rule_error("gritql-parser", "Expected integer list index")
+                    })?;
+                    self.skip_whitespace();
+                    self.expect_char(']')?;
+                    reference.push_str(&format!("[{index}]"));
+                }
+                _ => break,
+            }
+        }
+
+        Ok(reference)
+    }
+
     fn parse_pattern(&mut self) -> Result<GritPattern> {
         self.skip_whitespace();
 
-        if self.peek_word("not") {
+        if self.peek_keyword("not") {
             self.consume_word("not")?;
             self.skip_whitespace();
             let pattern = self.parse_pattern()?;
@@ -120,8 +369,29 @@ impl GritQLParser {
     fn parse_base_pattern(&mut self) -> Result<GritPattern> {
         let ident = self.parse_identifier()?;
 
-        // Check for ": $variable" syntax (e.g., "Profile: $name")
+        // Check for a pattern call (e.g., "named_profile($name)")
+        if self.current_char() == Some('(') {
+            let args = self.parse_call_args()?;
+            return Ok(GritPattern::Call { name: ident, args });
+        }
+
+        // Check for the older "Profile $name { * }" syntax
         self.skip_whitespace();
+        if self.current_char() == Some('$') {
+            self.advance(); // consume '$'
+            let var_name = self.parse_identifier()?;
+            self.parse_wildcard_body()?;
+            return Ok(GritPattern::Assignment {
+                var: var_name,
+                value: Box::new(GritPattern::NodeKind(ident)),
+            });
+        }
+        if self.current_char() == Some('{') {
+            self.parse_wildcard_body()?;
+            return Ok(GritPattern::NodeKind(ident));
+        }
+
+        // Check for ": $variable" syntax (e.g., "Profile: $name")
         if self.current_char() == Some(':') {
             self.advance(); // consume ':'
             self.skip_whitespace();
@@ -151,6 +421,19 @@ impl GritQLParser {
         Ok(GritPattern::NodeKind(ident))
     }
 
+    /// Parse an optional `{ * }` body, which matches any node contents
+    fn parse_wildcard_body(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        if self.current_char() != Some('{') {
+            return Ok(());
+        }
+        self.advance();
+        self.skip_whitespace();
+        self.expect_char('*')?;
+        self.skip_whitespace();
+        self.expect_char('}')
+    }
+
     fn parse_variable(&mut self) -> Result<GritPattern> {
         self.expect_char('$')?;
         let name = self.parse_identifier()?;
@@ -172,32 +455,6 @@ impl GritQLParser {
     fn parse_predicate(&mut self) -> Result<GritPredicate> {
         self.skip_whitespace();
         self.expect_char('{')?;
-        self.skip_whitespace();
-
-        // Check for pattern-level 'or {' or 'and {' or 'not' blocks
-        if self.peek_word("or") && self.peek_ahead_for_brace() {
-            let pred = self.parse_or_predicate_block()?;
-            self.skip_whitespace();
-            self.expect_char('}')?;
-            return Ok(pred);
-        }
-
-        if self.peek_word("and") && self.peek_ahead_for_brace() {
-            let pred = self.parse_and_predicate_block()?;
-            self.skip_whitespace();
-            self.expect_char('}')?;
-            return Ok(pred);
-        }
-
-        // Parse: not inner
-        if self.peek_word("not") {
-            self.consume_word("not")?;
-            self.skip_whitespace();
-            let pred = self.parse_predicate_inner()?;
-            self.skip_whitespace();
-            self.expect_char('}')?;
-            return Ok(GritPredicate::Not(Box::new(pred)));
-        }
 
         let pred = self.parse_predicate_inner()?;
         self.skip_whitespace();
@@ -272,27 +529,76 @@ impl GritQLParser {
         Ok(GritPredicate::And(preds))
     }
 
+    /// Parse a predicate expression; `not` binds tighter than `and`, which
+    /// binds tighter than `or`. Parentheses group sub-expressions.
     fn parse_predicate_inner(&mut self) -> Result<GritPredicate> {
+        let first = self.parse_and_predicate()?;
         self.skip_whitespace();
 
-        // Check for nested 'or {' or 'and {' blocks
-        if self.peek_word("or") && self.peek_ahead_for_brace() {
-            return self.parse_or_predicate_block();
+        if !self.peek_keyword("or") || self.peek_ahead_for_brace() {
+            return Ok(first);
         }
 
-        if self.peek_word("and") && self.peek_ahead_for_brace() {
-            return self.parse_and_predicate_block();
+        let mut preds = vec![first];
+        while self.peek_keyword("or") && !self.peek_ahead_for_brace() {
+            self.consume_word("or")?;
+            preds.push(self.parse_and_predicate()?);
+            self.skip_whitespace();
         }
+        Ok(GritPredicate::Or(preds))
+    }
 
-        // Check for 'not' prefix
-        if self.peek_word("not") {
-            self.consume_word("not")?;
+    fn parse_and_predicate(&mut self) -> Result<GritPredicate> {
+        let first = self.parse_unary_predicate()?;
+        self.skip_whitespace();
+
+        if !self.peek_keyword("and") || self.peek_ahead_for_brace() {
+            return Ok(first);
+        }
+
+        let mut preds = vec![first];
+        while self.peek_keyword("and") && !self.peek_ahead_for_brace() {
+            self.consume_word("and")?;
+            preds.push(self.parse_unary_predicate()?);
             self.skip_whitespace();
-            let pred = self.parse_simple_predicate()?;
+        }
+        Ok(GritPredicate::And(preds))
+    }
+
+    fn parse_unary_predicate(&mut self) -> Result<GritPredicate> {
+        self.skip_whitespace();
+
+        if self.peek_keyword("not") {
+            self.consume_word("not")?;
+            let pred = self.parse_unary_predicate()?;
             return Ok(GritPredicate::Not(Box::new(pred)));
         }
 
-        // NEW: Check for variable pattern with constraints: $var where { predicates }
+        self.parse_primary_predicate()
+    }
+
+    fn parse_primary_predicate(&mut self) -> Result<GritPredicate> {
+        self.skip_whitespace();
+
+        // Parenthesized group: ( predicate )
+        if self.current_char() == Some('(') {
+            self.advance();
+            let pred = self.parse_predicate_inner()?;
+            self.skip_whitespace();
+            self.expect_char(')')?;
+            return Ok(pred);
+        }
+
+        // Check for nested 'or {' or 'and {' blocks
+        if self.peek_keyword("or") && self.peek_ahead_for_brace() {
+            return self.parse_or_predicate_block();
+        }
+
+        if self.peek_keyword("and") && self.peek_ahead_for_brace() {
+            return self.parse_and_predicate_block();
+        }
+
+        // Check for variable pattern with constraints: $var where { predicates }
         // IMPORTANT: Must have braces after 'where' to distinguish from regular predicates
         if self.current_char() == Some('$') {
             let start_pos = self.pos;
@@ -300,35 +606,16 @@ impl GritQLParser {
             let var_name = self.parse_identifier()?;
             self.skip_whitespace();
 
-            // Check if followed by 'where {'
-            if self.peek_word("where") {
-                let after_where_pos = self.pos + 5; // "where".len()
-
-                // Look ahead to check for '{' after 'where'
-                let mut temp_pos = after_where_pos;
-                while temp_pos < self.input.len() {
-                    match self.input.chars().nth(temp_pos) {
-                        Some('{') => {
-                            // This is a variable pattern: $var where { ... }
-                            self.consume_word("where")?;
-                            self.skip_whitespace();
-
-                            // Parse nested where clause predicates
-                            let constraint = self.parse_predicate()?;
-
-                            return Ok(GritPredicate::VariablePattern {
-                                var: var_name,
-                                constraint: Box::new(constraint),
-                            });
-                        }
-                        Some(c) if c.is_whitespace() => {
-                            temp_pos += 1;
-                        }
-                        _ => {
-                            // Not a variable pattern, it's $var where <something else>
-                            break;
-                        }
-                    }
+            if self.peek_keyword("where") {
+                self.consume_word("where")?;
+                self.skip_whitespace();
+
+                if self.current_char() == Some('{') {
+                    let constraint = self.parse_predicate()?;
+                    return Ok(GritPredicate::VariablePattern {
+                        var: var_name,
+                        constraint: Box::new(constraint),
+                    });
                 }
             }
 
@@ -336,34 +623,7 @@ impl GritQLParser {
             self.pos = start_pos;
         }
 
-        let first = self.parse_simple_predicate()?;
-
-        self.skip_whitespace();
-
-        // Check for infix 'and' or 'or' operators (without braces)
-        if self.peek_word("and") && !self.peek_ahead_for_brace() {
-            let mut preds = vec![first];
-            while self.peek_word("and") && !self.peek_ahead_for_brace() {
-                self.consume_word("and")?;
-                self.skip_whitespace();
-                preds.push(self.parse_simple_predicate()?);
-                self.skip_whitespace();
-            }
-            return Ok(GritPredicate::And(preds));
-        }
-
-        if self.peek_word("or") && !self.peek_ahead_for_brace() {
-            let mut preds = vec![first];
-            while self.peek_word("or") && !self.peek_ahead_for_brace() {
-                self.consume_word("or")?;
-                self.skip_whitespace();
-                preds.push(self.parse_simple_predicate()?);
-                self.skip_whitespace();
-            }
-            return Ok(GritPredicate::Or(preds));
-        }
-
-        Ok(first)
+        self.parse_simple_predicate()
     }
 
     fn parse_simple_predicate(&mut self) -> Result<GritPredicate> {
@@ -406,7 +666,21 @@ impl GritQLParser {
             self.advance();
         }
 
-        let field_or_var = self.parse_identifier()?;
+        let field_or_var = if has_dollar {
+            self.parse_variable_reference()?
+        } else {
+            self.parse_identifier()?
+        };
+
+        // Function, pattern or built-in call: name($a, "value")
+        if !has_dollar && self.current_char() == Some('(') {
+            let args = self.parse_call_args()?;
+            return Ok(GritPredicate::Call {
+                name: field_or_var,
+                args,
+            });
+        }
+
         self.skip_whitespace();
 
         // Check for operators
@@ -498,10 +772,15 @@ impl GritQLParser {
         self.parse_simple_match_value()
     }
 
-    /// Parse a simple match value (string or regex)
+    /// Parse a simple match value (string, regex or variable)
     fn parse_simple_match_value(&mut self) -> Result<GritMatchValue> {
         self.skip_whitespace();
 
+        if self.current_char() == Some('$') {
+            self.advance();
+            return Ok(GritMatchValue::Variable(self.parse_variable_reference()?));
+        }
+
         // Check for r"..." regex
         if self.peek_word("r\"") {
             self.consume_word("r")?;
@@ -696,13 +975,26 @@ impl GritQLParser {
         Ok(self.input[start..self.pos].to_string())
     }
 
+    /// Skip whitespace and `//` line comments
     fn skip_whitespace(&mut self) {
-        while let Some(ch) = self.current_char() {
-            if ch.is_whitespace() {
-                self.advance();
-            } else {
+        loop {
+            while let Some(ch) = self.current_char() {
+                if ch.is_whitespace() {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+
+            if !self.peek_word("//") {
                 break;
             }
+            while let Some(ch) = self.current_char() {
+                self.advance();
+                if ch == '\n' {
+                    break;
+                }
+            }
         }
     }
 
@@ -732,6 +1024,15 @@ impl GritQLParser {
         self.input[self.pos..].starts_with(word)
     }
 
+    /// Like `peek_word`, but only when `word` is not the prefix of a longer identifier
+    fn peek_keyword(&self, word: &str) -> bool {
+        self.peek_word(word)
+            && !self.input[self.pos + word.len()..]
+                .chars()
+                .next()
+                .is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == '-')
+    }
+
     fn consume_word(&mut self, word: &str) -> Result<()> {
         if self.peek_word(word) {
             self.pos += word.len();
@@ -1162,4 +1463,149 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parse_parenthesized_not() {
+        let mut parser =
+            GritQLParser::new(r#"profile where { not (contains "a" and contains "b") }"#);
+        let result = parser.parse().unwrap();
+
+        let GritPattern::Where(_, predicate) = result else {
+            panic!("Expected Where pattern");
+        };
+        match *predicate {
+            GritPredicate::Not(inner) => {
+                assert!(matches!(*inner, GritPredicate::And(ref preds) if preds.len() == 2));
+            }
+            other => panic!("Expected Not predicate, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_operator_precedence() {
+        let mut parser = GritQLParser::new(
+            r#"profile where { not contains "a" and contains "b" or contains "c" }"#,
+        );
+        let result = parser.parse().unwrap();
+
+        let GritPattern::Where(_, predicate) = result else {
+            panic!("Expected Where pattern");
+        };
+        let GritPredicate::Or(preds) = *predicate else {
+            panic!("Expected Or at the top level");
+        };
+        assert_eq!(preds.len(), 2);
+        let GritPredicate::And(ref and_preds) = preds[0] else {
+            panic!("Expected And as first operand");
+        };
+        assert!(matches!(and_preds[0], GritPredicate::Not(_)));
+        assert!(matches!(preds[1], GritPredicate::Contains { .. }));
+    }
+
+    #[test]
+    fn test_parse_program_with_definitions() {
+        let source = r#"
+            // Reusable pieces
+            pattern named_profile($name) { Profile: $name }
+            function is_lower($text) { $text <: r"^[a-z]" }
+
+            named_profile($name) where { is_lower($name) }
+        "#;
+        let program = GritQLParser::new(source).parse_program().unwrap();
+
+        assert_eq!(program.definitions.len(), 2);
+        assert_eq!(program.definitions[0].name(), "named_profile");
+        assert_eq!(program.definitions[1].params(), ["text".to_string()]);
+        assert!(matches!(
+            program.definitions[1],
+            GritDefinition::Function { .. }
+        ));
+
+        let GritPattern::Where(base, predicate) = program.pattern else {
+            panic!("Expected Where pattern");
+        };
+        assert_eq!(
+            *base,
+            GritPattern::Call {
+                name: "named_profile".to_string(),
+                args: vec![GritMatchValue::Variable("name".to_string())],
+            }
+        );
+        assert_eq!(
+            *predicate,
+            GritPredicate::Call {
+                name: "is_lower".to_string(),
+                args: vec![GritMatchValue::Variable("name".to_string())],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_program_rejects_trailing_input() {
+        let result = GritQLParser::new("profile where { title } extra").parse_program();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_binding_with_wildcard_body() {
+        let expected = GritPattern::Assignment {
+            var: "name".to_string(),
+            value: Box::new(GritPattern::NodeKind("Profile".to_string())),
+        };
+        let program = GritQLParser::new("Profile $name { * }")
+            .parse_program()
+            .unwrap();
+        assert_eq!(program.pattern, expected);
+        assert_eq!(
+            GritQLParser::new("Profile $name").parse().unwrap(),
+            expected
+        );
+        assert_eq!(
+            GritQLParser::new("Profile { * }")
+                .parse_program()
+                .unwrap()
+                .pattern,
+            GritPattern::NodeKind("Profile".to_string())
+        );
+        assert!(
+            GritQLParser::new("Profile $name { title }")
+                .parse_program()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_accessor_and_index() {
+        let mut parser =
+            GritQLParser::new(r#"profile where { $p.parent == "Patient" and $p[-1] <: "x" }"#);
+        let result = parser.parse().unwrap();
+
+        let GritPattern::Where(_, predicate) = result else {
+            panic!("Expected Where pattern");
+        };
+        let GritPredicate::And(preds) = *predicate else {
+            panic!("Expected And predicate");
+        };
+        assert!(matches!(preds[0], GritPredicate::Equality { ref left, .. } if left == "p.parent"));
+        assert!(matches!(preds[1], GritPredicate::Match { ref var, .. } if var == "p[-1]"));
+    }
+
+    #[test]
+    fn test_split_accessor_path() {
+        assert_eq!(split_accessor_path("name"), ("name", vec![]));
+        assert_eq!(
+            split_accessor_path("p.parent"),
+            ("p", vec![AccessorSegment::Field("parent".to_string())])
+        );
+        assert_eq!(
+            split_accessor_path("p[-1].name"),
+            (
+                "p",
+                vec![
+                    AccessorSegment::Index(-1),
+                    AccessorSegment::Field("name".to_string())
+                ]
+            )
+        );
+    }
 }
@@ -8,7 +8,10 @@ pub mod engine;
 pub mod fhir_registry;
 pub mod gritql;
 pub mod gritql_ast;
+pub mod options;
 pub mod pattern_parser;
+pub mod project;
+pub mod suppression;
 
 // Re-export commonly used types
 pub use builtin::BuiltinRules;
@@ -21,7 +24,10 @@ pub use gritql_ast::{
     AstMatch, AstPattern, NodeType, Predicate, execute_pattern,
     matches_to_diagnostics as ast_matches_to_diagnostics,
 };
+pub use options::RuleOptions;
 pub use pattern_parser::parse_pattern;
+pub use project::{FnProjectRule, ProjectContext, ProjectRule};
+pub use suppression::{Suppression, SuppressionKind, Suppressions};
 
 /// Initialize the built-in rules registry
 ///
@@ -0,0 +1,220 @@
+//! Rule options
+//!
+//! Builtin rules that can be configured declare a typed options struct that
+//! implements [`RuleOptions`]. Options are set per rule in the linter
+//! configuration, next to the severity:
+//!
+//! ```jsonc
+//! {
+//!   "linter": {
+//!     "rules": {
+//!       "style": {
+//!         "naming-convention": {
+//!           "severity": "warn",
+//!           "options": { "names": { "profile": "[A-Z][A-Za-z0-9]*Profile" } }
+//!         }
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! [`validate_rule_options`] rejects options that don't match the rule's
+//! struct or are set on a rule without one, and [`options_schemas`] publishes
+//! the structs as JSON Schemas.
+
+use crate::builtin::BuiltinRules;
+use crate::builtin::metadata::MissingMetadataOptions;
+use crate::builtin::naming::NamingConventionOptions;
+use maki_core::config::{RuleSeverity, RulesConfiguration};
+use maki_core::{MakiError, Result, RuleConfig, Severity, UnifiedConfig};
+use schemars::{JsonSchema, Schema};
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+
+/// Typed options of a builtin rule
+pub trait RuleOptions: DeserializeOwned + JsonSchema + Default {
+    /// ID of the rule the options belong to
+    const RULE_ID: &'static str;
+
+    /// Check constraints serde can't express, such as regex syntax
+    fn validate(&self) -> std::result::Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Parse and validate `options` for the rule of `T`
+pub fn parse_options<T: RuleOptions>(
+    options: &serde_json::Value,
+) -> std::result::Result<T, String> {
+    let parsed: T = serde_json::from_value(options.clone()).map_err(|e| e.to_string())?;
+    parsed.validate()?;
+    Ok(parsed)
+}
+
+/// JSON Schemas of the options of all configurable builtin rules, by rule ID
+pub fn options_schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        schema::<NamingConventionOptions>(),
+        schema::<MissingMetadataOptions>(),
+    ]
+}
+
+fn schema<T: RuleOptions>() -> (&'static str, Schema) {
+    (T::RULE_ID, schemars::schema_for!(T))
+}
+
+/// Check the options of all rules configured in `config` and its overrides
+///
+/// Options of custom rules are not checked. Options set on builtin rules
+/// without an options struct are errors naming the rule.
+pub fn validate_rule_options(config: &UnifiedConfig) -> Result<()> {
+    let overrides = config.overrides.iter().flatten();
+    let all_rules: Vec<&RulesConfiguration> = std::iter::once(config.linter.as_ref())
+        .chain(overrides.map(|entry| entry.linter.as_ref()))
+        .filter_map(|linter| linter?.rules.as_ref())
+        .collect();
+    if all_rules.is_empty() {
+        return Ok(());
+    }
+    let builtin: HashSet<String> = BuiltinRules::all_rules()
+        .into_iter()
+        .map(|rule| rule.id)
+        .collect();
+
+    let mut errors: Vec<String> = all_rules
+        .into_iter()
+        .flat_map(|rules| rules.rules())
+        .filter_map(|(id, setting)| {
+            let options = setting.options()?;
+            let result = match id.as_str() {
+                NamingConventionOptions::RULE_ID => {
+                    parse_options::<NamingConventionOptions>(options).map(drop)
+                }
+                MissingMetadataOptions::RULE_ID => {
+                    parse_options::<MissingMetadataOptions>(options).map(drop)
+                }
+                id if builtin.contains(id) => Err("this rule takes no options".to_string()),
+                _ => Ok(()),
+            };
+            result
+                .err()
+                .map(|error| format!("Invalid options for rule '{id}': {error}"))
+        })
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    errors.sort();
+    errors.dedup();
+    Err(MakiError::ConfigError {
+        message: errors.join("\n"),
+    })
+}
+
+/// Per-rule engine configuration from the linter rules in `config`
+///
+/// `off` disables a rule, other severities override the rule's own.
+pub fn rule_configs(config: &UnifiedConfig) -> HashMap<String, RuleConfig> {
+    let Some(rules) = config.linter.as_ref().and_then(|l| l.rules.as_ref()) else {
+        return HashMap::new();
+    };
+    rules
+        .rules()
+        .map(|(id, setting)| {
+            let severity = match setting.severity() {
+                Some(RuleSeverity::Info) => Some(Severity::Info),
+                Some(RuleSeverity::Warn) => Some(Severity::Warning),
+                Some(RuleSeverity::Error) => Some(Severity::Error),
+                Some(RuleSeverity::Off) | None => None,
+            };
+            let options = setting
+                .options()
+                .and_then(|options| options.as_object())
+                .map(|options| options.clone().into_iter().collect())
+                .unwrap_or_default();
+            let config = RuleConfig {
+                severity,
+                options,
+                enabled: setting.severity() != Some(RuleSeverity::Off),
+            };
+            (id, config)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(rules: serde_json::Value) -> UnifiedConfig {
+        serde_json::from_value(serde_json::json!({ "linter": { "rules": rules } })).unwrap()
+    }
+
+    #[test]
+    fn test_validate_rule_options() {
+        let valid = config(serde_json::json!({
+            "style": {
+                "naming-convention": { "severity": "warn", "options": { "ids": { "profile": "[a-z-]+" } } }
+            },
+            "documentation": {
+                "missing-metadata": { "options": { "requiredFields": ["title"] } }
+            }
+        }));
+        assert!(validate_rule_options(&valid).is_ok());
+
+        let invalid = config(serde_json::json!({
+            "style": {
+                "naming-convention": { "options": { "names": { "profile": "([A-Z]" } } }
+            },
+            "documentation": {
+                "missing-metadata": { "options": { "requiredFields": ["summary"] } }
+            },
+            "blocking": {
+                "duplicate-definition": { "options": { "strict": true } }
+            }
+        }));
+        let message = validate_rule_options(&invalid).unwrap_err().to_string();
+        assert!(message.contains(
+            "Invalid options for rule 'blocking/duplicate-definition': this rule takes no options"
+        ));
+        assert!(message.contains("'documentation/missing-metadata': unknown variant `summary`"));
+        assert!(message.contains("'style/naming-convention': invalid regex '([A-Z]'"));
+
+        let invalid_override: UnifiedConfig = serde_json::from_value(serde_json::json!({
+            "overrides": [{
+                "include": ["examples/**"],
+                "linter": { "rules": { "documentation": {
+                    "missing-metadata": { "options": { "requiredFields": ["summary"] } }
+                } } }
+            }]
+        }))
+        .unwrap();
+        assert!(validate_rule_options(&invalid_override).is_err());
+    }
+
+    #[test]
+    fn test_rule_configs() {
+        let configs = rule_configs(&config(serde_json::json!({
+            "style": {
+                "naming-convention": { "severity": "error", "options": { "names": {} } }
+            },
+            "documentation": { "missing-metadata": "off" }
+        })));
+
+        let naming = &configs["style/naming-convention"];
+        assert!(naming.enabled);
+        assert_eq!(naming.severity, Some(Severity::Error));
+        assert!(naming.options.contains_key("names"));
+        assert!(!configs["documentation/missing-metadata"].enabled);
+    }
+
+    #[test]
+    fn test_options_schemas() {
+        let schemas = options_schemas();
+        let (id, naming) = &schemas[0];
+        assert_eq!(*id, "style/naming-convention");
+        let naming = naming.as_value();
+        assert!(naming["properties"]["names"].is_object());
+    }
+}
@@ -0,0 +1,224 @@
+//! Project-wide lint rules
+//!
+//! Most builtin rules see one file's [`SemanticModel`] at a time. Project
+//! rules run once per lint over every file, with a merged symbol table and a
+//! dependency graph of all definitions, so they can catch problems spanning
+//! files: duplicate ids, parent cycles, definitions that are never used.
+//! Their diagnostics may point into any file.
+//!
+//! [`DefaultRuleEngine::execute_project_rules`](crate::DefaultRuleEngine::execute_project_rules)
+//! runs them; once handed back with
+//! [`set_project_diagnostics`](crate::DefaultRuleEngine::set_project_diagnostics),
+//! each diagnostic is reported with the diagnostics of its file, so inline
+//! suppressions apply as usual.
+
+use crate::builtin::{duplicates, profile, unused};
+use maki_core::cst::ast::{AstNode, Document};
+use maki_core::semantic::{DependencyAnalyzer, DependencyGraph, FhirResource, SymbolTable};
+use maki_core::{Diagnostic, SemanticModel, UnifiedConfig};
+use std::path::Path;
+
+/// Everything a project rule can see
+pub struct ProjectContext<'a> {
+    /// Models of all linted files
+    pub models: &'a [SemanticModel],
+    /// Symbols of all files; a name defined twice keeps its last definition
+    pub symbols: SymbolTable,
+    /// Dependencies between all definitions, keyed by resource id
+    pub dependencies: DependencyGraph,
+    /// Project configuration, when linting with one
+    pub config: Option<&'a UnifiedConfig>,
+}
+
+impl<'a> ProjectContext<'a> {
+    pub fn new(models: &'a [SemanticModel]) -> Self {
+        let mut symbols = SymbolTable::default();
+        for model in models {
+            for name in model.symbols.symbol_names() {
+                if let Some(symbol) = model.symbols.get_symbol(name) {
+                    symbols.add_symbol(symbol.clone());
+                }
+            }
+        }
+
+        Self {
+            models,
+            symbols,
+            dependencies: DependencyAnalyzer::build_project_graph(models),
+            config: None,
+        }
+    }
+
+    /// Use the project configuration `config`
+    pub fn with_config(mut self, config: &'a UnifiedConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Each file's model with its document
+    pub fn documents(&self) -> impl Iterator<Item = (&'a SemanticModel, Document)> + 'a {
+        self.models
+            .iter()
+            .filter_map(|model| Document::cast(model.cst.clone()).map(|document| (model, document)))
+    }
+
+    /// Model of `file`
+    pub fn model(&self, file: &Path) -> Option<&'a SemanticModel> {
+        self.models.iter().find(|model| model.source_file == file)
+    }
+
+    /// All resources with the model that defines them
+    pub fn resources(&self) -> impl Iterator<Item = (&'a SemanticModel, &'a FhirResource)> + 'a {
+        self.models
+            .iter()
+            .flat_map(|model| model.resources.iter().map(move |r| (model, r)))
+    }
+
+    /// Resource with the given id or name
+    pub fn resource(&self, name: &str) -> Option<&'a FhirResource> {
+        self.resources()
+            .map(|(_, resource)| resource)
+            .find(|r| r.id == name || r.name.as_deref() == Some(name))
+    }
+}
+
+/// A rule that checks the whole project at once
+pub trait ProjectRule: Send + Sync {
+    /// Rule ID; the rule only runs while a rule with this ID is registered
+    /// and enabled
+    fn id(&self) -> &str;
+
+    fn check(&self, project: &ProjectContext<'_>) -> Vec<Diagnostic>;
+}
+
+/// Project rule backed by a check function
+pub struct FnProjectRule {
+    id: &'static str,
+    check: fn(&ProjectContext<'_>) -> Vec<Diagnostic>,
+}
+
+impl FnProjectRule {
+    pub const fn new(id: &'static str, check: fn(&ProjectContext<'_>) -> Vec<Diagnostic>) -> Self {
+        Self { id, check }
+    }
+}
+
+impl ProjectRule for FnProjectRule {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn check(&self, project: &ProjectContext<'_>) -> Vec<Diagnostic> {
+        (self.check)(project)
+    }
+}
+
+/// Builtin project rules
+pub fn builtin_project_rules() -> Vec<Box<dyn ProjectRule>> {
+    vec![
+        Box::new(FnProjectRule::new(
+            duplicates::DUPLICATE_DEFINITION,
+            duplicates::check_project_duplicates,
+        )),
+        Box::new(FnProjectRule::new(
+            duplicates::DUPLICATE_ALIAS,
+            duplicates::check_project_duplicate_aliases,
+        )),
+        Box::new(FnProjectRule::new(
+            profile::PROFILE_CIRCULAR_PARENT,
+            profile::check_circular_parents,
+        )),
+        Box::new(FnProjectRule::new(
+            unused::UNUSED_RULESET,
+            unused::check_unused_rulesets,
+        )),
+        Box::new(FnProjectRule::new(
+            unused::UNUSED_ALIAS,
+            unused::check_unused_aliases,
+        )),
+        Box::new(FnProjectRule::new(
+            unused::UNUSED_INVARIANT,
+            unused::check_unused_invariants,
+        )),
+        Box::new(FnProjectRule::new(
+            unused::UNUSED_VALUESET,
+            unused::check_unused_value_sets,
+        )),
+        Box::new(FnProjectRule::new(
+            unused::UNUSED_CODESYSTEM,
+            unused::check_unused_code_systems,
+        )),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maki_core::semantic::SemanticAnalyzer;
+    use maki_core::{DefaultSemanticAnalyzer, FshParser};
+    use std::path::PathBuf;
+
+    fn model(file: &str, source: &str) -> SemanticModel {
+        let parsed = FshParser::parse_content(source).unwrap();
+        DefaultSemanticAnalyzer::new()
+            .analyze(&parsed.cst, &parsed.source, PathBuf::from(file))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_project_context_spans_files() {
+        let models = vec![
+            model("a.fsh", "Profile: A\nParent: B\nId: profile-a\n"),
+            model("b.fsh", "Profile: B\nParent: Patient\nId: profile-b\n"),
+        ];
+        let project = ProjectContext::new(&models);
+
+        assert!(project.symbols.contains_symbol("profile-a"));
+        assert!(project.symbols.contains_symbol("profile-b"));
+        assert_eq!(
+            project.resource("B").map(|r| r.id.as_str()),
+            Some("profile-b")
+        );
+        // Parent by name resolves to the id of the profile in the other file
+        assert_eq!(
+            project.dependencies.get_dependencies("profile-a"),
+            vec!["profile-b"]
+        );
+        assert_eq!(
+            project.model(Path::new("b.fsh")).map(|m| m.resources.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_builtin_project_rules_report_across_files() {
+        let models = vec![
+            model("a.fsh", "Profile: A\nParent: B\nId: shared\n"),
+            model("b.fsh", "Profile: B\nParent: A\nId: profile-b\n"),
+            model("c.fsh", "ValueSet: VS\nId: shared\n"),
+        ];
+        let project = ProjectContext::new(&models);
+        let diagnostics: Vec<Diagnostic> = builtin_project_rules()
+            .iter()
+            .flat_map(|rule| rule.check(&project))
+            .collect();
+
+        let files_for = |rule_id: &str| {
+            let mut files: Vec<_> = diagnostics
+                .iter()
+                .filter(|d| d.rule_id == rule_id)
+                .map(|d| d.location.file.to_string_lossy().to_string())
+                .collect();
+            files.sort();
+            files
+        };
+        assert_eq!(
+            files_for(duplicates::DUPLICATE_DEFINITION),
+            vec!["a.fsh", "c.fsh"]
+        );
+        assert_eq!(
+            files_for(profile::PROFILE_CIRCULAR_PARENT),
+            vec!["a.fsh", "b.fsh"]
+        );
+    }
+}
@@ -0,0 +1,479 @@
+//! Inline suppression comments
+//!
+//! Diagnostics can be silenced from FSH comments instead of disabling a rule
+//! for the whole project:
+//!
+//! ```fsh
+//! // maki-ignore-next-line correctness/invalid-slicing: sliced by the base profile
+//! * component contains systolic 1..1
+//!
+//! // maki-ignore-start style/naming-convention
+//! Profile: legacy_profile
+//! Parent: Patient
+//! // maki-ignore-end
+//!
+//! // maki-ignore-file documentation/missing-title
+//! ```
+//!
+//! A directive without rule IDs suppresses every rule. A category such as
+//! `documentation` suppresses all rules in that category. Text after `:` is a
+//! free-form reason.
+//!
+//! Suppressions that silence nothing, name unknown rules, or are malformed
+//! (unclosed `maki-ignore-start`, stray `maki-ignore-end`) are reported as
+//! diagnostics themselves.
+
+use maki_core::cst::{FshSyntaxKind, TriviaToken};
+use maki_core::{CodeSuggestion, Diagnostic, SemanticModel, Severity};
+use std::ops::Range;
+
+/// Rule ID for suppressions that don't silence any diagnostic
+pub const UNUSED_SUPPRESSION: &str = "suppressions/unused-suppression";
+
+/// Rule ID for suppressions naming a rule that doesn't exist
+pub const UNKNOWN_SUPPRESSION_RULE: &str = "suppressions/unknown-rule";
+
+/// Rule ID for malformed suppression comments
+pub const INVALID_SUPPRESSION: &str = "suppressions/invalid-suppression";
+
+/// Rule IDs reported by the suppression pass rather than by a rule checker
+pub const SUPPRESSION_RULES: [&str; 3] = [
+    UNUSED_SUPPRESSION,
+    UNKNOWN_SUPPRESSION_RULE,
+    INVALID_SUPPRESSION,
+];
+
+const IGNORE_NEXT_LINE: &str = "maki-ignore-next-line";
+const IGNORE_START: &str = "maki-ignore-start";
+const IGNORE_END: &str = "maki-ignore-end";
+const IGNORE_FILE: &str = "maki-ignore-file";
+
+/// Scope of a suppression comment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionKind {
+    /// `maki-ignore-next-line`
+    NextLine,
+    /// `maki-ignore-start` … `maki-ignore-end`
+    Range,
+    /// `maki-ignore-file`
+    File,
+}
+
+/// A single suppression comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub kind: SuppressionKind,
+    /// Suppressed rule IDs or categories; empty suppresses every rule
+    pub rules: Vec<String>,
+    pub reason: Option<String>,
+    /// First suppressed line (1-based, inclusive)
+    pub start_line: usize,
+    /// Last suppressed line (1-based, inclusive)
+    pub end_line: usize,
+    /// Byte range of the directive comment
+    pub span: Range<usize>,
+}
+
+impl Suppression {
+    fn covers_line(&self, line: usize) -> bool {
+        self.kind == SuppressionKind::File || (self.start_line..=self.end_line).contains(&line)
+    }
+}
+
+/// Whether `pattern` (a rule ID or category) selects `rule_id`
+fn rule_matches(pattern: &str, rule_id: &str) -> bool {
+    rule_id == pattern
+        || rule_id
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Suppression comments of one file
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+    /// Malformed directives, reported as [`INVALID_SUPPRESSION`]
+    invalid: Vec<(Range<usize>, String)>,
+}
+
+impl Suppressions {
+    /// Collect the suppression directives from the comments of `model`
+    pub fn collect(model: &SemanticModel) -> Self {
+        let source = model.source.as_str();
+        let line_of = |offset: usize| model.source_map.offset_to_position(offset, source).0;
+
+        let mut result = Self::default();
+        let mut open: Vec<Suppression> = Vec::new();
+
+        let comments = model
+            .cst
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| {
+                matches!(
+                    token.kind(),
+                    FshSyntaxKind::CommentLine | FshSyntaxKind::CommentBlock
+                )
+            });
+
+        for token in comments {
+            let trivia = TriviaToken::from_token(&token);
+            let Some(content) = trivia.comment_content() else {
+                continue;
+            };
+            let Some((directive, rules, reason)) = parse_directive(&content) else {
+                continue;
+            };
+            let span = usize::from(trivia.range.start())..usize::from(trivia.range.end());
+            let line = line_of(span.start);
+            let end_line = line_of(span.end);
+
+            let mut suppression = Suppression {
+                kind: SuppressionKind::File,
+                rules,
+                reason,
+                start_line: line,
+                end_line: line,
+                span,
+            };
+            match directive {
+                IGNORE_NEXT_LINE => {
+                    suppression.kind = SuppressionKind::NextLine;
+                    suppression.end_line = next_code_line(source, end_line);
+                    result.suppressions.push(suppression);
+                }
+                IGNORE_START => {
+                    suppression.kind = SuppressionKind::Range;
+                    open.push(suppression);
+                }
+                IGNORE_END => match open.pop() {
+                    Some(mut start) => {
+                        start.end_line = line;
+                        result.suppressions.push(start);
+                    }
+                    None => result.invalid.push((
+                        suppression.span,
+                        format!("'{}' without a matching '{}'", IGNORE_END, IGNORE_START),
+                    )),
+                },
+                _ => result.suppressions.push(suppression),
+            }
+        }
+
+        // An unclosed range still suppresses up to the end of the file
+        for mut start in open {
+            result.invalid.push((
+                start.span.clone(),
+                format!("'{}' is never closed with '{}'", IGNORE_START, IGNORE_END),
+            ));
+            start.end_line = usize::MAX;
+            result.suppressions.push(start);
+        }
+
+        result
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.suppressions.is_empty() && self.invalid.is_empty()
+    }
+
+    pub fn suppressions(&self) -> &[Suppression] {
+        &self.suppressions
+    }
+
+    /// Drop suppressed diagnostics and report unused, unknown and malformed
+    /// suppressions
+    ///
+    /// `is_known_rule` tells whether a rule ID or category exists. IDs that
+    /// suppressed a diagnostic always count as known.
+    pub fn apply(
+        &self,
+        diagnostics: Vec<Diagnostic>,
+        model: &SemanticModel,
+        is_known_rule: impl Fn(&str) -> bool,
+    ) -> Vec<Diagnostic> {
+        // used[i][j]: whether rule j of suppression i silenced something
+        // (index 0 for suppressions without rule IDs)
+        let mut used: Vec<Vec<bool>> = self
+            .suppressions
+            .iter()
+            .map(|s| vec![false; s.rules.len().max(1)])
+            .collect();
+
+        let mut kept: Vec<Diagnostic> = diagnostics
+            .into_iter()
+            .filter(|diagnostic| {
+                let mut suppressed = false;
+                for (suppression, used) in self.suppressions.iter().zip(used.iter_mut()) {
+                    if !suppression.covers_line(diagnostic.location.line) {
+                        continue;
+                    }
+                    if suppression.rules.is_empty() {
+                        used[0] = true;
+                        suppressed = true;
+                    }
+                    for (rule, used) in suppression.rules.iter().zip(used.iter_mut()) {
+                        if rule_matches(rule, &diagnostic.rule_id) {
+                            *used = true;
+                            suppressed = true;
+                        }
+                    }
+                }
+                !suppressed
+            })
+            .collect();
+
+        let location = |span: &Range<usize>| {
+            model
+                .source_map
+                .span_to_diagnostic_location(span, &model.source, &model.source_file)
+        };
+
+        for (span, message) in &self.invalid {
+            kept.push(Diagnostic::new(
+                INVALID_SUPPRESSION,
+                Severity::Warning,
+                message.clone(),
+                location(span),
+            ));
+        }
+
+        for (suppression, used) in self.suppressions.iter().zip(&used) {
+            let mut unknown = Vec::new();
+            let mut unused = Vec::new();
+            for (rule, used) in suppression.rules.iter().zip(used) {
+                if *used {
+                    continue;
+                }
+                if is_known_rule(rule) {
+                    unused.push(rule.as_str());
+                } else {
+                    unknown.push(rule.as_str());
+                }
+            }
+
+            for rule in &unknown {
+                kept.push(Diagnostic::new(
+                    UNKNOWN_SUPPRESSION_RULE,
+                    Severity::Warning,
+                    format!("Suppression names unknown rule '{}'", rule),
+                    location(&suppression.span),
+                ));
+            }
+
+            if used.iter().all(|used| !used) && unknown.len() < suppression.rules.len().max(1) {
+                kept.push(
+                    Diagnostic::new(
+                        UNUSED_SUPPRESSION,
+                        Severity::Warning,
+                        "Suppression comment does not suppress any diagnostic",
+                        location(&suppression.span),
+                    )
+                    .with_suggestion(CodeSuggestion::safe(
+                        "Remove unused suppression",
+                        String::new(),
+                        location(&suppression.span),
+                    )),
+                );
+            } else {
+                for rule in unused {
+                    kept.push(Diagnostic::new(
+                        UNUSED_SUPPRESSION,
+                        Severity::Warning,
+                        format!("Suppression of '{}' does not suppress any diagnostic", rule),
+                        location(&suppression.span),
+                    ));
+                }
+            }
+        }
+
+        kept
+    }
+}
+
+/// Split `maki-ignore-next-line a/b, c/d: reason` into directive, rules and reason
+fn parse_directive(content: &str) -> Option<(&'static str, Vec<String>, Option<String>)> {
+    let (word, rest) = content
+        .split_once(char::is_whitespace)
+        .unwrap_or((content, ""));
+    let directive = [IGNORE_NEXT_LINE, IGNORE_START, IGNORE_END, IGNORE_FILE]
+        .into_iter()
+        .find(|known| *known == word.trim_end_matches(':'))?;
+
+    let rest = if word.ends_with(':') {
+        format!(":{}", rest)
+    } else {
+        rest.to_string()
+    };
+    let (rules, reason) = match rest.split_once(':') {
+        Some((rules, reason)) => (rules.to_string(), Some(reason.trim().to_string())),
+        None => (rest, None),
+    };
+    let rules = rules
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|rule| !rule.is_empty())
+        .map(str::to_string)
+        .collect();
+    let reason = reason.filter(|reason| !reason.is_empty());
+
+    Some((directive, rules, reason))
+}
+
+/// First line after `line` that isn't blank or only comments
+///
+/// Both `//` line comments and `/* ... */` block comments, which may span
+/// several lines, are skipped.
+fn next_code_line(source: &str, line: usize) -> usize {
+    let mut in_block = false;
+    source
+        .lines()
+        .enumerate()
+        .skip(line)
+        .find(|(_, text)| {
+            let mut rest = text.trim();
+            loop {
+                if in_block {
+                    let Some(end) = rest.find("*/") else {
+                        return false;
+                    };
+                    in_block = false;
+                    rest = rest[end + 2..].trim_start();
+                } else if let Some(comment) = rest.strip_prefix("/*") {
+                    in_block = true;
+                    rest = comment;
+                } else {
+                    return !rest.is_empty() && !rest.starts_with("//");
+                }
+            }
+        })
+        .map_or(line + 1, |(index, _)| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maki_core::cst::parse_fsh;
+    use maki_core::diagnostics::Location;
+    use std::path::PathBuf;
+
+    fn create_test_model(source: &str) -> SemanticModel {
+        let (cst, _, _) = parse_fsh(source);
+        let source_map = maki_core::SourceMap::new(source);
+        SemanticModel {
+            cst,
+            resources: Vec::new(),
+            symbols: Default::default(),
+            aliases: maki_core::semantic::AliasTable::new(),
+            references: Vec::new(),
+            source_file: PathBuf::from("test.fsh"),
+            source_map,
+            source: source.to_string(),
+            deferred_rules: maki_core::DeferredRuleQueue::new(),
+        }
+    }
+
+    fn diagnostic(rule_id: &str, line: usize) -> Diagnostic {
+        Diagnostic::new(
+            rule_id,
+            Severity::Warning,
+            "test",
+            Location::new(PathBuf::from("test.fsh"), line, 1, 0, 0),
+        )
+    }
+
+    fn is_known(rule: &str) -> bool {
+        ["correctness", "style"]
+            .iter()
+            .any(|category| rule_matches(category, rule))
+    }
+
+    #[test]
+    fn test_parse_directive() {
+        assert_eq!(
+            parse_directive("maki-ignore-next-line correctness/a, style/b: false positive"),
+            Some((
+                IGNORE_NEXT_LINE,
+                vec!["correctness/a".to_string(), "style/b".to_string()],
+                Some("false positive".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_directive("maki-ignore-start: generated"),
+            Some((IGNORE_START, Vec::new(), Some("generated".to_string())))
+        );
+        assert_eq!(parse_directive("maki-ignored"), None);
+        assert_eq!(parse_directive("TODO maki-ignore-file"), None);
+    }
+
+    #[test]
+    fn test_next_line_and_range() {
+        let source = "Profile: A\n\
+            // maki-ignore-next-line correctness/invalid-slicing: reason\n\
+            \n\
+            * component 1..1\n\
+            * status 1..1\n\
+            // maki-ignore-start style\n\
+            * code 1..1\n\
+            // maki-ignore-end\n\
+            * value[x] 1..1\n";
+        let model = create_test_model(source);
+        let suppressions = Suppressions::collect(&model);
+        assert_eq!(suppressions.suppressions().len(), 2);
+
+        let kept = suppressions.apply(
+            vec![
+                diagnostic("correctness/invalid-slicing", 4),
+                diagnostic("correctness/invalid-slicing", 5),
+                diagnostic("style/naming-convention", 7),
+                diagnostic("style/naming-convention", 9),
+            ],
+            &model,
+            is_known,
+        );
+        let lines: Vec<usize> = kept.iter().map(|d| d.location.line).collect();
+        assert_eq!(lines, vec![5, 9]);
+    }
+
+    #[test]
+    fn test_next_line_skips_block_comments() {
+        let source = "// maki-ignore-next-line correctness\n\
+            /* single line */\n\
+            /* spans\n\
+               several lines */ /* and another */\n\
+            * status 1..1\n";
+        assert_eq!(next_code_line(source, 1), 5);
+        assert_eq!(next_code_line("/* a */ * code 1..1\n", 0), 1);
+        assert_eq!(next_code_line("/* unterminated\n* code 1..1\n", 0), 1);
+    }
+
+    #[test]
+    fn test_file_suppression_and_reports() {
+        let source = "// maki-ignore-file style/naming-convention\n\
+            // maki-ignore-next-line correctness/unknown-thing, correctness/x\n\
+            Profile: a\n\
+            // maki-ignore-end\n\
+            // maki-ignore-start\n";
+        let model = create_test_model(source);
+        let kept = Suppressions::collect(&model).apply(
+            vec![diagnostic("style/naming-convention", 3)],
+            &model,
+            |rule| rule != "correctness/unknown-thing" && is_known(rule),
+        );
+
+        let ids: Vec<&str> = kept.iter().map(|d| d.rule_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                INVALID_SUPPRESSION,
+                INVALID_SUPPRESSION,
+                UNKNOWN_SUPPRESSION_RULE,
+                UNUSED_SUPPRESSION,
+                UNUSED_SUPPRESSION,
+            ]
+        );
+        assert!(kept[2].message.contains("correctness/unknown-thing"));
+        // The unclosed range silences nothing and can be removed
+        assert_eq!(kept[4].suggestions.len(), 1);
+        assert_eq!(kept[4].location.line, 5);
+    }
+}
@@ -1,7 +1,7 @@
 //! Tests for built-in FSH linting rules
 
 use maki_core::{FixSafety, RuleCategory, Severity};
-use maki_rules::builtin::BuiltinRules;
+use maki_rules::builtin::{BuiltinRules, extension_context, profile, slicing, terminology, unused};
 
 fn assert_rule_basics(rule: &maki_core::Rule) {
     assert!(!rule.id.is_empty());
@@ -14,6 +14,12 @@ fn assert_rule_basics(rule: &maki_core::Rule) {
     }
 }
 
+fn assert_has_rules(rules: &[maki_core::Rule], ids: &[&str]) {
+    for id in ids {
+        assert!(rules.iter().any(|r| r.id == *id), "missing {id} rule");
+    }
+}
+
 #[test]
 #[ignore] // TODO: Rule count may have changed
 fn blocking_rules_validate_critical_requirements() {
@@ -56,7 +62,23 @@ fn blocking_rules_validate_critical_requirements() {
 #[test]
 fn correctness_rules_have_required_metadata() {
     let rules = BuiltinRules::correctness_rules();
-    assert_eq!(rules.len(), 20); // Note: 2 rules commented out (duplicate_canonical_url, duplicate_identifier) due to GritQL hang issues. Added 7 rules: binding-strength-weakening, binding-without-valueset, instance-required-fields-missing, required-field-override, duplicate-rule, duplicate-alias, slice-name-collision
+    assert_has_rules(
+        &rules,
+        &[
+            profile::PROFILE_ASSIGNMENT_PRESENT,
+            slicing::SLICING_MISSING_DISCRIMINATOR,
+            slicing::SLICING_UNRESOLVED_DISCRIMINATOR,
+            slicing::SLICE_UNCONSTRAINED_DISCRIMINATOR,
+            slicing::SLICING_CLOSED_CONFLICT,
+            slicing::SLICE_CARDINALITY_EXCEEDS_MAX,
+            terminology::BINDING_CODE_NOT_IN_VALUESET,
+            terminology::CODE_NOT_IN_CODESYSTEM,
+            terminology::CODE_DISPLAY_MISMATCH,
+            extension_context::EXTENSION_CONTEXT_INVALID,
+            extension_context::EXTENSION_OUTSIDE_CONTEXT,
+            profile::PROFILE_CIRCULAR_PARENT,
+        ],
+    );
 
     for rule in &rules {
         assert_rule_basics(rule);
@@ -93,7 +115,16 @@ fn correctness_rules_have_required_metadata() {
 #[test]
 fn suspicious_rules_detect_risky_patterns() {
     let rules = BuiltinRules::suspicious_rules();
-    assert_eq!(rules.len(), 4); // Added 2 rules: binding-strength-inconsistent, must-support-propagation
+    assert_has_rules(
+        &rules,
+        &[
+            unused::UNUSED_RULESET,
+            unused::UNUSED_ALIAS,
+            unused::UNUSED_INVARIANT,
+            unused::UNUSED_VALUESET,
+            unused::UNUSED_CODESYSTEM,
+        ],
+    );
 
     for rule in &rules {
         assert_rule_basics(rule);
@@ -156,10 +187,15 @@ fn all_rules_combines_everything_without_duplicates() {
     let suspicious = BuiltinRules::suspicious_rules();
     let style = BuiltinRules::style_rules();
     let documentation = BuiltinRules::documentation_rules();
+    let suppressions = BuiltinRules::suppression_rules();
     let all = BuiltinRules::all_rules();
 
-    let expected_total =
-        blocking.len() + correctness.len() + suspicious.len() + style.len() + documentation.len();
+    let expected_total = blocking.len()
+        + correctness.len()
+        + suspicious.len()
+        + style.len()
+        + documentation.len()
+        + suppressions.len();
     assert_eq!(all.len(), expected_total);
 
     for rule in all {
@@ -2,7 +2,7 @@
 //!
 //! Tests the complete GritQL pipeline:
 //! - Parser: GritQL syntax → AST
-//! - Compiler: AST → Pattern<FshQueryContext>
+//! - Compiler: checks the AST before execution
 //! - QueryContext: All trait implementations
 //! - Pattern matching against FSH CST
 
@@ -12,7 +12,7 @@ use grit_util::{Ast, AstNode};
 use insta::assert_yaml_snapshot;
 use maki_core::cst::parse_fsh;
 use maki_rules::gritql::{
-    compiler::PatternCompiler,
+    GritQLCompiler,
     cst_tree::FshGritTree,
     parser::GritQLParser,
     query_context::{FshBinding, FshQueryContext, FshResolvedPattern},
@@ -65,11 +65,8 @@ fn test_parser_not_pattern() {
 
 #[test]
 fn test_compiler_simple_node() {
-    let mut parser = GritQLParser::new("profile");
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern("profile", "test-rule");
 
     assert!(result.is_ok());
     // Pattern compilation successful
@@ -77,11 +74,8 @@ fn test_compiler_simple_node() {
 
 #[test]
 fn test_compiler_where_clause() {
-    let mut parser = GritQLParser::new("profile where { description }");
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern("profile where { description }", "test-rule");
 
     assert!(result.is_ok());
     // Where clause compilation successful
@@ -89,11 +83,8 @@ fn test_compiler_where_clause() {
 
 #[test]
 fn test_compiler_variable() {
-    let mut parser = GritQLParser::new("$name");
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern("$name", "test-rule");
 
     assert!(result.is_ok());
     // Variable compilation successful
@@ -101,11 +92,8 @@ fn test_compiler_variable() {
 
 #[test]
 fn test_compiler_assignment() {
-    let mut parser = GritQLParser::new("$name = profile");
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern("$name = profile", "test-rule");
 
     assert!(result.is_ok());
     // Assignment compilation successful
@@ -195,8 +183,10 @@ fn test_full_pipeline_parse_compile() {
     let mut parser = GritQLParser::new(gritql);
     let parsed = parser.parse().expect("Parse failed");
 
-    let mut compiler = PatternCompiler::new();
-    let _compiled = compiler.compile(&parsed).expect("Compile failed");
+    let compiler = GritQLCompiler::new().unwrap();
+    let _compiled = compiler
+        .compile_pattern(gritql, "test-rule")
+        .expect("Compile failed");
 
     // If we get here, the pattern is ready for execution
     assert_yaml_snapshot!("full_pipeline_parse_compile", format!("{:?}", parsed));
@@ -204,7 +194,7 @@ fn test_full_pipeline_parse_compile() {
 
 #[test]
 fn test_syntax_kind_mapping() {
-    let mut compiler = PatternCompiler::new();
+    let compiler = GritQLCompiler::new().unwrap();
 
     // Test various FSH syntax kinds
     let test_cases = vec![
@@ -217,9 +207,7 @@ fn test_syntax_kind_mapping() {
     ];
 
     for (kind, should_succeed) in test_cases {
-        let mut parser = GritQLParser::new(kind);
-        let pattern = parser.parse().unwrap();
-        let result = compiler.compile(&pattern);
+        let result = compiler.compile_pattern(kind, "test-rule");
 
         assert_eq!(result.is_ok(), should_succeed, "Failed for kind: {kind}");
     }
@@ -249,11 +237,8 @@ fn test_complex_pattern_or() {
 fn test_nested_where_clauses() {
     let gritql = "profile where { description }";
 
-    let mut parser = GritQLParser::new(gritql);
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern(gritql, "test-rule");
 
     assert!(result.is_ok());
 }
@@ -262,30 +247,23 @@ fn test_nested_where_clauses() {
 fn test_variable_scoping() {
     let gritql = "$x = profile";
 
-    let mut parser = GritQLParser::new(gritql);
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern(gritql, "test-rule");
 
     assert!(result.is_ok());
 }
 
 #[test]
 fn test_multiple_variables() {
-    // Test that variables get unique indices
+    // Test that separate variables each compile
     let gritql1 = "$x";
     let gritql2 = "$y";
 
-    let mut compiler = PatternCompiler::new();
+    let compiler = GritQLCompiler::new().unwrap();
 
-    let mut parser1 = GritQLParser::new(gritql1);
-    let pattern1 = parser1.parse().unwrap();
-    compiler.compile(&pattern1).unwrap();
+    compiler.compile_pattern(gritql1, "test-rule").unwrap();
 
-    let mut parser2 = GritQLParser::new(gritql2);
-    let pattern2 = parser2.parse().unwrap();
-    compiler.compile(&pattern2).unwrap();
+    compiler.compile_pattern(gritql2, "test-rule").unwrap();
 
     // Both variables should have been registered
 }
@@ -297,8 +275,8 @@ fn test_predicate_field_exists() {
     let mut parser = GritQLParser::new(gritql);
     let pattern = parser.parse().unwrap();
 
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern(gritql, "test-rule");
 
     assert!(result.is_ok());
     assert_yaml_snapshot!("predicate_field_exists", format!("{:?}", pattern));
@@ -318,11 +296,8 @@ fn test_predicate_not() {
 fn test_error_invalid_syntax_kind() {
     let gritql = "invalid_syntax_kind_that_does_not_exist";
 
-    let mut parser = GritQLParser::new(gritql);
-    let pattern = parser.parse().unwrap();
-
-    let mut compiler = PatternCompiler::new();
-    let result = compiler.compile(&pattern);
+    let compiler = GritQLCompiler::new().unwrap();
+    let result = compiler.compile_pattern(gritql, "test-rule");
 
     assert!(result.is_err());
 }
@@ -142,6 +142,23 @@ mod gritql_compilation_tests {
         assert!(compiled.captures().contains(&"name".to_string()));
     }
 
+    #[test]
+    fn test_pattern_compilation_with_where_clause() {
+        let compiler = GritQLCompiler::new().unwrap();
+
+        // Test a variable binding constrained by a where clause
+        let pattern = r#"Profile: $name where { $name <: r"^[A-Z]" }"#;
+        let result = compiler.compile_pattern(pattern, "where-test");
+
+        assert!(
+            result.is_ok(),
+            "Pattern with a where clause should compile. Error: {:?}",
+            result.err()
+        );
+        let compiled = result.unwrap();
+        assert!(compiled.captures().contains(&"name".to_string()));
+    }
+
     #[test]
     #[ignore] // TODO: Empty patterns are now allowed for AST-based rules
     fn test_invalid_pattern_compilation() {